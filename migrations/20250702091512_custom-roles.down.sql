@@ -0,0 +1,17 @@
+-- Add down migration script here
+
+DROP TRIGGER IF EXISTS check_user_role_trigger ON users;
+DROP FUNCTION IF EXISTS check_user_role();
+
+-- Users holding a custom role fall back to the least privileged system role.
+UPDATE users SET role = 'staff'
+WHERE role NOT IN (SELECT name FROM roles WHERE organisation_pid IS NULL);
+
+DROP INDEX IF EXISTS roles_organisation_name_idx;
+DROP INDEX IF EXISTS roles_system_name_idx;
+
+DELETE FROM roles WHERE organisation_pid IS NOT NULL;
+ALTER TABLE roles DROP COLUMN IF EXISTS organisation_pid;
+
+ALTER TABLE roles ADD CONSTRAINT roles_name_key UNIQUE (name);
+ALTER TABLE users ADD CONSTRAINT users_role_fkey FOREIGN KEY (role) REFERENCES roles (name);
@@ -0,0 +1,29 @@
+-- Add up migration script here
+
+-- Roles may now belong to an organisation. System roles keep a NULL organisation.
+ALTER TABLE users DROP CONSTRAINT IF EXISTS users_role_fkey;
+ALTER TABLE roles DROP CONSTRAINT IF EXISTS roles_name_key;
+
+ALTER TABLE roles ADD COLUMN organisation_pid UUID REFERENCES organisations (pid) ON DELETE CASCADE;
+
+CREATE UNIQUE INDEX roles_system_name_idx ON roles (name) WHERE organisation_pid IS NULL;
+CREATE UNIQUE INDEX roles_organisation_name_idx ON roles (organisation_pid, name) WHERE organisation_pid IS NOT NULL;
+
+-- A user's role must be a system role or one defined by the user's organisation.
+CREATE OR REPLACE FUNCTION check_user_role()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM roles
+        WHERE name = NEW.role
+        AND (organisation_pid IS NULL OR organisation_pid = NEW.organisation_pid)
+    ) THEN
+        RAISE EXCEPTION 'role "%" does not exist', NEW.role USING ERRCODE = 'foreign_key_violation';
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER check_user_role_trigger
+BEFORE INSERT OR UPDATE OF role, organisation_pid ON users
+FOR EACH ROW EXECUTE FUNCTION check_user_role();
@@ -0,0 +1,114 @@
+-- Add down migration script here
+
+DROP POLICY IF EXISTS breed_summary_tenant ON breed_summary;
+ALTER TABLE breed_summary DISABLE ROW LEVEL SECURITY;
+
+DROP POLICY IF EXISTS species_summary_tenant ON species_summary;
+ALTER TABLE species_summary DISABLE ROW LEVEL SECURITY;
+
+DROP POLICY IF EXISTS livestock_summary_tenant ON livestock_summary;
+ALTER TABLE livestock_summary DISABLE ROW LEVEL SECURITY;
+
+DROP POLICY IF EXISTS audit_logs_tenant ON audit_logs;
+ALTER TABLE audit_logs DISABLE ROW LEVEL SECURITY;
+
+DROP POLICY IF EXISTS weight_records_tenant ON weight_records;
+ALTER TABLE weight_records DISABLE ROW LEVEL SECURITY;
+
+DROP POLICY IF EXISTS production_records_tenant ON production_records;
+ALTER TABLE production_records DISABLE ROW LEVEL SECURITY;
+
+DROP POLICY IF EXISTS health_records_tenant ON health_records;
+ALTER TABLE health_records DISABLE ROW LEVEL SECURITY;
+
+DROP POLICY IF EXISTS animals_tenant ON animals;
+ALTER TABLE animals DISABLE ROW LEVEL SECURITY;
+
+DROP POLICY IF EXISTS breeds_tenant ON breeds;
+DROP POLICY IF EXISTS breeds_tenant_read ON breeds;
+ALTER TABLE breeds DISABLE ROW LEVEL SECURITY;
+
+DROP POLICY IF EXISTS roles_tenant ON roles;
+DROP POLICY IF EXISTS roles_tenant_read ON roles;
+ALTER TABLE roles DISABLE ROW LEVEL SECURITY;
+
+DROP POLICY IF EXISTS users_tenant ON users;
+ALTER TABLE users DISABLE ROW LEVEL SECURITY;
+
+DROP POLICY IF EXISTS organisations_tenant ON organisations;
+ALTER TABLE organisations DISABLE ROW LEVEL SECURITY;
+
+-- Restore the original audit function before dropping its helpers.
+CREATE OR REPLACE FUNCTION process_audit() 
+RETURNS TRIGGER AS $$
+DECLARE
+    organisation_pid UUID;
+    excluded_cols TEXT[] := ARRAY['created_at', 'updated_at'];
+BEGIN
+    IF TG_TABLE_NAME = 'organisations' THEN
+        IF (TG_OP = 'DELETE') THEN
+            organisation_pid := OLD.pid;
+        ELSE
+            organisation_pid := NEW.pid;
+        END IF;
+    ELSIF TG_TABLE_NAME = 'users' THEN
+        IF (TG_OP = 'DELETE') THEN
+            organisation_pid := OLD.organisation_pid;
+        ELSE
+            organisation_pid := NEW.organisation_pid;
+        END IF;
+    ELSE
+        organisation_pid := NULL;
+    END IF;
+
+    INSERT INTO audit_logs (
+        organisation_pid,
+        table_name,
+        record_id,
+        action,
+        old_data,
+        new_data,
+        changed_by,
+        changed_at
+    ) VALUES (
+        organisation_pid,
+        TG_TABLE_NAME::VARCHAR(50),
+        CASE 
+            WHEN TG_OP = 'DELETE' THEN OLD.id::VARCHAR
+            ELSE NEW.id::VARCHAR
+        END,
+        TG_OP,
+        CASE WHEN TG_OP = 'DELETE' OR TG_OP = 'UPDATE'
+            THEN jsonb_strip_nulls(to_jsonb(OLD) - excluded_cols)
+            ELSE NULL
+        END,
+        CASE WHEN TG_OP = 'INSERT' OR TG_OP = 'UPDATE'
+            THEN jsonb_strip_nulls(to_jsonb(NEW) - excluded_cols)
+            ELSE NULL
+        END,
+        CASE
+            WHEN current_setting('app.current_user_pid', true) IS NOT NULL 
+            THEN (current_setting('app.current_user_pid', true))::UUID
+            ELSE NULL
+        END,
+        NOW()
+    );
+
+    IF TG_OP = 'DELETE' THEN
+        RETURN OLD;
+    ELSE
+        RETURN NEW;
+    END IF;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER;
+
+DROP FUNCTION IF EXISTS current_user_pid();
+DROP FUNCTION IF EXISTS current_org_pid();
+
+-- The role itself is cluster wide and may be used by other databases, so
+-- only this database's grants are removed.
+ALTER DEFAULT PRIVILEGES IN SCHEMA public REVOKE USAGE, SELECT ON SEQUENCES FROM polaris_tenant;
+ALTER DEFAULT PRIVILEGES IN SCHEMA public REVOKE SELECT, INSERT, UPDATE, DELETE ON TABLES FROM polaris_tenant;
+REVOKE USAGE, SELECT ON ALL SEQUENCES IN SCHEMA public FROM polaris_tenant;
+REVOKE SELECT, INSERT, UPDATE, DELETE ON ALL TABLES IN SCHEMA public FROM polaris_tenant;
+REVOKE USAGE ON SCHEMA public FROM polaris_tenant;
@@ -0,0 +1,146 @@
+-- Add up migration script here
+
+-- Role assumed (SET LOCAL ROLE) by request-scoped transactions. Roles are
+-- cluster wide so it may already exist from another database.
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_roles WHERE rolname = 'polaris_tenant') THEN
+        CREATE ROLE polaris_tenant NOLOGIN;
+    END IF;
+END
+$$;
+
+GRANT polaris_tenant TO CURRENT_USER;
+GRANT USAGE ON SCHEMA public TO polaris_tenant;
+GRANT SELECT, INSERT, UPDATE, DELETE ON ALL TABLES IN SCHEMA public TO polaris_tenant;
+GRANT USAGE, SELECT ON ALL SEQUENCES IN SCHEMA public TO polaris_tenant;
+ALTER DEFAULT PRIVILEGES IN SCHEMA public GRANT SELECT, INSERT, UPDATE, DELETE ON TABLES TO polaris_tenant;
+ALTER DEFAULT PRIVILEGES IN SCHEMA public GRANT USAGE, SELECT ON SEQUENCES TO polaris_tenant;
+
+-- Settings are reset to an empty string rather than NULL at the end of a
+-- transaction, so both mean "not set".
+CREATE OR REPLACE FUNCTION current_org_pid()
+RETURNS UUID AS $$
+    SELECT NULLIF(current_setting('app.current_org_pid', true), '')::UUID;
+$$ LANGUAGE sql STABLE;
+
+CREATE OR REPLACE FUNCTION current_user_pid()
+RETURNS UUID AS $$
+    SELECT NULLIF(current_setting('app.current_user_pid', true), '')::UUID;
+$$ LANGUAGE sql STABLE;
+
+-- Tenant tables. The table owner is not subject to these policies, so
+-- migrations, seeding and connections outside a tenant transaction still see
+-- every row.
+ALTER TABLE organisations ENABLE ROW LEVEL SECURITY;
+CREATE POLICY organisations_tenant ON organisations
+    USING (pid = current_org_pid());
+
+ALTER TABLE users ENABLE ROW LEVEL SECURITY;
+CREATE POLICY users_tenant ON users
+    USING (organisation_pid = current_org_pid());
+
+ALTER TABLE roles ENABLE ROW LEVEL SECURITY;
+CREATE POLICY roles_tenant_read ON roles FOR SELECT
+    USING (organisation_pid IS NULL OR organisation_pid = current_org_pid());
+CREATE POLICY roles_tenant ON roles
+    USING (organisation_pid = current_org_pid());
+
+ALTER TABLE breeds ENABLE ROW LEVEL SECURITY;
+CREATE POLICY breeds_tenant_read ON breeds FOR SELECT
+    USING (is_system_defined OR organisation_pid = current_org_pid());
+CREATE POLICY breeds_tenant ON breeds
+    USING (organisation_pid = current_org_pid());
+
+ALTER TABLE animals ENABLE ROW LEVEL SECURITY;
+CREATE POLICY animals_tenant ON animals
+    USING (organisation_pid = current_org_pid());
+
+ALTER TABLE health_records ENABLE ROW LEVEL SECURITY;
+CREATE POLICY health_records_tenant ON health_records
+    USING (organisation_pid = current_org_pid());
+
+ALTER TABLE production_records ENABLE ROW LEVEL SECURITY;
+CREATE POLICY production_records_tenant ON production_records
+    USING (organisation_pid = current_org_pid());
+
+ALTER TABLE weight_records ENABLE ROW LEVEL SECURITY;
+CREATE POLICY weight_records_tenant ON weight_records
+    USING (organisation_pid = current_org_pid());
+
+ALTER TABLE audit_logs ENABLE ROW LEVEL SECURITY;
+CREATE POLICY audit_logs_tenant ON audit_logs
+    USING (organisation_pid = current_org_pid());
+
+ALTER TABLE livestock_summary ENABLE ROW LEVEL SECURITY;
+CREATE POLICY livestock_summary_tenant ON livestock_summary
+    USING (organisation_pid = current_org_pid());
+
+ALTER TABLE species_summary ENABLE ROW LEVEL SECURITY;
+CREATE POLICY species_summary_tenant ON species_summary
+    USING (organisation_pid = current_org_pid());
+
+ALTER TABLE breed_summary ENABLE ROW LEVEL SECURITY;
+CREATE POLICY breed_summary_tenant ON breed_summary
+    USING (organisation_pid = current_org_pid());
+
+-- process_audit() cast the raw setting, which fails once the setting has been
+-- reset to an empty string on a pooled connection.
+CREATE OR REPLACE FUNCTION process_audit() 
+RETURNS TRIGGER AS $$
+DECLARE
+    organisation_pid UUID;
+    excluded_cols TEXT[] := ARRAY['created_at', 'updated_at'];
+BEGIN
+    IF TG_TABLE_NAME = 'organisations' THEN
+        IF (TG_OP = 'DELETE') THEN
+            organisation_pid := OLD.pid;
+        ELSE
+            organisation_pid := NEW.pid;
+        END IF;
+    ELSIF TG_TABLE_NAME = 'users' THEN
+        IF (TG_OP = 'DELETE') THEN
+            organisation_pid := OLD.organisation_pid;
+        ELSE
+            organisation_pid := NEW.organisation_pid;
+        END IF;
+    ELSE
+        organisation_pid := NULL;
+    END IF;
+
+    INSERT INTO audit_logs (
+        organisation_pid,
+        table_name,
+        record_id,
+        action,
+        old_data,
+        new_data,
+        changed_by,
+        changed_at
+    ) VALUES (
+        organisation_pid,
+        TG_TABLE_NAME::VARCHAR(50),
+        CASE 
+            WHEN TG_OP = 'DELETE' THEN OLD.id::VARCHAR
+            ELSE NEW.id::VARCHAR
+        END,
+        TG_OP,
+        CASE WHEN TG_OP = 'DELETE' OR TG_OP = 'UPDATE'
+            THEN jsonb_strip_nulls(to_jsonb(OLD) - excluded_cols)
+            ELSE NULL
+        END,
+        CASE WHEN TG_OP = 'INSERT' OR TG_OP = 'UPDATE'
+            THEN jsonb_strip_nulls(to_jsonb(NEW) - excluded_cols)
+            ELSE NULL
+        END,
+        current_user_pid(),
+        NOW()
+    );
+
+    IF TG_OP = 'DELETE' THEN
+        RETURN OLD;
+    ELSE
+        RETURN NEW;
+    END IF;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER;
@@ -0,0 +1,86 @@
+-- Add down migration script here
+
+DROP TRIGGER IF EXISTS audit_weight_records_trigger ON weight_records;
+DROP TRIGGER IF EXISTS audit_production_records_trigger ON production_records;
+DROP TRIGGER IF EXISTS audit_health_records_trigger ON health_records;
+DROP TRIGGER IF EXISTS audit_breeds_trigger ON breeds;
+DROP TRIGGER IF EXISTS audit_animals_trigger ON animals;
+
+DROP TRIGGER IF EXISTS audit_users_trigger ON users;
+CREATE TRIGGER audit_users_trigger
+AFTER INSERT OR UPDATE ON users
+FOR EACH ROW EXECUTE FUNCTION process_audit();
+
+DROP TRIGGER IF EXISTS audit_organisations_trigger ON organisations;
+CREATE TRIGGER audit_organisations_trigger
+AFTER INSERT OR UPDATE ON organisations
+FOR EACH ROW EXECUTE FUNCTION process_audit();
+
+-- Entries of tables that were not audited before are removed.
+DELETE FROM audit_logs WHERE table_name NOT IN ('organisations', 'users');
+
+CREATE OR REPLACE FUNCTION process_audit() 
+RETURNS TRIGGER AS $$
+DECLARE
+    organisation_pid UUID;
+    excluded_cols TEXT[] := ARRAY['created_at', 'updated_at'];
+BEGIN
+    IF TG_TABLE_NAME = 'organisations' THEN
+        IF (TG_OP = 'DELETE') THEN
+            organisation_pid := OLD.pid;
+        ELSE
+            organisation_pid := NEW.pid;
+        END IF;
+    ELSIF TG_TABLE_NAME = 'users' THEN
+        IF (TG_OP = 'DELETE') THEN
+            organisation_pid := OLD.organisation_pid;
+        ELSE
+            organisation_pid := NEW.organisation_pid;
+        END IF;
+    ELSE
+        organisation_pid := NULL;
+    END IF;
+
+    INSERT INTO audit_logs (
+        organisation_pid,
+        table_name,
+        record_id,
+        action,
+        old_data,
+        new_data,
+        changed_by,
+        changed_at
+    ) VALUES (
+        organisation_pid,
+        TG_TABLE_NAME::VARCHAR(50),
+        CASE 
+            WHEN TG_OP = 'DELETE' THEN OLD.id::VARCHAR
+            ELSE NEW.id::VARCHAR
+        END,
+        TG_OP,
+        CASE WHEN TG_OP = 'DELETE' OR TG_OP = 'UPDATE'
+            THEN jsonb_strip_nulls(to_jsonb(OLD) - excluded_cols)
+            ELSE NULL
+        END,
+        CASE WHEN TG_OP = 'INSERT' OR TG_OP = 'UPDATE'
+            THEN jsonb_strip_nulls(to_jsonb(NEW) - excluded_cols)
+            ELSE NULL
+        END,
+        current_user_pid(),
+        NOW()
+    );
+
+    IF TG_OP = 'DELETE' THEN
+        RETURN OLD;
+    ELSE
+        RETURN NEW;
+    END IF;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER;
+
+DROP INDEX IF EXISTS audit_logs_changed_at_idx;
+DROP INDEX IF EXISTS audit_logs_changed_by_idx;
+
+ALTER TABLE audit_logs DROP CONSTRAINT IF EXISTS audit_logs_changed_by_fkey;
+ALTER TABLE audit_logs ADD CONSTRAINT audit_logs_changed_by_fkey
+    FOREIGN KEY (changed_by) REFERENCES users (pid);
@@ -0,0 +1,100 @@
+-- Add up migration script here
+
+-- Audit entries outlive the users who made them.
+ALTER TABLE audit_logs DROP CONSTRAINT IF EXISTS audit_logs_changed_by_fkey;
+ALTER TABLE audit_logs ADD CONSTRAINT audit_logs_changed_by_fkey
+    FOREIGN KEY (changed_by) REFERENCES users (pid) ON DELETE SET NULL;
+
+CREATE INDEX audit_logs_changed_by_idx ON audit_logs (changed_by);
+CREATE INDEX audit_logs_changed_at_idx ON audit_logs (changed_at);
+
+-- Audits every table keyed by organisation. Records are identified by their
+-- pid when they have one since that is what the API exposes.
+CREATE OR REPLACE FUNCTION process_audit() 
+RETURNS TRIGGER AS $$
+DECLARE
+    row_data JSONB;
+    organisation_pid UUID;
+    -- Timestamps are noise and credentials must never reach the log.
+    excluded_cols TEXT[] := ARRAY[
+        'created_at', 'updated_at',
+        'password_hash', 'reset_token', 'token_hash', 'secret_hash', 'secret'
+    ];
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        row_data := to_jsonb(OLD);
+    ELSE
+        row_data := to_jsonb(NEW);
+    END IF;
+
+    IF TG_TABLE_NAME = 'organisations' THEN
+        organisation_pid := (row_data ->> 'pid')::UUID;
+    ELSE
+        organisation_pid := (row_data ->> 'organisation_pid')::UUID;
+    END IF;
+
+    -- Rows removed along with their organisation have nowhere to be logged.
+    IF organisation_pid IS NOT NULL
+        AND NOT EXISTS (SELECT 1 FROM organisations o WHERE o.pid = organisation_pid) THEN
+        RETURN NULL;
+    END IF;
+
+    INSERT INTO audit_logs (
+        organisation_pid,
+        table_name,
+        record_id,
+        action,
+        old_data,
+        new_data,
+        changed_by,
+        changed_at
+    ) VALUES (
+        organisation_pid,
+        TG_TABLE_NAME::VARCHAR(50),
+        COALESCE(row_data ->> 'pid', row_data ->> 'id'),
+        TG_OP,
+        CASE WHEN TG_OP = 'DELETE' OR TG_OP = 'UPDATE'
+            THEN jsonb_strip_nulls(to_jsonb(OLD) - excluded_cols)
+            ELSE NULL
+        END,
+        CASE WHEN TG_OP = 'INSERT' OR TG_OP = 'UPDATE'
+            THEN jsonb_strip_nulls(to_jsonb(NEW) - excluded_cols)
+            ELSE NULL
+        END,
+        current_user_pid(),
+        NOW()
+    );
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER;
+
+DROP TRIGGER IF EXISTS audit_organisations_trigger ON organisations;
+CREATE TRIGGER audit_organisations_trigger
+AFTER INSERT OR UPDATE OR DELETE ON organisations
+FOR EACH ROW EXECUTE FUNCTION process_audit();
+
+DROP TRIGGER IF EXISTS audit_users_trigger ON users;
+CREATE TRIGGER audit_users_trigger
+AFTER INSERT OR UPDATE OR DELETE ON users
+FOR EACH ROW EXECUTE FUNCTION process_audit();
+
+CREATE TRIGGER audit_animals_trigger
+AFTER INSERT OR UPDATE OR DELETE ON animals
+FOR EACH ROW EXECUTE FUNCTION process_audit();
+
+CREATE TRIGGER audit_breeds_trigger
+AFTER INSERT OR UPDATE OR DELETE ON breeds
+FOR EACH ROW EXECUTE FUNCTION process_audit();
+
+CREATE TRIGGER audit_health_records_trigger
+AFTER INSERT OR UPDATE OR DELETE ON health_records
+FOR EACH ROW EXECUTE FUNCTION process_audit();
+
+CREATE TRIGGER audit_production_records_trigger
+AFTER INSERT OR UPDATE OR DELETE ON production_records
+FOR EACH ROW EXECUTE FUNCTION process_audit();
+
+CREATE TRIGGER audit_weight_records_trigger
+AFTER INSERT OR UPDATE OR DELETE ON weight_records
+FOR EACH ROW EXECUTE FUNCTION process_audit();
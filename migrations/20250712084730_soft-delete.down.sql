@@ -0,0 +1,42 @@
+-- Add down migration script here
+
+-- Trashed rows were deleted as far as users are concerned.
+DELETE FROM health_records WHERE deleted_at IS NOT NULL;
+DELETE FROM production_records WHERE deleted_at IS NOT NULL;
+DELETE FROM weight_records WHERE deleted_at IS NOT NULL;
+UPDATE animals SET parent_female_id = NULL
+    WHERE parent_female_id IN (SELECT pid FROM animals WHERE deleted_at IS NOT NULL);
+UPDATE animals SET parent_male_id = NULL
+    WHERE parent_male_id IN (SELECT pid FROM animals WHERE deleted_at IS NOT NULL);
+DELETE FROM animals WHERE deleted_at IS NOT NULL;
+
+CREATE OR REPLACE FUNCTION set_previous_mass()
+RETURNS TRIGGER AS $$
+BEGIN
+    -- Get the most recent mass for this animal
+    SELECT mass INTO NEW.previous_mass
+    FROM weight_records
+    WHERE animal_pid = NEW.animal_pid
+        AND record_date < NEW.record_date  -- Only consider records before this one chronologically
+    ORDER BY record_date DESC, created_at DESC
+    LIMIT 1;
+    
+    -- If no previous record exists, set previous_mass to 0 or NULL
+    -- You can adjust this default behavior as needed
+    IF NEW.previous_mass IS NULL THEN
+        NEW.previous_mass = 0.00;
+    END IF;
+    
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP INDEX IF EXISTS weight_records_deleted_at_idx;
+DROP INDEX IF EXISTS production_records_deleted_at_idx;
+DROP INDEX IF EXISTS health_records_deleted_at_idx;
+DROP INDEX IF EXISTS animals_deleted_at_idx;
+
+ALTER TABLE weight_records DROP COLUMN deleted_by, DROP COLUMN deleted_at;
+ALTER TABLE production_records DROP COLUMN deleted_by, DROP COLUMN deleted_at;
+ALTER TABLE health_records DROP COLUMN deleted_by, DROP COLUMN deleted_at;
+ALTER TABLE animals DROP COLUMN deleted_by, DROP COLUMN deleted_at;
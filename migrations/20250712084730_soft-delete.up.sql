@@ -0,0 +1,44 @@
+-- Add up migration script here
+
+-- Deleting an animal or record moves it to the trash. Rows are only removed
+-- for good by the purge job once the retention period has passed.
+ALTER TABLE animals
+    ADD COLUMN deleted_at TIMESTAMP WITH TIME ZONE,
+    ADD COLUMN deleted_by UUID REFERENCES users (pid) ON DELETE SET NULL;
+
+ALTER TABLE health_records
+    ADD COLUMN deleted_at TIMESTAMP WITH TIME ZONE,
+    ADD COLUMN deleted_by UUID REFERENCES users (pid) ON DELETE SET NULL;
+
+ALTER TABLE production_records
+    ADD COLUMN deleted_at TIMESTAMP WITH TIME ZONE,
+    ADD COLUMN deleted_by UUID REFERENCES users (pid) ON DELETE SET NULL;
+
+ALTER TABLE weight_records
+    ADD COLUMN deleted_at TIMESTAMP WITH TIME ZONE,
+    ADD COLUMN deleted_by UUID REFERENCES users (pid) ON DELETE SET NULL;
+
+CREATE INDEX animals_deleted_at_idx ON animals (deleted_at) WHERE deleted_at IS NOT NULL;
+CREATE INDEX health_records_deleted_at_idx ON health_records (deleted_at) WHERE deleted_at IS NOT NULL;
+CREATE INDEX production_records_deleted_at_idx ON production_records (deleted_at) WHERE deleted_at IS NOT NULL;
+CREATE INDEX weight_records_deleted_at_idx ON weight_records (deleted_at) WHERE deleted_at IS NOT NULL;
+
+-- Trashed weights no longer count as an animal's previous mass.
+CREATE OR REPLACE FUNCTION set_previous_mass()
+RETURNS TRIGGER AS $$
+BEGIN
+    SELECT mass INTO NEW.previous_mass
+    FROM weight_records
+    WHERE animal_pid = NEW.animal_pid
+        AND record_date < NEW.record_date
+        AND deleted_at IS NULL
+    ORDER BY record_date DESC, created_at DESC
+    LIMIT 1;
+
+    IF NEW.previous_mass IS NULL THEN
+        NEW.previous_mass = 0.00;
+    END IF;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,73 @@
+-- Add down migration script here
+
+CREATE OR REPLACE FUNCTION process_audit() 
+RETURNS TRIGGER AS $$
+DECLARE
+    row_data JSONB;
+    organisation_pid UUID;
+    -- Timestamps are noise and credentials must never reach the log.
+    excluded_cols TEXT[] := ARRAY[
+        'created_at', 'updated_at',
+        'password_hash', 'reset_token', 'token_hash', 'secret_hash', 'secret'
+    ];
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        row_data := to_jsonb(OLD);
+    ELSE
+        row_data := to_jsonb(NEW);
+    END IF;
+
+    IF TG_TABLE_NAME = 'organisations' THEN
+        organisation_pid := (row_data ->> 'pid')::UUID;
+    ELSE
+        organisation_pid := (row_data ->> 'organisation_pid')::UUID;
+    END IF;
+
+    -- Rows removed along with their organisation have nowhere to be logged.
+    IF organisation_pid IS NOT NULL
+        AND NOT EXISTS (SELECT 1 FROM organisations o WHERE o.pid = organisation_pid) THEN
+        RETURN NULL;
+    END IF;
+
+    INSERT INTO audit_logs (
+        organisation_pid,
+        table_name,
+        record_id,
+        action,
+        old_data,
+        new_data,
+        changed_by,
+        changed_at
+    ) VALUES (
+        organisation_pid,
+        TG_TABLE_NAME::VARCHAR(50),
+        COALESCE(row_data ->> 'pid', row_data ->> 'id'),
+        TG_OP,
+        CASE WHEN TG_OP = 'DELETE' OR TG_OP = 'UPDATE'
+            THEN jsonb_strip_nulls(to_jsonb(OLD) - excluded_cols)
+            ELSE NULL
+        END,
+        CASE WHEN TG_OP = 'INSERT' OR TG_OP = 'UPDATE'
+            THEN jsonb_strip_nulls(to_jsonb(NEW) - excluded_cols)
+            ELSE NULL
+        END,
+        current_user_pid(),
+        NOW()
+    );
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER;
+
+DROP FUNCTION IF EXISTS current_impersonator_pid();
+
+DROP INDEX IF EXISTS audit_logs_impersonator_pid_idx;
+ALTER TABLE audit_logs DROP COLUMN IF EXISTS impersonator_pid;
+
+DROP TABLE IF EXISTS platform_audit_logs;
+
+ALTER TABLE organisations
+    DROP COLUMN IF EXISTS suspended_at,
+    DROP COLUMN IF EXISTS suspension_reason;
+
+ALTER TABLE users DROP COLUMN IF EXISTS is_platform_operator;
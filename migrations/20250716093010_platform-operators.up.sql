@@ -0,0 +1,99 @@
+-- Add up migration script here
+
+-- Platform operators run the service itself and act across organisations.
+ALTER TABLE users ADD COLUMN is_platform_operator BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Users of a suspended organisation are locked out until it is unsuspended.
+ALTER TABLE organisations
+    ADD COLUMN suspended_at TIMESTAMP WITH TIME ZONE,
+    ADD COLUMN suspension_reason TEXT;
+
+-- Operator actions are kept apart from the tenant audit logs since they are
+-- not made by a member of the organisation and outlive it.
+CREATE TABLE platform_audit_logs (
+    id SERIAL PRIMARY KEY,
+    operator_pid UUID REFERENCES users (pid) ON DELETE SET NULL,
+    organisation_pid UUID,
+    target_user_pid UUID,
+    action VARCHAR(50) NOT NULL,
+    details JSONB,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX platform_audit_logs_organisation_idx ON platform_audit_logs (organisation_pid);
+CREATE INDEX platform_audit_logs_created_at_idx ON platform_audit_logs (created_at);
+
+REVOKE ALL ON platform_audit_logs FROM polaris_tenant;
+
+-- Changes made while impersonating are logged as the operator as well as the
+-- user they act as.
+ALTER TABLE audit_logs
+    ADD COLUMN impersonator_pid UUID REFERENCES users (pid) ON DELETE SET NULL;
+
+CREATE INDEX audit_logs_impersonator_pid_idx ON audit_logs (impersonator_pid);
+
+CREATE OR REPLACE FUNCTION current_impersonator_pid()
+RETURNS UUID AS $$
+    SELECT NULLIF(current_setting('app.current_impersonator_pid', true), '')::UUID;
+$$ LANGUAGE sql STABLE;
+
+CREATE OR REPLACE FUNCTION process_audit() 
+RETURNS TRIGGER AS $$
+DECLARE
+    row_data JSONB;
+    organisation_pid UUID;
+    -- Timestamps are noise and credentials must never reach the log.
+    excluded_cols TEXT[] := ARRAY[
+        'created_at', 'updated_at',
+        'password_hash', 'reset_token', 'token_hash', 'secret_hash', 'secret'
+    ];
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        row_data := to_jsonb(OLD);
+    ELSE
+        row_data := to_jsonb(NEW);
+    END IF;
+
+    IF TG_TABLE_NAME = 'organisations' THEN
+        organisation_pid := (row_data ->> 'pid')::UUID;
+    ELSE
+        organisation_pid := (row_data ->> 'organisation_pid')::UUID;
+    END IF;
+
+    -- Rows removed along with their organisation have nowhere to be logged.
+    IF organisation_pid IS NOT NULL
+        AND NOT EXISTS (SELECT 1 FROM organisations o WHERE o.pid = organisation_pid) THEN
+        RETURN NULL;
+    END IF;
+
+    INSERT INTO audit_logs (
+        organisation_pid,
+        table_name,
+        record_id,
+        action,
+        old_data,
+        new_data,
+        changed_by,
+        impersonator_pid,
+        changed_at
+    ) VALUES (
+        organisation_pid,
+        TG_TABLE_NAME::VARCHAR(50),
+        COALESCE(row_data ->> 'pid', row_data ->> 'id'),
+        TG_OP,
+        CASE WHEN TG_OP = 'DELETE' OR TG_OP = 'UPDATE'
+            THEN jsonb_strip_nulls(to_jsonb(OLD) - excluded_cols)
+            ELSE NULL
+        END,
+        CASE WHEN TG_OP = 'INSERT' OR TG_OP = 'UPDATE'
+            THEN jsonb_strip_nulls(to_jsonb(NEW) - excluded_cols)
+            ELSE NULL
+        END,
+        current_user_pid(),
+        current_impersonator_pid(),
+        NOW()
+    );
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER;
@@ -0,0 +1,3 @@
+-- Add down migration script here
+
+ALTER TABLE organisations DROP COLUMN IF EXISTS settings;
@@ -0,0 +1,5 @@
+-- Add up migration script here
+
+-- Preferences other modules read their defaults from. Keys that are missing
+-- fall back to the application defaults.
+ALTER TABLE organisations ADD COLUMN settings JSONB NOT NULL DEFAULT '{}'::jsonb;
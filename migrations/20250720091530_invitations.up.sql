@@ -0,0 +1,32 @@
+-- Add up migration script here
+
+-- Admins invite people by email. The invitee sets their own password when
+-- accepting, so no credentials are handed over out of band. Only a hash of
+-- the invitation token is stored.
+CREATE TABLE invitations (
+    id SERIAL PRIMARY KEY,
+    pid UUID NOT NULL UNIQUE DEFAULT (uuid_generate_v4()),
+    organisation_pid UUID NOT NULL REFERENCES organisations (pid) ON DELETE CASCADE,
+    email VARCHAR(100) NOT NULL,
+    first_name VARCHAR(50) NOT NULL,
+    last_name VARCHAR(50) NOT NULL,
+    role VARCHAR(50) NOT NULL,
+    token_hash TEXT NOT NULL,
+    invited_by UUID REFERENCES users (pid) ON DELETE SET NULL,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    accepted_at TIMESTAMP WITH TIME ZONE,
+    revoked_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- One open invitation per email and organisation.
+CREATE UNIQUE INDEX invitations_open_email_idx ON invitations (organisation_pid, email)
+    WHERE accepted_at IS NULL AND revoked_at IS NULL;
+
+CREATE TRIGGER update_invitations_timestamp BEFORE UPDATE ON invitations
+FOR EACH ROW EXECUTE FUNCTION update_timestamp();
+
+ALTER TABLE invitations ENABLE ROW LEVEL SECURITY;
+CREATE POLICY invitations_tenant ON invitations
+    USING (organisation_pid = current_org_pid());
@@ -0,0 +1,16 @@
+-- Add down migration script here
+
+DROP POLICY IF EXISTS users_tenant ON users;
+CREATE POLICY users_tenant ON users
+    USING (organisation_pid = current_org_pid());
+
+ALTER TABLE users DROP CONSTRAINT IF EXISTS users_email_key;
+ALTER TABLE users ADD CONSTRAINT users_email_organisation_pid_key UNIQUE (email, organisation_pid);
+
+DROP TRIGGER IF EXISTS users_home_role_trigger ON users;
+DROP FUNCTION IF EXISTS sync_home_membership_role();
+
+DROP TRIGGER IF EXISTS users_home_membership_trigger ON users;
+DROP FUNCTION IF EXISTS create_home_membership();
+
+DROP TABLE IF EXISTS memberships;
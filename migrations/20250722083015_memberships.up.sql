@@ -0,0 +1,92 @@
+-- Add up migration script here
+
+-- A user may belong to several organisations with a role in each. The
+-- organisation and role kept on `users` are the user's home membership, the
+-- one they land in when they log in.
+CREATE TABLE memberships (
+    id SERIAL PRIMARY KEY,
+    user_pid UUID NOT NULL REFERENCES users (pid) ON DELETE CASCADE,
+    organisation_pid UUID NOT NULL REFERENCES organisations (pid) ON DELETE CASCADE,
+    role VARCHAR(50) NOT NULL,
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    UNIQUE (user_pid, organisation_pid)
+);
+
+CREATE INDEX memberships_organisation_pid_idx ON memberships (organisation_pid);
+
+CREATE TRIGGER update_memberships_timestamp BEFORE UPDATE ON memberships
+FOR EACH ROW EXECUTE FUNCTION update_timestamp();
+
+-- Same rule as a user's role: a system role or one of the organisation's.
+CREATE TRIGGER check_membership_role_trigger
+BEFORE INSERT OR UPDATE OF role, organisation_pid ON memberships
+FOR EACH ROW EXECUTE FUNCTION check_user_role();
+
+INSERT INTO memberships (user_pid, organisation_pid, role, is_active, created_at)
+SELECT pid, organisation_pid, role, COALESCE(is_active, TRUE), created_at
+FROM users
+WHERE organisation_pid IS NOT NULL;
+
+-- New accounts start with their home membership.
+CREATE OR REPLACE FUNCTION create_home_membership()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.organisation_pid IS NOT NULL THEN
+        INSERT INTO memberships (user_pid, organisation_pid, role, is_active)
+        VALUES (NEW.pid, NEW.organisation_pid, NEW.role, COALESCE(NEW.is_active, TRUE))
+        ON CONFLICT (user_pid, organisation_pid) DO NOTHING;
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER users_home_membership_trigger
+AFTER INSERT ON users
+FOR EACH ROW EXECUTE FUNCTION create_home_membership();
+
+-- The role on `users` stays the home membership's role.
+CREATE OR REPLACE FUNCTION sync_home_membership_role()
+RETURNS TRIGGER AS $$
+BEGIN
+    UPDATE memberships SET role = NEW.role
+    WHERE user_pid = NEW.pid AND organisation_pid = NEW.organisation_pid AND role <> NEW.role;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER users_home_role_trigger
+AFTER UPDATE OF role ON users
+FOR EACH ROW EXECUTE FUNCTION sync_home_membership_role();
+
+-- Accounts sharing an email become one account with a membership in each
+-- organisation. The oldest account is kept. The others still own their
+-- records, so they are disabled and their email is freed rather than deleted.
+WITH duplicates AS (
+    SELECT u.pid AS duplicate_pid, FIRST_VALUE(u.pid) OVER (PARTITION BY u.email ORDER BY u.id) AS kept_pid
+    FROM users u
+)
+UPDATE memberships m
+SET user_pid = d.kept_pid
+FROM duplicates d
+WHERE m.user_pid = d.duplicate_pid AND d.duplicate_pid <> d.kept_pid;
+
+UPDATE users u
+SET is_active = FALSE, email = 'merged-' || u.pid || '@invalid'
+WHERE EXISTS (SELECT 1 FROM users k WHERE k.email = u.email AND k.id < u.id);
+
+ALTER TABLE users DROP CONSTRAINT IF EXISTS users_email_organisation_pid_key;
+ALTER TABLE users ADD CONSTRAINT users_email_key UNIQUE (email);
+
+ALTER TABLE memberships ENABLE ROW LEVEL SECURITY;
+CREATE POLICY memberships_tenant ON memberships
+    USING (organisation_pid = current_org_pid());
+
+-- Members whose home is another organisation are visible too.
+DROP POLICY IF EXISTS users_tenant ON users;
+CREATE POLICY users_tenant ON users
+    USING (
+        organisation_pid = current_org_pid()
+        OR pid IN (SELECT user_pid FROM memberships WHERE organisation_pid = current_org_pid())
+    );
@@ -0,0 +1,104 @@
+-- Add up migration script here
+
+-- Keys for machines such as milking parlour software and weighing scales.
+-- A key acts for the admin who created it, limited to its scopes. Only a hash
+-- of the key's secret is stored.
+CREATE TABLE api_keys (
+    id SERIAL PRIMARY KEY,
+    pid UUID NOT NULL UNIQUE DEFAULT (uuid_generate_v4()),
+    organisation_pid UUID NOT NULL REFERENCES organisations (pid) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    secret_hash TEXT NOT NULL,
+    scopes TEXT[] NOT NULL DEFAULT '{}',
+    created_by UUID NOT NULL REFERENCES users (pid) ON DELETE CASCADE,
+    expires_at TIMESTAMP WITH TIME ZONE,
+    last_used_at TIMESTAMP WITH TIME ZONE,
+    revoked_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- Names tell the organisation's live keys apart.
+CREATE UNIQUE INDEX api_keys_live_name_idx ON api_keys (organisation_pid, name)
+    WHERE revoked_at IS NULL;
+
+CREATE TRIGGER update_api_keys_timestamp BEFORE UPDATE ON api_keys
+FOR EACH ROW EXECUTE FUNCTION update_timestamp();
+
+ALTER TABLE api_keys ENABLE ROW LEVEL SECURITY;
+CREATE POLICY api_keys_tenant ON api_keys
+    USING (organisation_pid = current_org_pid());
+
+-- Changes made with a key are logged as the key as well as its creator.
+ALTER TABLE audit_logs
+    ADD COLUMN api_key_pid UUID REFERENCES api_keys (pid) ON DELETE SET NULL;
+
+CREATE INDEX audit_logs_api_key_pid_idx ON audit_logs (api_key_pid);
+
+CREATE OR REPLACE FUNCTION current_api_key_pid()
+RETURNS UUID AS $$
+    SELECT NULLIF(current_setting('app.current_api_key_pid', true), '')::UUID;
+$$ LANGUAGE sql STABLE;
+
+CREATE OR REPLACE FUNCTION process_audit() 
+RETURNS TRIGGER AS $$
+DECLARE
+    row_data JSONB;
+    organisation_pid UUID;
+    -- Timestamps are noise and credentials must never reach the log.
+    excluded_cols TEXT[] := ARRAY[
+        'created_at', 'updated_at',
+        'password_hash', 'reset_token', 'token_hash', 'secret_hash', 'secret'
+    ];
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        row_data := to_jsonb(OLD);
+    ELSE
+        row_data := to_jsonb(NEW);
+    END IF;
+
+    IF TG_TABLE_NAME = 'organisations' THEN
+        organisation_pid := (row_data ->> 'pid')::UUID;
+    ELSE
+        organisation_pid := (row_data ->> 'organisation_pid')::UUID;
+    END IF;
+
+    -- Rows removed along with their organisation have nowhere to be logged.
+    IF organisation_pid IS NOT NULL
+        AND NOT EXISTS (SELECT 1 FROM organisations o WHERE o.pid = organisation_pid) THEN
+        RETURN NULL;
+    END IF;
+
+    INSERT INTO audit_logs (
+        organisation_pid,
+        table_name,
+        record_id,
+        action,
+        old_data,
+        new_data,
+        changed_by,
+        impersonator_pid,
+        api_key_pid,
+        changed_at
+    ) VALUES (
+        organisation_pid,
+        TG_TABLE_NAME::VARCHAR(50),
+        COALESCE(row_data ->> 'pid', row_data ->> 'id'),
+        TG_OP,
+        CASE WHEN TG_OP = 'DELETE' OR TG_OP = 'UPDATE'
+            THEN jsonb_strip_nulls(to_jsonb(OLD) - excluded_cols)
+            ELSE NULL
+        END,
+        CASE WHEN TG_OP = 'INSERT' OR TG_OP = 'UPDATE'
+            THEN jsonb_strip_nulls(to_jsonb(NEW) - excluded_cols)
+            ELSE NULL
+        END,
+        current_user_pid(),
+        current_impersonator_pid(),
+        current_api_key_pid(),
+        NOW()
+    );
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER;
@@ -0,0 +1,4 @@
+-- Add down migration script here
+
+DROP TABLE IF EXISTS oidc_logins;
+DROP TABLE IF EXISTS user_identities;
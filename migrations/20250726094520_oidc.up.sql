@@ -0,0 +1,30 @@
+-- Add up migration script here
+
+-- Accounts at an OpenID Connect provider that log in as a Polaris user. The
+-- provider's subject identifies the account, since emails can change.
+CREATE TABLE user_identities (
+    id SERIAL PRIMARY KEY,
+    issuer TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    user_pid UUID NOT NULL REFERENCES users (pid) ON DELETE CASCADE,
+    email VARCHAR(255),
+    last_login TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    UNIQUE (issuer, subject)
+);
+
+CREATE INDEX user_identities_user_pid_idx ON user_identities (user_pid);
+
+CREATE TRIGGER update_user_identities_timestamp BEFORE UPDATE ON user_identities
+FOR EACH ROW EXECUTE FUNCTION update_timestamp();
+
+-- Logins sent to the provider and not back yet. The state comes back with the
+-- callback, the nonce in the ID token and the verifier proves the code is ours.
+CREATE TABLE oidc_logins (
+    id SERIAL PRIMARY KEY,
+    state VARCHAR(64) NOT NULL UNIQUE,
+    nonce VARCHAR(64) NOT NULL,
+    code_verifier VARCHAR(128) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,73 @@
+-- Add up migration script here
+
+-- Things that happened in an organisation that partners may want to know
+-- about. Events are written in the same transaction as the change they
+-- describe, so none are lost or sent for changes that were rolled back.
+CREATE TABLE domain_events (
+    id SERIAL PRIMARY KEY,
+    pid UUID NOT NULL UNIQUE DEFAULT (uuid_generate_v4()),
+    organisation_pid UUID NOT NULL REFERENCES organisations (pid) ON DELETE CASCADE,
+    event_type VARCHAR(50) NOT NULL,
+    payload JSONB NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX domain_events_organisation_pid_idx ON domain_events (organisation_pid, created_at);
+
+-- Partner endpoints subscribed to some of the organisation's events. The
+-- secret signs each payload so the partner can check it came from us.
+CREATE TABLE webhooks (
+    id SERIAL PRIMARY KEY,
+    pid UUID NOT NULL UNIQUE DEFAULT (uuid_generate_v4()),
+    organisation_pid UUID NOT NULL REFERENCES organisations (pid) ON DELETE CASCADE,
+    url TEXT NOT NULL,
+    secret TEXT NOT NULL,
+    event_types TEXT[] NOT NULL DEFAULT '{}',
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_by UUID REFERENCES users (pid) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX webhooks_organisation_pid_idx ON webhooks (organisation_pid);
+
+CREATE TRIGGER update_webhooks_timestamp BEFORE UPDATE ON webhooks
+FOR EACH ROW EXECUTE FUNCTION update_timestamp();
+
+-- The outbox: one row per event and subscribed webhook, kept as the delivery
+-- log once sent.
+CREATE TABLE webhook_deliveries (
+    id SERIAL PRIMARY KEY,
+    pid UUID NOT NULL UNIQUE DEFAULT (uuid_generate_v4()),
+    organisation_pid UUID NOT NULL REFERENCES organisations (pid) ON DELETE CASCADE,
+    webhook_pid UUID NOT NULL REFERENCES webhooks (pid) ON DELETE CASCADE,
+    event_pid UUID NOT NULL REFERENCES domain_events (pid) ON DELETE CASCADE,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'delivered', 'failed')),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    last_attempt_at TIMESTAMP WITH TIME ZONE,
+    response_status INTEGER,
+    last_error TEXT,
+    delivered_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX webhook_deliveries_due_idx ON webhook_deliveries (next_attempt_at)
+    WHERE status = 'pending';
+CREATE INDEX webhook_deliveries_webhook_pid_idx ON webhook_deliveries (webhook_pid, created_at);
+
+CREATE TRIGGER update_webhook_deliveries_timestamp BEFORE UPDATE ON webhook_deliveries
+FOR EACH ROW EXECUTE FUNCTION update_timestamp();
+
+ALTER TABLE domain_events ENABLE ROW LEVEL SECURITY;
+CREATE POLICY domain_events_tenant ON domain_events
+    USING (organisation_pid = current_org_pid());
+
+ALTER TABLE webhooks ENABLE ROW LEVEL SECURITY;
+CREATE POLICY webhooks_tenant ON webhooks
+    USING (organisation_pid = current_org_pid());
+
+ALTER TABLE webhook_deliveries ENABLE ROW LEVEL SECURITY;
+CREATE POLICY webhook_deliveries_tenant ON webhook_deliveries
+    USING (organisation_pid = current_org_pid());
@@ -0,0 +1,9 @@
+-- Add down migration script here
+
+DROP TRIGGER IF EXISTS notify_animals_status_trigger ON animals;
+DROP TRIGGER IF EXISTS notify_weight_records_trigger ON weight_records;
+DROP TRIGGER IF EXISTS notify_health_records_trigger ON health_records;
+
+DROP FUNCTION IF EXISTS notify_animal_status_changed();
+DROP FUNCTION IF EXISTS notify_record_created();
+DROP FUNCTION IF EXISTS notify_live_event(UUID, TEXT, JSONB);
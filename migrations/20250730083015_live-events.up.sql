@@ -0,0 +1,82 @@
+-- Add up migration script here
+
+-- Tells every instance listening on `polaris_events` about a change, once the
+-- transaction making it commits. Payloads are kept small, NOTIFY takes at most
+-- 8000 bytes.
+CREATE OR REPLACE FUNCTION notify_live_event(organisation_pid UUID, event_type TEXT, data JSONB)
+RETURNS VOID AS $$
+BEGIN
+    PERFORM pg_notify(
+        'polaris_events',
+        jsonb_build_object(
+            'organisation', organisation_pid,
+            'type', event_type,
+            'data', data,
+            'occurredAt', NOW()
+        )::TEXT
+    );
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION notify_record_created()
+RETURNS TRIGGER AS $$
+DECLARE
+    data JSONB;
+BEGIN
+    data := jsonb_build_object(
+        'id', NEW.id,
+        'animalPid', NEW.animal_pid,
+        'tagId', (SELECT a.tag_id FROM animals a WHERE a.pid = NEW.animal_pid),
+        'status', NEW.status,
+        'recordDate', NEW.record_date
+    );
+
+    IF TG_TABLE_NAME = 'health_records' THEN
+        PERFORM notify_live_event(
+            NEW.organisation_pid,
+            'health_record.created',
+            data || jsonb_build_object('condition', NEW.condition, 'severity', NEW.severity)
+        );
+    ELSE
+        PERFORM notify_live_event(
+            NEW.organisation_pid,
+            'weight_record.created',
+            data || jsonb_build_object('mass', NEW.mass, 'unit', NEW.unit)
+        );
+    END IF;
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION notify_animal_status_changed()
+RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM notify_live_event(
+        NEW.organisation_pid,
+        'animal.status_changed',
+        jsonb_build_object(
+            'pid', NEW.pid,
+            'tagId', NEW.tag_id,
+            'name', NEW.name,
+            'previousStatus', OLD.status,
+            'status', NEW.status
+        )
+    );
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER notify_health_records_trigger
+AFTER INSERT ON health_records
+FOR EACH ROW EXECUTE FUNCTION notify_record_created();
+
+CREATE TRIGGER notify_weight_records_trigger
+AFTER INSERT ON weight_records
+FOR EACH ROW EXECUTE FUNCTION notify_record_created();
+
+CREATE TRIGGER notify_animals_status_trigger
+AFTER UPDATE OF status ON animals
+FOR EACH ROW WHEN (OLD.status IS DISTINCT FROM NEW.status)
+EXECUTE FUNCTION notify_animal_status_changed();
@@ -0,0 +1,4 @@
+-- Add down migration script here
+
+DROP TABLE IF EXISTS alerts;
+DROP TABLE IF EXISTS alert_rules;
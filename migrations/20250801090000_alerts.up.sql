@@ -0,0 +1,68 @@
+-- Add up migration script here
+
+-- Conditions an organisation wants to hear about, checked periodically
+-- against its animals' records.
+CREATE TABLE alert_rules (
+    id SERIAL PRIMARY KEY,
+    pid UUID NOT NULL UNIQUE DEFAULT (uuid_generate_v4()),
+    organisation_pid UUID NOT NULL REFERENCES organisations (pid) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    kind VARCHAR(50) NOT NULL CHECK (kind IN (
+        'weight_drop',
+        'weighing_overdue',
+        'milk_yield_drop',
+        'repeated_high_severity',
+        'vaccination_overdue'
+    )),
+    threshold DOUBLE PRECISION,
+    window_days INTEGER,
+    severity VARCHAR(20) NOT NULL DEFAULT 'medium' CHECK (severity IN ('low', 'medium', 'high')),
+    channels TEXT[] NOT NULL DEFAULT '{stream,webhook}',
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    last_evaluated_at TIMESTAMP WITH TIME ZONE,
+    created_by UUID REFERENCES users (pid) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX alert_rules_organisation_pid_idx ON alert_rules (organisation_pid);
+
+CREATE TRIGGER update_alert_rules_timestamp BEFORE UPDATE ON alert_rules
+FOR EACH ROW EXECUTE FUNCTION update_timestamp();
+
+-- An animal a rule matched. The fingerprint identifies what it matched on,
+-- e.g. the weighing that dropped, so the same evidence never raises a second
+-- alert, even once the first is resolved.
+CREATE TABLE alerts (
+    id SERIAL PRIMARY KEY,
+    pid UUID NOT NULL UNIQUE DEFAULT (uuid_generate_v4()),
+    organisation_pid UUID NOT NULL REFERENCES organisations (pid) ON DELETE CASCADE,
+    rule_pid UUID NOT NULL REFERENCES alert_rules (pid) ON DELETE CASCADE,
+    animal_pid UUID NOT NULL REFERENCES animals (pid) ON DELETE CASCADE,
+    fingerprint TEXT NOT NULL,
+    severity VARCHAR(20) NOT NULL CHECK (severity IN ('low', 'medium', 'high')),
+    message TEXT NOT NULL,
+    details JSONB NOT NULL DEFAULT '{}',
+    status VARCHAR(20) NOT NULL DEFAULT 'open' CHECK (status IN ('open', 'acknowledged', 'resolved')),
+    acknowledged_by UUID REFERENCES users (pid) ON DELETE SET NULL,
+    acknowledged_at TIMESTAMP WITH TIME ZONE,
+    resolved_by UUID REFERENCES users (pid) ON DELETE SET NULL,
+    resolved_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    UNIQUE (rule_pid, animal_pid, fingerprint)
+);
+
+CREATE INDEX alerts_organisation_pid_idx ON alerts (organisation_pid, status, created_at);
+CREATE INDEX alerts_animal_pid_idx ON alerts (animal_pid);
+
+CREATE TRIGGER update_alerts_timestamp BEFORE UPDATE ON alerts
+FOR EACH ROW EXECUTE FUNCTION update_timestamp();
+
+ALTER TABLE alert_rules ENABLE ROW LEVEL SECURITY;
+CREATE POLICY alert_rules_tenant ON alert_rules
+    USING (organisation_pid = current_org_pid());
+
+ALTER TABLE alerts ENABLE ROW LEVEL SECURITY;
+CREATE POLICY alerts_tenant ON alerts
+    USING (organisation_pid = current_org_pid());
@@ -0,0 +1,69 @@
+-- Add up migration script here
+-- When a kind of job is queued, as a cron expression. Schedules without an
+-- organisation are the platform's own; those of a kind that runs per
+-- organisation queue a job for every organisation that is not suspended.
+CREATE TABLE job_schedules (
+    id SERIAL PRIMARY KEY,
+    pid UUID NOT NULL UNIQUE DEFAULT (uuid_generate_v4()),
+    organisation_pid UUID REFERENCES organisations (pid) ON DELETE CASCADE,
+    kind VARCHAR(50) NOT NULL CHECK (kind IN ('summaries', 'alerts', 'purge', 'deliver')),
+    cron VARCHAR(100) NOT NULL,
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    next_run_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    last_run_at TIMESTAMP WITH TIME ZONE,
+    created_by UUID REFERENCES users (pid) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX job_schedules_due_idx ON job_schedules (next_run_at) WHERE is_active;
+CREATE INDEX job_schedules_organisation_pid_idx ON job_schedules (organisation_pid);
+
+CREATE TRIGGER update_job_schedules_timestamp BEFORE UPDATE ON job_schedules
+FOR EACH ROW EXECUTE FUNCTION update_timestamp();
+
+-- The queue. Workers claim a job with FOR UPDATE SKIP LOCKED and hold it
+-- until `locked_until`, after which a crashed worker's job is claimed again.
+CREATE TABLE jobs (
+    id SERIAL PRIMARY KEY,
+    pid UUID NOT NULL UNIQUE DEFAULT (uuid_generate_v4()),
+    organisation_pid UUID REFERENCES organisations (pid) ON DELETE CASCADE,
+    schedule_pid UUID REFERENCES job_schedules (pid) ON DELETE SET NULL,
+    kind VARCHAR(50) NOT NULL CHECK (kind IN ('summaries', 'alerts', 'purge', 'deliver')),
+    status VARCHAR(20) NOT NULL DEFAULT 'queued'
+        CHECK (status IN ('queued', 'running', 'succeeded', 'failed')),
+    attempts INT NOT NULL DEFAULT 0,
+    max_attempts INT NOT NULL,
+    run_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    locked_until TIMESTAMP WITH TIME ZONE,
+    result JSONB,
+    last_error TEXT,
+    finished_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX jobs_claim_idx ON jobs (run_at, id) WHERE status IN ('queued', 'running');
+CREATE INDEX jobs_organisation_pid_idx ON jobs (organisation_pid, created_at);
+CREATE INDEX jobs_schedule_pid_idx ON jobs (schedule_pid) WHERE status IN ('queued', 'running');
+
+CREATE TRIGGER update_jobs_timestamp BEFORE UPDATE ON jobs
+FOR EACH ROW EXECUTE FUNCTION update_timestamp();
+
+-- Tenants only see their own schedules and jobs, never the platform's.
+ALTER TABLE job_schedules ENABLE ROW LEVEL SECURITY;
+CREATE POLICY job_schedules_tenant ON job_schedules
+    USING (organisation_pid = current_org_pid());
+
+ALTER TABLE jobs ENABLE ROW LEVEL SECURITY;
+CREATE POLICY jobs_tenant ON jobs
+    USING (organisation_pid = current_org_pid());
+
+-- The platform's schedules, all in UTC: summaries nightly, alerts every 15
+-- minutes, the trash purge daily and webhook deliveries every 10 seconds.
+INSERT INTO job_schedules (kind, cron, next_run_at)
+VALUES
+    ('summaries', '0 2 * * *', date_trunc('day', NOW()) + INTERVAL '1 day 2 hours'),
+    ('alerts', '*/15 * * * *', NOW()),
+    ('purge', '0 3 * * *', date_trunc('day', NOW()) + INTERVAL '1 day 3 hours'),
+    ('deliver', '*/10 * * * * *', NOW());
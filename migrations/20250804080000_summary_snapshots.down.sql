@@ -0,0 +1,23 @@
+-- Add down migration script here
+
+DROP INDEX IF EXISTS breed_summary_org_created_idx;
+DROP INDEX IF EXISTS species_summary_org_created_idx;
+DROP INDEX IF EXISTS livestock_summary_org_created_idx;
+
+-- Only the latest snapshot of each specie and breed may stay.
+DELETE FROM species_summary older
+USING species_summary newer
+WHERE older.organisation_pid = newer.organisation_pid
+    AND older.specie_id = newer.specie_id
+    AND older.id < newer.id;
+
+DELETE FROM breed_summary older
+USING breed_summary newer
+WHERE older.organisation_pid = newer.organisation_pid
+    AND older.breed_id = newer.breed_id
+    AND older.id < newer.id;
+
+ALTER TABLE species_summary ADD CONSTRAINT species_summary_organisation_pid_specie_id_key
+    UNIQUE (organisation_pid, specie_id);
+ALTER TABLE breed_summary ADD CONSTRAINT breed_summary_organisation_pid_breed_id_key
+    UNIQUE (organisation_pid, breed_id);
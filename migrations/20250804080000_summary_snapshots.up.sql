@@ -0,0 +1,10 @@
+-- Add up migration script here
+
+-- Species and breed summaries are kept as snapshots, like the livestock
+-- summary, so trends can be read back from them.
+ALTER TABLE species_summary DROP CONSTRAINT species_summary_organisation_pid_specie_id_key;
+ALTER TABLE breed_summary DROP CONSTRAINT breed_summary_organisation_pid_breed_id_key;
+
+CREATE INDEX livestock_summary_org_created_idx ON livestock_summary (organisation_pid, created_at);
+CREATE INDEX species_summary_org_created_idx ON species_summary (organisation_pid, created_at);
+CREATE INDEX breed_summary_org_created_idx ON breed_summary (organisation_pid, created_at);
@@ -0,0 +1,6 @@
+-- Add down migration script here
+DROP TRIGGER IF EXISTS count_animals_update_trigger ON animals;
+DROP TRIGGER IF EXISTS count_animals_trigger ON animals;
+DROP FUNCTION IF EXISTS count_animals();
+DROP FUNCTION IF EXISTS count_animal(animals, INTEGER);
+DROP TABLE IF EXISTS animal_counts;
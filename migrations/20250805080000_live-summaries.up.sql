@@ -0,0 +1,153 @@
+-- Add up migration script here
+
+-- Running counts of each organisation's animals by specie and breed, kept up
+-- to date by a trigger on every change to an animal. Averages are kept as
+-- sums and counts so they can be adjusted one animal at a time. Animals in
+-- the trash are not counted.
+CREATE TABLE animal_counts (
+    id SERIAL PRIMARY KEY,
+    organisation_pid UUID NOT NULL REFERENCES organisations(pid) ON DELETE CASCADE,
+    specie_id INTEGER NOT NULL REFERENCES species(id) ON DELETE CASCADE,
+    breed_id INTEGER NOT NULL REFERENCES breeds(id) ON DELETE CASCADE,
+    total INTEGER NOT NULL DEFAULT 0,
+    males INTEGER NOT NULL DEFAULT 0,
+    females INTEGER NOT NULL DEFAULT 0,
+    unknown_gender INTEGER NOT NULL DEFAULT 0,
+    active INTEGER NOT NULL DEFAULT 0,
+    transferred INTEGER NOT NULL DEFAULT 0,
+    sold INTEGER NOT NULL DEFAULT 0,
+    deceased INTEGER NOT NULL DEFAULT 0,
+    purchase_value NUMERIC(14,2) NOT NULL DEFAULT 0,
+    weight_male NUMERIC(14,2) NOT NULL DEFAULT 0,
+    weighed_males INTEGER NOT NULL DEFAULT 0,
+    weight_female NUMERIC(14,2) NOT NULL DEFAULT 0,
+    weighed_females INTEGER NOT NULL DEFAULT 0,
+    birth_weight_male NUMERIC(14,2) NOT NULL DEFAULT 0,
+    birth_weighed_males INTEGER NOT NULL DEFAULT 0,
+    birth_weight_female NUMERIC(14,2) NOT NULL DEFAULT 0,
+    birth_weighed_females INTEGER NOT NULL DEFAULT 0,
+    -- Dates of birth as days since 1970, so the average age can be worked
+    -- out on the day it is read.
+    birth_days BIGINT NOT NULL DEFAULT 0,
+    born INTEGER NOT NULL DEFAULT 0,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    UNIQUE (organisation_pid, specie_id, breed_id)
+);
+
+ALTER TABLE animal_counts ENABLE ROW LEVEL SECURITY;
+CREATE POLICY animal_counts_tenant ON animal_counts
+    USING (organisation_pid = current_org_pid());
+
+-- Adds an animal to its counts, or takes it away with a `sign` of -1.
+CREATE OR REPLACE FUNCTION count_animal(animal animals, sign INTEGER)
+RETURNS VOID AS $$
+DECLARE
+    gender TEXT := COALESCE(animal.gender, 'unknown');
+BEGIN
+    IF animal.deleted_at IS NOT NULL THEN
+        RETURN;
+    END IF;
+
+    -- An animal being taken away was counted, unless its organisation, specie
+    -- or breed is being deleted with it and took the counts along.
+    IF sign > 0 THEN
+        INSERT INTO animal_counts (organisation_pid, specie_id, breed_id)
+        VALUES (animal.organisation_pid, animal.specie_id, animal.breed_id)
+        ON CONFLICT (organisation_pid, specie_id, breed_id) DO NOTHING;
+    END IF;
+
+    UPDATE animal_counts SET
+        total = total + sign,
+        males = males + CASE WHEN gender = 'male' THEN sign ELSE 0 END,
+        females = females + CASE WHEN gender = 'female' THEN sign ELSE 0 END,
+        unknown_gender = unknown_gender + CASE WHEN gender = 'unknown' THEN sign ELSE 0 END,
+        active = active + CASE WHEN animal.status = 'active' THEN sign ELSE 0 END,
+        transferred = transferred + CASE WHEN animal.status = 'transferred' THEN sign ELSE 0 END,
+        sold = sold + CASE WHEN animal.status = 'sold' THEN sign ELSE 0 END,
+        deceased = deceased + CASE WHEN animal.status = 'deceased' THEN sign ELSE 0 END,
+        purchase_value = purchase_value + sign * COALESCE(animal.purchase_price, 0),
+        weight_male = weight_male
+            + CASE WHEN gender = 'male' THEN sign * COALESCE(animal.current_weight, 0) ELSE 0 END,
+        weighed_males = weighed_males
+            + CASE WHEN gender = 'male' AND animal.current_weight IS NOT NULL THEN sign ELSE 0 END,
+        weight_female = weight_female
+            + CASE WHEN gender = 'female' THEN sign * COALESCE(animal.current_weight, 0) ELSE 0 END,
+        weighed_females = weighed_females
+            + CASE WHEN gender = 'female' AND animal.current_weight IS NOT NULL THEN sign ELSE 0 END,
+        birth_weight_male = birth_weight_male
+            + CASE WHEN gender = 'male' THEN sign * COALESCE(animal.weight_at_birth, 0) ELSE 0 END,
+        birth_weighed_males = birth_weighed_males
+            + CASE WHEN gender = 'male' AND animal.weight_at_birth IS NOT NULL THEN sign ELSE 0 END,
+        birth_weight_female = birth_weight_female
+            + CASE WHEN gender = 'female' THEN sign * COALESCE(animal.weight_at_birth, 0) ELSE 0 END,
+        birth_weighed_females = birth_weighed_females
+            + CASE WHEN gender = 'female' AND animal.weight_at_birth IS NOT NULL THEN sign ELSE 0 END,
+        birth_days = birth_days + sign * COALESCE(animal.date_of_birth - DATE '1970-01-01', 0),
+        born = born + CASE WHEN animal.date_of_birth IS NOT NULL THEN sign ELSE 0 END,
+        updated_at = NOW()
+    WHERE organisation_pid = animal.organisation_pid
+        AND specie_id = animal.specie_id
+        AND breed_id = animal.breed_id;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION count_animals()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'DELETE' OR TG_OP = 'UPDATE' THEN
+        PERFORM count_animal(OLD, -1);
+    END IF;
+
+    IF TG_OP = 'INSERT' OR TG_OP = 'UPDATE' THEN
+        PERFORM count_animal(NEW, 1);
+    END IF;
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER;
+
+CREATE TRIGGER count_animals_trigger
+AFTER INSERT OR DELETE ON animals
+FOR EACH ROW EXECUTE FUNCTION count_animals();
+
+CREATE TRIGGER count_animals_update_trigger
+AFTER UPDATE OF
+    organisation_pid, specie_id, breed_id, gender, status, purchase_price,
+    current_weight, weight_at_birth, date_of_birth, deleted_at
+ON animals
+FOR EACH ROW EXECUTE FUNCTION count_animals();
+
+-- Count the animals there already are.
+INSERT INTO animal_counts (
+    organisation_pid, specie_id, breed_id, total, males, females, unknown_gender,
+    active, transferred, sold, deceased, purchase_value,
+    weight_male, weighed_males, weight_female, weighed_females,
+    birth_weight_male, birth_weighed_males, birth_weight_female, birth_weighed_females,
+    birth_days, born
+)
+SELECT
+    organisation_pid,
+    specie_id,
+    breed_id,
+    COUNT(*),
+    COUNT(*) FILTER (WHERE gender = 'male'),
+    COUNT(*) FILTER (WHERE gender = 'female'),
+    COUNT(*) FILTER (WHERE COALESCE(gender, 'unknown') = 'unknown'),
+    COUNT(*) FILTER (WHERE status = 'active'),
+    COUNT(*) FILTER (WHERE status = 'transferred'),
+    COUNT(*) FILTER (WHERE status = 'sold'),
+    COUNT(*) FILTER (WHERE status = 'deceased'),
+    COALESCE(SUM(purchase_price), 0),
+    COALESCE(SUM(current_weight) FILTER (WHERE gender = 'male'), 0),
+    COUNT(current_weight) FILTER (WHERE gender = 'male'),
+    COALESCE(SUM(current_weight) FILTER (WHERE gender = 'female'), 0),
+    COUNT(current_weight) FILTER (WHERE gender = 'female'),
+    COALESCE(SUM(weight_at_birth) FILTER (WHERE gender = 'male'), 0),
+    COUNT(weight_at_birth) FILTER (WHERE gender = 'male'),
+    COALESCE(SUM(weight_at_birth) FILTER (WHERE gender = 'female'), 0),
+    COUNT(weight_at_birth) FILTER (WHERE gender = 'female'),
+    COALESCE(SUM(date_of_birth - DATE '1970-01-01'), 0),
+    COUNT(date_of_birth)
+FROM animals
+WHERE deleted_at IS NULL
+GROUP BY organisation_pid, specie_id, breed_id;
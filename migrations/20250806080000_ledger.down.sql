@@ -0,0 +1,7 @@
+-- Add down migration script here
+
+DROP TRIGGER IF EXISTS post_health_cost_trigger ON health_records;
+DROP TRIGGER IF EXISTS post_animal_purchase_trigger ON animals;
+DROP FUNCTION IF EXISTS post_health_cost();
+DROP FUNCTION IF EXISTS post_animal_purchase();
+DROP TABLE IF EXISTS ledger_entries;
@@ -0,0 +1,149 @@
+-- Add up migration script here
+
+-- Money spent and earned, by the whole organisation, one animal or a group of
+-- animals. Entries with a source are posted from the record named by `source`
+-- and `source_id`, and change along with it.
+--
+-- Entries outlive their animal, so purging it from the trash leaves its sales
+-- and expenses with the organisation. Groups are added along with their
+-- table, which references them from here.
+CREATE TABLE ledger_entries (
+    id SERIAL PRIMARY KEY,
+    pid UUID NOT NULL UNIQUE DEFAULT (uuid_generate_v4()),
+    organisation_pid UUID NOT NULL REFERENCES organisations (pid) ON DELETE CASCADE,
+    animal_pid UUID REFERENCES animals (pid) ON DELETE SET NULL,
+    group_pid UUID,
+    category VARCHAR(30) NOT NULL CHECK (category IN (
+        'purchase',
+        'feed',
+        'vet',
+        'labour',
+        'other_expense',
+        'sale_proceeds',
+        'milk_sales',
+        'other_income'
+    )),
+    amount NUMERIC(12, 2) NOT NULL CHECK (amount >= 0),
+    entry_date DATE NOT NULL DEFAULT CURRENT_DATE,
+    description TEXT,
+    source VARCHAR(30) CHECK (source IN ('animal_purchase', 'animal_sale', 'health_record')),
+    source_id TEXT,
+    created_by UUID REFERENCES users (pid) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    UNIQUE (source, source_id),
+    CHECK ((source IS NULL) = (source_id IS NULL)),
+    CONSTRAINT ledger_entries_scope_check CHECK (animal_pid IS NULL OR group_pid IS NULL)
+);
+
+CREATE INDEX ledger_entries_organisation_pid_idx ON ledger_entries (organisation_pid, entry_date);
+CREATE INDEX ledger_entries_animal_pid_idx ON ledger_entries (animal_pid);
+CREATE INDEX ledger_entries_group_pid_idx ON ledger_entries (group_pid);
+
+CREATE TRIGGER update_ledger_entries_timestamp BEFORE UPDATE ON ledger_entries
+FOR EACH ROW EXECUTE FUNCTION update_timestamp();
+
+CREATE TRIGGER audit_ledger_entries_trigger
+AFTER INSERT OR UPDATE OR DELETE ON ledger_entries
+FOR EACH ROW EXECUTE FUNCTION process_audit();
+
+ALTER TABLE ledger_entries ENABLE ROW LEVEL SECURITY;
+CREATE POLICY ledger_entries_tenant ON ledger_entries
+    USING (organisation_pid = current_org_pid());
+
+-- Posts what an animal was bought for, and withdraws it once the animal is
+-- trashed or its price removed.
+CREATE OR REPLACE FUNCTION post_animal_purchase()
+RETURNS TRIGGER
+SECURITY DEFINER
+AS $$
+BEGIN
+    IF NEW.deleted_at IS NULL AND COALESCE(NEW.purchase_price, 0) > 0 THEN
+        INSERT INTO ledger_entries (
+            organisation_pid, animal_pid, category, amount, entry_date,
+            description, source, source_id, created_by
+        )
+        VALUES (
+            NEW.organisation_pid, NEW.pid, 'purchase', NEW.purchase_price,
+            COALESCE(NEW.purchase_date, NEW.created_at::DATE),
+            format('Purchase of %s', NEW.tag_id), 'animal_purchase', NEW.pid::TEXT, NEW.created_by
+        )
+        ON CONFLICT (source, source_id) DO UPDATE SET
+            amount = EXCLUDED.amount,
+            entry_date = EXCLUDED.entry_date,
+            description = EXCLUDED.description;
+    ELSE
+        DELETE FROM ledger_entries
+        WHERE source = 'animal_purchase' AND source_id = NEW.pid::TEXT;
+    END IF;
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER post_animal_purchase_trigger
+AFTER INSERT OR UPDATE OF purchase_price, purchase_date, tag_id, deleted_at ON animals
+FOR EACH ROW EXECUTE FUNCTION post_animal_purchase();
+
+-- Posts what treating an animal cost as a vet expense, following the health
+-- record through edits, the trash and its removal.
+CREATE OR REPLACE FUNCTION post_health_cost()
+RETURNS TRIGGER
+SECURITY DEFINER
+AS $$
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        DELETE FROM ledger_entries
+        WHERE source = 'health_record' AND source_id = OLD.id::TEXT;
+        RETURN NULL;
+    END IF;
+
+    IF NEW.deleted_at IS NULL AND COALESCE(NEW.cost, 0) > 0 THEN
+        INSERT INTO ledger_entries (
+            organisation_pid, animal_pid, category, amount, entry_date,
+            description, source, source_id, created_by
+        )
+        VALUES (
+            NEW.organisation_pid, NEW.animal_pid, 'vet', NEW.cost, NEW.record_date,
+            format('Treatment for %s', NEW.condition), 'health_record', NEW.id::TEXT, NEW.created_by
+        )
+        ON CONFLICT (source, source_id) DO UPDATE SET
+            animal_pid = EXCLUDED.animal_pid,
+            amount = EXCLUDED.amount,
+            entry_date = EXCLUDED.entry_date,
+            description = EXCLUDED.description;
+    ELSE
+        DELETE FROM ledger_entries
+        WHERE source = 'health_record' AND source_id = NEW.id::TEXT;
+    END IF;
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER post_health_cost_trigger
+AFTER INSERT OR DELETE OR UPDATE OF animal_pid, cost, record_date, condition, deleted_at
+ON health_records
+FOR EACH ROW EXECUTE FUNCTION post_health_cost();
+
+-- Posts what is already recorded.
+INSERT INTO ledger_entries (
+    organisation_pid, animal_pid, category, amount, entry_date,
+    description, source, source_id, created_by
+)
+SELECT
+    a.organisation_pid, a.pid, 'purchase', a.purchase_price,
+    COALESCE(a.purchase_date, a.created_at::DATE),
+    format('Purchase of %s', a.tag_id), 'animal_purchase', a.pid::TEXT, a.created_by
+FROM animals a
+WHERE a.deleted_at IS NULL AND a.purchase_price > 0;
+
+INSERT INTO ledger_entries (
+    organisation_pid, animal_pid, category, amount, entry_date,
+    description, source, source_id, created_by
+)
+SELECT
+    hr.organisation_pid, hr.animal_pid, 'vet', hr.cost, hr.record_date,
+    format('Treatment for %s', hr.condition), 'health_record', hr.id::TEXT, hr.created_by
+FROM health_records hr
+WHERE hr.deleted_at IS NULL AND hr.cost > 0;
@@ -0,0 +1,16 @@
+-- Add down migration script here
+
+DROP TRIGGER IF EXISTS post_feed_purchase_trigger ON feed_purchases;
+DROP FUNCTION IF EXISTS post_feed_purchase();
+DELETE FROM ledger_entries WHERE source = 'feed_purchase';
+ALTER TABLE ledger_entries DROP CONSTRAINT ledger_entries_source_check;
+ALTER TABLE ledger_entries ADD CONSTRAINT ledger_entries_source_check
+    CHECK (source IN ('animal_purchase', 'animal_sale', 'health_record'));
+
+DROP TABLE IF EXISTS feeding_animals;
+DROP TABLE IF EXISTS feedings;
+DROP TABLE IF EXISTS ration_items;
+DROP TABLE IF EXISTS rations;
+DROP TABLE IF EXISTS feed_purchases;
+DROP TABLE IF EXISTS feedstuffs;
+DROP FUNCTION IF EXISTS adjust_feed_stock();
@@ -0,0 +1,215 @@
+-- Add up migration script here
+
+-- What animals are fed, with its nutrient profile. Percentages are of the
+-- feed as fed, energy is megajoules of metabolisable energy per kilogram of
+-- dry matter. Quantities are in the organisation's weight unit, and `stock`
+-- is kept up to date from purchases and feedings by triggers.
+CREATE TABLE feedstuffs (
+    id SERIAL PRIMARY KEY,
+    pid UUID NOT NULL UNIQUE DEFAULT (uuid_generate_v4()),
+    organisation_pid UUID NOT NULL REFERENCES organisations (pid) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    dry_matter NUMERIC(5, 2) CHECK (dry_matter BETWEEN 0 AND 100),
+    crude_protein NUMERIC(5, 2) CHECK (crude_protein BETWEEN 0 AND 100),
+    crude_fibre NUMERIC(5, 2) CHECK (crude_fibre BETWEEN 0 AND 100),
+    energy NUMERIC(6, 2) CHECK (energy >= 0),
+    stock NUMERIC(12, 2) NOT NULL DEFAULT 0,
+    reorder_level NUMERIC(12, 2) CHECK (reorder_level >= 0),
+    notes TEXT,
+    created_by UUID REFERENCES users (pid) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    UNIQUE (organisation_pid, name)
+);
+
+CREATE TABLE feed_purchases (
+    id SERIAL PRIMARY KEY,
+    pid UUID NOT NULL UNIQUE DEFAULT (uuid_generate_v4()),
+    organisation_pid UUID NOT NULL REFERENCES organisations (pid) ON DELETE CASCADE,
+    feedstuff_id INTEGER NOT NULL REFERENCES feedstuffs (id) ON DELETE CASCADE,
+    quantity NUMERIC(12, 2) NOT NULL CHECK (quantity > 0),
+    cost NUMERIC(12, 2) CHECK (cost >= 0),
+    supplier VARCHAR(100),
+    purchase_date DATE NOT NULL DEFAULT CURRENT_DATE,
+    created_by UUID REFERENCES users (pid) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- A daily diet: how much of each feedstuff an animal gets a day. Rations are
+-- meant for the animals of a specie, or of one of its breeds.
+CREATE TABLE rations (
+    id SERIAL PRIMARY KEY,
+    pid UUID NOT NULL UNIQUE DEFAULT (uuid_generate_v4()),
+    organisation_pid UUID NOT NULL REFERENCES organisations (pid) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    specie_id INTEGER REFERENCES species (id) ON DELETE SET NULL,
+    breed_id INTEGER REFERENCES breeds (id) ON DELETE SET NULL,
+    notes TEXT,
+    created_by UUID REFERENCES users (pid) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    UNIQUE (organisation_pid, name)
+);
+
+CREATE TABLE ration_items (
+    ration_id INTEGER NOT NULL REFERENCES rations (id) ON DELETE CASCADE,
+    feedstuff_id INTEGER NOT NULL REFERENCES feedstuffs (id) ON DELETE CASCADE,
+    organisation_pid UUID NOT NULL REFERENCES organisations (pid) ON DELETE CASCADE,
+    -- Per head per day.
+    quantity NUMERIC(10, 2) NOT NULL CHECK (quantity > 0),
+    PRIMARY KEY (ration_id, feedstuff_id)
+);
+
+-- Feed given out on a day, shared equally by the animals it was fed to.
+CREATE TABLE feedings (
+    id SERIAL PRIMARY KEY,
+    pid UUID NOT NULL UNIQUE DEFAULT (uuid_generate_v4()),
+    organisation_pid UUID NOT NULL REFERENCES organisations (pid) ON DELETE CASCADE,
+    feedstuff_id INTEGER NOT NULL REFERENCES feedstuffs (id) ON DELETE CASCADE,
+    ration_id INTEGER REFERENCES rations (id) ON DELETE SET NULL,
+    quantity NUMERIC(12, 2) NOT NULL CHECK (quantity > 0),
+    feeding_date DATE NOT NULL DEFAULT CURRENT_DATE,
+    notes TEXT,
+    created_by UUID REFERENCES users (pid) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE feeding_animals (
+    feeding_id INTEGER NOT NULL REFERENCES feedings (id) ON DELETE CASCADE,
+    animal_pid UUID NOT NULL REFERENCES animals (pid) ON DELETE CASCADE,
+    organisation_pid UUID NOT NULL REFERENCES organisations (pid) ON DELETE CASCADE,
+    PRIMARY KEY (feeding_id, animal_pid)
+);
+
+CREATE INDEX feed_purchases_feedstuff_id_idx ON feed_purchases (feedstuff_id, purchase_date);
+CREATE INDEX feedings_feedstuff_id_idx ON feedings (feedstuff_id, feeding_date);
+CREATE INDEX feedings_organisation_pid_idx ON feedings (organisation_pid, feeding_date);
+CREATE INDEX feeding_animals_animal_pid_idx ON feeding_animals (animal_pid);
+
+CREATE TRIGGER update_feedstuffs_timestamp BEFORE UPDATE ON feedstuffs
+FOR EACH ROW EXECUTE FUNCTION update_timestamp();
+
+CREATE TRIGGER update_feed_purchases_timestamp BEFORE UPDATE ON feed_purchases
+FOR EACH ROW EXECUTE FUNCTION update_timestamp();
+
+CREATE TRIGGER update_rations_timestamp BEFORE UPDATE ON rations
+FOR EACH ROW EXECUTE FUNCTION update_timestamp();
+
+CREATE TRIGGER update_feedings_timestamp BEFORE UPDATE ON feedings
+FOR EACH ROW EXECUTE FUNCTION update_timestamp();
+
+CREATE TRIGGER audit_feedstuffs_trigger
+AFTER INSERT OR UPDATE OR DELETE ON feedstuffs
+FOR EACH ROW EXECUTE FUNCTION process_audit();
+
+CREATE TRIGGER audit_feed_purchases_trigger
+AFTER INSERT OR UPDATE OR DELETE ON feed_purchases
+FOR EACH ROW EXECUTE FUNCTION process_audit();
+
+CREATE TRIGGER audit_rations_trigger
+AFTER INSERT OR UPDATE OR DELETE ON rations
+FOR EACH ROW EXECUTE FUNCTION process_audit();
+
+CREATE TRIGGER audit_feedings_trigger
+AFTER INSERT OR UPDATE OR DELETE ON feedings
+FOR EACH ROW EXECUTE FUNCTION process_audit();
+
+ALTER TABLE feedstuffs ENABLE ROW LEVEL SECURITY;
+CREATE POLICY feedstuffs_tenant ON feedstuffs
+    USING (organisation_pid = current_org_pid());
+
+ALTER TABLE feed_purchases ENABLE ROW LEVEL SECURITY;
+CREATE POLICY feed_purchases_tenant ON feed_purchases
+    USING (organisation_pid = current_org_pid());
+
+ALTER TABLE rations ENABLE ROW LEVEL SECURITY;
+CREATE POLICY rations_tenant ON rations
+    USING (organisation_pid = current_org_pid());
+
+ALTER TABLE ration_items ENABLE ROW LEVEL SECURITY;
+CREATE POLICY ration_items_tenant ON ration_items
+    USING (organisation_pid = current_org_pid());
+
+ALTER TABLE feedings ENABLE ROW LEVEL SECURITY;
+CREATE POLICY feedings_tenant ON feedings
+    USING (organisation_pid = current_org_pid());
+
+ALTER TABLE feeding_animals ENABLE ROW LEVEL SECURITY;
+CREATE POLICY feeding_animals_tenant ON feeding_animals
+    USING (organisation_pid = current_org_pid());
+
+-- Purchases add to a feedstuff's stock and feedings take from it.
+CREATE OR REPLACE FUNCTION adjust_feed_stock()
+RETURNS TRIGGER
+SECURITY DEFINER
+AS $$
+DECLARE
+    sign INTEGER := CASE WHEN TG_TABLE_NAME = 'feed_purchases' THEN 1 ELSE -1 END;
+BEGIN
+    IF TG_OP IN ('UPDATE', 'DELETE') THEN
+        UPDATE feedstuffs SET stock = stock - sign * OLD.quantity
+        WHERE id = OLD.feedstuff_id;
+    END IF;
+
+    IF TG_OP IN ('INSERT', 'UPDATE') THEN
+        UPDATE feedstuffs SET stock = stock + sign * NEW.quantity
+        WHERE id = NEW.feedstuff_id;
+    END IF;
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER adjust_feed_stock_on_purchase_trigger
+AFTER INSERT OR DELETE OR UPDATE OF feedstuff_id, quantity ON feed_purchases
+FOR EACH ROW EXECUTE FUNCTION adjust_feed_stock();
+
+CREATE TRIGGER adjust_feed_stock_on_feeding_trigger
+AFTER INSERT OR DELETE OR UPDATE OF feedstuff_id, quantity ON feedings
+FOR EACH ROW EXECUTE FUNCTION adjust_feed_stock();
+
+-- Feed bought is a feed expense in the ledger.
+ALTER TABLE ledger_entries DROP CONSTRAINT ledger_entries_source_check;
+ALTER TABLE ledger_entries ADD CONSTRAINT ledger_entries_source_check
+    CHECK (source IN ('animal_purchase', 'animal_sale', 'health_record', 'feed_purchase'));
+
+CREATE OR REPLACE FUNCTION post_feed_purchase()
+RETURNS TRIGGER
+SECURITY DEFINER
+AS $$
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        DELETE FROM ledger_entries
+        WHERE source = 'feed_purchase' AND source_id = OLD.id::TEXT;
+        RETURN NULL;
+    END IF;
+
+    IF COALESCE(NEW.cost, 0) > 0 THEN
+        INSERT INTO ledger_entries (
+            organisation_pid, category, amount, entry_date,
+            description, source, source_id, created_by
+        )
+        SELECT
+            NEW.organisation_pid, 'feed', NEW.cost, NEW.purchase_date,
+            format('%s %s', NEW.quantity, f.name), 'feed_purchase', NEW.id::TEXT, NEW.created_by
+        FROM feedstuffs f
+        WHERE f.id = NEW.feedstuff_id
+        ON CONFLICT (source, source_id) DO UPDATE SET
+            amount = EXCLUDED.amount,
+            entry_date = EXCLUDED.entry_date,
+            description = EXCLUDED.description;
+    ELSE
+        DELETE FROM ledger_entries
+        WHERE source = 'feed_purchase' AND source_id = NEW.id::TEXT;
+    END IF;
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER post_feed_purchase_trigger
+AFTER INSERT OR DELETE OR UPDATE OF feedstuff_id, quantity, cost, purchase_date
+ON feed_purchases
+FOR EACH ROW EXECUTE FUNCTION post_feed_purchase();
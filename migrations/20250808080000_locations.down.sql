@@ -0,0 +1,5 @@
+-- Add down migration script here
+
+DROP TABLE IF EXISTS animal_movements;
+ALTER TABLE animals DROP COLUMN IF EXISTS location_id;
+DROP TABLE IF EXISTS locations;
@@ -0,0 +1,59 @@
+-- Add up migration script here
+
+-- Where animals are kept: sites, the paddocks on them and pens on a site or
+-- in a paddock. Capacity counts the animals of every location beneath.
+CREATE TABLE locations (
+    id SERIAL PRIMARY KEY,
+    pid UUID NOT NULL UNIQUE DEFAULT (uuid_generate_v4()),
+    organisation_pid UUID NOT NULL REFERENCES organisations (pid) ON DELETE CASCADE,
+    parent_id INTEGER REFERENCES locations (id),
+    kind VARCHAR(20) NOT NULL CHECK (kind IN ('site', 'paddock', 'pen')),
+    name VARCHAR(100) NOT NULL,
+    capacity INTEGER CHECK (capacity > 0),
+    notes TEXT,
+    created_by UUID REFERENCES users (pid) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    CHECK ((kind = 'site') = (parent_id IS NULL))
+);
+
+CREATE UNIQUE INDEX locations_name_idx
+ON locations (organisation_pid, COALESCE(parent_id, 0), LOWER(name));
+CREATE INDEX locations_parent_id_idx ON locations (parent_id);
+
+ALTER TABLE animals ADD COLUMN location_id INTEGER REFERENCES locations (id) ON DELETE SET NULL;
+CREATE INDEX animals_location_id_idx ON animals (location_id);
+
+-- Every move of an animal. Animals moved together share a batch.
+CREATE TABLE animal_movements (
+    id SERIAL PRIMARY KEY,
+    pid UUID NOT NULL UNIQUE DEFAULT (uuid_generate_v4()),
+    organisation_pid UUID NOT NULL REFERENCES organisations (pid) ON DELETE CASCADE,
+    batch_pid UUID NOT NULL,
+    animal_pid UUID NOT NULL REFERENCES animals (pid) ON DELETE CASCADE,
+    from_location_id INTEGER REFERENCES locations (id) ON DELETE SET NULL,
+    to_location_id INTEGER REFERENCES locations (id) ON DELETE SET NULL,
+    moved_on DATE NOT NULL DEFAULT CURRENT_DATE,
+    reason TEXT,
+    moved_by UUID REFERENCES users (pid) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX animal_movements_animal_pid_idx ON animal_movements (animal_pid, moved_on);
+CREATE INDEX animal_movements_batch_pid_idx ON animal_movements (batch_pid);
+CREATE INDEX animal_movements_organisation_pid_idx ON animal_movements (organisation_pid, moved_on);
+
+CREATE TRIGGER update_locations_timestamp BEFORE UPDATE ON locations
+FOR EACH ROW EXECUTE FUNCTION update_timestamp();
+
+CREATE TRIGGER audit_locations_trigger
+AFTER INSERT OR UPDATE OR DELETE ON locations
+FOR EACH ROW EXECUTE FUNCTION process_audit();
+
+ALTER TABLE locations ENABLE ROW LEVEL SECURITY;
+CREATE POLICY locations_tenant ON locations
+    USING (organisation_pid = current_org_pid());
+
+ALTER TABLE animal_movements ENABLE ROW LEVEL SECURITY;
+CREATE POLICY animal_movements_tenant ON animal_movements
+    USING (organisation_pid = current_org_pid());
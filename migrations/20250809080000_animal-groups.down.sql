@@ -0,0 +1,5 @@
+-- Add down migration script here
+
+ALTER TABLE ledger_entries DROP CONSTRAINT IF EXISTS ledger_entries_group_pid_fkey;
+DROP TABLE IF EXISTS animal_group_members;
+DROP TABLE IF EXISTS animal_groups;
@@ -0,0 +1,55 @@
+-- Add up migration script here
+
+-- Named groups of animals. A static group's members are added and removed by
+-- hand, a dynamic group's are the animals matching its saved `filter`.
+CREATE TABLE animal_groups (
+    id SERIAL PRIMARY KEY,
+    pid UUID NOT NULL UNIQUE DEFAULT (uuid_generate_v4()),
+    organisation_pid UUID NOT NULL REFERENCES organisations (pid) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    kind VARCHAR(20) NOT NULL CHECK (kind IN ('static', 'dynamic')),
+    filter JSONB,
+    notes TEXT,
+    created_by UUID REFERENCES users (pid) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    CHECK ((kind = 'dynamic') = (filter IS NOT NULL))
+);
+
+CREATE UNIQUE INDEX animal_groups_name_idx ON animal_groups (organisation_pid, LOWER(name));
+
+-- Every stay of an animal in a group. Current members have not left yet.
+CREATE TABLE animal_group_members (
+    id SERIAL PRIMARY KEY,
+    organisation_pid UUID NOT NULL REFERENCES organisations (pid) ON DELETE CASCADE,
+    group_id INTEGER NOT NULL REFERENCES animal_groups (id) ON DELETE CASCADE,
+    animal_pid UUID NOT NULL REFERENCES animals (pid) ON DELETE CASCADE,
+    joined_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    left_at TIMESTAMP WITH TIME ZONE,
+    added_by UUID REFERENCES users (pid) ON DELETE SET NULL,
+    removed_by UUID REFERENCES users (pid) ON DELETE SET NULL
+);
+
+CREATE UNIQUE INDEX animal_group_members_current_idx
+ON animal_group_members (group_id, animal_pid) WHERE left_at IS NULL;
+CREATE INDEX animal_group_members_animal_pid_idx ON animal_group_members (animal_pid);
+
+CREATE TRIGGER update_animal_groups_timestamp BEFORE UPDATE ON animal_groups
+FOR EACH ROW EXECUTE FUNCTION update_timestamp();
+
+CREATE TRIGGER audit_animal_groups_trigger
+AFTER INSERT OR UPDATE OR DELETE ON animal_groups
+FOR EACH ROW EXECUTE FUNCTION process_audit();
+
+ALTER TABLE animal_groups ENABLE ROW LEVEL SECURITY;
+CREATE POLICY animal_groups_tenant ON animal_groups
+    USING (organisation_pid = current_org_pid());
+
+ALTER TABLE animal_group_members ENABLE ROW LEVEL SECURITY;
+CREATE POLICY animal_group_members_tenant ON animal_group_members
+    USING (organisation_pid = current_org_pid());
+
+-- Ledger entries can be for a group. Deleting the group leaves its entries
+-- with the organisation.
+ALTER TABLE ledger_entries ADD CONSTRAINT ledger_entries_group_pid_fkey
+    FOREIGN KEY (group_pid) REFERENCES animal_groups (pid) ON DELETE SET NULL;
@@ -0,0 +1,15 @@
+-- Add down migration script here
+
+DELETE FROM jobs WHERE kind = 'tasks';
+DELETE FROM job_schedules WHERE kind = 'tasks';
+ALTER TABLE jobs DROP CONSTRAINT jobs_kind_check;
+ALTER TABLE jobs ADD CONSTRAINT jobs_kind_check
+    CHECK (kind IN ('summaries', 'alerts', 'purge', 'deliver'));
+ALTER TABLE job_schedules DROP CONSTRAINT job_schedules_kind_check;
+ALTER TABLE job_schedules ADD CONSTRAINT job_schedules_kind_check
+    CHECK (kind IN ('summaries', 'alerts', 'purge', 'deliver'));
+
+DROP TABLE IF EXISTS task_animals;
+DROP TABLE IF EXISTS tasks;
+DROP TABLE IF EXISTS vaccination_schedules;
+DROP TABLE IF EXISTS recurring_tasks;
@@ -0,0 +1,131 @@
+-- Add up migration script here
+
+-- Tasks repeating on a cron schedule, read as dates. Each occurrence that
+-- comes due becomes a task.
+CREATE TABLE recurring_tasks (
+    id SERIAL PRIMARY KEY,
+    pid UUID NOT NULL UNIQUE DEFAULT (uuid_generate_v4()),
+    organisation_pid UUID NOT NULL REFERENCES organisations (pid) ON DELETE CASCADE,
+    title VARCHAR(200) NOT NULL,
+    description TEXT,
+    cron VARCHAR(100) NOT NULL,
+    assignee UUID REFERENCES users (pid) ON DELETE SET NULL,
+    priority VARCHAR(20) NOT NULL DEFAULT 'medium' CHECK (priority IN ('low', 'medium', 'high')),
+    group_id INTEGER REFERENCES animal_groups (id) ON DELETE SET NULL,
+    record_kind VARCHAR(20) CHECK (record_kind IN ('health', 'weight', 'production')),
+    next_due_on DATE NOT NULL,
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_by UUID REFERENCES users (pid) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX recurring_tasks_organisation_pid_idx ON recurring_tasks (organisation_pid);
+
+-- A vaccine a group's members get every `interval_days`. Tasks are raised
+-- `lead_days` before an animal is due, or straight away for animals never
+-- vaccinated with it.
+CREATE TABLE vaccination_schedules (
+    id SERIAL PRIMARY KEY,
+    pid UUID NOT NULL UNIQUE DEFAULT (uuid_generate_v4()),
+    organisation_pid UUID NOT NULL REFERENCES organisations (pid) ON DELETE CASCADE,
+    vaccine VARCHAR(255) NOT NULL,
+    group_id INTEGER NOT NULL REFERENCES animal_groups (id) ON DELETE CASCADE,
+    interval_days INTEGER NOT NULL CHECK (interval_days > 0),
+    lead_days INTEGER NOT NULL DEFAULT 7 CHECK (lead_days >= 0),
+    assignee UUID REFERENCES users (pid) ON DELETE SET NULL,
+    priority VARCHAR(20) NOT NULL DEFAULT 'medium' CHECK (priority IN ('low', 'medium', 'high')),
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_by UUID REFERENCES users (pid) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX vaccination_schedules_vaccine_idx
+ON vaccination_schedules (group_id, LOWER(vaccine));
+
+-- Work to do by a day. Generated tasks keep the pid of the recurring task,
+-- vaccination schedule or alert they came from in `source_pid`.
+CREATE TABLE tasks (
+    id SERIAL PRIMARY KEY,
+    pid UUID NOT NULL UNIQUE DEFAULT (uuid_generate_v4()),
+    organisation_pid UUID NOT NULL REFERENCES organisations (pid) ON DELETE CASCADE,
+    title VARCHAR(200) NOT NULL,
+    description TEXT,
+    due_on DATE NOT NULL,
+    assignee UUID REFERENCES users (pid) ON DELETE SET NULL,
+    priority VARCHAR(20) NOT NULL DEFAULT 'medium' CHECK (priority IN ('low', 'medium', 'high')),
+    status VARCHAR(20) NOT NULL DEFAULT 'open'
+        CHECK (status IN ('open', 'in_progress', 'done', 'cancelled')),
+    group_id INTEGER REFERENCES animal_groups (id) ON DELETE SET NULL,
+    record_kind VARCHAR(20) CHECK (record_kind IN ('health', 'weight', 'production')),
+    source VARCHAR(20) NOT NULL DEFAULT 'manual'
+        CHECK (source IN ('manual', 'recurring', 'vaccination', 'alert')),
+    source_pid UUID,
+    notes TEXT,
+    completed_by UUID REFERENCES users (pid) ON DELETE SET NULL,
+    completed_at TIMESTAMP WITH TIME ZONE,
+    created_by UUID REFERENCES users (pid) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    CHECK ((source = 'manual') = (source_pid IS NULL))
+);
+
+CREATE INDEX tasks_due_on_idx ON tasks (organisation_pid, due_on)
+WHERE status IN ('open', 'in_progress');
+CREATE INDEX tasks_assignee_idx ON tasks (assignee);
+CREATE INDEX tasks_source_pid_idx ON tasks (source_pid);
+-- An occurrence of a recurring task, or an alert, raises one task.
+CREATE UNIQUE INDEX tasks_recurring_idx ON tasks (source_pid, due_on) WHERE source = 'recurring';
+CREATE UNIQUE INDEX tasks_alert_idx ON tasks (source_pid) WHERE source = 'alert';
+
+-- The animals a task is about, besides its group's members.
+CREATE TABLE task_animals (
+    task_id INTEGER NOT NULL REFERENCES tasks (id) ON DELETE CASCADE,
+    organisation_pid UUID NOT NULL REFERENCES organisations (pid) ON DELETE CASCADE,
+    animal_pid UUID NOT NULL REFERENCES animals (pid) ON DELETE CASCADE,
+    PRIMARY KEY (task_id, animal_pid)
+);
+
+CREATE INDEX task_animals_animal_pid_idx ON task_animals (animal_pid);
+
+CREATE TRIGGER update_recurring_tasks_timestamp BEFORE UPDATE ON recurring_tasks
+FOR EACH ROW EXECUTE FUNCTION update_timestamp();
+
+CREATE TRIGGER update_vaccination_schedules_timestamp BEFORE UPDATE ON vaccination_schedules
+FOR EACH ROW EXECUTE FUNCTION update_timestamp();
+
+CREATE TRIGGER update_tasks_timestamp BEFORE UPDATE ON tasks
+FOR EACH ROW EXECUTE FUNCTION update_timestamp();
+
+CREATE TRIGGER audit_tasks_trigger
+AFTER INSERT OR UPDATE OR DELETE ON tasks
+FOR EACH ROW EXECUTE FUNCTION process_audit();
+
+ALTER TABLE recurring_tasks ENABLE ROW LEVEL SECURITY;
+CREATE POLICY recurring_tasks_tenant ON recurring_tasks
+    USING (organisation_pid = current_org_pid());
+
+ALTER TABLE vaccination_schedules ENABLE ROW LEVEL SECURITY;
+CREATE POLICY vaccination_schedules_tenant ON vaccination_schedules
+    USING (organisation_pid = current_org_pid());
+
+ALTER TABLE tasks ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tasks_tenant ON tasks
+    USING (organisation_pid = current_org_pid());
+
+ALTER TABLE task_animals ENABLE ROW LEVEL SECURITY;
+CREATE POLICY task_animals_tenant ON task_animals
+    USING (organisation_pid = current_org_pid());
+
+-- The recurring and vaccination tasks that came due are raised hourly, so
+-- every organisation gets them soon after its midnight.
+ALTER TABLE job_schedules DROP CONSTRAINT job_schedules_kind_check;
+ALTER TABLE job_schedules ADD CONSTRAINT job_schedules_kind_check
+    CHECK (kind IN ('summaries', 'alerts', 'purge', 'deliver', 'tasks'));
+ALTER TABLE jobs DROP CONSTRAINT jobs_kind_check;
+ALTER TABLE jobs ADD CONSTRAINT jobs_kind_check
+    CHECK (kind IN ('summaries', 'alerts', 'purge', 'deliver', 'tasks'));
+
+INSERT INTO job_schedules (kind, cron, next_run_at)
+VALUES ('tasks', '0 * * * *', date_trunc('hour', NOW()) + INTERVAL '1 hour');
@@ -0,0 +1,5 @@
+-- Add down migration script here
+
+DROP TRIGGER IF EXISTS track_visual_tag_trigger ON animals;
+DROP FUNCTION IF EXISTS track_visual_tag();
+DROP TABLE IF EXISTS animal_identifiers;
@@ -0,0 +1,71 @@
+-- Add up migration script here
+
+-- Every identifier an animal has carried: visual ear tags, ISO 11784/11785
+-- RFID numbers, brands and tattoos. An identifier is current until it is
+-- replaced or removed, and still resolves to the animal afterwards.
+CREATE TABLE animal_identifiers (
+    id SERIAL PRIMARY KEY,
+    pid UUID NOT NULL UNIQUE DEFAULT (uuid_generate_v4()),
+    organisation_pid UUID NOT NULL REFERENCES organisations (pid) ON DELETE CASCADE,
+    animal_pid UUID NOT NULL REFERENCES animals (pid) ON DELETE CASCADE,
+    kind VARCHAR(10) NOT NULL CHECK (kind IN ('visual', 'rfid', 'brand', 'tattoo')),
+    value VARCHAR(50) NOT NULL,
+    attached_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    removed_at TIMESTAMP WITH TIME ZONE,
+    removal_reason VARCHAR(255),
+    added_by UUID REFERENCES users (pid) ON DELETE SET NULL,
+    removed_by UUID REFERENCES users (pid) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX animal_identifiers_value_idx
+ON animal_identifiers (organisation_pid, kind, value) WHERE removed_at IS NULL;
+CREATE UNIQUE INDEX animal_identifiers_kind_idx
+ON animal_identifiers (animal_pid, kind) WHERE removed_at IS NULL;
+CREATE INDEX animal_identifiers_lookup_idx ON animal_identifiers (organisation_pid, value);
+
+CREATE TRIGGER update_animal_identifiers_timestamp BEFORE UPDATE ON animal_identifiers
+FOR EACH ROW EXECUTE FUNCTION update_timestamp();
+
+CREATE TRIGGER audit_animal_identifiers_trigger
+AFTER INSERT OR UPDATE OR DELETE ON animal_identifiers
+FOR EACH ROW EXECUTE FUNCTION process_audit();
+
+ALTER TABLE animal_identifiers ENABLE ROW LEVEL SECURITY;
+CREATE POLICY animal_identifiers_tenant ON animal_identifiers
+    USING (organisation_pid = current_org_pid());
+
+-- Keeps an animal's current visual identifier its tag ID, retiring the one it
+-- replaces.
+CREATE OR REPLACE FUNCTION track_visual_tag()
+RETURNS TRIGGER
+SECURITY DEFINER
+AS $$
+BEGIN
+    IF TG_OP = 'UPDATE' AND OLD.tag_id = NEW.tag_id THEN
+        RETURN NULL;
+    END IF;
+
+    UPDATE animal_identifiers
+    SET removed_at = NOW(), removed_by = current_user_pid(), removal_reason = 'replaced'
+    WHERE animal_pid = NEW.pid AND kind = 'visual' AND removed_at IS NULL;
+
+    INSERT INTO animal_identifiers (organisation_pid, animal_pid, kind, value, added_by)
+    VALUES (
+        NEW.organisation_pid, NEW.pid, 'visual', NEW.tag_id,
+        COALESCE(current_user_pid(), NEW.created_by)
+    );
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER track_visual_tag_trigger
+AFTER INSERT OR UPDATE OF tag_id ON animals
+FOR EACH ROW EXECUTE FUNCTION track_visual_tag();
+
+-- Registers the tags already in use.
+INSERT INTO animal_identifiers (organisation_pid, animal_pid, kind, value, attached_at, added_by)
+SELECT a.organisation_pid, a.pid, 'visual', a.tag_id, a.created_at, a.created_by
+FROM animals a;
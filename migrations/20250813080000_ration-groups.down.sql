@@ -0,0 +1,4 @@
+-- Add down migration script here
+
+ALTER TABLE rations DROP CONSTRAINT IF EXISTS rations_scope_check;
+ALTER TABLE rations DROP COLUMN IF EXISTS group_id;
@@ -0,0 +1,7 @@
+-- Add up migration script here
+
+-- Rations can be meant for a group of animals instead of a specie or breed.
+ALTER TABLE rations
+    ADD COLUMN group_id INTEGER REFERENCES animal_groups (id) ON DELETE SET NULL;
+ALTER TABLE rations ADD CONSTRAINT rations_scope_check
+    CHECK (group_id IS NULL OR (specie_id IS NULL AND breed_id IS NULL));
@@ -0,0 +1,58 @@
+#![allow(clippy::missing_errors_doc)]
+
+//! Checks organisations' alert rules against their records, raising alerts
+//! for the anomalies found. The job scheduler runs it on a schedule.
+
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    AppContext, Result,
+    models::{ModelError, alerts::AlertRule},
+};
+
+#[derive(Debug, Clone)]
+pub struct Evaluator {
+    db: PgPool,
+}
+
+impl Evaluator {
+    #[must_use]
+    pub fn new(ctx: &AppContext) -> Self {
+        Self { db: ctx.db.clone() }
+    }
+
+    /// Evaluates every active rule and returns how many alerts were raised.
+    pub async fn evaluate_all(&self) -> Result<usize> {
+        self.evaluate(None).await
+    }
+
+    /// Evaluates the organisation's active rules and returns how many alerts
+    /// were raised.
+    pub async fn evaluate_organisation(&self, org_pid: Uuid) -> Result<usize> {
+        self.evaluate(Some(org_pid)).await
+    }
+
+    /// A rule that fails is logged and does not hold up the others.
+    async fn evaluate(&self, org_pid: Option<Uuid>) -> Result<usize> {
+        let rules = AlertRule::find_active(&self.db, org_pid).await?;
+
+        let mut raised = 0;
+
+        for rule in &rules {
+            let mut txn = self.db.begin().await.map_err(ModelError::Sqlx)?;
+
+            match rule.evaluate(&mut txn).await {
+                Ok(alerts) => {
+                    txn.commit().await.map_err(ModelError::Sqlx)?;
+                    raised += alerts.len();
+                }
+                Err(error) => {
+                    tracing::error!("Failed to evaluate alert rule {}: {error}", rule.pid);
+                }
+            }
+        }
+
+        Ok(raised)
+    }
+}
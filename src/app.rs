@@ -3,13 +3,17 @@ use std::io::IsTerminal;
 
 use crate::{
     AppContext,
+    alerts::Evaluator,
     config::{AppConfig, env::Environment},
     controllers,
     errors::Result,
+    jobs::Scheduler,
     models::{
-        animals::Animal, breeds::Breed, health::HealthRecord, orgs::Organisation,
-        production::ProductionRecord, users::User, weight::WeightRecord,
+        ModelError, animals::Animal, breeds::Breed, enums::Subscription, health::HealthRecord,
+        orgs::Organisation, production::ProductionRecord, trash::Trash, users::User,
+        weight::WeightRecord,
     },
+    webhooks::Dispatcher,
 };
 
 use axum::{
@@ -25,6 +29,7 @@ use dotenv::dotenv;
 use sqlx::PgPool;
 use tokio::net::TcpListener;
 use tower_http::cors::CorsLayer;
+use uuid::Uuid;
 
 #[derive(Debug, Parser)]
 #[command(
@@ -50,6 +55,20 @@ impl App {
         });
         let cli = Self::parse();
         let config = cli.config()?;
+
+        if cli.commands.as_ref().is_some_and(Commands::is_task) {
+            cli.init(&config).await?;
+            return Ok(());
+        }
+
+        if matches!(cli.commands, Some(Commands::Worker)) {
+            let ctx = cli.init(&config).await?;
+
+            println!("Running {} job workers", config.jobs().workers());
+            Scheduler::new(&ctx).run().await;
+            return Ok(());
+        }
+
         let (listener, router) = cli.create_app().await?;
 
         println!("Running on {}", config.server.url());
@@ -74,7 +93,16 @@ impl App {
 
         match &self.commands {
             Some(Commands::Seed) => Self::seed_data(&ctx.db).await?,
-            None => {}
+            Some(Commands::Purge) => Self::purge_trash(&ctx).await?,
+            Some(Commands::Deliver) => Self::deliver_webhooks(&ctx).await?,
+            Some(Commands::Alerts) => Self::evaluate_alerts(&ctx).await?,
+            Some(Commands::Plan { organisation, plan }) => {
+                Self::change_plan(&ctx.db, *organisation, plan).await?;
+            }
+            Some(Commands::Operator { email, revoke }) => {
+                Self::set_operator(&ctx.db, email, !revoke).await?;
+            }
+            Some(Commands::Worker) | None => {}
         }
 
         Ok(ctx)
@@ -85,6 +113,10 @@ impl App {
         let ctx = self.init(&config).await?;
         let listener: TcpListener = TcpListener::bind(config.server().address()).await?;
 
+        if config.jobs().in_process() {
+            tokio::spawn(Scheduler::new(&ctx).run());
+        }
+
         let cors_layer: CorsLayer = CorsLayer::new()
             .allow_headers([AUTHORIZATION, ACCEPT, CONTENT_TYPE])
             .allow_methods([Method::GET, Method::POST, Method::PATCH, Method::DELETE])
@@ -109,6 +141,85 @@ impl App {
         WeightRecord::seed(db, "weightRecords.json").await?;
         Ok(())
     }
+
+    /// Permanently deletes animals and records that have been in the trash
+    /// for longer than the configured retention period.
+    pub async fn purge_trash(ctx: &AppContext) -> Result<()> {
+        let retention_days = ctx.config.trash().retention_days();
+
+        let mut txn = ctx.db.begin().await?;
+        let purged = Trash::purge(&mut txn, retention_days).await?;
+        txn.commit().await?;
+
+        tracing::info!(
+            animals = purged.animals,
+            health_records = purged.health_records,
+            production_records = purged.production_records,
+            weight_records = purged.weight_records,
+            "purged trash older than {retention_days} days"
+        );
+
+        Ok(())
+    }
+}
+
+impl App {
+    /// Sends the webhook deliveries that are due once, rather than waiting for
+    /// the scheduled job.
+    pub async fn deliver_webhooks(ctx: &AppContext) -> Result<()> {
+        let delivered = Dispatcher::new(ctx)?.dispatch_due().await?;
+
+        tracing::info!("delivered {delivered} webhook events");
+
+        Ok(())
+    }
+
+    /// Evaluates the alert rules once, rather than waiting for the scheduled
+    /// job.
+    pub async fn evaluate_alerts(ctx: &AppContext) -> Result<()> {
+        let raised = Evaluator::new(ctx).evaluate_all().await?;
+
+        tracing::info!("raised {raised} alerts");
+
+        Ok(())
+    }
+
+    /// Moves an organisation to another subscription tier.
+    pub async fn change_plan(db: &PgPool, organisation: Uuid, plan: &str) -> Result<()> {
+        let subscription = Subscription::try_from(plan.to_string())?;
+
+        let organisation =
+            Organisation::change_subscription(db, organisation, &subscription).await?;
+
+        tracing::info!(
+            "{} is now on the {} plan",
+            organisation.name,
+            organisation.subscription()
+        );
+
+        Ok(())
+    }
+
+    /// Grants or revokes a user's access to the platform console.
+    pub async fn set_operator(db: &PgPool, email: &str, operator: bool) -> Result<()> {
+        let user = User::find_by_email(db, email)
+            .await?
+            .ok_or_else(|| ModelError::EntityNotFound)?;
+
+        let user = user.set_platform_operator(db, operator).await?;
+
+        tracing::info!(
+            "{} is {} a platform operator",
+            user.email,
+            if user.is_platform_operator() {
+                "now"
+            } else {
+                "no longer"
+            }
+        );
+
+        Ok(())
+    }
 }
 
 impl Default for App {
@@ -122,4 +233,37 @@ enum Commands {
     /// Seeds data to the Database
     #[clap(alias("s"))]
     Seed,
+    /// Permanently deletes trashed animals and records past their retention period
+    Purge,
+    /// Sends the webhook deliveries that are due
+    Deliver,
+    /// Evaluates the alert rules
+    Alerts,
+    /// Runs the job workers and schedules without the server
+    Worker,
+    /// Changes an organisation's subscription plan
+    Plan {
+        /// The organisation's pid
+        organisation: Uuid,
+        /// basic, business or enterprise
+        plan: String,
+    },
+    /// Grants a user access to the platform console
+    Operator {
+        /// The user's email address
+        email: String,
+        /// Revokes the access instead
+        #[arg(long)]
+        revoke: bool,
+    },
+}
+
+impl Commands {
+    /// Whether the command runs once and exits instead of starting the server.
+    const fn is_task(&self) -> bool {
+        matches!(
+            self,
+            Self::Purge | Self::Deliver | Self::Alerts | Self::Plan { .. } | Self::Operator { .. }
+        )
+    }
 }
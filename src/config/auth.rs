@@ -3,6 +3,7 @@ use std::path::PathBuf;
 
 use jsonwebtoken::{DecodingKey, EncodingKey};
 use serde::Deserialize;
+use uuid::Uuid;
 
 use super::ConfigResult;
 
@@ -17,6 +18,26 @@ pub struct RsaJwtConfig {
 pub struct AuthConfig {
     pub access: RsaJwtConfig,
     pub refresh: RsaJwtConfig,
+    /// Single sign-on through an OIDC provider. Off when absent.
+    #[serde(default)]
+    pub oidc: Option<OidcConfig>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct OidcConfig {
+    /// The provider's issuer URL, where its discovery document is served from.
+    pub issuer: String,
+    pub client_id: String,
+    pub client_secret: String,
+    /// Polaris' callback URL as registered with the provider.
+    pub redirect_uri: String,
+    /// The organisation people without an account join on their first login.
+    pub organisation: Uuid,
+    /// The role they join it with.
+    #[serde(default = "OidcConfig::default_role")]
+    pub role: String,
+    #[serde(default = "OidcConfig::default_scopes")]
+    pub scopes: Vec<String>,
 }
 
 impl AuthConfig {
@@ -29,6 +50,21 @@ impl AuthConfig {
     pub const fn refresh(&self) -> &RsaJwtConfig {
         &self.refresh
     }
+
+    #[must_use]
+    pub const fn oidc(&self) -> Option<&OidcConfig> {
+        self.oidc.as_ref()
+    }
+}
+
+impl OidcConfig {
+    fn default_role() -> String {
+        "staff".into()
+    }
+
+    fn default_scopes() -> Vec<String> {
+        vec!["openid".into(), "email".into(), "profile".into()]
+    }
 }
 
 impl RsaJwtConfig {
@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct JobsConfig {
+    /// Whether the server runs the workers itself. Turn it off when they run
+    /// separately with the `worker` command.
+    pub(crate) in_process: bool,
+    /// Jobs run at the same time by one process.
+    pub(crate) workers: usize,
+    /// Seconds an idle worker waits before looking for jobs again.
+    pub(crate) poll_interval: u64,
+    /// Attempts after which a job is given up on.
+    pub(crate) max_attempts: i32,
+    /// Seconds before the first retry. Each further retry waits twice as long.
+    pub(crate) retry_delay: i32,
+    /// Seconds a worker may hold a job before another may take it over.
+    pub(crate) lease: i32,
+    /// Days finished jobs are kept for.
+    pub(crate) retention_days: u32,
+}
+
+impl JobsConfig {
+    #[must_use]
+    pub fn in_process(&self) -> bool {
+        self.in_process
+    }
+
+    #[must_use]
+    pub fn workers(&self) -> usize {
+        self.workers
+    }
+
+    #[must_use]
+    pub fn poll_interval(&self) -> u64 {
+        self.poll_interval
+    }
+
+    #[must_use]
+    pub fn max_attempts(&self) -> i32 {
+        self.max_attempts
+    }
+
+    #[must_use]
+    pub fn retry_delay(&self) -> i32 {
+        self.retry_delay
+    }
+
+    #[must_use]
+    pub fn lease(&self) -> i32 {
+        self.lease
+    }
+
+    #[must_use]
+    pub fn retention_days(&self) -> u32 {
+        self.retention_days
+    }
+}
+
+impl Default for JobsConfig {
+    fn default() -> Self {
+        Self {
+            in_process: true,
+            workers: 2,
+            poll_interval: 5,
+            max_attempts: 5,
+            retry_delay: 60,
+            lease: 900,
+            retention_days: 14,
+        }
+    }
+}
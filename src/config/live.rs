@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct LiveConfig {
+    /// Seconds between checks that an open event stream's user may still
+    /// read it.
+    pub(crate) recheck_interval: u64,
+}
+
+impl LiveConfig {
+    #[must_use]
+    pub fn recheck_interval(&self) -> u64 {
+        self.recheck_interval
+    }
+}
+
+impl Default for LiveConfig {
+    fn default() -> Self {
+        Self {
+            recheck_interval: 60,
+        }
+    }
+}
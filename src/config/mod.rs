@@ -5,20 +5,28 @@ pub mod auth;
 pub mod db;
 pub mod env;
 pub mod error;
+pub mod jobs;
+pub mod live;
 pub mod logger;
 pub mod server;
+pub mod trash;
+pub mod webhooks;
 
 use std::path::PathBuf;
 
 use serde::Deserialize;
 
 pub use self::{
-    auth::{AuthConfig, RsaJwtConfig},
+    auth::{AuthConfig, OidcConfig, RsaJwtConfig},
     db::DatabaseConfig,
     env::Environment,
     error::{ConfigError, ConfigResult},
+    jobs::JobsConfig,
+    live::LiveConfig,
     logger::TelemetryConfig,
     server::ServerConfig,
+    trash::TrashConfig,
+    webhooks::WebhooksConfig,
 };
 
 #[derive(Debug, Clone, Deserialize)]
@@ -27,6 +35,14 @@ pub struct AppConfig {
     pub(crate) logger: TelemetryConfig,
     pub(crate) db: DatabaseConfig,
     pub(crate) auth: AuthConfig,
+    #[serde(default)]
+    pub(crate) jobs: JobsConfig,
+    #[serde(default)]
+    pub(crate) trash: TrashConfig,
+    #[serde(default)]
+    pub(crate) webhooks: WebhooksConfig,
+    #[serde(default)]
+    pub(crate) live: LiveConfig,
 }
 
 impl AppConfig {
@@ -107,6 +123,26 @@ impl AppConfig {
     pub fn auth(&self) -> &AuthConfig {
         &self.auth
     }
+
+    #[must_use]
+    pub fn jobs(&self) -> &JobsConfig {
+        &self.jobs
+    }
+
+    #[must_use]
+    pub fn trash(&self) -> &TrashConfig {
+        &self.trash
+    }
+
+    #[must_use]
+    pub fn webhooks(&self) -> &WebhooksConfig {
+        &self.webhooks
+    }
+
+    #[must_use]
+    pub fn live(&self) -> &LiveConfig {
+        &self.live
+    }
 }
 
 pub fn render_string(template: &str, locals: &serde_json::Value) -> ConfigResult<String> {
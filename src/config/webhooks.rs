@@ -0,0 +1,38 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WebhooksConfig {
+    /// Seconds to wait for a partner to answer.
+    pub(crate) timeout: u64,
+    /// Attempts after which a delivery is given up on.
+    pub(crate) max_attempts: i32,
+    /// Seconds before the first retry. Each further retry waits twice as long.
+    pub(crate) retry_delay: i32,
+}
+
+impl WebhooksConfig {
+    #[must_use]
+    pub fn timeout(&self) -> u64 {
+        self.timeout
+    }
+
+    #[must_use]
+    pub fn max_attempts(&self) -> i32 {
+        self.max_attempts
+    }
+
+    #[must_use]
+    pub fn retry_delay(&self) -> i32 {
+        self.retry_delay
+    }
+}
+
+impl Default for WebhooksConfig {
+    fn default() -> Self {
+        Self {
+            timeout: 10,
+            max_attempts: 8,
+            retry_delay: 30,
+        }
+    }
+}
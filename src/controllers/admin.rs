@@ -1,60 +1,338 @@
 use axum::{
-    Extension, Json, Router, body::Body, debug_handler, extract::State, http::StatusCode,
-    response::Response, routing::post,
+    Extension, Json, Router, debug_handler,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{delete, get, patch, post},
 };
 use serde_json::json;
+use uuid::Uuid;
 
 use crate::{
     AppContext, Error, Result,
-    middlewares::TokenClaims,
-    models::{ModelError, dto::CreateNewUser, users::User},
+    middlewares::{EntitlementLayer, PermissionLayer},
+    models::{
+        ModelError,
+        api_keys::ApiKey,
+        dto::{CreateApiKey, InviteUser, UpdateApiKey, UpdateUserRole},
+        entitlements::Entitlement,
+        invitations::{Invitation, InvitationQuery},
+        roles::{Action, Resource},
+        tenant::TenantTransaction,
+        users::{User, UserQuery},
+    },
+    views::{
+        api_key::{ApiKeyResponse, IssuedApiKeyResponse},
+        invitation::{InvitationResponse, IssuedInvitationResponse},
+        user::UserResponse,
+    },
 };
 
-/// Allows an admin to create a new user within their organisation.
-///
-/// This handler validates the admin from the JWT claims, then creates a new
-/// user tied to the same organisation, and commits the changes in a transaction.
-///
-/// # Arguments
-/// * `ctx` - Application context with DB access.
-/// * `auth` - JWT token claims of the requesting admin.
-/// * `params` - New user creation details.
-///
-/// # Returns
-/// A `201 Created` response with a confirmation message.
-///
-/// # Errors
-/// Returns an error if:
-/// * The admin is invalid or not found.
-/// * User creation or transaction commit fails.
+/// Invites someone to the admin's organisation with a role. They set their
+/// own password when accepting the invitation.
+#[debug_handler(state = AppContext)]
+async fn invite(
+    admin: User,
+    mut txn: TenantTransaction,
+    Json(params): Json<InviteUser<'static>>,
+) -> Result<Response> {
+    let issued = Invitation::create(&mut txn, admin.organisation_pid, admin.pid, &params).await?;
+
+    txn.commit().await?;
+
+    tracing::info!(
+        "{} invited {} as {}",
+        admin.email,
+        issued.invitation.email,
+        issued.invitation.role
+    );
+
+    Ok((
+        StatusCode::CREATED,
+        Json(IssuedInvitationResponse::new(&issued)),
+    )
+        .into_response())
+}
+
+/// Lists the organisation's invitations, optionally filtered by `status`.
+#[debug_handler(state = AppContext)]
+async fn list_invitations(
+    admin: User,
+    mut txn: TenantTransaction,
+    Query(conditions): Query<InvitationQuery>,
+) -> Result<Response> {
+    let invitations = Invitation::find_all(&mut *txn, admin.organisation_pid, &conditions).await?;
+
+    let invitations = invitations
+        .iter()
+        .map(InvitationResponse::new)
+        .collect::<Vec<_>>();
+
+    Ok((StatusCode::OK, Json(invitations)).into_response())
+}
+
+/// Issues a new token for an invitation that has not been accepted or
+/// revoked and gives it a fresh expiry.
+#[debug_handler(state = AppContext)]
+async fn resend_invitation(
+    admin: User,
+    mut txn: TenantTransaction,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    let issued = Invitation::resend(&mut txn, admin.organisation_pid, pid).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::OK, Json(IssuedInvitationResponse::new(&issued))).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn revoke_invitation(
+    admin: User,
+    mut txn: TenantTransaction,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    let invitation = Invitation::revoke(&mut txn, admin.organisation_pid, pid).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::OK, Json(InvitationResponse::new(&invitation))).into_response())
+}
+
+/// Keys cannot manage keys, otherwise one could grant another everything its
+/// creator may do.
+fn reject_api_key(api_key: Option<&Extension<ApiKey>>) -> Result<()> {
+    match api_key {
+        Some(_) => Err(Error::Forbidden.into()),
+        None => Ok(()),
+    }
+}
+
+/// Creates an API key for a machine integration. The key acts for the admin
+/// who created it, limited to its scopes.
+#[debug_handler(state = AppContext)]
+async fn create_api_key(
+    admin: User,
+    api_key: Option<Extension<ApiKey>>,
+    mut txn: TenantTransaction,
+    Json(params): Json<CreateApiKey<'static>>,
+) -> Result<Response> {
+    reject_api_key(api_key.as_ref())?;
+
+    let issued = ApiKey::create(&mut txn, &admin, &params).await?;
+
+    txn.commit().await?;
+
+    tracing::info!("{} created API key {}", admin.email, issued.api_key.name);
+
+    Ok((
+        StatusCode::CREATED,
+        Json(IssuedApiKeyResponse::new(&issued)),
+    )
+        .into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn list_api_keys(admin: User, mut txn: TenantTransaction) -> Result<Response> {
+    let api_keys = ApiKey::find_all(&mut *txn, admin.organisation_pid).await?;
+
+    let api_keys = api_keys.iter().map(ApiKeyResponse::new).collect::<Vec<_>>();
+
+    Ok((StatusCode::OK, Json(api_keys)).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn one_api_key(
+    admin: User,
+    mut txn: TenantTransaction,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    let api_key = ApiKey::find_by_pid(&mut *txn, admin.organisation_pid, pid).await?;
+
+    Ok((StatusCode::OK, Json(ApiKeyResponse::new(&api_key))).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn update_api_key(
+    admin: User,
+    api_key: Option<Extension<ApiKey>>,
+    mut txn: TenantTransaction,
+    Path(pid): Path<Uuid>,
+    Json(params): Json<UpdateApiKey<'static>>,
+) -> Result<Response> {
+    reject_api_key(api_key.as_ref())?;
+
+    let api_key = ApiKey::update(&mut txn, &admin, pid, &params).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::OK, Json(ApiKeyResponse::new(&api_key))).into_response())
+}
+
+/// Revokes a key. It is kept so that audit entries made with it still name
+/// it.
+#[debug_handler(state = AppContext)]
+async fn revoke_api_key(
+    admin: User,
+    mut txn: TenantTransaction,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    let api_key = ApiKey::revoke(&mut txn, admin.organisation_pid, pid).await?;
+
+    txn.commit().await?;
+
+    tracing::info!("{} revoked API key {}", admin.email, api_key.name);
+
+    Ok((StatusCode::OK, Json(ApiKeyResponse::new(&api_key))).into_response())
+}
+
+/// Lists the users of the admin's organisation, optionally filtered by
+/// `role` and `active`.
+#[debug_handler(state = AppContext)]
+async fn list_users(
+    admin: User,
+    mut txn: TenantTransaction,
+    Query(conditions): Query<UserQuery>,
+) -> Result<Response> {
+    let users = User::read_all(&mut *txn, admin.organisation_pid, &conditions).await?;
+
+    let users = users.iter().map(UserResponse::new).collect::<Vec<_>>();
+
+    Ok((StatusCode::OK, Json(users)).into_response())
+}
+
+#[debug_handler]
+async fn one_user(
+    admin: User,
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    let user = User::read_by_pid(&ctx.db, admin.organisation_pid, pid).await?;
+
+    Ok((StatusCode::OK, Json(UserResponse::new(&user))).into_response())
+}
+
+/// Fetches a user of the admin's organisation that the admin is allowed to
+/// modify. Admins cannot demote, deactivate or delete their own account.
+async fn managed_user(ctx: &AppContext, admin: &User, pid: Uuid) -> Result<User> {
+    if admin.pid == pid {
+        return Err(ModelError::Validation(
+            "You cannot change the role or status of your own account".into(),
+        )
+        .into());
+    }
+
+    User::read_by_pid(&ctx.db, admin.organisation_pid, pid)
+        .await
+        .map_err(Into::into)
+}
 
 #[debug_handler]
-async fn add_user(
+async fn update_role(
+    admin: User,
     State(ctx): State<AppContext>,
-    Extension(auth): Extension<TokenClaims>,
-    Json(params): Json<CreateNewUser<'static>>,
+    Path(pid): Path<Uuid>,
+    Json(params): Json<UpdateUserRole<'static>>,
 ) -> Result<Response> {
-    let mut txn = ctx.db.begin().await.map_err(ModelError::Sqlx)?;
+    let user = managed_user(&ctx, &admin, pid).await?;
 
-    let admin = User::find_by_claims_key(&mut *txn, &auth.sub)
-        .await?
-        .ok_or_else(|| Error::InvalidToken)?;
+    let user = user.update_role(&ctx.db, &params).await?;
 
-    let user = User::admin_create_user(&mut *txn, admin.organisation_pid, &params).await?;
+    Ok((StatusCode::OK, Json(UserResponse::new(&user))).into_response())
+}
 
-    txn.commit().await?;
+#[debug_handler]
+async fn deactivate(
+    admin: User,
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    let user = managed_user(&ctx, &admin, pid).await?;
+
+    let user = user.set_active(&ctx.db, false).await?;
+
+    Ok((StatusCode::OK, Json(UserResponse::new(&user))).into_response())
+}
+
+#[debug_handler]
+async fn reactivate(
+    admin: User,
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    let user = managed_user(&ctx, &admin, pid).await?;
+
+    let user = user.set_active(&ctx.db, true).await?;
+
+    Ok((StatusCode::OK, Json(UserResponse::new(&user))).into_response())
+}
+
+#[debug_handler]
+async fn reset_password(
+    admin: User,
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    let user = User::read_by_pid(&ctx.db, admin.organisation_pid, pid).await?;
+
+    let user = user.require_password_reset(&ctx.db).await?;
+
+    Ok((StatusCode::OK, Json(UserResponse::new(&user))).into_response())
+}
 
-    // email the new user how to login and change his password immediately.
-    Ok(
-        Response::builder()
-        .status(StatusCode::CREATED)
-        .body(Body::new(json!({ 
-                "message": format!("Successfully created new user {} {} with role {}", &user.first_name, &user.last_name, &user.role) }).to_string()))
-        ?)
+#[debug_handler]
+async fn remove_user(
+    admin: User,
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    let user = managed_user(&ctx, &admin, pid).await?;
+
+    User::delete_by_pid(&ctx.db, admin.organisation_pid, user.pid).await?;
+
+    Ok((StatusCode::NO_CONTENT, Json(json!({}))).into_response())
 }
 
 pub fn route(ctx: AppContext) -> Router {
+    let can_manage = PermissionLayer::new(Resource::Users, Action::Manage);
+    let has_seat = EntitlementLayer::new(&ctx, Entitlement::User);
+    let has_api_access = EntitlementLayer::new(&ctx, Entitlement::ApiAccess);
+
     Router::new()
-        .route("/add-user", post(add_user))
+        .route("/invitations", post(invite).layer(has_seat.clone()))
+        .route("/invitations", get(list_invitations))
+        .route("/invitations/{pid}", delete(revoke_invitation))
+        .route(
+            "/invitations/{pid}/resend",
+            post(resend_invitation).layer(has_seat.clone()),
+        )
+        .route(
+            "/api-keys",
+            post(create_api_key).layer(has_api_access.clone()),
+        )
+        .route(
+            "/api-keys",
+            get(list_api_keys).layer(has_api_access.clone()),
+        )
+        .route(
+            "/api-keys/{pid}",
+            get(one_api_key).layer(has_api_access.clone()),
+        )
+        .route(
+            "/api-keys/{pid}",
+            patch(update_api_key).layer(has_api_access.clone()),
+        )
+        .route(
+            "/api-keys/{pid}",
+            delete(revoke_api_key).layer(has_api_access),
+        )
+        .route("/users", get(list_users))
+        .route("/users/{pid}", get(one_user))
+        .route("/users/{pid}", delete(remove_user))
+        .route("/users/{pid}/role", patch(update_role))
+        .route("/users/{pid}/deactivate", patch(deactivate))
+        .route("/users/{pid}/reactivate", patch(reactivate).layer(has_seat))
+        .route("/users/{pid}/reset-password", post(reset_password))
+        .route_layer(can_manage)
         .with_state(ctx)
 }
@@ -0,0 +1,134 @@
+use axum::{
+    Json, Router, debug_handler,
+    extract::{Path, Query},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{delete, get, patch, post},
+};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{
+    AppContext, Result,
+    middlewares::PermissionLayer,
+    models::{
+        alerts::{Alert, AlertRule},
+        dto::{AlertQuery, CreateAlertRule, UpdateAlertRule},
+        roles::{Action, Resource},
+        tenant::TenantTransaction,
+        users::User,
+    },
+    views::alert::{AlertResponse, AlertRuleResponse},
+};
+
+#[debug_handler(state = AppContext)]
+async fn list(
+    user: User,
+    mut txn: TenantTransaction,
+    Query(params): Query<AlertQuery>,
+) -> Result<Response> {
+    let alerts = Alert::find_all(&mut *txn, user.organisation_pid, &params).await?;
+
+    let alerts = alerts.iter().map(AlertResponse::new).collect::<Vec<_>>();
+
+    Ok((StatusCode::OK, Json(alerts)).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn one(user: User, mut txn: TenantTransaction, Path(pid): Path<Uuid>) -> Result<Response> {
+    let alert = Alert::find_by_pid(&mut *txn, user.organisation_pid, pid).await?;
+
+    Ok((StatusCode::OK, Json(AlertResponse::new(&alert))).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn acknowledge(
+    user: User,
+    mut txn: TenantTransaction,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    let alert = Alert::acknowledge(&mut txn, &user, pid).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::OK, Json(AlertResponse::new(&alert))).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn resolve(
+    user: User,
+    mut txn: TenantTransaction,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    let alert = Alert::resolve(&mut txn, &user, pid).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::OK, Json(AlertResponse::new(&alert))).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn create_rule(
+    user: User,
+    mut txn: TenantTransaction,
+    Json(params): Json<CreateAlertRule<'static>>,
+) -> Result<Response> {
+    let rule = AlertRule::create(&mut *txn, &user, &params).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::CREATED, Json(AlertRuleResponse::new(&rule))).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn list_rules(user: User, mut txn: TenantTransaction) -> Result<Response> {
+    let rules = AlertRule::find_all(&mut *txn, user.organisation_pid).await?;
+
+    let rules = rules.iter().map(AlertRuleResponse::new).collect::<Vec<_>>();
+
+    Ok((StatusCode::OK, Json(rules)).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn update_rule(
+    user: User,
+    mut txn: TenantTransaction,
+    Path(pid): Path<Uuid>,
+    Json(params): Json<UpdateAlertRule<'static>>,
+) -> Result<Response> {
+    let rule = AlertRule::update(&mut txn, user.organisation_pid, pid, &params).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::OK, Json(AlertRuleResponse::new(&rule))).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn remove_rule(
+    user: User,
+    mut txn: TenantTransaction,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    AlertRule::delete_by_pid(&mut *txn, user.organisation_pid, pid).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::NO_CONTENT, Json(json!({}))).into_response())
+}
+
+pub fn router(ctx: AppContext) -> Router {
+    let can_read = PermissionLayer::new(Resource::Reports, Action::Read);
+    let can_handle = PermissionLayer::new(Resource::HealthRecords, Action::Write);
+    let can_manage = PermissionLayer::new(Resource::Organisation, Action::Manage);
+
+    Router::new()
+        .route("/", get(list).layer(can_read))
+        .route("/rules", get(list_rules).layer(can_read))
+        .route("/rules", post(create_rule).layer(can_manage))
+        .route("/rules/{pid}", patch(update_rule).layer(can_manage))
+        .route("/rules/{pid}", delete(remove_rule).layer(can_manage))
+        .route("/{pid}", get(one).layer(can_read))
+        .route("/{pid}/acknowledge", post(acknowledge).layer(can_handle))
+        .route("/{pid}/resolve", post(resolve).layer(can_handle))
+        .with_state(ctx)
+}
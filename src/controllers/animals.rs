@@ -12,61 +12,84 @@ use uuid::Uuid;
 
 use crate::{
     AppContext, Result,
+    middlewares::{EntitlementLayer, PermissionLayer},
     models::{
         animals::{Animal, AnimalQuery},
-        dto::{LinkOffspring, RegisterAnimal, UpdateAnimal},
+        audit::AuditLog,
+        dto::{
+            LinkOffspring, NewIdentifier, RegisterAnimal, RemoveIdentifier, ScanQuery, UpdateAnimal,
+        },
+        entitlements::Entitlement,
+        identifiers::AnimalIdentifier,
+        roles::{Action, Resource},
+        settings::OrganisationSettings,
+        tenant::TenantTransaction,
         users::User,
     },
 };
 
-#[debug_handler]
+#[debug_handler(state = AppContext)]
 async fn list(
     user: User,
-    State(ctx): State<AppContext>,
+    mut txn: TenantTransaction,
     Query(conditions): Query<AnimalQuery>,
 ) -> Result<Response> {
-    let models = Animal::find_all(&ctx.db, user.organisation_pid, &conditions).await?;
+    let models = Animal::find_all(&mut *txn, user.organisation_pid, &conditions).await?;
 
     Ok((StatusCode::OK, Json(models)).into_response())
 }
 
-#[debug_handler]
-async fn one(user: User, State(ctx): State<AppContext>, Path(id): Path<Uuid>) -> Result<Response> {
-    let model = Animal::find_by_id(&ctx.db, user.organisation_pid, id).await?;
+#[debug_handler(state = AppContext)]
+async fn one(user: User, mut txn: TenantTransaction, Path(id): Path<Uuid>) -> Result<Response> {
+    let model = Animal::find_by_id(&mut *txn, user.organisation_pid, id).await?;
 
     Ok((StatusCode::OK, Json(model)).into_response())
 }
 
-#[debug_handler]
+#[debug_handler(state = AppContext)]
 async fn add(
     user: User,
-    State(ctx): State<AppContext>,
+    mut txn: TenantTransaction,
     Json(params): Json<RegisterAnimal<'static>>,
 ) -> Result<Response> {
-    let model = Animal::register(&ctx.db, user.organisation_pid, user.pid, &params).await?;
+    OrganisationSettings::find(&mut *txn, user.organisation_pid)
+        .await?
+        .tag_id
+        .check(&params.tag_id)?;
+
+    let model = Animal::register(&mut txn, user.organisation_pid, user.pid, &params).await?;
+
+    txn.commit().await?;
 
     Ok((StatusCode::CREATED, Json(model)).into_response())
 }
 
-#[debug_handler]
-async fn remove(
-    user: User,
-    State(ctx): State<AppContext>,
-    Path(id): Path<Uuid>,
-) -> Result<Response> {
-    let _query = Animal::delete_by_id(&ctx.db, user.organisation_pid, id).await?;
+#[debug_handler(state = AppContext)]
+async fn remove(user: User, mut txn: TenantTransaction, Path(id): Path<Uuid>) -> Result<Response> {
+    let _query = Animal::delete_by_id(&mut *txn, user.organisation_pid, id, user.pid).await?;
+
+    txn.commit().await?;
 
     Ok((StatusCode::NO_CONTENT, Json(json!({}))).into_response())
 }
 
-#[debug_handler]
+#[debug_handler(state = AppContext)]
 async fn update(
     user: User,
-    State(ctx): State<AppContext>,
+    mut txn: TenantTransaction,
     Path(id): Path<Uuid>,
     Json(params): Json<UpdateAnimal<'static>>,
 ) -> Result<Response> {
-    let model = Animal::update_by_id(&ctx.db, &params, user.organisation_pid, id).await?;
+    if let Some(tag_id) = &params.tag_id {
+        OrganisationSettings::find(&mut *txn, user.organisation_pid)
+            .await?
+            .tag_id
+            .check(tag_id)?;
+    }
+
+    let model = Animal::update_by_id(&mut txn, &params, user.organisation_pid, id).await?;
+
+    txn.commit().await?;
 
     Ok((StatusCode::CREATED, Json(model)).into_response())
 }
@@ -82,25 +105,131 @@ async fn get_by_tag_id(
     Ok((StatusCode::OK, Json(model)).into_response())
 }
 
-#[debug_handler]
+/// The animal a reader's scan identifies, by a current or former identifier.
+#[debug_handler(state = AppContext)]
+async fn scan(
+    user: User,
+    mut txn: TenantTransaction,
+    Query(params): Query<ScanQuery>,
+) -> Result<Response> {
+    let found = AnimalIdentifier::scan(&mut txn, user.organisation_pid, &params.raw).await?;
+
+    Ok((StatusCode::OK, Json(found)).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn identifiers(
+    user: User,
+    mut txn: TenantTransaction,
+    Path(id): Path<Uuid>,
+) -> Result<Response> {
+    let animal = Animal::find_by_id(&mut *txn, user.organisation_pid, id).await?;
+
+    let identifiers =
+        AnimalIdentifier::find_by_animal(&mut *txn, user.organisation_pid, animal.pid).await?;
+
+    Ok((StatusCode::OK, Json(identifiers)).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn attach_identifier(
+    user: User,
+    mut txn: TenantTransaction,
+    Path(id): Path<Uuid>,
+    Json(params): Json<NewIdentifier<'static>>,
+) -> Result<Response> {
+    if params.kind == "visual" {
+        OrganisationSettings::find(&mut *txn, user.organisation_pid)
+            .await?
+            .tag_id
+            .check(&params.value)?;
+    }
+
+    let identifier =
+        AnimalIdentifier::attach(&mut txn, user.organisation_pid, user.pid, id, &params).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::CREATED, Json(identifier)).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn remove_identifier(
+    user: User,
+    mut txn: TenantTransaction,
+    Path((id, pid)): Path<(Uuid, Uuid)>,
+    Query(params): Query<RemoveIdentifier>,
+) -> Result<Response> {
+    let identifier =
+        AnimalIdentifier::remove(&mut txn, user.organisation_pid, user.pid, id, pid, &params)
+            .await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::OK, Json(identifier)).into_response())
+}
+
+#[debug_handler(state = AppContext)]
 async fn link_offspring(
     user: User,
-    State(ctx): State<AppContext>,
+    mut txn: TenantTransaction,
     Json(params): Json<LinkOffspring<'static>>,
 ) -> Result<Response> {
-    let model = Animal::link_offspring(&ctx.db, user.organisation_pid, &params).await?;
+    let model = Animal::link_offspring(&mut txn, user.organisation_pid, &params).await?;
+
+    txn.commit().await?;
 
     Ok((StatusCode::CREATED, Json(model)).into_response())
 }
 
+#[debug_handler(state = AppContext)]
+async fn restore(user: User, mut txn: TenantTransaction, Path(id): Path<Uuid>) -> Result<Response> {
+    let model = Animal::restore_by_id(&mut *txn, user.organisation_pid, id).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::OK, Json(model)).into_response())
+}
+
+/// The change history of an animal and its health, production and weight
+/// records.
+#[debug_handler(state = AppContext)]
+async fn history(user: User, mut txn: TenantTransaction, Path(id): Path<Uuid>) -> Result<Response> {
+    let animal = Animal::find_by_id(&mut *txn, user.organisation_pid, id).await?;
+
+    let logs = AuditLog::find_by_animal(&mut *txn, user.organisation_pid, animal.pid).await?;
+
+    Ok((StatusCode::OK, Json(logs)).into_response())
+}
+
 pub fn router(ctx: AppContext) -> Router {
+    let can_read = PermissionLayer::new(Resource::Animals, Action::Read);
+    let can_write = PermissionLayer::new(Resource::Animals, Action::Write);
+    let can_delete = PermissionLayer::new(Resource::Animals, Action::Delete);
+    let has_room = EntitlementLayer::new(&ctx, Entitlement::Animal);
+
     Router::new()
-        .route("/", get(list))
-        .route("/", post(add))
-        .route("/{id}", get(one))
-        .route("/{id}", delete(remove))
-        .route("/{id}", patch(update))
-        .route("/tag-id/{id}", get(get_by_tag_id))
-        .route("/link-offspring", patch(link_offspring))
+        .route("/", get(list).layer(can_read))
+        .route("/", post(add).layer(has_room.clone()).layer(can_write))
+        .route("/{id}", get(one).layer(can_read))
+        .route("/{id}", delete(remove).layer(can_delete))
+        .route(
+            "/{id}/restore",
+            patch(restore).layer(has_room).layer(can_delete),
+        )
+        .route("/{id}", patch(update).layer(can_write))
+        .route("/{id}/history", get(history).layer(can_read))
+        .route("/{id}/identifiers", get(identifiers).layer(can_read))
+        .route(
+            "/{id}/identifiers",
+            post(attach_identifier).layer(can_write),
+        )
+        .route(
+            "/{id}/identifiers/{pid}",
+            delete(remove_identifier).layer(can_write),
+        )
+        .route("/tag-id/{id}", get(get_by_tag_id).layer(can_read))
+        .route("/scan", get(scan).layer(can_read))
+        .route("/link-offspring", patch(link_offspring).layer(can_write))
         .with_state(ctx)
 }
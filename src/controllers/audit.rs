@@ -0,0 +1,38 @@
+use axum::{
+    Json, Router, debug_handler,
+    extract::Query,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+};
+
+use crate::{
+    AppContext, Result,
+    middlewares::PermissionLayer,
+    models::{
+        audit::{AuditLog, AuditQuery},
+        roles::{Action, Resource},
+        tenant::TenantTransaction,
+        users::User,
+    },
+};
+
+/// Lists the organisation's audit entries, newest first.
+#[debug_handler(state = AppContext)]
+async fn all(
+    user: User,
+    mut txn: TenantTransaction,
+    Query(conditions): Query<AuditQuery>,
+) -> Result<Response> {
+    let logs = AuditLog::find_all(&mut *txn, user.organisation_pid, &conditions).await?;
+
+    Ok((StatusCode::OK, Json(logs)).into_response())
+}
+
+pub fn router(ctx: AppContext) -> Router {
+    let can_read = PermissionLayer::new(Resource::AuditLogs, Action::Read);
+
+    Router::new()
+        .route("/", get(all).layer(can_read))
+        .with_state(ctx)
+}
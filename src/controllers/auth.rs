@@ -4,7 +4,7 @@ use axum::{
     Extension, Json, Router,
     body::Body,
     debug_handler,
-    extract::State,
+    extract::{Query, State},
     http::{StatusCode, header::SET_COOKIE},
     response::{IntoResponse, Redirect, Response},
     routing::{get, post},
@@ -15,7 +15,17 @@ use serde_json::json;
 use crate::{
     AppContext, Error, Result,
     middlewares::{AuthLayer, TokenClaims},
-    models::{ModelError, dto::*, orgs::*, users::*},
+    models::{
+        ModelError,
+        dto::*,
+        entitlements::Entitlement,
+        identities::{OidcLogin, UserIdentity},
+        invitations::Invitation,
+        memberships::Membership,
+        orgs::*,
+        users::*,
+    },
+    oidc::Provider,
     views::user::*,
 };
 
@@ -72,6 +82,8 @@ async fn register(
 /// # Errors
 /// Returns:
 /// * `WrongCredentials` if email or password is invalid.
+/// * `AccountDisabled` if the user has been deactivated, even with a password
+///   change pending.
 /// * `ModelError` or other internal errors for DB or token generation issues.
 
 #[debug_handler]
@@ -82,24 +94,140 @@ async fn login(
     let validator = Validator::new(&params);
     let params = validator.validate()?;
 
-    let user = User::find_by_email(&ctx.db, &params.email)
+    let user = User::find_for_login(&ctx.db, &params.email)
         .await?
         .ok_or_else(|| Error::WrongCredentials)?;
 
+    let is_password_valid = user.validate_password(params.password.trim())?;
+
+    if !is_password_valid {
+        return Err(Error::WrongCredentials.into());
+    }
+
+    if !user.is_active {
+        return Err(Error::AccountDisabled.into());
+    }
+
     if user.password_change_required {
         return Ok(Redirect::temporary("/auth/update-password").into_response());
     }
 
-    let is_password_valid = user.validate_password(params.password.trim())?;
+    let organisation = Organisation::find_by_pid(&ctx.db, user.organisation_pid).await?;
 
-    if !is_password_valid {
-        return Err(Error::WrongCredentials.into());
+    if organisation.is_suspended() && !user.is_platform_operator {
+        return Err(Error::OrganisationSuspended.into());
     }
 
-    let access_token = ctx.auth.access.jwt(&user)?;
-    let refresh_token = ctx.auth.refresh.jwt(&user)?;
+    issue_tokens(&ctx, &user)
+}
 
-    let body = LoginResponse::new(&user);
+/// Starts a single sign-on login by sending the person to the identity
+/// provider.
+///
+/// # Arguments
+/// * `ctx` - Application context with DB access and the provider's settings.
+///
+/// # Returns
+/// A redirect to the provider's authorization endpoint, carrying the login's
+/// state, nonce and PKCE challenge.
+///
+/// # Errors
+/// Returns:
+/// * `EntityNotFound` if single sign-on is not configured.
+/// * `Reqwest` if the provider's discovery document cannot be fetched.
+#[debug_handler]
+async fn oidc_login(State(ctx): State<AppContext>) -> Result<Response> {
+    let config = ctx
+        .config
+        .auth()
+        .oidc()
+        .ok_or_else(|| ModelError::EntityNotFound)?;
+
+    let provider = Provider::discover(config).await?;
+    let login = OidcLogin::create(&ctx.db).await?;
+
+    Ok(Redirect::to(provider.authorization_url(&login)?.as_str()).into_response())
+}
+
+/// Finishes a single sign-on login when the identity provider sends the
+/// person back.
+///
+/// The code is traded for an ID token, whose account logs in as the user it
+/// is linked to. People logging in for the first time are linked by verified
+/// email, or given an account in the configured organisation.
+///
+/// # Arguments
+/// * `ctx` - Application context with DB access and the provider's settings.
+/// * `params` - The state and code, or error, sent back by the provider.
+///
+/// # Returns
+/// A `200 OK` response with tokens, like at login.
+///
+/// # Errors
+/// Returns:
+/// * `EntityNotFound` if single sign-on is not configured.
+/// * `Unauthorised` if the person did not log in at the provider.
+/// * `InvalidToken` if the state is unknown or expired, or the code or ID
+///   token is rejected.
+/// * `AccountDisabled` or `OrganisationSuspended` as at login.
+#[debug_handler]
+async fn oidc_callback(
+    State(ctx): State<AppContext>,
+    Query(params): Query<OidcCallback>,
+) -> Result<Response> {
+    let config = ctx
+        .config
+        .auth()
+        .oidc()
+        .ok_or_else(|| ModelError::EntityNotFound)?;
+
+    let login = OidcLogin::take(&ctx.db, &params.state)
+        .await?
+        .ok_or_else(|| Error::InvalidToken)?;
+
+    if let Some(error) = &params.error {
+        tracing::info!(
+            "Identity provider refused login: {error} {}",
+            params.error_description.as_deref().unwrap_or_default()
+        );
+        return Err(Error::Unauthorised.into());
+    }
+
+    let code = params.code.as_deref().ok_or_else(|| Error::InvalidToken)?;
+
+    let provider = Provider::discover(config).await?;
+    let claims = provider.exchange(code, &login).await?;
+
+    let mut txn = ctx.db.begin().await?;
+
+    let user = UserIdentity::sign_in(&mut txn, config, &claims).await?;
+
+    txn.commit().await?;
+
+    let user = User::find_for_login(&ctx.db, &user.email)
+        .await?
+        .ok_or_else(|| Error::WrongCredentials)?;
+
+    if !user.is_active {
+        return Err(Error::AccountDisabled.into());
+    }
+
+    let organisation = Organisation::find_by_pid(&ctx.db, user.organisation_pid).await?;
+
+    if organisation.is_suspended() && !user.is_platform_operator {
+        return Err(Error::OrganisationSuspended.into());
+    }
+
+    issue_tokens(&ctx, &user)
+}
+
+/// Responds with fresh access and refresh tokens for `user`, in the
+/// authorization header, cookies and a login body.
+fn issue_tokens(ctx: &AppContext, user: &User) -> Result<Response> {
+    let access_token = ctx.auth.access.jwt(user)?;
+    let refresh_token = ctx.auth.refresh.jwt(user)?;
+
+    let body = LoginResponse::new(user);
 
     let access_cookie = Cookie::build(("accessToken", &access_token))
         .path("/")
@@ -134,33 +262,29 @@ async fn login(
 
 /// Logs out the authenticated user and invalidates their session.
 ///
-/// This handler finds the user based on token claims, logs the logout event,
+/// This handler logs the logout event of the user loaded by the `AuthLayer`
 /// and clears the access and refresh cookies.
 ///
 /// # Arguments
 /// * `ctx` - Application context with DB and services.
-/// * `auth` - Decoded JWT token claims.
+/// * `user` - The authenticated user.
 ///
 /// # Returns
 /// A `200 OK` response with a logout success message.
 ///
 /// # Errors
 /// Returns an error if:
-/// * User lookup or update fails.
+/// * The user update fails.
 /// * Transaction commit fails.
 
 #[debug_handler]
 async fn logout(
     State(ctx): State<AppContext>,
-    Extension(auth): Extension<TokenClaims>,
+    Extension(user): Extension<User>,
 ) -> Result<Response> {
     let mut txn = ctx.db.begin().await.map_err(ModelError::Sqlx)?;
 
-    let mut user = User::find_by_claims_key(&mut *txn, &auth.sub)
-        .await?
-        .ok_or_else(|| ModelError::EntityNotFound)?;
-
-    user = user.record_logout(&mut *txn).await?;
+    let user = user.record_logout(&mut *txn).await?;
 
     tracing::info!("User {} logged out at {:?}", &user.email, user.last_login);
 
@@ -196,45 +320,106 @@ async fn logout(
 
 /// Retrieves information about the currently authenticated user.
 ///
-/// This handler extracts the user from token claims and returns public user info.
+/// This handler returns public info of the user loaded by the `AuthLayer`.
 ///
 /// # Arguments
-/// * `ctx` - App context for DB access.
-/// * `auth` - JWT token claims used to identify the user.
+/// * `user` - The authenticated user.
 ///
 /// # Returns
 /// A `200 OK` response containing the current user's data.
 ///
 /// # Errors
-/// Returns `InvalidToken` if the user cannot be found.
+/// This handler does not fail on its own, the `AuthLayer` rejects unknown users.
 
 #[debug_handler]
-async fn current(
+async fn current(Extension(user): Extension<User>) -> Result<Response> {
+    Ok((StatusCode::OK, Json(CurrentUser::new(&user))).into_response())
+}
+
+/// Lists the organisations the current user belongs to.
+///
+/// # Arguments
+/// * `ctx` - App context for DB access.
+/// * `user` - The authenticated user.
+///
+/// # Returns
+/// A `200 OK` response with the user's memberships, home organisation first.
+///
+/// # Errors
+/// Returns an error if the DB query fails.
+#[debug_handler]
+async fn organisations(
     State(ctx): State<AppContext>,
-    Extension(auth): Extension<TokenClaims>,
+    Extension(user): Extension<User>,
 ) -> Result<Response> {
-    let user = User::find_by_claims_key(&ctx.db, &auth.sub)
+    let memberships = Membership::find_by_user(&ctx.db, user.pid).await?;
+
+    Ok((StatusCode::OK, Json(memberships)).into_response())
+}
+
+/// Re-issues the user's tokens for another organisation they belong to.
+///
+/// # Arguments
+/// * `ctx` - App context for DB access.
+/// * `user` - The authenticated user.
+/// * `params` - The organisation to switch to.
+///
+/// # Returns
+/// A `200 OK` response with tokens for the organisation, like at login.
+///
+/// # Errors
+/// Returns:
+/// * `Forbidden` if the user is not a member of the organisation, or the
+///   request was made with an API key or an impersonation token.
+/// * `AccountDisabled` if their membership was deactivated.
+/// * `OrganisationSuspended` if the organisation is suspended.
+#[debug_handler]
+async fn switch_organisation(
+    State(ctx): State<AppContext>,
+    claims: Option<Extension<TokenClaims>>,
+    Extension(user): Extension<User>,
+    Json(params): Json<SwitchOrganisation>,
+) -> Result<Response> {
+    // API keys belong to one organisation, and impersonation tokens are tied
+    // to the organisation being supported.
+    if claims.is_none_or(|Extension(claims)| claims.impersonator.is_some()) {
+        return Err(Error::Forbidden.into());
+    }
+
+    let member = User::find_member(&ctx.db, user.pid, Some(params.organisation))
         .await?
-        .ok_or_else(|| Error::InvalidToken)?;
+        .ok_or_else(|| Error::Forbidden)?;
 
-    Ok((StatusCode::OK, Json(CurrentUser::new(&user))).into_response())
+    if !member.is_active {
+        return Err(Error::AccountDisabled.into());
+    }
+
+    let organisation = Organisation::find_by_pid(&ctx.db, member.organisation_pid).await?;
+
+    if organisation.is_suspended() && !member.is_platform_operator {
+        return Err(Error::OrganisationSuspended.into());
+    }
+
+    tracing::info!("{} switched to {}", member.email, organisation.name);
+
+    issue_tokens(&ctx, &member)
 }
 
-/// Updates the password for a user who was created by a super-user.
+/// Updates the password of a user whose admin required them to change it.
 ///
-/// This is typically used for first-time login scenarios where the password
-/// needs to be changed immediately.
+/// The current password must be given, since the user has no token yet.
 ///
 /// # Arguments
 /// * `ctx` - Application context with DB access.
-/// * `params` - Password update payload containing email and new password.
+/// * `params` - Password update payload containing email, current and new password.
 ///
 /// # Returns
 /// A redirect to the login page upon success.
 ///
 /// # Errors
 /// Returns an error if:
-/// * The user cannot be found.
+/// * The user cannot be found or the current password is wrong.
+/// * The user has been deactivated.
 /// * The password update or DB commit fails.
 #[debug_handler]
 async fn update_password(
@@ -243,10 +428,18 @@ async fn update_password(
 ) -> Result<Response> {
     let mut txn = ctx.db.begin().await?;
 
-    let mut user = User::find_by_email(&mut *txn, &params.email)
+    let mut user = User::find_for_login(&mut *txn, &params.email)
         .await?
         .ok_or_else(|| Error::Forbidden)?;
 
+    if !user.validate_password(params.current_password.trim())? {
+        return Err(Error::WrongCredentials.into());
+    }
+
+    if !user.is_active {
+        return Err(Error::AccountDisabled.into());
+    }
+
     user = user.update_password(&mut *txn, &params).await?;
 
     tracing::info!(
@@ -261,12 +454,66 @@ async fn update_password(
     Ok(Redirect::to("/auth/login").into_response())
 }
 
+/// Accepts an invitation and creates the invitee's account.
+///
+/// The invitee chooses their own password, so they can log in straight away.
+///
+/// # Arguments
+/// * `ctx` - Application context with DB access.
+/// * `params` - The invitation token and the new password.
+///
+/// # Returns
+/// A `201 Created` response with the new user.
+///
+/// # Errors
+/// Returns an error if:
+/// * The token is invalid, expired, revoked or already used.
+/// * The organisation has no room for another user.
+/// * User creation or DB commit fails.
+#[debug_handler]
+async fn accept_invitation(
+    State(ctx): State<AppContext>,
+    Json(params): Json<AcceptInvitation<'static>>,
+) -> Result<Response> {
+    let mut txn = ctx.db.begin().await?;
+
+    let invitation = Invitation::find_by_token(&mut *txn, &params.token).await?;
+    let organisation = Organisation::find_by_pid(&mut *txn, invitation.organisation_pid).await?;
+
+    Entitlement::User
+        .check(&mut *txn, organisation.pid, &organisation.subscription())
+        .await?;
+
+    let user = Invitation::accept(&mut txn, &params).await?;
+
+    txn.commit().await?;
+
+    tracing::info!(
+        "{} accepted their invitation to {}",
+        user.email,
+        organisation.name
+    );
+
+    Ok((StatusCode::CREATED, Json(UserResponse::new(&user))).into_response())
+}
+
 pub fn router(ctx: AppContext) -> Router {
     Router::new()
         .route("/register", post(register))
         .route("/login", post(login))
+        .route("/oidc/login", get(oidc_login))
+        .route("/oidc/callback", get(oidc_callback))
         .route("/logout", post(logout).layer(AuthLayer::new(&ctx)))
         .route("/current", get(current).layer(AuthLayer::new(&ctx)))
+        .route(
+            "/organisations",
+            get(organisations).layer(AuthLayer::new(&ctx)),
+        )
+        .route(
+            "/switch-organisation",
+            post(switch_organisation).layer(AuthLayer::new(&ctx)),
+        )
         .route("/update-password", post(update_password))
+        .route("/accept-invitation", post(accept_invitation))
         .with_state(ctx)
 }
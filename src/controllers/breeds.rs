@@ -1,5 +1,5 @@
 use axum::{
-    Extension, Json, Router,
+    Json, Router,
     body::Body,
     debug_handler,
     extract::{Path, Query, State},
@@ -11,11 +11,13 @@ use serde_json::json;
 
 use crate::{
     AppContext, Error, Result,
-    middlewares::TokenClaims,
+    middlewares::PermissionLayer,
     models::{
         breeds::{Breed, BreedQuery},
         dto::{RegisterBreed, UpdateBreed},
+        roles::{Action, Resource},
         species::Specie,
+        tenant::TenantTransaction,
         users::User,
     },
     views::animals::BreedResponse,
@@ -24,7 +26,6 @@ use crate::{
 #[debug_handler]
 async fn all(
     State(ctx): State<AppContext>,
-    Extension(_auth): Extension<TokenClaims>,
     user: User,
     Query(conditions): Query<BreedQuery>,
 ) -> Result<Response> {
@@ -59,14 +60,12 @@ async fn one(State(ctx): State<AppContext>, user: User, Path(id): Path<i32>) ->
         .into_response())
 }
 
-#[debug_handler]
+#[debug_handler(state = AppContext)]
 async fn add(
-    State(ctx): State<AppContext>,
+    mut tx: TenantTransaction,
     user: User,
     Json(params): Json<RegisterBreed<'static>>,
 ) -> Result<Response> {
-    let mut tx = ctx.db.begin().await?;
-
     let specie = Specie::find_by_name(&mut *tx, &params.specie).await?;
     let breed = Breed::create(&mut *tx, user.organisation_pid, &params, specie.id).await?;
 
@@ -81,14 +80,8 @@ async fn add(
     Ok(response)
 }
 
-#[debug_handler]
-async fn remove(
-    State(ctx): State<AppContext>,
-    user: User,
-    Path(id): Path<i32>,
-) -> Result<Response> {
-    let mut tx = ctx.db.begin().await?;
-
+#[debug_handler(state = AppContext)]
+async fn remove(mut tx: TenantTransaction, user: User, Path(id): Path<i32>) -> Result<Response> {
     let _result = Breed::delete_breed(&mut *tx, user.organisation_pid, id).await?;
 
     tx.commit().await?;
@@ -96,25 +89,31 @@ async fn remove(
     Ok((StatusCode::NO_CONTENT, Json(json!({}))).into_response())
 }
 
-#[debug_handler]
-#[tracing::instrument(name = "Update Breed by its ID", skip(ctx, user))]
+#[debug_handler(state = AppContext)]
+#[tracing::instrument(name = "Update Breed by its ID", skip(txn, user))]
 async fn update(
-    State(ctx): State<AppContext>,
+    mut txn: TenantTransaction,
     user: User,
     Path(id): Path<i32>,
     Json(params): Json<UpdateBreed<'static>>,
 ) -> Result<Response> {
-    let breed = Breed::update_by_id(&ctx.db, user.organisation_pid, id, &params).await?;
+    let breed = Breed::update_by_id(&mut txn, user.organisation_pid, id, &params).await?;
+
+    txn.commit().await?;
 
     Ok((StatusCode::CREATED, Json(breed)).into_response())
 }
 
 pub fn router(ctx: AppContext) -> Router {
+    let can_read = PermissionLayer::new(Resource::Breeds, Action::Read);
+    let can_write = PermissionLayer::new(Resource::Breeds, Action::Write);
+    let can_delete = PermissionLayer::new(Resource::Breeds, Action::Delete);
+
     Router::new()
-        .route("/", get(all))
-        .route("/", post(add))
-        .route("/{id}", get(one))
-        .route("/{id}", delete(remove))
-        .route("/{id}", patch(update))
+        .route("/", get(all).layer(can_read))
+        .route("/", post(add).layer(can_write))
+        .route("/{id}", get(one).layer(can_read))
+        .route("/{id}", delete(remove).layer(can_delete))
+        .route("/{id}", patch(update).layer(can_write))
         .with_state(ctx)
 }
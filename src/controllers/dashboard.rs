@@ -9,10 +9,13 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     AppContext, Result,
+    middlewares::PermissionLayer,
     models::{
+        LiveSummary,
         animals::{Animal, AnimalResponse},
         health::{HealthRecord, HealthRecordResponse},
         livestock::LivestockSummary,
+        roles::{Action, Resource},
         users::User,
     },
 };
@@ -23,6 +26,7 @@ struct DashboardResponse {
     pub livestock: Vec<AnimalResponse>,
     pub health: Vec<HealthRecordResponse>,
     pub livestock_summary: Vec<LivestockSummary>,
+    pub live_summary: LiveSummary,
 }
 
 #[debug_handler]
@@ -30,6 +34,7 @@ async fn metrics(user: User, State(ctx): State<AppContext>) -> Result<Response>
     let livestock = Animal::find_most_valuable(&ctx.db, user.organisation_pid).await?;
     let health = HealthRecord::find_recent_activities(&ctx.db, user.organisation_pid).await?;
     let livestock_summary = LivestockSummary::find_all(&ctx.db, user.organisation_pid).await?;
+    let live_summary = LiveSummary::find_livestock(&ctx.db, user.organisation_pid).await?;
 
     Ok((
         StatusCode::OK,
@@ -37,11 +42,16 @@ async fn metrics(user: User, State(ctx): State<AppContext>) -> Result<Response>
             livestock,
             health,
             livestock_summary,
+            live_summary,
         }),
     )
         .into_response())
 }
 
 pub fn router(ctx: AppContext) -> Router {
-    Router::new().route("/", get(metrics)).with_state(ctx)
+    let can_read = PermissionLayer::new(Resource::Reports, Action::Read);
+
+    Router::new()
+        .route("/", get(metrics).layer(can_read))
+        .with_state(ctx)
 }
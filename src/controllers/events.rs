@@ -0,0 +1,233 @@
+use std::{pin::pin, time::Duration};
+
+use axum::{
+    Extension, Router, debug_handler,
+    extract::{
+        State,
+        ws::{Message, WebSocket, WebSocketUpgrade},
+    },
+    response::{
+        IntoResponse, Response,
+        sse::{Event, KeepAlive, Sse},
+    },
+    routing::get,
+};
+use chrono::{DateTime, Utc};
+use futures::{Stream, StreamExt, future};
+use tokio::{sync::watch, time};
+
+use crate::{
+    AppContext, Result,
+    live::LiveEvent,
+    middlewares::{PermissionLayer, TokenClaims},
+    models::{
+        api_keys::ApiKey,
+        entitlements::Entitlement,
+        orgs::Organisation,
+        roles::{Action, Resource, Role},
+        users::User,
+    },
+};
+
+/// Who a live connection was opened for, checked again while it stays open.
+struct Viewer {
+    user: User,
+    api_key: Option<ApiKey>,
+    /// When the token or key the connection was opened with expires.
+    expires_at: Option<DateTime<Utc>>,
+}
+
+impl Viewer {
+    fn new(user: User, claims: Option<&TokenClaims>, api_key: Option<ApiKey>) -> Self {
+        let expires_at = match (&api_key, claims) {
+            (Some(api_key), _) => api_key.expires_at.map(|at| at.with_timezone(&Utc)),
+            (None, Some(claims)) => DateTime::from_timestamp(claims.exp, 0),
+            (None, None) => None,
+        };
+
+        Self {
+            user,
+            api_key,
+            expires_at,
+        }
+    }
+
+    /// The role the viewer reads events with now, or `None` once they may no
+    /// longer read them because they were deactivated, their organisation
+    /// was suspended, their key revoked or out of plan, or their role
+    /// changed.
+    async fn role(&self, ctx: &AppContext) -> Result<Option<Role>> {
+        let user =
+            User::find_member(&ctx.db, self.user.pid, Some(self.user.organisation_pid)).await?;
+        let Some(user) = user.filter(|user| user.is_active) else {
+            return Ok(None);
+        };
+
+        // As in the `AuthLayer`, operators keep access to their own
+        // suspended organisation but keys do not.
+        let organisation = Organisation::find_by_pid(&ctx.db, user.organisation_pid).await?;
+        if organisation.is_suspended() && (self.api_key.is_some() || !user.is_platform_operator) {
+            return Ok(None);
+        }
+
+        let role = Role::find_for_organisation(&ctx.db, user.organisation_pid, &user.role).await?;
+        let role = match &self.api_key {
+            Some(api_key) => {
+                let api_key =
+                    ApiKey::find_by_pid(&ctx.db, api_key.organisation_pid, api_key.pid).await?;
+                let in_plan = Entitlement::ApiAccess
+                    .check(&ctx.db, organisation.pid, &organisation.subscription())
+                    .await
+                    .is_ok();
+                if !api_key.is_usable() || !in_plan {
+                    return Ok(None);
+                }
+
+                api_key.restrict(&role)
+            }
+            None => role,
+        };
+
+        Ok(role.allows(Resource::Reports, Action::Read).then_some(role))
+    }
+}
+
+/// Resolves once the viewer's token or key expires or they lose access,
+/// which is checked every `recheck_interval` seconds. Until then `role` is
+/// kept up to date so role changes apply to the open connection.
+async fn watch_access(ctx: AppContext, viewer: Viewer, role: watch::Sender<Role>) {
+    let expired = async {
+        match viewer.expires_at {
+            Some(at) => time::sleep((at - Utc::now()).to_std().unwrap_or_default()).await,
+            None => future::pending().await,
+        }
+    };
+
+    let revoked = async {
+        let mut checks = time::interval(Duration::from_secs(ctx.config.live().recheck_interval()));
+        // The first tick is immediate, and access was just checked.
+        checks.tick().await;
+
+        loop {
+            checks.tick().await;
+
+            match viewer.role(&ctx).await {
+                Ok(Some(current)) => {
+                    role.send_replace(current);
+                }
+                Ok(None) => return,
+                Err(error) => {
+                    tracing::error!("Could not check live event access: {error}");
+                    return;
+                }
+            }
+        }
+    };
+
+    tokio::select! {
+        () = expired => {}
+        () = revoked => {}
+    }
+}
+
+/// The organisation's events the viewer's role may read, until they may no
+/// longer read them.
+async fn readable(
+    ctx: &AppContext,
+    viewer: Viewer,
+    role: Role,
+) -> Result<impl Stream<Item = LiveEvent> + use<>> {
+    let events = ctx.live.subscribe(viewer.user.organisation_pid).await?;
+
+    let (role, current) = watch::channel(role);
+    let ended = watch_access(ctx.clone(), viewer, role);
+
+    Ok(events.take_until(ended).filter(move |event| {
+        future::ready(current.borrow().allows(event.resource(), Action::Read))
+    }))
+}
+
+/// The organisation's events as Server-Sent Events, named after their type.
+#[debug_handler]
+async fn stream(
+    user: User,
+    State(ctx): State<AppContext>,
+    Extension(role): Extension<Role>,
+    claims: Option<Extension<TokenClaims>>,
+    api_key: Option<Extension<ApiKey>>,
+) -> Result<Response> {
+    let viewer = Viewer::new(
+        user,
+        claims.as_ref().map(|Extension(claims)| claims),
+        api_key.map(|Extension(api_key)| api_key),
+    );
+    let events = readable(&ctx, viewer, role).await?;
+
+    let events = events.map(|event| {
+        Event::default()
+            .event(event.event_type.clone())
+            .json_data(event)
+    });
+
+    Ok(Sse::new(events)
+        .keep_alive(KeepAlive::default())
+        .into_response())
+}
+
+/// The same events over a WebSocket, one JSON message each.
+#[debug_handler]
+async fn socket(
+    user: User,
+    State(ctx): State<AppContext>,
+    Extension(role): Extension<Role>,
+    claims: Option<Extension<TokenClaims>>,
+    api_key: Option<Extension<ApiKey>>,
+    upgrade: WebSocketUpgrade,
+) -> Result<Response> {
+    let viewer = Viewer::new(
+        user,
+        claims.as_ref().map(|Extension(claims)| claims),
+        api_key.map(|Extension(api_key)| api_key),
+    );
+    let events = readable(&ctx, viewer, role).await?;
+
+    Ok(upgrade.on_upgrade(move |socket| forward(socket, events)))
+}
+
+async fn forward(mut socket: WebSocket, events: impl Stream<Item = LiveEvent>) {
+    let mut events = pin!(events);
+
+    loop {
+        tokio::select! {
+            event = events.next() => {
+                let Some(event) = event else {
+                    // The viewer may no longer read events.
+                    socket.send(Message::Close(None)).await.ok();
+                    break;
+                };
+                let Ok(text) = serde_json::to_string(&event) else { continue };
+
+                if socket.send(Message::Text(text.into())).await.is_err() {
+                    break;
+                }
+            }
+            message = socket.recv() => {
+                // Messages from the client are ignored, the socket only
+                // pushes.
+                if let Some(Ok(Message::Close(_)) | Err(_)) | None = message {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+pub fn router(ctx: AppContext) -> Router {
+    let can_read = PermissionLayer::new(Resource::Reports, Action::Read);
+
+    Router::new()
+        .route("/stream", get(stream))
+        .route("/ws", get(socket))
+        .route_layer(can_read)
+        .with_state(ctx)
+}
@@ -0,0 +1,287 @@
+use axum::{
+    Json, Router, debug_handler,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{delete, get, patch, post},
+};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{
+    AppContext, Result,
+    middlewares::PermissionLayer,
+    models::{
+        dto::{
+            ConversionQuery, FeedQuery, FeedRation, NewFeedPurchase, NewFeeding, NewFeedstuff,
+            NewRation, StockQuery, UpdateFeedstuff, UpdateRation,
+        },
+        feed::{ConversionReport, FeedPurchase, Feeding, Feedstuff, Ration, StockLevel},
+        roles::{Action, Resource},
+        tenant::TenantTransaction,
+        users::User,
+    },
+};
+
+#[debug_handler]
+async fn feedstuffs(user: User, State(ctx): State<AppContext>) -> Result<Response> {
+    let feedstuffs = Feedstuff::find_all(&ctx.db, user.organisation_pid).await?;
+
+    Ok((StatusCode::OK, Json(feedstuffs)).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn add_feedstuff(
+    user: User,
+    mut txn: TenantTransaction,
+    Json(params): Json<NewFeedstuff<'static>>,
+) -> Result<Response> {
+    let feedstuff = Feedstuff::create(&mut txn, user.organisation_pid, user.pid, &params).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::CREATED, Json(feedstuff)).into_response())
+}
+
+#[debug_handler]
+async fn feedstuff(
+    user: User,
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    let feedstuff = Feedstuff::find_by_pid(&ctx.db, user.organisation_pid, pid).await?;
+
+    Ok((StatusCode::OK, Json(feedstuff)).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn update_feedstuff(
+    user: User,
+    mut txn: TenantTransaction,
+    Path(pid): Path<Uuid>,
+    Json(params): Json<UpdateFeedstuff<'static>>,
+) -> Result<Response> {
+    let feedstuff = Feedstuff::update(&mut txn, user.organisation_pid, pid, &params).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::OK, Json(feedstuff)).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn remove_feedstuff(
+    user: User,
+    mut txn: TenantTransaction,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    Feedstuff::delete_by_pid(&mut txn, user.organisation_pid, pid).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::NO_CONTENT, Json(json!({}))).into_response())
+}
+
+#[debug_handler]
+async fn purchases(
+    user: User,
+    State(ctx): State<AppContext>,
+    Query(params): Query<FeedQuery>,
+) -> Result<Response> {
+    let purchases = FeedPurchase::find_all(&ctx.db, user.organisation_pid, &params).await?;
+
+    Ok((StatusCode::OK, Json(purchases)).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn add_purchase(
+    user: User,
+    mut txn: TenantTransaction,
+    Json(params): Json<NewFeedPurchase<'static>>,
+) -> Result<Response> {
+    let purchase = FeedPurchase::create(&mut txn, user.organisation_pid, user.pid, &params).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::CREATED, Json(purchase)).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn remove_purchase(
+    user: User,
+    mut txn: TenantTransaction,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    FeedPurchase::delete_by_pid(&mut txn, user.organisation_pid, pid).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::NO_CONTENT, Json(json!({}))).into_response())
+}
+
+#[debug_handler]
+async fn rations(user: User, State(ctx): State<AppContext>) -> Result<Response> {
+    let rations = Ration::find_all(&ctx.db, user.organisation_pid).await?;
+
+    Ok((StatusCode::OK, Json(rations)).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn add_ration(
+    user: User,
+    mut txn: TenantTransaction,
+    Json(params): Json<NewRation<'static>>,
+) -> Result<Response> {
+    let ration = Ration::create(&mut txn, user.organisation_pid, user.pid, &params).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::CREATED, Json(ration)).into_response())
+}
+
+#[debug_handler]
+async fn ration(
+    user: User,
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    let ration = Ration::find_by_pid(&ctx.db, user.organisation_pid, pid).await?;
+
+    Ok((StatusCode::OK, Json(ration)).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn update_ration(
+    user: User,
+    mut txn: TenantTransaction,
+    Path(pid): Path<Uuid>,
+    Json(params): Json<UpdateRation<'static>>,
+) -> Result<Response> {
+    let ration = Ration::update(&mut txn, user.organisation_pid, pid, &params).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::OK, Json(ration)).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn remove_ration(
+    user: User,
+    mut txn: TenantTransaction,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    Ration::delete_by_pid(&mut txn, user.organisation_pid, pid).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::NO_CONTENT, Json(json!({}))).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn feed_ration(
+    user: User,
+    mut txn: TenantTransaction,
+    Path(pid): Path<Uuid>,
+    Json(params): Json<FeedRation<'static>>,
+) -> Result<Response> {
+    let feedings = Ration::feed(&mut txn, user.organisation_pid, user.pid, pid, &params).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::CREATED, Json(feedings)).into_response())
+}
+
+#[debug_handler]
+async fn feedings(
+    user: User,
+    State(ctx): State<AppContext>,
+    Query(params): Query<FeedQuery>,
+) -> Result<Response> {
+    let feedings = Feeding::find_all(&ctx.db, user.organisation_pid, &params).await?;
+
+    Ok((StatusCode::OK, Json(feedings)).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn add_feeding(
+    user: User,
+    mut txn: TenantTransaction,
+    Json(params): Json<NewFeeding<'static>>,
+) -> Result<Response> {
+    let feeding = Feeding::create(&mut txn, user.organisation_pid, user.pid, &params).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::CREATED, Json(feeding)).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn remove_feeding(
+    user: User,
+    mut txn: TenantTransaction,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    Feeding::delete_by_pid(&mut txn, user.organisation_pid, pid).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::NO_CONTENT, Json(json!({}))).into_response())
+}
+
+#[debug_handler]
+async fn stock(
+    user: User,
+    State(ctx): State<AppContext>,
+    Query(params): Query<StockQuery>,
+) -> Result<Response> {
+    let levels = StockLevel::find_all(&ctx.db, user.organisation_pid, params).await?;
+
+    Ok((StatusCode::OK, Json(levels)).into_response())
+}
+
+#[debug_handler]
+async fn conversion(
+    user: User,
+    State(ctx): State<AppContext>,
+    Query(params): Query<ConversionQuery>,
+) -> Result<Response> {
+    let report = ConversionReport::find(&ctx.db, user.organisation_pid, params).await?;
+
+    Ok((StatusCode::OK, Json(report)).into_response())
+}
+
+pub fn router(ctx: AppContext) -> Router {
+    let can_read = PermissionLayer::new(Resource::Feed, Action::Read);
+    let can_write = PermissionLayer::new(Resource::Feed, Action::Write);
+    let can_delete = PermissionLayer::new(Resource::Feed, Action::Delete);
+
+    Router::new()
+        .route("/feedstuffs", get(feedstuffs).layer(can_read))
+        .route("/feedstuffs", post(add_feedstuff).layer(can_write))
+        .route("/feedstuffs/{pid}", get(feedstuff).layer(can_read))
+        .route(
+            "/feedstuffs/{pid}",
+            patch(update_feedstuff).layer(can_write),
+        )
+        .route(
+            "/feedstuffs/{pid}",
+            delete(remove_feedstuff).layer(can_delete),
+        )
+        .route("/purchases", get(purchases).layer(can_read))
+        .route("/purchases", post(add_purchase).layer(can_write))
+        .route(
+            "/purchases/{pid}",
+            delete(remove_purchase).layer(can_delete),
+        )
+        .route("/rations", get(rations).layer(can_read))
+        .route("/rations", post(add_ration).layer(can_write))
+        .route("/rations/{pid}", get(ration).layer(can_read))
+        .route("/rations/{pid}", patch(update_ration).layer(can_write))
+        .route("/rations/{pid}", delete(remove_ration).layer(can_delete))
+        .route("/rations/{pid}/feed", post(feed_ration).layer(can_write))
+        .route("/feedings", get(feedings).layer(can_read))
+        .route("/feedings", post(add_feeding).layer(can_write))
+        .route("/feedings/{pid}", delete(remove_feeding).layer(can_delete))
+        .route("/stock", get(stock).layer(can_read))
+        .route("/conversion", get(conversion).layer(can_read))
+        .with_state(ctx)
+}
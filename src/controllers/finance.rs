@@ -0,0 +1,144 @@
+use axum::{
+    Json, Router, debug_handler,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{delete, get, patch, post},
+};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{
+    AppContext, Result,
+    middlewares::PermissionLayer,
+    models::{
+        dto::{LedgerQuery, NewLedgerEntry, ProfitQuery, UpdateLedgerEntry},
+        finance::{AnimalProfit, LedgerEntry, ProfitAndLoss},
+        roles::{Action, Resource},
+        settings::OrganisationSettings,
+        tenant::TenantTransaction,
+        users::User,
+    },
+    views::reports::ProfitReport,
+};
+
+#[debug_handler]
+async fn entries(
+    user: User,
+    State(ctx): State<AppContext>,
+    Query(params): Query<LedgerQuery>,
+) -> Result<Response> {
+    let entries = LedgerEntry::find_all(&ctx.db, user.organisation_pid, &params).await?;
+
+    Ok((StatusCode::OK, Json(entries)).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn add(
+    user: User,
+    mut txn: TenantTransaction,
+    Json(params): Json<NewLedgerEntry<'static>>,
+) -> Result<Response> {
+    let entry = LedgerEntry::create(&mut txn, user.organisation_pid, user.pid, &params).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::CREATED, Json(entry)).into_response())
+}
+
+#[debug_handler]
+async fn one(user: User, State(ctx): State<AppContext>, Path(pid): Path<Uuid>) -> Result<Response> {
+    let entry = LedgerEntry::find_by_pid(&ctx.db, user.organisation_pid, pid).await?;
+
+    Ok((StatusCode::OK, Json(entry)).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn update(
+    user: User,
+    mut txn: TenantTransaction,
+    Path(pid): Path<Uuid>,
+    Json(params): Json<UpdateLedgerEntry<'static>>,
+) -> Result<Response> {
+    let entry = LedgerEntry::update(&mut txn, user.organisation_pid, pid, &params).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::OK, Json(entry)).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn remove(user: User, mut txn: TenantTransaction, Path(pid): Path<Uuid>) -> Result<Response> {
+    LedgerEntry::delete_by_pid(&mut txn, user.organisation_pid, pid).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::NO_CONTENT, Json(json!({}))).into_response())
+}
+
+#[debug_handler]
+async fn herd(
+    user: User,
+    State(ctx): State<AppContext>,
+    Query(params): Query<ProfitQuery>,
+) -> Result<Response> {
+    let settings = OrganisationSettings::find(&ctx.db, user.organisation_pid).await?;
+    let profit = ProfitAndLoss::for_herd(&ctx.db, user.organisation_pid, params).await?;
+
+    Ok((StatusCode::OK, Json(ProfitReport::new(profit, &settings))).into_response())
+}
+
+#[debug_handler]
+async fn animals(
+    user: User,
+    State(ctx): State<AppContext>,
+    Query(params): Query<ProfitQuery>,
+) -> Result<Response> {
+    let animals = AnimalProfit::find_all(&ctx.db, user.organisation_pid, params).await?;
+
+    Ok((StatusCode::OK, Json(animals)).into_response())
+}
+
+#[debug_handler]
+async fn animal(
+    user: User,
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+    Query(params): Query<ProfitQuery>,
+) -> Result<Response> {
+    let settings = OrganisationSettings::find(&ctx.db, user.organisation_pid).await?;
+    let profit = ProfitAndLoss::for_animal(&ctx.db, user.organisation_pid, pid, params).await?;
+
+    Ok((StatusCode::OK, Json(ProfitReport::new(profit, &settings))).into_response())
+}
+
+#[debug_handler]
+async fn group(
+    user: User,
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+    Query(params): Query<ProfitQuery>,
+) -> Result<Response> {
+    let settings = OrganisationSettings::find(&ctx.db, user.organisation_pid).await?;
+    let profit = ProfitAndLoss::for_group(&ctx.db, user.organisation_pid, pid, params).await?;
+
+    Ok((StatusCode::OK, Json(ProfitReport::new(profit, &settings))).into_response())
+}
+
+pub fn router(ctx: AppContext) -> Router {
+    let can_read = PermissionLayer::new(Resource::Finances, Action::Read);
+    let can_write = PermissionLayer::new(Resource::Finances, Action::Write);
+    let can_delete = PermissionLayer::new(Resource::Finances, Action::Delete);
+
+    Router::new()
+        .route("/entries", get(entries).layer(can_read))
+        .route("/entries", post(add).layer(can_write))
+        .route("/entries/{pid}", get(one).layer(can_read))
+        .route("/entries/{pid}", patch(update).layer(can_write))
+        .route("/entries/{pid}", delete(remove).layer(can_delete))
+        .route("/herd", get(herd).layer(can_read))
+        .route("/animals", get(animals).layer(can_read))
+        .route("/animals/{pid}", get(animal).layer(can_read))
+        .route("/groups/{pid}", get(group).layer(can_read))
+        .with_state(ctx)
+}
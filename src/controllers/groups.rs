@@ -0,0 +1,226 @@
+use axum::{
+    Json, Router, debug_handler,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{delete, get, patch, post},
+};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{
+    AppContext, Result,
+    middlewares::PermissionLayer,
+    models::{
+        LiveSummary,
+        dto::{GroupMembers, GroupMove, GroupTreatment, GroupWeighing, NewGroup, UpdateGroup},
+        groups::AnimalGroup,
+        roles::{Action, Resource},
+        tenant::TenantTransaction,
+        users::User,
+    },
+    pdf::PdfReport,
+};
+
+#[debug_handler]
+async fn list(user: User, State(ctx): State<AppContext>) -> Result<Response> {
+    let groups = AnimalGroup::find_all(&ctx.db, user.organisation_pid).await?;
+
+    Ok((StatusCode::OK, Json(groups)).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn add(
+    user: User,
+    mut txn: TenantTransaction,
+    Json(params): Json<NewGroup<'static>>,
+) -> Result<Response> {
+    let group = AnimalGroup::create(&mut txn, user.organisation_pid, user.pid, &params).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::CREATED, Json(group)).into_response())
+}
+
+#[debug_handler]
+async fn get_one(
+    user: User,
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    let group = AnimalGroup::find_by_pid(&ctx.db, user.organisation_pid, pid).await?;
+
+    Ok((StatusCode::OK, Json(group)).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn update(
+    user: User,
+    mut txn: TenantTransaction,
+    Path(pid): Path<Uuid>,
+    Json(params): Json<UpdateGroup<'static>>,
+) -> Result<Response> {
+    let group = AnimalGroup::update(&mut txn, user.organisation_pid, pid, &params).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::OK, Json(group)).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn remove(user: User, mut txn: TenantTransaction, Path(pid): Path<Uuid>) -> Result<Response> {
+    AnimalGroup::delete_by_pid(&mut txn, user.organisation_pid, pid).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::NO_CONTENT, Json(json!({}))).into_response())
+}
+
+/// Lists the members, recording who joined or left a dynamic group since it
+/// was last looked at.
+#[debug_handler(state = AppContext)]
+async fn members(
+    user: User,
+    mut txn: TenantTransaction,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    let members = AnimalGroup::find_members(&mut txn, user.organisation_pid, pid).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::OK, Json(members)).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn add_members(
+    user: User,
+    mut txn: TenantTransaction,
+    Path(pid): Path<Uuid>,
+    Json(params): Json<GroupMembers<'static>>,
+) -> Result<Response> {
+    let members =
+        AnimalGroup::add_members(&mut txn, user.organisation_pid, user.pid, pid, &params).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::OK, Json(members)).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn remove_members(
+    user: User,
+    mut txn: TenantTransaction,
+    Path(pid): Path<Uuid>,
+    Json(params): Json<GroupMembers<'static>>,
+) -> Result<Response> {
+    let members =
+        AnimalGroup::remove_members(&mut txn, user.organisation_pid, user.pid, pid, &params)
+            .await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::OK, Json(members)).into_response())
+}
+
+#[debug_handler]
+async fn history(
+    user: User,
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    let history = AnimalGroup::find_history(&ctx.db, user.organisation_pid, pid).await?;
+
+    Ok((StatusCode::OK, Json(history)).into_response())
+}
+
+#[debug_handler]
+async fn summary(
+    user: User,
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    let group = AnimalGroup::find_by_pid(&ctx.db, user.organisation_pid, pid).await?;
+    let pids = group.animal_pids(&ctx.db).await?;
+    let summary = LiveSummary::find_animals(&ctx.db, user.organisation_pid, &pids).await?;
+
+    Ok((StatusCode::OK, Json(summary)).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn weigh(
+    user: User,
+    mut txn: TenantTransaction,
+    Path(pid): Path<Uuid>,
+    Json(params): Json<GroupWeighing<'static>>,
+) -> Result<Response> {
+    let records =
+        AnimalGroup::weigh(&mut txn, user.organisation_pid, user.pid, pid, &params).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::CREATED, Json(records)).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn treat(
+    user: User,
+    mut txn: TenantTransaction,
+    Path(pid): Path<Uuid>,
+    Json(params): Json<GroupTreatment<'static>>,
+) -> Result<Response> {
+    let records =
+        AnimalGroup::treat(&mut txn, user.organisation_pid, user.pid, pid, &params).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::CREATED, Json(records)).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn move_to(
+    user: User,
+    mut txn: TenantTransaction,
+    Path(pid): Path<Uuid>,
+    Json(params): Json<GroupMove<'static>>,
+) -> Result<Response> {
+    let movements =
+        AnimalGroup::move_to(&mut txn, user.organisation_pid, user.pid, pid, &params).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::CREATED, Json(movements)).into_response())
+}
+
+#[debug_handler]
+async fn pdf(user: User, State(ctx): State<AppContext>, Path(pid): Path<Uuid>) -> Result<Response> {
+    let report = PdfReport::group(&ctx.db, user.organisation_pid, pid).await?;
+
+    Ok(report.render()?.into_response())
+}
+
+pub fn router(ctx: AppContext) -> Router {
+    let can_read = PermissionLayer::new(Resource::Animals, Action::Read);
+    let can_write = PermissionLayer::new(Resource::Animals, Action::Write);
+    let can_delete = PermissionLayer::new(Resource::Animals, Action::Delete);
+    let can_weigh = PermissionLayer::new(Resource::WeightRecords, Action::Write);
+    let can_treat = PermissionLayer::new(Resource::HealthRecords, Action::Write);
+    let can_move = PermissionLayer::new(Resource::Locations, Action::Write);
+    let can_report = PermissionLayer::new(Resource::Reports, Action::Read);
+
+    Router::new()
+        .route("/", get(list).layer(can_read))
+        .route("/", post(add).layer(can_write))
+        .route("/{pid}", get(get_one).layer(can_read))
+        .route("/{pid}", patch(update).layer(can_write))
+        .route("/{pid}", delete(remove).layer(can_delete))
+        .route("/{pid}/members", get(members).layer(can_read))
+        .route("/{pid}/members", post(add_members).layer(can_write))
+        .route("/{pid}/members", delete(remove_members).layer(can_write))
+        .route("/{pid}/history", get(history).layer(can_read))
+        .route("/{pid}/summary", get(summary).layer(can_read))
+        .route("/{pid}/weighings", post(weigh).layer(can_weigh))
+        .route("/{pid}/treatments", post(treat).layer(can_treat))
+        .route("/{pid}/movements", post(move_to).layer(can_move))
+        .route("/{pid}/pdf", get(pdf).layer(can_report))
+        .with_state(ctx)
+}
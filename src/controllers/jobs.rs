@@ -0,0 +1,143 @@
+use axum::{
+    Json, Router, debug_handler,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{delete, get, patch, post},
+};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{
+    AppContext, Result,
+    middlewares::PermissionLayer,
+    models::{
+        ModelError,
+        dto::{CreateJobSchedule, EnqueueJob, JobQuery, UpdateJobSchedule, Validator},
+        jobs::{Job, JobKind, JobSchedule},
+        roles::{Action, Resource},
+        tenant::TenantTransaction,
+        users::User,
+    },
+    views::job::{JobResponse, JobScheduleResponse},
+};
+
+#[debug_handler(state = AppContext)]
+async fn list(
+    user: User,
+    mut txn: TenantTransaction,
+    Query(params): Query<JobQuery>,
+) -> Result<Response> {
+    let jobs = Job::find_all(&mut *txn, user.organisation_pid, &params).await?;
+
+    let jobs = jobs.iter().map(JobResponse::new).collect::<Vec<_>>();
+
+    Ok((StatusCode::OK, Json(jobs)).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn one(user: User, mut txn: TenantTransaction, Path(pid): Path<Uuid>) -> Result<Response> {
+    let job = Job::find_by_pid(&mut *txn, user.organisation_pid, pid).await?;
+
+    Ok((StatusCode::OK, Json(JobResponse::new(&job))).into_response())
+}
+
+/// Queues a job for the organisation to run as soon as a worker is free.
+#[debug_handler]
+async fn enqueue(
+    State(ctx): State<AppContext>,
+    user: User,
+    mut txn: TenantTransaction,
+    Json(params): Json<EnqueueJob<'static>>,
+) -> Result<Response> {
+    let validator = Validator::new(&params);
+    let params = validator.validate()?;
+
+    let kind = params.kind.parse::<JobKind>()?;
+    if !kind.per_organisation() {
+        return Err(ModelError::Validation(format!("{kind} jobs are run by the platform")).into());
+    }
+
+    let job = Job::enqueue(
+        &mut *txn,
+        Some(user.organisation_pid),
+        kind,
+        ctx.config.jobs().max_attempts(),
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::ACCEPTED, Json(JobResponse::new(&job))).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn list_schedules(user: User, mut txn: TenantTransaction) -> Result<Response> {
+    let schedules = JobSchedule::find_all(&mut *txn, user.organisation_pid).await?;
+
+    let schedules = schedules
+        .iter()
+        .map(JobScheduleResponse::new)
+        .collect::<Vec<_>>();
+
+    Ok((StatusCode::OK, Json(schedules)).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn create_schedule(
+    user: User,
+    mut txn: TenantTransaction,
+    Json(params): Json<CreateJobSchedule<'static>>,
+) -> Result<Response> {
+    let schedule = JobSchedule::create(&mut txn, &user, &params).await?;
+
+    txn.commit().await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(JobScheduleResponse::new(&schedule)),
+    )
+        .into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn update_schedule(
+    user: User,
+    mut txn: TenantTransaction,
+    Path(pid): Path<Uuid>,
+    Json(params): Json<UpdateJobSchedule<'static>>,
+) -> Result<Response> {
+    let schedule = JobSchedule::update(&mut txn, user.organisation_pid, pid, &params).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::OK, Json(JobScheduleResponse::new(&schedule))).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn remove_schedule(
+    user: User,
+    mut txn: TenantTransaction,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    JobSchedule::delete_by_pid(&mut *txn, user.organisation_pid, pid).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::NO_CONTENT, Json(json!({}))).into_response())
+}
+
+pub fn router(ctx: AppContext) -> Router {
+    let can_manage = PermissionLayer::new(Resource::Organisation, Action::Manage);
+
+    Router::new()
+        .route("/", get(list))
+        .route("/", post(enqueue))
+        .route("/schedules", get(list_schedules))
+        .route("/schedules", post(create_schedule))
+        .route("/schedules/{pid}", patch(update_schedule))
+        .route("/schedules/{pid}", delete(remove_schedule))
+        .route("/{pid}", get(one))
+        .route_layer(can_manage)
+        .with_state(ctx)
+}
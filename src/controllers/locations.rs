@@ -0,0 +1,124 @@
+use axum::{
+    Json, Router, debug_handler,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{delete, get, patch, post},
+};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{
+    AppContext, Result,
+    middlewares::PermissionLayer,
+    models::{
+        dto::{MoveAnimals, MovementQuery, NewLocation, UpdateLocation},
+        locations::{Location, Movement, Occupancy},
+        roles::{Action, Resource},
+        tenant::TenantTransaction,
+        users::User,
+    },
+};
+
+#[debug_handler]
+async fn list(user: User, State(ctx): State<AppContext>) -> Result<Response> {
+    let locations = Location::find_all(&ctx.db, user.organisation_pid).await?;
+
+    Ok((StatusCode::OK, Json(locations)).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn add(
+    user: User,
+    mut txn: TenantTransaction,
+    Json(params): Json<NewLocation<'static>>,
+) -> Result<Response> {
+    let location = Location::create(&mut txn, user.organisation_pid, user.pid, &params).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::CREATED, Json(location)).into_response())
+}
+
+#[debug_handler]
+async fn get_one(
+    user: User,
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    let location = Location::find_by_pid(&ctx.db, user.organisation_pid, pid).await?;
+
+    Ok((StatusCode::OK, Json(location)).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn update(
+    user: User,
+    mut txn: TenantTransaction,
+    Path(pid): Path<Uuid>,
+    Json(params): Json<UpdateLocation<'static>>,
+) -> Result<Response> {
+    let location = Location::update(&mut txn, user.organisation_pid, pid, &params).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::OK, Json(location)).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn remove(user: User, mut txn: TenantTransaction, Path(pid): Path<Uuid>) -> Result<Response> {
+    Location::delete_by_pid(&mut txn, user.organisation_pid, pid).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::NO_CONTENT, Json(json!({}))).into_response())
+}
+
+#[debug_handler]
+async fn occupancy(user: User, State(ctx): State<AppContext>) -> Result<Response> {
+    let occupancy = Occupancy::find_all(&ctx.db, user.organisation_pid).await?;
+
+    Ok((StatusCode::OK, Json(occupancy)).into_response())
+}
+
+#[debug_handler]
+async fn movements(
+    user: User,
+    State(ctx): State<AppContext>,
+    Query(params): Query<MovementQuery>,
+) -> Result<Response> {
+    let movements = Movement::find_all(&ctx.db, user.organisation_pid, &params).await?;
+
+    Ok((StatusCode::OK, Json(movements)).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn move_animals(
+    user: User,
+    mut txn: TenantTransaction,
+    Json(params): Json<MoveAnimals<'static>>,
+) -> Result<Response> {
+    let movements =
+        Location::move_animals(&mut txn, user.organisation_pid, user.pid, &params).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::CREATED, Json(movements)).into_response())
+}
+
+pub fn router(ctx: AppContext) -> Router {
+    let can_read = PermissionLayer::new(Resource::Locations, Action::Read);
+    let can_write = PermissionLayer::new(Resource::Locations, Action::Write);
+    let can_delete = PermissionLayer::new(Resource::Locations, Action::Delete);
+
+    Router::new()
+        .route("/", get(list).layer(can_read))
+        .route("/", post(add).layer(can_write))
+        .route("/occupancy", get(occupancy).layer(can_read))
+        .route("/movements", get(movements).layer(can_read))
+        .route("/movements", post(move_animals).layer(can_write))
+        .route("/{pid}", get(get_one).layer(can_read))
+        .route("/{pid}", patch(update).layer(can_write))
+        .route("/{pid}", delete(remove).layer(can_delete))
+        .with_state(ctx)
+}
@@ -1,21 +1,34 @@
 pub mod admin;
+pub mod alerts;
 pub mod animals;
+pub mod audit;
 pub mod auth;
 pub mod breeds;
 pub mod dashboard;
+pub mod events;
+pub mod feed;
+pub mod finance;
+pub mod groups;
 pub mod health;
+pub mod jobs;
+pub mod locations;
+pub mod organisation;
+pub mod platform;
 pub mod production;
 pub mod reports;
+pub mod roles;
 pub mod species;
+pub mod subscription;
+pub mod tasks;
+pub mod trash;
+pub mod webhooks;
 pub mod weight;
 
 use std::sync::Arc;
 
 use crate::{
     AppContext,
-    middlewares::{
-        self, AdminLayer, AuthLayer, authorisation::AuthorisationLayer, refresh::RefreshTokenLayer,
-    },
+    middlewares::{self, AuthLayer, authorisation::AuthorisationLayer, refresh::RefreshTokenLayer},
 };
 
 use axum::{
@@ -61,17 +74,29 @@ pub fn router(ctx: AppContext) -> Router {
         .on_failure(middlewares::on_failure);
 
     let protected_routes = Router::new()
-        .nest(
-            "/admin",
-            admin::route((*ctx).clone()).layer(AdminLayer::new(&ctx)),
-        )
+        .nest("/admin", admin::route((*ctx).clone()))
+        .nest("/platform", platform::router((*ctx).clone()))
+        .nest("/roles", roles::router((*ctx).clone()))
+        .nest("/organisation", organisation::router((*ctx).clone()))
+        .nest("/subscription", subscription::router((*ctx).clone()))
+        .nest("/audit", audit::router((*ctx).clone()))
         .nest("/breeds", breeds::router((*ctx).clone()))
         .nest("/categories", species::router((*ctx).clone()))
         .nest("/animals", animals::router((*ctx).clone()))
+        .nest("/groups", groups::router((*ctx).clone()))
         .nest("/production-records", production::router((*ctx).clone()))
         .nest("/health-records", health::router((*ctx).clone()))
         .nest("/weight-records", weight::router((*ctx).clone()))
+        .nest("/feed", feed::router((*ctx).clone()))
+        .nest("/locations", locations::router((*ctx).clone()))
+        .nest("/tasks", tasks::router((*ctx).clone()))
+        .nest("/finance", finance::router((*ctx).clone()))
+        .nest("/trash", trash::router((*ctx).clone()))
+        .nest("/webhooks", webhooks::router((*ctx).clone()))
+        .nest("/alerts", alerts::router((*ctx).clone()))
+        .nest("/jobs", jobs::router((*ctx).clone()))
         .nest("/dashboard", dashboard::router((*ctx).clone()))
+        .nest("/events", events::router((*ctx).clone()))
         .nest("/reports", reports::router(ctx.clone()))
         .layer(AuthorisationLayer::new(&ctx))
         .layer(AuthLayer::new(&ctx))
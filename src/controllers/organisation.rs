@@ -0,0 +1,89 @@
+use axum::{
+    Json, Router, debug_handler,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, patch},
+};
+use serde_json::Value;
+
+use crate::{
+    AppContext, Result,
+    middlewares::PermissionLayer,
+    models::{
+        dto::UpdateOrganisation,
+        orgs::Organisation,
+        roles::{Action, Resource},
+        settings::OrganisationSettings,
+        tenant::TenantTransaction,
+        users::User,
+    },
+    views::organisation::OrganisationProfile,
+};
+
+#[debug_handler(state = AppContext)]
+async fn current(user: User, mut txn: TenantTransaction) -> Result<Response> {
+    let organisation = Organisation::find_by_pid(&mut *txn, user.organisation_pid).await?;
+    let settings = OrganisationSettings::find(&mut *txn, user.organisation_pid).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(OrganisationProfile::new(&organisation, settings)),
+    )
+        .into_response())
+}
+
+/// Updates the organisation's name, address, phone or email.
+#[debug_handler(state = AppContext)]
+async fn update(
+    user: User,
+    mut txn: TenantTransaction,
+    Json(params): Json<UpdateOrganisation<'static>>,
+) -> Result<Response> {
+    let organisation = Organisation::update(&mut *txn, user.organisation_pid, &params).await?;
+    let settings = OrganisationSettings::find(&mut *txn, user.organisation_pid).await?;
+
+    txn.commit().await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(OrganisationProfile::new(&organisation, settings)),
+    )
+        .into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn settings(user: User, mut txn: TenantTransaction) -> Result<Response> {
+    let settings = OrganisationSettings::find(&mut *txn, user.organisation_pid).await?;
+
+    Ok((StatusCode::OK, Json(settings)).into_response())
+}
+
+/// Changes the settings whose keys are present in the body and keeps the rest.
+#[debug_handler(state = AppContext)]
+async fn update_settings(
+    user: User,
+    mut txn: TenantTransaction,
+    Json(changes): Json<Value>,
+) -> Result<Response> {
+    let settings = OrganisationSettings::find(&mut *txn, user.organisation_pid)
+        .await?
+        .merge(changes)?
+        .save(&mut *txn, user.organisation_pid)
+        .await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::OK, Json(settings)).into_response())
+}
+
+pub fn router(ctx: AppContext) -> Router {
+    let can_read = PermissionLayer::new(Resource::Organisation, Action::Read);
+    let can_manage = PermissionLayer::new(Resource::Organisation, Action::Manage);
+
+    Router::new()
+        .route("/", get(current).layer(can_read))
+        .route("/", patch(update).layer(can_manage))
+        .route("/settings", get(settings).layer(can_read))
+        .route("/settings", patch(update_settings).layer(can_manage))
+        .with_state(ctx)
+}
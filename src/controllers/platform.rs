@@ -0,0 +1,268 @@
+use axum::{
+    Json, Router, debug_handler,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{delete, get, patch, post},
+};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{
+    AppContext, Result,
+    middlewares::OperatorLayer,
+    models::{
+        ModelError,
+        dto::{ChangeSubscription, SuspendOrganisation, Validator},
+        entitlements::Usage,
+        enums::Subscription,
+        orgs::{Organisation, OrganisationQuery},
+        platform::{PlatformAction, PlatformAudit, PlatformAuditQuery},
+        users::User,
+    },
+    views::platform::{ImpersonationResponse, OrganisationDetails, OrganisationResponse},
+};
+
+/// Lists every organisation, optionally filtered by `subscription`,
+/// `suspended` and a `search` on the name or email.
+#[debug_handler]
+async fn list(
+    State(ctx): State<AppContext>,
+    Query(conditions): Query<OrganisationQuery>,
+) -> Result<Response> {
+    let organisations = Organisation::read_all(&ctx.db, &conditions).await?;
+
+    let organisations = organisations
+        .iter()
+        .map(OrganisationResponse::new)
+        .collect::<Vec<_>>();
+
+    Ok((StatusCode::OK, Json(organisations)).into_response())
+}
+
+#[debug_handler]
+async fn one(State(ctx): State<AppContext>, Path(pid): Path<Uuid>) -> Result<Response> {
+    let organisation = Organisation::find_by_pid(&ctx.db, pid).await?;
+    let usage = Usage::for_organisation(&ctx.db, pid).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(OrganisationDetails::new(&organisation, usage)),
+    )
+        .into_response())
+}
+
+#[debug_handler]
+async fn change_subscription(
+    operator: User,
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+    Json(params): Json<ChangeSubscription<'static>>,
+) -> Result<Response> {
+    let subscription = Subscription::try_from(params.subscription.to_string())?;
+
+    let mut txn = ctx.db.begin().await?;
+
+    let previous = Organisation::find_by_pid(&mut *txn, pid)
+        .await?
+        .subscription();
+    let organisation = Organisation::change_subscription(&mut *txn, pid, &subscription).await?;
+
+    PlatformAudit::record(
+        &mut *txn,
+        operator.pid,
+        pid,
+        None,
+        PlatformAction::ChangeSubscription,
+        json!({ "from": previous.to_string(), "to": subscription.to_string() }),
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    let usage = Usage::for_organisation(&ctx.db, pid).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(OrganisationDetails::new(&organisation, usage)),
+    )
+        .into_response())
+}
+
+#[debug_handler]
+async fn suspend(
+    operator: User,
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+    Json(params): Json<SuspendOrganisation<'static>>,
+) -> Result<Response> {
+    let validator = Validator::new(&params);
+    let params = validator.validate()?;
+
+    if pid == operator.organisation_pid {
+        return Err(
+            ModelError::Validation("You cannot suspend your own organisation".into()).into(),
+        );
+    }
+
+    let reason = params.reason.as_deref().map(str::trim);
+
+    let mut txn = ctx.db.begin().await?;
+
+    let organisation = Organisation::suspend(&mut *txn, pid, reason).await?;
+
+    PlatformAudit::record(
+        &mut *txn,
+        operator.pid,
+        pid,
+        None,
+        PlatformAction::Suspend,
+        json!({ "reason": reason }),
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(OrganisationResponse::new(&organisation)),
+    )
+        .into_response())
+}
+
+#[debug_handler]
+async fn unsuspend(
+    operator: User,
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    let mut txn = ctx.db.begin().await?;
+
+    let organisation = Organisation::unsuspend(&mut *txn, pid).await?;
+
+    PlatformAudit::record(
+        &mut *txn,
+        operator.pid,
+        pid,
+        None,
+        PlatformAction::Unsuspend,
+        json!({}),
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(OrganisationResponse::new(&organisation)),
+    )
+        .into_response())
+}
+
+/// Issues a short-lived access token for the organisation's admin so an
+/// operator can see what they see. No refresh token is issued, so the session
+/// ends when the token expires.
+#[debug_handler]
+async fn impersonate(
+    operator: User,
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    let mut txn = ctx.db.begin().await?;
+
+    let organisation = Organisation::find_by_pid(&mut *txn, pid).await?;
+    let admin = User::find_organisation_admin(&mut *txn, pid).await?;
+
+    let access_token = ctx.auth.access.impersonation_jwt(&admin, &operator)?;
+
+    PlatformAudit::record(
+        &mut *txn,
+        operator.pid,
+        pid,
+        Some(admin.pid),
+        PlatformAction::Impersonate,
+        json!({ "organisation": organisation.name, "email": admin.email }),
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    tracing::warn!(
+        "Operator {} is impersonating {} of {}",
+        operator.email,
+        admin.email,
+        organisation.name
+    );
+
+    Ok((
+        StatusCode::OK,
+        Json(ImpersonationResponse::new(
+            access_token,
+            ctx.auth.access.exp,
+            &admin,
+        )),
+    )
+        .into_response())
+}
+
+/// Deletes the organisation along with its users, animals and records.
+#[debug_handler]
+async fn remove(
+    operator: User,
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    if pid == operator.organisation_pid {
+        return Err(
+            ModelError::Validation("You cannot delete your own organisation".into()).into(),
+        );
+    }
+
+    let mut txn = ctx.db.begin().await?;
+
+    let organisation = Organisation::find_by_pid(&mut *txn, pid).await?;
+
+    Organisation::delete_by_id(&mut *txn, organisation.id).await?;
+
+    PlatformAudit::record(
+        &mut *txn,
+        operator.pid,
+        pid,
+        None,
+        PlatformAction::DeleteOrganisation,
+        json!({ "name": organisation.name }),
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::NO_CONTENT, Json(json!({}))).into_response())
+}
+
+/// Lists what operators have done, newest first.
+#[debug_handler]
+async fn audit(
+    State(ctx): State<AppContext>,
+    Query(conditions): Query<PlatformAuditQuery>,
+) -> Result<Response> {
+    let logs = PlatformAudit::find_all(&ctx.db, &conditions).await?;
+
+    Ok((StatusCode::OK, Json(logs)).into_response())
+}
+
+pub fn router(ctx: AppContext) -> Router {
+    Router::new()
+        .route("/organisations", get(list))
+        .route("/organisations/{pid}", get(one))
+        .route("/organisations/{pid}", delete(remove))
+        .route(
+            "/organisations/{pid}/subscription",
+            patch(change_subscription),
+        )
+        .route("/organisations/{pid}/suspend", patch(suspend))
+        .route("/organisations/{pid}/unsuspend", patch(unsuspend))
+        .route("/organisations/{pid}/impersonate", post(impersonate))
+        .route("/audit", get(audit))
+        .route_layer(OperatorLayer::new())
+        .with_state(ctx)
+}
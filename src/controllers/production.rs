@@ -9,9 +9,13 @@ use serde_json::json;
 
 use crate::{
     AppContext, Result,
+    middlewares::PermissionLayer,
     models::{
         dto::records::{NewProductionRecord, UpdateProductionRecord},
         production::{ProductionQuery, ProductionRecord},
+        roles::{Action, Resource},
+        settings::OrganisationSettings,
+        tenant::TenantTransaction,
         users::User,
     },
 };
@@ -34,46 +38,70 @@ async fn one(user: User, State(ctx): State<AppContext>, Path(id): Path<i32>) ->
     Ok((StatusCode::OK, Json(item)).into_response())
 }
 
-#[debug_handler]
+#[debug_handler(state = AppContext)]
 async fn add(
     user: User,
-    State(ctx): State<AppContext>,
-    Json(params): Json<NewProductionRecord<'static>>,
+    mut txn: TenantTransaction,
+    Json(mut params): Json<NewProductionRecord<'static>>,
 ) -> Result<Response> {
-    let item = ProductionRecord::create(&ctx.db, &params, user.organisation_pid, user.pid).await?;
+    if params.record_date.is_none() {
+        let settings = OrganisationSettings::find(&mut *txn, user.organisation_pid).await?;
+        params.record_date = Some(settings.today().to_string().into());
+    }
+
+    let item =
+        ProductionRecord::create(&mut *txn, &params, user.organisation_pid, user.pid).await?;
+
+    txn.commit().await?;
 
     Ok((StatusCode::CREATED, Json(item)).into_response())
 }
 
-#[debug_handler]
-async fn remove(
-    user: User,
-    State(ctx): State<AppContext>,
-    Path(id): Path<i32>,
-) -> Result<Response> {
-    let _query = ProductionRecord::delete_by_id(&ctx.db, id, user.organisation_pid).await?;
+#[debug_handler(state = AppContext)]
+async fn remove(user: User, mut txn: TenantTransaction, Path(id): Path<i32>) -> Result<Response> {
+    let _query =
+        ProductionRecord::delete_by_id(&mut *txn, id, user.organisation_pid, user.pid).await?;
+
+    txn.commit().await?;
 
     Ok((StatusCode::NO_CONTENT, Json(json!({}))).into_response())
 }
 
-#[debug_handler]
+#[debug_handler(state = AppContext)]
 async fn update(
     user: User,
-    State(ctx): State<AppContext>,
+    mut txn: TenantTransaction,
     Path(id): Path<i32>,
     Json(params): Json<UpdateProductionRecord<'static>>,
 ) -> Result<Response> {
-    let model = ProductionRecord::update_by_id(&ctx.db, id, user.organisation_pid, &params).await?;
+    let model =
+        ProductionRecord::update_by_id(&mut txn, id, user.organisation_pid, &params).await?;
+
+    txn.commit().await?;
 
     Ok((StatusCode::CREATED, Json(model)).into_response())
 }
 
+#[debug_handler(state = AppContext)]
+async fn restore(user: User, mut txn: TenantTransaction, Path(id): Path<i32>) -> Result<Response> {
+    let model = ProductionRecord::restore_by_id(&mut txn, id, user.organisation_pid).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::OK, Json(model)).into_response())
+}
+
 pub fn router(ctx: AppContext) -> Router {
+    let can_read = PermissionLayer::new(Resource::ProductionRecords, Action::Read);
+    let can_write = PermissionLayer::new(Resource::ProductionRecords, Action::Write);
+    let can_delete = PermissionLayer::new(Resource::ProductionRecords, Action::Delete);
+
     Router::new()
-        .route("/", get(all))
-        .route("/", post(add))
-        .route("/{id}", get(one))
-        .route("/{id}", delete(remove))
-        .route("/{id}", patch(update))
+        .route("/", get(all).layer(can_read))
+        .route("/", post(add).layer(can_write))
+        .route("/{id}", get(one).layer(can_read))
+        .route("/{id}", delete(remove).layer(can_delete))
+        .route("/{id}/restore", patch(restore).layer(can_delete))
+        .route("/{id}", patch(update).layer(can_write))
         .with_state(ctx)
 }
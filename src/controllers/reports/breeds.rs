@@ -9,7 +9,15 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     AppContext, Result,
-    models::{BreedSummary, BreedSummaryQuery, users::User},
+    middlewares::{EntitlementLayer, PermissionLayer},
+    models::{
+        BreedSummary, BreedSummaryQuery, LiveSummary,
+        entitlements::{Entitlement, ReportType},
+        roles::{Action, Resource},
+        trends::{Trend, TrendQuery, TrendSource},
+        users::User,
+    },
+    pdf::PdfReport,
 };
 
 #[derive(Debug, Deserialize, Clone, Serialize)]
@@ -46,9 +54,55 @@ async fn all(
     Ok((StatusCode::OK, Json(reports)).into_response())
 }
 
+#[debug_handler]
+async fn live(
+    State(ctx): State<AppContext>,
+    user: User,
+    Query(params): Query<BreedSummaryQuery>,
+) -> Result<Response> {
+    let summaries = LiveSummary::find_breeds(&ctx.db, user.organisation_pid, &params).await?;
+
+    Ok((StatusCode::OK, Json(summaries)).into_response())
+}
+
+#[debug_handler]
+async fn trends(
+    State(ctx): State<AppContext>,
+    user: User,
+    Query(params): Query<TrendQuery>,
+) -> Result<Response> {
+    let trend = Trend::find(&ctx.db, user.organisation_pid, TrendSource::Breeds, &params).await?;
+
+    Ok((StatusCode::OK, Json(trend)).into_response())
+}
+
+#[debug_handler]
+async fn pdf(
+    State(ctx): State<AppContext>,
+    user: User,
+    Query(params): Query<BreedSummaryQuery>,
+) -> Result<Response> {
+    let mut report = PdfReport::breeds(&ctx.db, user.organisation_pid, &params).await?;
+    if let Some(subject) = params.breed.as_ref().or(params.specie.as_ref()) {
+        report = report.about(subject);
+    }
+
+    Ok(report.render()?.into_response())
+}
+
 pub fn router(ctx: AppContext) -> Router {
+    let can_read = PermissionLayer::new(Resource::Reports, Action::Read);
+    let can_generate = PermissionLayer::new(Resource::Reports, Action::Generate);
+    let in_plan = EntitlementLayer::new(&ctx, Entitlement::Report(ReportType::Breeds));
+
     Router::new()
-        .route("/", post(add))
-        .route("/", get(all))
+        .route("/", post(add).layer(in_plan.clone()).layer(can_generate))
+        .route("/", get(all).layer(in_plan.clone()).layer(can_read))
+        .route("/live", get(live).layer(in_plan.clone()).layer(can_read))
+        .route(
+            "/trends",
+            get(trends).layer(in_plan.clone()).layer(can_read),
+        )
+        .route("/pdf", get(pdf).layer(in_plan).layer(can_read))
         .with_state(ctx)
 }
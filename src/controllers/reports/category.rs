@@ -9,7 +9,15 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     AppContext, Result,
-    models::{SpecieSummary, users::User},
+    middlewares::{EntitlementLayer, PermissionLayer},
+    models::{
+        LiveSummary, SpecieSummary,
+        entitlements::{Entitlement, ReportType},
+        roles::{Action, Resource},
+        trends::{Trend, TrendQuery, TrendSource},
+        users::User,
+    },
+    pdf::PdfReport,
 };
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -17,6 +25,11 @@ struct CategoryReportQuery {
     specie: String,
 }
 
+#[derive(Debug, Deserialize, Serialize, Clone)]
+struct LiveReportQuery {
+    specie: Option<String>,
+}
+
 #[debug_handler]
 async fn add(
     State(ctx): State<AppContext>,
@@ -39,9 +52,63 @@ async fn all(
     Ok((StatusCode::OK, Json(report)).into_response())
 }
 
+#[debug_handler]
+async fn live(
+    State(ctx): State<AppContext>,
+    user: User,
+    Query(params): Query<LiveReportQuery>,
+) -> Result<Response> {
+    let summaries =
+        LiveSummary::find_species(&ctx.db, user.organisation_pid, params.specie.as_deref()).await?;
+
+    Ok((StatusCode::OK, Json(summaries)).into_response())
+}
+
+#[debug_handler]
+async fn trends(
+    State(ctx): State<AppContext>,
+    user: User,
+    Query(params): Query<TrendQuery>,
+) -> Result<Response> {
+    let trend = Trend::find(
+        &ctx.db,
+        user.organisation_pid,
+        TrendSource::Species,
+        &params,
+    )
+    .await?;
+
+    Ok((StatusCode::OK, Json(trend)).into_response())
+}
+
+#[debug_handler]
+async fn pdf(
+    State(ctx): State<AppContext>,
+    user: User,
+    Query(params): Query<LiveReportQuery>,
+) -> Result<Response> {
+    let mut report =
+        PdfReport::species(&ctx.db, user.organisation_pid, params.specie.as_deref()).await?;
+    if let Some(specie) = &params.specie {
+        report = report.about(specie);
+    }
+
+    Ok(report.render()?.into_response())
+}
+
 pub fn router(ctx: AppContext) -> Router {
+    let can_read = PermissionLayer::new(Resource::Reports, Action::Read);
+    let can_generate = PermissionLayer::new(Resource::Reports, Action::Generate);
+    let in_plan = EntitlementLayer::new(&ctx, Entitlement::Report(ReportType::Categories));
+
     Router::new()
-        .route("/", get(all))
-        .route("/", post(add))
+        .route("/", get(all).layer(in_plan.clone()).layer(can_read))
+        .route("/", post(add).layer(in_plan.clone()).layer(can_generate))
+        .route("/live", get(live).layer(in_plan.clone()).layer(can_read))
+        .route(
+            "/trends",
+            get(trends).layer(in_plan.clone()).layer(can_read),
+        )
+        .route("/pdf", get(pdf).layer(in_plan).layer(can_read))
         .with_state(ctx)
 }
@@ -1,6 +1,6 @@
 use axum::{
     Json, Router, debug_handler,
-    extract::State,
+    extract::{Query, State},
     http::StatusCode,
     response::{IntoResponse, Response},
     routing::{get, post},
@@ -8,26 +8,80 @@ use axum::{
 
 use crate::{
     AppContext, Result,
-    models::{livestock::LivestockSummary, users::User},
+    middlewares::PermissionLayer,
+    models::{
+        LiveSummary,
+        livestock::LivestockSummary,
+        roles::{Action, Resource},
+        settings::OrganisationSettings,
+        trends::{Trend, TrendQuery, TrendSource},
+        users::User,
+    },
+    pdf::PdfReport,
+    views::reports::LivestockReport,
 };
 
 #[debug_handler]
 async fn all(State(ctx): State<AppContext>, user: User) -> Result<Response> {
-    let reports = LivestockSummary::find_all(&ctx.db, user.organisation_pid).await?;
+    let settings = OrganisationSettings::find(&ctx.db, user.organisation_pid).await?;
+    let reports = LivestockSummary::find_all(&ctx.db, user.organisation_pid)
+        .await?
+        .into_iter()
+        .map(|summary| LivestockReport::new(summary, &settings))
+        .collect::<Vec<_>>();
 
     Ok((StatusCode::OK, Json(reports)).into_response())
 }
 
 #[debug_handler]
 async fn add(State(ctx): State<AppContext>, user: User) -> Result<Response> {
+    let settings = OrganisationSettings::find(&ctx.db, user.organisation_pid).await?;
     let report = LivestockSummary::generate(&ctx.db, user.organisation_pid).await?;
+    let report = LivestockReport::new(report, &settings);
 
     Ok((StatusCode::CREATED, Json(report)).into_response())
 }
 
+#[debug_handler]
+async fn live(State(ctx): State<AppContext>, user: User) -> Result<Response> {
+    let summary = LiveSummary::find_livestock(&ctx.db, user.organisation_pid).await?;
+
+    Ok((StatusCode::OK, Json(summary)).into_response())
+}
+
+#[debug_handler]
+async fn trends(
+    State(ctx): State<AppContext>,
+    user: User,
+    Query(params): Query<TrendQuery>,
+) -> Result<Response> {
+    let trend = Trend::find(
+        &ctx.db,
+        user.organisation_pid,
+        TrendSource::Livestock,
+        &params,
+    )
+    .await?;
+
+    Ok((StatusCode::OK, Json(trend)).into_response())
+}
+
+#[debug_handler]
+async fn pdf(State(ctx): State<AppContext>, user: User) -> Result<Response> {
+    let report = PdfReport::herd(&ctx.db, user.organisation_pid).await?;
+
+    Ok(report.render()?.into_response())
+}
+
 pub fn router(ctx: AppContext) -> Router {
+    let can_read = PermissionLayer::new(Resource::Reports, Action::Read);
+    let can_generate = PermissionLayer::new(Resource::Reports, Action::Generate);
+
     Router::new()
-        .route("/", get(all))
-        .route("/", post(add))
+        .route("/", get(all).layer(can_read))
+        .route("/", post(add).layer(can_generate))
+        .route("/live", get(live).layer(can_read))
+        .route("/trends", get(trends).layer(can_read))
+        .route("/pdf", get(pdf).layer(can_read))
         .with_state(ctx)
 }
@@ -9,10 +9,12 @@ use crate::AppContext;
 pub mod breeds;
 pub mod category;
 pub mod livestock;
+pub mod records;
 
 pub fn router(ctx: Arc<AppContext>) -> Router {
     Router::new()
         .nest("/categories", category::router((*ctx).clone()))
         .nest("/breeds", breeds::router((*ctx).clone()))
         .nest("/livestock", livestock::router((*ctx).clone()))
+        .merge(records::router((*ctx).clone()))
 }
@@ -0,0 +1,55 @@
+use axum::{
+    Router, debug_handler,
+    extract::{Path, Query, State},
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use uuid::Uuid;
+
+use crate::{
+    AppContext, Result,
+    middlewares::PermissionLayer,
+    models::{
+        roles::{Action, Resource},
+        users::User,
+    },
+    pdf::{PdfReport, PeriodQuery},
+};
+
+#[debug_handler]
+async fn health(
+    State(ctx): State<AppContext>,
+    user: User,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    let report = PdfReport::health(&ctx.db, user.organisation_pid, pid).await?;
+
+    Ok(report.render()?.into_response())
+}
+
+#[debug_handler]
+async fn production(
+    State(ctx): State<AppContext>,
+    user: User,
+    Query(params): Query<PeriodQuery>,
+) -> Result<Response> {
+    let report = PdfReport::production(&ctx.db, user.organisation_pid, params).await?;
+
+    Ok(report.render()?.into_response())
+}
+
+pub fn router(ctx: AppContext) -> Router {
+    Router::new()
+        .route(
+            "/animals/{pid}/health/pdf",
+            get(health).layer(PermissionLayer::new(Resource::HealthRecords, Action::Read)),
+        )
+        .route(
+            "/production/pdf",
+            get(production).layer(PermissionLayer::new(
+                Resource::ProductionRecords,
+                Action::Read,
+            )),
+        )
+        .with_state(ctx)
+}
@@ -0,0 +1,92 @@
+use axum::{
+    Json, Router, debug_handler,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{delete, get, patch, post},
+};
+use serde_json::json;
+
+use crate::{
+    AppContext, Result,
+    middlewares::PermissionLayer,
+    models::{
+        dto::{CreateRole, UpdateRole},
+        roles::{Action, Resource, Role},
+        users::User,
+    },
+    views::roles::{PermissionCatalogue, RoleResponse},
+};
+
+/// Lists the system roles and the organisation's custom roles.
+#[debug_handler]
+async fn all(State(ctx): State<AppContext>, user: User) -> Result<Response> {
+    let roles = Role::find_by_organisation(&ctx.db, user.organisation_pid).await?;
+
+    let roles = roles.iter().map(RoleResponse::new).collect::<Vec<_>>();
+
+    Ok((StatusCode::OK, Json(roles)).into_response())
+}
+
+/// Lists every `resource:action` permission a custom role can be granted.
+#[debug_handler]
+async fn permissions() -> Result<Response> {
+    Ok((StatusCode::OK, Json(PermissionCatalogue::all())).into_response())
+}
+
+#[debug_handler]
+async fn one(State(ctx): State<AppContext>, user: User, Path(id): Path<i32>) -> Result<Response> {
+    let role = Role::find_by_id_for_organisation(&ctx.db, user.organisation_pid, id).await?;
+
+    Ok((StatusCode::OK, Json(RoleResponse::new(&role))).into_response())
+}
+
+#[debug_handler]
+async fn add(
+    State(ctx): State<AppContext>,
+    user: User,
+    Json(params): Json<CreateRole<'static>>,
+) -> Result<Response> {
+    let role = Role::create(&ctx.db, user.organisation_pid, &params).await?;
+
+    Ok((StatusCode::CREATED, Json(RoleResponse::new(&role))).into_response())
+}
+
+#[debug_handler]
+async fn update(
+    State(ctx): State<AppContext>,
+    user: User,
+    Path(id): Path<i32>,
+    Json(params): Json<UpdateRole<'static>>,
+) -> Result<Response> {
+    let role = Role::find_custom(&ctx.db, user.organisation_pid, id).await?;
+
+    let role = role.update(&ctx.db, &params).await?;
+
+    Ok((StatusCode::OK, Json(RoleResponse::new(&role))).into_response())
+}
+
+#[debug_handler]
+async fn remove(
+    State(ctx): State<AppContext>,
+    user: User,
+    Path(id): Path<i32>,
+) -> Result<Response> {
+    Role::delete_by_id(&ctx.db, user.organisation_pid, id).await?;
+
+    Ok((StatusCode::NO_CONTENT, Json(json!({}))).into_response())
+}
+
+pub fn router(ctx: AppContext) -> Router {
+    let can_read = PermissionLayer::new(Resource::Roles, Action::Read);
+    let can_manage = PermissionLayer::new(Resource::Roles, Action::Manage);
+
+    Router::new()
+        .route("/", get(all).layer(can_read))
+        .route("/", post(add).layer(can_manage))
+        .route("/permissions", get(permissions).layer(can_read))
+        .route("/{id}", get(one).layer(can_read))
+        .route("/{id}", patch(update).layer(can_manage))
+        .route("/{id}", delete(remove).layer(can_manage))
+        .with_state(ctx)
+}
@@ -1,18 +1,22 @@
 use axum::{
-    Extension, Json, Router, debug_handler,
+    Json, Router, debug_handler,
     extract::{Path, State},
     http::StatusCode,
     response::{IntoResponse, Response},
     routing::get,
 };
 
-use crate::{AppContext, Result, middlewares::TokenClaims, models::species::Specie};
+use crate::{
+    AppContext, Result,
+    middlewares::PermissionLayer,
+    models::{
+        roles::{Action, Resource},
+        species::Specie,
+    },
+};
 
 #[debug_handler]
-async fn all(
-    State(ctx): State<AppContext>,
-    Extension(_auth): Extension<TokenClaims>,
-) -> Result<Response> {
+async fn all(State(ctx): State<AppContext>) -> Result<Response> {
     let species = Specie::find_by_all(&ctx.db).await?;
 
     Ok((StatusCode::OK, Json(species)).into_response())
@@ -26,8 +30,10 @@ async fn one(State(ctx): State<AppContext>, Path(id): Path<i32>) -> Result<Respo
 }
 
 pub fn router(ctx: AppContext) -> Router {
+    let can_read = PermissionLayer::new(Resource::Breeds, Action::Read);
+
     Router::new()
-        .route("/", get(all))
-        .route("/{id}", get(one))
+        .route("/", get(all).layer(can_read))
+        .route("/{id}", get(one).layer(can_read))
         .with_state(ctx)
 }
@@ -0,0 +1,30 @@
+use axum::{
+    Json, Router, debug_handler,
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+};
+
+use crate::{
+    AppContext, Result,
+    models::{entitlements::Usage, orgs::Organisation, users::User},
+    views::subscription::SubscriptionResponse,
+};
+
+/// The organisation's plan, its limits and how much of them is in use.
+#[debug_handler]
+async fn current(State(ctx): State<AppContext>, user: User) -> Result<Response> {
+    let organisation = Organisation::find_by_pid(&ctx.db, user.organisation_pid).await?;
+    let usage = Usage::for_organisation(&ctx.db, user.organisation_pid).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(SubscriptionResponse::new(&organisation, usage)),
+    )
+        .into_response())
+}
+
+pub fn router(ctx: AppContext) -> Router {
+    Router::new().route("/", get(current)).with_state(ctx)
+}
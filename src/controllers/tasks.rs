@@ -0,0 +1,255 @@
+use axum::{
+    Json, Router, debug_handler,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{delete, get, patch, post},
+};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{
+    AppContext, Result,
+    middlewares::PermissionLayer,
+    models::{
+        dto::{
+            CompleteTask, NewRecurringTask, NewTask, NewVaccinationSchedule, TaskQuery,
+            UpdateRecurringTask, UpdateTask, UpdateVaccinationSchedule,
+        },
+        roles::{Action, Resource},
+        settings::OrganisationSettings,
+        tasks::{OverdueTasks, RecurringTask, Task, VaccinationSchedule},
+        tenant::TenantTransaction,
+        users::User,
+    },
+};
+
+#[debug_handler]
+async fn list(
+    user: User,
+    State(ctx): State<AppContext>,
+    Query(params): Query<TaskQuery>,
+) -> Result<Response> {
+    let tasks = Task::find_all(&ctx.db, user.organisation_pid, &params).await?;
+
+    Ok((StatusCode::OK, Json(tasks)).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn add(
+    user: User,
+    mut txn: TenantTransaction,
+    Json(params): Json<NewTask<'static>>,
+) -> Result<Response> {
+    let task = Task::create(&mut txn, user.organisation_pid, user.pid, &params).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::CREATED, Json(task)).into_response())
+}
+
+/// The user's unfinished tasks due today or earlier, in the organisation's
+/// timezone.
+#[debug_handler]
+async fn today(user: User, State(ctx): State<AppContext>) -> Result<Response> {
+    let today = OrganisationSettings::find(&ctx.db, user.organisation_pid)
+        .await?
+        .today();
+    let tasks = Task::find_today(&ctx.db, user.organisation_pid, user.pid, today).await?;
+
+    Ok((StatusCode::OK, Json(tasks)).into_response())
+}
+
+#[debug_handler]
+async fn overdue(user: User, State(ctx): State<AppContext>) -> Result<Response> {
+    let today = OrganisationSettings::find(&ctx.db, user.organisation_pid)
+        .await?
+        .today();
+    let overdue = OverdueTasks::find_all(&ctx.db, user.organisation_pid, today).await?;
+
+    Ok((StatusCode::OK, Json(overdue)).into_response())
+}
+
+#[debug_handler]
+async fn get_one(
+    user: User,
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    let task = Task::find_by_pid(&ctx.db, user.organisation_pid, pid).await?;
+
+    Ok((StatusCode::OK, Json(task)).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn update(
+    user: User,
+    mut txn: TenantTransaction,
+    Path(pid): Path<Uuid>,
+    Json(params): Json<UpdateTask<'static>>,
+) -> Result<Response> {
+    let task = Task::update(&mut txn, user.organisation_pid, pid, &params).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::OK, Json(task)).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn remove(user: User, mut txn: TenantTransaction, Path(pid): Path<Uuid>) -> Result<Response> {
+    Task::delete_by_pid(&mut txn, user.organisation_pid, pid).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::NO_CONTENT, Json(json!({}))).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn complete(
+    user: User,
+    mut txn: TenantTransaction,
+    Path(pid): Path<Uuid>,
+    Json(params): Json<CompleteTask<'static>>,
+) -> Result<Response> {
+    let completion =
+        Task::complete(&mut txn, user.organisation_pid, user.pid, pid, &params).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::OK, Json(completion)).into_response())
+}
+
+#[debug_handler]
+async fn list_recurring(user: User, State(ctx): State<AppContext>) -> Result<Response> {
+    let recurring = RecurringTask::find_all(&ctx.db, user.organisation_pid).await?;
+
+    Ok((StatusCode::OK, Json(recurring)).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn add_recurring(
+    user: User,
+    mut txn: TenantTransaction,
+    Json(params): Json<NewRecurringTask<'static>>,
+) -> Result<Response> {
+    let recurring =
+        RecurringTask::create(&mut txn, user.organisation_pid, user.pid, &params).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::CREATED, Json(recurring)).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn update_recurring(
+    user: User,
+    mut txn: TenantTransaction,
+    Path(pid): Path<Uuid>,
+    Json(params): Json<UpdateRecurringTask<'static>>,
+) -> Result<Response> {
+    let recurring = RecurringTask::update(&mut txn, user.organisation_pid, pid, &params).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::OK, Json(recurring)).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn remove_recurring(
+    user: User,
+    mut txn: TenantTransaction,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    RecurringTask::delete_by_pid(&mut txn, user.organisation_pid, pid).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::NO_CONTENT, Json(json!({}))).into_response())
+}
+
+#[debug_handler]
+async fn list_vaccinations(user: User, State(ctx): State<AppContext>) -> Result<Response> {
+    let schedules = VaccinationSchedule::find_all(&ctx.db, user.organisation_pid).await?;
+
+    Ok((StatusCode::OK, Json(schedules)).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn add_vaccination(
+    user: User,
+    mut txn: TenantTransaction,
+    Json(params): Json<NewVaccinationSchedule<'static>>,
+) -> Result<Response> {
+    let schedule =
+        VaccinationSchedule::create(&mut txn, user.organisation_pid, user.pid, &params).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::CREATED, Json(schedule)).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn update_vaccination(
+    user: User,
+    mut txn: TenantTransaction,
+    Path(pid): Path<Uuid>,
+    Json(params): Json<UpdateVaccinationSchedule<'static>>,
+) -> Result<Response> {
+    let schedule =
+        VaccinationSchedule::update(&mut txn, user.organisation_pid, pid, &params).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::OK, Json(schedule)).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn remove_vaccination(
+    user: User,
+    mut txn: TenantTransaction,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    VaccinationSchedule::delete_by_pid(&mut txn, user.organisation_pid, pid).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::NO_CONTENT, Json(json!({}))).into_response())
+}
+
+pub fn router(ctx: AppContext) -> Router {
+    let can_read = PermissionLayer::new(Resource::Tasks, Action::Read);
+    let can_write = PermissionLayer::new(Resource::Tasks, Action::Write);
+    let can_delete = PermissionLayer::new(Resource::Tasks, Action::Delete);
+    let can_manage = PermissionLayer::new(Resource::Tasks, Action::Manage);
+
+    Router::new()
+        .route("/", get(list).layer(can_read))
+        .route("/", post(add).layer(can_write))
+        .route("/today", get(today).layer(can_read))
+        .route("/overdue", get(overdue).layer(can_manage))
+        .route("/recurring", get(list_recurring).layer(can_read))
+        .route("/recurring", post(add_recurring).layer(can_manage))
+        .route(
+            "/recurring/{pid}",
+            patch(update_recurring).layer(can_manage),
+        )
+        .route(
+            "/recurring/{pid}",
+            delete(remove_recurring).layer(can_manage),
+        )
+        .route("/vaccinations", get(list_vaccinations).layer(can_read))
+        .route("/vaccinations", post(add_vaccination).layer(can_manage))
+        .route(
+            "/vaccinations/{pid}",
+            patch(update_vaccination).layer(can_manage),
+        )
+        .route(
+            "/vaccinations/{pid}",
+            delete(remove_vaccination).layer(can_manage),
+        )
+        .route("/{pid}", get(get_one).layer(can_read))
+        .route("/{pid}", patch(update).layer(can_write))
+        .route("/{pid}", delete(remove).layer(can_delete))
+        .route("/{pid}/complete", post(complete).layer(can_write))
+        .with_state(ctx)
+}
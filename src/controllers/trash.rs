@@ -0,0 +1,47 @@
+use axum::{
+    Extension, Json, Router, debug_handler,
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+};
+
+use crate::{
+    AppContext, Result,
+    models::{
+        roles::{Action, Role},
+        tenant::TenantTransaction,
+        trash::{TRASHABLE, Trash, TrashQuery},
+        users::User,
+    },
+};
+
+/// Lists the trashed animals and records the user could have deleted.
+#[debug_handler]
+async fn all(
+    State(ctx): State<AppContext>,
+    user: User,
+    Extension(role): Extension<Role>,
+    mut txn: TenantTransaction,
+    Query(conditions): Query<TrashQuery>,
+) -> Result<Response> {
+    let resources = TRASHABLE
+        .into_iter()
+        .filter(|resource| conditions.resource.is_none_or(|wanted| wanted == *resource))
+        .filter(|resource| role.allows(*resource, Action::Delete))
+        .collect::<Vec<_>>();
+
+    let items = Trash::find_all(
+        &mut *txn,
+        user.organisation_pid,
+        &resources,
+        ctx.config.trash().retention_days(),
+    )
+    .await?;
+
+    Ok((StatusCode::OK, Json(items)).into_response())
+}
+
+pub fn router(ctx: AppContext) -> Router {
+    Router::new().route("/", get(all)).with_state(ctx)
+}
@@ -0,0 +1,149 @@
+use axum::{
+    Json, Router, debug_handler,
+    extract::Path,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{delete, get, patch, post},
+};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{
+    AppContext, Result,
+    middlewares::PermissionLayer,
+    models::{
+        dto::{CreateWebhook, UpdateWebhook},
+        events::EventType,
+        roles::{Action, Resource},
+        tenant::TenantTransaction,
+        users::User,
+        webhooks::{Webhook, WebhookDelivery},
+    },
+    views::webhook::{CreatedWebhookResponse, WebhookDeliveryResponse, WebhookResponse},
+};
+
+/// Subscribes a partner endpoint to some of the organisation's events. The
+/// response carries the secret deliveries are signed with.
+#[debug_handler(state = AppContext)]
+async fn create(
+    user: User,
+    mut txn: TenantTransaction,
+    Json(params): Json<CreateWebhook<'static>>,
+) -> Result<Response> {
+    let webhook = Webhook::create(&mut *txn, &user, &params).await?;
+
+    txn.commit().await?;
+
+    tracing::info!("{} added a webhook for {}", user.email, webhook.url);
+
+    Ok((
+        StatusCode::CREATED,
+        Json(CreatedWebhookResponse::new(&webhook)),
+    )
+        .into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn list(user: User, mut txn: TenantTransaction) -> Result<Response> {
+    let webhooks = Webhook::find_all(&mut *txn, user.organisation_pid).await?;
+
+    let webhooks = webhooks
+        .iter()
+        .map(WebhookResponse::new)
+        .collect::<Vec<_>>();
+
+    Ok((StatusCode::OK, Json(webhooks)).into_response())
+}
+
+/// Lists the event types webhooks can subscribe to.
+#[debug_handler]
+async fn event_types() -> Result<Response> {
+    let event_types = EventType::ALL
+        .iter()
+        .map(EventType::as_str)
+        .collect::<Vec<_>>();
+
+    Ok((StatusCode::OK, Json(event_types)).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn one(user: User, mut txn: TenantTransaction, Path(pid): Path<Uuid>) -> Result<Response> {
+    let webhook = Webhook::find_by_pid(&mut *txn, user.organisation_pid, pid).await?;
+
+    Ok((StatusCode::OK, Json(WebhookResponse::new(&webhook))).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn update(
+    user: User,
+    mut txn: TenantTransaction,
+    Path(pid): Path<Uuid>,
+    Json(params): Json<UpdateWebhook<'static>>,
+) -> Result<Response> {
+    let webhook = Webhook::update(&mut *txn, user.organisation_pid, pid, &params).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::OK, Json(WebhookResponse::new(&webhook))).into_response())
+}
+
+#[debug_handler(state = AppContext)]
+async fn remove(user: User, mut txn: TenantTransaction, Path(pid): Path<Uuid>) -> Result<Response> {
+    Webhook::delete_by_pid(&mut *txn, user.organisation_pid, pid).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::NO_CONTENT, Json(json!({}))).into_response())
+}
+
+/// The webhook's delivery log, newest first.
+#[debug_handler(state = AppContext)]
+async fn deliveries(
+    user: User,
+    mut txn: TenantTransaction,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    let deliveries = WebhookDelivery::find_by_webhook(&mut txn, user.organisation_pid, pid).await?;
+
+    let deliveries = deliveries
+        .iter()
+        .map(WebhookDeliveryResponse::new)
+        .collect::<Vec<_>>();
+
+    Ok((StatusCode::OK, Json(deliveries)).into_response())
+}
+
+/// Sends a delivery again, whether it went through or was given up on.
+#[debug_handler(state = AppContext)]
+async fn redeliver(
+    user: User,
+    mut txn: TenantTransaction,
+    Path((pid, delivery)): Path<(Uuid, Uuid)>,
+) -> Result<Response> {
+    let delivery =
+        WebhookDelivery::redeliver(&mut txn, user.organisation_pid, pid, delivery).await?;
+
+    txn.commit().await?;
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(WebhookDeliveryResponse::new(&delivery)),
+    )
+        .into_response())
+}
+
+pub fn router(ctx: AppContext) -> Router {
+    let can_manage = PermissionLayer::new(Resource::Organisation, Action::Manage);
+
+    Router::new()
+        .route("/", post(create))
+        .route("/", get(list))
+        .route("/event-types", get(event_types))
+        .route("/{pid}", get(one))
+        .route("/{pid}", patch(update))
+        .route("/{pid}", delete(remove))
+        .route("/{pid}/deliveries", get(deliveries))
+        .route("/{pid}/deliveries/{delivery}/redeliver", post(redeliver))
+        .route_layer(can_manage)
+        .with_state(ctx)
+}
@@ -9,8 +9,11 @@ use serde_json::json;
 
 use crate::{
     AppContext, Result,
+    middlewares::PermissionLayer,
     models::{
         dto::records::{NewWeightRecord, UpdateWeightRecord},
+        roles::{Action, Resource},
+        tenant::TenantTransaction,
         users::User,
         weight::{WeightQuery, WeightRecord},
     },
@@ -27,13 +30,15 @@ async fn all(
     Ok((StatusCode::OK, Json(models)).into_response())
 }
 
-#[debug_handler]
+#[debug_handler(state = AppContext)]
 async fn add(
     user: User,
-    State(ctx): State<AppContext>,
+    mut txn: TenantTransaction,
     Json(params): Json<NewWeightRecord<'static>>,
 ) -> Result<Response> {
-    let model = WeightRecord::create(&ctx.db, &params, user.organisation_pid, user.pid).await?;
+    let model = WeightRecord::weigh(&mut txn, params, user.organisation_pid, user.pid).await?;
+
+    txn.commit().await?;
 
     Ok((StatusCode::CREATED, Json(model)).into_response())
 }
@@ -45,35 +50,50 @@ async fn one(user: User, State(ctx): State<AppContext>, Path(id): Path<i32>) ->
     Ok((StatusCode::OK, Json(model)).into_response())
 }
 
-#[debug_handler]
-async fn remove(
-    user: User,
-    State(ctx): State<AppContext>,
-    Path(id): Path<i32>,
-) -> Result<Response> {
-    let _result = WeightRecord::delete_by_id(&ctx.db, user.organisation_pid, id).await?;
+#[debug_handler(state = AppContext)]
+async fn remove(user: User, mut txn: TenantTransaction, Path(id): Path<i32>) -> Result<Response> {
+    let _result =
+        WeightRecord::delete_by_id(&mut *txn, user.organisation_pid, id, user.pid).await?;
+
+    txn.commit().await?;
 
     Ok((StatusCode::NO_CONTENT, Json(json!({}))).into_response())
 }
 
-#[debug_handler]
+#[debug_handler(state = AppContext)]
 async fn update(
     user: User,
-    State(ctx): State<AppContext>,
+    mut txn: TenantTransaction,
     Path(id): Path<i32>,
     Json(params): Json<UpdateWeightRecord<'static>>,
 ) -> Result<Response> {
-    let model = WeightRecord::update_by_id(&ctx.db, id, user.organisation_pid, &params).await?;
+    let model = WeightRecord::update_by_id(&mut txn, id, user.organisation_pid, &params).await?;
+
+    txn.commit().await?;
 
     Ok((StatusCode::CREATED, Json(model)).into_response())
 }
 
+#[debug_handler(state = AppContext)]
+async fn restore(user: User, mut txn: TenantTransaction, Path(id): Path<i32>) -> Result<Response> {
+    let model = WeightRecord::restore_by_id(&mut txn, user.organisation_pid, id).await?;
+
+    txn.commit().await?;
+
+    Ok((StatusCode::OK, Json(model)).into_response())
+}
+
 pub fn router(ctx: AppContext) -> Router {
+    let can_read = PermissionLayer::new(Resource::WeightRecords, Action::Read);
+    let can_write = PermissionLayer::new(Resource::WeightRecords, Action::Write);
+    let can_delete = PermissionLayer::new(Resource::WeightRecords, Action::Delete);
+
     Router::new()
-        .route("/", get(all))
-        .route("/", post(add))
-        .route("/{id}", get(one))
-        .route("/{id}", delete(remove))
-        .route("/{id}", patch(update))
+        .route("/", get(all).layer(can_read))
+        .route("/", post(add).layer(can_write))
+        .route("/{id}", get(one).layer(can_read))
+        .route("/{id}", delete(remove).layer(can_delete))
+        .route("/{id}/restore", patch(restore).layer(can_delete))
+        .route("/{id}", patch(update).layer(can_write))
         .with_state(ctx)
 }
@@ -52,6 +52,8 @@ impl IntoResponse for Report {
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
+    #[error("Account deactivated")]
+    AccountDisabled,
     #[error(transparent)]
     Axum(#[from] axum::Error),
     #[error(transparent)]
@@ -74,6 +76,10 @@ pub enum Error {
     MissingCredentials,
     #[error(transparent)]
     Model(#[from] ModelError),
+    #[error("Organisation suspended")]
+    OrganisationSuspended,
+    #[error(transparent)]
+    Reqwest(#[from] reqwest::Error),
     #[error("Invalid login details")]
     WrongCredentials,
     #[error("Unauthorised")]
@@ -96,10 +102,22 @@ impl Error {
                 "Something went wrong on our end",
             ),
             Self::Model(error) => return error.response(),
+            Self::Reqwest(_) => (
+                StatusCode::BAD_GATEWAY,
+                "The identity provider could not be reached",
+            ),
             Self::WrongCredentials => (StatusCode::UNAUTHORIZED, "Wrong email or password"),
             Self::InvalidToken => (StatusCode::BAD_REQUEST, "Invalid auth token"),
             Self::MissingCredentials => (StatusCode::BAD_REQUEST, "Missing credentials"),
             Self::Forbidden => (StatusCode::FORBIDDEN, "You do not have permission"),
+            Self::AccountDisabled => (
+                StatusCode::FORBIDDEN,
+                "This account has been deactivated. Contact your administrator.",
+            ),
+            Self::OrganisationSuspended => (
+                StatusCode::FORBIDDEN,
+                "This organisation has been suspended. Contact support.",
+            ),
             Self::Unauthorised | Self::ExpiredToken => {
                 (StatusCode::UNAUTHORIZED, "Login to continue.")
             }
@@ -0,0 +1,215 @@
+#![allow(clippy::missing_errors_doc)]
+
+//! Runs background work from a queue in Postgres.
+//!
+//! Schedules queue jobs when they are due and workers claim them with
+//! `FOR UPDATE SKIP LOCKED`, so any number of servers and `worker` processes
+//! can share the queue.
+
+use std::time::Duration;
+
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{
+    AppContext, Result,
+    alerts::Evaluator,
+    config::JobsConfig,
+    models::{
+        BreedSummary, ModelError, SpecieSummary, SummarySubject,
+        entitlements::ReportType,
+        jobs::{Job, JobKind, JobSchedule},
+        livestock::LivestockSummary,
+        orgs::Organisation,
+        tasks::Task,
+        trash::Trash,
+    },
+    webhooks::Dispatcher,
+};
+
+/// Seconds between looks for schedules that are due. Schedules may fire
+/// every few seconds, so this is not configurable.
+const SCHEDULE_INTERVAL: u64 = 1;
+
+#[derive(Clone)]
+pub struct Scheduler {
+    ctx: AppContext,
+    config: JobsConfig,
+}
+
+impl Scheduler {
+    #[must_use]
+    pub fn new(ctx: &AppContext) -> Self {
+        Self {
+            ctx: ctx.clone(),
+            config: ctx.config.jobs().clone(),
+        }
+    }
+
+    /// Fires the schedules and runs the configured number of workers, for as
+    /// long as the process runs.
+    pub async fn run(self) {
+        for worker in 0..self.config.workers().max(1) {
+            tokio::spawn(self.clone().work(worker));
+        }
+
+        let mut interval = tokio::time::interval(Duration::from_secs(SCHEDULE_INTERVAL));
+
+        loop {
+            interval.tick().await;
+
+            if let Err(error) = self.queue_due().await {
+                tracing::error!("Failed to queue scheduled jobs: {error}");
+            }
+        }
+    }
+
+    async fn work(self, worker: usize) {
+        let idle = Duration::from_secs(self.config.poll_interval());
+
+        loop {
+            match self.run_next().await {
+                Ok(Some(_)) => {}
+                Ok(None) => tokio::time::sleep(idle).await,
+                Err(error) => {
+                    tracing::error!("Job worker {worker} failed: {error}");
+                    tokio::time::sleep(idle).await;
+                }
+            }
+        }
+    }
+
+    /// Queues the jobs of the schedules that are due and returns how many
+    /// were queued.
+    pub async fn queue_due(&self) -> Result<u64> {
+        let mut txn = self.ctx.db.begin().await.map_err(ModelError::Sqlx)?;
+
+        Job::give_up_abandoned(&mut *txn).await?;
+
+        let mut queued = 0;
+        for schedule in JobSchedule::claim_due(&mut txn).await? {
+            queued += schedule.fire(&mut txn, self.config.max_attempts()).await?;
+        }
+
+        txn.commit().await.map_err(ModelError::Sqlx)?;
+
+        Ok(queued)
+    }
+
+    /// Runs the next job that is due, if there is one, and returns it as it
+    /// finished. A job that fails is retried later.
+    pub async fn run_next(&self) -> Result<Option<Job>> {
+        let Some(job) = Job::claim(&self.ctx.db, self.config.lease()).await? else {
+            return Ok(None);
+        };
+
+        let job = match self.perform(&job).await {
+            Ok(result) => Job::complete(&self.ctx.db, job.pid, result).await?,
+            Err(error) => {
+                tracing::error!("The {} job {} failed: {error}", job.kind, job.pid);
+
+                Job::fail(
+                    &self.ctx.db,
+                    job.pid,
+                    &error.to_string(),
+                    self.config.retry_delay(),
+                )
+                .await?
+            }
+        };
+
+        Ok(Some(job))
+    }
+
+    /// Does the job's work and returns what it did.
+    async fn perform(&self, job: &Job) -> Result<serde_json::Value> {
+        match (job.kind()?, job.organisation_pid) {
+            (JobKind::Summaries, Some(org_pid)) => self.summarise(org_pid).await,
+            (JobKind::Summaries, None) => {
+                Err(ModelError::Validation("A summaries job needs an organisation".into()).into())
+            }
+            (JobKind::Alerts, org_pid) => {
+                let evaluator = Evaluator::new(&self.ctx);
+                let raised = match org_pid {
+                    Some(org_pid) => evaluator.evaluate_organisation(org_pid).await?,
+                    None => evaluator.evaluate_all().await?,
+                };
+
+                Ok(json!({ "raised": raised }))
+            }
+            (JobKind::Purge, _) => {
+                let mut txn = self.ctx.db.begin().await.map_err(ModelError::Sqlx)?;
+                let purged =
+                    Trash::purge(&mut txn, self.ctx.config.trash().retention_days()).await?;
+                let jobs = Job::prune(&mut *txn, self.config.retention_days()).await?;
+                txn.commit().await.map_err(ModelError::Sqlx)?;
+
+                Ok(json!({
+                    "animals": purged.animals,
+                    "healthRecords": purged.health_records,
+                    "productionRecords": purged.production_records,
+                    "weightRecords": purged.weight_records,
+                    "jobs": jobs,
+                }))
+            }
+            (JobKind::Deliver, _) => {
+                let delivered = Dispatcher::new(&self.ctx)?.dispatch_due().await?;
+
+                Ok(json!({ "delivered": delivered }))
+            }
+            (JobKind::Tasks, Some(org_pid)) => {
+                let mut txn = self.ctx.db.begin().await.map_err(ModelError::Sqlx)?;
+                let generated = Task::generate(&mut txn, org_pid).await?;
+                txn.commit().await.map_err(ModelError::Sqlx)?;
+
+                Ok(json!(generated))
+            }
+            (JobKind::Tasks, None) => {
+                Err(ModelError::Validation("A tasks job needs an organisation".into()).into())
+            }
+        }
+    }
+
+    /// Generates the summaries the organisation's plan includes: the
+    /// livestock summary, and one for each specie and breed it keeps.
+    async fn summarise(&self, org_pid: Uuid) -> Result<serde_json::Value> {
+        let db = &self.ctx.db;
+        let reports = Organisation::find_by_pid(db, org_pid)
+            .await?
+            .subscription()
+            .limits()
+            .reports;
+
+        let subjects = SummarySubject::find_by_organisation(db, org_pid).await?;
+
+        let mut species = subjects
+            .iter()
+            .map(|subject| subject.specie_name.as_str())
+            .collect::<Vec<_>>();
+        species.dedup();
+
+        let mut generated = json!({ "livestock": 0, "species": 0, "breeds": 0 });
+
+        if reports.contains(&ReportType::Livestock) {
+            LivestockSummary::generate(db, org_pid).await?;
+            generated["livestock"] = json!(1);
+        }
+
+        if reports.contains(&ReportType::Categories) {
+            for specie in &species {
+                SpecieSummary::generate(db, org_pid, specie).await?;
+            }
+            generated["species"] = json!(species.len());
+        }
+
+        if reports.contains(&ReportType::Breeds) {
+            for subject in &subjects {
+                BreedSummary::generate(db, org_pid, &subject.breed_name, &subject.specie_name)
+                    .await?;
+            }
+            generated["breeds"] = json!(subjects.len());
+        }
+
+        Ok(generated)
+    }
+}
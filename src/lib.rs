@@ -1,12 +1,18 @@
+pub mod alerts;
 pub mod app;
 pub mod config;
 pub mod controllers;
 pub mod errors;
+pub mod jobs;
+pub mod live;
 pub mod middlewares;
 pub mod models;
+pub mod oidc;
+pub mod pdf;
 pub mod seed;
 pub mod state;
 pub mod views;
+pub mod webhooks;
 
 pub use self::{
     app::App,
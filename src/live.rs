@@ -0,0 +1,129 @@
+#![allow(clippy::missing_errors_doc)]
+
+//! Pushes changes to connected clients as they happen. Changes are announced
+//! by the database with `NOTIFY`, so every instance hears about them no matter
+//! which one made them.
+
+use std::{sync::Arc, time::Duration};
+
+use chrono::{DateTime, FixedOffset};
+use futures::{Stream, stream};
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgListener;
+use tokio::sync::{
+    OnceCell,
+    broadcast::{self, error::RecvError},
+};
+use uuid::Uuid;
+
+use crate::{
+    Result,
+    models::{ModelError, roles::Resource},
+};
+
+/// The channel the database announces changes on.
+pub const CHANNEL: &str = "polaris_events";
+
+/// How many events a slow client may fall behind before it misses some.
+const CAPACITY: usize = 256;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LiveEvent {
+    pub organisation: Uuid,
+    #[serde(rename = "type")]
+    pub event_type: String,
+    pub data: serde_json::Value,
+    pub occurred_at: DateTime<FixedOffset>,
+}
+
+impl LiveEvent {
+    /// The resource a client must be allowed to read to be sent the event, as
+    /// it carries the changed record.
+    #[must_use]
+    pub fn resource(&self) -> Resource {
+        match self.event_type.split_once('.').map(|(kind, _)| kind) {
+            Some("health_record") => Resource::HealthRecords,
+            Some("weight_record") => Resource::WeightRecords,
+            Some("animal") => Resource::Animals,
+            _ => Resource::Reports,
+        }
+    }
+}
+
+/// Shares one database listener between every client of an instance. It is
+/// started by the first client to subscribe.
+#[derive(Clone)]
+pub struct LiveEvents {
+    url: String,
+    sender: Arc<OnceCell<broadcast::Sender<LiveEvent>>>,
+}
+
+impl LiveEvents {
+    #[must_use]
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            sender: Arc::new(OnceCell::new()),
+        }
+    }
+
+    /// The organisation's events from now on. The stream ends if the
+    /// listener stops.
+    pub async fn subscribe(&self, org_pid: Uuid) -> Result<impl Stream<Item = LiveEvent> + use<>> {
+        let receiver = self
+            .sender
+            .get_or_try_init(|| self.listen())
+            .await?
+            .subscribe();
+
+        Ok(stream::unfold(receiver, move |mut receiver| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) if event.organisation == org_pid => return Some((event, receiver)),
+                    Ok(_) => {}
+                    Err(RecvError::Lagged(missed)) => {
+                        tracing::warn!(
+                            "A live event client fell behind and missed {missed} events"
+                        );
+                    }
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        }))
+    }
+
+    /// Listens on its own connection, rather than holding one of the pool's
+    /// for as long as the app runs.
+    async fn listen(&self) -> Result<broadcast::Sender<LiveEvent>> {
+        let mut listener = PgListener::connect(&self.url)
+            .await
+            .map_err(ModelError::from)?;
+        listener.listen(CHANNEL).await.map_err(ModelError::from)?;
+
+        let (sender, _) = broadcast::channel(CAPACITY);
+        let events = sender.clone();
+
+        tokio::spawn(async move {
+            loop {
+                match listener.recv().await {
+                    Ok(notification) => {
+                        match serde_json::from_str::<LiveEvent>(notification.payload()) {
+                            // Nobody may be subscribed, which is fine.
+                            Ok(event) => {
+                                events.send(event).ok();
+                            }
+                            Err(error) => tracing::error!("Unreadable live event: {error}"),
+                        }
+                    }
+                    Err(error) => {
+                        tracing::error!("Lost the live event listener: {error}");
+                        tokio::time::sleep(Duration::from_secs(1)).await;
+                    }
+                }
+            }
+        });
+
+        Ok(sender)
+    }
+}
@@ -4,13 +4,21 @@ use std::{
     task::{Context, Poll},
 };
 
-use crate::{AppContext, Error};
+use crate::{
+    AppContext, Error,
+    models::{
+        api_keys::{API_KEY_PREFIX, ApiKey},
+        entitlements::Entitlement,
+        orgs::Organisation,
+        users::User,
+    },
+};
 
 use axum::{
     RequestPartsExt,
     body::Body,
     extract::{FromRef, FromRequestParts},
-    http::{Request, Response},
+    http::{HeaderMap, Request, Response, header::AUTHORIZATION},
 };
 use axum_extra::{
     TypedHeader,
@@ -27,8 +35,34 @@ pub struct TokenClaims {
     pub role: String,
     pub exp: i64,
     pub iat: i64,
+    /// The platform operator acting as `sub`, on impersonation tokens.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub impersonator: Option<String>,
+    /// The organisation the user is acting in. Tokens without it act in the
+    /// user's home organisation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub organisation: Option<String>,
+}
+
+/// The API key a request was made with, from the `X-Api-Key` header or a
+/// bearer token carrying the key prefix.
+pub(crate) fn api_key_token(headers: &HeaderMap) -> Option<String> {
+    if let Some(key) = headers.get("x-api-key") {
+        return key.to_str().ok().map(str::to_string);
+    }
+
+    headers
+        .get(AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .filter(|token| token.starts_with(API_KEY_PREFIX))
+        .map(str::to_string)
 }
 
+/// Authenticates requests with an access token, or with an API key for
+/// machine integrations. Either way the acting [`User`] is inserted for the
+/// handlers, along with the [`TokenClaims`] or the [`ApiKey`].
 #[derive(Clone)]
 pub struct AuthLayer {
     state: AppContext,
@@ -80,13 +114,90 @@ where
         Box::pin(async move {
             let (mut parts, body) = req.into_parts();
 
+            if let Some(token) = api_key_token(&parts.headers) {
+                let api_key = match ApiKey::authenticate(&state.db, &token).await {
+                    Ok(Some(api_key)) => api_key,
+                    Ok(None) => return Ok(Error::InvalidToken.response()),
+                    Err(e) => return Ok(e.response()),
+                };
+
+                // The key stops working once its creator leaves or is
+                // deactivated.
+                let user = match User::find_member(
+                    &state.db,
+                    api_key.created_by,
+                    Some(api_key.organisation_pid),
+                )
+                .await
+                {
+                    Ok(Some(user)) if user.is_active => user,
+                    Ok(Some(_)) => return Ok(Error::AccountDisabled.response()),
+                    Ok(None) => return Ok(Error::InvalidToken.response()),
+                    Err(e) => return Ok(e.response()),
+                };
+
+                let organisation =
+                    match Organisation::find_by_pid(&state.db, api_key.organisation_pid).await {
+                        Ok(organisation) if organisation.is_suspended() => {
+                            return Ok(Error::OrganisationSuspended.response());
+                        }
+                        Ok(organisation) => organisation,
+                        Err(e) => return Ok(e.response()),
+                    };
+
+                // Keys stop working when the plan no longer includes API
+                // access, e.g. after a downgrade.
+                if let Err(e) = Entitlement::ApiAccess
+                    .check(&state.db, organisation.pid, &organisation.subscription())
+                    .await
+                {
+                    return Ok(e.response());
+                }
+
+                let mut req = Request::from_parts(parts, body);
+                req.extensions_mut().insert(api_key);
+                req.extensions_mut().insert(user);
+                return inner.call(req).await;
+            }
+
             let auth = match TokenClaims::from_request_parts(&mut parts, &state).await {
                 Ok(claims) => claims,
                 Err(e) => return Ok(e.response()),
             };
 
+            // A valid token is not enough, the account may have been
+            // deactivated since the token was issued.
+            let user = match User::find_by_claims(&state.db, &auth).await {
+                Ok(Some(user)) if user.is_active => user,
+                Ok(Some(_)) => return Ok(Error::AccountDisabled.response()),
+                Ok(None) => return Ok(Error::InvalidToken.response()),
+                Err(e) => return Ok(e.response()),
+            };
+
+            // Operators keep access to the console even if their own
+            // organisation is suspended.
+            if !user.is_platform_operator {
+                match Organisation::find_by_pid(&state.db, user.organisation_pid).await {
+                    Ok(organisation) if organisation.is_suspended() => {
+                        return Ok(Error::OrganisationSuspended.response());
+                    }
+                    Ok(_) => {}
+                    Err(e) => return Ok(e.response()),
+                }
+            }
+
+            // Impersonation ends as soon as the operator loses their access.
+            if let Some(impersonator) = &auth.impersonator {
+                match User::find_by_claims_key(&state.db, impersonator).await {
+                    Ok(Some(operator)) if operator.is_active && operator.is_platform_operator => {}
+                    Ok(_) => return Ok(Error::InvalidToken.response()),
+                    Err(e) => return Ok(e.response()),
+                }
+            }
+
             let mut req = Request::from_parts(parts, body);
             req.extensions_mut().insert(auth);
+            req.extensions_mut().insert(user);
             inner.call(req).await
         })
     }
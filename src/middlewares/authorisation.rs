@@ -3,12 +3,15 @@ use std::{
     task::{Context, Poll},
 };
 
-use crate::{AppContext, models::roles::Role};
+use crate::{
+    AppContext, Error,
+    models::{api_keys::ApiKey, roles::Role, users::User},
+};
 
 use axum::{
     body::Body,
     extract::FromRequestParts,
-    http::{Request, Response, StatusCode},
+    http::{Request, Response},
 };
 use futures_util::future::BoxFuture;
 use tower::{Layer, Service};
@@ -67,57 +70,47 @@ where
         Box::pin(async move {
             let (mut parts, body) = req.into_parts();
 
-            let method = parts.method.clone();
-            let uri = parts.uri.clone();
+            let api_key = parts.extensions.get::<ApiKey>().cloned();
 
-            let claims = match TokenClaims::from_request_parts(&mut parts, &state).await {
-                Ok(claims) => claims,
-                Err(e) => return Ok(e.response()),
+            let claims = match api_key {
+                Some(_) => None,
+                None => match TokenClaims::from_request_parts(&mut parts, &state).await {
+                    Ok(claims) => Some(claims),
+                    Err(e) => return Ok(e.response()),
+                },
             };
 
-            let role: Role = match Role::find_by_name(&state.db, &claims.role).await {
-                Ok(role) => role,
-                Err(e) => return Ok(e.response()),
+            let user = match (parts.extensions.get::<User>(), &claims) {
+                (Some(user), _) => user.clone(),
+                (None, Some(claims)) => match User::find_by_claims(&state.db, claims).await {
+                    Ok(Some(user)) => user,
+                    Ok(None) => return Ok(Error::InvalidToken.response()),
+                    Err(e) => return Ok(e.response()),
+                },
+                (None, None) => return Ok(Error::InvalidToken.response()),
             };
 
-            if uri.path().contains("users") && !role.can_manage_users() {
-                return Ok(Response::builder()
-                    .status(StatusCode::FORBIDDEN)
-                    .body(Body::new(
-                        serde_json::json!({
-                            "messsage": "Forbidden. Access denied"
-                        })
-                        .to_string(),
-                    ))
-                    .unwrap());
-            }
-
-            if method == "DELETE" && !role.can_delete() {
-                return Ok(Response::builder()
-                    .status(StatusCode::FORBIDDEN)
-                    .body(Body::new(
-                        serde_json::json!({
-                            "message": "Forbidden. Access denied"
-                        })
-                        .to_string(),
-                    ))
-                    .unwrap());
-            }
-
-            if (method == "POST" || method == "PATCH" || method == "PUT") && !role.can_write() {
-                return Ok(Response::builder()
-                    .status(StatusCode::FORBIDDEN)
-                    .body(Body::new(
-                        serde_json::json!({
-                            "message": "Forbidden. Access denied"
-                        })
-                        .to_string(),
-                    ))
-                    .unwrap());
-            }
+            // The role is read from the user rather than the claims so that
+            // role changes apply without waiting for the token to expire.
+            let role =
+                match Role::find_for_organisation(&state.db, user.organisation_pid, &user.role)
+                    .await
+                {
+                    Ok(role) => role,
+                    Err(e) => return Ok(e.response()),
+                };
+
+            // Keys only get their scopes, within what their creator may do.
+            let role = match &api_key {
+                Some(api_key) => api_key.restrict(&role),
+                None => role,
+            };
 
+            // What the role may do is checked per route by the `PermissionLayer`.
             let mut req = Request::from_parts(parts, body);
-            req.extensions_mut().insert(claims);
+            if let Some(claims) = claims {
+                req.extensions_mut().insert(claims);
+            }
             req.extensions_mut().insert(role);
             inner.call(req).await
         })
@@ -0,0 +1,104 @@
+use std::{
+    convert::Infallible,
+    task::{Context, Poll},
+};
+
+use axum::{
+    body::Body,
+    http::{Request, Response},
+};
+use futures_util::future::BoxFuture;
+use tower::{Layer, Service};
+
+use crate::{
+    AppContext, Error,
+    models::{entitlements::Entitlement, orgs::Organisation, users::User},
+};
+
+/// Route layer declaring what a handler needs the organisation's
+/// subscription to include, e.g.
+/// `post(add).layer(EntitlementLayer::new(&ctx, Entitlement::Animal))`.
+///
+/// Relies on the [`User`] inserted by the `AuthLayer`, so apply it inside the
+/// route's `PermissionLayer`.
+#[derive(Clone)]
+pub struct EntitlementLayer {
+    state: AppContext,
+    entitlement: Entitlement,
+}
+
+impl EntitlementLayer {
+    #[must_use]
+    pub fn new(ctx: &AppContext, entitlement: Entitlement) -> Self {
+        Self {
+            state: ctx.clone(),
+            entitlement,
+        }
+    }
+}
+
+impl<S> Layer<S> for EntitlementLayer {
+    type Service = EntitlementService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Self::Service {
+            inner,
+            state: self.state.clone(),
+            entitlement: self.entitlement,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct EntitlementService<S> {
+    inner: S,
+    state: AppContext,
+    entitlement: Entitlement,
+}
+
+impl<S, B> Service<Request<B>> for EntitlementService<S>
+where
+    S: Service<Request<B>, Response = Response<Body>, Error = Infallible> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    B: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<B>) -> Self::Future {
+        let state = self.state.clone();
+        let entitlement = self.entitlement;
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        let org_pid = req
+            .extensions()
+            .get::<User>()
+            .map(|user| user.organisation_pid);
+
+        Box::pin(async move {
+            let Some(org_pid) = org_pid else {
+                return Ok(Error::Unauthorised.response());
+            };
+
+            let organisation = match Organisation::find_by_pid(&state.db, org_pid).await {
+                Ok(organisation) => organisation,
+                Err(e) => return Ok(e.response()),
+            };
+
+            if let Err(e) = entitlement
+                .check(&state.db, org_pid, &organisation.subscription())
+                .await
+            {
+                return Ok(e.response());
+            }
+
+            inner.call(req).await
+        })
+    }
+}
@@ -2,9 +2,14 @@
 pub mod admin;
 pub mod auth;
 pub mod authorisation;
+pub mod entitlement;
 pub mod manager;
+pub mod operator;
+pub mod permission;
 pub mod refresh;
 pub mod staff;
 pub mod trace;
 
-pub(crate) use self::{admin::*, auth::*, manager::*, staff::*, trace::*};
+pub(crate) use self::{
+    admin::*, auth::*, entitlement::*, manager::*, operator::*, permission::*, staff::*, trace::*,
+};
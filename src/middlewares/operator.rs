@@ -0,0 +1,87 @@
+use std::{
+    convert::Infallible,
+    task::{Context, Poll},
+};
+
+use axum::{
+    body::Body,
+    http::{Request, Response},
+};
+use futures_util::future::BoxFuture;
+use tower::{Layer, Service};
+
+use crate::{
+    Error,
+    models::{api_keys::ApiKey, users::User},
+};
+
+/// Restricts the platform console to platform operators. Operators sit above
+/// organisation admins and act across every organisation.
+///
+/// Relies on the [`User`] inserted by the `AuthLayer`. Impersonation tokens
+/// are rejected so an operator acting as an org admin cannot reach it, and so
+/// are API keys.
+#[derive(Clone, Copy, Default)]
+pub struct OperatorLayer;
+
+impl OperatorLayer {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self
+    }
+}
+
+impl<S> Layer<S> for OperatorLayer {
+    type Service = OperatorService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Self::Service { inner }
+    }
+}
+
+#[derive(Clone)]
+pub struct OperatorService<S> {
+    inner: S,
+}
+
+impl<S, B> Service<Request<B>> for OperatorService<S>
+where
+    S: Service<Request<B>, Response = Response<Body>, Error = Infallible> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    B: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<B>) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        let impersonating = req
+            .extensions()
+            .get::<super::TokenClaims>()
+            .is_some_and(|claims| claims.impersonator.is_some());
+
+        let with_api_key = req.extensions().get::<ApiKey>().is_some();
+
+        let allowed = !impersonating
+            && !with_api_key
+            && req
+                .extensions()
+                .get::<User>()
+                .is_some_and(User::is_platform_operator);
+
+        Box::pin(async move {
+            if !allowed {
+                return Ok(Error::Forbidden.response());
+            }
+
+            inner.call(req).await
+        })
+    }
+}
@@ -0,0 +1,85 @@
+use std::{
+    convert::Infallible,
+    task::{Context, Poll},
+};
+
+use axum::{
+    body::Body,
+    http::{Request, Response},
+};
+use futures_util::future::BoxFuture;
+use tower::{Layer, Service};
+
+use crate::{
+    Error,
+    models::roles::{Action, Resource, Role},
+};
+
+/// Route layer declaring the permission a handler requires, e.g.
+/// `get(all).layer(PermissionLayer::new(Resource::Animals, Action::Read))`.
+///
+/// Relies on the [`Role`] inserted by the `AuthorisationLayer`.
+#[derive(Clone, Copy)]
+pub struct PermissionLayer {
+    resource: Resource,
+    action: Action,
+}
+
+impl PermissionLayer {
+    #[must_use]
+    pub const fn new(resource: Resource, action: Action) -> Self {
+        Self { resource, action }
+    }
+}
+
+impl<S> Layer<S> for PermissionLayer {
+    type Service = PermissionService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Self::Service {
+            inner,
+            resource: self.resource,
+            action: self.action,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct PermissionService<S> {
+    inner: S,
+    resource: Resource,
+    action: Action,
+}
+
+impl<S, B> Service<Request<B>> for PermissionService<S>
+where
+    S: Service<Request<B>, Response = Response<Body>, Error = Infallible> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    B: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<B>) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        let allowed = req
+            .extensions()
+            .get::<Role>()
+            .is_some_and(|role| role.allows(self.resource, self.action));
+
+        Box::pin(async move {
+            if !allowed {
+                return Ok(Error::Forbidden.response());
+            }
+
+            inner.call(req).await
+        })
+    }
+}
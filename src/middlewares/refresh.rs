@@ -77,6 +77,11 @@ where
         Box::pin(async move {
             let (mut parts, body) = req.into_parts();
 
+            // API keys have nothing to refresh.
+            if super::api_key_token(&parts.headers).is_some() {
+                return inner.call(Request::from_parts(parts, body)).await;
+            }
+
             match TokenClaims::from_request_parts(&mut parts, &state).await {
                 Ok(claims) => {
                     let mut req = Request::from_parts(parts, body);
@@ -107,7 +112,10 @@ where
                     };
 
                     // Fetch the user and issue a new access-token
-                    let user = match User::find_by_claims_key(&state.db, &token_claims.sub).await {
+                    let user = match User::find_by_claims(&state.db, &token_claims).await {
+                        Ok(Some(user)) if !user.is_active => {
+                            return Ok(crate::Error::AccountDisabled.response());
+                        }
                         Ok(Some(user)) => user,
                         Ok(None) => return Ok(crate::Error::InvalidToken.response()),
                         Err(e) => return Ok(e.response()),
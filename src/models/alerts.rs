@@ -0,0 +1,613 @@
+#![allow(clippy::missing_errors_doc)]
+
+use std::{fmt, str::FromStr};
+
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use sqlx::{Executor, PgConnection, Postgres, prelude::FromRow, types::Json};
+use uuid::Uuid;
+
+use super::{
+    ModelError, ModelResult,
+    dto::{AlertQuery, CreateAlertRule, UpdateAlertRule, Validator},
+    events::{DomainEvent, EventType},
+    tasks::Task,
+    users::User,
+};
+
+/// Where triggered alerts are sent: the live event stream and the
+/// organisation's webhooks.
+pub const ALERT_CHANNELS: &[&str] = &["stream", "webhook"];
+
+/// The anomalies a rule can look for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    /// The latest weighing is more than `threshold` percent below the one
+    /// before it.
+    WeightDrop,
+    /// No weighing in the last `window_days`.
+    WeighingOverdue,
+    /// The latest day's milk is more than `threshold` percent below the
+    /// average of the seven days before it.
+    MilkYieldDrop,
+    /// At least `threshold` high severity health records in the last
+    /// `window_days`.
+    RepeatedHighSeverity,
+    /// No vaccination in the last `window_days`.
+    VaccinationOverdue,
+}
+
+// What each kind of rule matches, see `AlertKind::matches`.
+const WEIGHT_DROP: &str = "
+    SELECT
+        a.pid AS animal_pid,
+        w.id::TEXT AS fingerprint,
+        format(
+            '%s lost %s%% of its weight since it was last weighed',
+            a.tag_id,
+            ROUND((w.previous_mass - w.mass) / w.previous_mass * 100, 1)
+        ) AS message,
+        jsonb_build_object(
+            'tagId', a.tag_id,
+            'weightRecordId', w.id,
+            'mass', w.mass,
+            'previousMass', w.previous_mass,
+            'recordDate', w.record_date
+        ) AS details
+    FROM animals a
+    JOIN LATERAL (
+        SELECT w.id, w.mass, w.previous_mass, w.record_date
+        FROM weight_records w
+        WHERE w.animal_pid = a.pid AND w.deleted_at IS NULL
+        ORDER BY w.record_date DESC, w.id DESC
+        LIMIT 1
+    ) w ON TRUE
+    WHERE a.organisation_pid = $1 AND a.status = 'active' AND a.deleted_at IS NULL
+        AND w.previous_mass > 0
+        AND (w.previous_mass - w.mass) / w.previous_mass * 100 > $2";
+
+const WEIGHING_OVERDUE: &str = "
+    SELECT
+        a.pid AS animal_pid,
+        COALESCE(w.last_weighed::TEXT, 'never') AS fingerprint,
+        CASE WHEN w.last_weighed IS NULL
+            THEN format('%s has never been weighed', a.tag_id)
+            ELSE format(
+                '%s has not been weighed since %s',
+                a.tag_id,
+                to_char(w.last_weighed, 'DD-MM-YYYY')
+            )
+        END AS message,
+        jsonb_build_object(
+            'tagId', a.tag_id,
+            'lastWeighed', w.last_weighed,
+            'windowDays', $3
+        ) AS details
+    FROM animals a
+    JOIN LATERAL (
+        SELECT MAX(w.record_date) AS last_weighed
+        FROM weight_records w
+        WHERE w.animal_pid = a.pid AND w.deleted_at IS NULL
+    ) w ON TRUE
+    WHERE a.organisation_pid = $1 AND a.status = 'active' AND a.deleted_at IS NULL
+        AND COALESCE(w.last_weighed, a.created_at::DATE) < CURRENT_DATE - $3";
+
+const MILK_YIELD_DROP: &str = "
+    WITH daily AS (
+        SELECT p.animal_pid, p.record_date, SUM(p.quantity) AS quantity
+        FROM production_records p
+        WHERE p.organisation_pid = $1 AND p.product_type = 'milk'
+            AND p.deleted_at IS NULL
+        GROUP BY p.animal_pid, p.record_date
+    ),
+    latest AS (
+        SELECT DISTINCT ON (d.animal_pid) d.animal_pid, d.record_date, d.quantity
+        FROM daily d
+        ORDER BY d.animal_pid, d.record_date DESC
+    )
+    SELECT
+        a.pid AS animal_pid,
+        l.record_date::TEXT AS fingerprint,
+        format(
+            '%s gave %s%% less milk than its 7-day average',
+            a.tag_id,
+            ROUND((average.quantity - l.quantity) / average.quantity * 100, 1)
+        ) AS message,
+        jsonb_build_object(
+            'tagId', a.tag_id,
+            'quantity', l.quantity,
+            'average', ROUND(average.quantity, 2),
+            'recordDate', l.record_date
+        ) AS details
+    FROM latest l
+    JOIN animals a ON a.pid = l.animal_pid
+    JOIN LATERAL (
+        SELECT AVG(d.quantity) AS quantity
+        FROM daily d
+        WHERE d.animal_pid = l.animal_pid
+            AND d.record_date >= l.record_date - 7
+            AND d.record_date < l.record_date
+    ) average ON average.quantity > 0
+    WHERE a.status = 'active' AND a.deleted_at IS NULL
+        AND (average.quantity - l.quantity) / average.quantity * 100 > $2";
+
+const REPEATED_HIGH_SEVERITY: &str = "
+    SELECT
+        a.pid AS animal_pid,
+        MAX(h.id)::TEXT AS fingerprint,
+        format(
+            '%s had %s high severity health records in %s days',
+            a.tag_id,
+            COUNT(*),
+            $3
+        ) AS message,
+        jsonb_build_object(
+            'tagId', a.tag_id,
+            'count', COUNT(*),
+            'windowDays', $3
+        ) AS details
+    FROM animals a
+    JOIN health_records h ON h.animal_pid = a.pid AND h.deleted_at IS NULL
+    WHERE a.organisation_pid = $1 AND a.status = 'active' AND a.deleted_at IS NULL
+        AND h.severity = 'high'
+        AND h.record_date > CURRENT_DATE - $3
+    GROUP BY a.pid, a.tag_id
+    HAVING COUNT(*) >= $2";
+
+const VACCINATION_OVERDUE: &str = "
+    SELECT
+        a.pid AS animal_pid,
+        COALESCE(h.last_vaccinated::TEXT, 'never') AS fingerprint,
+        CASE WHEN h.last_vaccinated IS NULL
+            THEN format('%s has never been vaccinated', a.tag_id)
+            ELSE format(
+                '%s has not been vaccinated since %s',
+                a.tag_id,
+                to_char(h.last_vaccinated, 'DD-MM-YYYY')
+            )
+        END AS message,
+        jsonb_build_object(
+            'tagId', a.tag_id,
+            'lastVaccinated', h.last_vaccinated,
+            'windowDays', $3
+        ) AS details
+    FROM animals a
+    JOIN LATERAL (
+        SELECT MAX(h.record_date) AS last_vaccinated
+        FROM health_records h
+        WHERE h.animal_pid = a.pid AND h.condition = 'vaccination'
+            AND h.deleted_at IS NULL
+    ) h ON TRUE
+    WHERE a.organisation_pid = $1 AND a.status = 'active' AND a.deleted_at IS NULL
+        AND COALESCE(h.last_vaccinated, a.created_at::DATE) < CURRENT_DATE - $3";
+
+impl AlertKind {
+    pub const ALL: &'static [Self] = &[
+        Self::WeightDrop,
+        Self::WeighingOverdue,
+        Self::MilkYieldDrop,
+        Self::RepeatedHighSeverity,
+        Self::VaccinationOverdue,
+    ];
+
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::WeightDrop => "weight_drop",
+            Self::WeighingOverdue => "weighing_overdue",
+            Self::MilkYieldDrop => "milk_yield_drop",
+            Self::RepeatedHighSeverity => "repeated_high_severity",
+            Self::VaccinationOverdue => "vaccination_overdue",
+        }
+    }
+
+    /// The threshold and window the kind is evaluated with, filling in
+    /// defaults and dropping what it does not use.
+    pub fn settings(
+        &self,
+        threshold: Option<f64>,
+        window_days: Option<i32>,
+    ) -> ModelResult<(Option<f64>, Option<i32>)> {
+        let percentage = |threshold: Option<f64>| match threshold {
+            Some(threshold) if threshold < 100.0 => Ok(Some(threshold)),
+            Some(_) => Err(ModelError::Validation(
+                "Threshold must be a percentage below 100".into(),
+            )),
+            None => Err(ModelError::Validation(format!(
+                "A {self} rule needs a threshold percentage"
+            ))),
+        };
+        let window = |window_days: Option<i32>| {
+            window_days.map(Some).ok_or_else(|| {
+                ModelError::Validation(format!("A {self} rule needs a window in days"))
+            })
+        };
+
+        match self {
+            Self::WeightDrop | Self::MilkYieldDrop => Ok((percentage(threshold)?, None)),
+            Self::WeighingOverdue | Self::VaccinationOverdue => Ok((None, window(window_days)?)),
+            Self::RepeatedHighSeverity => Ok((
+                Some(threshold.unwrap_or(2.0).ceil()),
+                Some(window_days.unwrap_or(30)),
+            )),
+        }
+    }
+
+    /// The animals the rule matches, as `animal_pid`, `fingerprint`,
+    /// `message` and `details`. Binds the organisation as `$1`, the threshold
+    /// as `$2` and the window as `$3`.
+    const fn matches(self) -> &'static str {
+        match self {
+            Self::WeightDrop => WEIGHT_DROP,
+            Self::WeighingOverdue => WEIGHING_OVERDUE,
+            Self::MilkYieldDrop => MILK_YIELD_DROP,
+            Self::RepeatedHighSeverity => REPEATED_HIGH_SEVERITY,
+            Self::VaccinationOverdue => VACCINATION_OVERDUE,
+        }
+    }
+}
+
+impl fmt::Display for AlertKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for AlertKind {
+    type Err = ModelError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .find(|kind| kind.as_str() == value.trim())
+            .copied()
+            .ok_or_else(|| ModelError::Validation(format!("Unknown alert kind {value}")))
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, FromRow, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AlertRule {
+    pub id: i32,
+    pub pid: Uuid,
+    pub organisation_pid: Uuid,
+    pub name: String,
+    pub kind: String,
+    pub threshold: Option<f64>,
+    pub window_days: Option<i32>,
+    pub severity: String,
+    pub channels: Vec<String>,
+    pub is_active: bool,
+    pub last_evaluated_at: Option<DateTime<FixedOffset>>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<FixedOffset>,
+    pub updated_at: DateTime<FixedOffset>,
+}
+
+#[derive(Debug, Deserialize, Serialize, FromRow, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Alert {
+    pub id: i32,
+    pub pid: Uuid,
+    pub organisation_pid: Uuid,
+    pub rule_pid: Uuid,
+    pub animal_pid: Uuid,
+    #[serde(skip_serializing)]
+    pub fingerprint: String,
+    pub severity: String,
+    pub message: String,
+    pub details: Json<serde_json::Value>,
+    pub status: String,
+    pub acknowledged_by: Option<Uuid>,
+    pub acknowledged_at: Option<DateTime<FixedOffset>>,
+    pub resolved_by: Option<Uuid>,
+    pub resolved_at: Option<DateTime<FixedOffset>>,
+    pub created_at: DateTime<FixedOffset>,
+    pub updated_at: DateTime<FixedOffset>,
+}
+
+/// The stored form of the requested channels, without duplicates. Rules
+/// without channels use all of them.
+fn channels(channels: Option<&[String]>) -> Vec<String> {
+    let mut channels = channels.map_or_else(
+        || ALERT_CHANNELS.iter().map(ToString::to_string).collect(),
+        <[String]>::to_vec,
+    );
+
+    channels.sort();
+    channels.dedup();
+    channels
+}
+
+impl AlertRule {
+    pub fn kind(&self) -> ModelResult<AlertKind> {
+        self.kind.parse()
+    }
+
+    pub async fn create<'e, C>(
+        db: C,
+        creator: &User,
+        dto: &CreateAlertRule<'_>,
+    ) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        let validator = Validator::new(dto);
+        let dto = validator.validate()?;
+
+        let kind = dto.kind.parse::<AlertKind>()?;
+        let (threshold, window_days) = kind.settings(dto.threshold, dto.window_days)?;
+
+        sqlx::query_as::<_, Self>(
+            "
+            INSERT INTO alert_rules (
+                organisation_pid, name, kind, threshold, window_days, severity, channels,
+                created_by
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8) RETURNING *",
+        )
+        .bind(creator.organisation_pid)
+        .bind(dto.name.trim())
+        .bind(kind.as_str())
+        .bind(threshold)
+        .bind(window_days)
+        .bind(dto.severity.as_deref().unwrap_or("medium"))
+        .bind(channels(dto.channels.as_deref()))
+        .bind(creator.pid)
+        .fetch_one(db)
+        .await
+        .map_err(Into::into)
+    }
+
+    pub async fn find_all<'e, C>(db: C, org_pid: Uuid) -> ModelResult<Vec<Self>>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_as::<_, Self>(
+            "SELECT * FROM alert_rules WHERE organisation_pid = $1 ORDER BY name, id",
+        )
+        .bind(org_pid)
+        .fetch_all(db)
+        .await
+        .map_err(Into::into)
+    }
+
+    /// Active rules of one organisation, or of every one, for the evaluator.
+    pub async fn find_active<'e, C>(db: C, org_pid: Option<Uuid>) -> ModelResult<Vec<Self>>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_as::<_, Self>(
+            "
+            SELECT r.* FROM alert_rules r
+            JOIN organisations o ON r.organisation_pid = o.pid
+            WHERE r.is_active AND o.suspended_at IS NULL
+                AND ($1::UUID IS NULL OR r.organisation_pid = $1)
+            ORDER BY r.id",
+        )
+        .bind(org_pid)
+        .fetch_all(db)
+        .await
+        .map_err(Into::into)
+    }
+
+    pub async fn find_by_pid<'e, C>(db: C, org_pid: Uuid, pid: Uuid) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_as::<_, Self>(
+            "SELECT * FROM alert_rules WHERE pid = $1 AND organisation_pid = $2",
+        )
+        .bind(pid)
+        .bind(org_pid)
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    pub async fn update(
+        db: &mut PgConnection,
+        org_pid: Uuid,
+        pid: Uuid,
+        dto: &UpdateAlertRule<'_>,
+    ) -> ModelResult<Self> {
+        let validator = Validator::new(dto);
+        let dto = validator.validate()?;
+
+        let rule = Self::find_by_pid(&mut *db, org_pid, pid).await?;
+        let (threshold, window_days) = rule.kind()?.settings(
+            dto.threshold.or(rule.threshold),
+            dto.window_days.or(rule.window_days),
+        )?;
+
+        sqlx::query_as::<_, Self>(
+            "
+            UPDATE alert_rules SET
+                name = COALESCE($2, name),
+                threshold = $3,
+                window_days = $4,
+                severity = COALESCE($5, severity),
+                channels = COALESCE($6, channels),
+                is_active = COALESCE($7, is_active)
+            WHERE pid = $1 RETURNING *",
+        )
+        .bind(rule.pid)
+        .bind(dto.name.as_deref().map(str::trim))
+        .bind(threshold)
+        .bind(window_days)
+        .bind(dto.severity.as_deref())
+        .bind(
+            dto.channels
+                .as_deref()
+                .map(|requested| channels(Some(requested))),
+        )
+        .bind(dto.is_active)
+        .fetch_one(&mut *db)
+        .await
+        .map_err(Into::into)
+    }
+
+    /// Deletes the rule along with its alerts.
+    pub async fn delete_by_pid<'e, C>(db: C, org_pid: Uuid, pid: Uuid) -> ModelResult<()>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        let result =
+            sqlx::query("DELETE FROM alert_rules WHERE pid = $1 AND organisation_pid = $2")
+                .bind(pid)
+                .bind(org_pid)
+                .execute(db)
+                .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(ModelError::EntityNotFound);
+        }
+
+        Ok(())
+    }
+
+    /// Raises an alert for each animal the rule matches on new evidence, with
+    /// a task to look into it, and sends it to the rule's channels. Call it
+    /// inside a transaction so the alerts and their notifications go out
+    /// together.
+    pub async fn evaluate(&self, db: &mut PgConnection) -> ModelResult<Vec<Alert>> {
+        let query = format!(
+            "
+            INSERT INTO alerts (
+                organisation_pid, rule_pid, animal_pid, fingerprint, severity, message, details
+            )
+            SELECT $1, $4, m.animal_pid, m.fingerprint, $5, m.message, m.details
+            FROM ({}) m
+            ON CONFLICT (rule_pid, animal_pid, fingerprint) DO NOTHING
+            RETURNING *",
+            self.kind()?.matches()
+        );
+
+        let alerts = sqlx::query_as::<_, Alert>(&query)
+            .bind(self.organisation_pid)
+            .bind(self.threshold)
+            .bind(self.window_days)
+            .bind(self.pid)
+            .bind(&self.severity)
+            .fetch_all(&mut *db)
+            .await?;
+
+        for alert in &alerts {
+            Task::raise_for_alert(&mut *db, alert).await?;
+
+            if self.channels.iter().any(|channel| channel == "webhook") {
+                DomainEvent::emit(
+                    &mut *db,
+                    self.organisation_pid,
+                    EventType::AlertTriggered,
+                    alert,
+                )
+                .await?;
+            }
+
+            if self.channels.iter().any(|channel| channel == "stream") {
+                let data = serde_json::to_value(alert)
+                    .map_err(|error| ModelError::Validation(error.to_string()))?;
+
+                sqlx::query("SELECT notify_live_event($1, 'alert.triggered', $2)")
+                    .bind(self.organisation_pid)
+                    .bind(Json(data))
+                    .execute(&mut *db)
+                    .await?;
+            }
+        }
+
+        sqlx::query("UPDATE alert_rules SET last_evaluated_at = NOW() WHERE pid = $1")
+            .bind(self.pid)
+            .execute(&mut *db)
+            .await?;
+
+        Ok(alerts)
+    }
+}
+
+impl Alert {
+    pub async fn find_all<'e, C>(
+        db: C,
+        org_pid: Uuid,
+        conditions: &AlertQuery,
+    ) -> ModelResult<Vec<Self>>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_as::<_, Self>(
+            "
+            SELECT * FROM alerts
+            WHERE organisation_pid = $1
+                AND ($2::TEXT IS NULL OR status = $2)
+                AND ($3::UUID IS NULL OR rule_pid = $3)
+                AND ($4::UUID IS NULL OR animal_pid = $4)
+            ORDER BY created_at DESC, id DESC",
+        )
+        .bind(org_pid)
+        .bind(conditions.status.as_deref())
+        .bind(conditions.rule)
+        .bind(conditions.animal)
+        .fetch_all(db)
+        .await
+        .map_err(Into::into)
+    }
+
+    pub async fn find_by_pid<'e, C>(db: C, org_pid: Uuid, pid: Uuid) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_as::<_, Self>("SELECT * FROM alerts WHERE pid = $1 AND organisation_pid = $2")
+            .bind(pid)
+            .bind(org_pid)
+            .fetch_optional(db)
+            .await?
+            .ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    /// Marks an open alert as being looked into.
+    pub async fn acknowledge(db: &mut PgConnection, user: &User, pid: Uuid) -> ModelResult<Self> {
+        let alert = Self::find_by_pid(&mut *db, user.organisation_pid, pid).await?;
+
+        if alert.status != "open" {
+            return Err(ModelError::Conflict(format!(
+                "The alert is already {}",
+                alert.status
+            )));
+        }
+
+        sqlx::query_as::<_, Self>(
+            "
+            UPDATE alerts
+            SET status = 'acknowledged', acknowledged_by = $2, acknowledged_at = NOW()
+            WHERE pid = $1 RETURNING *",
+        )
+        .bind(alert.pid)
+        .bind(user.pid)
+        .fetch_one(&mut *db)
+        .await
+        .map_err(Into::into)
+    }
+
+    /// Closes an open or acknowledged alert.
+    pub async fn resolve(db: &mut PgConnection, user: &User, pid: Uuid) -> ModelResult<Self> {
+        let alert = Self::find_by_pid(&mut *db, user.organisation_pid, pid).await?;
+
+        if alert.status == "resolved" {
+            return Err(ModelError::Conflict("The alert is already resolved".into()));
+        }
+
+        sqlx::query_as::<_, Self>(
+            "
+            UPDATE alerts
+            SET status = 'resolved', resolved_by = $2, resolved_at = NOW()
+            WHERE pid = $1 RETURNING *",
+        )
+        .bind(alert.pid)
+        .bind(user.pid)
+        .fetch_one(&mut *db)
+        .await
+        .map_err(Into::into)
+    }
+}
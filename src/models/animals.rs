@@ -5,7 +5,10 @@ use async_trait::async_trait;
 use chrono::{DateTime, FixedOffset, NaiveDate};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use sqlx::{Encode, Executor, FromRow, Postgres, postgres::PgQueryResult};
+use sqlx::{
+    Encode, Executor, FromRow, Postgres,
+    postgres::{PgConnection, PgQueryResult},
+};
 use uuid::Uuid;
 
 use crate::{models::dto::Gender, seed::Seedable};
@@ -13,6 +16,9 @@ use crate::{models::dto::Gender, seed::Seedable};
 use super::{
     ModelError, ModelResult,
     dto::{LinkOffspring, RegisterAnimal, UpdateAnimal},
+    events::{DomainEvent, EventType},
+    finance::LedgerEntry,
+    identifiers::RESOLVE_QUERY,
 };
 
 #[derive(Debug, Deserialize, Serialize, Encode, FromRow)]
@@ -40,19 +46,23 @@ pub struct AnimalResponse {
     pub(crate) weight_at_birth: Option<Decimal>,
     pub(crate) current_weight: Option<Decimal>,
     pub(crate) notes: Option<String>,
+    pub(crate) location_pid: Option<Uuid>,
+    pub(crate) location_name: Option<String>,
     pub(crate) created_by: Uuid,
     pub(crate) created_by_name: String,
     pub(crate) created_at: DateTime<FixedOffset>,
     pub(crate) updated_at: DateTime<FixedOffset>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Default, Deserialize, Serialize, Clone, PartialEq, Eq)]
 pub struct AnimalQuery {
     pub specie: Option<String>,
     pub breed: Option<String>,
     pub purchase_date: Option<NaiveDate>,
     pub female_parent: Option<Uuid>,
     pub male_parent: Option<Uuid>,
+    /// Animals kept in the location or any location within it.
+    pub location: Option<Uuid>,
 }
 
 #[derive(Debug, Deserialize, FromRow, Encode, Serialize)]
@@ -105,6 +115,8 @@ const SELECT_QUERY: &str = "
                 a.purchase_date,
                 a.purchase_price,
                 a.notes,
+                l.pid AS location_pid,
+                l.name AS location_name,
                 a.created_by,
                 CONCAT(u.first_name, ' ', u.last_name) AS created_by_name
             FROM
@@ -123,9 +135,11 @@ const SELECT_QUERY: &str = "
                 animals m ON a.parent_male_id = m.pid
             LEFT JOIN
                 animals n ON a.parent_male_id = n.pid
+            LEFT JOIN
+                locations l ON a.location_id = l.id
             LEFT JOIN
                 users u ON a.created_by = u.pid
-            WHERE a.organisation_pid = $1
+            WHERE a.organisation_pid = $1 AND a.deleted_at IS NULL
             ";
 
 fn select_query(conditions: &str) -> String {
@@ -180,15 +194,50 @@ impl Animal {
             .bind(parent);
         }
 
+        if let Some(location) = conditions.location {
+            query = sqlx::query_as::<_, AnimalResponse>(Box::leak(
+                select_query(
+                    "AND a.location_id IN (
+                        WITH RECURSIVE beneath AS (
+                            SELECT id FROM locations WHERE pid = $2
+                            UNION ALL
+                            SELECT c.id FROM locations c JOIN beneath b ON c.parent_id = b.id
+                        )
+                        SELECT id FROM beneath
+                    )
+                    ORDER BY a.created_at DESC",
+                )
+                .into_boxed_str(),
+            ))
+            .bind(org_pid)
+            .bind(location);
+        }
+
         query.fetch_all(db).await.map_err(Into::into)
     }
 
-    pub async fn find_most_valuable<'e, C>(
-        db: &C,
+    /// The animals with the given pids, by tag.
+    pub async fn find_by_pids<'e, C>(
+        db: C,
         org_pid: Uuid,
+        pids: &[Uuid],
     ) -> ModelResult<Vec<AnimalResponse>>
     where
-        for<'a> &'a C: Executor<'e, Database = Postgres>,
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_as::<_, AnimalResponse>(Box::leak(
+            select_query("AND a.pid = ANY($2) ORDER BY a.tag_id").into_boxed_str(),
+        ))
+        .bind(org_pid)
+        .bind(pids)
+        .fetch_all(db)
+        .await
+        .map_err(Into::into)
+    }
+
+    pub async fn find_most_valuable<'e, C>(db: C, org_pid: Uuid) -> ModelResult<Vec<AnimalResponse>>
+    where
+        C: Executor<'e, Database = Postgres>,
     {
         let query = sqlx::query_as::<_, AnimalResponse>(Box::leak(
             select_query(
@@ -217,33 +266,33 @@ impl Animal {
     }
 
     pub async fn find_by_tag_id<'e, C>(
-        db: &C,
+        db: C,
         org_pid: Uuid,
         tag_id: &str,
     ) -> ModelResult<AnimalResponse>
     where
-        for<'a> &'a C: Executor<'e, Database = Postgres>,
+        C: Executor<'e, Database = Postgres>,
     {
-        let item = sqlx::query_as::<_, AnimalResponse>(Box::leak(
-            select_query("AND a.tag_id = $2").into_boxed_str(),
-        ))
-        .bind(org_pid)
-        .bind(tag_id.trim())
-        .fetch_optional(db)
-        .await?;
+        let query = select_query(&format!(
+            "AND a.pid = (SELECT i.animal_pid FROM animal_identifiers i WHERE i.pid = ({RESOLVE_QUERY}))"
+        ));
+
+        let item = sqlx::query_as::<_, AnimalResponse>(&query)
+            .bind(org_pid)
+            .bind(tag_id.trim())
+            .fetch_optional(db)
+            .await?;
 
         item.ok_or_else(|| ModelError::EntityNotFound)
     }
 
-    pub async fn register<'e, C>(
-        db: C,
+    /// Registers the animal and emits `animal.registered`.
+    pub async fn register(
+        db: &mut PgConnection,
         org_pid: Uuid,
         user_pid: Uuid,
         params: &RegisterAnimal<'_>,
-    ) -> ModelResult<Self>
-    where
-        C: Executor<'e, Database = Postgres>,
-    {
+    ) -> ModelResult<Self> {
         let purchase_price = params.purchase_price.map(|price| Decimal::new(price, 2));
         let current_weight = params.current_weight.map(|mass| Decimal::new(mass, 2));
         let birth_weight = params.weight_at_birth.map(|mass| Decimal::new(mass, 2));
@@ -296,11 +345,11 @@ impl Animal {
                 $7,
                 $8,
                 CASE
-                    WHEN $9 IS NOT NULL THEN (SELECT pid FROM animals WHERE tag_id = $9 AND organisation_pid = $1)
+                    WHEN $9 IS NOT NULL THEN (SELECT pid FROM animals WHERE tag_id = $9 AND organisation_pid = $1 AND deleted_at IS NULL)
                     ELSE NULL
                 END,
                 CASE
-                    WHEN $10 IS NOT NULL THEN (SELECT pid FROM animals WHERE tag_id = $10 AND organisation_pid = $1)
+                    WHEN $10 IS NOT NULL THEN (SELECT pid FROM animals WHERE tag_id = $10 AND organisation_pid = $1 AND deleted_at IS NULL)
                     ELSE NULL
                 END,
                 $11,
@@ -329,22 +378,24 @@ impl Animal {
         .bind(current_weight)
         .bind(params.notes.as_deref())
         .bind(user_pid)
-        .fetch_one(db)
+        .fetch_one(&mut *db)
         .await?;
 
+        DomainEvent::emit(&mut *db, org_pid, EventType::AnimalRegistered, &query).await?;
+
         Ok(query)
     }
 
-    pub async fn update_by_id<'e, C>(
-        db: &C,
+    /// Updates the animal, emitting `animal.sold` or `animal.deceased` when
+    /// its status changes to one of them. A sold animal's `sale_price` is
+    /// posted to the ledger, and withdrawn if it turns out not to be sold.
+    pub async fn update_by_id(
+        db: &mut PgConnection,
         params: &UpdateAnimal<'_>,
         org_pid: Uuid,
         id: Uuid,
-    ) -> ModelResult<Self>
-    where
-        for<'a> &'a C: Executor<'e, Database = Postgres>,
-    {
-        let item = Self::find_by_id(db, org_pid, id).await?;
+    ) -> ModelResult<Self> {
+        let item = Self::find_by_id(&mut *db, org_pid, id).await?;
 
         let tag_id = params
             .tag_id
@@ -421,12 +472,12 @@ impl Animal {
                 status = $9,
                 parent_female_id =
                     CASE
-                        WHEN $10 IS NOT NULL THEN (SELECT pid FROM animals WHERE tag_id = $10 AND organisation_pid = $2)
+                        WHEN $10 IS NOT NULL THEN (SELECT pid FROM animals WHERE tag_id = $10 AND organisation_pid = $2 AND deleted_at IS NULL)
                         ELSE NULL
                     END,
                 parent_male_id = 
                     CASE
-                        WHEN $11 IS NOT NULL THEN (SELECT pid FROM animals WHERE tag_id = $11 AND organisation_pid = $2)
+                        WHEN $11 IS NOT NULL THEN (SELECT pid FROM animals WHERE tag_id = $11 AND organisation_pid = $2 AND deleted_at IS NULL)
                         ELSE NULL
                     END,
                 purchase_date = $12,
@@ -434,7 +485,7 @@ impl Animal {
                 weight_at_birth = $14,
                 current_weight = $15,
                 notes = $16
-            WHERE pid = $1 AND organisation_pid = $2
+            WHERE pid = $1 AND organisation_pid = $2 AND deleted_at IS NULL
             RETURNING *
             ",
         )
@@ -454,21 +505,34 @@ impl Animal {
         .bind(weight_at_birth)
         .bind(current_weight)
         .bind(notes)
-        .fetch_one(db)
+        .fetch_one(&mut *db)
         .await?;
 
+        if query.status != item.status
+            && let Some(event_type) = EventType::for_animal_status(&query.status)
+        {
+            DomainEvent::emit(&mut *db, org_pid, event_type, &query).await?;
+        }
+
+        if query.status == "sold" {
+            if let Some(price) = params.sale_price {
+                LedgerEntry::post_sale(&mut *db, &query, Decimal::new(price, 2), params.sale_date)
+                    .await?;
+            }
+        } else if item.status == "sold" {
+            LedgerEntry::withdraw_sale(&mut *db, query.pid).await?;
+        }
+
         Ok(query)
     }
 
-    pub async fn link_offspring<'e, C>(
-        db: &C,
+    pub async fn link_offspring(
+        db: &mut PgConnection,
         org_pid: Uuid,
         params: &LinkOffspring<'_>,
-    ) -> ModelResult<Self>
-    where
-        for<'a> &'a C: Executor<'e, Database = Postgres>,
-    {
-        let offspring = Self::find_by_tag_id(db, org_pid, params.offspring_tag_id.as_ref()).await?;
+    ) -> ModelResult<Self> {
+        let offspring =
+            Self::find_by_tag_id(&mut *db, org_pid, params.offspring_tag_id.as_ref()).await?;
         match params.parent_gender {
             Gender::Male => {
                 let query = sqlx::query_as::<_, Self>("
@@ -476,17 +540,17 @@ impl Animal {
                     SET
                         parent_male_id =
                         CASE
-                            WHEN $1 IS NOT NULL THEN (SELECT pid FROM animals WHERE tag_id = $1 AND organisation_pid = $2)
+                            WHEN $1 IS NOT NULL THEN (SELECT pid FROM animals WHERE tag_id = $1 AND organisation_pid = $2 AND deleted_at IS NULL)
                             ELSE NULL
                         END
                     WHERE
-                        pid = $3 AND organisation_pid = $2
+                        pid = $3 AND organisation_pid = $2 AND deleted_at IS NULL
                     RETURNING *
                 ")
                 .bind(params.parent_tag_id.as_ref())
                 .bind(org_pid)
                 .bind(offspring.pid)
-                .fetch_one(db)
+                .fetch_one(&mut *db)
                 .await?;
                 Ok(query)
             }
@@ -496,17 +560,17 @@ impl Animal {
                     SET
                         parent_female_id =
                         CASE
-                            WHEN $1 IS NOT NULL THEN (SELECT pid FROM animals WHERE tag_id = $1 AND organisation_pid = $2)
+                            WHEN $1 IS NOT NULL THEN (SELECT pid FROM animals WHERE tag_id = $1 AND organisation_pid = $2 AND deleted_at IS NULL)
                             ELSE NULL
                         END
                     WHERE
-                        pid = $3 AND organisation_pid = $2
+                        pid = $3 AND organisation_pid = $2 AND deleted_at IS NULL
                     RETURNING *
                 ")
                 .bind(params.parent_tag_id.as_ref())
                 .bind(org_pid)
                 .bind(offspring.pid)
-                .fetch_one(db)
+                .fetch_one(&mut *db)
                 .await?;
                 Ok(query)
             }
@@ -519,16 +583,43 @@ impl Animal {
         Self::seed_data(db, &animals).await
     }
 
-    pub async fn delete_by_id<'e, C>(db: C, org_id: Uuid, id: Uuid) -> ModelResult<PgQueryResult>
+    /// Moves the animal to the trash. Its records stay in place and reappear
+    /// when it is restored.
+    pub async fn delete_by_id<'e, C>(
+        db: C,
+        org_id: Uuid,
+        id: Uuid,
+        user_pid: Uuid,
+    ) -> ModelResult<PgQueryResult>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query(
+            "UPDATE animals SET deleted_at = NOW(), deleted_by = $3
+            WHERE pid = $1 AND organisation_pid = $2 AND deleted_at IS NULL",
+        )
+        .bind(id)
+        .bind(org_id)
+        .bind(user_pid)
+        .execute(db)
+        .await
+        .map_err(Into::into)
+    }
+
+    pub async fn restore_by_id<'e, C>(db: C, org_id: Uuid, id: Uuid) -> ModelResult<Self>
     where
         C: Executor<'e, Database = Postgres>,
     {
-        sqlx::query("DELETE FROM animals WHERE pid = $1 AND organisation_pid = $2")
-            .bind(id)
-            .bind(org_id)
-            .execute(db)
-            .await
-            .map_err(Into::into)
+        sqlx::query_as::<_, Self>(
+            "UPDATE animals SET deleted_at = NULL, deleted_by = NULL
+            WHERE pid = $1 AND organisation_pid = $2 AND deleted_at IS NOT NULL
+            RETURNING *",
+        )
+        .bind(id)
+        .bind(org_id)
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| ModelError::EntityNotFound)
     }
 }
 
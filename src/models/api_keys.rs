@@ -0,0 +1,290 @@
+#![allow(clippy::missing_errors_doc)]
+
+use argon2::{Argon2, PasswordHash, PasswordVerifier};
+use chrono::{DateTime, FixedOffset, Utc};
+use rand::{Rng, distr::Alphanumeric};
+use serde::{Deserialize, Serialize};
+use sqlx::{Executor, PgConnection, Postgres, prelude::FromRow, types::Json};
+use uuid::Uuid;
+
+use super::{
+    ModelError, ModelResult,
+    dto::{CreateApiKey, UpdateApiKey, Validator},
+    roles::{Permission, Role, Scope},
+    users::User,
+};
+
+/// Marks a bearer token as an API key rather than a JWT.
+pub const API_KEY_PREFIX: &str = "plk_";
+
+#[derive(Debug, Deserialize, Serialize, FromRow, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiKey {
+    pub id: i32,
+    pub pid: Uuid,
+    pub organisation_pid: Uuid,
+    pub name: String,
+    #[serde(skip_serializing)]
+    pub(crate) secret_hash: String,
+    pub scopes: Vec<String>,
+    /// The admin the key acts for.
+    pub created_by: Uuid,
+    pub expires_at: Option<DateTime<FixedOffset>>,
+    pub last_used_at: Option<DateTime<FixedOffset>>,
+    pub revoked_at: Option<DateTime<FixedOffset>>,
+    pub created_at: DateTime<FixedOffset>,
+    pub updated_at: DateTime<FixedOffset>,
+}
+
+/// A key along with its token. Only a hash of the token's secret is stored, so
+/// this is the one chance to hand it over.
+#[derive(Debug, Clone)]
+pub struct IssuedApiKey {
+    pub api_key: ApiKey,
+    pub token: String,
+}
+
+impl ApiKey {
+    /// A token of the form `plk_<key pid>.<secret>` and the hash of its
+    /// secret.
+    fn issue_token(pid: Uuid) -> ModelResult<(String, String)> {
+        let secret: String = rand::rng()
+            .sample_iter(&Alphanumeric)
+            .take(40)
+            .map(char::from)
+            .collect();
+
+        let hash = User::hash_password(&secret)?;
+
+        Ok((format!("{API_KEY_PREFIX}{pid}.{secret}"), hash))
+    }
+
+    /// Keys may only be granted what their creator's role allows.
+    async fn check_scopes(
+        db: &mut PgConnection,
+        creator: &User,
+        scopes: &[Scope],
+    ) -> ModelResult<Vec<String>> {
+        let role =
+            Role::find_for_organisation(&mut *db, creator.organisation_pid, &creator.role).await?;
+
+        if let Some(scope) = scopes
+            .iter()
+            .find(|scope| !role.allows(scope.resource, scope.action))
+        {
+            return Err(ModelError::Validation(format!(
+                "Your role does not allow granting {scope}"
+            )));
+        }
+
+        Ok(scopes.iter().map(ToString::to_string).collect())
+    }
+
+    fn check_expiry(expires_at: Option<DateTime<FixedOffset>>) -> ModelResult<()> {
+        if expires_at.is_some_and(|expires_at| expires_at <= Utc::now()) {
+            return Err(ModelError::Validation(
+                "Expiry must be in the future".into(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn unique_name(error: sqlx::Error) -> ModelError {
+        match error {
+            sqlx::Error::Database(err) if err.constraint() == Some("api_keys_live_name_idx") => {
+                ModelError::EntityAlreadyExists("An API key with that name already exists".into())
+            }
+            error => ModelError::Sqlx(error),
+        }
+    }
+
+    #[must_use]
+    pub fn is_usable(&self) -> bool {
+        self.revoked_at.is_none()
+            && self
+                .expires_at
+                .is_none_or(|expires_at| expires_at > Utc::now())
+    }
+
+    /// The role the key acts with: its scopes, less anything its creator's
+    /// current role no longer allows.
+    #[must_use]
+    pub fn restrict(&self, role: &Role) -> Role {
+        let scopes = self
+            .scopes
+            .iter()
+            .filter_map(|scope| scope.parse::<Scope>().ok())
+            .filter(|scope| role.allows(scope.resource, scope.action));
+
+        Role {
+            permissions: Json(Permission::from_scopes(scopes)),
+            ..role.clone()
+        }
+    }
+
+    pub async fn create(
+        db: &mut PgConnection,
+        creator: &User,
+        dto: &CreateApiKey<'_>,
+    ) -> ModelResult<IssuedApiKey> {
+        let validator = Validator::new(dto);
+        let dto = validator.validate()?;
+
+        Self::check_expiry(dto.expires_at)?;
+        let scopes = Self::check_scopes(&mut *db, creator, &dto.scopes()?).await?;
+
+        let pid = Uuid::new_v4();
+        let (token, secret_hash) = Self::issue_token(pid)?;
+
+        let api_key = sqlx::query_as::<_, Self>(
+            "
+            INSERT INTO api_keys
+            (pid, organisation_pid, name, secret_hash, scopes, created_by, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING *",
+        )
+        .bind(pid)
+        .bind(creator.organisation_pid)
+        .bind(dto.name.trim())
+        .bind(secret_hash)
+        .bind(scopes)
+        .bind(creator.pid)
+        .bind(dto.expires_at)
+        .fetch_one(&mut *db)
+        .await
+        .map_err(Self::unique_name)?;
+
+        Ok(IssuedApiKey { api_key, token })
+    }
+
+    /// The organisation's keys, newest first. Revoked keys are kept so audit
+    /// entries made with them can still be traced.
+    pub async fn find_all<'e, C>(db: C, org_pid: Uuid) -> ModelResult<Vec<Self>>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_as::<_, Self>(
+            "SELECT * FROM api_keys WHERE organisation_pid = $1 ORDER BY created_at DESC, id DESC",
+        )
+        .bind(org_pid)
+        .fetch_all(db)
+        .await
+        .map_err(Into::into)
+    }
+
+    pub async fn find_by_pid<'e, C>(db: C, org_pid: Uuid, pid: Uuid) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_as::<_, Self>("SELECT * FROM api_keys WHERE pid = $1 AND organisation_pid = $2")
+            .bind(pid)
+            .bind(org_pid)
+            .fetch_optional(db)
+            .await?
+            .ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    /// Renames the key, changes its scopes or its expiry. The token stays the
+    /// same.
+    pub async fn update(
+        db: &mut PgConnection,
+        editor: &User,
+        pid: Uuid,
+        dto: &UpdateApiKey<'_>,
+    ) -> ModelResult<Self> {
+        let validator = Validator::new(dto);
+        let dto = validator.validate()?;
+
+        let api_key = Self::find_by_pid(&mut *db, editor.organisation_pid, pid).await?;
+
+        if api_key.revoked_at.is_some() {
+            return Err(ModelError::Validation(
+                "API key has already been revoked".into(),
+            ));
+        }
+
+        Self::check_expiry(dto.expires_at)?;
+        let scopes = match dto.scopes()? {
+            Some(scopes) => Some(Self::check_scopes(&mut *db, editor, &scopes).await?),
+            None => None,
+        };
+
+        sqlx::query_as::<_, Self>(
+            "
+            UPDATE api_keys SET
+                name = COALESCE($2, name),
+                scopes = COALESCE($3, scopes),
+                expires_at = COALESCE($4, expires_at)
+            WHERE pid = $1 RETURNING *",
+        )
+        .bind(pid)
+        .bind(dto.name.as_deref().map(str::trim))
+        .bind(scopes)
+        .bind(dto.expires_at)
+        .fetch_one(&mut *db)
+        .await
+        .map_err(Self::unique_name)
+    }
+
+    pub async fn revoke(db: &mut PgConnection, org_pid: Uuid, pid: Uuid) -> ModelResult<Self> {
+        let api_key = Self::find_by_pid(&mut *db, org_pid, pid).await?;
+
+        if api_key.revoked_at.is_some() {
+            return Err(ModelError::Validation(
+                "API key has already been revoked".into(),
+            ));
+        }
+
+        sqlx::query_as::<_, Self>(
+            "UPDATE api_keys SET revoked_at = NOW() WHERE pid = $1 RETURNING *",
+        )
+        .bind(pid)
+        .fetch_one(&mut *db)
+        .await
+        .map_err(Into::into)
+    }
+
+    /// Finds the usable key a token was issued for and records that it was
+    /// used. Unknown, revoked and expired keys are all `None`.
+    pub async fn authenticate<'e, C>(db: C, token: &str) -> ModelResult<Option<Self>>
+    where
+        C: Executor<'e, Database = Postgres> + Copy,
+    {
+        let Some((pid, secret)) = token
+            .trim()
+            .strip_prefix(API_KEY_PREFIX)
+            .and_then(|token| token.split_once('.'))
+        else {
+            return Ok(None);
+        };
+        let Ok(pid) = Uuid::parse_str(pid) else {
+            return Ok(None);
+        };
+
+        let Some(api_key) = sqlx::query_as::<_, Self>("SELECT * FROM api_keys WHERE pid = $1")
+            .bind(pid)
+            .fetch_optional(db)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let hash = PasswordHash::new(&api_key.secret_hash)?;
+
+        if Argon2::default()
+            .verify_password(secret.as_bytes(), &hash)
+            .is_err()
+            || !api_key.is_usable()
+        {
+            return Ok(None);
+        }
+
+        sqlx::query_as::<_, Self>(
+            "UPDATE api_keys SET last_used_at = NOW() WHERE pid = $1 RETURNING *",
+        )
+        .bind(pid)
+        .fetch_optional(db)
+        .await
+        .map_err(Into::into)
+    }
+}
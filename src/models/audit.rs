@@ -0,0 +1,142 @@
+#![allow(clippy::missing_errors_doc)]
+
+use chrono::{DateTime, FixedOffset, NaiveDate};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{Executor, Postgres, prelude::FromRow, types::Json};
+use uuid::Uuid;
+
+use super::ModelResult;
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct AuditQuery {
+    pub table: Option<String>,
+    pub record: Option<String>,
+    pub user: Option<Uuid>,
+    pub api_key: Option<Uuid>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditLog {
+    pub id: i32,
+    pub organisation_pid: Option<Uuid>,
+    pub table_name: String,
+    pub record_id: String,
+    pub action: String,
+    pub old_data: Option<Json<Value>>,
+    pub new_data: Option<Json<Value>>,
+    pub changed_by: Option<Uuid>,
+    pub changed_by_name: Option<String>,
+    /// The platform operator who made the change while impersonating
+    /// `changed_by`, if any.
+    pub impersonator_pid: Option<Uuid>,
+    pub impersonator_name: Option<String>,
+    /// The API key the change was made with, if any.
+    pub api_key_pid: Option<Uuid>,
+    pub api_key_name: Option<String>,
+    pub changed_at: DateTime<FixedOffset>,
+}
+
+const FETCH_QUERY: &str = "
+    SELECT
+        al.id,
+        al.organisation_pid,
+        al.table_name,
+        al.record_id,
+        al.action,
+        al.old_data,
+        al.new_data,
+        al.changed_by,
+        NULLIF(CONCAT_WS(' ', u.first_name, u.last_name), '') AS changed_by_name,
+        al.impersonator_pid,
+        NULLIF(CONCAT_WS(' ', o.first_name, o.last_name), '') AS impersonator_name,
+        al.api_key_pid,
+        k.name AS api_key_name,
+        al.changed_at
+    FROM
+        audit_logs al
+    LEFT JOIN
+        users u ON al.changed_by = u.pid
+    LEFT JOIN
+        users o ON al.impersonator_pid = o.pid
+    LEFT JOIN
+        api_keys k ON al.api_key_pid = k.pid
+    WHERE
+        al.organisation_pid = $1
+";
+
+/// Tables whose rows reference an animal through `animal_pid`.
+const ANIMAL_RECORD_TABLES: [&str; 3] = ["health_records", "production_records", "weight_records"];
+
+const DEFAULT_LIMIT: i64 = 100;
+
+impl AuditLog {
+    pub async fn find_all<'e, C>(
+        db: C,
+        org_pid: Uuid,
+        conditions: &AuditQuery,
+    ) -> ModelResult<Vec<Self>>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        let query = format!(
+            "{FETCH_QUERY}
+            AND ($2::TEXT IS NULL OR al.table_name = $2)
+            AND ($3::TEXT IS NULL OR al.record_id = $3)
+            AND ($4::UUID IS NULL OR al.changed_by = $4)
+            AND ($5::UUID IS NULL OR al.api_key_pid = $5)
+            AND ($6::DATE IS NULL OR al.changed_at >= $6)
+            AND ($7::DATE IS NULL OR al.changed_at < $7 + 1)
+            ORDER BY al.changed_at DESC, al.id DESC
+            LIMIT $8"
+        );
+
+        sqlx::query_as::<_, Self>(&query)
+            .bind(org_pid)
+            .bind(conditions.table.as_deref())
+            .bind(conditions.record.as_deref())
+            .bind(conditions.user)
+            .bind(conditions.api_key)
+            .bind(conditions.from)
+            .bind(conditions.to)
+            .bind(conditions.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, 1000))
+            .fetch_all(db)
+            .await
+            .map_err(Into::into)
+    }
+
+    /// Changes to the animal itself and to its health, production and weight
+    /// records, oldest first.
+    pub async fn find_by_animal<'e, C>(
+        db: C,
+        org_pid: Uuid,
+        animal_pid: Uuid,
+    ) -> ModelResult<Vec<Self>>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        let query = format!(
+            "{FETCH_QUERY}
+            AND (
+                (al.table_name = 'animals' AND al.record_id = $2::TEXT)
+                OR (
+                    al.table_name = ANY($3)
+                    AND COALESCE(al.new_data, al.old_data) ->> 'animal_pid' = $2::TEXT
+                )
+            )
+            ORDER BY al.changed_at, al.id"
+        );
+
+        sqlx::query_as::<_, Self>(&query)
+            .bind(org_pid)
+            .bind(animal_pid.to_string())
+            .bind(ANIMAL_RECORD_TABLES.as_slice())
+            .fetch_all(db)
+            .await
+            .map_err(Into::into)
+    }
+}
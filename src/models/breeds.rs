@@ -4,7 +4,11 @@
 use async_trait::async_trait;
 use chrono::{DateTime, FixedOffset};
 use serde::{Deserialize, Serialize};
-use sqlx::{Encode, Executor, Postgres, postgres::PgQueryResult, prelude::FromRow};
+use sqlx::{
+    Encode, Executor, Postgres,
+    postgres::{PgConnection, PgQueryResult},
+    prelude::FromRow,
+};
 use uuid::Uuid;
 
 use crate::{models::species::Specie, seed::Seedable};
@@ -37,9 +41,9 @@ pub struct Breed {
 
 impl Breed {
     #[tracing::instrument(skip(db))]
-    pub async fn find_by_id<'e, C>(db: &C, id: i32, org_pid: Uuid) -> ModelResult<Self>
+    pub async fn find_by_id<'e, C>(db: C, id: i32, org_pid: Uuid) -> ModelResult<Self>
     where
-        for<'a> &'a C: Executor<'e, Database = Postgres>,
+        C: Executor<'e, Database = Postgres>,
     {
         sqlx::query_as::<_, Self>("
                 SELECT * FROM breeds WHERE id = $1 AND (is_system_defined = TRUE OR organisation_pid = $2)"
@@ -147,16 +151,13 @@ impl Breed {
         Self::seed_data(db, &breeds).await
     }
 
-    pub async fn update_by_id<'e, C>(
-        db: &C,
+    pub async fn update_by_id(
+        db: &mut PgConnection,
         org_pid: Uuid,
         id: i32,
         params: &UpdateBreed<'_>,
-    ) -> ModelResult<Self>
-    where
-        for<'a> &'a C: Executor<'e, Database = Postgres>,
-    {
-        let model = Self::find_by_id(db, id, org_pid).await?;
+    ) -> ModelResult<Self> {
+        let model = Self::find_by_id(&mut *db, id, org_pid).await?;
 
         let name = params
             .name
@@ -180,9 +181,9 @@ impl Breed {
         );
 
         let specie = if let Some(specie) = params.specie.as_ref() {
-            Specie::find_by_name(db, specie).await?
+            Specie::find_by_name(&mut *db, specie).await?
         } else {
-            Specie::find_by_id(db, model.specie_id).await?
+            Specie::find_by_id(&mut *db, model.specie_id).await?
         };
 
         let query = sqlx::query_as::<_, Self>(
@@ -208,7 +209,7 @@ impl Breed {
         .bind(&male_weight)
         .bind(&female_weight)
         .bind(&gestation_period)
-        .fetch_one(db)
+        .fetch_one(&mut *db)
         .await?;
 
         Ok(query)
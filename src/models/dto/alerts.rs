@@ -0,0 +1,77 @@
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+use crate::models::alerts::{ALERT_CHANNELS, AlertKind};
+
+#[derive(Debug, Deserialize, Serialize, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateAlertRule<'a> {
+    #[validate(length(min = 2, max = 100, message = "Name must have 2-100 characters"))]
+    pub name: Cow<'a, str>,
+    #[validate(custom(function = "validate_kind"))]
+    pub kind: Cow<'a, str>,
+    /// A percentage for drops, a number of records for repeated ones.
+    #[validate(range(exclusive_min = 0.0, message = "Threshold must be above 0"))]
+    pub threshold: Option<f64>,
+    #[validate(range(min = 1, max = 3650, message = "Window must be 1-3650 days"))]
+    pub window_days: Option<i32>,
+    #[validate(custom(function = "validate_severity"))]
+    pub severity: Option<Cow<'a, str>>,
+    #[validate(custom(function = "validate_channels"))]
+    pub channels: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateAlertRule<'a> {
+    #[validate(length(min = 2, max = 100, message = "Name must have 2-100 characters"))]
+    pub name: Option<Cow<'a, str>>,
+    #[validate(range(exclusive_min = 0.0, message = "Threshold must be above 0"))]
+    pub threshold: Option<f64>,
+    #[validate(range(min = 1, max = 3650, message = "Window must be 1-3650 days"))]
+    pub window_days: Option<i32>,
+    #[validate(custom(function = "validate_severity"))]
+    pub severity: Option<Cow<'a, str>>,
+    #[validate(custom(function = "validate_channels"))]
+    pub channels: Option<Vec<String>>,
+    pub is_active: Option<bool>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct AlertQuery {
+    pub status: Option<String>,
+    pub rule: Option<Uuid>,
+    pub animal: Option<Uuid>,
+}
+
+fn validate_kind(kind: &str) -> Result<(), ValidationError> {
+    kind.parse::<AlertKind>().map(|_| ()).map_err(|_| {
+        ValidationError::new("invalid_kind").with_message(Cow::Borrowed(
+            "Kind must be one of weight_drop, weighing_overdue, milk_yield_drop, repeated_high_severity or vaccination_overdue",
+        ))
+    })
+}
+
+fn validate_severity(severity: &str) -> Result<(), ValidationError> {
+    if ["low", "medium", "high"].contains(&severity) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("invalid_severity")
+            .with_message(Cow::Borrowed("Severity must be one of low, medium or high")))
+    }
+}
+
+fn validate_channels(channels: &[String]) -> Result<(), ValidationError> {
+    if channels
+        .iter()
+        .all(|channel| ALERT_CHANNELS.contains(&channel.as_str()))
+    {
+        Ok(())
+    } else {
+        Err(ValidationError::new("invalid_channel")
+            .with_message(Cow::Borrowed("Channels must be stream or webhook")))
+    }
+}
@@ -2,6 +2,7 @@
 
 use std::borrow::Cow;
 
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 use validator::Validate;
 
@@ -141,7 +142,7 @@ pub struct RegisterAnimal<'a> {
     pub notes: Option<Cow<'a, str>>,
 }
 
-#[derive(Debug, Deserialize, Validate, Serialize, Clone)]
+#[derive(Debug, Default, Deserialize, Validate, Serialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct UpdateAnimal<'a> {
     pub tag_id: Option<Cow<'a, str>>,
@@ -158,6 +159,11 @@ pub struct UpdateAnimal<'a> {
     pub weight_at_birth: Option<i64>,
     pub current_weight: Option<i64>,
     pub notes: Option<Cow<'a, str>>,
+    /// What a sold animal was sold for, posted to the ledger as its sale
+    /// proceeds.
+    pub sale_price: Option<i64>,
+    /// When it was sold, today by default.
+    pub sale_date: Option<NaiveDate>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
@@ -0,0 +1,58 @@
+use std::borrow::Cow;
+
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use super::roles::validate_scopes;
+use crate::models::{ModelResult, roles::Scope};
+
+#[derive(Debug, Deserialize, Serialize, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateApiKey<'a> {
+    #[validate(length(min = 2, max = 100, message = "Name must have 2-100 characters"))]
+    pub name: Cow<'a, str>,
+    #[validate(
+        length(min = 1, message = "An API key needs at least one scope"),
+        custom(function = "validate_scopes")
+    )]
+    pub scopes: Vec<String>,
+    /// Keys without an expiry work until they are revoked.
+    pub expires_at: Option<DateTime<FixedOffset>>,
+}
+
+impl CreateApiKey<'_> {
+    /// Parses the requested `resource:action` scopes.
+    ///
+    /// # Errors
+    /// Fails if any of the scopes is not in the catalogue.
+    pub fn scopes(&self) -> ModelResult<Vec<Scope>> {
+        self.scopes.iter().map(|scope| scope.parse()).collect()
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateApiKey<'a> {
+    #[validate(length(min = 2, max = 100, message = "Name must have 2-100 characters"))]
+    pub name: Option<Cow<'a, str>>,
+    #[validate(
+        length(min = 1, message = "An API key needs at least one scope"),
+        custom(function = "validate_scopes")
+    )]
+    pub scopes: Option<Vec<String>>,
+    pub expires_at: Option<DateTime<FixedOffset>>,
+}
+
+impl UpdateApiKey<'_> {
+    /// Parses the requested `resource:action` scopes, if any.
+    ///
+    /// # Errors
+    /// Fails if any of the scopes is not in the catalogue.
+    pub fn scopes(&self) -> ModelResult<Option<Vec<Scope>>> {
+        self.scopes
+            .as_ref()
+            .map(|scopes| scopes.iter().map(|scope| scope.parse()).collect())
+            .transpose()
+    }
+}
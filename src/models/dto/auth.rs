@@ -1,6 +1,7 @@
 use std::borrow::Cow;
 
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 use validator::{Validate, ValidationError};
 
 use crate::models::orgs::RegisterOrg;
@@ -32,6 +33,7 @@ pub struct LoginUser<'a> {
 pub struct UpdatePassword<'a> {
     #[validate(email(message = "Invalid e-mail address"))]
     pub email: Cow<'a, str>,
+    pub current_password: Cow<'a, str>,
     #[validate(length(min = 5, max = 50, message = "Password must have 8-48 characters"))]
     pub password: Cow<'a, str>,
     #[validate(must_match(other = "password", message = "Passwords must match"))]
@@ -40,7 +42,7 @@ pub struct UpdatePassword<'a> {
 
 #[derive(Debug, Deserialize, Serialize, Clone, Validate)]
 #[serde(rename_all = "camelCase")]
-pub struct CreateNewUser<'a> {
+pub struct InviteUser<'a> {
     #[validate(email(message = "Invalid e-mail address"))]
     pub email: Cow<'a, str>,
     #[validate(length(min = 2, max = 50, message = "First name must have 2-50 characters"))]
@@ -51,16 +53,51 @@ pub struct CreateNewUser<'a> {
     pub role: Cow<'a, str>,
 }
 
+#[derive(Debug, Deserialize, Serialize, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct AcceptInvitation<'a> {
+    pub token: Cow<'a, str>,
+    #[validate(length(min = 5, max = 50, message = "Password must have 8-48 characters"))]
+    pub password: Cow<'a, str>,
+    #[validate(must_match(other = "password", message = "Passwords must match"))]
+    pub confirm_password: Cow<'a, str>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateUserRole<'a> {
+    #[validate(custom(function = "validate_role"))]
+    pub role: Cow<'a, str>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct SwitchOrganisation {
+    pub organisation: Uuid,
+}
+
+/// What an OIDC provider sends back to the callback: a code on
+/// success, an error when the person cancelled or was refused.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct OidcCallback {
+    pub state: String,
+    pub code: Option<String>,
+    pub error: Option<String>,
+    pub error_description: Option<String>,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct RegisterAdminParams<'a> {
     pub organisation: RegisterOrg<'a>,
     pub user: RegisterAdmin<'a>,
 }
 
+// Whether the role exists is checked against the organisation's roles when
+// the user is saved.
 fn validate_role(role: &str) -> Result<(), ValidationError> {
-    match role.to_lowercase().trim() {
-        "admin" | "manager" | "staff" => Ok(()),
-        _ => Err(ValidationError::new("invalid_role")
-            .with_message(Cow::Borrowed("Only admin, manager and staff roles allowed"))),
+    if (1..=50).contains(&role.trim().len()) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("invalid_role")
+            .with_message(Cow::Borrowed("Role must have 1-50 characters")))
     }
 }
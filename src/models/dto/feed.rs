@@ -0,0 +1,157 @@
+use std::borrow::Cow;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+/// Nutrients are given in hundredths, like quantities: `8850` is 88.5%.
+#[derive(Debug, Deserialize, Serialize, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct NewFeedstuff<'a> {
+    #[validate(length(min = 1, max = 100, message = "Name must have 1 to 100 characters"))]
+    pub name: Cow<'a, str>,
+    #[validate(range(min = 0, max = 10000, message = "Dry matter must be 0 to 100%"))]
+    pub dry_matter: Option<i64>,
+    #[validate(range(min = 0, max = 10000, message = "Crude protein must be 0 to 100%"))]
+    pub crude_protein: Option<i64>,
+    #[validate(range(min = 0, max = 10000, message = "Crude fibre must be 0 to 100%"))]
+    pub crude_fibre: Option<i64>,
+    /// Megajoules of metabolisable energy per kilogram of dry matter.
+    #[validate(range(min = 0, message = "Energy must not be negative"))]
+    pub energy: Option<i64>,
+    /// Stock at or below which the feedstuff counts as running low.
+    #[validate(range(min = 0, message = "Reorder level must not be negative"))]
+    pub reorder_level: Option<i64>,
+    pub notes: Option<Cow<'a, str>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateFeedstuff<'a> {
+    #[validate(length(min = 1, max = 100, message = "Name must have 1 to 100 characters"))]
+    pub name: Option<Cow<'a, str>>,
+    #[validate(range(min = 0, max = 10000, message = "Dry matter must be 0 to 100%"))]
+    pub dry_matter: Option<i64>,
+    #[validate(range(min = 0, max = 10000, message = "Crude protein must be 0 to 100%"))]
+    pub crude_protein: Option<i64>,
+    #[validate(range(min = 0, max = 10000, message = "Crude fibre must be 0 to 100%"))]
+    pub crude_fibre: Option<i64>,
+    #[validate(range(min = 0, message = "Energy must not be negative"))]
+    pub energy: Option<i64>,
+    #[validate(range(min = 0, message = "Reorder level must not be negative"))]
+    pub reorder_level: Option<i64>,
+    pub notes: Option<Cow<'a, str>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct NewFeedPurchase<'a> {
+    pub feedstuff: Uuid,
+    #[validate(range(min = 1, message = "Quantity must be above 0"))]
+    pub quantity: i64,
+    /// In cents. Posted to the ledger as a feed expense.
+    #[validate(range(min = 0, message = "Cost must not be negative"))]
+    pub cost: Option<i64>,
+    #[validate(length(max = 100, message = "Supplier must have at most 100 characters"))]
+    pub supplier: Option<Cow<'a, str>>,
+    pub purchase_date: Option<NaiveDate>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RationItem {
+    pub feedstuff: Uuid,
+    /// Per head per day.
+    pub quantity: i64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct NewRation<'a> {
+    #[validate(length(min = 1, max = 100, message = "Name must have 1 to 100 characters"))]
+    pub name: Cow<'a, str>,
+    /// The specie, and optionally breed, whose animals the ration feeds.
+    pub specie: Option<Cow<'a, str>>,
+    pub breed: Option<Cow<'a, str>>,
+    /// The pid of the group the ration feeds, instead of a specie.
+    pub group: Option<Uuid>,
+    #[validate(custom(function = "validate_items"))]
+    pub items: Vec<RationItem>,
+    pub notes: Option<Cow<'a, str>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateRation<'a> {
+    #[validate(length(min = 1, max = 100, message = "Name must have 1 to 100 characters"))]
+    pub name: Option<Cow<'a, str>>,
+    /// A specie or breed takes the ration off its group, and a group off its
+    /// specie and breed.
+    pub specie: Option<Cow<'a, str>>,
+    pub breed: Option<Cow<'a, str>>,
+    pub group: Option<Uuid>,
+    /// Replaces every item of the ration.
+    #[validate(custom(function = "validate_items"))]
+    pub items: Option<Vec<RationItem>>,
+    pub notes: Option<Cow<'a, str>>,
+}
+
+/// Feeds a ration for a day to the animals tagged, or to every active animal
+/// it is meant for.
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedRation<'a> {
+    pub feeding_date: Option<NaiveDate>,
+    pub tag_ids: Option<Vec<Cow<'a, str>>>,
+    pub notes: Option<Cow<'a, str>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct NewFeeding<'a> {
+    pub feedstuff: Uuid,
+    /// In total, shared equally by the animals tagged.
+    #[validate(range(min = 1, message = "Quantity must be above 0"))]
+    pub quantity: i64,
+    pub feeding_date: Option<NaiveDate>,
+    pub tag_ids: Option<Vec<Cow<'a, str>>>,
+    pub notes: Option<Cow<'a, str>>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct FeedQuery {
+    pub feedstuff: Option<Uuid>,
+    pub animal: Option<Uuid>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+/// How stock is projected: from the average daily use over the last `days`,
+/// flagging feedstuffs that run out `within` days.
+#[derive(Debug, Default, Clone, Copy, Deserialize, Serialize)]
+pub struct StockQuery {
+    pub days: Option<i64>,
+    pub within: Option<i64>,
+}
+
+/// The period a feed conversion covers, all time by default.
+#[derive(Debug, Default, Clone, Copy, Deserialize, Serialize)]
+pub struct ConversionQuery {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+fn validate_items(items: &[RationItem]) -> Result<(), ValidationError> {
+    if items.is_empty() {
+        return Err(ValidationError::new("no_items")
+            .with_message(Cow::Borrowed("A ration needs at least one feedstuff")));
+    }
+
+    if items.iter().any(|item| item.quantity < 1) {
+        return Err(ValidationError::new("invalid_quantity")
+            .with_message(Cow::Borrowed("Quantities must be above 0")));
+    }
+
+    Ok(())
+}
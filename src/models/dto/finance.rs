@@ -0,0 +1,63 @@
+use std::borrow::Cow;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+use crate::models::finance::LedgerCategory;
+
+#[derive(Debug, Deserialize, Serialize, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct NewLedgerEntry<'a> {
+    #[validate(custom(function = "validate_category"))]
+    pub category: Cow<'a, str>,
+    /// In cents, like every other amount the API takes.
+    #[validate(range(min = 1, message = "Amount must be above 0"))]
+    pub amount: i64,
+    /// Defaults to the organisation's today.
+    pub entry_date: Option<NaiveDate>,
+    #[validate(length(max = 500, message = "Description must have at most 500 characters"))]
+    pub description: Option<Cow<'a, str>>,
+    /// The animal the entry is for. With neither it nor a `group`, the entry
+    /// is for the whole organisation.
+    pub tag_id: Option<Cow<'a, str>>,
+    /// The pid of the group the entry is for, instead of an animal.
+    pub group: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateLedgerEntry<'a> {
+    #[validate(custom(function = "validate_category"))]
+    pub category: Option<Cow<'a, str>>,
+    #[validate(range(min = 1, message = "Amount must be above 0"))]
+    pub amount: Option<i64>,
+    pub entry_date: Option<NaiveDate>,
+    #[validate(length(max = 500, message = "Description must have at most 500 characters"))]
+    pub description: Option<Cow<'a, str>>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct LedgerQuery {
+    pub category: Option<String>,
+    pub animal: Option<Uuid>,
+    pub group: Option<Uuid>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+/// The period a profit and loss covers, all time by default.
+#[derive(Debug, Default, Clone, Copy, Deserialize, Serialize)]
+pub struct ProfitQuery {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+fn validate_category(category: &str) -> Result<(), ValidationError> {
+    category.parse::<LedgerCategory>().map(|_| ()).map_err(|_| {
+        ValidationError::new("invalid_category").with_message(Cow::Borrowed(
+            "Category must be one of purchase, feed, vet, labour, other_expense, sale_proceeds, milk_sales or other_income",
+        ))
+    })
+}
@@ -0,0 +1,131 @@
+use std::borrow::Cow;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+use super::{MoveAnimals, records::NewHealthRecord};
+use crate::models::animals::AnimalQuery;
+
+#[derive(Debug, Deserialize, Serialize, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct NewGroup<'a> {
+    #[validate(length(min = 1, max = 100, message = "Name must have 1 to 100 characters"))]
+    pub name: Cow<'a, str>,
+    /// `static` or `dynamic`.
+    #[validate(custom(function = "validate_kind"))]
+    pub kind: Cow<'a, str>,
+    /// Which animals a dynamic group holds, with the filters of `GET /animals`.
+    /// Unlike there, every filter given applies.
+    pub filter: Option<AnimalQuery>,
+    /// The first members of a static group.
+    pub tag_ids: Option<Vec<Cow<'a, str>>>,
+    pub notes: Option<Cow<'a, str>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateGroup<'a> {
+    #[validate(length(min = 1, max = 100, message = "Name must have 1 to 100 characters"))]
+    pub name: Option<Cow<'a, str>>,
+    /// Replaces the filter of a dynamic group.
+    pub filter: Option<AnimalQuery>,
+    pub notes: Option<Cow<'a, str>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupMembers<'a> {
+    #[validate(length(min = 1, message = "Tag IDs must name at least one animal"))]
+    pub tag_ids: Vec<Cow<'a, str>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupWeight<'a> {
+    pub tag_id: Cow<'a, str>,
+    pub mass: i64,
+}
+
+/// A weighing of some or all of a group's members, on one day.
+#[derive(Debug, Deserialize, Serialize, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupWeighing<'a> {
+    pub record_date: NaiveDate,
+    #[validate(length(min = 1, message = "Weights must weigh at least one animal"))]
+    pub weights: Vec<GroupWeight<'a>>,
+    /// Defaults to the organisation's preferred weight unit.
+    pub unit: Option<Cow<'a, str>>,
+    pub notes: Option<Cow<'a, str>>,
+}
+
+/// The same treatment given to every member of a group.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupTreatment<'a> {
+    pub record_date: Cow<'a, str>,
+    pub condition: Cow<'a, str>,
+    pub description: Cow<'a, str>,
+    pub treatment: Cow<'a, str>,
+    pub severity: Cow<'a, str>,
+    pub status: Cow<'a, str>,
+    pub medicine: Option<Cow<'a, str>>,
+    pub dosage: Option<Cow<'a, str>>,
+    /// The cost of treating each animal.
+    pub cost: Option<i64>,
+    pub performed_by: Option<Cow<'a, str>>,
+    pub prognosis: Option<Cow<'a, str>>,
+    pub notes: Option<Cow<'a, str>>,
+}
+
+impl<'a> GroupTreatment<'a> {
+    #[must_use]
+    pub fn for_animal(&self, tag_id: &str) -> NewHealthRecord<'a> {
+        NewHealthRecord {
+            tag_id: Cow::Owned(tag_id.to_string()),
+            record_date: self.record_date.clone(),
+            condition: self.condition.clone(),
+            description: self.description.clone(),
+            treatment: self.treatment.clone(),
+            severity: self.severity.clone(),
+            status: self.status.clone(),
+            medicine: self.medicine.clone(),
+            dosage: self.dosage.clone(),
+            cost: self.cost,
+            performed_by: self.performed_by.clone(),
+            prognosis: self.prognosis.clone(),
+            notes: self.notes.clone(),
+        }
+    }
+}
+
+/// Moves every member of a group.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupMove<'a> {
+    pub to: Option<Uuid>,
+    pub moved_on: Option<NaiveDate>,
+    pub reason: Option<Cow<'a, str>>,
+}
+
+impl<'a> GroupMove<'a> {
+    #[must_use]
+    pub fn for_animals(&self, tag_ids: Vec<String>) -> MoveAnimals<'a> {
+        MoveAnimals {
+            tag_ids: tag_ids.into_iter().map(Cow::Owned).collect(),
+            to: self.to,
+            moved_on: self.moved_on,
+            reason: self.reason.clone(),
+        }
+    }
+}
+
+fn validate_kind(kind: &str) -> Result<(), ValidationError> {
+    if ["static", "dynamic"].contains(&kind) {
+        return Ok(());
+    }
+
+    Err(ValidationError::new("invalid_kind")
+        .with_message(Cow::Borrowed("Kind must be static or dynamic")))
+}
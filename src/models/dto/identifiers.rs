@@ -0,0 +1,42 @@
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+use validator::{Validate, ValidationError};
+
+/// An identifier to attach to an animal, replacing its current one of the
+/// same kind.
+#[derive(Debug, Deserialize, Serialize, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct NewIdentifier<'a> {
+    /// `visual`, `rfid`, `brand` or `tattoo`.
+    #[validate(custom(function = "validate_kind"))]
+    pub kind: Cow<'a, str>,
+    #[validate(length(min = 1, max = 50, message = "Value must have 1 to 50 characters"))]
+    pub value: Cow<'a, str>,
+    /// Why the identifier it replaces came off, e.g. `lost`. Defaults to
+    /// `replaced`.
+    #[validate(length(max = 255, message = "Reason must not exceed 255 characters"))]
+    pub reason: Option<Cow<'a, str>>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct RemoveIdentifier {
+    /// Defaults to `removed`.
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct ScanQuery {
+    /// What the reader sent, e.g. `982 000123456789`, `3D6.00075BCD15` or
+    /// an FDX-B telegram, or any other identifier as printed.
+    pub raw: String,
+}
+
+fn validate_kind(kind: &str) -> Result<(), ValidationError> {
+    if ["visual", "rfid", "brand", "tattoo"].contains(&kind) {
+        return Ok(());
+    }
+
+    Err(ValidationError::new("invalid_kind")
+        .with_message(Cow::Borrowed("Kind must be visual, rfid, brand or tattoo")))
+}
@@ -0,0 +1,52 @@
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+use validator::{Validate, ValidationError};
+
+use crate::models::jobs::{JobKind, parse_cron};
+
+#[derive(Debug, Deserialize, Serialize, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateJobSchedule<'a> {
+    #[validate(custom(function = "validate_kind"))]
+    pub kind: Cow<'a, str>,
+    #[validate(custom(function = "validate_cron"))]
+    pub cron: Cow<'a, str>,
+    pub is_active: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateJobSchedule<'a> {
+    #[validate(custom(function = "validate_cron"))]
+    pub cron: Option<Cow<'a, str>>,
+    pub is_active: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct EnqueueJob<'a> {
+    #[validate(custom(function = "validate_kind"))]
+    pub kind: Cow<'a, str>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct JobQuery {
+    pub kind: Option<String>,
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+}
+
+fn validate_kind(kind: &str) -> Result<(), ValidationError> {
+    kind.parse::<JobKind>().map(|_| ()).map_err(|_| {
+        ValidationError::new("invalid_kind").with_message(Cow::Borrowed(
+            "Kind must be one of summaries, alerts, purge, deliver or tasks",
+        ))
+    })
+}
+
+fn validate_cron(cron: &str) -> Result<(), ValidationError> {
+    parse_cron(cron).map(|_| ()).map_err(|error| {
+        ValidationError::new("invalid_cron").with_message(Cow::Owned(error.to_string()))
+    })
+}
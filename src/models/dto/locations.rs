@@ -0,0 +1,64 @@
+use std::borrow::Cow;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+#[derive(Debug, Deserialize, Serialize, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct NewLocation<'a> {
+    #[validate(length(min = 1, max = 100, message = "Name must have 1 to 100 characters"))]
+    pub name: Cow<'a, str>,
+    /// `site`, `paddock` or `pen`.
+    #[validate(custom(function = "validate_kind"))]
+    pub kind: Cow<'a, str>,
+    /// The site a paddock is on, or the site or paddock a pen is in.
+    pub parent: Option<Uuid>,
+    /// How many animals it holds, counting those in the locations within.
+    #[validate(range(min = 1, message = "Capacity must be above 0"))]
+    pub capacity: Option<i32>,
+    pub notes: Option<Cow<'a, str>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateLocation<'a> {
+    #[validate(length(min = 1, max = 100, message = "Name must have 1 to 100 characters"))]
+    pub name: Option<Cow<'a, str>>,
+    #[validate(range(min = 1, message = "Capacity must be above 0"))]
+    pub capacity: Option<i32>,
+    pub notes: Option<Cow<'a, str>>,
+}
+
+/// Moves animals together, in one go.
+#[derive(Debug, Deserialize, Serialize, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct MoveAnimals<'a> {
+    #[validate(length(min = 1, message = "Tag IDs must name at least one animal"))]
+    pub tag_ids: Vec<Cow<'a, str>>,
+    /// Where they go, or none when they leave the organisation's locations.
+    pub to: Option<Uuid>,
+    /// Defaults to today.
+    pub moved_on: Option<NaiveDate>,
+    #[validate(length(max = 500, message = "Reason must have at most 500 characters"))]
+    pub reason: Option<Cow<'a, str>>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct MovementQuery {
+    pub animal: Option<Uuid>,
+    /// Moves into or out of the location.
+    pub location: Option<Uuid>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+fn validate_kind(kind: &str) -> Result<(), ValidationError> {
+    if ["site", "paddock", "pen"].contains(&kind) {
+        return Ok(());
+    }
+
+    Err(ValidationError::new("invalid_kind")
+        .with_message(Cow::Borrowed("Kind must be one of site, paddock or pen")))
+}
@@ -1,13 +1,28 @@
 #![allow(clippy::missing_const_for_fn)]
+pub mod alerts;
 pub mod animals;
+pub mod api_keys;
 pub mod auth;
+pub mod feed;
+pub mod finance;
+pub mod groups;
+pub mod identifiers;
+pub mod jobs;
+pub mod locations;
+pub mod platform;
 pub mod records;
+pub mod roles;
+pub mod tasks;
+pub mod webhooks;
 
 use std::collections::BTreeMap;
 
 use validator::Validate;
 
-pub use self::{animals::*, auth::*};
+pub use self::{
+    alerts::*, animals::*, api_keys::*, auth::*, feed::*, finance::*, groups::*, identifiers::*,
+    jobs::*, locations::*, platform::*, roles::*, tasks::*, webhooks::*,
+};
 
 use super::{ModelError, ModelResult};
 
@@ -0,0 +1,29 @@
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangeSubscription<'a> {
+    pub subscription: Cow<'a, str>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct SuspendOrganisation<'a> {
+    #[validate(length(max = 500, message = "Reason must have at most 500 characters"))]
+    pub reason: Option<Cow<'a, str>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateOrganisation<'a> {
+    #[validate(length(min = 2, max = 255, message = "Name must have 2-255 characters"))]
+    pub name: Option<Cow<'a, str>>,
+    pub address: Option<Cow<'a, str>>,
+    #[validate(length(max = 20, message = "Phone must have at most 20 characters"))]
+    pub phone: Option<Cow<'a, str>>,
+    #[validate(email(message = "Invalid e-mail address"))]
+    pub email: Option<Cow<'a, str>>,
+}
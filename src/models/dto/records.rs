@@ -67,8 +67,10 @@ pub struct NewWeightRecord<'a> {
     pub tag_id: Cow<'a, str>,
     pub record_date: NaiveDate,
     pub mass: i64,
-    pub unit: Cow<'a, str>,
-    pub status: Cow<'a, str>,
+    /// Defaults to the organisation's preferred weight unit.
+    pub unit: Option<Cow<'a, str>>,
+    /// Defaults to a classification against the animal's previous weighing.
+    pub status: Option<Cow<'a, str>>,
     pub notes: Option<Cow<'a, str>>,
 }
 
@@ -0,0 +1,77 @@
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+use validator::{Validate, ValidationError};
+
+use crate::models::{ModelResult, roles::Scope};
+
+#[derive(Debug, Deserialize, Serialize, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateRole<'a> {
+    #[validate(custom(function = "validate_role_name"))]
+    pub name: Cow<'a, str>,
+    pub description: Option<Cow<'a, str>>,
+    #[validate(custom(function = "validate_scopes"))]
+    pub permissions: Vec<String>,
+}
+
+impl CreateRole<'_> {
+    /// Parses the requested `resource:action` permissions.
+    ///
+    /// # Errors
+    /// Fails if any of the permissions is not in the catalogue.
+    pub fn scopes(&self) -> ModelResult<Vec<Scope>> {
+        self.permissions.iter().map(|scope| scope.parse()).collect()
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateRole<'a> {
+    pub description: Option<Cow<'a, str>>,
+    #[validate(custom(function = "validate_scopes"))]
+    pub permissions: Option<Vec<String>>,
+}
+
+impl UpdateRole<'_> {
+    /// Parses the requested `resource:action` permissions, if any.
+    ///
+    /// # Errors
+    /// Fails if any of the permissions is not in the catalogue.
+    pub fn scopes(&self) -> ModelResult<Option<Vec<Scope>>> {
+        self.permissions
+            .as_ref()
+            .map(|permissions| permissions.iter().map(|scope| scope.parse()).collect())
+            .transpose()
+    }
+}
+
+fn validate_role_name(name: &str) -> Result<(), ValidationError> {
+    let name = name.trim();
+
+    if (2..=50).contains(&name.len())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        Ok(())
+    } else {
+        Err(
+            ValidationError::new("invalid_role_name").with_message(Cow::Borrowed(
+                "Role name must have 2-50 letters, digits, dashes or underscores",
+            )),
+        )
+    }
+}
+
+pub(super) fn validate_scopes(scopes: &[String]) -> Result<(), ValidationError> {
+    if scopes.iter().all(|scope| scope.parse::<Scope>().is_ok()) {
+        Ok(())
+    } else {
+        Err(
+            ValidationError::new("invalid_permission").with_message(Cow::Borrowed(
+                "Permissions must be of the form resource:action",
+            )),
+        )
+    }
+}
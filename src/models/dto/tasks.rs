@@ -0,0 +1,204 @@
+use std::borrow::Cow;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+use super::{GroupTreatment, GroupWeighing, records::NewProductionRecord};
+
+#[derive(Debug, Deserialize, Serialize, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct NewTask<'a> {
+    #[validate(length(min = 1, max = 200, message = "Title must have 1 to 200 characters"))]
+    pub title: Cow<'a, str>,
+    pub description: Option<Cow<'a, str>>,
+    pub due_on: NaiveDate,
+    /// The pid of the user doing it.
+    pub assignee: Option<Uuid>,
+    /// `low`, `medium` or `high`. Defaults to `medium`.
+    #[validate(custom(function = "validate_priority"))]
+    pub priority: Option<Cow<'a, str>>,
+    /// The animals it is about.
+    pub tag_ids: Option<Vec<Cow<'a, str>>>,
+    /// A group it is about, whose members it covers as they are when it is
+    /// completed.
+    pub group: Option<Uuid>,
+    /// `health`, `weight` or `production`, the record completing it creates.
+    #[validate(custom(function = "validate_record_kind"))]
+    pub record_kind: Option<Cow<'a, str>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateTask<'a> {
+    #[validate(length(min = 1, max = 200, message = "Title must have 1 to 200 characters"))]
+    pub title: Option<Cow<'a, str>>,
+    pub description: Option<Cow<'a, str>>,
+    pub due_on: Option<NaiveDate>,
+    pub assignee: Option<Uuid>,
+    #[validate(custom(function = "validate_priority"))]
+    pub priority: Option<Cow<'a, str>>,
+    /// `open`, `in_progress` or `cancelled`. Tasks are done by completing
+    /// them.
+    #[validate(custom(function = "validate_status"))]
+    pub status: Option<Cow<'a, str>>,
+    pub notes: Option<Cow<'a, str>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProductionReading<'a> {
+    pub tag_id: Cow<'a, str>,
+    pub quantity: i64,
+}
+
+/// Production of some or all of a task's animals, on one day.
+#[derive(Debug, Deserialize, Serialize, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskProduction<'a> {
+    pub production_type: Cow<'a, str>,
+    pub unit: Cow<'a, str>,
+    pub quality: Option<Cow<'a, str>>,
+    /// Defaults to today.
+    pub record_date: Option<NaiveDate>,
+    #[validate(length(min = 1, message = "Readings must record at least one animal"))]
+    pub readings: Vec<ProductionReading<'a>>,
+    pub notes: Option<Cow<'a, str>>,
+}
+
+impl<'a> TaskProduction<'a> {
+    #[must_use]
+    pub fn for_reading(
+        &self,
+        reading: &ProductionReading<'_>,
+        record_date: NaiveDate,
+    ) -> NewProductionRecord<'a> {
+        NewProductionRecord {
+            tag_id: Cow::Owned(reading.tag_id.trim().to_uppercase()),
+            production_type: self.production_type.clone(),
+            quantity: reading.quantity,
+            unit: self.unit.clone(),
+            quality: self.quality.clone(),
+            notes: self.notes.clone(),
+            record_date: Some(Cow::Owned(record_date.to_string())),
+        }
+    }
+}
+
+/// Marks a task done, recording the work at most one way: a treatment of
+/// each of its animals, a weighing or production readings.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CompleteTask<'a> {
+    pub notes: Option<Cow<'a, str>>,
+    pub treatment: Option<GroupTreatment<'a>>,
+    pub weighing: Option<GroupWeighing<'a>>,
+    pub production: Option<TaskProduction<'a>>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct TaskQuery {
+    pub status: Option<String>,
+    pub assignee: Option<Uuid>,
+    pub animal: Option<Uuid>,
+    pub source: Option<String>,
+    /// Due on or before.
+    pub due: Option<NaiveDate>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct NewRecurringTask<'a> {
+    #[validate(length(min = 1, max = 200, message = "Title must have 1 to 200 characters"))]
+    pub title: Cow<'a, str>,
+    pub description: Option<Cow<'a, str>>,
+    /// When it recurs, e.g. `0 6 * * TUE` for every Tuesday. Only the days
+    /// it fires on count.
+    pub cron: Cow<'a, str>,
+    /// The first day it may come due. Defaults to today.
+    pub starts_on: Option<NaiveDate>,
+    pub assignee: Option<Uuid>,
+    #[validate(custom(function = "validate_priority"))]
+    pub priority: Option<Cow<'a, str>>,
+    pub group: Option<Uuid>,
+    #[validate(custom(function = "validate_record_kind"))]
+    pub record_kind: Option<Cow<'a, str>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateRecurringTask<'a> {
+    #[validate(length(min = 1, max = 200, message = "Title must have 1 to 200 characters"))]
+    pub title: Option<Cow<'a, str>>,
+    pub description: Option<Cow<'a, str>>,
+    pub cron: Option<Cow<'a, str>>,
+    pub assignee: Option<Uuid>,
+    #[validate(custom(function = "validate_priority"))]
+    pub priority: Option<Cow<'a, str>>,
+    pub is_active: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct NewVaccinationSchedule<'a> {
+    #[validate(length(min = 1, max = 255, message = "Vaccine must have 1 to 255 characters"))]
+    pub vaccine: Cow<'a, str>,
+    /// The group whose members get it.
+    pub group: Uuid,
+    #[validate(range(min = 1, message = "Interval days must be above 0"))]
+    pub interval_days: i32,
+    /// How many days before an animal is due its task is raised. Defaults
+    /// to 7.
+    #[validate(range(min = 0, message = "Lead days must not be negative"))]
+    pub lead_days: Option<i32>,
+    pub assignee: Option<Uuid>,
+    #[validate(custom(function = "validate_priority"))]
+    pub priority: Option<Cow<'a, str>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateVaccinationSchedule<'a> {
+    #[validate(range(min = 1, message = "Interval days must be above 0"))]
+    pub interval_days: Option<i32>,
+    #[validate(range(min = 0, message = "Lead days must not be negative"))]
+    pub lead_days: Option<i32>,
+    pub assignee: Option<Uuid>,
+    #[validate(custom(function = "validate_priority"))]
+    pub priority: Option<Cow<'a, str>>,
+    pub is_active: Option<bool>,
+}
+
+fn validate_priority(priority: &str) -> Result<(), ValidationError> {
+    if ["low", "medium", "high"].contains(&priority) {
+        return Ok(());
+    }
+
+    Err(ValidationError::new("invalid_priority")
+        .with_message(Cow::Borrowed("Priority must be one of low, medium or high")))
+}
+
+fn validate_status(status: &str) -> Result<(), ValidationError> {
+    if ["open", "in_progress", "cancelled"].contains(&status) {
+        return Ok(());
+    }
+
+    Err(
+        ValidationError::new("invalid_status").with_message(Cow::Borrowed(
+            "Status must be open, in_progress or cancelled",
+        )),
+    )
+}
+
+fn validate_record_kind(kind: &str) -> Result<(), ValidationError> {
+    if ["health", "weight", "production"].contains(&kind) {
+        return Ok(());
+    }
+
+    Err(
+        ValidationError::new("invalid_record_kind").with_message(Cow::Borrowed(
+            "Record kind must be health, weight or production",
+        )),
+    )
+}
@@ -0,0 +1,62 @@
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+use validator::{Validate, ValidationError};
+
+use crate::models::{ModelResult, events::EventType};
+
+#[derive(Debug, Deserialize, Serialize, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateWebhook<'a> {
+    #[validate(url(message = "Invalid URL"))]
+    pub url: Cow<'a, str>,
+    #[validate(
+        length(min = 1, message = "A webhook needs at least one event type"),
+        custom(function = "validate_event_types")
+    )]
+    pub event_types: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateWebhook<'a> {
+    #[validate(url(message = "Invalid URL"))]
+    pub url: Option<Cow<'a, str>>,
+    #[validate(
+        length(min = 1, message = "A webhook needs at least one event type"),
+        custom(function = "validate_event_types")
+    )]
+    pub event_types: Option<Vec<String>>,
+    pub is_active: Option<bool>,
+}
+
+fn validate_event_types(event_types: &[String]) -> Result<(), ValidationError> {
+    if event_types
+        .iter()
+        .all(|event_type| event_type.parse::<EventType>().is_ok())
+    {
+        Ok(())
+    } else {
+        Err(
+            ValidationError::new("invalid_event_type").with_message(Cow::Borrowed(
+                "Event types must be one of animal.registered, animal.sold, animal.deceased, health_record.high_severity or alert.triggered",
+            )),
+        )
+    }
+}
+
+/// The event types in their stored form, without duplicates.
+///
+/// # Errors
+/// Fails if any of them is not an event webhooks can subscribe to.
+pub(crate) fn event_types(event_types: &[String]) -> ModelResult<Vec<String>> {
+    let mut parsed = event_types
+        .iter()
+        .map(|event_type| event_type.parse::<EventType>())
+        .collect::<ModelResult<Vec<_>>>()?;
+
+    parsed.sort_by_key(EventType::as_str);
+    parsed.dedup();
+
+    Ok(parsed.iter().map(ToString::to_string).collect())
+}
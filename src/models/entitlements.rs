@@ -0,0 +1,157 @@
+#![allow(clippy::missing_errors_doc)]
+
+use serde::{Deserialize, Serialize};
+use sqlx::{Executor, Postgres, prelude::FromRow};
+use uuid::Uuid;
+
+use super::{ModelError, ModelResult, enums::Subscription};
+
+/// The reports an organisation can read and generate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportType {
+    Livestock,
+    Categories,
+    Breeds,
+}
+
+impl ReportType {
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Livestock => "livestock",
+            Self::Categories => "categories",
+            Self::Breeds => "breeds",
+        }
+    }
+}
+
+/// What a subscription tier includes. `None` means unlimited.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Limits {
+    pub max_animals: Option<i64>,
+    pub max_users: Option<i64>,
+    pub reports: &'static [ReportType],
+    pub api_access: bool,
+}
+
+impl Subscription {
+    #[must_use]
+    pub const fn limits(&self) -> Limits {
+        match self {
+            Self::Basic => Limits {
+                max_animals: Some(25),
+                max_users: Some(3),
+                reports: &[ReportType::Livestock],
+                api_access: false,
+            },
+            Self::Business => Limits {
+                max_animals: Some(1000),
+                max_users: Some(25),
+                reports: &[
+                    ReportType::Livestock,
+                    ReportType::Categories,
+                    ReportType::Breeds,
+                ],
+                api_access: false,
+            },
+            Self::Enterprise => Limits {
+                max_animals: None,
+                max_users: None,
+                reports: &[
+                    ReportType::Livestock,
+                    ReportType::Categories,
+                    ReportType::Breeds,
+                ],
+                api_access: true,
+            },
+        }
+    }
+}
+
+/// What an organisation currently uses of its limits. Trashed animals and
+/// deactivated users do not count.
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct Usage {
+    pub animals: i64,
+    pub users: i64,
+}
+
+impl Usage {
+    pub async fn for_organisation<'e, C>(db: C, org_pid: Uuid) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_as::<_, Self>(
+            "
+            SELECT
+                (SELECT COUNT(*) FROM animals
+                    WHERE organisation_pid = $1 AND deleted_at IS NULL) AS animals,
+                (SELECT COUNT(*) FROM memberships m
+                    JOIN users u ON m.user_pid = u.pid
+                    WHERE m.organisation_pid = $1 AND m.is_active AND u.is_active) AS users
+            ",
+        )
+        .bind(org_pid)
+        .fetch_one(db)
+        .await
+        .map_err(Into::into)
+    }
+}
+
+/// Something a route needs the organisation's subscription to allow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Entitlement {
+    /// Room for one more animal.
+    Animal,
+    /// Room for one more active user.
+    User,
+    Report(ReportType),
+    ApiAccess,
+}
+
+impl Entitlement {
+    /// Fails with [`ModelError::PlanLimit`] when `subscription` does not
+    /// cover the entitlement.
+    pub async fn check<'e, C>(
+        &self,
+        db: C,
+        org_pid: Uuid,
+        subscription: &Subscription,
+    ) -> ModelResult<()>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        let limits = subscription.limits();
+
+        let denied = match self {
+            Self::Animal => {
+                let usage = Usage::for_organisation(db, org_pid).await?;
+
+                limits
+                    .max_animals
+                    .filter(|max| usage.animals >= *max)
+                    .map(|max| format!("up to {max} animals"))
+            }
+            Self::User => {
+                let usage = Usage::for_organisation(db, org_pid).await?;
+
+                limits
+                    .max_users
+                    .filter(|max| usage.users >= *max)
+                    .map(|max| format!("up to {max} active users"))
+            }
+            Self::Report(report) => (!limits.reports.contains(report))
+                .then(|| format!("no {} reports", report.as_str())),
+            Self::ApiAccess => (!limits.api_access).then(|| "no API access".to_string()),
+        };
+
+        denied.map_or(Ok(()), |allowance| {
+            Err(ModelError::PlanLimit(format!(
+                "The {subscription} plan allows {allowance}. Upgrade your subscription to continue."
+            )))
+        })
+    }
+}
@@ -7,8 +7,11 @@ use crate::Error;
 #[sqlx(type_name = "subscription_type")]
 #[sqlx(rename_all = "lowercase")]
 pub enum Subscription {
+    #[serde(alias = "basic")]
     Basic,
+    #[serde(alias = "business")]
     Business,
+    #[serde(alias = "enterprise")]
     Enterprise,
 }
 
@@ -15,11 +15,15 @@ pub enum ModelError {
     #[error("{0}")]
     ArgonHash(argon2::password_hash::Error),
     #[error("{0}")]
+    Conflict(String),
+    #[error("{0}")]
     EntityAlreadyExists(String),
     #[error("Entity not found")]
     EntityNotFound,
     #[error(transparent)]
     Parse(#[from] ParseError),
+    #[error("{0}")]
+    PlanLimit(String),
     #[error(transparent)]
     Seed(#[from] SeedError),
     #[error(transparent)]
@@ -45,9 +49,12 @@ impl ModelError {
                 "Something went wrong on our end",
             ),
             Self::EntityNotFound => (StatusCode::NOT_FOUND, "Entity not found"),
-            Self::EntityAlreadyExists(error) => (StatusCode::CONFLICT, error.as_str()),
+            Self::EntityAlreadyExists(error) | Self::Conflict(error) => {
+                (StatusCode::CONFLICT, error.as_str())
+            }
             Self::Uuid(_e) => (StatusCode::UNPROCESSABLE_ENTITY, "Bad request"),
             Self::Validation(e) => (StatusCode::BAD_REQUEST, e.as_str()),
+            Self::PlanLimit(e) => (StatusCode::PAYMENT_REQUIRED, e.as_str()),
             Self::Parse(_) => (
                 StatusCode::UNPROCESSABLE_ENTITY,
                 "The record date is not a valid date.",
@@ -0,0 +1,130 @@
+#![allow(clippy::missing_errors_doc)]
+
+use std::{fmt, str::FromStr};
+
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, prelude::FromRow, types::Json};
+use uuid::Uuid;
+
+use super::{ModelError, ModelResult};
+
+/// The events webhooks can subscribe to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum EventType {
+    #[serde(rename = "animal.registered")]
+    AnimalRegistered,
+    #[serde(rename = "animal.sold")]
+    AnimalSold,
+    #[serde(rename = "animal.deceased")]
+    AnimalDeceased,
+    #[serde(rename = "health_record.high_severity")]
+    HighSeverityHealthRecord,
+    #[serde(rename = "alert.triggered")]
+    AlertTriggered,
+}
+
+impl EventType {
+    pub const ALL: &'static [Self] = &[
+        Self::AnimalRegistered,
+        Self::AnimalSold,
+        Self::AnimalDeceased,
+        Self::HighSeverityHealthRecord,
+        Self::AlertTriggered,
+    ];
+
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::AnimalRegistered => "animal.registered",
+            Self::AnimalSold => "animal.sold",
+            Self::AnimalDeceased => "animal.deceased",
+            Self::HighSeverityHealthRecord => "health_record.high_severity",
+            Self::AlertTriggered => "alert.triggered",
+        }
+    }
+
+    /// The event for an animal moving to `status`, if partners are told about
+    /// it.
+    #[must_use]
+    pub fn for_animal_status(status: &str) -> Option<Self> {
+        match status {
+            "sold" => Some(Self::AnimalSold),
+            "deceased" => Some(Self::AnimalDeceased),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for EventType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for EventType {
+    type Err = ModelError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .find(|event_type| event_type.as_str() == value.trim())
+            .copied()
+            .ok_or_else(|| ModelError::Validation(format!("Unknown event type {value}")))
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, FromRow, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DomainEvent {
+    pub id: i32,
+    pub pid: Uuid,
+    pub organisation_pid: Uuid,
+    pub event_type: String,
+    pub payload: Json<serde_json::Value>,
+    pub created_at: DateTime<FixedOffset>,
+}
+
+impl DomainEvent {
+    /// Records the event and queues a delivery to each active webhook
+    /// subscribed to it. Call it inside the transaction making the change, so
+    /// the event stands or falls with it.
+    pub async fn emit<T>(
+        db: &mut PgConnection,
+        org_pid: Uuid,
+        event_type: EventType,
+        payload: &T,
+    ) -> ModelResult<Self>
+    where
+        T: Serialize + Sync,
+    {
+        let payload = serde_json::to_value(payload)
+            .map_err(|error| ModelError::Validation(error.to_string()))?;
+
+        let event = sqlx::query_as::<_, Self>(
+            "
+            INSERT INTO domain_events (organisation_pid, event_type, payload)
+            VALUES ($1, $2, $3) RETURNING *",
+        )
+        .bind(org_pid)
+        .bind(event_type.as_str())
+        .bind(Json(payload))
+        .fetch_one(&mut *db)
+        .await?;
+
+        sqlx::query(
+            "
+            INSERT INTO webhook_deliveries (organisation_pid, webhook_pid, event_pid)
+            SELECT w.organisation_pid, w.pid, $3
+            FROM webhooks w
+            WHERE w.organisation_pid = $1 AND w.is_active AND $2 = ANY (w.event_types)",
+        )
+        .bind(org_pid)
+        .bind(event_type.as_str())
+        .bind(event.pid)
+        .execute(&mut *db)
+        .await?;
+
+        Ok(event)
+    }
+}
@@ -0,0 +1,1310 @@
+#![allow(clippy::missing_errors_doc)]
+#![allow(clippy::too_many_lines)]
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Days, FixedOffset, NaiveDate};
+use rust_decimal::{Decimal, prelude::ToPrimitive};
+use serde::{Deserialize, Serialize};
+use sqlx::{Executor, PgConnection, Postgres, prelude::FromRow, types::Json};
+use uuid::Uuid;
+
+use super::{
+    ModelError, ModelResult,
+    dto::{
+        ConversionQuery, FeedQuery, FeedRation, NewFeedPurchase, NewFeeding, NewFeedstuff,
+        NewRation, RationItem, StockQuery, UpdateFeedstuff, UpdateRation, Validator,
+    },
+    groups::AnimalGroup,
+    settings::OrganisationSettings,
+};
+
+/// Something animals are fed. Quantities are in the organisation's weight
+/// unit.
+#[derive(Debug, Deserialize, Serialize, FromRow, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Feedstuff {
+    pub id: i32,
+    pub pid: Uuid,
+    pub organisation_pid: Uuid,
+    pub name: String,
+    /// Percent of the feed as fed.
+    pub dry_matter: Option<Decimal>,
+    pub crude_protein: Option<Decimal>,
+    pub crude_fibre: Option<Decimal>,
+    /// Megajoules of metabolisable energy per kilogram of dry matter.
+    pub energy: Option<Decimal>,
+    /// What was bought less what was fed.
+    pub stock: Decimal,
+    pub reorder_level: Option<Decimal>,
+    pub notes: Option<String>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<FixedOffset>,
+    pub updated_at: DateTime<FixedOffset>,
+}
+
+fn hundredths(value: i64) -> Decimal {
+    Decimal::new(value, 2)
+}
+
+fn unique_name(kind: &'static str) -> impl Fn(sqlx::Error) -> ModelError {
+    move |error| match error {
+        sqlx::Error::Database(err)
+            if err
+                .constraint()
+                .is_some_and(|constraint| constraint.ends_with("_organisation_pid_name_key")) =>
+        {
+            ModelError::EntityAlreadyExists(format!("A {kind} with that name already exists"))
+        }
+        error => ModelError::Sqlx(error),
+    }
+}
+
+/// The feedstuff `pid`, locked until the transaction ends so its stock can
+/// be checked before it is fed.
+async fn lock_feedstuff(db: &mut PgConnection, org_pid: Uuid, pid: Uuid) -> ModelResult<Feedstuff> {
+    sqlx::query_as::<_, Feedstuff>(
+        "SELECT * FROM feedstuffs WHERE pid = $1 AND organisation_pid = $2 FOR UPDATE",
+    )
+    .bind(pid)
+    .bind(org_pid)
+    .fetch_optional(&mut *db)
+    .await?
+    .ok_or_else(|| ModelError::Validation(format!("Unknown feedstuff {pid}")))
+}
+
+/// The animals tagged `tag_ids`, each once.
+async fn tagged(
+    db: &mut PgConnection,
+    org_pid: Uuid,
+    tag_ids: &[String],
+) -> ModelResult<Vec<Uuid>> {
+    let tag_ids = tag_ids
+        .iter()
+        .map(|tag_id| tag_id.trim().to_uppercase())
+        .collect::<Vec<_>>();
+
+    let found = sqlx::query_as::<_, (String, Uuid)>(
+        "SELECT tag_id, pid FROM animals
+        WHERE organisation_pid = $1 AND tag_id = ANY($2) AND deleted_at IS NULL",
+    )
+    .bind(org_pid)
+    .bind(&tag_ids)
+    .fetch_all(&mut *db)
+    .await?;
+
+    let unknown = tag_ids
+        .iter()
+        .filter(|tag_id| !found.iter().any(|(found, _)| found == *tag_id))
+        .cloned()
+        .collect::<Vec<_>>();
+    if !unknown.is_empty() {
+        return Err(ModelError::Validation(format!(
+            "No animal is tagged {}",
+            unknown.join(", ")
+        )));
+    }
+
+    Ok(found.into_iter().map(|(_, pid)| pid).collect())
+}
+
+impl Feedstuff {
+    pub async fn create(
+        db: &mut PgConnection,
+        org_pid: Uuid,
+        user_pid: Uuid,
+        params: &NewFeedstuff<'_>,
+    ) -> ModelResult<Self> {
+        let validator = Validator::new(params);
+        let params = validator.validate()?;
+
+        sqlx::query_as::<_, Self>(
+            "
+            INSERT INTO feedstuffs (
+                organisation_pid, name, dry_matter, crude_protein, crude_fibre, energy,
+                reorder_level, notes, created_by
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING *",
+        )
+        .bind(org_pid)
+        .bind(params.name.trim())
+        .bind(params.dry_matter.map(hundredths))
+        .bind(params.crude_protein.map(hundredths))
+        .bind(params.crude_fibre.map(hundredths))
+        .bind(params.energy.map(hundredths))
+        .bind(params.reorder_level.map(hundredths))
+        .bind(params.notes.as_deref())
+        .bind(user_pid)
+        .fetch_one(&mut *db)
+        .await
+        .map_err(unique_name("feedstuff"))
+    }
+
+    pub async fn find_all<'e, C>(db: C, org_pid: Uuid) -> ModelResult<Vec<Self>>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_as::<_, Self>(
+            "SELECT * FROM feedstuffs WHERE organisation_pid = $1 ORDER BY name",
+        )
+        .bind(org_pid)
+        .fetch_all(db)
+        .await
+        .map_err(Into::into)
+    }
+
+    pub async fn find_by_pid<'e, C>(db: C, org_pid: Uuid, pid: Uuid) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_as::<_, Self>(
+            "SELECT * FROM feedstuffs WHERE pid = $1 AND organisation_pid = $2",
+        )
+        .bind(pid)
+        .bind(org_pid)
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    pub async fn update(
+        db: &mut PgConnection,
+        org_pid: Uuid,
+        pid: Uuid,
+        params: &UpdateFeedstuff<'_>,
+    ) -> ModelResult<Self> {
+        let validator = Validator::new(params);
+        let params = validator.validate()?;
+
+        sqlx::query_as::<_, Self>(
+            "
+            UPDATE feedstuffs SET
+                name = COALESCE($3, name),
+                dry_matter = COALESCE($4, dry_matter),
+                crude_protein = COALESCE($5, crude_protein),
+                crude_fibre = COALESCE($6, crude_fibre),
+                energy = COALESCE($7, energy),
+                reorder_level = COALESCE($8, reorder_level),
+                notes = COALESCE($9, notes)
+            WHERE pid = $1 AND organisation_pid = $2
+            RETURNING *",
+        )
+        .bind(pid)
+        .bind(org_pid)
+        .bind(params.name.as_deref().map(str::trim))
+        .bind(params.dry_matter.map(hundredths))
+        .bind(params.crude_protein.map(hundredths))
+        .bind(params.crude_fibre.map(hundredths))
+        .bind(params.energy.map(hundredths))
+        .bind(params.reorder_level.map(hundredths))
+        .bind(params.notes.as_deref())
+        .fetch_optional(&mut *db)
+        .await
+        .map_err(unique_name("feedstuff"))?
+        .ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    /// Only feedstuffs nothing was bought or fed of can be deleted, so their
+    /// history and what was spent on them stay.
+    pub async fn delete_by_pid(db: &mut PgConnection, org_pid: Uuid, pid: Uuid) -> ModelResult<()> {
+        let feedstuff = Self::find_by_pid(&mut *db, org_pid, pid).await?;
+
+        let used = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS (SELECT 1 FROM feed_purchases WHERE feedstuff_id = $1)
+                OR EXISTS (SELECT 1 FROM feedings WHERE feedstuff_id = $1)",
+        )
+        .bind(feedstuff.id)
+        .fetch_one(&mut *db)
+        .await?;
+        if used {
+            return Err(ModelError::Conflict(format!(
+                "{} has purchases or feedings recorded",
+                feedstuff.name
+            )));
+        }
+
+        sqlx::query("DELETE FROM feedstuffs WHERE id = $1")
+            .bind(feedstuff.id)
+            .execute(&mut *db)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, FromRow, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedPurchase {
+    pub id: i32,
+    pub pid: Uuid,
+    pub organisation_pid: Uuid,
+    pub feedstuff_pid: Uuid,
+    pub feedstuff_name: String,
+    pub quantity: Decimal,
+    pub cost: Option<Decimal>,
+    pub supplier: Option<String>,
+    pub purchase_date: NaiveDate,
+    pub created_by: Option<Uuid>,
+    pub created_by_name: Option<String>,
+    pub created_at: DateTime<FixedOffset>,
+    pub updated_at: DateTime<FixedOffset>,
+}
+
+const PURCHASES_QUERY: &str = "
+    SELECT
+        p.id,
+        p.pid,
+        p.organisation_pid,
+        f.pid AS feedstuff_pid,
+        f.name AS feedstuff_name,
+        p.quantity,
+        p.cost,
+        p.supplier,
+        p.purchase_date,
+        p.created_by,
+        CONCAT(u.first_name, ' ', u.last_name) AS created_by_name,
+        p.created_at,
+        p.updated_at
+    FROM
+        feed_purchases p
+    JOIN
+        feedstuffs f ON p.feedstuff_id = f.id
+    LEFT JOIN
+        users u ON p.created_by = u.pid
+    WHERE
+        p.organisation_pid = $1
+";
+
+impl FeedPurchase {
+    /// Adds what was bought to the feedstuff's stock and posts its cost to
+    /// the ledger.
+    pub async fn create(
+        db: &mut PgConnection,
+        org_pid: Uuid,
+        user_pid: Uuid,
+        params: &NewFeedPurchase<'_>,
+    ) -> ModelResult<Self> {
+        let validator = Validator::new(params);
+        let params = validator.validate()?;
+
+        let feedstuff = lock_feedstuff(&mut *db, org_pid, params.feedstuff).await?;
+
+        let pid = sqlx::query_scalar::<_, Uuid>(
+            "
+            INSERT INTO feed_purchases (
+                organisation_pid, feedstuff_id, quantity, cost, supplier, purchase_date,
+                created_by
+            )
+            VALUES ($1, $2, $3, $4, $5, COALESCE($6, CURRENT_DATE), $7)
+            RETURNING pid",
+        )
+        .bind(org_pid)
+        .bind(feedstuff.id)
+        .bind(Decimal::new(params.quantity, 2))
+        .bind(params.cost.map(|cost| Decimal::new(cost, 2)))
+        .bind(params.supplier.as_deref())
+        .bind(params.purchase_date)
+        .bind(user_pid)
+        .fetch_one(&mut *db)
+        .await?;
+
+        Self::find_by_pid(&mut *db, org_pid, pid).await
+    }
+
+    pub async fn find_all<'e, C>(
+        db: C,
+        org_pid: Uuid,
+        conditions: &FeedQuery,
+    ) -> ModelResult<Vec<Self>>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        let query = format!(
+            "{PURCHASES_QUERY}
+            AND ($2::UUID IS NULL OR f.pid = $2)
+            AND ($3::DATE IS NULL OR p.purchase_date >= $3)
+            AND ($4::DATE IS NULL OR p.purchase_date <= $4)
+            ORDER BY p.purchase_date DESC, p.id DESC"
+        );
+
+        sqlx::query_as::<_, Self>(&query)
+            .bind(org_pid)
+            .bind(conditions.feedstuff)
+            .bind(conditions.from)
+            .bind(conditions.to)
+            .fetch_all(db)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn find_by_pid<'e, C>(db: C, org_pid: Uuid, pid: Uuid) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        let query = format!("{PURCHASES_QUERY} AND p.pid = $2");
+
+        sqlx::query_as::<_, Self>(&query)
+            .bind(org_pid)
+            .bind(pid)
+            .fetch_optional(db)
+            .await?
+            .ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    /// Takes a purchase recorded by mistake back out of stock and the ledger,
+    /// unless some of it has been fed since.
+    pub async fn delete_by_pid(db: &mut PgConnection, org_pid: Uuid, pid: Uuid) -> ModelResult<()> {
+        let purchase = Self::find_by_pid(&mut *db, org_pid, pid).await?;
+        let feedstuff = lock_feedstuff(&mut *db, org_pid, purchase.feedstuff_pid).await?;
+
+        if feedstuff.stock < purchase.quantity {
+            return Err(ModelError::Conflict(format!(
+                "Only {} of {} is left in stock",
+                feedstuff.stock, feedstuff.name
+            )));
+        }
+
+        sqlx::query("DELETE FROM feed_purchases WHERE id = $1")
+            .bind(purchase.id)
+            .execute(&mut *db)
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// A feedstuff in a ration, with what it brings per head per day.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RationLine {
+    pub feedstuff_pid: Uuid,
+    pub feedstuff_name: String,
+    pub quantity: Decimal,
+    #[serde(skip_serializing)]
+    pub dry_matter: Option<Decimal>,
+    #[serde(skip_serializing)]
+    pub crude_protein: Option<Decimal>,
+    #[serde(skip_serializing)]
+    pub energy: Option<Decimal>,
+}
+
+/// What a ration gives an animal a day. Figures the feedstuffs have no
+/// nutrient profile for are left out.
+#[derive(Debug, Default, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Nutrients {
+    pub as_fed: Decimal,
+    pub dry_matter: Decimal,
+    pub crude_protein: Decimal,
+    /// In megajoules of metabolisable energy.
+    pub energy: Decimal,
+}
+
+#[derive(Debug, Deserialize, Serialize, FromRow, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Ration {
+    pub id: i32,
+    pub pid: Uuid,
+    pub organisation_pid: Uuid,
+    pub name: String,
+    pub specie_id: Option<i32>,
+    pub specie_name: Option<String>,
+    pub breed_id: Option<i32>,
+    pub breed_name: Option<String>,
+    pub group_id: Option<i32>,
+    pub group_pid: Option<Uuid>,
+    pub group_name: Option<String>,
+    pub notes: Option<String>,
+    pub items: Json<Vec<RationLine>>,
+    #[sqlx(skip)]
+    pub nutrients: Nutrients,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<FixedOffset>,
+    pub updated_at: DateTime<FixedOffset>,
+}
+
+const RATIONS_QUERY: &str = "
+    SELECT
+        r.id,
+        r.pid,
+        r.organisation_pid,
+        r.name,
+        r.specie_id,
+        s.name AS specie_name,
+        r.breed_id,
+        b.name AS breed_name,
+        r.group_id,
+        g.pid AS group_pid,
+        g.name AS group_name,
+        r.notes,
+        COALESCE((
+            SELECT jsonb_agg(jsonb_build_object(
+                'feedstuffPid', f.pid,
+                'feedstuffName', f.name,
+                'quantity', i.quantity::TEXT,
+                'dryMatter', f.dry_matter::TEXT,
+                'crudeProtein', f.crude_protein::TEXT,
+                'energy', f.energy::TEXT
+            ) ORDER BY f.name)
+            FROM ration_items i
+            JOIN feedstuffs f ON i.feedstuff_id = f.id
+            WHERE i.ration_id = r.id
+        ), '[]') AS items,
+        r.created_by,
+        r.created_at,
+        r.updated_at
+    FROM
+        rations r
+    LEFT JOIN
+        species s ON r.specie_id = s.id
+    LEFT JOIN
+        breeds b ON r.breed_id = b.id
+    LEFT JOIN
+        animal_groups g ON r.group_id = g.id
+    WHERE
+        r.organisation_pid = $1
+";
+
+impl Ration {
+    fn with_nutrients(mut self) -> Self {
+        let mut nutrients = Nutrients::default();
+
+        for item in self.items.iter() {
+            let percent = |value: Option<Decimal>| {
+                item.quantity * value.unwrap_or_default() / Decimal::ONE_HUNDRED
+            };
+            let dry_matter = percent(item.dry_matter);
+
+            nutrients.as_fed += item.quantity;
+            nutrients.dry_matter += dry_matter;
+            nutrients.crude_protein += percent(item.crude_protein);
+            nutrients.energy += dry_matter * item.energy.unwrap_or_default();
+        }
+
+        self.nutrients = Nutrients {
+            as_fed: nutrients.as_fed.round_dp(2),
+            dry_matter: nutrients.dry_matter.round_dp(2),
+            crude_protein: nutrients.crude_protein.round_dp(2),
+            energy: nutrients.energy.round_dp(2),
+        };
+        self
+    }
+
+    /// The specie and breed named, as the ids a ration is stored with.
+    async fn specie_and_breed(
+        db: &mut PgConnection,
+        org_pid: Uuid,
+        specie: Option<&str>,
+        breed: Option<&str>,
+    ) -> ModelResult<(Option<i32>, Option<i32>)> {
+        let Some(specie) = specie else {
+            return match breed {
+                Some(_) => Err(ModelError::Validation(
+                    "A breed needs the specie it belongs to".into(),
+                )),
+                None => Ok((None, None)),
+            };
+        };
+
+        let specie_id = sqlx::query_scalar::<_, i32>("SELECT id FROM species WHERE name ILIKE $1")
+            .bind(specie.trim())
+            .fetch_optional(&mut *db)
+            .await?
+            .ok_or_else(|| ModelError::Validation(format!("Unknown specie {specie}")))?;
+
+        let breed_id = match breed {
+            Some(breed) => Some(
+                sqlx::query_scalar::<_, i32>(
+                    "SELECT id FROM breeds WHERE specie_id = $1 AND name ILIKE $2
+                    AND (organisation_pid IS NULL OR organisation_pid = $3)",
+                )
+                .bind(specie_id)
+                .bind(breed.trim())
+                .bind(org_pid)
+                .fetch_optional(&mut *db)
+                .await?
+                .ok_or_else(|| ModelError::Validation(format!("Unknown {specie} breed {breed}")))?,
+            ),
+            None => None,
+        };
+
+        Ok((Some(specie_id), breed_id))
+    }
+
+    /// The id of the group a ration is for, which it cannot be along with a
+    /// specie or breed.
+    async fn group(
+        db: &mut PgConnection,
+        org_pid: Uuid,
+        group: Option<Uuid>,
+        specie: Option<&str>,
+        breed: Option<&str>,
+    ) -> ModelResult<Option<i32>> {
+        let Some(group) = group else {
+            return Ok(None);
+        };
+        if specie.is_some() || breed.is_some() {
+            return Err(ModelError::Validation(
+                "A ration is for a group or a specie, not both".into(),
+            ));
+        }
+
+        let group = AnimalGroup::find_by_pid(&mut *db, org_pid, group)
+            .await
+            .map_err(|_| ModelError::Validation(format!("Unknown group {group}")))?;
+
+        Ok(Some(group.id))
+    }
+
+    async fn set_items(
+        db: &mut PgConnection,
+        org_pid: Uuid,
+        ration_id: i32,
+        items: &[RationItem],
+    ) -> ModelResult<()> {
+        let mut feedstuffs = Vec::with_capacity(items.len());
+        for item in items {
+            if items
+                .iter()
+                .filter(|other| other.feedstuff == item.feedstuff)
+                .count()
+                > 1
+            {
+                return Err(ModelError::Validation(
+                    "Each feedstuff can only be in a ration once".into(),
+                ));
+            }
+
+            let feedstuff = Feedstuff::find_by_pid(&mut *db, org_pid, item.feedstuff)
+                .await
+                .map_err(|_| {
+                    ModelError::Validation(format!("Unknown feedstuff {}", item.feedstuff))
+                })?;
+            feedstuffs.push((feedstuff.id, Decimal::new(item.quantity, 2)));
+        }
+
+        sqlx::query("DELETE FROM ration_items WHERE ration_id = $1")
+            .bind(ration_id)
+            .execute(&mut *db)
+            .await?;
+
+        for (feedstuff_id, quantity) in feedstuffs {
+            sqlx::query(
+                "INSERT INTO ration_items (ration_id, feedstuff_id, organisation_pid, quantity)
+                VALUES ($1, $2, $3, $4)",
+            )
+            .bind(ration_id)
+            .bind(feedstuff_id)
+            .bind(org_pid)
+            .bind(quantity)
+            .execute(&mut *db)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn create(
+        db: &mut PgConnection,
+        org_pid: Uuid,
+        user_pid: Uuid,
+        params: &NewRation<'_>,
+    ) -> ModelResult<Self> {
+        let validator = Validator::new(params);
+        let params = validator.validate()?;
+
+        let group_id = Self::group(
+            &mut *db,
+            org_pid,
+            params.group,
+            params.specie.as_deref(),
+            params.breed.as_deref(),
+        )
+        .await?;
+        let (specie_id, breed_id) = Self::specie_and_breed(
+            &mut *db,
+            org_pid,
+            params.specie.as_deref(),
+            params.breed.as_deref(),
+        )
+        .await?;
+
+        let (id, pid) = sqlx::query_as::<_, (i32, Uuid)>(
+            "
+            INSERT INTO rations (
+                organisation_pid, name, specie_id, breed_id, group_id, notes, created_by
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING id, pid",
+        )
+        .bind(org_pid)
+        .bind(params.name.trim())
+        .bind(specie_id)
+        .bind(breed_id)
+        .bind(group_id)
+        .bind(params.notes.as_deref())
+        .bind(user_pid)
+        .fetch_one(&mut *db)
+        .await
+        .map_err(unique_name("ration"))?;
+
+        Self::set_items(&mut *db, org_pid, id, &params.items).await?;
+
+        Self::find_by_pid(&mut *db, org_pid, pid).await
+    }
+
+    pub async fn find_all<'e, C>(db: C, org_pid: Uuid) -> ModelResult<Vec<Self>>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        let query = format!("{RATIONS_QUERY} ORDER BY r.name");
+
+        let rations = sqlx::query_as::<_, Self>(&query)
+            .bind(org_pid)
+            .fetch_all(db)
+            .await?;
+
+        Ok(rations.into_iter().map(Self::with_nutrients).collect())
+    }
+
+    pub async fn find_by_pid<'e, C>(db: C, org_pid: Uuid, pid: Uuid) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        let query = format!("{RATIONS_QUERY} AND r.pid = $2");
+
+        sqlx::query_as::<_, Self>(&query)
+            .bind(org_pid)
+            .bind(pid)
+            .fetch_optional(db)
+            .await?
+            .map(Self::with_nutrients)
+            .ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    /// Changing the specie keeps the breed only if it is given again.
+    pub async fn update(
+        db: &mut PgConnection,
+        org_pid: Uuid,
+        pid: Uuid,
+        params: &UpdateRation<'_>,
+    ) -> ModelResult<Self> {
+        let validator = Validator::new(params);
+        let params = validator.validate()?;
+
+        let ration = Self::find_by_pid(&mut *db, org_pid, pid).await?;
+
+        let (specie_id, breed_id, group_id) = if params.group.is_some() {
+            let group_id = Self::group(
+                &mut *db,
+                org_pid,
+                params.group,
+                params.specie.as_deref(),
+                params.breed.as_deref(),
+            )
+            .await?;
+            (None, None, group_id)
+        } else if params.specie.is_some() || params.breed.is_some() {
+            let (specie_id, breed_id) = Self::specie_and_breed(
+                &mut *db,
+                org_pid,
+                params.specie.as_deref().or(ration.specie_name.as_deref()),
+                params.breed.as_deref(),
+            )
+            .await?;
+            (specie_id, breed_id, None)
+        } else {
+            (ration.specie_id, ration.breed_id, ration.group_id)
+        };
+
+        sqlx::query(
+            "
+            UPDATE rations SET
+                name = COALESCE($2, name),
+                specie_id = $3,
+                breed_id = $4,
+                group_id = $5,
+                notes = COALESCE($6, notes)
+            WHERE id = $1",
+        )
+        .bind(ration.id)
+        .bind(params.name.as_deref().map(str::trim))
+        .bind(specie_id)
+        .bind(breed_id)
+        .bind(group_id)
+        .bind(params.notes.as_deref())
+        .execute(&mut *db)
+        .await
+        .map_err(unique_name("ration"))?;
+
+        if let Some(items) = &params.items {
+            Self::set_items(&mut *db, org_pid, ration.id, items).await?;
+        }
+
+        Self::find_by_pid(&mut *db, org_pid, pid).await
+    }
+
+    /// Feedings keep what was fed, only losing the ration they came from.
+    pub async fn delete_by_pid(db: &mut PgConnection, org_pid: Uuid, pid: Uuid) -> ModelResult<()> {
+        let deleted = sqlx::query("DELETE FROM rations WHERE pid = $1 AND organisation_pid = $2")
+            .bind(pid)
+            .bind(org_pid)
+            .execute(&mut *db)
+            .await?;
+
+        if deleted.rows_affected() == 0 {
+            return Err(ModelError::EntityNotFound);
+        }
+
+        Ok(())
+    }
+
+    /// Feeds the ration for a day to the animals tagged, or else to every
+    /// active animal of its group, or of its specie and breed, recording a
+    /// feeding of each of its feedstuffs.
+    pub async fn feed(
+        db: &mut PgConnection,
+        org_pid: Uuid,
+        user_pid: Uuid,
+        pid: Uuid,
+        params: &FeedRation<'_>,
+    ) -> ModelResult<Vec<Feeding>> {
+        let ration = Self::find_by_pid(&mut *db, org_pid, pid).await?;
+
+        let animals = if let Some(tag_ids) = &params.tag_ids {
+            let tag_ids = tag_ids.iter().map(ToString::to_string).collect::<Vec<_>>();
+            tagged(&mut *db, org_pid, &tag_ids).await?
+        } else {
+            let members = match ration.group_pid {
+                Some(group) => Some(
+                    AnimalGroup::find_by_pid(&mut *db, org_pid, group)
+                        .await?
+                        .animal_pids(&mut *db)
+                        .await?,
+                ),
+                None => None,
+            };
+
+            sqlx::query_scalar::<_, Uuid>(
+                "SELECT pid FROM animals
+                WHERE organisation_pid = $1 AND status = 'active' AND deleted_at IS NULL
+                AND ($2::INTEGER IS NULL OR specie_id = $2)
+                AND ($3::INTEGER IS NULL OR breed_id = $3)
+                AND ($4::UUID[] IS NULL OR pid = ANY($4))",
+            )
+            .bind(org_pid)
+            .bind(ration.specie_id)
+            .bind(ration.breed_id)
+            .bind(members)
+            .fetch_all(&mut *db)
+            .await?
+        };
+        if animals.is_empty() {
+            return Err(ModelError::Validation(format!(
+                "No animals to feed {} to",
+                ration.name
+            )));
+        }
+
+        let head = Decimal::from(animals.len());
+        let mut feedings = Vec::with_capacity(ration.items.len());
+        for item in ration.items.iter() {
+            let pid = Feeding::record(
+                &mut *db,
+                org_pid,
+                user_pid,
+                item.feedstuff_pid,
+                Some(ration.id),
+                item.quantity * head,
+                params.feeding_date,
+                &animals,
+                params.notes.as_deref(),
+            )
+            .await?;
+            feedings.push(Feeding::find_by_pid(&mut *db, org_pid, pid).await?);
+        }
+
+        Ok(feedings)
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, FromRow, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Feeding {
+    pub id: i32,
+    pub pid: Uuid,
+    pub organisation_pid: Uuid,
+    pub feedstuff_pid: Uuid,
+    pub feedstuff_name: String,
+    pub ration_pid: Option<Uuid>,
+    pub ration_name: Option<String>,
+    pub quantity: Decimal,
+    pub feeding_date: NaiveDate,
+    /// The animals the feed was shared by, none if it went to the herd.
+    pub tag_ids: Vec<String>,
+    pub notes: Option<String>,
+    pub created_by: Option<Uuid>,
+    pub created_by_name: Option<String>,
+    pub created_at: DateTime<FixedOffset>,
+    pub updated_at: DateTime<FixedOffset>,
+}
+
+const FEEDINGS_QUERY: &str = "
+    SELECT
+        fd.id,
+        fd.pid,
+        fd.organisation_pid,
+        f.pid AS feedstuff_pid,
+        f.name AS feedstuff_name,
+        r.pid AS ration_pid,
+        r.name AS ration_name,
+        fd.quantity,
+        fd.feeding_date,
+        ARRAY(
+            SELECT a.tag_id::TEXT
+            FROM feeding_animals fa
+            JOIN animals a ON fa.animal_pid = a.pid
+            WHERE fa.feeding_id = fd.id
+            ORDER BY a.tag_id
+        ) AS tag_ids,
+        fd.notes,
+        fd.created_by,
+        CONCAT(u.first_name, ' ', u.last_name) AS created_by_name,
+        fd.created_at,
+        fd.updated_at
+    FROM
+        feedings fd
+    JOIN
+        feedstuffs f ON fd.feedstuff_id = f.id
+    LEFT JOIN
+        rations r ON fd.ration_id = r.id
+    LEFT JOIN
+        users u ON fd.created_by = u.pid
+    WHERE
+        fd.organisation_pid = $1
+";
+
+impl Feeding {
+    /// Takes `quantity` of the feedstuff out of stock for `animals`, or the
+    /// herd, and returns the new feeding's pid.
+    #[allow(clippy::too_many_arguments)]
+    async fn record(
+        db: &mut PgConnection,
+        org_pid: Uuid,
+        user_pid: Uuid,
+        feedstuff_pid: Uuid,
+        ration_id: Option<i32>,
+        quantity: Decimal,
+        date: Option<NaiveDate>,
+        animals: &[Uuid],
+        notes: Option<&str>,
+    ) -> ModelResult<Uuid> {
+        let feedstuff = lock_feedstuff(&mut *db, org_pid, feedstuff_pid).await?;
+        if feedstuff.stock < quantity {
+            return Err(ModelError::Validation(format!(
+                "Only {} of {} is in stock",
+                feedstuff.stock, feedstuff.name
+            )));
+        }
+
+        let (id, pid) = sqlx::query_as::<_, (i32, Uuid)>(
+            "
+            INSERT INTO feedings (
+                organisation_pid, feedstuff_id, ration_id, quantity, feeding_date, notes,
+                created_by
+            )
+            VALUES ($1, $2, $3, $4, COALESCE($5, CURRENT_DATE), $6, $7)
+            RETURNING id, pid",
+        )
+        .bind(org_pid)
+        .bind(feedstuff.id)
+        .bind(ration_id)
+        .bind(quantity)
+        .bind(date)
+        .bind(notes)
+        .bind(user_pid)
+        .fetch_one(&mut *db)
+        .await?;
+
+        sqlx::query(
+            "INSERT INTO feeding_animals (feeding_id, animal_pid, organisation_pid)
+            SELECT $1, UNNEST($2::UUID[]), $3",
+        )
+        .bind(id)
+        .bind(animals)
+        .bind(org_pid)
+        .execute(&mut *db)
+        .await?;
+
+        Ok(pid)
+    }
+
+    pub async fn create(
+        db: &mut PgConnection,
+        org_pid: Uuid,
+        user_pid: Uuid,
+        params: &NewFeeding<'_>,
+    ) -> ModelResult<Self> {
+        let validator = Validator::new(params);
+        let params = validator.validate()?;
+
+        let tag_ids = params
+            .tag_ids
+            .iter()
+            .flatten()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        let animals = tagged(&mut *db, org_pid, &tag_ids).await?;
+
+        let pid = Self::record(
+            &mut *db,
+            org_pid,
+            user_pid,
+            params.feedstuff,
+            None,
+            Decimal::new(params.quantity, 2),
+            params.feeding_date,
+            &animals,
+            params.notes.as_deref(),
+        )
+        .await?;
+
+        Self::find_by_pid(&mut *db, org_pid, pid).await
+    }
+
+    pub async fn find_all<'e, C>(
+        db: C,
+        org_pid: Uuid,
+        conditions: &FeedQuery,
+    ) -> ModelResult<Vec<Self>>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        let query = format!(
+            "{FEEDINGS_QUERY}
+            AND ($2::UUID IS NULL OR f.pid = $2)
+            AND ($3::UUID IS NULL OR EXISTS (
+                SELECT 1 FROM feeding_animals fa
+                WHERE fa.feeding_id = fd.id AND fa.animal_pid = $3
+            ))
+            AND ($4::DATE IS NULL OR fd.feeding_date >= $4)
+            AND ($5::DATE IS NULL OR fd.feeding_date <= $5)
+            ORDER BY fd.feeding_date DESC, fd.id DESC"
+        );
+
+        sqlx::query_as::<_, Self>(&query)
+            .bind(org_pid)
+            .bind(conditions.feedstuff)
+            .bind(conditions.animal)
+            .bind(conditions.from)
+            .bind(conditions.to)
+            .fetch_all(db)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn find_by_pid<'e, C>(db: C, org_pid: Uuid, pid: Uuid) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        let query = format!("{FEEDINGS_QUERY} AND fd.pid = $2");
+
+        sqlx::query_as::<_, Self>(&query)
+            .bind(org_pid)
+            .bind(pid)
+            .fetch_optional(db)
+            .await?
+            .ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    /// Puts what was fed back in stock.
+    pub async fn delete_by_pid(db: &mut PgConnection, org_pid: Uuid, pid: Uuid) -> ModelResult<()> {
+        let deleted = sqlx::query("DELETE FROM feedings WHERE pid = $1 AND organisation_pid = $2")
+            .bind(pid)
+            .bind(org_pid)
+            .execute(&mut *db)
+            .await?;
+
+        if deleted.rows_affected() == 0 {
+            return Err(ModelError::EntityNotFound);
+        }
+
+        Ok(())
+    }
+}
+
+/// How long a feedstuff's stock lasts at the rate it has been fed.
+#[derive(Debug, Deserialize, Serialize, FromRow, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StockLevel {
+    pub pid: Uuid,
+    pub name: String,
+    pub stock: Decimal,
+    pub reorder_level: Option<Decimal>,
+    /// The average fed a day over the days looked back on.
+    pub daily_use: Decimal,
+    #[sqlx(skip)]
+    pub days_left: Option<Decimal>,
+    #[sqlx(skip)]
+    pub runs_out_on: Option<NaiveDate>,
+    /// At or below its reorder level, or running out within the days asked.
+    #[sqlx(skip)]
+    pub low: bool,
+}
+
+impl StockLevel {
+    pub async fn find_all<'e, C>(db: C, org_pid: Uuid, params: StockQuery) -> ModelResult<Vec<Self>>
+    where
+        C: Executor<'e, Database = Postgres> + Copy,
+    {
+        let days = params.days.unwrap_or(30);
+        let within = params.within.unwrap_or(14);
+        if !(1..=365).contains(&days) {
+            return Err(ModelError::Validation(
+                "Days must be between 1 and 365".into(),
+            ));
+        }
+        if within < 0 {
+            return Err(ModelError::Validation("Within must not be negative".into()));
+        }
+
+        let today = OrganisationSettings::find(db, org_pid).await?.today();
+
+        let mut levels = sqlx::query_as::<_, Self>(
+            "
+            SELECT
+                f.pid,
+                f.name,
+                f.stock,
+                f.reorder_level,
+                ROUND(COALESCE((
+                    SELECT SUM(fd.quantity)
+                    FROM feedings fd
+                    WHERE fd.feedstuff_id = f.id
+                        AND fd.feeding_date > $2::DATE - $3::INTEGER
+                        AND fd.feeding_date <= $2
+                ), 0) / $3, 2) AS daily_use
+            FROM feedstuffs f
+            WHERE f.organisation_pid = $1
+            ORDER BY f.name",
+        )
+        .bind(org_pid)
+        .bind(today)
+        .bind(i32::try_from(days).unwrap_or(30))
+        .fetch_all(db)
+        .await?;
+
+        for level in &mut levels {
+            if !level.daily_use.is_zero() {
+                let days_left = (level.stock.max(Decimal::ZERO) / level.daily_use).round_dp(1);
+                level.days_left = Some(days_left);
+                level.runs_out_on = days_left
+                    .trunc()
+                    .to_u64()
+                    .and_then(|days| today.checked_add_days(Days::new(days)));
+            }
+
+            level.low = level
+                .reorder_level
+                .is_some_and(|reorder| level.stock <= reorder)
+                || level
+                    .days_left
+                    .is_some_and(|days_left| days_left <= Decimal::from(within));
+        }
+
+        Ok(levels)
+    }
+}
+
+/// A product and how much feed went into each unit of it.
+#[derive(Debug, Deserialize, Serialize, FromRow, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedYield {
+    #[serde(skip)]
+    pub animal_pid: Option<Uuid>,
+    pub product_type: String,
+    pub unit: String,
+    pub quantity: Decimal,
+    #[sqlx(skip)]
+    pub feed_per_unit: Option<Decimal>,
+}
+
+/// How well feed was turned into weight and produce.
+#[derive(Debug, Deserialize, Serialize, FromRow, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedConversion {
+    pub animal_pid: Option<Uuid>,
+    pub tag_id: Option<String>,
+    /// As fed.
+    pub feed: Decimal,
+    /// Feedstuffs without a dry matter figure count as fed.
+    pub dry_matter: Decimal,
+    /// Between the first and last weighing in the period.
+    pub weight_gain: Option<Decimal>,
+    /// Feed per unit of weight gained.
+    #[sqlx(skip)]
+    pub conversion_ratio: Option<Decimal>,
+    #[sqlx(skip)]
+    pub yields: Vec<FeedYield>,
+}
+
+impl FeedConversion {
+    fn with_yields(mut self, yields: Vec<FeedYield>) -> Self {
+        let per = |quantity: Decimal| {
+            (quantity > Decimal::ZERO).then(|| (self.feed / quantity).round_dp(2))
+        };
+
+        self.conversion_ratio = self.weight_gain.and_then(per);
+        self.yields = yields
+            .into_iter()
+            .map(|feed_yield| FeedYield {
+                feed_per_unit: per(feed_yield.quantity),
+                ..feed_yield
+            })
+            .collect();
+        self
+    }
+}
+
+/// Feed conversion of the herd and of each animal fed in a period.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversionReport {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub herd: FeedConversion,
+    pub animals: Vec<FeedConversion>,
+}
+
+impl ConversionReport {
+    pub async fn find<'e, C>(db: C, org_pid: Uuid, period: ConversionQuery) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres> + Copy,
+    {
+        let gain = "
+            (SELECT
+                (ARRAY_AGG(w.mass ORDER BY w.record_date DESC, w.id DESC))[1]
+                - (ARRAY_AGG(w.mass ORDER BY w.record_date, w.id))[1]
+            FROM weight_records w
+            WHERE w.animal_pid = a.pid AND w.deleted_at IS NULL
+                AND ($2::DATE IS NULL OR w.record_date >= $2)
+                AND ($3::DATE IS NULL OR w.record_date <= $3)
+            HAVING COUNT(*) > 1)";
+
+        let query = format!(
+            "
+            WITH shares AS (
+                SELECT
+                    fa.animal_pid,
+                    fd.quantity / COUNT(*) OVER (PARTITION BY fd.id) AS feed,
+                    COALESCE(f.dry_matter, 100) / 100 AS dry_matter
+                FROM feedings fd
+                JOIN feeding_animals fa ON fa.feeding_id = fd.id
+                JOIN feedstuffs f ON fd.feedstuff_id = f.id
+                WHERE fd.organisation_pid = $1
+                    AND ($2::DATE IS NULL OR fd.feeding_date >= $2)
+                    AND ($3::DATE IS NULL OR fd.feeding_date <= $3)
+            )
+            SELECT
+                a.pid AS animal_pid,
+                a.tag_id,
+                ROUND(SUM(s.feed), 2) AS feed,
+                ROUND(SUM(s.feed * s.dry_matter), 2) AS dry_matter,
+                {gain} AS weight_gain
+            FROM shares s
+            JOIN animals a ON s.animal_pid = a.pid
+            WHERE a.deleted_at IS NULL
+            GROUP BY a.pid, a.tag_id
+            ORDER BY a.tag_id"
+        );
+
+        let animals = sqlx::query_as::<_, FeedConversion>(&query)
+            .bind(org_pid)
+            .bind(period.from)
+            .bind(period.to)
+            .fetch_all(db)
+            .await?;
+
+        let query = format!(
+            "
+            SELECT
+                NULL::UUID AS animal_pid,
+                NULL::TEXT AS tag_id,
+                COALESCE((
+                    SELECT ROUND(SUM(fd.quantity), 2)
+                    FROM feedings fd
+                    WHERE fd.organisation_pid = $1
+                        AND ($2::DATE IS NULL OR fd.feeding_date >= $2)
+                        AND ($3::DATE IS NULL OR fd.feeding_date <= $3)
+                ), 0) AS feed,
+                COALESCE((
+                    SELECT ROUND(SUM(fd.quantity * COALESCE(f.dry_matter, 100) / 100), 2)
+                    FROM feedings fd
+                    JOIN feedstuffs f ON fd.feedstuff_id = f.id
+                    WHERE fd.organisation_pid = $1
+                        AND ($2::DATE IS NULL OR fd.feeding_date >= $2)
+                        AND ($3::DATE IS NULL OR fd.feeding_date <= $3)
+                ), 0) AS dry_matter,
+                (
+                    SELECT SUM({gain})
+                    FROM animals a
+                    WHERE a.organisation_pid = $1 AND a.deleted_at IS NULL
+                ) AS weight_gain"
+        );
+
+        let herd = sqlx::query_as::<_, FeedConversion>(&query)
+            .bind(org_pid)
+            .bind(period.from)
+            .bind(period.to)
+            .fetch_one(db)
+            .await?;
+
+        let yields = sqlx::query_as::<_, FeedYield>(
+            "
+            SELECT p.animal_pid, p.product_type, p.unit, SUM(p.quantity) AS quantity
+            FROM production_records p
+            JOIN animals a ON p.animal_pid = a.pid
+            WHERE p.organisation_pid = $1 AND p.deleted_at IS NULL AND a.deleted_at IS NULL
+                AND ($2::DATE IS NULL OR p.record_date >= $2)
+                AND ($3::DATE IS NULL OR p.record_date <= $3)
+            GROUP BY p.animal_pid, p.product_type, p.unit
+            ORDER BY p.product_type, p.unit",
+        )
+        .bind(org_pid)
+        .bind(period.from)
+        .bind(period.to)
+        .fetch_all(db)
+        .await?;
+
+        let mut herd_yields = BTreeMap::<(String, String), FeedYield>::new();
+        for feed_yield in &yields {
+            herd_yields
+                .entry((feed_yield.product_type.clone(), feed_yield.unit.clone()))
+                .and_modify(|total| total.quantity += feed_yield.quantity)
+                .or_insert_with(|| FeedYield {
+                    animal_pid: None,
+                    ..feed_yield.clone()
+                });
+        }
+
+        let animals = animals
+            .into_iter()
+            .map(|animal| {
+                let own = yields
+                    .iter()
+                    .filter(|feed_yield| feed_yield.animal_pid == animal.animal_pid)
+                    .cloned()
+                    .collect();
+                animal.with_yields(own)
+            })
+            .collect();
+
+        Ok(Self {
+            from: period.from,
+            to: period.to,
+            herd: herd.with_yields(herd_yields.into_values().collect()),
+            animals,
+        })
+    }
+}
@@ -0,0 +1,610 @@
+#![allow(clippy::missing_errors_doc)]
+
+use std::{fmt, str::FromStr};
+
+use chrono::{DateTime, FixedOffset, NaiveDate};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{Executor, PgConnection, Postgres, prelude::FromRow};
+use uuid::Uuid;
+
+use super::{
+    ModelError, ModelResult,
+    animals::Animal,
+    dto::{LedgerQuery, NewLedgerEntry, ProfitQuery, UpdateLedgerEntry, Validator},
+    groups::AnimalGroup,
+};
+
+/// What money was spent on or earned from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LedgerCategory {
+    Purchase,
+    Feed,
+    Vet,
+    Labour,
+    OtherExpense,
+    SaleProceeds,
+    MilkSales,
+    OtherIncome,
+}
+
+impl LedgerCategory {
+    pub const ALL: &'static [Self] = &[
+        Self::Purchase,
+        Self::Feed,
+        Self::Vet,
+        Self::Labour,
+        Self::OtherExpense,
+        Self::SaleProceeds,
+        Self::MilkSales,
+        Self::OtherIncome,
+    ];
+
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Purchase => "purchase",
+            Self::Feed => "feed",
+            Self::Vet => "vet",
+            Self::Labour => "labour",
+            Self::OtherExpense => "other_expense",
+            Self::SaleProceeds => "sale_proceeds",
+            Self::MilkSales => "milk_sales",
+            Self::OtherIncome => "other_income",
+        }
+    }
+
+    /// Whether entries of the category are money earned rather than spent.
+    #[must_use]
+    pub const fn is_income(&self) -> bool {
+        matches!(
+            self,
+            Self::SaleProceeds | Self::MilkSales | Self::OtherIncome
+        )
+    }
+}
+
+impl fmt::Display for LedgerCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for LedgerCategory {
+    type Err = ModelError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .find(|category| category.as_str() == value.trim())
+            .copied()
+            .ok_or_else(|| ModelError::Validation(format!("Unknown ledger category {value}")))
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, FromRow, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LedgerEntry {
+    pub id: i32,
+    pub pid: Uuid,
+    pub organisation_pid: Uuid,
+    pub animal_pid: Option<Uuid>,
+    pub animal_tag_id: Option<String>,
+    pub group_pid: Option<Uuid>,
+    pub group_name: Option<String>,
+    pub category: String,
+    pub amount: Decimal,
+    pub entry_date: NaiveDate,
+    pub description: Option<String>,
+    /// The kind of record the entry was posted from, if it was.
+    pub source: Option<String>,
+    pub source_id: Option<String>,
+    pub created_by: Option<Uuid>,
+    pub created_by_name: Option<String>,
+    pub created_at: DateTime<FixedOffset>,
+    pub updated_at: DateTime<FixedOffset>,
+}
+
+const FETCH_QUERY: &str = "
+    SELECT
+        l.id,
+        l.pid,
+        l.organisation_pid,
+        l.animal_pid,
+        a.tag_id AS animal_tag_id,
+        l.group_pid,
+        g.name AS group_name,
+        l.category,
+        l.amount,
+        l.entry_date,
+        l.description,
+        l.source,
+        l.source_id,
+        l.created_by,
+        CONCAT(u.first_name, ' ', u.last_name) AS created_by_name,
+        l.created_at,
+        l.updated_at
+    FROM
+        ledger_entries l
+    LEFT JOIN
+        animals a ON l.animal_pid = a.pid
+    LEFT JOIN
+        animal_groups g ON l.group_pid = g.pid
+    LEFT JOIN
+        users u ON l.created_by = u.pid
+    WHERE
+        l.organisation_pid = $1
+";
+
+fn fetch_query(conditions: &str) -> String {
+    format!("{FETCH_QUERY} {conditions}")
+}
+
+impl LedgerEntry {
+    pub async fn create(
+        db: &mut PgConnection,
+        org_pid: Uuid,
+        user_pid: Uuid,
+        params: &NewLedgerEntry<'_>,
+    ) -> ModelResult<Self> {
+        let validator = Validator::new(params);
+        let params = validator.validate()?;
+
+        let animal_pid = match &params.tag_id {
+            Some(tag_id) => Some(
+                sqlx::query_scalar::<_, Uuid>(
+                    "SELECT pid FROM animals
+                    WHERE tag_id = $1 AND organisation_pid = $2 AND deleted_at IS NULL",
+                )
+                .bind(tag_id.to_uppercase())
+                .bind(org_pid)
+                .fetch_optional(&mut *db)
+                .await?
+                .ok_or_else(|| ModelError::Validation(format!("No animal is tagged {tag_id}")))?,
+            ),
+            None => None,
+        };
+
+        if let Some(group_pid) = params.group {
+            if animal_pid.is_some() {
+                return Err(ModelError::Validation(
+                    "An entry is for an animal or a group, not both".into(),
+                ));
+            }
+            AnimalGroup::find_by_pid(&mut *db, org_pid, group_pid)
+                .await
+                .map_err(|_| ModelError::Validation("No such group".into()))?;
+        }
+
+        let pid = sqlx::query_scalar::<_, Uuid>(
+            "
+            INSERT INTO ledger_entries (
+                organisation_pid, animal_pid, group_pid, category, amount, entry_date,
+                description, created_by
+            )
+            VALUES ($1, $2, $3, $4, $5, COALESCE($6, CURRENT_DATE), $7, $8)
+            RETURNING pid",
+        )
+        .bind(org_pid)
+        .bind(animal_pid)
+        .bind(params.group)
+        .bind(params.category.trim())
+        .bind(Decimal::new(params.amount, 2))
+        .bind(params.entry_date)
+        .bind(params.description.as_deref())
+        .bind(user_pid)
+        .fetch_one(&mut *db)
+        .await?;
+
+        Self::find_by_pid(&mut *db, org_pid, pid).await
+    }
+
+    pub async fn find_all<'e, C>(
+        db: C,
+        org_pid: Uuid,
+        conditions: &LedgerQuery,
+    ) -> ModelResult<Vec<Self>>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        let query = fetch_query(
+            "AND ($2::TEXT IS NULL OR l.category = $2)
+            AND ($3::UUID IS NULL OR l.animal_pid = $3)
+            AND ($4::UUID IS NULL OR l.group_pid = $4)
+            AND ($5::DATE IS NULL OR l.entry_date >= $5)
+            AND ($6::DATE IS NULL OR l.entry_date <= $6)
+            ORDER BY l.entry_date DESC, l.id DESC",
+        );
+
+        sqlx::query_as::<_, Self>(&query)
+            .bind(org_pid)
+            .bind(conditions.category.as_deref())
+            .bind(conditions.animal)
+            .bind(conditions.group)
+            .bind(conditions.from)
+            .bind(conditions.to)
+            .fetch_all(db)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn find_by_pid<'e, C>(db: C, org_pid: Uuid, pid: Uuid) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        let query = fetch_query("AND l.pid = $2");
+
+        sqlx::query_as::<_, Self>(&query)
+            .bind(org_pid)
+            .bind(pid)
+            .fetch_optional(db)
+            .await?
+            .ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    /// Entries posted from records change with them, not on their own.
+    fn ensure_manual(&self) -> ModelResult<()> {
+        self.source.as_ref().map_or(Ok(()), |source| {
+            Err(ModelError::Conflict(format!(
+                "The entry was posted from a {} and changes with it",
+                source.replace('_', " ")
+            )))
+        })
+    }
+
+    pub async fn update(
+        db: &mut PgConnection,
+        org_pid: Uuid,
+        pid: Uuid,
+        params: &UpdateLedgerEntry<'_>,
+    ) -> ModelResult<Self> {
+        let validator = Validator::new(params);
+        let params = validator.validate()?;
+
+        let entry = Self::find_by_pid(&mut *db, org_pid, pid).await?;
+        entry.ensure_manual()?;
+
+        sqlx::query(
+            "
+            UPDATE ledger_entries SET
+                category = COALESCE($2, category),
+                amount = COALESCE($3, amount),
+                entry_date = COALESCE($4, entry_date),
+                description = COALESCE($5, description)
+            WHERE pid = $1",
+        )
+        .bind(entry.pid)
+        .bind(params.category.as_deref().map(str::trim))
+        .bind(params.amount.map(|amount| Decimal::new(amount, 2)))
+        .bind(params.entry_date)
+        .bind(params.description.as_deref())
+        .execute(&mut *db)
+        .await?;
+
+        Self::find_by_pid(&mut *db, org_pid, pid).await
+    }
+
+    pub async fn delete_by_pid(db: &mut PgConnection, org_pid: Uuid, pid: Uuid) -> ModelResult<()> {
+        let entry = Self::find_by_pid(&mut *db, org_pid, pid).await?;
+        entry.ensure_manual()?;
+
+        sqlx::query("DELETE FROM ledger_entries WHERE pid = $1")
+            .bind(entry.pid)
+            .execute(&mut *db)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Posts what a sold animal was sold for, on `date` or today, replacing
+    /// what was posted for an earlier sale price.
+    pub async fn post_sale(
+        db: &mut PgConnection,
+        animal: &Animal,
+        price: Decimal,
+        date: Option<NaiveDate>,
+    ) -> ModelResult<()> {
+        sqlx::query(
+            "
+            INSERT INTO ledger_entries (
+                organisation_pid, animal_pid, category, amount, entry_date, description,
+                source, source_id
+            )
+            VALUES ($1, $2, 'sale_proceeds', $3, COALESCE($4, CURRENT_DATE), $5, 'animal_sale', $2::TEXT)
+            ON CONFLICT (source, source_id) DO UPDATE SET
+                amount = EXCLUDED.amount,
+                entry_date = COALESCE($4, ledger_entries.entry_date),
+                description = EXCLUDED.description",
+        )
+        .bind(animal.organisation_pid)
+        .bind(animal.pid)
+        .bind(price)
+        .bind(date)
+        .bind(format!("Sale of {}", animal.tag_id))
+        .execute(&mut *db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Withdraws the sale of an animal that turns out not to be sold.
+    pub async fn withdraw_sale(db: &mut PgConnection, animal_pid: Uuid) -> ModelResult<()> {
+        sqlx::query("DELETE FROM ledger_entries WHERE source = 'animal_sale' AND source_id = $1")
+            .bind(animal_pid.to_string())
+            .execute(&mut *db)
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Money spent or earned in one category.
+#[derive(Debug, Deserialize, Serialize, FromRow, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CategoryTotal {
+    pub category: String,
+    #[sqlx(skip)]
+    pub income: bool,
+    pub amount: Decimal,
+    pub entries: i64,
+}
+
+/// What producing a unit of a product cost: the operating costs spread over
+/// everything of it produced in the period.
+#[derive(Debug, Deserialize, Serialize, FromRow, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProductionCost {
+    pub product_type: String,
+    pub unit: String,
+    pub quantity: Decimal,
+    #[sqlx(skip)]
+    pub cost_per_unit: Option<Decimal>,
+}
+
+/// The profit and loss of the herd, of one animal or of a group.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfitAndLoss {
+    pub animal_pid: Option<Uuid>,
+    pub group_pid: Option<Uuid>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub income: Decimal,
+    pub expenses: Decimal,
+    pub profit: Decimal,
+    /// Expenses other than purchases.
+    pub operating_costs: Decimal,
+    /// What the animal was bought for, or what the herd's or group's
+    /// purchases in the period came to.
+    pub purchase_price: Option<Decimal>,
+    /// The profit as a percentage of the purchase price.
+    pub return_on_purchase: Option<Decimal>,
+    pub categories: Vec<CategoryTotal>,
+    pub production_costs: Vec<ProductionCost>,
+}
+
+/// One animal's line in the herd's profit and loss.
+#[derive(Debug, Deserialize, Serialize, FromRow, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AnimalProfit {
+    pub animal_pid: Uuid,
+    pub tag_id: String,
+    pub name: String,
+    pub status: String,
+    pub income: Decimal,
+    pub expenses: Decimal,
+    pub profit: Decimal,
+    pub purchase_price: Option<Decimal>,
+    #[sqlx(skip)]
+    pub return_on_purchase: Option<Decimal>,
+}
+
+fn return_on(profit: Decimal, purchase_price: Option<Decimal>) -> Option<Decimal> {
+    purchase_price
+        .filter(|price| !price.is_zero())
+        .map(|price| (profit / price * Decimal::ONE_HUNDRED).round_dp(2))
+}
+
+/// What a profit and loss totals.
+enum Scope {
+    Herd,
+    Animal(Uuid),
+    /// The group's entries and those of the animals in it now.
+    Group(Uuid, Vec<Uuid>),
+}
+
+impl ProfitAndLoss {
+    pub async fn for_herd<'e, C>(db: C, org_pid: Uuid, period: ProfitQuery) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres> + Copy,
+    {
+        Self::find(db, org_pid, Scope::Herd, period).await
+    }
+
+    pub async fn for_animal<'e, C>(
+        db: C,
+        org_pid: Uuid,
+        animal_pid: Uuid,
+        period: ProfitQuery,
+    ) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres> + Copy,
+    {
+        let animal = Animal::find_by_id(db, org_pid, animal_pid).await?;
+
+        let mut profit = Self::find(db, org_pid, Scope::Animal(animal_pid), period).await?;
+        profit.purchase_price = animal.purchase_price;
+        profit.return_on_purchase = return_on(profit.profit, animal.purchase_price);
+
+        Ok(profit)
+    }
+
+    /// The profit and loss of a group: what was entered for the group itself
+    /// and for the animals in it now.
+    pub async fn for_group<'e, C>(
+        db: C,
+        org_pid: Uuid,
+        group_pid: Uuid,
+        period: ProfitQuery,
+    ) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres> + Copy,
+    {
+        let group = AnimalGroup::find_by_pid(db, org_pid, group_pid).await?;
+        let animals = group.animal_pids(db).await?;
+
+        Self::find(db, org_pid, Scope::Group(group_pid, animals), period).await
+    }
+
+    /// Totals the ledger and production in the scope. The purchase price is
+    /// what purchases in the period came to.
+    async fn find<'e, C>(
+        db: C,
+        org_pid: Uuid,
+        scope: Scope,
+        period: ProfitQuery,
+    ) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres> + Copy,
+    {
+        let (animal_pid, group_pid, animals) = match scope {
+            Scope::Herd => (None, None, None),
+            Scope::Animal(pid) => (Some(pid), None, Some(vec![pid])),
+            Scope::Group(pid, animals) => (None, Some(pid), Some(animals)),
+        };
+
+        let mut categories = sqlx::query_as::<_, CategoryTotal>(
+            "
+            SELECT category, SUM(amount) AS amount, COUNT(*) AS entries
+            FROM ledger_entries
+            WHERE organisation_pid = $1
+                AND ($2::UUID[] IS NULL OR animal_pid = ANY($2) OR group_pid = $3)
+                AND ($4::DATE IS NULL OR entry_date >= $4)
+                AND ($5::DATE IS NULL OR entry_date <= $5)
+            GROUP BY category",
+        )
+        .bind(org_pid)
+        .bind(animals.as_deref())
+        .bind(group_pid)
+        .bind(period.from)
+        .bind(period.to)
+        .fetch_all(db)
+        .await?;
+
+        let mut production_costs = sqlx::query_as::<_, ProductionCost>(
+            "
+            SELECT product_type, unit, SUM(quantity) AS quantity
+            FROM production_records
+            WHERE organisation_pid = $1 AND deleted_at IS NULL
+                AND ($2::UUID[] IS NULL OR animal_pid = ANY($2))
+                AND ($3::DATE IS NULL OR record_date >= $3)
+                AND ($4::DATE IS NULL OR record_date <= $4)
+            GROUP BY product_type, unit
+            ORDER BY product_type, unit",
+        )
+        .bind(org_pid)
+        .bind(animals.as_deref())
+        .bind(period.from)
+        .bind(period.to)
+        .fetch_all(db)
+        .await?;
+
+        let category = |total: &CategoryTotal| total.category.parse::<LedgerCategory>();
+        for total in &mut categories {
+            total.income = category(total)?.is_income();
+        }
+        categories.sort_by_key(|total| {
+            LedgerCategory::ALL
+                .iter()
+                .position(|category| category.as_str() == total.category)
+        });
+
+        let sum = |include: &dyn Fn(LedgerCategory) -> bool| {
+            categories
+                .iter()
+                .filter(|total| category(total).is_ok_and(include))
+                .map(|total| total.amount)
+                .sum::<Decimal>()
+        };
+        let income = sum(&|category| category.is_income());
+        let expenses = sum(&|category| !category.is_income());
+        let purchases = sum(&|category| category == LedgerCategory::Purchase);
+        let operating_costs = expenses - purchases;
+        let profit = income - expenses;
+
+        for cost in &mut production_costs {
+            cost.cost_per_unit =
+                (!cost.quantity.is_zero()).then(|| (operating_costs / cost.quantity).round_dp(2));
+        }
+
+        let purchase_price = (!purchases.is_zero()).then_some(purchases);
+
+        Ok(Self {
+            animal_pid,
+            group_pid,
+            from: period.from,
+            to: period.to,
+            income,
+            expenses,
+            profit,
+            operating_costs,
+            purchase_price,
+            return_on_purchase: return_on(profit, purchase_price),
+            categories,
+            production_costs,
+        })
+    }
+}
+
+impl AnimalProfit {
+    /// Every animal's profit and loss, the most profitable first.
+    pub async fn find_all<'e, C>(
+        db: C,
+        org_pid: Uuid,
+        period: ProfitQuery,
+    ) -> ModelResult<Vec<Self>>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        let mut animals = sqlx::query_as::<_, Self>(
+            "
+            SELECT
+                a.pid AS animal_pid,
+                a.tag_id,
+                a.name,
+                a.status,
+                a.purchase_price,
+                totals.income,
+                totals.expenses,
+                totals.income - totals.expenses AS profit
+            FROM animals a
+            JOIN LATERAL (
+                SELECT
+                    COALESCE(SUM(l.amount) FILTER (
+                        WHERE l.category IN ('sale_proceeds', 'milk_sales', 'other_income')
+                    ), 0) AS income,
+                    COALESCE(SUM(l.amount) FILTER (
+                        WHERE l.category NOT IN ('sale_proceeds', 'milk_sales', 'other_income')
+                    ), 0) AS expenses
+                FROM ledger_entries l
+                WHERE l.animal_pid = a.pid
+                    AND ($2::DATE IS NULL OR l.entry_date >= $2)
+                    AND ($3::DATE IS NULL OR l.entry_date <= $3)
+            ) totals ON TRUE
+            WHERE a.organisation_pid = $1 AND a.deleted_at IS NULL
+            ORDER BY profit DESC, a.tag_id",
+        )
+        .bind(org_pid)
+        .bind(period.from)
+        .bind(period.to)
+        .fetch_all(db)
+        .await?;
+
+        for animal in &mut animals {
+            animal.return_on_purchase = return_on(animal.profit, animal.purchase_price);
+        }
+
+        Ok(animals)
+    }
+}
@@ -0,0 +1,622 @@
+#![allow(clippy::missing_errors_doc)]
+
+use std::borrow::Cow;
+
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use sqlx::{Executor, PgConnection, Postgres, prelude::FromRow, types::Json};
+use uuid::Uuid;
+
+use super::{
+    ModelError, ModelResult,
+    animals::AnimalQuery,
+    dto::{
+        GroupMembers, GroupMove, GroupTreatment, GroupWeighing, NewGroup, UpdateGroup, Validator,
+        records::NewWeightRecord,
+    },
+    health::HealthRecord,
+    locations::{Location, Movement},
+    weight::WeightRecord,
+};
+
+/// A named group of animals, kept by hand or by a saved filter.
+#[derive(Debug, Deserialize, Serialize, FromRow, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AnimalGroup {
+    pub id: i32,
+    pub pid: Uuid,
+    pub organisation_pid: Uuid,
+    pub name: String,
+    pub kind: String,
+    pub filter: Option<Json<AnimalQuery>>,
+    pub notes: Option<String>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<FixedOffset>,
+    pub updated_at: DateTime<FixedOffset>,
+}
+
+/// An animal in a group now.
+#[derive(Debug, Deserialize, Serialize, FromRow, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupMember {
+    pub animal_pid: Uuid,
+    pub tag_id: String,
+    pub name: String,
+    pub status: String,
+    pub joined_at: DateTime<FixedOffset>,
+}
+
+/// A stay of an animal in a group. Members of a dynamic group have no
+/// `added_by` or `removed_by`: they join and leave as they match its filter.
+#[derive(Debug, Deserialize, Serialize, FromRow, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Membership {
+    pub animal_pid: Uuid,
+    pub tag_id: String,
+    pub joined_at: DateTime<FixedOffset>,
+    pub left_at: Option<DateTime<FixedOffset>>,
+    pub added_by_name: Option<String>,
+    pub removed_by_name: Option<String>,
+}
+
+const GROUPS_QUERY: &str = "
+    SELECT
+        id,
+        pid,
+        organisation_pid,
+        name,
+        kind,
+        filter,
+        notes,
+        created_by,
+        created_at,
+        updated_at
+    FROM
+        animal_groups
+    WHERE
+        organisation_pid = $1
+";
+
+/// The animals matching every filter of an `AnimalQuery`.
+const MATCHING_QUERY: &str = "
+    SELECT a.pid
+    FROM animals a
+    JOIN species s ON a.specie_id = s.id
+    JOIN breeds b ON a.breed_id = b.id
+    WHERE a.organisation_pid = $1
+        AND a.deleted_at IS NULL
+        AND ($2::TEXT IS NULL OR s.name ILIKE '%' || $2 || '%')
+        AND ($3::TEXT IS NULL OR b.name ILIKE '%' || $3 || '%')
+        AND ($4::DATE IS NULL OR a.purchase_date = $4)
+        AND ($5::UUID IS NULL OR a.parent_female_id = $5)
+        AND ($6::UUID IS NULL OR a.parent_male_id = $6)
+        AND ($7::UUID IS NULL OR a.location_id IN (
+            WITH RECURSIVE beneath AS (
+                SELECT id FROM locations WHERE pid = $7
+                UNION ALL
+                SELECT c.id FROM locations c JOIN beneath b ON c.parent_id = b.id
+            )
+            SELECT id FROM beneath
+        ))
+    ORDER BY a.tag_id
+";
+
+fn unique_name(error: sqlx::Error) -> ModelError {
+    match error {
+        sqlx::Error::Database(err) if err.constraint() == Some("animal_groups_name_idx") => {
+            ModelError::EntityAlreadyExists("A group with that name already exists".into())
+        }
+        error => ModelError::Sqlx(error),
+    }
+}
+
+/// A dynamic group's filter must narrow the animals down.
+fn check_filter(filter: Option<&AnimalQuery>) -> ModelResult<()> {
+    match filter {
+        Some(filter) if *filter == AnimalQuery::default() => Err(ModelError::Validation(
+            "A dynamic group's filter must filter on something".into(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+impl AnimalGroup {
+    pub async fn create(
+        db: &mut PgConnection,
+        org_pid: Uuid,
+        user_pid: Uuid,
+        params: &NewGroup<'_>,
+    ) -> ModelResult<Self> {
+        let validator = Validator::new(params);
+        let params = validator.validate()?;
+
+        match (params.kind.as_ref(), &params.filter, &params.tag_ids) {
+            ("static", Some(_), _) => {
+                return Err(ModelError::Validation(
+                    "A static group has no filter, its members are added by tag ID".into(),
+                ));
+            }
+            ("dynamic", None, _) => {
+                return Err(ModelError::Validation(
+                    "A dynamic group needs a filter".into(),
+                ));
+            }
+            ("dynamic", _, Some(_)) => {
+                return Err(ModelError::Validation(
+                    "Members of a dynamic group follow its filter".into(),
+                ));
+            }
+            _ => check_filter(params.filter.as_ref())?,
+        }
+
+        let group = sqlx::query_as::<_, Self>(
+            "
+            INSERT INTO animal_groups (organisation_pid, name, kind, filter, notes, created_by)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING *",
+        )
+        .bind(org_pid)
+        .bind(params.name.trim())
+        .bind(params.kind.as_ref())
+        .bind(params.filter.clone().map(Json))
+        .bind(params.notes.as_deref())
+        .bind(user_pid)
+        .fetch_one(&mut *db)
+        .await
+        .map_err(unique_name)?;
+
+        match &params.tag_ids {
+            Some(tag_ids) if !tag_ids.is_empty() => {
+                group.add(&mut *db, user_pid, tag_ids).await?;
+            }
+            _ => group.refresh(&mut *db).await?,
+        }
+
+        Ok(group)
+    }
+
+    pub async fn find_all<'e, C>(db: C, org_pid: Uuid) -> ModelResult<Vec<Self>>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        let query = format!("{GROUPS_QUERY} ORDER BY LOWER(name)");
+
+        sqlx::query_as::<_, Self>(&query)
+            .bind(org_pid)
+            .fetch_all(db)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn find_by_pid<'e, C>(db: C, org_pid: Uuid, pid: Uuid) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        let query = format!("{GROUPS_QUERY} AND pid = $2");
+
+        sqlx::query_as::<_, Self>(&query)
+            .bind(org_pid)
+            .bind(pid)
+            .fetch_optional(db)
+            .await?
+            .ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    pub async fn update(
+        db: &mut PgConnection,
+        org_pid: Uuid,
+        pid: Uuid,
+        params: &UpdateGroup<'_>,
+    ) -> ModelResult<Self> {
+        let validator = Validator::new(params);
+        let params = validator.validate()?;
+
+        let group = Self::find_by_pid(&mut *db, org_pid, pid).await?;
+        if group.kind == "static" && params.filter.is_some() {
+            return Err(ModelError::Validation(
+                "A static group has no filter, its members are added by tag ID".into(),
+            ));
+        }
+        check_filter(params.filter.as_ref())?;
+
+        let group = sqlx::query_as::<_, Self>(
+            "
+            UPDATE animal_groups SET
+                name = COALESCE($2, name),
+                filter = COALESCE($3, filter),
+                notes = COALESCE($4, notes)
+            WHERE id = $1
+            RETURNING *",
+        )
+        .bind(group.id)
+        .bind(params.name.as_deref().map(str::trim))
+        .bind(params.filter.clone().map(Json))
+        .bind(params.notes.as_deref())
+        .fetch_one(&mut *db)
+        .await
+        .map_err(unique_name)?;
+
+        group.refresh(&mut *db).await?;
+
+        Ok(group)
+    }
+
+    /// Deletes the group along with its membership history. The animals
+    /// are left as they are.
+    pub async fn delete_by_pid(db: &mut PgConnection, org_pid: Uuid, pid: Uuid) -> ModelResult<()> {
+        let deleted =
+            sqlx::query("DELETE FROM animal_groups WHERE pid = $1 AND organisation_pid = $2")
+                .bind(pid)
+                .bind(org_pid)
+                .execute(&mut *db)
+                .await?;
+
+        if deleted.rows_affected() == 0 {
+            return Err(ModelError::EntityNotFound);
+        }
+
+        Ok(())
+    }
+
+    /// Adds the animals tagged to a static group. Animals in it already
+    /// are left as they are.
+    pub async fn add_members(
+        db: &mut PgConnection,
+        org_pid: Uuid,
+        user_pid: Uuid,
+        pid: Uuid,
+        params: &GroupMembers<'_>,
+    ) -> ModelResult<Vec<GroupMember>> {
+        let validator = Validator::new(params);
+        let params = validator.validate()?;
+
+        let group = Self::find_by_pid(&mut *db, org_pid, pid).await?;
+        group.ensure_static()?;
+        group.add(&mut *db, user_pid, &params.tag_ids).await?;
+
+        group.members(&mut *db).await
+    }
+
+    /// Takes the animals tagged out of a static group, keeping their stay
+    /// in its history.
+    pub async fn remove_members(
+        db: &mut PgConnection,
+        org_pid: Uuid,
+        user_pid: Uuid,
+        pid: Uuid,
+        params: &GroupMembers<'_>,
+    ) -> ModelResult<Vec<GroupMember>> {
+        let validator = Validator::new(params);
+        let params = validator.validate()?;
+
+        let group = Self::find_by_pid(&mut *db, org_pid, pid).await?;
+        group.ensure_static()?;
+        let animals = group.resolve_tags(&mut *db, &params.tag_ids).await?;
+
+        sqlx::query(
+            "
+            UPDATE animal_group_members SET left_at = NOW(), removed_by = $3
+            WHERE group_id = $1 AND animal_pid = ANY($2) AND left_at IS NULL",
+        )
+        .bind(group.id)
+        .bind(&animals)
+        .bind(user_pid)
+        .execute(&mut *db)
+        .await?;
+
+        group.members(&mut *db).await
+    }
+
+    /// The group's members now. A dynamic group's membership is brought up
+    /// to date with its filter first.
+    pub async fn find_members(
+        db: &mut PgConnection,
+        org_pid: Uuid,
+        pid: Uuid,
+    ) -> ModelResult<Vec<GroupMember>> {
+        let group = Self::find_by_pid(&mut *db, org_pid, pid).await?;
+        group.refresh(&mut *db).await?;
+
+        group.members(&mut *db).await
+    }
+
+    /// Every stay of an animal in the group, the latest joiners first.
+    pub async fn find_history<'e, C>(
+        db: C,
+        org_pid: Uuid,
+        pid: Uuid,
+    ) -> ModelResult<Vec<Membership>>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_as::<_, Membership>(
+            "
+            SELECT
+                m.animal_pid,
+                a.tag_id,
+                m.joined_at,
+                m.left_at,
+                NULLIF(CONCAT_WS(' ', ad.first_name, ad.last_name), '') AS added_by_name,
+                NULLIF(CONCAT_WS(' ', rm.first_name, rm.last_name), '') AS removed_by_name
+            FROM animal_group_members m
+            JOIN animal_groups g ON m.group_id = g.id
+            JOIN animals a ON m.animal_pid = a.pid
+            LEFT JOIN users ad ON m.added_by = ad.pid
+            LEFT JOIN users rm ON m.removed_by = rm.pid
+            WHERE g.organisation_pid = $1 AND g.pid = $2
+            ORDER BY m.joined_at DESC, a.tag_id, m.id DESC",
+        )
+        .bind(org_pid)
+        .bind(pid)
+        .fetch_all(db)
+        .await
+        .map_err(Into::into)
+    }
+
+    /// The pids of the group's members now, without recording a dynamic
+    /// group's changes.
+    pub async fn animal_pids<'e, C>(&self, db: C) -> ModelResult<Vec<Uuid>>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        match &self.filter {
+            Some(Json(filter)) => {
+                sqlx::query_scalar::<_, Uuid>(MATCHING_QUERY)
+                    .bind(self.organisation_pid)
+                    .bind(filter.specie.as_deref())
+                    .bind(filter.breed.as_deref())
+                    .bind(filter.purchase_date)
+                    .bind(filter.female_parent)
+                    .bind(filter.male_parent)
+                    .bind(filter.location)
+                    .fetch_all(db)
+                    .await
+            }
+            None => {
+                sqlx::query_scalar::<_, Uuid>(
+                    "
+                    SELECT a.pid
+                    FROM animal_group_members m
+                    JOIN animals a ON m.animal_pid = a.pid
+                    WHERE m.group_id = $1 AND m.left_at IS NULL AND a.deleted_at IS NULL
+                    ORDER BY a.tag_id",
+                )
+                .bind(self.id)
+                .fetch_all(db)
+                .await
+            }
+        }
+        .map_err(Into::into)
+    }
+
+    /// Weighs members of the group, each classified against its previous
+    /// weighing.
+    pub async fn weigh(
+        db: &mut PgConnection,
+        org_pid: Uuid,
+        user_pid: Uuid,
+        pid: Uuid,
+        params: &GroupWeighing<'_>,
+    ) -> ModelResult<Vec<WeightRecord>> {
+        let validator = Validator::new(params);
+        let params = validator.validate()?;
+
+        let group = Self::find_by_pid(&mut *db, org_pid, pid).await?;
+        let members = group.tag_ids(&mut *db).await?;
+
+        let outsiders = params
+            .weights
+            .iter()
+            .map(|weight| weight.tag_id.trim().to_uppercase())
+            .filter(|tag_id| !members.contains(tag_id))
+            .collect::<Vec<_>>();
+        if !outsiders.is_empty() {
+            return Err(ModelError::Validation(format!(
+                "Not in {}: {}",
+                group.name,
+                outsiders.join(", ")
+            )));
+        }
+
+        let mut records = Vec::with_capacity(params.weights.len());
+        for weight in &params.weights {
+            let record = WeightRecord::weigh(
+                &mut *db,
+                NewWeightRecord {
+                    tag_id: weight.tag_id.trim().to_uppercase().into(),
+                    record_date: params.record_date,
+                    mass: weight.mass,
+                    unit: params.unit.clone(),
+                    status: None,
+                    notes: params.notes.clone(),
+                },
+                org_pid,
+                user_pid,
+            )
+            .await?;
+            records.push(record);
+        }
+
+        Ok(records)
+    }
+
+    /// Records the treatment for every member of the group.
+    pub async fn treat(
+        db: &mut PgConnection,
+        org_pid: Uuid,
+        user_pid: Uuid,
+        pid: Uuid,
+        params: &GroupTreatment<'_>,
+    ) -> ModelResult<Vec<HealthRecord>> {
+        let group = Self::find_by_pid(&mut *db, org_pid, pid).await?;
+        let members = group.occupied_tag_ids(&mut *db).await?;
+
+        let mut records = Vec::with_capacity(members.len());
+        for tag_id in &members {
+            let record =
+                HealthRecord::create(&mut *db, &params.for_animal(tag_id), org_pid, user_pid)
+                    .await?;
+            records.push(record);
+        }
+
+        Ok(records)
+    }
+
+    /// Moves every member of the group, as one batch.
+    pub async fn move_to(
+        db: &mut PgConnection,
+        org_pid: Uuid,
+        user_pid: Uuid,
+        pid: Uuid,
+        params: &GroupMove<'_>,
+    ) -> ModelResult<Vec<Movement>> {
+        let group = Self::find_by_pid(&mut *db, org_pid, pid).await?;
+        let members = group.occupied_tag_ids(&mut *db).await?;
+
+        Location::move_animals(&mut *db, org_pid, user_pid, &params.for_animals(members)).await
+    }
+
+    fn ensure_static(&self) -> ModelResult<()> {
+        if self.kind == "dynamic" {
+            return Err(ModelError::Validation(
+                "Members of a dynamic group follow its filter".into(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Records the animals that started or stopped matching a dynamic
+    /// group's filter.
+    async fn refresh(&self, db: &mut PgConnection) -> ModelResult<()> {
+        if self.filter.is_none() {
+            return Ok(());
+        }
+
+        let matching = self.animal_pids(&mut *db).await?;
+
+        sqlx::query(
+            "
+            UPDATE animal_group_members SET left_at = NOW()
+            WHERE group_id = $1 AND left_at IS NULL AND animal_pid <> ALL($2)",
+        )
+        .bind(self.id)
+        .bind(&matching)
+        .execute(&mut *db)
+        .await?;
+
+        sqlx::query(
+            "
+            INSERT INTO animal_group_members (organisation_pid, group_id, animal_pid)
+            SELECT $1, $2, matching.pid FROM UNNEST($3::UUID[]) AS matching (pid)
+            ON CONFLICT (group_id, animal_pid) WHERE left_at IS NULL DO NOTHING",
+        )
+        .bind(self.organisation_pid)
+        .bind(self.id)
+        .bind(&matching)
+        .execute(&mut *db)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn add(
+        &self,
+        db: &mut PgConnection,
+        user_pid: Uuid,
+        tag_ids: &[Cow<'_, str>],
+    ) -> ModelResult<()> {
+        let animals = self.resolve_tags(&mut *db, tag_ids).await?;
+
+        sqlx::query(
+            "
+            INSERT INTO animal_group_members (organisation_pid, group_id, animal_pid, added_by)
+            SELECT $1, $2, joining.pid, $4 FROM UNNEST($3::UUID[]) AS joining (pid)
+            ON CONFLICT (group_id, animal_pid) WHERE left_at IS NULL DO NOTHING",
+        )
+        .bind(self.organisation_pid)
+        .bind(self.id)
+        .bind(&animals)
+        .bind(user_pid)
+        .execute(&mut *db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// The pids of the animals tagged, failing on tags no animal has.
+    async fn resolve_tags(
+        &self,
+        db: &mut PgConnection,
+        tag_ids: &[Cow<'_, str>],
+    ) -> ModelResult<Vec<Uuid>> {
+        let tag_ids = tag_ids
+            .iter()
+            .map(|tag_id| tag_id.trim().to_uppercase())
+            .collect::<Vec<_>>();
+
+        let animals = sqlx::query_as::<_, (Uuid, String)>(
+            "
+            SELECT pid, tag_id FROM animals
+            WHERE organisation_pid = $1 AND tag_id = ANY($2) AND deleted_at IS NULL",
+        )
+        .bind(self.organisation_pid)
+        .bind(&tag_ids)
+        .fetch_all(&mut *db)
+        .await?;
+
+        let unknown = tag_ids
+            .iter()
+            .filter(|tag_id| !animals.iter().any(|(_, found)| found == *tag_id))
+            .cloned()
+            .collect::<Vec<_>>();
+        if !unknown.is_empty() {
+            return Err(ModelError::Validation(format!(
+                "No animal is tagged {}",
+                unknown.join(", ")
+            )));
+        }
+
+        Ok(animals.into_iter().map(|(pid, _)| pid).collect())
+    }
+
+    async fn members(&self, db: &mut PgConnection) -> ModelResult<Vec<GroupMember>> {
+        sqlx::query_as::<_, GroupMember>(
+            "
+            SELECT a.pid AS animal_pid, a.tag_id, a.name, a.status, m.joined_at
+            FROM animal_group_members m
+            JOIN animals a ON m.animal_pid = a.pid
+            WHERE m.group_id = $1 AND m.left_at IS NULL AND a.deleted_at IS NULL
+            ORDER BY a.tag_id",
+        )
+        .bind(self.id)
+        .fetch_all(&mut *db)
+        .await
+        .map_err(Into::into)
+    }
+
+    /// The tags of the members now, recording a dynamic group's changes.
+    pub(crate) async fn tag_ids(&self, db: &mut PgConnection) -> ModelResult<Vec<String>> {
+        self.refresh(&mut *db).await?;
+
+        Ok(self
+            .members(&mut *db)
+            .await?
+            .into_iter()
+            .map(|member| member.tag_id)
+            .collect())
+    }
+
+    /// Like `tag_ids`, but failing when the group has no members.
+    async fn occupied_tag_ids(&self, db: &mut PgConnection) -> ModelResult<Vec<String>> {
+        let tag_ids = self.tag_ids(&mut *db).await?;
+        if tag_ids.is_empty() {
+            return Err(ModelError::Validation(format!(
+                "{} has no members",
+                self.name
+            )));
+        }
+
+        Ok(tag_ids)
+    }
+}
@@ -5,7 +5,11 @@ use std::{borrow::Cow, str::FromStr};
 use chrono::{DateTime, FixedOffset, NaiveDate};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use sqlx::{Encode, Executor, Postgres, postgres::PgQueryResult, prelude::FromRow};
+use sqlx::{
+    Encode, Executor, Postgres,
+    postgres::{PgConnection, PgQueryResult},
+    prelude::FromRow,
+};
 use uuid::Uuid;
 
 use crate::seed::Seedable;
@@ -13,6 +17,8 @@ use crate::seed::Seedable;
 use super::{
     ModelError, ModelResult,
     dto::records::{NewHealthRecord, UpdateHealthRecord},
+    events::{DomainEvent, EventType},
+    trash::ensure_animal_restored,
 };
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -116,6 +122,8 @@ const FETCH_QUERY: &str = "
         users u ON hr.created_by = u.pid
     WHERE
         hr.organisation_pid = $1
+        AND hr.deleted_at IS NULL
+        AND a.deleted_at IS NULL
 ";
 
 fn fetch_query(conditions: &str) -> String {
@@ -123,15 +131,14 @@ fn fetch_query(conditions: &str) -> String {
 }
 
 impl HealthRecord {
-    pub async fn create<'a, C>(
-        db: C,
+    /// Creates the record, emitting `health_record.high_severity` for high
+    /// severity ones.
+    pub async fn create(
+        db: &mut PgConnection,
         params: &NewHealthRecord<'_>,
         org_pid: Uuid,
         user_pid: Uuid,
-    ) -> ModelResult<Self>
-    where
-        C: Executor<'a, Database = Postgres>,
-    {
+    ) -> ModelResult<Self> {
         let record_date = NaiveDate::from_str(&params.record_date)?;
 
         let record = sqlx::query_as::<_, Self>(
@@ -152,7 +159,7 @@ impl HealthRecord {
                     notes
             )
             VALUES (
-                    (SELECT pid FROM animals WHERE tag_id = $1 AND organisation_pid = $2),
+                    (SELECT pid FROM animals WHERE tag_id = $1 AND organisation_pid = $2 AND deleted_at IS NULL),
                     $2,
                     $3,
                     $4,
@@ -183,9 +190,19 @@ impl HealthRecord {
         .bind(params.cost.map(|cost| Decimal::new(cost, 2)))
         .bind(params.performed_by.as_deref())
         .bind(params.notes.as_deref())
-        .fetch_one(db)
+        .fetch_one(&mut *db)
         .await?;
 
+        if record.severity == "high" {
+            DomainEvent::emit(
+                &mut *db,
+                org_pid,
+                EventType::HighSeverityHealthRecord,
+                &record,
+            )
+            .await?;
+        }
+
         Ok(record)
     }
 
@@ -219,12 +236,12 @@ impl HealthRecord {
     }
 
     pub async fn find_by_id<'e, C>(
-        db: &C,
+        db: C,
         id: i32,
         org_pid: Uuid,
     ) -> ModelResult<HealthRecordResponse>
     where
-        for<'a> &'a C: Executor<'e, Database = Postgres>,
+        C: Executor<'e, Database = Postgres>,
     {
         let record = sqlx::query_as::<_, HealthRecordResponse>(Box::leak(
             fetch_query("AND hr.id = $2").into_boxed_str(),
@@ -238,11 +255,11 @@ impl HealthRecord {
     }
 
     pub async fn find_recent_activities<'e, C>(
-        db: &C,
+        db: C,
         org_pid: Uuid,
     ) -> ModelResult<Vec<HealthRecordResponse>>
     where
-        for<'a> &'a C: Executor<'e, Database = Postgres>,
+        C: Executor<'e, Database = Postgres>,
     {
         let query = sqlx::query_as::<_, HealthRecordResponse>(Box::leak(
             fetch_query("ORDER BY hr.created_at DESC LIMIT 20").into_boxed_str(),
@@ -328,28 +345,49 @@ impl HealthRecord {
         .map_err(Into::into)
     }
 
-    pub async fn delete_by_id<'e, C>(db: C, org_pid: Uuid, id: i32) -> ModelResult<PgQueryResult>
+    pub async fn delete_by_id<'e, C>(
+        db: C,
+        org_pid: Uuid,
+        id: i32,
+        user_pid: Uuid,
+    ) -> ModelResult<PgQueryResult>
     where
         C: Executor<'e, Database = Postgres>,
     {
-        sqlx::query("DELETE FROM health_records WHERE id = $1 AND organisation_pid = $2")
-            .bind(id)
-            .bind(org_pid)
-            .execute(db)
-            .await
-            .map_err(Into::into)
+        sqlx::query(
+            "UPDATE health_records SET deleted_at = NOW(), deleted_by = $3
+            WHERE id = $1 AND organisation_pid = $2 AND deleted_at IS NULL",
+        )
+        .bind(id)
+        .bind(org_pid)
+        .bind(user_pid)
+        .execute(db)
+        .await
+        .map_err(Into::into)
     }
 
-    pub async fn update_by_id<'e, C>(
-        db: &C,
+    pub async fn restore_by_id(db: &mut PgConnection, org_pid: Uuid, id: i32) -> ModelResult<Self> {
+        ensure_animal_restored(&mut *db, "health_records", org_pid, id).await?;
+
+        sqlx::query_as::<_, Self>(
+            "UPDATE health_records SET deleted_at = NULL, deleted_by = NULL
+            WHERE id = $1 AND organisation_pid = $2
+            RETURNING *",
+        )
+        .bind(id)
+        .bind(org_pid)
+        .fetch_one(&mut *db)
+        .await
+        .map_err(Into::into)
+    }
+
+    pub async fn update_by_id(
+        db: &mut PgConnection,
         id: i32,
         org_pid: Uuid,
         params: &UpdateHealthRecord<'_>,
-    ) -> ModelResult<Self>
-    where
-        for<'a> &'a C: Executor<'e, Database = Postgres>,
-    {
-        let model = Self::find_by_id(db, id, org_pid).await?;
+    ) -> ModelResult<Self> {
+        let model = Self::find_by_id(&mut *db, id, org_pid).await?;
         // Set values to be updated
         let condition = params
             .condition
@@ -413,7 +451,7 @@ impl HealthRecord {
                             prognosis = $13,
                             cost = $14
                     WHERE
-                        id = $1 AND organisation_pid = $2
+                        id = $1 AND organisation_pid = $2 AND deleted_at IS NULL
                     RETURNING *
                 ",
         )
@@ -431,7 +469,7 @@ impl HealthRecord {
         .bind(record_date)
         .bind(prognosis)
         .bind(cost)
-        .fetch_one(db)
+        .fetch_one(&mut *db)
         .await?;
 
         Ok(updated)
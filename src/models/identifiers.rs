@@ -0,0 +1,411 @@
+#![allow(clippy::missing_errors_doc)]
+
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use sqlx::{Executor, PgConnection, Postgres, prelude::FromRow};
+use uuid::Uuid;
+
+use super::{
+    ModelError, ModelResult,
+    animals::{Animal, AnimalResponse},
+    dto::{NewIdentifier, RemoveIdentifier, Validator},
+};
+
+/// The largest national ID the 38 bits of an ISO 11784 code hold.
+const MAX_NATIONAL_ID: u64 = (1 << 38) - 1;
+
+/// An identifier an animal carries or once carried. Removed identifiers
+/// still resolve to the animal.
+#[derive(Debug, Deserialize, Serialize, FromRow, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AnimalIdentifier {
+    pub pid: Uuid,
+    pub animal_pid: Uuid,
+    pub kind: String,
+    pub value: String,
+    pub attached_at: DateTime<FixedOffset>,
+    pub removed_at: Option<DateTime<FixedOffset>>,
+    pub removal_reason: Option<String>,
+    pub added_by: Option<Uuid>,
+    pub removed_by: Option<Uuid>,
+}
+
+/// The animal a scan resolved to, and the identifier it matched.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanMatch {
+    pub identifier: AnimalIdentifier,
+    pub animal: AnimalResponse,
+}
+
+const IDENTIFIERS_QUERY: &str = "
+    SELECT
+        ai.pid,
+        ai.animal_pid,
+        ai.kind,
+        ai.value,
+        ai.attached_at,
+        ai.removed_at,
+        ai.removal_reason,
+        ai.added_by,
+        ai.removed_by
+    FROM
+        animal_identifiers ai
+    WHERE
+        ai.organisation_pid = $1
+";
+
+/// The identifier a value resolves to among the organisation's animals:
+/// current identifiers before removed ones, visual tags first, then the most
+/// recently removed. Brands and tattoos are matched whatever their case.
+pub(crate) const RESOLVE_QUERY: &str = "
+    SELECT ai.pid
+    FROM animal_identifiers ai
+    JOIN animals r ON r.pid = ai.animal_pid AND r.deleted_at IS NULL
+    WHERE ai.organisation_pid = $1
+        AND (ai.value = $2 OR (ai.kind IN ('brand', 'tattoo') AND ai.value = UPPER($2)))
+    ORDER BY ai.removed_at IS NOT NULL, ai.kind <> 'visual', ai.removed_at DESC
+    LIMIT 1
+";
+
+/// Reads an ISO 11784/11785 number the way readers send it, as the canonical
+/// 15 digits: the 3 digit country or manufacturer code and the 12 digit
+/// national ID.
+///
+/// Accepts the decimal form, with or without separators
+/// (`982 000123456789`), the hexadecimal form (`3D6.00075BCD15`) and an FDX-B
+/// telegram of the 8 code bytes and their CRC as received, in 20 hex digits,
+/// each optionally behind an `FDX-B` or `HDX` label. `None` when the scan has
+/// none of those shapes.
+#[must_use]
+pub fn read_rfid(raw: &str) -> Option<ModelResult<String>> {
+    let raw = strip_label(raw.trim());
+
+    let digits = raw
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '-' | '_'))
+        .collect::<String>();
+    if digits.len() == 15 && digits.chars().all(|c| c.is_ascii_digit()) {
+        let country = digits[..3].parse().ok()?;
+        let national = digits[3..].parse().ok()?;
+        return Some(canonical_rfid(country, national));
+    }
+
+    if let Some((country, national)) = raw.split_once('.')
+        && country.len() == 3
+        && national.len() == 10
+    {
+        let country = u64::from_str_radix(country, 16).ok()?;
+        let national = u64::from_str_radix(national, 16).ok()?;
+        return Some(canonical_rfid(country, national));
+    }
+
+    if raw.len() == 20 && raw.chars().all(|c| c.is_ascii_hexdigit()) {
+        let bytes = (0..10)
+            .map(|i| u8::from_str_radix(&raw[i * 2..i * 2 + 2], 16))
+            .collect::<Result<Vec<_>, _>>()
+            .ok()?;
+        return Some(read_telegram(&bytes));
+    }
+
+    None
+}
+
+/// Strips the protocol label some readers put in front of a scan.
+fn strip_label(raw: &str) -> &str {
+    for label in ["FDX-B", "FDXB", "FDX", "HDX"] {
+        if raw.len() > label.len()
+            && raw.is_char_boundary(label.len())
+            && raw[..label.len()].eq_ignore_ascii_case(label)
+        {
+            return raw[label.len()..].trim_start_matches([' ', ':', '-']);
+        }
+    }
+
+    raw
+}
+
+/// An FDX-B telegram is sent least significant bit first: 38 bits of national
+/// ID, 10 of country code, the data block flag, 14 reserved bits and the
+/// animal flag, then a CRC-16/CCITT of the 8 code bytes.
+fn read_telegram(bytes: &[u8]) -> ModelResult<String> {
+    let (code, crc) = bytes.split_at(8);
+
+    if crc16(code).to_le_bytes() != crc {
+        return Err(ModelError::Validation(
+            "RFID telegram failed its checksum, scan the animal again".into(),
+        ));
+    }
+
+    let mut word = [0; 8];
+    word.copy_from_slice(code);
+    let code = u64::from_le_bytes(word);
+
+    canonical_rfid((code >> 38) & 0x3FF, code & MAX_NATIONAL_ID)
+}
+
+/// The CRC-16/CCITT ISO 11785 protects codes with, over bits taken least
+/// significant first.
+fn crc16(bytes: &[u8]) -> u16 {
+    bytes.iter().fold(0, |crc, byte| {
+        (0..8).fold(crc ^ u16::from(*byte), |crc, _| {
+            if crc & 1 == 1 {
+                (crc >> 1) ^ 0x8408
+            } else {
+                crc >> 1
+            }
+        })
+    })
+}
+
+fn canonical_rfid(country: u64, national: u64) -> ModelResult<String> {
+    if !(1..=999).contains(&country) {
+        return Err(ModelError::Validation(
+            "RFID country or manufacturer code must be 001 to 999".into(),
+        ));
+    }
+
+    if national > MAX_NATIONAL_ID {
+        return Err(ModelError::Validation(format!(
+            "RFID national ID must not exceed {MAX_NATIONAL_ID}"
+        )));
+    }
+
+    Ok(format!("{country:03}{national:012}"))
+}
+
+/// The value an identifier of the kind is stored as. Visual tags are checked
+/// against the organisation's tag ID rules by the caller.
+pub fn normalise(kind: &str, value: &str) -> ModelResult<String> {
+    let value = value.trim();
+
+    match kind {
+        "rfid" => read_rfid(value).unwrap_or_else(|| {
+            Err(ModelError::Validation(
+                "RFID must be 15 digits: a 3 digit country or manufacturer code and a 12 digit national ID"
+                    .into(),
+            ))
+        }),
+        "brand" => {
+            let brand = value.to_uppercase();
+            if brand.is_empty()
+                || brand.chars().count() > 20
+                || !brand
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, ' ' | '-' | '/'))
+            {
+                return Err(ModelError::Validation(
+                    "Brand must have 1 to 20 letters, digits, spaces, dashes and slashes"
+                        .into(),
+                ));
+            }
+            Ok(brand)
+        }
+        "tattoo" => {
+            let tattoo = value.to_uppercase();
+            if tattoo.is_empty()
+                || tattoo.len() > 12
+                || !tattoo.chars().all(|c| c.is_ascii_alphanumeric()) {
+                return Err(ModelError::Validation(
+                    "Tattoo must have 1 to 12 letters and digits".into(),
+                ));
+            }
+            Ok(tattoo)
+        }
+        _ => Ok(value.to_string()),
+    }
+}
+
+fn unique_value(error: sqlx::Error) -> ModelError {
+    match error {
+        sqlx::Error::Database(err)
+            if err.constraint() == Some("animal_identifiers_value_idx")
+                || err.constraint() == Some("animals_organisation_pid_tag_id_key") =>
+        {
+            ModelError::EntityAlreadyExists("Another animal carries that identifier".into())
+        }
+        error => ModelError::Sqlx(error),
+    }
+}
+
+impl AnimalIdentifier {
+    /// Every identifier of the animal, current ones first.
+    pub async fn find_by_animal<'e, C>(
+        db: C,
+        org_pid: Uuid,
+        animal_pid: Uuid,
+    ) -> ModelResult<Vec<Self>>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        let query = format!(
+            "{IDENTIFIERS_QUERY} AND ai.animal_pid = $2
+            ORDER BY ai.removed_at IS NOT NULL, ai.kind, ai.attached_at DESC, ai.id DESC"
+        );
+
+        sqlx::query_as::<_, Self>(&query)
+            .bind(org_pid)
+            .bind(animal_pid)
+            .fetch_all(db)
+            .await
+            .map_err(Into::into)
+    }
+
+    /// Attaches the identifier, removing the animal's current one of its kind.
+    /// A visual tag becomes the animal's tag ID.
+    pub async fn attach(
+        db: &mut PgConnection,
+        org_pid: Uuid,
+        user_pid: Uuid,
+        animal_pid: Uuid,
+        params: &NewIdentifier<'_>,
+    ) -> ModelResult<Self> {
+        Validator::new(params).validate()?;
+        let animal = Animal::find_by_id(&mut *db, org_pid, animal_pid).await?;
+        let value = normalise(&params.kind, &params.value)?;
+
+        sqlx::query(
+            "UPDATE animal_identifiers
+            SET removed_at = NOW(), removed_by = $4, removal_reason = $5
+            WHERE organisation_pid = $1 AND animal_pid = $2 AND kind = $3 AND removed_at IS NULL",
+        )
+        .bind(org_pid)
+        .bind(animal.pid)
+        .bind(params.kind.as_ref())
+        .bind(user_pid)
+        .bind(params.reason.as_deref().unwrap_or("replaced"))
+        .execute(&mut *db)
+        .await?;
+
+        if params.kind == "visual" {
+            // Tracking the tag ID registers it.
+            sqlx::query("UPDATE animals SET tag_id = $3 WHERE organisation_pid = $1 AND pid = $2")
+                .bind(org_pid)
+                .bind(animal.pid)
+                .bind(&value)
+                .execute(&mut *db)
+                .await
+                .map_err(unique_value)?;
+
+            let query = format!(
+                "{IDENTIFIERS_QUERY} AND ai.animal_pid = $2 AND ai.kind = 'visual'
+                AND ai.removed_at IS NULL"
+            );
+
+            return sqlx::query_as::<_, Self>(&query)
+                .bind(org_pid)
+                .bind(animal.pid)
+                .fetch_one(&mut *db)
+                .await
+                .map_err(Into::into);
+        }
+
+        sqlx::query_as::<_, Self>(
+            "INSERT INTO animal_identifiers (organisation_pid, animal_pid, kind, value, added_by)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING pid, animal_pid, kind, value, attached_at, removed_at, removal_reason,
+                added_by, removed_by",
+        )
+        .bind(org_pid)
+        .bind(animal.pid)
+        .bind(params.kind.as_ref())
+        .bind(&value)
+        .bind(user_pid)
+        .fetch_one(&mut *db)
+        .await
+        .map_err(unique_value)
+    }
+
+    /// Takes an identifier off the animal. Visual tags can only be replaced,
+    /// as every animal has a tag ID.
+    pub async fn remove(
+        db: &mut PgConnection,
+        org_pid: Uuid,
+        user_pid: Uuid,
+        animal_pid: Uuid,
+        pid: Uuid,
+        params: &RemoveIdentifier,
+    ) -> ModelResult<Self> {
+        let query =
+            format!("{IDENTIFIERS_QUERY} AND ai.animal_pid = $2 AND ai.pid = $3 FOR UPDATE");
+
+        let identifier = sqlx::query_as::<_, Self>(&query)
+            .bind(org_pid)
+            .bind(animal_pid)
+            .bind(pid)
+            .fetch_optional(&mut *db)
+            .await?
+            .ok_or(ModelError::EntityNotFound)?;
+
+        if identifier.removed_at.is_some() {
+            return Err(ModelError::Conflict(
+                "The identifier has already been removed".into(),
+            ));
+        }
+
+        if identifier.kind == "visual" {
+            return Err(ModelError::Validation(
+                "A visual tag can only be replaced".into(),
+            ));
+        }
+
+        sqlx::query_as::<_, Self>(
+            "UPDATE animal_identifiers
+            SET removed_at = NOW(), removed_by = $3, removal_reason = $4
+            WHERE organisation_pid = $1 AND pid = $2
+            RETURNING pid, animal_pid, kind, value, attached_at, removed_at, removal_reason,
+                added_by, removed_by",
+        )
+        .bind(org_pid)
+        .bind(pid)
+        .bind(user_pid)
+        .bind(params.reason.as_deref().unwrap_or("removed"))
+        .fetch_one(&mut *db)
+        .await
+        .map_err(Into::into)
+    }
+
+    /// Finds the animal a reader's scan identifies, by any identifier it
+    /// carries or once carried. A scan shaped like an RFID number is read as
+    /// one first.
+    pub async fn scan(db: &mut PgConnection, org_pid: Uuid, raw: &str) -> ModelResult<ScanMatch> {
+        let raw = raw.trim();
+        let rfid = read_rfid(raw);
+
+        if let Some(Ok(value)) = &rfid
+            && let Some(found) = Self::resolve(&mut *db, org_pid, value).await?
+        {
+            return Ok(found);
+        }
+
+        if let Some(found) = Self::resolve(&mut *db, org_pid, raw).await? {
+            return Ok(found);
+        }
+
+        match rfid {
+            Some(Err(err)) => Err(err),
+            _ => Err(ModelError::EntityNotFound),
+        }
+    }
+
+    async fn resolve(
+        db: &mut PgConnection,
+        org_pid: Uuid,
+        value: &str,
+    ) -> ModelResult<Option<ScanMatch>> {
+        let query = format!("{IDENTIFIERS_QUERY} AND ai.pid = ({RESOLVE_QUERY})");
+
+        let Some(identifier) = sqlx::query_as::<_, Self>(&query)
+            .bind(org_pid)
+            .bind(value)
+            .fetch_optional(&mut *db)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let animal = Animal::find_by_id(&mut *db, org_pid, identifier.animal_pid).await?;
+
+        Ok(Some(ScanMatch { identifier, animal }))
+    }
+}
@@ -0,0 +1,214 @@
+#![allow(clippy::missing_errors_doc)]
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::{DateTime, FixedOffset};
+use rand::{Rng, distr::Alphanumeric};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{Executor, PgConnection, Postgres, prelude::FromRow};
+use uuid::Uuid;
+
+use super::{ModelError, ModelResult, entitlements::Entitlement, orgs::Organisation, users::User};
+use crate::{config::OidcConfig, oidc::IdTokenClaims};
+
+/// A login sent to the identity provider, kept until the provider sends the
+/// person back.
+#[derive(Debug, Deserialize, Serialize, FromRow, Clone)]
+pub struct OidcLogin {
+    pub id: i32,
+    pub state: String,
+    pub nonce: String,
+    pub code_verifier: String,
+    pub created_at: DateTime<FixedOffset>,
+}
+
+/// An account at an identity provider and the user it logs in as.
+#[derive(Debug, Deserialize, Serialize, FromRow, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UserIdentity {
+    pub id: i32,
+    pub issuer: String,
+    pub subject: String,
+    pub user_pid: Uuid,
+    pub email: Option<String>,
+    pub last_login: Option<DateTime<FixedOffset>>,
+    pub created_at: DateTime<FixedOffset>,
+    pub updated_at: DateTime<FixedOffset>,
+}
+
+fn random_string(len: usize) -> String {
+    rand::rng()
+        .sample_iter(&Alphanumeric)
+        .take(len)
+        .map(char::from)
+        .collect()
+}
+
+impl OidcLogin {
+    /// Starts a login with a fresh state, nonce and PKCE verifier. Logins
+    /// abandoned at the provider are cleared out on the way.
+    pub async fn create<'e, C>(db: C) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_as::<_, Self>(
+            "
+            WITH stale AS (
+                DELETE FROM oidc_logins WHERE created_at < NOW() - INTERVAL '10 minutes'
+            )
+            INSERT INTO oidc_logins (state, nonce, code_verifier)
+            VALUES ($1, $2, $3) RETURNING *",
+        )
+        .bind(random_string(32))
+        .bind(random_string(32))
+        .bind(random_string(64))
+        .fetch_one(db)
+        .await
+        .map_err(Into::into)
+    }
+
+    /// Ends the login `state` belongs to. Each state can be used once, and
+    /// only for ten minutes.
+    pub async fn take<'e, C>(db: C, state: &str) -> ModelResult<Option<Self>>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_as::<_, Self>(
+            "
+            DELETE FROM oidc_logins WHERE state = $1
+            RETURNING *",
+        )
+        .bind(state)
+        .fetch_optional(db)
+        .await
+        .map(|login| {
+            login.filter(|login| {
+                login.created_at > chrono::Utc::now() - chrono::Duration::minutes(10)
+            })
+        })
+        .map_err(Into::into)
+    }
+
+    /// The S256 challenge the provider checks the verifier against.
+    #[must_use]
+    pub fn code_challenge(&self) -> String {
+        URL_SAFE_NO_PAD.encode(Sha256::digest(self.code_verifier.as_bytes()))
+    }
+}
+
+impl UserIdentity {
+    pub async fn find<'e, C>(db: C, issuer: &str, subject: &str) -> ModelResult<Option<Self>>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_as::<_, Self>(
+            "SELECT * FROM user_identities WHERE issuer = $1 AND subject = $2",
+        )
+        .bind(issuer)
+        .bind(subject)
+        .fetch_optional(db)
+        .await
+        .map_err(Into::into)
+    }
+
+    /// Links the provider's account to `user_pid`, or records another login
+    /// with it.
+    async fn link<'e, C>(db: C, claims: &IdTokenClaims, user_pid: Uuid) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_as::<_, Self>(
+            "
+            INSERT INTO user_identities (issuer, subject, user_pid, email, last_login)
+            VALUES ($1, $2, $3, $4, NOW())
+            ON CONFLICT (issuer, subject) DO UPDATE
+            SET email = EXCLUDED.email, last_login = EXCLUDED.last_login
+            RETURNING *",
+        )
+        .bind(&claims.iss)
+        .bind(&claims.sub)
+        .bind(user_pid)
+        .bind(&claims.email)
+        .fetch_one(db)
+        .await
+        .map_err(Into::into)
+    }
+
+    /// The user a validated ID token logs in as.
+    ///
+    /// A known account logs in as the user it was linked to. Otherwise the
+    /// account is linked to the user with its email, provided the provider
+    /// verified that email, and anyone else joins the configured organisation
+    /// with the configured role.
+    pub async fn sign_in(
+        db: &mut PgConnection,
+        config: &OidcConfig,
+        claims: &IdTokenClaims,
+    ) -> ModelResult<User> {
+        if let Some(identity) = Self::find(&mut *db, &claims.iss, &claims.sub).await? {
+            Self::link(&mut *db, claims, identity.user_pid).await?;
+
+            return User::find_by_pid(&mut *db, identity.user_pid)
+                .await?
+                .ok_or_else(|| ModelError::EntityNotFound);
+        }
+
+        let email = claims.email.as_deref().map(str::trim).ok_or_else(|| {
+            ModelError::Validation("The identity provider did not share an email address".into())
+        })?;
+
+        let user = match User::find_by_email(&mut *db, email).await? {
+            Some(_) if claims.email_verified != Some(true) => {
+                return Err(ModelError::Conflict(
+                    "An account with that email already exists. Log in with your password.".into(),
+                ));
+            }
+            Some(user) => user,
+            None => {
+                let organisation = Organisation::find_by_pid(&mut *db, config.organisation).await?;
+
+                Entitlement::User
+                    .check(&mut *db, organisation.pid, &organisation.subscription())
+                    .await?;
+
+                let (first_name, last_name) = Self::names(claims, email);
+
+                User::create_provisioned(
+                    &mut *db,
+                    organisation.pid,
+                    &config.role,
+                    email,
+                    &first_name,
+                    &last_name,
+                )
+                .await?
+            }
+        };
+
+        Self::link(&mut *db, claims, user.pid).await?;
+
+        Ok(user)
+    }
+
+    /// The person's names from the token, falling back to splitting their
+    /// full name or using their email.
+    fn names(claims: &IdTokenClaims, email: &str) -> (String, String) {
+        if let Some(first_name) = &claims.given_name {
+            return (
+                first_name.clone(),
+                claims.family_name.clone().unwrap_or_default(),
+            );
+        }
+
+        match claims.name.as_deref().map(str::trim) {
+            Some(name) if !name.is_empty() => {
+                let (first_name, last_name) = name.split_once(' ').unwrap_or((name, ""));
+                (first_name.to_string(), last_name.trim().to_string())
+            }
+            _ => (
+                email.split('@').next().unwrap_or(email).to_string(),
+                String::new(),
+            ),
+        }
+    }
+}
@@ -0,0 +1,328 @@
+#![allow(clippy::missing_errors_doc)]
+
+use argon2::{Argon2, PasswordHash, PasswordVerifier};
+use chrono::{DateTime, Duration, FixedOffset, Utc};
+use rand::{Rng, distr::Alphanumeric};
+use serde::{Deserialize, Serialize};
+use sqlx::{Executor, PgConnection, Postgres, prelude::FromRow};
+use uuid::Uuid;
+
+use super::{
+    ModelError, ModelResult,
+    dto::{AcceptInvitation, InviteUser, Validator},
+    roles::Role,
+    users::User,
+};
+
+/// How long an invitation can be accepted for after it was sent.
+pub const INVITATION_TTL_DAYS: i64 = 7;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InvitationStatus {
+    Pending,
+    Accepted,
+    Revoked,
+    Expired,
+}
+
+impl InvitationStatus {
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Accepted => "accepted",
+            Self::Revoked => "revoked",
+            Self::Expired => "expired",
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct InvitationQuery {
+    pub status: Option<InvitationStatus>,
+}
+
+#[derive(Debug, Deserialize, Serialize, FromRow, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Invitation {
+    pub id: i32,
+    pub pid: Uuid,
+    pub organisation_pid: Uuid,
+    pub email: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub role: String,
+    #[serde(skip_serializing)]
+    pub(crate) token_hash: String,
+    pub invited_by: Option<Uuid>,
+    pub expires_at: DateTime<FixedOffset>,
+    pub accepted_at: Option<DateTime<FixedOffset>>,
+    pub revoked_at: Option<DateTime<FixedOffset>>,
+    pub created_at: DateTime<FixedOffset>,
+    pub updated_at: DateTime<FixedOffset>,
+}
+
+/// An invitation along with the token the invitee accepts it with. Only a
+/// hash of the token is stored, so this is the one chance to hand it over.
+#[derive(Debug, Clone)]
+pub struct IssuedInvitation {
+    pub invitation: Invitation,
+    pub token: String,
+}
+
+impl Invitation {
+    /// A token of the form `<invitation pid>.<secret>` and the hash of its
+    /// secret.
+    fn issue_token(pid: Uuid) -> ModelResult<(String, String)> {
+        let secret: String = rand::rng()
+            .sample_iter(&Alphanumeric)
+            .take(32)
+            .map(char::from)
+            .collect();
+
+        let hash = User::hash_password(&secret)?;
+
+        Ok((format!("{pid}.{secret}"), hash))
+    }
+
+    fn expiry() -> DateTime<FixedOffset> {
+        (Utc::now() + Duration::days(INVITATION_TTL_DAYS)).fixed_offset()
+    }
+
+    #[must_use]
+    pub fn status(&self) -> InvitationStatus {
+        if self.accepted_at.is_some() {
+            InvitationStatus::Accepted
+        } else if self.revoked_at.is_some() {
+            InvitationStatus::Revoked
+        } else if self.expires_at <= Utc::now() {
+            InvitationStatus::Expired
+        } else {
+            InvitationStatus::Pending
+        }
+    }
+
+    /// Invites `dto.email` to the organisation with the given role.
+    pub async fn create(
+        db: &mut PgConnection,
+        org_pid: Uuid,
+        invited_by: Uuid,
+        dto: &InviteUser<'_>,
+    ) -> ModelResult<IssuedInvitation> {
+        let validator = Validator::new(dto);
+        let dto = validator.validate()?;
+
+        let role = dto.role.trim().to_lowercase();
+
+        Role::find_for_organisation(&mut *db, org_pid, &role)
+            .await
+            .map_err(|error| match error {
+                ModelError::EntityNotFound => {
+                    ModelError::Validation("Role does not exist in this organisation".into())
+                }
+                error => error,
+            })?;
+
+        let is_member = sqlx::query_scalar::<_, bool>(
+            "
+            SELECT EXISTS (
+                SELECT 1 FROM memberships m
+                JOIN users u ON m.user_pid = u.pid
+                WHERE u.email = $1 AND m.organisation_pid = $2
+            )
+            ",
+        )
+        .bind(dto.email.trim())
+        .bind(org_pid)
+        .fetch_one(&mut *db)
+        .await?;
+
+        if is_member {
+            return Err(ModelError::EntityAlreadyExists(
+                "User with that email address already exist".into(),
+            ));
+        }
+
+        let pid = Uuid::new_v4();
+        let (token, token_hash) = Self::issue_token(pid)?;
+
+        let query = sqlx::query_as::<_, Self>(
+            "
+            INSERT INTO invitations
+            (pid, organisation_pid, email, first_name, last_name, role, token_hash, invited_by, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9) RETURNING *",
+        )
+        .bind(pid)
+        .bind(org_pid)
+        .bind(dto.email.trim())
+        .bind(dto.first_name.trim())
+        .bind(dto.last_name.trim())
+        .bind(role)
+        .bind(token_hash)
+        .bind(invited_by)
+        .bind(Self::expiry())
+        .fetch_one(&mut *db)
+        .await;
+
+        match query {
+            Ok(invitation) => Ok(IssuedInvitation { invitation, token }),
+            Err(sqlx::Error::Database(err))
+                if err.constraint() == Some("invitations_open_email_idx") =>
+            {
+                Err(ModelError::EntityAlreadyExists(
+                    "An invitation for that email address is already pending".into(),
+                ))
+            }
+            Err(e) => Err(ModelError::Sqlx(e)),
+        }
+    }
+
+    /// The organisation's invitations, newest first, optionally filtered by
+    /// status.
+    pub async fn find_all<'e, C>(
+        db: C,
+        org_pid: Uuid,
+        conditions: &InvitationQuery,
+    ) -> ModelResult<Vec<Self>>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_as::<_, Self>(
+            "
+            SELECT * FROM invitations
+            WHERE organisation_pid = $1
+            AND (
+                $2::TEXT IS NULL OR $2 = CASE
+                    WHEN accepted_at IS NOT NULL THEN 'accepted'
+                    WHEN revoked_at IS NOT NULL THEN 'revoked'
+                    WHEN expires_at <= NOW() THEN 'expired'
+                    ELSE 'pending'
+                END
+            )
+            ORDER BY created_at DESC, id DESC
+            ",
+        )
+        .bind(org_pid)
+        .bind(conditions.status.as_ref().map(InvitationStatus::as_str))
+        .fetch_all(db)
+        .await
+        .map_err(Into::into)
+    }
+
+    pub async fn find_by_pid<'e, C>(db: C, org_pid: Uuid, pid: Uuid) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_as::<_, Self>(
+            "SELECT * FROM invitations WHERE pid = $1 AND organisation_pid = $2",
+        )
+        .bind(pid)
+        .bind(org_pid)
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    /// Issues a new token and restarts the expiry. The previous token stops
+    /// working.
+    pub async fn resend(
+        db: &mut PgConnection,
+        org_pid: Uuid,
+        pid: Uuid,
+    ) -> ModelResult<IssuedInvitation> {
+        let invitation = Self::find_by_pid(&mut *db, org_pid, pid).await?;
+
+        if matches!(
+            invitation.status(),
+            InvitationStatus::Accepted | InvitationStatus::Revoked
+        ) {
+            return Err(ModelError::Validation(format!(
+                "Invitation has already been {}",
+                invitation.status().as_str()
+            )));
+        }
+
+        let (token, token_hash) = Self::issue_token(pid)?;
+
+        let invitation = sqlx::query_as::<_, Self>(
+            "UPDATE invitations SET token_hash = $2, expires_at = $3 WHERE pid = $1 RETURNING *",
+        )
+        .bind(pid)
+        .bind(token_hash)
+        .bind(Self::expiry())
+        .fetch_one(&mut *db)
+        .await?;
+
+        Ok(IssuedInvitation { invitation, token })
+    }
+
+    pub async fn revoke(db: &mut PgConnection, org_pid: Uuid, pid: Uuid) -> ModelResult<Self> {
+        let invitation = Self::find_by_pid(&mut *db, org_pid, pid).await?;
+
+        if matches!(
+            invitation.status(),
+            InvitationStatus::Accepted | InvitationStatus::Revoked
+        ) {
+            return Err(ModelError::Validation(format!(
+                "Invitation has already been {}",
+                invitation.status().as_str()
+            )));
+        }
+
+        sqlx::query_as::<_, Self>(
+            "UPDATE invitations SET revoked_at = NOW() WHERE pid = $1 RETURNING *",
+        )
+        .bind(pid)
+        .fetch_one(&mut *db)
+        .await
+        .map_err(Into::into)
+    }
+
+    /// Finds the pending invitation a token was issued for.
+    pub async fn find_by_token<'e, C>(db: C, token: &str) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        let invalid = || ModelError::Validation("Invitation is invalid or has expired".into());
+
+        let (pid, secret) = token.trim().split_once('.').ok_or_else(invalid)?;
+        let pid = Uuid::parse_str(pid).map_err(|_| invalid())?;
+
+        let invitation = sqlx::query_as::<_, Self>("SELECT * FROM invitations WHERE pid = $1")
+            .bind(pid)
+            .fetch_optional(db)
+            .await?
+            .ok_or_else(invalid)?;
+
+        let hash = PasswordHash::new(&invitation.token_hash)?;
+
+        if Argon2::default()
+            .verify_password(secret.as_bytes(), &hash)
+            .is_err()
+            || invitation.status() != InvitationStatus::Pending
+        {
+            return Err(invalid());
+        }
+
+        Ok(invitation)
+    }
+
+    /// Adds the invitee to the organisation, creating their account if they
+    /// don't have one yet, and closes the invitation.
+    pub async fn accept(db: &mut PgConnection, dto: &AcceptInvitation<'_>) -> ModelResult<User> {
+        let validator = Validator::new(dto);
+        let dto = validator.validate()?;
+
+        let invitation = Self::find_by_token(&mut *db, &dto.token).await?;
+
+        let user = User::create_invited(&mut *db, &invitation, &dto.password).await?;
+
+        sqlx::query("UPDATE invitations SET accepted_at = NOW() WHERE pid = $1")
+            .bind(invitation.pid)
+            .execute(&mut *db)
+            .await?;
+
+        Ok(user)
+    }
+}
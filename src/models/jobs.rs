@@ -0,0 +1,525 @@
+#![allow(clippy::missing_errors_doc)]
+
+use std::{fmt, str::FromStr};
+
+use chrono::{DateTime, FixedOffset, Utc};
+use cron::Schedule;
+use serde::{Deserialize, Serialize};
+use sqlx::{Executor, PgConnection, PgPool, Postgres, prelude::FromRow, types::Json};
+use uuid::Uuid;
+
+use super::{
+    ModelError, ModelResult,
+    dto::{CreateJobSchedule, JobQuery, UpdateJobSchedule, Validator},
+    settings::OrganisationSettings,
+    users::User,
+};
+
+/// The work a job does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    /// Generates the livestock, species and breed summaries the
+    /// organisation's plan includes.
+    Summaries,
+    /// Evaluates the alert rules.
+    Alerts,
+    /// Permanently deletes the trash past its retention period, and finished
+    /// jobs past theirs.
+    Purge,
+    /// Sends the webhook deliveries that are due.
+    Deliver,
+    /// Raises the tasks of the recurring tasks and vaccination schedules
+    /// that came due.
+    Tasks,
+}
+
+impl JobKind {
+    pub const ALL: &'static [Self] = &[
+        Self::Summaries,
+        Self::Alerts,
+        Self::Purge,
+        Self::Deliver,
+        Self::Tasks,
+    ];
+
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Summaries => "summaries",
+            Self::Alerts => "alerts",
+            Self::Purge => "purge",
+            Self::Deliver => "deliver",
+            Self::Tasks => "tasks",
+        }
+    }
+
+    /// Whether the job works on one organisation's data. The others are the
+    /// platform's own and only it schedules them.
+    #[must_use]
+    pub const fn per_organisation(&self) -> bool {
+        matches!(self, Self::Summaries | Self::Alerts | Self::Tasks)
+    }
+}
+
+impl fmt::Display for JobKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for JobKind {
+    type Err = ModelError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .find(|kind| kind.as_str() == value.trim())
+            .copied()
+            .ok_or_else(|| ModelError::Validation(format!("Unknown job kind {value}")))
+    }
+}
+
+/// Parses a cron expression of five fields, minute to day of week, or six
+/// with the seconds first.
+pub fn parse_cron(expression: &str) -> ModelResult<Schedule> {
+    let expression = expression.trim();
+
+    let expression = match expression.split_whitespace().count() {
+        5 => format!("0 {expression}"),
+        6 => expression.to_string(),
+        _ => {
+            return Err(ModelError::Validation(
+                "A cron expression has five fields, or six with the seconds first".into(),
+            ));
+        }
+    };
+
+    Schedule::from_str(&expression)
+        .map_err(|error| ModelError::Validation(format!("Invalid cron expression: {error}")))
+}
+
+/// When `cron` next fires after `after`, read in the timezone `offset`.
+fn next_run(cron: &str, offset: FixedOffset, after: DateTime<Utc>) -> ModelResult<DateTime<Utc>> {
+    parse_cron(cron)?
+        .after(&after.with_timezone(&offset))
+        .next()
+        .map(|next| next.with_timezone(&Utc))
+        .ok_or_else(|| ModelError::Validation("The cron expression never fires again".into()))
+}
+
+/// The timezone an organisation's schedules are read in, UTC for the
+/// platform's.
+async fn offset(db: &mut PgConnection, org_pid: Option<Uuid>) -> ModelResult<FixedOffset> {
+    let utc = FixedOffset::east_opt(0).expect("UTC is a valid offset");
+
+    let Some(org_pid) = org_pid else {
+        return Ok(utc);
+    };
+
+    Ok(OrganisationSettings::find(&mut *db, org_pid)
+        .await?
+        .offset()
+        .unwrap_or(utc))
+}
+
+#[derive(Debug, Deserialize, Serialize, FromRow, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct JobSchedule {
+    pub id: i32,
+    pub pid: Uuid,
+    pub organisation_pid: Option<Uuid>,
+    pub kind: String,
+    pub cron: String,
+    pub is_active: bool,
+    pub next_run_at: DateTime<FixedOffset>,
+    pub last_run_at: Option<DateTime<FixedOffset>>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<FixedOffset>,
+    pub updated_at: DateTime<FixedOffset>,
+}
+
+#[derive(Debug, Deserialize, Serialize, FromRow, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Job {
+    pub id: i32,
+    pub pid: Uuid,
+    pub organisation_pid: Option<Uuid>,
+    pub schedule_pid: Option<Uuid>,
+    pub kind: String,
+    pub status: String,
+    pub attempts: i32,
+    pub max_attempts: i32,
+    pub run_at: DateTime<FixedOffset>,
+    pub locked_until: Option<DateTime<FixedOffset>>,
+    pub result: Option<Json<serde_json::Value>>,
+    pub last_error: Option<String>,
+    pub finished_at: Option<DateTime<FixedOffset>>,
+    pub created_at: DateTime<FixedOffset>,
+    pub updated_at: DateTime<FixedOffset>,
+}
+
+impl JobSchedule {
+    pub fn kind(&self) -> ModelResult<JobKind> {
+        self.kind.parse()
+    }
+
+    /// Schedules one of the kinds that run per organisation for the
+    /// creator's organisation. The cron expression is read in the
+    /// organisation's timezone.
+    pub async fn create(
+        db: &mut PgConnection,
+        creator: &User,
+        dto: &CreateJobSchedule<'_>,
+    ) -> ModelResult<Self> {
+        let validator = Validator::new(dto);
+        let dto = validator.validate()?;
+
+        let kind = dto.kind.parse::<JobKind>()?;
+        if !kind.per_organisation() {
+            return Err(ModelError::Validation(format!(
+                "{kind} jobs are scheduled by the platform"
+            )));
+        }
+
+        let offset = offset(&mut *db, Some(creator.organisation_pid)).await?;
+        let next_run_at = next_run(&dto.cron, offset, Utc::now())?;
+
+        sqlx::query_as::<_, Self>(
+            "
+            INSERT INTO job_schedules (organisation_pid, kind, cron, is_active, next_run_at, created_by)
+            VALUES ($1, $2, $3, $4, $5, $6) RETURNING *",
+        )
+        .bind(creator.organisation_pid)
+        .bind(kind.as_str())
+        .bind(dto.cron.trim())
+        .bind(dto.is_active.unwrap_or(true))
+        .bind(next_run_at)
+        .bind(creator.pid)
+        .fetch_one(&mut *db)
+        .await
+        .map_err(Into::into)
+    }
+
+    pub async fn find_all<'e, C>(db: C, org_pid: Uuid) -> ModelResult<Vec<Self>>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_as::<_, Self>(
+            "SELECT * FROM job_schedules WHERE organisation_pid = $1 ORDER BY created_at, id",
+        )
+        .bind(org_pid)
+        .fetch_all(db)
+        .await
+        .map_err(Into::into)
+    }
+
+    pub async fn find_by_pid<'e, C>(db: C, org_pid: Uuid, pid: Uuid) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_as::<_, Self>(
+            "SELECT * FROM job_schedules WHERE pid = $1 AND organisation_pid = $2",
+        )
+        .bind(pid)
+        .bind(org_pid)
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    /// Changes when the schedule fires, or pauses it. The next run is worked
+    /// out again from now.
+    pub async fn update(
+        db: &mut PgConnection,
+        org_pid: Uuid,
+        pid: Uuid,
+        dto: &UpdateJobSchedule<'_>,
+    ) -> ModelResult<Self> {
+        let validator = Validator::new(dto);
+        let dto = validator.validate()?;
+
+        let schedule = Self::find_by_pid(&mut *db, org_pid, pid).await?;
+        let cron = dto.cron.as_deref().unwrap_or(&schedule.cron).trim();
+
+        let offset = offset(&mut *db, Some(org_pid)).await?;
+        let next_run_at = next_run(cron, offset, Utc::now())?;
+
+        sqlx::query_as::<_, Self>(
+            "
+            UPDATE job_schedules SET
+                cron = $2,
+                is_active = COALESCE($3, is_active),
+                next_run_at = $4
+            WHERE pid = $1 RETURNING *",
+        )
+        .bind(schedule.pid)
+        .bind(cron)
+        .bind(dto.is_active)
+        .bind(next_run_at)
+        .fetch_one(&mut *db)
+        .await
+        .map_err(Into::into)
+    }
+
+    /// Deletes the schedule. The jobs it queued are kept.
+    pub async fn delete_by_pid<'e, C>(db: C, org_pid: Uuid, pid: Uuid) -> ModelResult<()>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        let result =
+            sqlx::query("DELETE FROM job_schedules WHERE pid = $1 AND organisation_pid = $2")
+                .bind(pid)
+                .bind(org_pid)
+                .execute(db)
+                .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(ModelError::EntityNotFound);
+        }
+
+        Ok(())
+    }
+
+    /// Locks the active schedules that are due, skipping those another
+    /// process is already firing. Call it inside a transaction.
+    pub async fn claim_due(db: &mut PgConnection) -> ModelResult<Vec<Self>> {
+        sqlx::query_as::<_, Self>(
+            "
+            SELECT * FROM job_schedules
+            WHERE is_active AND next_run_at <= NOW()
+            ORDER BY next_run_at, id
+            FOR UPDATE SKIP LOCKED",
+        )
+        .fetch_all(db)
+        .await
+        .map_err(Into::into)
+    }
+
+    /// Queues the schedule's job and works out when it next fires. A platform
+    /// schedule of a per-organisation kind queues a job for every
+    /// organisation that is not suspended. Nothing new is queued for an
+    /// organisation while the schedule's last job for it has not finished.
+    /// Returns how many jobs were queued.
+    pub async fn fire(&self, db: &mut PgConnection, max_attempts: i32) -> ModelResult<u64> {
+        let kind = self.kind()?;
+
+        let targets = match (self.organisation_pid, kind.per_organisation()) {
+            (Some(_), _) => "SELECT pid FROM organisations WHERE pid = $4 AND suspended_at IS NULL",
+            (None, true) => "SELECT pid FROM organisations WHERE suspended_at IS NULL",
+            (None, false) => "SELECT NULL::UUID AS pid",
+        };
+
+        let query = format!(
+            "
+            INSERT INTO jobs (organisation_pid, schedule_pid, kind, max_attempts)
+            SELECT t.pid, $1, $2, $3
+            FROM ({targets}) t
+            WHERE NOT EXISTS (
+                SELECT 1 FROM jobs j
+                WHERE j.schedule_pid = $1
+                    AND j.organisation_pid IS NOT DISTINCT FROM t.pid
+                    AND j.status IN ('queued', 'running')
+            )"
+        );
+
+        let queued = sqlx::query(&query)
+            .bind(self.pid)
+            .bind(kind.as_str())
+            .bind(max_attempts)
+            .bind(self.organisation_pid)
+            .execute(&mut *db)
+            .await?
+            .rows_affected();
+
+        let offset = offset(&mut *db, self.organisation_pid).await?;
+        let next_run_at = next_run(&self.cron, offset, Utc::now())?;
+
+        sqlx::query(
+            "UPDATE job_schedules SET last_run_at = NOW(), next_run_at = $2 WHERE pid = $1",
+        )
+        .bind(self.pid)
+        .bind(next_run_at)
+        .execute(&mut *db)
+        .await?;
+
+        Ok(queued)
+    }
+}
+
+impl Job {
+    pub fn kind(&self) -> ModelResult<JobKind> {
+        self.kind.parse()
+    }
+
+    /// Queues a job to run as soon as a worker is free.
+    pub async fn enqueue<'e, C>(
+        db: C,
+        org_pid: Option<Uuid>,
+        kind: JobKind,
+        max_attempts: i32,
+    ) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_as::<_, Self>(
+            "INSERT INTO jobs (organisation_pid, kind, max_attempts) VALUES ($1, $2, $3) RETURNING *",
+        )
+        .bind(org_pid)
+        .bind(kind.as_str())
+        .bind(max_attempts)
+        .fetch_one(db)
+        .await
+        .map_err(Into::into)
+    }
+
+    /// The organisation's most recent jobs, newest first.
+    pub async fn find_all<'e, C>(
+        db: C,
+        org_pid: Uuid,
+        conditions: &JobQuery,
+    ) -> ModelResult<Vec<Self>>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_as::<_, Self>(
+            "
+            SELECT * FROM jobs
+            WHERE organisation_pid = $1
+                AND ($2::TEXT IS NULL OR kind = $2)
+                AND ($3::TEXT IS NULL OR status = $3)
+            ORDER BY created_at DESC, id DESC
+            LIMIT $4",
+        )
+        .bind(org_pid)
+        .bind(conditions.kind.as_deref())
+        .bind(conditions.status.as_deref())
+        .bind(conditions.limit.unwrap_or(50).clamp(1, 500))
+        .fetch_all(db)
+        .await
+        .map_err(Into::into)
+    }
+
+    pub async fn find_by_pid<'e, C>(db: C, org_pid: Uuid, pid: Uuid) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_as::<_, Self>("SELECT * FROM jobs WHERE pid = $1 AND organisation_pid = $2")
+            .bind(pid)
+            .bind(org_pid)
+            .fetch_optional(db)
+            .await?
+            .ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    /// Takes the next job that is due for `lease` seconds, along with one
+    /// whose worker let its lease run out. Other workers skip the job while
+    /// it is being taken.
+    pub async fn claim(db: &PgPool, lease: i32) -> ModelResult<Option<Self>> {
+        sqlx::query_as::<_, Self>(
+            "
+            WITH next AS (
+                SELECT id FROM jobs
+                WHERE (status = 'queued' AND run_at <= NOW())
+                    OR (status = 'running' AND locked_until < NOW() AND attempts < max_attempts)
+                ORDER BY run_at, id
+                LIMIT 1
+                FOR UPDATE SKIP LOCKED
+            )
+            UPDATE jobs j
+            SET
+                status = 'running',
+                attempts = j.attempts + 1,
+                locked_until = NOW() + make_interval(secs => $1)
+            FROM next
+            WHERE j.id = next.id
+            RETURNING j.*",
+        )
+        .bind(f64::from(lease))
+        .fetch_optional(db)
+        .await
+        .map_err(Into::into)
+    }
+
+    pub async fn complete<'e, C>(db: C, pid: Uuid, result: serde_json::Value) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_as::<_, Self>(
+            "
+            UPDATE jobs
+            SET
+                status = 'succeeded',
+                locked_until = NULL,
+                result = $2,
+                last_error = NULL,
+                finished_at = NOW()
+            WHERE pid = $1 RETURNING *",
+        )
+        .bind(pid)
+        .bind(Json(result))
+        .fetch_one(db)
+        .await
+        .map_err(Into::into)
+    }
+
+    /// Records a failed attempt. The job is retried after `retry_delay`
+    /// seconds, doubling with each attempt, until it runs out of attempts.
+    pub async fn fail<'e, C>(db: C, pid: Uuid, error: &str, retry_delay: i32) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_as::<_, Self>(
+            "
+            UPDATE jobs
+            SET
+                status = CASE WHEN attempts >= max_attempts THEN 'failed' ELSE 'queued' END,
+                finished_at = CASE WHEN attempts >= max_attempts THEN NOW() END,
+                run_at = NOW() + make_interval(secs => $3 * POWER(2, LEAST(attempts - 1, 16))),
+                locked_until = NULL,
+                last_error = $2
+            WHERE pid = $1 RETURNING *",
+        )
+        .bind(pid)
+        .bind(error)
+        .bind(f64::from(retry_delay))
+        .fetch_one(db)
+        .await
+        .map_err(Into::into)
+    }
+
+    /// Fails the jobs whose worker stopped during their last attempt.
+    pub async fn give_up_abandoned<'e, C>(db: C) -> ModelResult<u64>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        let result = sqlx::query(
+            "
+            UPDATE jobs
+            SET
+                status = 'failed',
+                locked_until = NULL,
+                last_error = 'The worker running the last attempt stopped',
+                finished_at = NOW()
+            WHERE status = 'running' AND locked_until < NOW() AND attempts >= max_attempts",
+        )
+        .execute(db)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Deletes jobs that finished more than `retention_days` ago.
+    pub async fn prune<'e, C>(db: C, retention_days: u32) -> ModelResult<u64>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        let result =
+            sqlx::query("DELETE FROM jobs WHERE finished_at < NOW() - MAKE_INTERVAL(days => $1)")
+                .bind(i32::try_from(retention_days).unwrap_or(i32::MAX))
+                .execute(db)
+                .await?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -56,7 +56,7 @@ impl SummaryData {
                 COUNT(*) as total,
                 COUNT(*) FILTER (WHERE gender = 'male') as males,
                 COUNT(*) FILTER (WHERE gender = 'female') as females,
-                COUNT(*) FILTER (WHERE gender = 'unkown') as unkown_gender,
+                COUNT(*) FILTER (WHERE COALESCE(gender, 'unknown') = 'unknown') as unkown_gender,
                 COUNT(*) FILTER (WHERE status = 'active') as active,
                 COUNT(*) FILTER (WHERE status = 'transferred') as transferred,
                 COUNT(*) FILTER (WHERE status = 'deceased') as deceased,
@@ -65,7 +65,7 @@ impl SummaryData {
                 COUNT(DISTINCT breed_id) as breeds,
                 COALESCE(SUM(purchase_price), 0) as total_purchased_value
             FROM animals
-            WHERE organisation_pid = $1
+            WHERE organisation_pid = $1 AND deleted_at IS NULL
             "#,
         )
         .bind(org_pid)
@@ -0,0 +1,609 @@
+#![allow(clippy::missing_errors_doc)]
+
+use std::borrow::Cow;
+
+use chrono::{DateTime, FixedOffset, NaiveDate};
+use serde::{Deserialize, Serialize};
+use sqlx::{Executor, PgConnection, Postgres, prelude::FromRow};
+use uuid::Uuid;
+
+use super::{
+    ModelError, ModelResult,
+    dto::{MoveAnimals, MovementQuery, NewLocation, UpdateLocation, Validator},
+};
+
+/// A site, paddock or pen.
+#[derive(Debug, Deserialize, Serialize, FromRow, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Location {
+    pub id: i32,
+    pub pid: Uuid,
+    pub organisation_pid: Uuid,
+    #[serde(skip)]
+    pub parent_id: Option<i32>,
+    pub parent_pid: Option<Uuid>,
+    pub kind: String,
+    pub name: String,
+    /// The names of the locations it is within and its own, e.g.
+    /// `Home farm / North paddock`.
+    pub path: String,
+    pub capacity: Option<i32>,
+    pub notes: Option<String>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<FixedOffset>,
+    pub updated_at: DateTime<FixedOffset>,
+}
+
+/// Locations ordered as a tree, each after the location it is within.
+const LOCATIONS_QUERY: &str = "
+    WITH RECURSIVE tree AS (
+        SELECT id, ARRAY[LOWER(name)]::TEXT[] AS sort, name::TEXT AS path
+        FROM locations
+        WHERE organisation_pid = $1 AND parent_id IS NULL
+        UNION ALL
+        SELECT c.id, t.sort || LOWER(c.name)::TEXT, t.path || ' / ' || c.name
+        FROM locations c
+        JOIN tree t ON c.parent_id = t.id
+    )
+    SELECT
+        l.id,
+        l.pid,
+        l.organisation_pid,
+        l.parent_id,
+        p.pid AS parent_pid,
+        l.kind,
+        l.name,
+        t.path,
+        l.capacity,
+        l.notes,
+        l.created_by,
+        l.created_at,
+        l.updated_at
+    FROM
+        tree t
+    JOIN
+        locations l ON t.id = l.id
+    LEFT JOIN
+        locations p ON l.parent_id = p.id
+    WHERE
+        l.organisation_pid = $1
+";
+
+/// The ids of the location `$1` and every location within it.
+const BENEATH: &str = "
+    WITH RECURSIVE beneath AS (
+        SELECT id FROM locations WHERE id = $1
+        UNION ALL
+        SELECT c.id FROM locations c JOIN beneath b ON c.parent_id = b.id
+    )
+    SELECT id FROM beneath
+";
+
+fn unique_name(error: sqlx::Error) -> ModelError {
+    match error {
+        sqlx::Error::Database(err) if err.constraint() == Some("locations_name_idx") => {
+            ModelError::EntityAlreadyExists("A location with that name already exists there".into())
+        }
+        error => ModelError::Sqlx(error),
+    }
+}
+
+impl Location {
+    pub async fn create(
+        db: &mut PgConnection,
+        org_pid: Uuid,
+        user_pid: Uuid,
+        params: &NewLocation<'_>,
+    ) -> ModelResult<Self> {
+        let validator = Validator::new(params);
+        let params = validator.validate()?;
+
+        let parent = match params.parent {
+            Some(parent) => Some(
+                Self::find_by_pid(&mut *db, org_pid, parent)
+                    .await
+                    .map_err(|_| ModelError::Validation(format!("Unknown location {parent}")))?,
+            ),
+            None => None,
+        };
+
+        let kind = params.kind.as_ref();
+        match (kind, parent.as_ref().map(|parent| parent.kind.as_str())) {
+            ("site", None) | ("paddock", Some("site")) | ("pen", Some("site" | "paddock")) => {}
+            ("site", Some(_)) => {
+                return Err(ModelError::Validation(
+                    "A site cannot be within another location".into(),
+                ));
+            }
+            ("paddock", _) => {
+                return Err(ModelError::Validation("A paddock must be on a site".into()));
+            }
+            _ => {
+                return Err(ModelError::Validation(
+                    "A pen must be on a site or in a paddock".into(),
+                ));
+            }
+        }
+
+        let pid = sqlx::query_scalar::<_, Uuid>(
+            "
+            INSERT INTO locations (
+                organisation_pid, parent_id, kind, name, capacity, notes, created_by
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING pid",
+        )
+        .bind(org_pid)
+        .bind(parent.map(|parent| parent.id))
+        .bind(kind)
+        .bind(params.name.trim())
+        .bind(params.capacity)
+        .bind(params.notes.as_deref())
+        .bind(user_pid)
+        .fetch_one(&mut *db)
+        .await
+        .map_err(unique_name)?;
+
+        Self::find_by_pid(&mut *db, org_pid, pid).await
+    }
+
+    pub async fn find_all<'e, C>(db: C, org_pid: Uuid) -> ModelResult<Vec<Self>>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        let query = format!("{LOCATIONS_QUERY} ORDER BY t.sort");
+
+        sqlx::query_as::<_, Self>(&query)
+            .bind(org_pid)
+            .fetch_all(db)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn find_by_pid<'e, C>(db: C, org_pid: Uuid, pid: Uuid) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        let query = format!("{LOCATIONS_QUERY} AND l.pid = $2");
+
+        sqlx::query_as::<_, Self>(&query)
+            .bind(org_pid)
+            .bind(pid)
+            .fetch_optional(db)
+            .await?
+            .ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    pub async fn update(
+        db: &mut PgConnection,
+        org_pid: Uuid,
+        pid: Uuid,
+        params: &UpdateLocation<'_>,
+    ) -> ModelResult<Self> {
+        let validator = Validator::new(params);
+        let params = validator.validate()?;
+
+        let updated = sqlx::query(
+            "
+            UPDATE locations SET
+                name = COALESCE($3, name),
+                capacity = COALESCE($4, capacity),
+                notes = COALESCE($5, notes)
+            WHERE pid = $1 AND organisation_pid = $2",
+        )
+        .bind(pid)
+        .bind(org_pid)
+        .bind(params.name.as_deref().map(str::trim))
+        .bind(params.capacity)
+        .bind(params.notes.as_deref())
+        .execute(&mut *db)
+        .await
+        .map_err(unique_name)?;
+
+        if updated.rows_affected() == 0 {
+            return Err(ModelError::EntityNotFound);
+        }
+
+        Self::find_by_pid(&mut *db, org_pid, pid).await
+    }
+
+    /// Only empty locations can be deleted. Movements keep their date and
+    /// reason, but lose the location.
+    pub async fn delete_by_pid(db: &mut PgConnection, org_pid: Uuid, pid: Uuid) -> ModelResult<()> {
+        let location = Self::find_by_pid(&mut *db, org_pid, pid).await?;
+
+        let (locations, animals) = sqlx::query_as::<_, (i64, i64)>(
+            "SELECT
+                (SELECT COUNT(*) FROM locations WHERE parent_id = $1),
+                (SELECT COUNT(*) FROM animals WHERE location_id = $1 AND deleted_at IS NULL)",
+        )
+        .bind(location.id)
+        .fetch_one(&mut *db)
+        .await?;
+
+        if locations > 0 {
+            return Err(ModelError::Conflict(format!(
+                "{} has locations within it",
+                location.path
+            )));
+        }
+        if animals > 0 {
+            return Err(ModelError::Conflict(format!(
+                "{} still holds {animals} animals",
+                location.path
+            )));
+        }
+
+        sqlx::query("DELETE FROM locations WHERE id = $1")
+            .bind(location.id)
+            .execute(&mut *db)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Moves the animals tagged together, recording a movement of each in
+    /// one batch. Animals already there are left out, and no location on
+    /// the way may be filled beyond its capacity.
+    pub async fn move_animals(
+        db: &mut PgConnection,
+        org_pid: Uuid,
+        user_pid: Uuid,
+        params: &MoveAnimals<'_>,
+    ) -> ModelResult<Vec<Movement>> {
+        let validator = Validator::new(params);
+        let params = validator.validate()?;
+
+        let to = match params.to {
+            Some(to) => Some(
+                Self::find_by_pid(&mut *db, org_pid, to)
+                    .await
+                    .map_err(|_| ModelError::Validation(format!("Unknown location {to}")))?,
+            ),
+            None => None,
+        };
+
+        let animals = Self::lock_animals(&mut *db, org_pid, &params.tag_ids).await?;
+
+        let moved_on = match params.moved_on {
+            Some(moved_on) => moved_on,
+            None => {
+                sqlx::query_scalar::<_, NaiveDate>("SELECT CURRENT_DATE")
+                    .fetch_one(&mut *db)
+                    .await?
+            }
+        };
+        if let Some((_, tag_id, _, last)) = animals
+            .iter()
+            .find(|(_, _, _, last)| last.is_some_and(|last| last > moved_on))
+        {
+            return Err(ModelError::Validation(format!(
+                "{tag_id} was last moved on {}, after {moved_on}",
+                last.unwrap_or(moved_on)
+            )));
+        }
+
+        let to_id = to.as_ref().map(|to| to.id);
+        let moving = animals
+            .iter()
+            .filter(|(_, _, location_id, _)| *location_id != to_id)
+            .collect::<Vec<_>>();
+        if moving.is_empty() {
+            return Err(ModelError::Validation(format!(
+                "The animals are already in {}",
+                to.map_or_else(|| "no location".to_string(), |to| to.path)
+            )));
+        }
+        let pids = moving.iter().map(|(pid, ..)| *pid).collect::<Vec<_>>();
+
+        if let Some(to) = &to {
+            Self::ensure_room(&mut *db, org_pid, to, &pids).await?;
+        }
+
+        let batch_pid = Uuid::new_v4();
+        sqlx::query(
+            "
+            INSERT INTO animal_movements (
+                organisation_pid, batch_pid, animal_pid, from_location_id, to_location_id,
+                moved_on, reason, moved_by
+            )
+            SELECT $1, $2, moving.pid, moving.location_id, $5, $6, $7, $8
+            FROM UNNEST($3::UUID[], $4::INTEGER[]) AS moving (pid, location_id)",
+        )
+        .bind(org_pid)
+        .bind(batch_pid)
+        .bind(&pids)
+        .bind(
+            moving
+                .iter()
+                .map(|(_, _, location_id, _)| *location_id)
+                .collect::<Vec<_>>(),
+        )
+        .bind(to_id)
+        .bind(moved_on)
+        .bind(params.reason.as_deref())
+        .bind(user_pid)
+        .execute(&mut *db)
+        .await?;
+
+        sqlx::query("UPDATE animals SET location_id = $2 WHERE pid = ANY($1)")
+            .bind(&pids)
+            .bind(to_id)
+            .execute(&mut *db)
+            .await?;
+
+        Movement::find_by_batch(&mut *db, org_pid, batch_pid).await
+    }
+
+    /// Locks the animals tagged for a move, with the location each is in and
+    /// when it last moved. Fails when any of the tags is unknown.
+    async fn lock_animals(
+        db: &mut PgConnection,
+        org_pid: Uuid,
+        tag_ids: &[Cow<'_, str>],
+    ) -> ModelResult<Vec<(Uuid, String, Option<i32>, Option<NaiveDate>)>> {
+        let tag_ids = tag_ids
+            .iter()
+            .map(|tag_id| tag_id.trim().to_uppercase())
+            .collect::<Vec<_>>();
+
+        let animals = sqlx::query_as::<_, (Uuid, String, Option<i32>, Option<NaiveDate>)>(
+            "
+            SELECT
+                a.pid,
+                a.tag_id,
+                a.location_id,
+                (SELECT MAX(m.moved_on) FROM animal_movements m WHERE m.animal_pid = a.pid)
+            FROM animals a
+            WHERE a.organisation_pid = $1 AND a.tag_id = ANY($2) AND a.deleted_at IS NULL
+            ORDER BY a.tag_id
+            FOR UPDATE OF a",
+        )
+        .bind(org_pid)
+        .bind(&tag_ids)
+        .fetch_all(&mut *db)
+        .await?;
+
+        let unknown = tag_ids
+            .iter()
+            .filter(|tag_id| !animals.iter().any(|(_, found, _, _)| found == *tag_id))
+            .cloned()
+            .collect::<Vec<_>>();
+        if !unknown.is_empty() {
+            return Err(ModelError::Validation(format!(
+                "No animal is tagged {}",
+                unknown.join(", ")
+            )));
+        }
+
+        Ok(animals)
+    }
+
+    /// Fails when moving the `arriving` animals into `to` would fill it, or
+    /// a location it is within, beyond its capacity.
+    async fn ensure_room(
+        db: &mut PgConnection,
+        org_pid: Uuid,
+        to: &Self,
+        arriving: &[Uuid],
+    ) -> ModelResult<()> {
+        let arriving_active = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM animals WHERE pid = ANY($1) AND status = 'active'",
+        )
+        .bind(arriving)
+        .fetch_one(&mut *db)
+        .await?;
+
+        let query = format!(
+            "SELECT COUNT(*) FROM animals
+            WHERE location_id IN ({BENEATH})
+            AND status = 'active' AND deleted_at IS NULL AND pid <> ALL($2)"
+        );
+
+        let mut location = Some(to.clone());
+        while let Some(current) = location {
+            if let Some(capacity) = current.capacity {
+                let staying = sqlx::query_scalar::<_, i64>(&query)
+                    .bind(current.id)
+                    .bind(arriving)
+                    .fetch_one(&mut *db)
+                    .await?;
+
+                if staying + arriving_active > i64::from(capacity) {
+                    return Err(ModelError::Conflict(format!(
+                        "{} has room for {} more",
+                        current.path,
+                        (i64::from(capacity) - staying).max(0)
+                    )));
+                }
+            }
+
+            location = match current.parent_pid {
+                Some(parent) => Some(Self::find_by_pid(&mut *db, org_pid, parent).await?),
+                None => None,
+            };
+        }
+
+        Ok(())
+    }
+}
+
+/// How full a location is. Only active animals count.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Occupancy {
+    pub pid: Uuid,
+    pub parent_pid: Option<Uuid>,
+    pub kind: String,
+    pub path: String,
+    pub capacity: Option<i32>,
+    /// Kept in the location itself.
+    pub animals: i64,
+    /// Kept in it or any location within it.
+    pub total: i64,
+    pub free: Option<i64>,
+    pub over_capacity: bool,
+}
+
+impl Occupancy {
+    pub async fn find_all<'e, C>(db: C, org_pid: Uuid) -> ModelResult<Vec<Self>>
+    where
+        C: Executor<'e, Database = Postgres> + Copy,
+    {
+        let locations = Location::find_all(db, org_pid).await?;
+        let counts = sqlx::query_as::<_, (i32, i64)>(
+            "SELECT location_id, COUNT(*) FROM animals
+            WHERE organisation_pid = $1 AND location_id IS NOT NULL
+            AND status = 'active' AND deleted_at IS NULL
+            GROUP BY location_id",
+        )
+        .bind(org_pid)
+        .fetch_all(db)
+        .await?;
+
+        let count = |id: i32| {
+            counts
+                .iter()
+                .find(|(location_id, _)| *location_id == id)
+                .map_or(0, |(_, count)| *count)
+        };
+
+        let mut occupancy = locations
+            .iter()
+            .map(|location| Self {
+                pid: location.pid,
+                parent_pid: location.parent_pid,
+                kind: location.kind.clone(),
+                path: location.path.clone(),
+                capacity: location.capacity,
+                animals: count(location.id),
+                total: 0,
+                free: None,
+                over_capacity: false,
+            })
+            .collect::<Vec<_>>();
+
+        for location in &locations {
+            let animals = count(location.id);
+            let mut current = Some(location);
+            while let Some(ancestor) = current {
+                if let Some(entry) = occupancy.iter_mut().find(|entry| entry.pid == ancestor.pid) {
+                    entry.total += animals;
+                }
+                current = ancestor
+                    .parent_id
+                    .and_then(|parent| locations.iter().find(|location| location.id == parent));
+            }
+        }
+
+        for entry in &mut occupancy {
+            if let Some(capacity) = entry.capacity {
+                entry.free = Some((i64::from(capacity) - entry.total).max(0));
+                entry.over_capacity = entry.total > i64::from(capacity);
+            }
+        }
+
+        Ok(occupancy)
+    }
+}
+
+/// An animal's move from one location to another.
+#[derive(Debug, Deserialize, Serialize, FromRow, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Movement {
+    pub id: i32,
+    pub pid: Uuid,
+    pub organisation_pid: Uuid,
+    /// Shared by the animals moved together.
+    pub batch_pid: Uuid,
+    pub animal_pid: Uuid,
+    pub tag_id: String,
+    pub from_pid: Option<Uuid>,
+    pub from_name: Option<String>,
+    pub to_pid: Option<Uuid>,
+    pub to_name: Option<String>,
+    pub moved_on: NaiveDate,
+    pub reason: Option<String>,
+    pub moved_by: Option<Uuid>,
+    pub moved_by_name: Option<String>,
+    pub created_at: DateTime<FixedOffset>,
+}
+
+const MOVEMENTS_QUERY: &str = "
+    SELECT
+        m.id,
+        m.pid,
+        m.organisation_pid,
+        m.batch_pid,
+        m.animal_pid,
+        a.tag_id,
+        f.pid AS from_pid,
+        f.name AS from_name,
+        t.pid AS to_pid,
+        t.name AS to_name,
+        m.moved_on,
+        m.reason,
+        m.moved_by,
+        CONCAT(u.first_name, ' ', u.last_name) AS moved_by_name,
+        m.created_at
+    FROM
+        animal_movements m
+    JOIN
+        animals a ON m.animal_pid = a.pid
+    LEFT JOIN
+        locations f ON m.from_location_id = f.id
+    LEFT JOIN
+        locations t ON m.to_location_id = t.id
+    LEFT JOIN
+        users u ON m.moved_by = u.pid
+    WHERE
+        m.organisation_pid = $1
+";
+
+impl Movement {
+    pub async fn find_all<'e, C>(
+        db: C,
+        org_pid: Uuid,
+        conditions: &MovementQuery,
+    ) -> ModelResult<Vec<Self>>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        let query = format!(
+            "{MOVEMENTS_QUERY}
+            AND ($2::UUID IS NULL OR m.animal_pid = $2)
+            AND ($3::UUID IS NULL OR f.pid = $3 OR t.pid = $3)
+            AND ($4::DATE IS NULL OR m.moved_on >= $4)
+            AND ($5::DATE IS NULL OR m.moved_on <= $5)
+            ORDER BY m.moved_on DESC, m.id DESC"
+        );
+
+        sqlx::query_as::<_, Self>(&query)
+            .bind(org_pid)
+            .bind(conditions.animal)
+            .bind(conditions.location)
+            .bind(conditions.from)
+            .bind(conditions.to)
+            .fetch_all(db)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn find_by_batch<'e, C>(
+        db: C,
+        org_pid: Uuid,
+        batch_pid: Uuid,
+    ) -> ModelResult<Vec<Self>>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        let query = format!("{MOVEMENTS_QUERY} AND m.batch_pid = $2 ORDER BY a.tag_id");
+
+        sqlx::query_as::<_, Self>(&query)
+            .bind(org_pid)
+            .bind(batch_pid)
+            .fetch_all(db)
+            .await
+            .map_err(Into::into)
+    }
+}
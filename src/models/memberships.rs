@@ -0,0 +1,67 @@
+#![allow(clippy::missing_errors_doc)]
+
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use sqlx::{Executor, Postgres, prelude::FromRow};
+use uuid::Uuid;
+
+use super::ModelResult;
+
+/// A user's place in an organisation along with the organisation's name.
+#[derive(Debug, Deserialize, Serialize, FromRow, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Membership {
+    pub organisation_pid: Uuid,
+    pub organisation_name: String,
+    pub role: String,
+    pub is_active: bool,
+    /// Whether this is the organisation the user lands in when logging in.
+    pub is_home: bool,
+    pub created_at: DateTime<FixedOffset>,
+}
+
+impl Membership {
+    /// The organisations a user belongs to, home first.
+    pub async fn find_by_user<'e, C>(db: C, user_pid: Uuid) -> ModelResult<Vec<Self>>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_as::<_, Self>(
+            "
+            SELECT
+                m.organisation_pid,
+                o.name AS organisation_name,
+                m.role,
+                m.is_active,
+                m.organisation_pid = u.organisation_pid AS is_home,
+                m.created_at
+            FROM memberships m
+            JOIN organisations o ON m.organisation_pid = o.pid
+            JOIN users u ON m.user_pid = u.pid
+            WHERE m.user_pid = $1
+            ORDER BY is_home DESC, o.name
+            ",
+        )
+        .bind(user_pid)
+        .fetch_all(db)
+        .await
+        .map_err(Into::into)
+    }
+
+    /// Adds an existing user to an organisation.
+    pub async fn create<'e, C>(db: C, user_pid: Uuid, org_pid: Uuid, role: &str) -> ModelResult<()>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query(
+            "INSERT INTO memberships (user_pid, organisation_pid, role) VALUES ($1, $2, $3)",
+        )
+        .bind(user_pid)
+        .bind(org_pid)
+        .bind(role)
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+}
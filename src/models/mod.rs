@@ -1,16 +1,37 @@
+pub mod alerts;
 pub mod animals;
+pub mod api_keys;
+pub mod audit;
 pub mod breeds;
 pub mod dto;
+pub mod entitlements;
 pub mod enums;
 pub mod errors;
+pub mod events;
+pub mod feed;
+pub mod finance;
+pub mod groups;
 pub mod health;
+pub mod identifiers;
+pub mod identities;
+pub mod invitations;
+pub mod jobs;
 pub mod livestock;
+pub mod locations;
+pub mod memberships;
 pub mod orgs;
+pub mod platform;
 pub mod production;
 pub mod roles;
+pub mod settings;
 pub mod species;
 pub mod summaries;
+pub mod tasks;
+pub mod tenant;
+pub mod trash;
+pub mod trends;
 pub mod users;
+pub mod webhooks;
 pub mod weight;
 
 pub use self::{
@@ -9,7 +9,11 @@ use uuid::Uuid;
 
 use crate::seed::Seedable;
 
-use super::{ModelError, ModelResult, enums::Subscription};
+use super::{
+    ModelError, ModelResult,
+    dto::{UpdateOrganisation, Validator},
+    enums::Subscription,
+};
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -24,6 +28,9 @@ pub struct RegisterOrg<'a> {
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default, Eq)]
 pub struct OrganisationQuery {
     pub subscription: Option<Subscription>,
+    /// Matches part of the organisation's name or email.
+    pub search: Option<String>,
+    pub suspended: Option<bool>,
 }
 
 impl OrganisationQuery {
@@ -31,6 +38,8 @@ impl OrganisationQuery {
     pub const fn new(sub: Subscription) -> Self {
         Self {
             subscription: Some(sub),
+            search: None,
+            suspended: None,
         }
     }
 }
@@ -44,6 +53,8 @@ pub struct Organisation {
     pub(crate) phone: Option<String>,
     pub(crate) email: Option<String>,
     pub(crate) subscription_type: Option<Subscription>,
+    pub(crate) suspended_at: Option<DateTime<FixedOffset>>,
+    pub(crate) suspension_reason: Option<String>,
     pub(crate) created_at: DateTime<FixedOffset>,
     pub(crate) updated_at: DateTime<FixedOffset>,
 }
@@ -98,18 +109,21 @@ impl Organisation {
     where
         C: Executor<'e, Database = Postgres>,
     {
-        let mut query = sqlx::query_as::<_, Self>("SELECT * FROM organisations");
-
-        if let Some(subscription) = &condition.subscription {
-            query = sqlx::query_as::<_, Self>(
-                "SELECT * FROM organisations WHERE subscription_type::text LIKE $1",
-            )
-            .bind(format!("%{subscription}%",));
-        }
-
-        let orgs = query.fetch_all(db).await?;
-
-        Ok(orgs)
+        sqlx::query_as::<_, Self>(
+            "
+            SELECT * FROM organisations
+            WHERE ($1::subscription_type IS NULL OR subscription_type = $1)
+                AND ($2::TEXT IS NULL OR name ILIKE '%' || $2 || '%' OR email ILIKE '%' || $2 || '%')
+                AND ($3::BOOLEAN IS NULL OR (suspended_at IS NOT NULL) = $3)
+            ORDER BY id
+            ",
+        )
+        .bind(condition.subscription.as_ref())
+        .bind(condition.search.as_deref().map(str::trim))
+        .bind(condition.suspended)
+        .fetch_all(db)
+        .await
+        .map_err(Into::into)
     }
 
     pub async fn read_one<'e, C>(db: C, id: i32) -> ModelResult<Self>
@@ -123,6 +137,129 @@ impl Organisation {
             .ok_or_else(|| ModelError::EntityNotFound)
     }
 
+    pub async fn find_by_pid<'e, C>(db: C, pid: Uuid) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_as::<_, Self>("SELECT * FROM organisations WHERE pid = $1")
+            .bind(pid)
+            .fetch_optional(db)
+            .await?
+            .ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    /// Updates the organisation's profile. Fields that are not given are kept.
+    pub async fn update<'e, C>(db: C, pid: Uuid, dto: &UpdateOrganisation<'_>) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        let validator = Validator::new(dto);
+        let dto = validator.validate()?;
+
+        let query = sqlx::query_as::<_, Self>(
+            "
+            UPDATE organisations
+            SET name = COALESCE($2, name),
+            address = COALESCE($3, address),
+            phone = COALESCE($4, phone),
+            email = COALESCE($5, email)
+            WHERE pid = $1 RETURNING *",
+        )
+        .bind(pid)
+        .bind(dto.name.as_deref().map(str::trim))
+        .bind(dto.address.as_deref().map(str::trim))
+        .bind(dto.phone.as_deref().map(str::trim))
+        .bind(dto.email.as_deref().map(str::trim))
+        .fetch_optional(db)
+        .await;
+
+        match query {
+            Ok(Some(org)) => Ok(org),
+            Ok(None) => Err(ModelError::EntityNotFound),
+            Err(sqlx::Error::Database(err))
+                if err.constraint() == Some("organisations_name_key") =>
+            {
+                Err(ModelError::EntityAlreadyExists(
+                    "Organisation with that name already exists".into(),
+                ))
+            }
+            Err(e) => Err(ModelError::Sqlx(e)),
+        }
+    }
+
+    pub async fn change_subscription<'e, C>(
+        db: C,
+        pid: Uuid,
+        subscription: &Subscription,
+    ) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_as::<_, Self>(
+            "UPDATE organisations SET subscription_type = $2 WHERE pid = $1 RETURNING *",
+        )
+        .bind(pid)
+        .bind(subscription)
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    /// Locks the organisation's users out until it is unsuspended.
+    pub async fn suspend<'e, C>(db: C, pid: Uuid, reason: Option<&str>) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_as::<_, Self>(
+            "
+            UPDATE organisations
+            SET suspended_at = NOW(),
+            suspension_reason = $2
+            WHERE pid = $1 RETURNING *",
+        )
+        .bind(pid)
+        .bind(reason)
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    pub async fn unsuspend<'e, C>(db: C, pid: Uuid) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_as::<_, Self>(
+            "
+            UPDATE organisations
+            SET suspended_at = NULL,
+            suspension_reason = NULL
+            WHERE pid = $1 RETURNING *",
+        )
+        .bind(pid)
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    #[must_use]
+    pub const fn pid(&self) -> Uuid {
+        self.pid
+    }
+
+    #[must_use]
+    pub const fn is_suspended(&self) -> bool {
+        self.suspended_at.is_some()
+    }
+
+    /// Organisations registered before subscriptions were mandatory are on
+    /// the basic plan.
+    #[must_use]
+    pub fn subscription(&self) -> Subscription {
+        self.subscription_type
+            .clone()
+            .unwrap_or(Subscription::Basic)
+    }
+
     pub async fn delete_by_id<'e, C>(db: C, id: i32) -> ModelResult<PgQueryResult>
     where
         C: Executor<'e, Database = Postgres>,
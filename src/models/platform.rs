@@ -0,0 +1,124 @@
+#![allow(clippy::missing_errors_doc)]
+
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{Executor, Postgres, prelude::FromRow, types::Json};
+use uuid::Uuid;
+
+use super::ModelResult;
+
+/// What a platform operator did to an organisation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlatformAction {
+    ChangeSubscription,
+    Suspend,
+    Unsuspend,
+    Impersonate,
+    DeleteOrganisation,
+}
+
+impl PlatformAction {
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::ChangeSubscription => "change_subscription",
+            Self::Suspend => "suspend",
+            Self::Unsuspend => "unsuspend",
+            Self::Impersonate => "impersonate",
+            Self::DeleteOrganisation => "delete_organisation",
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct PlatformAuditQuery {
+    pub organisation: Option<Uuid>,
+    pub operator: Option<Uuid>,
+    pub action: Option<PlatformAction>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct PlatformAudit {
+    pub id: i32,
+    pub operator_pid: Option<Uuid>,
+    pub operator_name: Option<String>,
+    pub organisation_pid: Option<Uuid>,
+    pub target_user_pid: Option<Uuid>,
+    pub action: String,
+    pub details: Option<Json<Value>>,
+    pub created_at: DateTime<FixedOffset>,
+}
+
+const DEFAULT_LIMIT: i64 = 100;
+
+impl PlatformAudit {
+    pub async fn record<'e, C>(
+        db: C,
+        operator_pid: Uuid,
+        organisation_pid: Uuid,
+        target_user_pid: Option<Uuid>,
+        action: PlatformAction,
+        details: Value,
+    ) -> ModelResult<()>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query(
+            "
+            INSERT INTO platform_audit_logs
+            (operator_pid, organisation_pid, target_user_pid, action, details)
+            VALUES ($1, $2, $3, $4, $5)
+            ",
+        )
+        .bind(operator_pid)
+        .bind(organisation_pid)
+        .bind(target_user_pid)
+        .bind(action.as_str())
+        .bind(Json(details))
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Operator actions, newest first.
+    pub async fn find_all<'e, C>(db: C, conditions: &PlatformAuditQuery) -> ModelResult<Vec<Self>>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_as::<_, Self>(
+            "
+            SELECT
+                pal.id,
+                pal.operator_pid,
+                NULLIF(CONCAT_WS(' ', u.first_name, u.last_name), '') AS operator_name,
+                pal.organisation_pid,
+                pal.target_user_pid,
+                pal.action,
+                pal.details,
+                pal.created_at
+            FROM
+                platform_audit_logs pal
+            LEFT JOIN
+                users u ON pal.operator_pid = u.pid
+            WHERE
+                ($1::UUID IS NULL OR pal.organisation_pid = $1)
+                AND ($2::UUID IS NULL OR pal.operator_pid = $2)
+                AND ($3::TEXT IS NULL OR pal.action = $3)
+            ORDER BY pal.created_at DESC, pal.id DESC
+            LIMIT $4
+            ",
+        )
+        .bind(conditions.organisation)
+        .bind(conditions.operator)
+        .bind(conditions.action.as_ref().map(PlatformAction::as_str))
+        .bind(conditions.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, 1000))
+        .fetch_all(db)
+        .await
+        .map_err(Into::into)
+    }
+}
@@ -5,7 +5,11 @@ use async_trait::async_trait;
 use chrono::{DateTime, FixedOffset, NaiveDate};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use sqlx::{Encode, Executor, Postgres, postgres::PgQueryResult, prelude::FromRow};
+use sqlx::{
+    Encode, Executor, Postgres,
+    postgres::{PgConnection, PgQueryResult},
+    prelude::FromRow,
+};
 use uuid::Uuid;
 
 use crate::seed::Seedable;
@@ -13,6 +17,7 @@ use crate::seed::Seedable;
 use super::{
     ModelError, ModelResult,
     dto::records::{NewProductionRecord, UpdateProductionRecord},
+    trash::ensure_animal_restored,
 };
 
 #[derive(Debug, Deserialize, Clone)]
@@ -89,6 +94,8 @@ const FETCH_ALL_QUERY: &str = "
         users u ON pr.created_by = u.pid
     WHERE
         pr.organisation_pid = $1
+        AND pr.deleted_at IS NULL
+        AND a.deleted_at IS NULL
 ";
 
 fn fetch_query(conditions: &str) -> String {
@@ -135,12 +142,12 @@ impl ProductionRecord {
     }
 
     pub async fn find_by_id<'a, C>(
-        db: &C,
+        db: C,
         id: i32,
         org_pid: Uuid,
     ) -> ModelResult<ProductionRecordCleaned>
     where
-        for<'e> &'e C: Executor<'a, Database = Postgres>,
+        C: Executor<'a, Database = Postgres>,
     {
         sqlx::query_as::<_, ProductionRecordCleaned>(Box::leak(
             fetch_query("AND pr.id = $2").into_boxed_str(),
@@ -184,7 +191,7 @@ impl ProductionRecord {
                     )
                     VALUES
                     (
-                        (SELECT pid FROM animals a WHERE a.tag_id = $1 AND a.organisation_pid = $2),
+                        (SELECT pid FROM animals a WHERE a.tag_id = $1 AND a.organisation_pid = $2 AND a.deleted_at IS NULL),
                         $2,
                         $3,
                         $4,
@@ -212,16 +219,13 @@ impl ProductionRecord {
         Ok(item)
     }
 
-    pub async fn update_by_id<'e, C>(
-        db: &C,
+    pub async fn update_by_id(
+        db: &mut PgConnection,
         id: i32,
         org_pid: Uuid,
         params: &UpdateProductionRecord<'_>,
-    ) -> ModelResult<Self>
-    where
-        for<'a> &'a C: Executor<'e, Database = Postgres>,
-    {
-        let model = Self::find_by_id(db, id, org_pid).await?;
+    ) -> ModelResult<Self> {
+        let model = Self::find_by_id(&mut *db, id, org_pid).await?;
 
         let product_type = params
             .production_type
@@ -252,7 +256,7 @@ impl ProductionRecord {
                         notes = $7,
                         record_date = $8
                 WHERE
-                    id = $1 AND organisation_pid = $2
+                    id = $1 AND organisation_pid = $2 AND deleted_at IS NULL
                 RETURNING *
             ",
         )
@@ -264,22 +268,46 @@ impl ProductionRecord {
         .bind(quality)
         .bind(notes)
         .bind(record_date)
-        .fetch_one(db)
+        .fetch_one(&mut *db)
         .await?;
 
         Ok(updated)
     }
 
-    pub async fn delete_by_id<'e, C>(db: C, id: i32, org_pid: Uuid) -> ModelResult<PgQueryResult>
+    pub async fn delete_by_id<'e, C>(
+        db: C,
+        id: i32,
+        org_pid: Uuid,
+        user_pid: Uuid,
+    ) -> ModelResult<PgQueryResult>
     where
         C: Executor<'e, Database = Postgres>,
     {
-        let query =
-            sqlx::query("DELETE FROM production_records WHERE id = $1 AND organisation_pid = $2 ")
-                .bind(id)
-                .bind(org_pid)
-                .execute(db)
-                .await?;
+        let query = sqlx::query(
+            "UPDATE production_records SET deleted_at = NOW(), deleted_by = $3
+            WHERE id = $1 AND organisation_pid = $2 AND deleted_at IS NULL",
+        )
+        .bind(id)
+        .bind(org_pid)
+        .bind(user_pid)
+        .execute(db)
+        .await?;
+
+        Ok(query)
+    }
+
+    pub async fn restore_by_id(db: &mut PgConnection, id: i32, org_pid: Uuid) -> ModelResult<Self> {
+        ensure_animal_restored(&mut *db, "production_records", org_pid, id).await?;
+
+        let query = sqlx::query_as::<_, Self>(
+            "UPDATE production_records SET deleted_at = NULL, deleted_by = NULL
+            WHERE id = $1 AND organisation_pid = $2
+            RETURNING *",
+        )
+        .bind(id)
+        .bind(org_pid)
+        .fetch_one(&mut *db)
+        .await?;
 
         Ok(query)
     }
@@ -291,12 +319,12 @@ impl ProductionRecord {
     }
 
     pub async fn find_by_animal<'e, C>(
-        db: &C,
+        db: C,
         org_pid: Uuid,
         animal_pid: Uuid,
     ) -> ModelResult<Vec<ProductionRecordCleaned>>
     where
-        for<'a> &'a C: Executor<'e, Database = Postgres>,
+        C: Executor<'e, Database = Postgres>,
     {
         let query = sqlx::query_as::<_, ProductionRecordCleaned>(Box::leak(
             fetch_query("AND animal_pid = $2").into_boxed_str(),
@@ -308,6 +336,30 @@ impl ProductionRecord {
 
         Ok(query)
     }
+
+    pub async fn find_by_period<'e, C>(
+        db: C,
+        org_pid: Uuid,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> ModelResult<Vec<ProductionRecordCleaned>>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        let query = sqlx::query_as::<_, ProductionRecordCleaned>(Box::leak(
+            fetch_query(
+                "AND pr.record_date BETWEEN $2 AND $3 ORDER BY pr.record_date, b.tag_id, pr.id",
+            )
+            .into_boxed_str(),
+        ))
+        .bind(org_pid)
+        .bind(from)
+        .bind(to)
+        .fetch_all(db)
+        .await?;
+
+        Ok(query)
+    }
 }
 
 #[async_trait]
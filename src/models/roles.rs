@@ -2,18 +2,207 @@
 #![allow(clippy::equatable_if_let)]
 #![allow(clippy::use_self)]
 
+use std::{collections::BTreeSet, fmt, str::FromStr};
+
 use chrono::{DateTime, FixedOffset};
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
-use sqlx::{Encode, Executor, Postgres, prelude::FromRow, types::Json};
+use sqlx::{Encode, Executor, Postgres, postgres::PgQueryResult, prelude::FromRow, types::Json};
+use uuid::Uuid;
+
+use super::{
+    ModelError, ModelResult,
+    dto::{CreateRole, UpdateRole, Validator},
+};
+
+/// A resource a permission can be granted on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Resource {
+    Animals,
+    Breeds,
+    HealthRecords,
+    ProductionRecords,
+    WeightRecords,
+    Feed,
+    Locations,
+    Tasks,
+    Finances,
+    Reports,
+    Users,
+    Roles,
+    AuditLogs,
+    Organisation,
+}
+
+impl Resource {
+    pub const ALL: &'static [Self] = &[
+        Self::Animals,
+        Self::Breeds,
+        Self::HealthRecords,
+        Self::ProductionRecords,
+        Self::WeightRecords,
+        Self::Feed,
+        Self::Locations,
+        Self::Tasks,
+        Self::Finances,
+        Self::Reports,
+        Self::Users,
+        Self::Roles,
+        Self::AuditLogs,
+        Self::Organisation,
+    ];
+
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Animals => "animals",
+            Self::Breeds => "breeds",
+            Self::HealthRecords => "health_records",
+            Self::ProductionRecords => "production_records",
+            Self::WeightRecords => "weight_records",
+            Self::Feed => "feed",
+            Self::Locations => "locations",
+            Self::Tasks => "tasks",
+            Self::Finances => "finances",
+            Self::Reports => "reports",
+            Self::Users => "users",
+            Self::Roles => "roles",
+            Self::AuditLogs => "audit_logs",
+            Self::Organisation => "organisation",
+        }
+    }
+
+    /// The actions that can be granted on the resource.
+    #[must_use]
+    pub fn actions(&self) -> &'static [Action] {
+        match self {
+            Self::Reports => &[Action::Read, Action::Generate],
+            Self::Users | Self::Roles | Self::Organisation => &[Action::Read, Action::Manage],
+            Self::AuditLogs => &[Action::Read],
+            _ => &[Action::Read, Action::Write, Action::Delete, Action::Manage],
+        }
+    }
+}
+
+impl FromStr for Resource {
+    type Err = ModelError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .find(|resource| resource.as_str() == s)
+            .copied()
+            .ok_or_else(|| ModelError::Validation(format!("Unknown resource {s}")))
+    }
+}
+
+/// An action that can be performed on a [`Resource`]. `Manage` implies every
+/// other action on the same resource.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Read,
+    Write,
+    Delete,
+    Generate,
+    Manage,
+}
+
+impl Action {
+    pub const ALL: &'static [Self] = &[
+        Self::Read,
+        Self::Write,
+        Self::Delete,
+        Self::Generate,
+        Self::Manage,
+    ];
+
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Read => "read",
+            Self::Write => "write",
+            Self::Delete => "delete",
+            Self::Generate => "generate",
+            Self::Manage => "manage",
+        }
+    }
+}
+
+impl FromStr for Action {
+    type Err = ModelError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .find(|action| action.as_str() == s)
+            .copied()
+            .ok_or_else(|| ModelError::Validation(format!("Unknown action {s}")))
+    }
+}
+
+/// A single grant written as `resource:action`, e.g. `health_records:write`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Scope {
+    pub resource: Resource,
+    pub action: Action,
+}
+
+impl Scope {
+    #[must_use]
+    pub fn new(resource: Resource, action: Action) -> Self {
+        Self { resource, action }
+    }
+
+    /// Every scope that can be granted to a custom role.
+    #[must_use]
+    pub fn catalogue() -> Vec<Self> {
+        Resource::ALL
+            .iter()
+            .flat_map(|resource| {
+                resource
+                    .actions()
+                    .iter()
+                    .map(|action| Self::new(*resource, *action))
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.resource.as_str(), self.action.as_str())
+    }
+}
+
+impl FromStr for Scope {
+    type Err = ModelError;
 
-use super::{ModelError, ModelResult};
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (resource, action) = s
+            .trim()
+            .split_once(':')
+            .ok_or_else(|| ModelError::Validation(format!("Invalid permission {s}")))?;
 
-/// Represents [`User`] permissions i.e Read, Write and Delete
+        let resource: Resource = resource.parse()?;
+        let action: Action = action.parse()?;
+
+        if !resource.actions().contains(&action) {
+            return Err(ModelError::Validation(format!("Invalid permission {s}")));
+        }
+
+        Ok(Self::new(resource, action))
+    }
+}
+
+/// Represents [`User`] permissions i.e Read, Write and Delete, plus any
+/// resource-level [`Scope`]s granted to custom roles.
 #[derive(Debug, Deserialize, Clone, Serialize)]
 #[serde(from = "PermissionJson", into = "PermissionJson")]
 pub struct Permission {
     permissions: u8,
+    scopes: BTreeSet<Scope>,
 }
 
 impl Permission {
@@ -25,12 +214,26 @@ impl Permission {
 
     #[must_use]
     pub fn new() -> Self {
-        Self { permissions: 0 }
+        Self {
+            permissions: 0,
+            scopes: BTreeSet::new(),
+        }
     }
 
     #[must_use]
     pub fn from_bits(bits: u8) -> Self {
-        Self { permissions: bits }
+        Self {
+            permissions: bits,
+            scopes: BTreeSet::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn from_scopes<I: IntoIterator<Item = Scope>>(scopes: I) -> Self {
+        Self {
+            permissions: 0,
+            scopes: scopes.into_iter().collect(),
+        }
     }
 
     fn has_permission(&self, flag: u8) -> bool {
@@ -102,6 +305,37 @@ impl Permission {
     pub fn admin() -> Self {
         Self {
             permissions: Self::PERMISSION_ALL,
+            scopes: BTreeSet::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn scopes(&self) -> &BTreeSet<Scope> {
+        &self.scopes
+    }
+
+    /// Checks whether `action` may be performed on `resource`, either through
+    /// an explicit scope or through the role-wide read/write/delete flags.
+    #[must_use]
+    pub fn allows(&self, resource: Resource, action: Action) -> bool {
+        if self.has_all_permissions()
+            || self.scopes.contains(&Scope::new(resource, action))
+            || self.scopes.contains(&Scope::new(resource, Action::Manage))
+        {
+            return true;
+        }
+
+        match (resource, action) {
+            // Everyone reads the settings the other modules default from.
+            (Resource::Organisation, Action::Read) => self.can_read(),
+            (
+                Resource::Users | Resource::Roles | Resource::AuditLogs | Resource::Organisation,
+                _,
+            ) => self.can_manage_users(),
+            (_, Action::Read) => self.can_read(),
+            (_, Action::Write | Action::Generate) => self.can_write(),
+            (_, Action::Delete) => self.can_delete(),
+            (_, Action::Manage) => self.can_write() && self.can_delete(),
         }
     }
 }
@@ -137,6 +371,14 @@ impl From<Map<String, Value>> for Permission {
             permission.set_manage_users(*value);
         }
 
+        if let Some(Value::Array(scopes)) = map.get("scopes") {
+            permission.scopes = scopes
+                .iter()
+                .filter_map(Value::as_str)
+                .filter_map(|scope| scope.parse().ok())
+                .collect();
+        }
+
         permission
     }
 }
@@ -158,6 +400,19 @@ impl From<Permission> for Map<String, Value> {
             Value::Bool(permission.can_manage_users()),
         );
 
+        if !permission.scopes.is_empty() {
+            map.insert(
+                "scopes".into(),
+                Value::Array(
+                    permission
+                        .scopes
+                        .iter()
+                        .map(|scope| Value::String(scope.to_string()))
+                        .collect(),
+                ),
+            );
+        }
+
         map
     }
 }
@@ -174,6 +429,8 @@ pub struct PermissionJson {
     delete: Option<bool>,
     #[serde(default)]
     manage_users: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    scopes: Option<Vec<String>>,
 }
 
 impl From<PermissionJson> for Permission {
@@ -199,6 +456,13 @@ impl From<PermissionJson> for Permission {
         if let Some(value) = pj.manage_users {
             permission.set_manage_users(value);
         }
+
+        if let Some(scopes) = pj.scopes {
+            permission.scopes = scopes
+                .iter()
+                .filter_map(|scope| scope.parse().ok())
+                .collect();
+        }
         permission
     }
 }
@@ -212,6 +476,7 @@ impl From<Permission> for PermissionJson {
                 write: None,
                 delete: None,
                 manage_users: None,
+                scopes: None,
             };
         }
 
@@ -221,6 +486,8 @@ impl From<Permission> for PermissionJson {
             write: Some(permission.can_write()),
             delete: Some(permission.can_delete()),
             manage_users: Some(permission.can_manage_users()),
+            scopes: (!permission.scopes.is_empty())
+                .then(|| permission.scopes.iter().map(ToString::to_string).collect()),
         }
     }
 }
@@ -232,6 +499,7 @@ pub struct Role {
     pub(crate) permissions: Json<Permission>,
     pub(crate) description: Option<String>,
     pub(crate) created_at: DateTime<FixedOffset>,
+    pub(crate) organisation_pid: Option<Uuid>,
 }
 
 impl Role {
@@ -255,7 +523,7 @@ impl Role {
         query.ok_or_else(|| ModelError::EntityNotFound)
     }
 
-    /// Fetches all Roles from the database.
+    /// Fetches all system Roles from the database.
     ///
     /// # Errors
     ///
@@ -266,13 +534,13 @@ impl Role {
     where
         C: Executor<'e, Database = Postgres>,
     {
-        sqlx::query_as::<_, Self>("SELECT * FROM roles")
+        sqlx::query_as::<_, Self>("SELECT * FROM roles WHERE organisation_pid IS NULL ORDER BY id")
             .fetch_all(db)
             .await
             .map_err(Into::into)
     }
 
-    /// Fetches a Role by its name from the database.
+    /// Fetches a system Role by its name from the database.
     ///
     /// # Errors
     ///
@@ -284,14 +552,214 @@ impl Role {
     where
         C: Executor<'e, Database = Postgres>,
     {
-        let query = sqlx::query_as::<_, Self>("SELECT * FROM roles WHERE name = $1")
-            .bind(name)
-            .fetch_optional(db)
-            .await?;
+        let query = sqlx::query_as::<_, Self>(
+            "SELECT * FROM roles WHERE name = $1 AND organisation_pid IS NULL",
+        )
+        .bind(name)
+        .fetch_optional(db)
+        .await?;
 
         query.ok_or_else(|| ModelError::EntityNotFound)
     }
 
+    /// Fetches the Role named `name` that is visible to an organisation, i.e a
+    /// system role or one of the organisation's custom roles.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if .
+    /// * Database connection fails.
+    /// * Query execution fails.
+    /// * The Role does not exist.
+    pub async fn find_for_organisation<'e, C>(db: C, org_pid: Uuid, name: &str) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_as::<_, Self>(
+            "SELECT * FROM roles WHERE name = $1 AND (organisation_pid IS NULL OR organisation_pid = $2)",
+        )
+        .bind(name)
+        .bind(org_pid)
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    /// Fetches a Role by its ID if it is visible to the organisation.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if .
+    /// * Database connection fails.
+    /// * Query execution fails.
+    /// * The Role does not exist.
+    pub async fn find_by_id_for_organisation<'e, C>(
+        db: C,
+        org_pid: Uuid,
+        id: i32,
+    ) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_as::<_, Self>(
+            "SELECT * FROM roles WHERE id = $1 AND (organisation_pid IS NULL OR organisation_pid = $2)",
+        )
+        .bind(id)
+        .bind(org_pid)
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    /// Fetches the system roles followed by the organisation's custom roles.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if .
+    /// * Database connection fails.
+    /// * Query execution fails.
+    pub async fn find_by_organisation<'e, C>(db: C, org_pid: Uuid) -> ModelResult<Vec<Self>>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_as::<_, Self>(
+            "SELECT * FROM roles WHERE organisation_pid IS NULL OR organisation_pid = $1
+            ORDER BY organisation_pid NULLS FIRST, id",
+        )
+        .bind(org_pid)
+        .fetch_all(db)
+        .await
+        .map_err(Into::into)
+    }
+
+    /// Fetches a custom role owned by the organisation. System roles are not
+    /// returned since they cannot be modified.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if .
+    /// * Database connection fails.
+    /// * Query execution fails.
+    /// * The Role does not exist or is a system role.
+    pub async fn find_custom<'e, C>(db: C, org_pid: Uuid, id: i32) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_as::<_, Self>("SELECT * FROM roles WHERE id = $1 AND organisation_pid = $2")
+            .bind(id)
+            .bind(org_pid)
+            .fetch_optional(db)
+            .await?
+            .ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    /// Creates a custom role for the organisation.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if .
+    /// * The input fails validation.
+    /// * The name is taken by a system role or another role of the organisation.
+    /// * Database connection or query execution fails.
+    pub async fn create<'e, C>(db: &C, org_pid: Uuid, dto: &CreateRole<'_>) -> ModelResult<Self>
+    where
+        for<'a> &'a C: Executor<'e, Database = Postgres>,
+    {
+        let validator = Validator::new(dto);
+        let dto = validator.validate()?;
+        let name = dto.name.trim().to_lowercase();
+
+        if Self::find_for_organisation(db, org_pid, &name)
+            .await
+            .is_ok()
+        {
+            return Err(ModelError::EntityAlreadyExists(format!(
+                "A role named {name} already exists"
+            )));
+        }
+
+        let permission = Permission::from_scopes(dto.scopes()?);
+
+        sqlx::query_as::<_, Self>(
+            "INSERT INTO roles (organisation_pid, name, description, permissions)
+            VALUES ($1, $2, $3, $4) RETURNING *",
+        )
+        .bind(org_pid)
+        .bind(name)
+        .bind(dto.description.as_deref())
+        .bind(Json(permission))
+        .fetch_one(db)
+        .await
+        .map_err(Into::into)
+    }
+
+    /// Updates the description and/or the permissions of a custom role.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if .
+    /// * The input fails validation.
+    /// * Database connection or query execution fails.
+    pub async fn update<'e, C>(&self, db: C, dto: &UpdateRole<'_>) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        let validator = Validator::new(dto);
+        let dto = validator.validate()?;
+
+        let permission = dto
+            .scopes()?
+            .map_or_else(|| self.permissions.0.clone(), Permission::from_scopes);
+
+        sqlx::query_as::<_, Self>(
+            "UPDATE roles SET description = COALESCE($1, description), permissions = $2
+            WHERE id = $3 AND organisation_pid = $4 RETURNING *",
+        )
+        .bind(dto.description.as_deref())
+        .bind(Json(permission))
+        .bind(self.id)
+        .bind(self.organisation_pid)
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    /// Deletes a custom role of the organisation.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if .
+    /// * The role does not exist or is a system role.
+    /// * The role is still assigned to users.
+    /// * Database connection or query execution fails.
+    pub async fn delete_by_id<'e, C>(db: &C, org_pid: Uuid, id: i32) -> ModelResult<PgQueryResult>
+    where
+        for<'a> &'a C: Executor<'e, Database = Postgres>,
+    {
+        let role = Self::find_custom(db, org_pid, id).await?;
+
+        let (assigned,): (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM memberships WHERE role = $1 AND organisation_pid = $2",
+        )
+        .bind(&role.name)
+        .bind(org_pid)
+        .fetch_one(db)
+        .await?;
+
+        if assigned > 0 {
+            return Err(ModelError::Conflict(
+                "Role is assigned to users, reassign them before deleting it".into(),
+            ));
+        }
+
+        sqlx::query("DELETE FROM roles WHERE id = $1 AND organisation_pid = $2")
+            .bind(id)
+            .bind(org_pid)
+            .execute(db)
+            .await
+            .map_err(Into::into)
+    }
+
     #[must_use]
     pub fn id(&self) -> i32 {
         self.id
@@ -338,4 +806,19 @@ impl Role {
     pub fn can_manage_users(&self) -> bool {
         self.permissions.can_manage_users()
     }
+
+    #[must_use]
+    pub fn organisation_pid(&self) -> Option<Uuid> {
+        self.organisation_pid
+    }
+
+    #[must_use]
+    pub fn is_system_defined(&self) -> bool {
+        self.organisation_pid.is_none()
+    }
+
+    #[must_use]
+    pub fn allows(&self, resource: Resource, action: Action) -> bool {
+        self.permissions.allows(resource, action)
+    }
 }
@@ -0,0 +1,339 @@
+#![allow(clippy::missing_errors_doc)]
+
+use chrono::{FixedOffset, NaiveDate, Offset, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{Executor, Postgres, types::Json};
+use uuid::Uuid;
+
+use super::{ModelError, ModelResult};
+
+/// Date formats the frontend and reports know how to render.
+pub const DATE_FORMATS: [&str; 4] = ["%d-%m-%Y", "%Y-%m-%d", "%m/%d/%Y", "%d/%m/%Y"];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WeightUnit {
+    #[default]
+    Kg,
+    Lb,
+}
+
+impl WeightUnit {
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Kg => "kg",
+            Self::Lb => "lb",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VolumeUnit {
+    #[default]
+    Litres,
+    Gallons,
+}
+
+impl VolumeUnit {
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Litres => "litres",
+            Self::Gallons => "gallons",
+        }
+    }
+}
+
+/// Rules new and renamed animals' tag IDs must follow.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TagIdRules {
+    pub prefix: Option<String>,
+    pub min_length: usize,
+    pub max_length: usize,
+    /// Only uppercase letters, digits and dashes.
+    pub uppercase: bool,
+}
+
+impl Default for TagIdRules {
+    fn default() -> Self {
+        Self {
+            prefix: None,
+            min_length: 1,
+            max_length: 50,
+            uppercase: false,
+        }
+    }
+}
+
+impl TagIdRules {
+    pub fn check(&self, tag_id: &str) -> ModelResult<()> {
+        let tag_id = tag_id.trim();
+        let length = tag_id.chars().count();
+
+        if length < self.min_length || length > self.max_length {
+            return Err(ModelError::Validation(format!(
+                "Tag ID must have {}-{} characters",
+                self.min_length, self.max_length
+            )));
+        }
+
+        if let Some(prefix) = &self.prefix
+            && !tag_id.starts_with(prefix.as_str())
+        {
+            return Err(ModelError::Validation(format!(
+                "Tag ID must start with {prefix}"
+            )));
+        }
+
+        if self.uppercase
+            && !tag_id
+                .chars()
+                .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '-')
+        {
+            return Err(ModelError::Validation(
+                "Tag ID may only contain uppercase letters, digits and dashes".into(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// How far an animal's mass may move from its previous weighing before the
+/// record is classified as under or overweight.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct WeightThresholds {
+    pub underweight_loss_percent: Decimal,
+    pub overweight_gain_percent: Decimal,
+}
+
+impl Default for WeightThresholds {
+    fn default() -> Self {
+        Self {
+            underweight_loss_percent: Decimal::new(10, 0),
+            overweight_gain_percent: Decimal::new(25, 0),
+        }
+    }
+}
+
+impl WeightThresholds {
+    /// Classifies `mass` against the previous weighing. A first weighing is
+    /// always normal.
+    #[must_use]
+    pub fn classify(&self, previous_mass: Option<Decimal>, mass: Decimal) -> &'static str {
+        let Some(previous) = previous_mass.filter(|previous| !previous.is_zero()) else {
+            return "normal";
+        };
+
+        let change = (mass - previous) / previous * Decimal::ONE_HUNDRED;
+
+        if change <= -self.underweight_loss_percent {
+            "underweight"
+        } else if change >= self.overweight_gain_percent {
+            "overweight"
+        } else {
+            "normal"
+        }
+    }
+}
+
+/// How printed reports are branded.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Branding {
+    /// The accent colour of report headers, as `#RRGGBB`.
+    pub colour: String,
+    /// A line printed at the foot of every page, e.g. a registration number.
+    pub footer: Option<String>,
+}
+
+impl Default for Branding {
+    fn default() -> Self {
+        Self {
+            colour: "#2E7D32".into(),
+            footer: None,
+        }
+    }
+}
+
+impl Branding {
+    /// The accent colour as red, green and blue between 0 and 1.
+    #[must_use]
+    pub fn rgb(&self) -> Option<(f32, f32, f32)> {
+        let hex = self.colour.strip_prefix('#')?;
+        if hex.len() != 6 {
+            return None;
+        }
+
+        let channel = |i: usize| {
+            u8::from_str_radix(hex.get(i..i + 2)?, 16)
+                .ok()
+                .map(|c| f32::from(c) / 255.0)
+        };
+
+        Some((channel(0)?, channel(2)?, channel(4)?))
+    }
+}
+
+/// An organisation's preferences. Missing keys fall back to their defaults so
+/// organisations that never saved settings behave as before.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct OrganisationSettings {
+    /// `UTC` or a fixed offset such as `+03:00`.
+    pub timezone: String,
+    /// ISO 4217 code, e.g. `KES`.
+    pub currency: String,
+    pub weight_unit: WeightUnit,
+    pub volume_unit: VolumeUnit,
+    pub date_format: String,
+    pub tag_id: TagIdRules,
+    pub weight_status: WeightThresholds,
+    pub branding: Branding,
+}
+
+impl Default for OrganisationSettings {
+    fn default() -> Self {
+        Self {
+            timezone: "UTC".into(),
+            currency: "USD".into(),
+            weight_unit: WeightUnit::default(),
+            volume_unit: VolumeUnit::default(),
+            date_format: DATE_FORMATS[0].into(),
+            tag_id: TagIdRules::default(),
+            weight_status: WeightThresholds::default(),
+            branding: Branding::default(),
+        }
+    }
+}
+
+impl OrganisationSettings {
+    pub async fn find<'e, C>(db: C, org_pid: Uuid) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_scalar::<_, Json<Self>>("SELECT settings FROM organisations WHERE pid = $1")
+            .bind(org_pid)
+            .fetch_optional(db)
+            .await?
+            .map(|settings| settings.0)
+            .ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    pub async fn save<'e, C>(&self, db: C, org_pid: Uuid) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        self.validate()?;
+
+        sqlx::query_scalar::<_, Json<Self>>(
+            "UPDATE organisations SET settings = $2 WHERE pid = $1 RETURNING settings",
+        )
+        .bind(org_pid)
+        .bind(Json(self))
+        .fetch_optional(db)
+        .await?
+        .map(|settings| settings.0)
+        .ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    /// Applies the keys present in `changes` on top of these settings.
+    /// Nested objects are replaced as a whole.
+    pub fn merge(&self, changes: Value) -> ModelResult<Self> {
+        let Value::Object(changes) = changes else {
+            return Err(ModelError::Validation("Settings must be an object".into()));
+        };
+
+        let mut settings =
+            serde_json::to_value(self).map_err(|e| ModelError::Validation(e.to_string()))?;
+
+        if let Value::Object(current) = &mut settings {
+            current.extend(changes);
+        }
+
+        serde_json::from_value(settings).map_err(|e| ModelError::Validation(e.to_string()))
+    }
+
+    pub fn validate(&self) -> ModelResult<()> {
+        if self.offset().is_none() {
+            return Err(ModelError::Validation(
+                "Timezone must be UTC or an offset such as +03:00".into(),
+            ));
+        }
+
+        if self.currency.len() != 3 || !self.currency.chars().all(|c| c.is_ascii_uppercase()) {
+            return Err(ModelError::Validation(
+                "Currency must be a three letter ISO code".into(),
+            ));
+        }
+
+        if !DATE_FORMATS.contains(&self.date_format.as_str()) {
+            return Err(ModelError::Validation(format!(
+                "Date format must be one of {}",
+                DATE_FORMATS.join(", ")
+            )));
+        }
+
+        if self.tag_id.min_length == 0
+            || self.tag_id.min_length > self.tag_id.max_length
+            || self.tag_id.max_length > 50
+        {
+            return Err(ModelError::Validation(
+                "Tag ID lengths must be between 1 and 50".into(),
+            ));
+        }
+
+        if self.weight_status.underweight_loss_percent <= Decimal::ZERO
+            || self.weight_status.overweight_gain_percent <= Decimal::ZERO
+        {
+            return Err(ModelError::Validation(
+                "Weight thresholds must be positive percentages".into(),
+            ));
+        }
+
+        if self.branding.rgb().is_none() {
+            return Err(ModelError::Validation(
+                "Branding colour must be a hex colour such as #2E7D32".into(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn offset(&self) -> Option<FixedOffset> {
+        let timezone = self.timezone.trim();
+
+        if timezone.eq_ignore_ascii_case("UTC") {
+            return FixedOffset::east_opt(0);
+        }
+
+        let (sign, rest) = match timezone.split_at_checked(1)? {
+            ("+", rest) => (1, rest),
+            ("-", rest) => (-1, rest),
+            _ => return None,
+        };
+        let (hours, minutes) = rest.split_once(':')?;
+        let hours = hours.parse::<i32>().ok().filter(|h| (0..=14).contains(h))?;
+        let minutes = minutes
+            .parse::<i32>()
+            .ok()
+            .filter(|m| (0..60).contains(m))?;
+
+        FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+    }
+
+    /// Today's date in the organisation's timezone.
+    #[must_use]
+    pub fn today(&self) -> NaiveDate {
+        let offset = self.offset().unwrap_or_else(|| Utc.fix());
+
+        Utc::now().with_timezone(&offset).date_naive()
+    }
+}
@@ -170,6 +170,36 @@ impl BreedSummary {
 
         Ok(query)
     }
+
+    /// The most recent summary of each breed matching `params`.
+    pub async fn find_latest<'e, C>(
+        db: &C,
+        org_pid: Uuid,
+        params: &BreedSummaryQuery,
+    ) -> ModelResult<Vec<Self>>
+    where
+        for<'a> &'a C: Executor<'e, Database = Postgres>,
+    {
+        let query = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT * FROM (
+                SELECT DISTINCT ON (bs.breed_id) * FROM breed_summary bs
+                WHERE bs.organisation_pid = $1
+                    AND ($2::TEXT IS NULL OR bs.specie_name ILIKE $2)
+                    AND ($3::TEXT IS NULL OR bs.breed_name ILIKE $3)
+                ORDER BY bs.breed_id, bs.created_at DESC, bs.id DESC
+            ) latest
+            ORDER BY specie_name, breed_name
+        "#,
+        )
+        .bind(org_pid)
+        .bind(&params.specie)
+        .bind(&params.breed)
+        .fetch_all(db)
+        .await?;
+
+        Ok(query)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Encode, FromRow)]
@@ -209,7 +239,7 @@ impl BreedSummaryExtract {
                 COUNT(*)                                                AS      total,
                 COUNT(*)    FILTER (WHERE gender = 'male')              AS      males,
                 COUNT(*)    FILTER (WHERE gender = 'female')            AS      females,
-                COUNT(*)    FILTER (WHERE gender = 'unkown')            AS      unkown_gender,
+                COUNT(*)    FILTER (WHERE COALESCE(gender, 'unknown') = 'unknown') AS      unkown_gender,
                 COUNT(*)    FILTER (WHERE status = 'active')            AS      active,
                 COUNT(*)    FILTER (WHERE status = 'sold')              AS      sold,
                 COUNT(*)    FILTER (WHERE status = 'transferred')       AS      transferred,
@@ -235,6 +265,8 @@ impl BreedSummaryExtract {
             FROM animals
             WHERE
                 organisation_pid = $1
+            AND
+                deleted_at IS NULL
             AND
                 breed_id = (SELECT id FROM breeds b WHERE b.name ILIKE $2)
             AND
@@ -0,0 +1,228 @@
+#![allow(clippy::missing_errors_doc)]
+
+use chrono::{DateTime, FixedOffset};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{Executor, Postgres, prelude::FromRow};
+use uuid::Uuid;
+
+use super::BreedSummaryQuery;
+use crate::models::ModelResult;
+
+/// The sums over `animal_counts` every live summary is made of. The average
+/// age is worked out from the dates of birth on the day it is read.
+const COLUMNS: &str = r"
+    COALESCE(SUM(c.total), 0)::BIGINT                   AS  total,
+    COALESCE(SUM(c.males), 0)::BIGINT                   AS  males,
+    COALESCE(SUM(c.females), 0)::BIGINT                 AS  females,
+    COALESCE(SUM(c.unknown_gender), 0)::BIGINT          AS  unknown_gender,
+    COALESCE(SUM(c.active), 0)::BIGINT                  AS  active,
+    COALESCE(SUM(c.transferred), 0)::BIGINT             AS  transferred,
+    COALESCE(SUM(c.sold), 0)::BIGINT                    AS  sold,
+    COALESCE(SUM(c.deceased), 0)::BIGINT                AS  deceased,
+    COALESCE(SUM(c.purchase_value), 0)                  AS  total_purchase_value,
+    ROUND(SUM(c.weight_male) / NULLIF(SUM(c.weighed_males), 0), 2)
+                                                        AS  average_weight_male,
+    ROUND(SUM(c.weight_female) / NULLIF(SUM(c.weighed_females), 0), 2)
+                                                        AS  average_weight_female,
+    ROUND(SUM(c.birth_weight_male) / NULLIF(SUM(c.birth_weighed_males), 0), 2)
+                                                        AS  average_birth_weight_male,
+    ROUND(SUM(c.birth_weight_female) / NULLIF(SUM(c.birth_weighed_females), 0), 2)
+                                                        AS  average_birth_weight_female,
+    ROUND(
+        ((CURRENT_DATE - DATE '1970-01-01') - SUM(c.birth_days) / NULLIF(SUM(c.born), 0))
+        / 30.4375,
+        2
+    )                                                   AS  average_age_months,
+    MAX(c.updated_at)                                   AS  updated_at
+";
+
+/// A summary of the animals as they are now, kept up to date as animals
+/// change rather than generated. Unlike the summaries it is not stored, so
+/// it has no `pid`.
+#[derive(Debug, Deserialize, Serialize, FromRow, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LiveSummary {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub specie_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub breed_name: Option<String>,
+    /// How many species are kept, in the livestock summary.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub species: Option<i64>,
+    /// How many breeds are kept, in the livestock and species summaries.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub breeds: Option<i64>,
+    pub total: i64,
+    pub males: i64,
+    pub females: i64,
+    pub unknown_gender: i64,
+    pub active: i64,
+    pub transferred: i64,
+    pub sold: i64,
+    pub deceased: i64,
+    pub total_purchase_value: Decimal,
+    pub average_weight_male: Option<Decimal>,
+    pub average_weight_female: Option<Decimal>,
+    pub average_birth_weight_male: Option<Decimal>,
+    pub average_birth_weight_female: Option<Decimal>,
+    pub average_age_months: Option<Decimal>,
+    /// When an animal last changed the summary.
+    pub updated_at: Option<DateTime<FixedOffset>>,
+}
+
+impl LiveSummary {
+    /// All of the organisation's animals.
+    pub async fn find_livestock<'e, C>(db: &C, org_pid: Uuid) -> ModelResult<Self>
+    where
+        for<'a> &'a C: Executor<'e, Database = Postgres>,
+    {
+        let summary = sqlx::query_as::<_, Self>(&format!(
+            r"
+            SELECT
+                NULL::TEXT                                          AS  specie_name,
+                NULL::TEXT                                          AS  breed_name,
+                COUNT(DISTINCT c.specie_id) FILTER (WHERE c.total > 0) AS species,
+                COUNT(DISTINCT c.breed_id) FILTER (WHERE c.total > 0) AS breeds,
+                {COLUMNS}
+            FROM animal_counts c
+            WHERE c.organisation_pid = $1
+            "
+        ))
+        .bind(org_pid)
+        .fetch_one(db)
+        .await?;
+
+        Ok(summary)
+    }
+
+    /// One summary for each specie kept, or only for `specie`.
+    pub async fn find_species<'e, C>(
+        db: &C,
+        org_pid: Uuid,
+        specie: Option<&str>,
+    ) -> ModelResult<Vec<Self>>
+    where
+        for<'a> &'a C: Executor<'e, Database = Postgres>,
+    {
+        let summaries = sqlx::query_as::<_, Self>(&format!(
+            r"
+            SELECT
+                s.name::TEXT                                        AS  specie_name,
+                NULL::TEXT                                          AS  breed_name,
+                NULL::BIGINT                                        AS  species,
+                COUNT(DISTINCT c.breed_id) FILTER (WHERE c.total > 0) AS breeds,
+                {COLUMNS}
+            FROM animal_counts c
+            JOIN species s ON s.id = c.specie_id
+            WHERE c.organisation_pid = $1
+                AND ($2::TEXT IS NULL OR s.name ILIKE $2)
+            GROUP BY s.name
+            HAVING SUM(c.total) > 0
+            ORDER BY s.name
+            "
+        ))
+        .bind(org_pid)
+        .bind(specie)
+        .fetch_all(db)
+        .await?;
+
+        Ok(summaries)
+    }
+
+    /// One summary for each breed kept, of any specie or of the one asked for.
+    pub async fn find_breeds<'e, C>(
+        db: &C,
+        org_pid: Uuid,
+        params: &BreedSummaryQuery,
+    ) -> ModelResult<Vec<Self>>
+    where
+        for<'a> &'a C: Executor<'e, Database = Postgres>,
+    {
+        let summaries = sqlx::query_as::<_, Self>(&format!(
+            r"
+            SELECT
+                s.name::TEXT                                        AS  specie_name,
+                b.name::TEXT                                        AS  breed_name,
+                NULL::BIGINT                                        AS  species,
+                NULL::BIGINT                                        AS  breeds,
+                {COLUMNS}
+            FROM animal_counts c
+            JOIN species s ON s.id = c.specie_id
+            JOIN breeds b ON b.id = c.breed_id
+            WHERE c.organisation_pid = $1
+                AND ($2::TEXT IS NULL OR s.name ILIKE $2)
+                AND ($3::TEXT IS NULL OR b.name ILIKE $3)
+            GROUP BY s.name, b.name
+            HAVING SUM(c.total) > 0
+            ORDER BY s.name, b.name
+            "
+        ))
+        .bind(org_pid)
+        .bind(params.specie.as_deref())
+        .bind(params.breed.as_deref())
+        .fetch_all(db)
+        .await?;
+
+        Ok(summaries)
+    }
+
+    /// The animals given, counted from the animals themselves rather than
+    /// the running counts. Animals in the trash are left out.
+    pub async fn find_animals<'e, C>(
+        db: &C,
+        org_pid: Uuid,
+        animal_pids: &[Uuid],
+    ) -> ModelResult<Self>
+    where
+        for<'a> &'a C: Executor<'e, Database = Postgres>,
+    {
+        let summary = sqlx::query_as::<_, Self>(&format!(
+            r"
+            SELECT
+                NULL::TEXT                                          AS  specie_name,
+                NULL::TEXT                                          AS  breed_name,
+                COUNT(DISTINCT c.specie_id)                         AS  species,
+                COUNT(DISTINCT c.breed_id)                          AS  breeds,
+                {COLUMNS}
+            FROM (
+                SELECT
+                    a.specie_id,
+                    a.breed_id,
+                    1 AS total,
+                    (COALESCE(a.gender, 'unknown') = 'male')::INT AS males,
+                    (COALESCE(a.gender, 'unknown') = 'female')::INT AS females,
+                    (COALESCE(a.gender, 'unknown') = 'unknown')::INT AS unknown_gender,
+                    (a.status = 'active')::INT AS active,
+                    (a.status = 'transferred')::INT AS transferred,
+                    (a.status = 'sold')::INT AS sold,
+                    (a.status = 'deceased')::INT AS deceased,
+                    COALESCE(a.purchase_price, 0) AS purchase_value,
+                    CASE WHEN a.gender = 'male' THEN a.current_weight END AS weight_male,
+                    (a.gender = 'male' AND a.current_weight IS NOT NULL)::INT AS weighed_males,
+                    CASE WHEN a.gender = 'female' THEN a.current_weight END AS weight_female,
+                    (a.gender = 'female' AND a.current_weight IS NOT NULL)::INT AS weighed_females,
+                    CASE WHEN a.gender = 'male' THEN a.weight_at_birth END AS birth_weight_male,
+                    (a.gender = 'male' AND a.weight_at_birth IS NOT NULL)::INT
+                                                                AS  birth_weighed_males,
+                    CASE WHEN a.gender = 'female' THEN a.weight_at_birth END AS birth_weight_female,
+                    (a.gender = 'female' AND a.weight_at_birth IS NOT NULL)::INT
+                                                                AS  birth_weighed_females,
+                    a.date_of_birth - DATE '1970-01-01' AS birth_days,
+                    (a.date_of_birth IS NOT NULL)::INT AS born,
+                    a.updated_at
+                FROM animals a
+                WHERE a.organisation_pid = $1
+                    AND a.pid = ANY($2)
+                    AND a.deleted_at IS NULL
+            ) c
+            "
+        ))
+        .bind(org_pid)
+        .bind(animal_pids)
+        .fetch_one(db)
+        .await?;
+
+        Ok(summary)
+    }
+}
@@ -1,4 +1,6 @@
 mod breeds;
+mod live;
 mod species;
+mod subjects;
 
-pub use self::{breeds::*, species::*};
+pub use self::{breeds::*, live::*, species::*, subjects::*};
@@ -119,6 +119,30 @@ impl SpecieSummary {
 
         Ok(reports)
     }
+
+    /// The most recent summary of each specie, or only of `specie`.
+    pub async fn find_latest<'e, C>(
+        db: &C,
+        org_pid: Uuid,
+        specie: Option<&str>,
+    ) -> ModelResult<Vec<Self>>
+    where
+        for<'a> &'a C: Executor<'e, Database = Postgres>,
+    {
+        let reports = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT DISTINCT ON (specie_id) * FROM species_summary
+            WHERE organisation_pid = $1 AND ($2::TEXT IS NULL OR specie_name ILIKE $2)
+            ORDER BY specie_id, created_at DESC, id DESC
+        "#,
+        )
+        .bind(org_pid)
+        .bind(specie)
+        .fetch_all(db)
+        .await?;
+
+        Ok(reports)
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Encode, FromRow, Clone)]
@@ -152,7 +176,7 @@ impl SpecieSummaryExtract {
                 COUNT(*)                                                AS      total,
                 COUNT(*)    FILTER (WHERE gender = 'male')              AS      males,
                 COUNT(*)    FILTER (WHERE gender = 'female')            AS      females,
-                COUNT(*)    FILTER (WHERE gender = 'unkown')            AS      unkown_gender,
+                COUNT(*)    FILTER (WHERE COALESCE(gender, 'unknown') = 'unknown') AS      unkown_gender,
                 COUNT(*)    FILTER (WHERE status = 'active')            AS      active,
                 COUNT(*)    FILTER (WHERE status = 'sold')              AS      sold,
                 COUNT(*)    FILTER (WHERE status = 'transferred')       AS      transferred,
@@ -165,7 +189,7 @@ impl SpecieSummaryExtract {
                 EXTRACT(MONTH FROM  AGE(CURRENT_DATE, date_of_birth))
                 )                                                       AS      average_age_months
             FROM animals
-            WHERE organisation_pid = $1 and specie_id = (SELECT id FROM species WHERE name = $2)
+            WHERE organisation_pid = $1 and deleted_at IS NULL and specie_id = (SELECT id FROM species WHERE name = $2)
         "#,
         )
             .bind(org_pid)
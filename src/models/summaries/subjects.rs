@@ -0,0 +1,37 @@
+#![allow(clippy::missing_errors_doc)]
+
+use serde::Serialize;
+use sqlx::{Executor, Postgres, prelude::FromRow};
+use uuid::Uuid;
+
+use crate::models::ModelResult;
+
+/// A breed an organisation keeps, with its specie: what a breed summary is
+/// generated for.
+#[derive(Debug, Serialize, FromRow, Clone, PartialEq, Eq)]
+pub struct SummarySubject {
+    pub specie_name: String,
+    pub breed_name: String,
+}
+
+impl SummarySubject {
+    /// The breeds among the organisation's animals, trashed ones aside.
+    pub async fn find_by_organisation<'e, C>(db: C, org_pid: Uuid) -> ModelResult<Vec<Self>>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_as::<_, Self>(
+            "
+            SELECT DISTINCT s.name AS specie_name, b.name AS breed_name
+            FROM animals a
+            JOIN species s ON a.specie_id = s.id
+            JOIN breeds b ON a.breed_id = b.id
+            WHERE a.organisation_pid = $1 AND a.deleted_at IS NULL
+            ORDER BY s.name, b.name",
+        )
+        .bind(org_pid)
+        .fetch_all(db)
+        .await
+        .map_err(Into::into)
+    }
+}
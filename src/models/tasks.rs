@@ -0,0 +1,1277 @@
+#![allow(clippy::missing_errors_doc)]
+
+use std::{borrow::Cow, collections::BTreeMap};
+
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveTime, TimeDelta};
+use cron::Schedule;
+use serde::{Deserialize, Serialize};
+use sqlx::{Executor, PgConnection, Postgres, prelude::FromRow};
+use uuid::Uuid;
+
+use super::{
+    ModelError, ModelResult,
+    alerts::Alert,
+    dto::{
+        CompleteTask, GroupWeighing, NewRecurringTask, NewTask, NewVaccinationSchedule,
+        TaskProduction, TaskQuery, UpdateRecurringTask, UpdateTask, UpdateVaccinationSchedule,
+        Validator, records::NewWeightRecord,
+    },
+    groups::AnimalGroup,
+    health::HealthRecord,
+    jobs::parse_cron,
+    production::ProductionRecord,
+    settings::OrganisationSettings,
+    weight::WeightRecord,
+};
+
+/// Work to do by a day, for one person or anyone.
+#[derive(Debug, Deserialize, Serialize, FromRow, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Task {
+    pub id: i32,
+    pub pid: Uuid,
+    pub organisation_pid: Uuid,
+    pub title: String,
+    pub description: Option<String>,
+    pub due_on: NaiveDate,
+    pub assignee_pid: Option<Uuid>,
+    pub assignee_name: Option<String>,
+    pub priority: String,
+    pub status: String,
+    pub group_pid: Option<Uuid>,
+    pub group_name: Option<String>,
+    pub record_kind: Option<String>,
+    /// `manual`, `recurring`, `vaccination` or `alert`.
+    pub source: String,
+    /// The recurring task, vaccination schedule or alert it came from.
+    pub source_pid: Option<Uuid>,
+    pub tag_ids: Vec<String>,
+    pub notes: Option<String>,
+    pub completed_by: Option<Uuid>,
+    pub completed_at: Option<DateTime<FixedOffset>>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<FixedOffset>,
+    pub updated_at: DateTime<FixedOffset>,
+}
+
+/// A completed task and the records completing it created.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Completion {
+    pub task: Task,
+    pub health_records: Vec<HealthRecord>,
+    pub weight_records: Vec<WeightRecord>,
+    pub production_records: Vec<ProductionRecord>,
+}
+
+/// A person's tasks past their due date. Unassigned tasks come last, without
+/// an assignee.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OverdueTasks {
+    pub assignee_pid: Option<Uuid>,
+    pub assignee_name: Option<String>,
+    pub overdue: usize,
+    pub oldest_due_on: NaiveDate,
+    pub tasks: Vec<Task>,
+}
+
+/// How many tasks a run of [`Task::generate`] raised.
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Generated {
+    pub recurring: usize,
+    pub vaccination: usize,
+}
+
+#[derive(Debug, Deserialize, Serialize, FromRow, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RecurringTask {
+    pub id: i32,
+    pub pid: Uuid,
+    pub organisation_pid: Uuid,
+    pub title: String,
+    pub description: Option<String>,
+    pub cron: String,
+    pub assignee: Option<Uuid>,
+    pub priority: String,
+    #[serde(skip_serializing)]
+    pub group_id: Option<i32>,
+    pub group_pid: Option<Uuid>,
+    pub record_kind: Option<String>,
+    /// The next day it comes due.
+    pub next_due_on: NaiveDate,
+    pub is_active: bool,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<FixedOffset>,
+    pub updated_at: DateTime<FixedOffset>,
+}
+
+#[derive(Debug, Deserialize, Serialize, FromRow, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VaccinationSchedule {
+    pub id: i32,
+    pub pid: Uuid,
+    pub organisation_pid: Uuid,
+    pub vaccine: String,
+    pub group_pid: Uuid,
+    pub group_name: String,
+    pub interval_days: i32,
+    pub lead_days: i32,
+    pub assignee: Option<Uuid>,
+    pub priority: String,
+    pub is_active: bool,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<FixedOffset>,
+    pub updated_at: DateTime<FixedOffset>,
+}
+
+const TASKS_QUERY: &str = "
+    SELECT
+        t.id,
+        t.pid,
+        t.organisation_pid,
+        t.title,
+        t.description,
+        t.due_on,
+        t.assignee AS assignee_pid,
+        NULLIF(CONCAT_WS(' ', u.first_name, u.last_name), '') AS assignee_name,
+        t.priority,
+        t.status,
+        g.pid AS group_pid,
+        g.name AS group_name,
+        t.record_kind,
+        t.source,
+        t.source_pid,
+        ARRAY(
+            SELECT a.tag_id
+            FROM task_animals ta
+            JOIN animals a ON ta.animal_pid = a.pid
+            WHERE ta.task_id = t.id
+            ORDER BY a.tag_id
+        ) AS tag_ids,
+        t.notes,
+        t.completed_by,
+        t.completed_at,
+        t.created_by,
+        t.created_at,
+        t.updated_at
+    FROM
+        tasks t
+        LEFT JOIN users u ON t.assignee = u.pid
+        LEFT JOIN animal_groups g ON t.group_id = g.id
+    WHERE
+        t.organisation_pid = $1
+";
+
+/// The most pressing first.
+const TASKS_ORDER: &str =
+    "ORDER BY t.due_on, CASE t.priority WHEN 'high' THEN 0 WHEN 'medium' THEN 1 ELSE 2 END, t.id";
+
+const RECURRING_QUERY: &str = "
+    SELECT
+        r.id,
+        r.pid,
+        r.organisation_pid,
+        r.title,
+        r.description,
+        r.cron,
+        r.assignee,
+        r.priority,
+        r.group_id,
+        g.pid AS group_pid,
+        r.record_kind,
+        r.next_due_on,
+        r.is_active,
+        r.created_by,
+        r.created_at,
+        r.updated_at
+    FROM
+        recurring_tasks r
+        LEFT JOIN animal_groups g ON r.group_id = g.id
+    WHERE
+        r.organisation_pid = $1
+";
+
+const SCHEDULES_QUERY: &str = "
+    SELECT
+        v.id,
+        v.pid,
+        v.organisation_pid,
+        v.vaccine,
+        g.pid AS group_pid,
+        g.name AS group_name,
+        v.interval_days,
+        v.lead_days,
+        v.assignee,
+        v.priority,
+        v.is_active,
+        v.created_by,
+        v.created_at,
+        v.updated_at
+    FROM
+        vaccination_schedules v
+        JOIN animal_groups g ON v.group_id = g.id
+    WHERE
+        v.organisation_pid = $1
+";
+
+/// The first day on or after `from` the schedule fires on.
+fn next_due(schedule: &Schedule, from: NaiveDate) -> Option<NaiveDate> {
+    let before = from.and_time(NaiveTime::MIN).and_utc() - TimeDelta::seconds(1);
+
+    schedule.after(&before).next().map(|next| next.date_naive())
+}
+
+/// An assignee must be a member of the organisation.
+async fn check_assignee(
+    db: &mut PgConnection,
+    org_pid: Uuid,
+    assignee: Option<Uuid>,
+) -> ModelResult<()> {
+    let Some(assignee) = assignee else {
+        return Ok(());
+    };
+
+    let is_member = sqlx::query_scalar::<_, bool>(
+        "
+        SELECT EXISTS (
+            SELECT 1 FROM memberships
+            WHERE user_pid = $1 AND organisation_pid = $2 AND is_active
+        )",
+    )
+    .bind(assignee)
+    .bind(org_pid)
+    .fetch_one(&mut *db)
+    .await?;
+
+    if !is_member {
+        return Err(ModelError::Validation(
+            "The assignee is not a member of the organisation".into(),
+        ));
+    }
+
+    Ok(())
+}
+
+async fn group_id(
+    db: &mut PgConnection,
+    org_pid: Uuid,
+    group: Option<Uuid>,
+) -> ModelResult<Option<i32>> {
+    let Some(group) = group else {
+        return Ok(None);
+    };
+
+    match AnimalGroup::find_by_pid(&mut *db, org_pid, group).await {
+        Ok(group) => Ok(Some(group.id)),
+        Err(ModelError::EntityNotFound) => Err(ModelError::Validation("No such group".into())),
+        Err(error) => Err(error),
+    }
+}
+
+async fn animal_pids(
+    db: &mut PgConnection,
+    org_pid: Uuid,
+    tag_ids: &[Cow<'_, str>],
+) -> ModelResult<Vec<Uuid>> {
+    let tag_ids = tag_ids
+        .iter()
+        .map(|tag_id| tag_id.trim().to_uppercase())
+        .collect::<Vec<_>>();
+
+    let animals = sqlx::query_as::<_, (Uuid, String)>(
+        "
+        SELECT pid, tag_id FROM animals
+        WHERE organisation_pid = $1 AND tag_id = ANY($2) AND deleted_at IS NULL",
+    )
+    .bind(org_pid)
+    .bind(&tag_ids)
+    .fetch_all(&mut *db)
+    .await?;
+
+    let unknown = tag_ids
+        .iter()
+        .filter(|tag_id| !animals.iter().any(|(_, found)| found == *tag_id))
+        .cloned()
+        .collect::<Vec<_>>();
+    if !unknown.is_empty() {
+        return Err(ModelError::Validation(format!(
+            "No animal is tagged {}",
+            unknown.join(", ")
+        )));
+    }
+
+    Ok(animals.into_iter().map(|(pid, _)| pid).collect())
+}
+
+async fn link_animals(
+    db: &mut PgConnection,
+    org_pid: Uuid,
+    task_id: i32,
+    animal_pids: &[Uuid],
+) -> ModelResult<()> {
+    sqlx::query(
+        "
+        INSERT INTO task_animals (task_id, organisation_pid, animal_pid)
+        SELECT $1, $2, animal.pid FROM UNNEST($3::UUID[]) AS animal (pid)
+        ON CONFLICT DO NOTHING",
+    )
+    .bind(task_id)
+    .bind(org_pid)
+    .bind(animal_pids)
+    .execute(&mut *db)
+    .await?;
+
+    Ok(())
+}
+
+/// The tags recorded against that are not the task's.
+fn outsiders<'a>(tag_ids: impl Iterator<Item = &'a str>, animals: &[String]) -> ModelResult<()> {
+    let outsiders = tag_ids
+        .map(|tag_id| tag_id.trim().to_uppercase())
+        .filter(|tag_id| !animals.contains(tag_id))
+        .collect::<Vec<_>>();
+
+    if !outsiders.is_empty() {
+        return Err(ModelError::Validation(format!(
+            "Not on the task: {}",
+            outsiders.join(", ")
+        )));
+    }
+
+    Ok(())
+}
+
+/// Records a weighing of the task's animals.
+async fn weigh(
+    db: &mut PgConnection,
+    org_pid: Uuid,
+    user_pid: Uuid,
+    weighing: &GroupWeighing<'_>,
+    animals: &[String],
+) -> ModelResult<Vec<WeightRecord>> {
+    let validator = Validator::new(weighing);
+    let weighing = validator.validate()?;
+    outsiders(
+        weighing.weights.iter().map(|weight| weight.tag_id.as_ref()),
+        animals,
+    )?;
+
+    let mut records = Vec::with_capacity(weighing.weights.len());
+    for weight in &weighing.weights {
+        let record = WeightRecord::weigh(
+            &mut *db,
+            NewWeightRecord {
+                tag_id: weight.tag_id.trim().to_uppercase().into(),
+                record_date: weighing.record_date,
+                mass: weight.mass,
+                unit: weighing.unit.clone(),
+                status: None,
+                notes: weighing.notes.clone(),
+            },
+            org_pid,
+            user_pid,
+        )
+        .await?;
+        records.push(record);
+    }
+
+    Ok(records)
+}
+
+/// Records production readings of the task's animals, dated today unless
+/// the readings say otherwise.
+async fn produce(
+    db: &mut PgConnection,
+    org_pid: Uuid,
+    user_pid: Uuid,
+    production: &TaskProduction<'_>,
+    animals: &[String],
+) -> ModelResult<Vec<ProductionRecord>> {
+    let validator = Validator::new(production);
+    let production = validator.validate()?;
+    outsiders(
+        production
+            .readings
+            .iter()
+            .map(|reading| reading.tag_id.as_ref()),
+        animals,
+    )?;
+
+    let record_date = match production.record_date {
+        Some(record_date) => record_date,
+        None => OrganisationSettings::find(&mut *db, org_pid).await?.today(),
+    };
+
+    let mut records = Vec::with_capacity(production.readings.len());
+    for reading in &production.readings {
+        let record = ProductionRecord::create(
+            &mut *db,
+            &production.for_reading(reading, record_date),
+            org_pid,
+            user_pid,
+        )
+        .await?;
+        records.push(record);
+    }
+
+    Ok(records)
+}
+
+impl Task {
+    pub async fn create(
+        db: &mut PgConnection,
+        org_pid: Uuid,
+        user_pid: Uuid,
+        params: &NewTask<'_>,
+    ) -> ModelResult<Self> {
+        let validator = Validator::new(params);
+        let params = validator.validate()?;
+
+        check_assignee(&mut *db, org_pid, params.assignee).await?;
+        let group_id = group_id(&mut *db, org_pid, params.group).await?;
+        let animals = match &params.tag_ids {
+            Some(tag_ids) => animal_pids(&mut *db, org_pid, tag_ids).await?,
+            None => vec![],
+        };
+
+        let task_id = sqlx::query_scalar::<_, i32>(
+            "
+            INSERT INTO tasks (
+                organisation_pid, title, description, due_on, assignee, priority, group_id,
+                record_kind, created_by
+            )
+            VALUES ($1, $2, $3, $4, $5, COALESCE($6, 'medium'), $7, $8, $9)
+            RETURNING id",
+        )
+        .bind(org_pid)
+        .bind(params.title.trim())
+        .bind(params.description.as_deref())
+        .bind(params.due_on)
+        .bind(params.assignee)
+        .bind(params.priority.as_deref())
+        .bind(group_id)
+        .bind(params.record_kind.as_deref())
+        .bind(user_pid)
+        .fetch_one(&mut *db)
+        .await?;
+
+        link_animals(&mut *db, org_pid, task_id, &animals).await?;
+
+        Self::find_by_id(&mut *db, org_pid, task_id).await
+    }
+
+    pub async fn find_all<'e, C>(
+        db: C,
+        org_pid: Uuid,
+        conditions: &TaskQuery,
+    ) -> ModelResult<Vec<Self>>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        let query = format!(
+            "{TASKS_QUERY}
+            AND ($2::TEXT IS NULL OR t.status = $2)
+            AND ($3::UUID IS NULL OR t.assignee = $3)
+            AND ($4::UUID IS NULL OR EXISTS (
+                SELECT 1 FROM task_animals ta WHERE ta.task_id = t.id AND ta.animal_pid = $4
+            ))
+            AND ($5::TEXT IS NULL OR t.source = $5)
+            AND ($6::DATE IS NULL OR t.due_on <= $6)
+            {TASKS_ORDER}"
+        );
+
+        sqlx::query_as::<_, Self>(&query)
+            .bind(org_pid)
+            .bind(conditions.status.as_deref())
+            .bind(conditions.assignee)
+            .bind(conditions.animal)
+            .bind(conditions.source.as_deref())
+            .bind(conditions.due)
+            .fetch_all(db)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn find_by_pid<'e, C>(db: C, org_pid: Uuid, pid: Uuid) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        let query = format!("{TASKS_QUERY} AND t.pid = $2");
+
+        sqlx::query_as::<_, Self>(&query)
+            .bind(org_pid)
+            .bind(pid)
+            .fetch_optional(db)
+            .await?
+            .ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    async fn find_by_id(db: &mut PgConnection, org_pid: Uuid, id: i32) -> ModelResult<Self> {
+        let query = format!("{TASKS_QUERY} AND t.id = $2");
+
+        sqlx::query_as::<_, Self>(&query)
+            .bind(org_pid)
+            .bind(id)
+            .fetch_optional(&mut *db)
+            .await?
+            .ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    /// The user's unfinished tasks due by `today`, overdue ones included.
+    pub async fn find_today<'e, C>(
+        db: C,
+        org_pid: Uuid,
+        user_pid: Uuid,
+        today: NaiveDate,
+    ) -> ModelResult<Vec<Self>>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        let query = format!(
+            "{TASKS_QUERY}
+            AND t.assignee = $2
+            AND t.status IN ('open', 'in_progress')
+            AND t.due_on <= $3
+            {TASKS_ORDER}"
+        );
+
+        sqlx::query_as::<_, Self>(&query)
+            .bind(org_pid)
+            .bind(user_pid)
+            .bind(today)
+            .fetch_all(db)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn update(
+        db: &mut PgConnection,
+        org_pid: Uuid,
+        pid: Uuid,
+        params: &UpdateTask<'_>,
+    ) -> ModelResult<Self> {
+        let validator = Validator::new(params);
+        let params = validator.validate()?;
+
+        let task = Self::find_by_pid(&mut *db, org_pid, pid).await?;
+        if task.status == "done" {
+            return Err(ModelError::Conflict("The task is already done".into()));
+        }
+        check_assignee(&mut *db, org_pid, params.assignee).await?;
+
+        sqlx::query(
+            "
+            UPDATE tasks SET
+                title = COALESCE($2, title),
+                description = COALESCE($3, description),
+                due_on = COALESCE($4, due_on),
+                assignee = COALESCE($5, assignee),
+                priority = COALESCE($6, priority),
+                status = COALESCE($7, status),
+                notes = COALESCE($8, notes)
+            WHERE id = $1",
+        )
+        .bind(task.id)
+        .bind(params.title.as_deref().map(str::trim))
+        .bind(params.description.as_deref())
+        .bind(params.due_on)
+        .bind(params.assignee)
+        .bind(params.priority.as_deref())
+        .bind(params.status.as_deref())
+        .bind(params.notes.as_deref())
+        .execute(&mut *db)
+        .await?;
+
+        Self::find_by_id(&mut *db, org_pid, task.id).await
+    }
+
+    pub async fn delete_by_pid(db: &mut PgConnection, org_pid: Uuid, pid: Uuid) -> ModelResult<()> {
+        let deleted = sqlx::query("DELETE FROM tasks WHERE pid = $1 AND organisation_pid = $2")
+            .bind(pid)
+            .bind(org_pid)
+            .execute(&mut *db)
+            .await?;
+
+        if deleted.rows_affected() == 0 {
+            return Err(ModelError::EntityNotFound);
+        }
+
+        Ok(())
+    }
+
+    /// Marks the task done, creating the records of the work when they are
+    /// given. A task raised by an alert resolves it.
+    pub async fn complete(
+        db: &mut PgConnection,
+        org_pid: Uuid,
+        user_pid: Uuid,
+        pid: Uuid,
+        params: &CompleteTask<'_>,
+    ) -> ModelResult<Completion> {
+        let task = Self::find_by_pid(&mut *db, org_pid, pid).await?;
+        if ["done", "cancelled"].contains(&task.status.as_str()) {
+            return Err(ModelError::Conflict(format!(
+                "The task is already {}",
+                task.status
+            )));
+        }
+
+        let recorded = [
+            params.treatment.as_ref().map(|_| "health"),
+            params.weighing.as_ref().map(|_| "weight"),
+            params.production.as_ref().map(|_| "production"),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+        match (recorded.as_slice(), task.record_kind.as_deref()) {
+            ([], _) | ([_], None) => {}
+            ([kind], Some(record_kind)) if *kind == record_kind => {}
+            ([_], Some(record_kind)) => {
+                return Err(ModelError::Validation(format!(
+                    "The task is completed with a {record_kind} record"
+                )));
+            }
+            _ => {
+                return Err(ModelError::Validation(
+                    "Record the work one way: a treatment, a weighing or production".into(),
+                ));
+            }
+        }
+
+        let animals = if recorded.is_empty() {
+            vec![]
+        } else {
+            task.animals(&mut *db).await?
+        };
+
+        let mut health_records = vec![];
+        if let Some(treatment) = &params.treatment {
+            for tag_id in &animals {
+                let record = HealthRecord::create(
+                    &mut *db,
+                    &treatment.for_animal(tag_id),
+                    org_pid,
+                    user_pid,
+                )
+                .await?;
+                health_records.push(record);
+            }
+        }
+
+        let weight_records = match &params.weighing {
+            Some(weighing) => weigh(&mut *db, org_pid, user_pid, weighing, &animals).await?,
+            None => vec![],
+        };
+        let production_records = match &params.production {
+            Some(production) => produce(&mut *db, org_pid, user_pid, production, &animals).await?,
+            None => vec![],
+        };
+
+        sqlx::query(
+            "
+            UPDATE tasks SET
+                status = 'done',
+                completed_by = $2,
+                completed_at = NOW(),
+                notes = COALESCE($3, notes)
+            WHERE id = $1",
+        )
+        .bind(task.id)
+        .bind(user_pid)
+        .bind(params.notes.as_deref())
+        .execute(&mut *db)
+        .await?;
+
+        if task.source == "alert" {
+            sqlx::query(
+                "
+                UPDATE alerts
+                SET status = 'resolved', resolved_by = $2, resolved_at = NOW()
+                WHERE pid = $1 AND status <> 'resolved'",
+            )
+            .bind(task.source_pid)
+            .bind(user_pid)
+            .execute(&mut *db)
+            .await?;
+        }
+
+        Ok(Completion {
+            task: Self::find_by_id(&mut *db, org_pid, task.id).await?,
+            health_records,
+            weight_records,
+            production_records,
+        })
+    }
+
+    /// Raises a task to look into the alert, due today. An alert raises one
+    /// task at most.
+    pub async fn raise_for_alert(db: &mut PgConnection, alert: &Alert) -> ModelResult<()> {
+        let today = OrganisationSettings::find(&mut *db, alert.organisation_pid)
+            .await?
+            .today();
+
+        let task_id = sqlx::query_scalar::<_, i32>(
+            "
+            INSERT INTO tasks (organisation_pid, title, due_on, priority, source, source_pid)
+            VALUES ($1, LEFT($2, 200), $3, $4, 'alert', $5)
+            ON CONFLICT (source_pid) WHERE source = 'alert' DO NOTHING
+            RETURNING id",
+        )
+        .bind(alert.organisation_pid)
+        .bind(&alert.message)
+        .bind(today)
+        .bind(&alert.severity)
+        .bind(alert.pid)
+        .fetch_optional(&mut *db)
+        .await?;
+
+        if let Some(task_id) = task_id {
+            link_animals(
+                &mut *db,
+                alert.organisation_pid,
+                task_id,
+                &[alert.animal_pid],
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Raises the tasks of the organisation's recurring tasks and vaccination
+    /// schedules that came due. Running it again raises nothing new.
+    pub async fn generate(db: &mut PgConnection, org_pid: Uuid) -> ModelResult<Generated> {
+        let today = OrganisationSettings::find(&mut *db, org_pid).await?.today();
+
+        let mut generated = Generated::default();
+
+        for recurring in RecurringTask::find_due(&mut *db, org_pid, today).await? {
+            generated.recurring += recurring.raise(&mut *db, today).await?;
+        }
+
+        for schedule in VaccinationSchedule::find_active(&mut *db, org_pid).await? {
+            generated.vaccination += schedule.raise(&mut *db, today).await?;
+        }
+
+        Ok(generated)
+    }
+
+    /// The tags of the animals the task covers: its own and its group's
+    /// members now.
+    async fn animals(&self, db: &mut PgConnection) -> ModelResult<Vec<String>> {
+        let mut animals = self.tag_ids.clone();
+
+        if let Some(group_pid) = self.group_pid {
+            let group =
+                AnimalGroup::find_by_pid(&mut *db, self.organisation_pid, group_pid).await?;
+            animals.extend(group.tag_ids(&mut *db).await?);
+        }
+
+        animals.sort();
+        animals.dedup();
+
+        if animals.is_empty() {
+            return Err(ModelError::Validation(
+                "The task has no animals to record".into(),
+            ));
+        }
+
+        Ok(animals)
+    }
+}
+
+impl OverdueTasks {
+    /// The unfinished tasks due before `today`, per assignee.
+    pub async fn find_all<'e, C>(db: C, org_pid: Uuid, today: NaiveDate) -> ModelResult<Vec<Self>>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        let query = format!(
+            "{TASKS_QUERY}
+            AND t.status IN ('open', 'in_progress')
+            AND t.due_on < $2
+            {TASKS_ORDER}"
+        );
+
+        let tasks = sqlx::query_as::<_, Task>(&query)
+            .bind(org_pid)
+            .bind(today)
+            .fetch_all(db)
+            .await?;
+
+        let mut assignees = BTreeMap::<(bool, Option<String>, Option<Uuid>), Vec<Task>>::new();
+        for task in tasks {
+            assignees
+                .entry((
+                    task.assignee_pid.is_none(),
+                    task.assignee_name.clone(),
+                    task.assignee_pid,
+                ))
+                .or_default()
+                .push(task);
+        }
+
+        Ok(assignees
+            .into_iter()
+            .map(|((_, assignee_name, assignee_pid), tasks)| Self {
+                assignee_pid,
+                assignee_name,
+                overdue: tasks.len(),
+                oldest_due_on: tasks[0].due_on,
+                tasks,
+            })
+            .collect())
+    }
+}
+
+impl RecurringTask {
+    pub async fn create(
+        db: &mut PgConnection,
+        org_pid: Uuid,
+        user_pid: Uuid,
+        params: &NewRecurringTask<'_>,
+    ) -> ModelResult<Self> {
+        let validator = Validator::new(params);
+        let params = validator.validate()?;
+
+        let starts_on = match params.starts_on {
+            Some(starts_on) => starts_on,
+            None => OrganisationSettings::find(&mut *db, org_pid).await?.today(),
+        };
+        let next_due_on = next_due(&parse_cron(&params.cron)?, starts_on).ok_or_else(|| {
+            ModelError::Validation("The cron expression never fires again".into())
+        })?;
+
+        check_assignee(&mut *db, org_pid, params.assignee).await?;
+        let group_id = group_id(&mut *db, org_pid, params.group).await?;
+
+        let id = sqlx::query_scalar::<_, i32>(
+            "
+            INSERT INTO recurring_tasks (
+                organisation_pid, title, description, cron, assignee, priority, group_id,
+                record_kind, next_due_on, created_by
+            )
+            VALUES ($1, $2, $3, $4, $5, COALESCE($6, 'medium'), $7, $8, $9, $10)
+            RETURNING id",
+        )
+        .bind(org_pid)
+        .bind(params.title.trim())
+        .bind(params.description.as_deref())
+        .bind(params.cron.trim())
+        .bind(params.assignee)
+        .bind(params.priority.as_deref())
+        .bind(group_id)
+        .bind(params.record_kind.as_deref())
+        .bind(next_due_on)
+        .bind(user_pid)
+        .fetch_one(&mut *db)
+        .await?;
+
+        Self::find_by_id(&mut *db, org_pid, id).await
+    }
+
+    pub async fn find_all<'e, C>(db: C, org_pid: Uuid) -> ModelResult<Vec<Self>>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        let query = format!("{RECURRING_QUERY} ORDER BY r.next_due_on, r.id");
+
+        sqlx::query_as::<_, Self>(&query)
+            .bind(org_pid)
+            .fetch_all(db)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn find_by_pid<'e, C>(db: C, org_pid: Uuid, pid: Uuid) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        let query = format!("{RECURRING_QUERY} AND r.pid = $2");
+
+        sqlx::query_as::<_, Self>(&query)
+            .bind(org_pid)
+            .bind(pid)
+            .fetch_optional(db)
+            .await?
+            .ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    async fn find_by_id(db: &mut PgConnection, org_pid: Uuid, id: i32) -> ModelResult<Self> {
+        let query = format!("{RECURRING_QUERY} AND r.id = $2");
+
+        sqlx::query_as::<_, Self>(&query)
+            .bind(org_pid)
+            .bind(id)
+            .fetch_optional(&mut *db)
+            .await?
+            .ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    /// The active ones due by `today`, locked so concurrent runs raise their
+    /// tasks once.
+    async fn find_due(
+        db: &mut PgConnection,
+        org_pid: Uuid,
+        today: NaiveDate,
+    ) -> ModelResult<Vec<Self>> {
+        let query = format!(
+            "{RECURRING_QUERY} AND r.is_active AND r.next_due_on <= $2
+            ORDER BY r.id FOR UPDATE OF r"
+        );
+
+        sqlx::query_as::<_, Self>(&query)
+            .bind(org_pid)
+            .bind(today)
+            .fetch_all(&mut *db)
+            .await
+            .map_err(Into::into)
+    }
+
+    /// Changing the cron expression moves the next due day to when it next
+    /// fires from today.
+    pub async fn update(
+        db: &mut PgConnection,
+        org_pid: Uuid,
+        pid: Uuid,
+        params: &UpdateRecurringTask<'_>,
+    ) -> ModelResult<Self> {
+        let validator = Validator::new(params);
+        let params = validator.validate()?;
+
+        let recurring = Self::find_by_pid(&mut *db, org_pid, pid).await?;
+        check_assignee(&mut *db, org_pid, params.assignee).await?;
+
+        let next_due_on = match &params.cron {
+            Some(cron) => {
+                let today = OrganisationSettings::find(&mut *db, org_pid).await?.today();
+                Some(next_due(&parse_cron(cron)?, today).ok_or_else(|| {
+                    ModelError::Validation("The cron expression never fires again".into())
+                })?)
+            }
+            None => None,
+        };
+
+        sqlx::query(
+            "
+            UPDATE recurring_tasks SET
+                title = COALESCE($2, title),
+                description = COALESCE($3, description),
+                cron = COALESCE($4, cron),
+                next_due_on = COALESCE($5, next_due_on),
+                assignee = COALESCE($6, assignee),
+                priority = COALESCE($7, priority),
+                is_active = COALESCE($8, is_active)
+            WHERE id = $1",
+        )
+        .bind(recurring.id)
+        .bind(params.title.as_deref().map(str::trim))
+        .bind(params.description.as_deref())
+        .bind(params.cron.as_deref().map(str::trim))
+        .bind(next_due_on)
+        .bind(params.assignee)
+        .bind(params.priority.as_deref())
+        .bind(params.is_active)
+        .execute(&mut *db)
+        .await?;
+
+        Self::find_by_id(&mut *db, org_pid, recurring.id).await
+    }
+
+    /// Deletes it. The tasks it raised are kept.
+    pub async fn delete_by_pid(db: &mut PgConnection, org_pid: Uuid, pid: Uuid) -> ModelResult<()> {
+        let deleted =
+            sqlx::query("DELETE FROM recurring_tasks WHERE pid = $1 AND organisation_pid = $2")
+                .bind(pid)
+                .bind(org_pid)
+                .execute(&mut *db)
+                .await?;
+
+        if deleted.rows_affected() == 0 {
+            return Err(ModelError::EntityNotFound);
+        }
+
+        Ok(())
+    }
+
+    /// Raises the task of the latest occurrence due by `today`, skipping
+    /// those missed before it, and moves on to the next. Returns how many
+    /// tasks were raised.
+    async fn raise(&self, db: &mut PgConnection, today: NaiveDate) -> ModelResult<usize> {
+        let schedule = parse_cron(&self.cron)?;
+
+        let mut due_on = self.next_due_on;
+        let mut next = Some(due_on);
+        while let Some(occurrence) = next.filter(|occurrence| *occurrence <= today) {
+            due_on = occurrence;
+            next = next_due(&schedule, occurrence + TimeDelta::days(1));
+        }
+
+        let raised = sqlx::query(
+            "
+            INSERT INTO tasks (
+                organisation_pid, title, description, due_on, assignee, priority, group_id,
+                record_kind, source, source_pid, created_by
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, 'recurring', $9, $10)
+            ON CONFLICT (source_pid, due_on) WHERE source = 'recurring' DO NOTHING",
+        )
+        .bind(self.organisation_pid)
+        .bind(&self.title)
+        .bind(self.description.as_deref())
+        .bind(due_on)
+        .bind(self.assignee)
+        .bind(&self.priority)
+        .bind(self.group_id)
+        .bind(self.record_kind.as_deref())
+        .bind(self.pid)
+        .bind(self.created_by)
+        .execute(&mut *db)
+        .await?
+        .rows_affected();
+
+        // A schedule that never fires again is done with.
+        sqlx::query(
+            "
+            UPDATE recurring_tasks
+            SET next_due_on = COALESCE($2, next_due_on), is_active = $2 IS NOT NULL
+            WHERE id = $1",
+        )
+        .bind(self.id)
+        .bind(next)
+        .execute(&mut *db)
+        .await?;
+
+        Ok(usize::try_from(raised).unwrap_or_default())
+    }
+}
+
+impl VaccinationSchedule {
+    pub async fn create(
+        db: &mut PgConnection,
+        org_pid: Uuid,
+        user_pid: Uuid,
+        params: &NewVaccinationSchedule<'_>,
+    ) -> ModelResult<Self> {
+        let validator = Validator::new(params);
+        let params = validator.validate()?;
+
+        check_assignee(&mut *db, org_pid, params.assignee).await?;
+        let group_id = group_id(&mut *db, org_pid, Some(params.group)).await?;
+
+        let id = sqlx::query_scalar::<_, i32>(
+            "
+            INSERT INTO vaccination_schedules (
+                organisation_pid, vaccine, group_id, interval_days, lead_days, assignee,
+                priority, created_by
+            )
+            VALUES ($1, $2, $3, $4, COALESCE($5, 7), $6, COALESCE($7, 'medium'), $8)
+            RETURNING id",
+        )
+        .bind(org_pid)
+        .bind(params.vaccine.trim())
+        .bind(group_id)
+        .bind(params.interval_days)
+        .bind(params.lead_days)
+        .bind(params.assignee)
+        .bind(params.priority.as_deref())
+        .bind(user_pid)
+        .fetch_one(&mut *db)
+        .await
+        .map_err(|error| match error {
+            sqlx::Error::Database(err)
+                if err.constraint() == Some("vaccination_schedules_vaccine_idx") =>
+            {
+                ModelError::EntityAlreadyExists(
+                    "The group already has a schedule for that vaccine".into(),
+                )
+            }
+            error => ModelError::Sqlx(error),
+        })?;
+
+        Self::find_by_id(&mut *db, org_pid, id).await
+    }
+
+    pub async fn find_all<'e, C>(db: C, org_pid: Uuid) -> ModelResult<Vec<Self>>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        let query = format!("{SCHEDULES_QUERY} ORDER BY LOWER(g.name), LOWER(v.vaccine)");
+
+        sqlx::query_as::<_, Self>(&query)
+            .bind(org_pid)
+            .fetch_all(db)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn find_by_pid<'e, C>(db: C, org_pid: Uuid, pid: Uuid) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        let query = format!("{SCHEDULES_QUERY} AND v.pid = $2");
+
+        sqlx::query_as::<_, Self>(&query)
+            .bind(org_pid)
+            .bind(pid)
+            .fetch_optional(db)
+            .await?
+            .ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    async fn find_by_id(db: &mut PgConnection, org_pid: Uuid, id: i32) -> ModelResult<Self> {
+        let query = format!("{SCHEDULES_QUERY} AND v.id = $2");
+
+        sqlx::query_as::<_, Self>(&query)
+            .bind(org_pid)
+            .bind(id)
+            .fetch_optional(&mut *db)
+            .await?
+            .ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    async fn find_active(db: &mut PgConnection, org_pid: Uuid) -> ModelResult<Vec<Self>> {
+        let query = format!("{SCHEDULES_QUERY} AND v.is_active ORDER BY v.id FOR UPDATE OF v");
+
+        sqlx::query_as::<_, Self>(&query)
+            .bind(org_pid)
+            .fetch_all(&mut *db)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn update(
+        db: &mut PgConnection,
+        org_pid: Uuid,
+        pid: Uuid,
+        params: &UpdateVaccinationSchedule<'_>,
+    ) -> ModelResult<Self> {
+        let validator = Validator::new(params);
+        let params = validator.validate()?;
+
+        let schedule = Self::find_by_pid(&mut *db, org_pid, pid).await?;
+        check_assignee(&mut *db, org_pid, params.assignee).await?;
+
+        sqlx::query(
+            "
+            UPDATE vaccination_schedules SET
+                interval_days = COALESCE($2, interval_days),
+                lead_days = COALESCE($3, lead_days),
+                assignee = COALESCE($4, assignee),
+                priority = COALESCE($5, priority),
+                is_active = COALESCE($6, is_active)
+            WHERE id = $1",
+        )
+        .bind(schedule.id)
+        .bind(params.interval_days)
+        .bind(params.lead_days)
+        .bind(params.assignee)
+        .bind(params.priority.as_deref())
+        .bind(params.is_active)
+        .execute(&mut *db)
+        .await?;
+
+        Self::find_by_id(&mut *db, org_pid, schedule.id).await
+    }
+
+    /// Deletes it. The tasks it raised are kept.
+    pub async fn delete_by_pid(db: &mut PgConnection, org_pid: Uuid, pid: Uuid) -> ModelResult<()> {
+        let deleted = sqlx::query(
+            "DELETE FROM vaccination_schedules WHERE pid = $1 AND organisation_pid = $2",
+        )
+        .bind(pid)
+        .bind(org_pid)
+        .execute(&mut *db)
+        .await?;
+
+        if deleted.rows_affected() == 0 {
+            return Err(ModelError::EntityNotFound);
+        }
+
+        Ok(())
+    }
+
+    /// Raises a task for the group's active members due the vaccine within
+    /// the lead days, one per due day. An animal is due `interval_days`
+    /// after its last vaccination with the vaccine, or after the last of
+    /// the schedule's tasks for it that was done or cancelled; never
+    /// vaccinated, it is due today. Animals with a task still open are left
+    /// to it. Returns how many tasks were raised.
+    async fn raise(&self, db: &mut PgConnection, today: NaiveDate) -> ModelResult<usize> {
+        let group =
+            AnimalGroup::find_by_pid(&mut *db, self.organisation_pid, self.group_pid).await?;
+        let members = group.animal_pids(&mut *db).await?;
+
+        let due = sqlx::query_as::<_, (Uuid, NaiveDate)>(
+            "
+            SELECT pid, due_on FROM (
+                SELECT
+                    a.pid,
+                    COALESCE(GREATEST(v.last_vaccinated, c.last_closed) + $3, $4) AS due_on,
+                    c.pending
+                FROM animals a
+                LEFT JOIN LATERAL (
+                    SELECT MAX(h.record_date) AS last_vaccinated
+                    FROM health_records h
+                    WHERE h.animal_pid = a.pid AND h.condition = 'vaccination'
+                        AND h.medicine ILIKE $2 AND h.deleted_at IS NULL
+                ) v ON TRUE
+                LEFT JOIN LATERAL (
+                    SELECT
+                        MAX(t.due_on) FILTER (WHERE t.status IN ('done', 'cancelled'))
+                            AS last_closed,
+                        BOOL_OR(t.status IN ('open', 'in_progress')) AS pending
+                    FROM tasks t
+                    JOIN task_animals ta ON ta.task_id = t.id
+                    WHERE t.source = 'vaccination' AND t.source_pid = $5
+                        AND ta.animal_pid = a.pid
+                ) c ON TRUE
+                WHERE a.pid = ANY($1) AND a.status = 'active' AND a.deleted_at IS NULL
+            ) animals
+            WHERE pending IS NOT TRUE AND due_on <= $4 + $6
+            ORDER BY due_on, pid",
+        )
+        .bind(&members)
+        .bind(&self.vaccine)
+        .bind(self.interval_days)
+        .bind(today)
+        .bind(self.pid)
+        .bind(self.lead_days)
+        .fetch_all(&mut *db)
+        .await?;
+
+        let mut days = BTreeMap::<NaiveDate, Vec<Uuid>>::new();
+        for (animal_pid, due_on) in due {
+            days.entry(due_on).or_default().push(animal_pid);
+        }
+
+        for (due_on, animals) in &days {
+            let task_id = sqlx::query_scalar::<_, i32>(
+                "
+                INSERT INTO tasks (
+                    organisation_pid, title, description, due_on, assignee, priority,
+                    record_kind, source, source_pid, created_by
+                )
+                VALUES ($1, LEFT($2, 200), $3, $4, $5, $6, 'health', 'vaccination', $7, $8)
+                RETURNING id",
+            )
+            .bind(self.organisation_pid)
+            .bind(format!("{} vaccination", self.vaccine))
+            .bind(format!("Due for members of {}", self.group_name))
+            .bind(due_on)
+            .bind(self.assignee)
+            .bind(&self.priority)
+            .bind(self.pid)
+            .bind(self.created_by)
+            .fetch_one(&mut *db)
+            .await?;
+
+            link_animals(&mut *db, self.organisation_pid, task_id, animals).await?;
+        }
+
+        Ok(days.len())
+    }
+}
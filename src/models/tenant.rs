@@ -0,0 +1,131 @@
+#![allow(clippy::missing_errors_doc)]
+
+use std::ops::{Deref, DerefMut};
+
+use axum::extract::{FromRef, FromRequestParts};
+use sqlx::{PgConnection, PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::{AppContext, middlewares::TokenClaims};
+
+use super::{ModelError, ModelResult, api_keys::ApiKey, users::User};
+
+/// A transaction scoped to one organisation and user.
+///
+/// It sets `app.current_org_pid` and `app.current_user_pid` and assumes the
+/// `polaris_tenant` role, so row-level security hides every other
+/// organisation's rows even if a query forgets its `organisation_pid` filter.
+/// The settings and role only last until the transaction ends.
+///
+/// Extracting it in a handler begins the transaction for the authenticated
+/// user. Changes must be committed with [`TenantTransaction::commit`].
+pub struct TenantTransaction {
+    txn: Transaction<'static, Postgres>,
+}
+
+impl TenantTransaction {
+    pub async fn begin(db: &PgPool, org_pid: Uuid, user_pid: Uuid) -> ModelResult<Self> {
+        let mut txn = db.begin().await?;
+
+        sqlx::query(
+            "SELECT set_config('app.current_org_pid', $1, true),
+            set_config('app.current_user_pid', $2, true)",
+        )
+        .bind(org_pid.to_string())
+        .bind(user_pid.to_string())
+        .execute(&mut *txn)
+        .await?;
+
+        sqlx::query("SET LOCAL ROLE polaris_tenant")
+            .execute(&mut *txn)
+            .await?;
+
+        Ok(Self { txn })
+    }
+
+    pub async fn for_user(db: &PgPool, user: &User) -> ModelResult<Self> {
+        Self::begin(db, user.organisation_pid, user.pid).await
+    }
+
+    /// Begins the transaction for a request made with an API key. Changes are
+    /// audited as the key as well as the user it acts for.
+    pub async fn for_api_key(db: &PgPool, user: &User, api_key: &ApiKey) -> ModelResult<Self> {
+        let mut tenant = Self::for_user(db, user).await?;
+
+        sqlx::query("SELECT set_config('app.current_api_key_pid', $1, true)")
+            .bind(api_key.pid.to_string())
+            .execute(&mut *tenant.txn)
+            .await?;
+
+        Ok(tenant)
+    }
+
+    /// Begins the transaction for a platform operator impersonating `user`.
+    /// Changes are audited as the operator as well as the user.
+    pub async fn for_impersonation(
+        db: &PgPool,
+        user: &User,
+        impersonator_pid: Uuid,
+    ) -> ModelResult<Self> {
+        let mut tenant = Self::for_user(db, user).await?;
+
+        sqlx::query("SELECT set_config('app.current_impersonator_pid', $1, true)")
+            .bind(impersonator_pid.to_string())
+            .execute(&mut *tenant.txn)
+            .await?;
+
+        Ok(tenant)
+    }
+
+    pub async fn commit(self) -> ModelResult<()> {
+        self.txn.commit().await.map_err(Into::into)
+    }
+}
+
+impl Deref for TenantTransaction {
+    type Target = PgConnection;
+
+    fn deref(&self) -> &Self::Target {
+        &self.txn
+    }
+}
+
+impl DerefMut for TenantTransaction {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.txn
+    }
+}
+
+impl<S> FromRequestParts<S> for TenantTransaction
+where
+    S: Send + Sync,
+    AppContext: FromRef<S>,
+{
+    type Rejection = crate::errors::Error;
+
+    async fn from_request_parts(
+        parts: &mut axum::http::request::Parts,
+        state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        let context = AppContext::from_ref(state);
+
+        let user = User::from_request_parts(parts, state).await?;
+
+        let impersonator = parts
+            .extensions
+            .get::<TokenClaims>()
+            .and_then(|claims| claims.impersonator.as_deref())
+            .map(Uuid::parse_str)
+            .transpose()
+            .map_err(ModelError::from)?;
+
+        match (parts.extensions.get::<ApiKey>(), impersonator) {
+            (Some(api_key), _) => Self::for_api_key(&context.db, &user, api_key).await,
+            (None, Some(impersonator)) => {
+                Self::for_impersonation(&context.db, &user, impersonator).await
+            }
+            (None, None) => Self::for_user(&context.db, &user).await,
+        }
+        .map_err(Into::into)
+    }
+}
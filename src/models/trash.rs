@@ -0,0 +1,221 @@
+#![allow(clippy::missing_errors_doc)]
+
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use sqlx::{Executor, Postgres, postgres::PgConnection, prelude::FromRow};
+use uuid::Uuid;
+
+use super::{ModelError, ModelResult, roles::Resource};
+
+/// The resources that are moved to the trash instead of being deleted.
+pub const TRASHABLE: [Resource; 4] = [
+    Resource::Animals,
+    Resource::HealthRecords,
+    Resource::ProductionRecords,
+    Resource::WeightRecords,
+];
+
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct TrashQuery {
+    pub resource: Option<Resource>,
+}
+
+#[derive(Debug, Deserialize, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashItem {
+    pub resource: String,
+    pub record_id: String,
+    pub animal_pid: Uuid,
+    pub animal_tag_id: String,
+    pub summary: Option<String>,
+    pub deleted_at: DateTime<FixedOffset>,
+    pub deleted_by: Option<Uuid>,
+    pub deleted_by_name: Option<String>,
+    pub purge_after: DateTime<FixedOffset>,
+}
+
+/// Rows removed for good by [`Trash::purge`].
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PurgeSummary {
+    pub animals: u64,
+    pub health_records: u64,
+    pub production_records: u64,
+    pub weight_records: u64,
+}
+
+const TRASH_QUERY: &str = "
+        SELECT
+            'animals' AS resource,
+            a.pid::TEXT AS record_id,
+            a.pid AS animal_pid,
+            a.tag_id AS animal_tag_id,
+            a.name AS summary,
+            a.deleted_at,
+            a.deleted_by
+        FROM animals a
+        WHERE a.organisation_pid = $1 AND a.deleted_at IS NOT NULL
+        UNION ALL
+        SELECT
+            'health_records',
+            hr.id::TEXT,
+            hr.animal_pid,
+            a.tag_id,
+            hr.condition,
+            hr.deleted_at,
+            hr.deleted_by
+        FROM health_records hr
+        JOIN animals a ON hr.animal_pid = a.pid
+        WHERE hr.organisation_pid = $1 AND hr.deleted_at IS NOT NULL
+        UNION ALL
+        SELECT
+            'production_records',
+            pr.id::TEXT,
+            pr.animal_pid,
+            a.tag_id,
+            pr.product_type,
+            pr.deleted_at,
+            pr.deleted_by
+        FROM production_records pr
+        JOIN animals a ON pr.animal_pid = a.pid
+        WHERE pr.organisation_pid = $1 AND pr.deleted_at IS NOT NULL
+        UNION ALL
+        SELECT
+            'weight_records',
+            wr.id::TEXT,
+            wr.animal_pid,
+            a.tag_id,
+            CONCAT(wr.mass, ' ', wr.unit),
+            wr.deleted_at,
+            wr.deleted_by
+        FROM weight_records wr
+        JOIN animals a ON wr.animal_pid = a.pid
+        WHERE wr.organisation_pid = $1 AND wr.deleted_at IS NOT NULL
+";
+
+pub struct Trash;
+
+impl Trash {
+    /// Lists the trashed rows of `resources`, most recently deleted first.
+    pub async fn find_all<'e, C>(
+        db: C,
+        org_pid: Uuid,
+        resources: &[Resource],
+        retention_days: u32,
+    ) -> ModelResult<Vec<TrashItem>>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        let resources = resources.iter().map(Resource::as_str).collect::<Vec<_>>();
+
+        let query = format!(
+            "
+            SELECT
+                    t.resource,
+                    t.record_id,
+                    t.animal_pid,
+                    t.animal_tag_id,
+                    t.summary,
+                    t.deleted_at,
+                    t.deleted_by,
+                    NULLIF(CONCAT_WS(' ', u.first_name, u.last_name), '') AS deleted_by_name,
+                    t.deleted_at + MAKE_INTERVAL(days => $3) AS purge_after
+                FROM ({TRASH_QUERY}) t
+                LEFT JOIN users u ON t.deleted_by = u.pid
+                WHERE t.resource = ANY($2)
+            ORDER BY t.deleted_at DESC, t.record_id
+            "
+        );
+
+        sqlx::query_as::<_, TrashItem>(&query)
+            .bind(org_pid)
+            .bind(resources)
+            .bind(i32::try_from(retention_days).unwrap_or(i32::MAX))
+            .fetch_all(db)
+            .await
+            .map_err(Into::into)
+    }
+
+    /// Hard-deletes every row that has been in the trash for longer than
+    /// `retention_days`, across all organisations.
+    pub async fn purge(db: &mut PgConnection, retention_days: u32) -> ModelResult<PurgeSummary> {
+        let days = i32::try_from(retention_days).unwrap_or(i32::MAX);
+        let mut summary = PurgeSummary::default();
+
+        for (table, count) in [
+            ("health_records", &mut summary.health_records),
+            ("production_records", &mut summary.production_records),
+            ("weight_records", &mut summary.weight_records),
+        ] {
+            let query =
+                format!("DELETE FROM {table} WHERE deleted_at < NOW() - MAKE_INTERVAL(days => $1)");
+
+            *count = sqlx::query(&query)
+                .bind(days)
+                .execute(&mut *db)
+                .await?
+                .rows_affected();
+        }
+
+        // Offspring outlive purged parents.
+        sqlx::query(
+            "
+            UPDATE animals SET
+                parent_female_id = CASE WHEN f.pid IS NULL THEN child.parent_female_id END,
+                parent_male_id = CASE WHEN m.pid IS NULL THEN child.parent_male_id END
+            FROM animals child
+            LEFT JOIN animals f
+                ON child.parent_female_id = f.pid
+                AND f.deleted_at < NOW() - MAKE_INTERVAL(days => $1)
+            LEFT JOIN animals m
+                ON child.parent_male_id = m.pid
+                AND m.deleted_at < NOW() - MAKE_INTERVAL(days => $1)
+            WHERE animals.id = child.id
+                AND (f.pid IS NOT NULL OR m.pid IS NOT NULL)
+            ",
+        )
+        .bind(days)
+        .execute(&mut *db)
+        .await?;
+
+        summary.animals =
+            sqlx::query("DELETE FROM animals WHERE deleted_at < NOW() - MAKE_INTERVAL(days => $1)")
+                .bind(days)
+                .execute(&mut *db)
+                .await?
+                .rows_affected();
+
+        Ok(summary)
+    }
+}
+
+/// Fails with a conflict when the record belongs to a trashed animal, which
+/// has to be restored first.
+pub(crate) async fn ensure_animal_restored(
+    db: &mut PgConnection,
+    table: &str,
+    org_pid: Uuid,
+    id: i32,
+) -> ModelResult<()> {
+    let query = format!(
+        "SELECT a.deleted_at IS NOT NULL
+        FROM {table} r
+        JOIN animals a ON r.animal_pid = a.pid
+        WHERE r.id = $1 AND r.organisation_pid = $2 AND r.deleted_at IS NOT NULL"
+    );
+
+    let animal_deleted = sqlx::query_scalar::<_, bool>(&query)
+        .bind(id)
+        .bind(org_pid)
+        .fetch_optional(db)
+        .await?
+        .ok_or(ModelError::EntityNotFound)?;
+
+    if animal_deleted {
+        return Err(ModelError::Conflict(
+            "The animal this record belongs to is in the trash, restore it first".into(),
+        ));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,398 @@
+#![allow(clippy::missing_errors_doc)]
+
+//! Trends read the summary snapshots back as a time series: the latest
+//! snapshot of each period stands for that period.
+
+use std::{fmt, str::FromStr};
+
+use chrono::{Days, NaiveDate};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{Executor, Postgres, prelude::FromRow};
+use uuid::Uuid;
+
+use crate::models::{ModelError, ModelResult, settings::OrganisationSettings};
+
+/// The most periods one trend may cover.
+const MAX_PERIODS: i64 = 400;
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct TrendQuery {
+    pub metric: String,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub interval: Option<String>,
+    /// The species or breeds to compare, separated by commas. All of them
+    /// when left out.
+    pub compare: Option<String>,
+    /// Only the breeds of this specie.
+    pub specie: Option<String>,
+}
+
+/// The summaries a trend is read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrendSource {
+    Livestock,
+    Species,
+    Breeds,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrendMetric {
+    Total,
+    Active,
+    Deceased,
+    AverageWeight,
+    PurchaseValue,
+}
+
+impl TrendMetric {
+    pub const ALL: [Self; 5] = [
+        Self::Total,
+        Self::Active,
+        Self::Deceased,
+        Self::AverageWeight,
+        Self::PurchaseValue,
+    ];
+
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Total => "total",
+            Self::Active => "active",
+            Self::Deceased => "deceased",
+            Self::AverageWeight => "average_weight",
+            Self::PurchaseValue => "purchase_value",
+        }
+    }
+
+    /// The SQL for the metric's value in a row of the source's summaries.
+    fn column(self, source: TrendSource) -> ModelResult<&'static str> {
+        Ok(match (self, source) {
+            (Self::Total, _) => "total::numeric",
+            (Self::Active, _) => "active::numeric",
+            (Self::Deceased, _) => "deceased::numeric",
+            (Self::AverageWeight, TrendSource::Livestock) => {
+                return Err(ModelError::Validation(
+                    "The livestock summary has no average weight; use the categories or breeds trends"
+                        .into(),
+                ));
+            }
+            // Weighted by how many males and females were weighed.
+            (Self::AverageWeight, _) => {
+                "ROUND(
+                    (COALESCE(average_weight_male * males, 0)
+                        + COALESCE(average_weight_female * females, 0))
+                    / NULLIF(
+                        CASE WHEN average_weight_male IS NULL THEN 0 ELSE males END
+                            + CASE WHEN average_weight_female IS NULL THEN 0 ELSE females END,
+                        0
+                    ),
+                    2
+                )"
+            }
+            (Self::PurchaseValue, TrendSource::Livestock) => "total_purchased_value",
+            (Self::PurchaseValue, _) => "total_purchase_value",
+        })
+    }
+}
+
+impl fmt::Display for TrendMetric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for TrendMetric {
+    type Err = ModelError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .find(|metric| metric.as_str() == value.trim())
+            .copied()
+            .ok_or_else(|| {
+                ModelError::Validation(format!(
+                    "Unknown metric {value}; use total, active, deceased, average_weight or purchase_value"
+                ))
+            })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrendInterval {
+    Day,
+    Week,
+    Month,
+    Quarter,
+}
+
+impl TrendInterval {
+    pub const ALL: [Self; 4] = [Self::Day, Self::Week, Self::Month, Self::Quarter];
+
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Day => "day",
+            Self::Week => "week",
+            Self::Month => "month",
+            Self::Quarter => "quarter",
+        }
+    }
+
+    /// The length of one period, as a Postgres interval.
+    const fn step(self) -> &'static str {
+        match self {
+            Self::Day => "1 day",
+            Self::Week => "1 week",
+            Self::Month => "1 month",
+            Self::Quarter => "3 months",
+        }
+    }
+
+    /// Roughly how many periods lie between two dates, to refuse trends
+    /// that would be too long before asking Postgres for them.
+    const fn periods(self, from: NaiveDate, to: NaiveDate) -> i64 {
+        let days = to.signed_duration_since(from).num_days() + 1;
+
+        match self {
+            Self::Day => days,
+            Self::Week => days / 7 + 1,
+            Self::Month => days / 28 + 1,
+            Self::Quarter => days / 90 + 1,
+        }
+    }
+}
+
+impl FromStr for TrendInterval {
+    type Err = ModelError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .find(|interval| interval.as_str() == value.trim())
+            .copied()
+            .ok_or_else(|| {
+                ModelError::Validation(format!(
+                    "Unknown interval {value}; use day, week, month or quarter"
+                ))
+            })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrendPoint {
+    pub period: NaiveDate,
+    /// The metric in the period's latest snapshot, or nothing when no
+    /// summary was generated in the period.
+    pub value: Option<Decimal>,
+    /// The difference from the last period before with a value.
+    pub change: Option<Decimal>,
+    pub change_percent: Option<Decimal>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrendSeries {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub specie: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub breed: Option<String>,
+    pub points: Vec<TrendPoint>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Trend {
+    pub metric: TrendMetric,
+    pub interval: TrendInterval,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub series: Vec<TrendSeries>,
+}
+
+#[derive(Debug, FromRow)]
+struct TrendRow {
+    specie: Option<String>,
+    breed: Option<String>,
+    period: NaiveDate,
+    value: Option<Decimal>,
+}
+
+impl TrendSource {
+    /// The summaries of the source, as `specie`, `breed`, `created_at` and the
+    /// `value` column given. Binds the organisation as `$4`, the names compared
+    /// as `$5` and, for breeds, the specie as `$6`.
+    fn snapshots(self, value: &str) -> String {
+        match self {
+            Self::Livestock => format!(
+                "SELECT NULL::text AS specie, NULL::text AS breed, created_at, {value} AS value
+                FROM livestock_summary
+                WHERE organisation_pid = $4"
+            ),
+            Self::Species => format!(
+                "SELECT specie_name::text AS specie, NULL::text AS breed, created_at, {value} AS value
+                FROM species_summary
+                WHERE organisation_pid = $4
+                    AND ($5::text[] IS NULL OR LOWER(specie_name) = ANY($5))"
+            ),
+            Self::Breeds => format!(
+                "SELECT specie_name::text AS specie, breed_name::text AS breed, created_at, {value} AS value
+                FROM breed_summary
+                WHERE organisation_pid = $4
+                    AND ($5::text[] IS NULL OR LOWER(breed_name) = ANY($5))
+                    AND ($6::text IS NULL OR specie_name ILIKE $6)"
+            ),
+        }
+    }
+}
+
+impl TrendSeries {
+    /// Groups rows ordered by specie, breed and period into series, each point
+    /// with its change from the last period that had a value.
+    fn from_rows(rows: Vec<TrendRow>) -> Vec<Self> {
+        let mut series: Vec<Self> = Vec::new();
+        for row in rows {
+            let point = TrendPoint {
+                period: row.period,
+                value: row.value,
+                change: None,
+                change_percent: None,
+            };
+
+            match series.last_mut() {
+                Some(last) if last.specie == row.specie && last.breed == row.breed => {
+                    last.points.push(point);
+                }
+                _ => series.push(Self {
+                    specie: row.specie,
+                    breed: row.breed,
+                    points: vec![point],
+                }),
+            }
+        }
+
+        for series in &mut series {
+            let mut previous: Option<Decimal> = None;
+
+            for point in &mut series.points {
+                let Some(value) = point.value else {
+                    continue;
+                };
+
+                if let Some(previous) = previous {
+                    let change = value - previous;
+                    point.change = Some(change);
+                    point.change_percent = (!previous.is_zero())
+                        .then(|| (change / previous * Decimal::ONE_HUNDRED).round_dp(2));
+                }
+                previous = Some(value);
+            }
+        }
+
+        series
+    }
+}
+
+impl Trend {
+    /// Reads the metric from the source's summaries, one series for the
+    /// livestock or for each specie or breed compared.
+    pub async fn find<'e, C>(
+        db: &C,
+        org_pid: Uuid,
+        source: TrendSource,
+        params: &TrendQuery,
+    ) -> ModelResult<Self>
+    where
+        for<'a> &'a C: Executor<'e, Database = Postgres>,
+    {
+        let metric = params.metric.parse::<TrendMetric>()?;
+        let interval = params
+            .interval
+            .as_deref()
+            .map_or(Ok(TrendInterval::Month), str::parse)?;
+
+        let to = match params.to {
+            Some(to) => to,
+            None => OrganisationSettings::find(db, org_pid).await?.today(),
+        };
+        let from = params
+            .from
+            .unwrap_or_else(|| to.checked_sub_days(Days::new(365)).unwrap_or(to));
+
+        if from > to {
+            return Err(ModelError::Validation(
+                "A trend must start before it ends".into(),
+            ));
+        }
+        if interval.periods(from, to) > MAX_PERIODS {
+            return Err(ModelError::Validation(format!(
+                "A trend covers at most {MAX_PERIODS} periods; use a longer interval or a shorter range"
+            )));
+        }
+
+        let compare = params.compare.as_deref().map(|names| {
+            names
+                .split(',')
+                .map(|name| name.trim().to_lowercase())
+                .filter(|name| !name.is_empty())
+                .collect::<Vec<_>>()
+        });
+
+        let value = metric.column(source)?;
+        let snapshots = source.snapshots(value);
+
+        let query = format!(
+            "WITH periods AS (
+                SELECT generate_series(
+                    date_trunc($1, $2::date::timestamp),
+                    date_trunc($1, $3::date::timestamp),
+                    $7::interval
+                )::date AS period
+            ),
+            snapshots AS (
+                SELECT DISTINCT ON (specie, breed, period)
+                    specie, breed, date_trunc($1, created_at)::date AS period, value
+                FROM ({snapshots}) s
+                WHERE created_at >= date_trunc($1, $2::date::timestamp)
+                    AND created_at < $3::date + 1
+                ORDER BY specie, breed, period, created_at DESC
+            ),
+            subjects AS (
+                SELECT DISTINCT specie, breed FROM snapshots
+            )
+            SELECT subjects.specie, subjects.breed, periods.period, snapshots.value
+            FROM subjects
+            CROSS JOIN periods
+            LEFT JOIN snapshots
+                ON snapshots.specie IS NOT DISTINCT FROM subjects.specie
+                AND snapshots.breed IS NOT DISTINCT FROM subjects.breed
+                AND snapshots.period = periods.period
+            ORDER BY subjects.specie NULLS FIRST, subjects.breed NULLS FIRST, periods.period"
+        );
+
+        let rows = sqlx::query_as::<_, TrendRow>(&query)
+            .bind(interval.as_str())
+            .bind(from)
+            .bind(to)
+            .bind(org_pid)
+            .bind(compare)
+            .bind(params.specie.as_deref())
+            .bind(interval.step())
+            .fetch_all(db)
+            .await?;
+
+        let series = TrendSeries::from_rows(rows);
+
+        Ok(Self {
+            metric,
+            interval,
+            from,
+            to,
+            series,
+        })
+    }
+}
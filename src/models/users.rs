@@ -8,14 +8,18 @@ use axum::extract::{FromRef, FromRequestParts};
 use chrono::{DateTime, FixedOffset, Utc};
 use rand::{Rng, distr::Alphanumeric};
 use serde::{Deserialize, Serialize};
-use sqlx::{Encode, Executor, PgPool, Postgres, prelude::FromRow};
+use sqlx::{
+    Encode, Executor, PgConnection, PgPool, Postgres, postgres::PgQueryResult, prelude::FromRow,
+};
 use uuid::Uuid;
 
 use crate::{AppContext, middlewares::TokenClaims, seed::Seedable};
 
 use super::{
     ModelError, ModelResult,
-    dto::{CreateNewUser, RegisterAdmin, UpdatePassword, Validator},
+    dto::{RegisterAdmin, UpdatePassword, UpdateUserRole, Validator},
+    invitations::Invitation,
+    memberships::Membership,
 };
 
 #[derive(Debug, Default, Clone, Deserialize, Serialize)]
@@ -42,6 +46,8 @@ pub struct User {
     pub(crate) first_name: String,
     pub(crate) last_name: String,
     pub(crate) is_active: bool,
+    #[serde(default)]
+    pub(crate) is_platform_operator: bool,
     pub(crate) password_change_required: bool,
     pub(crate) reset_token: Option<String>,
     pub(crate) reset_token_sent_at: Option<DateTime<FixedOffset>>,
@@ -51,24 +57,117 @@ pub struct User {
     pub(crate) updated_at: DateTime<FixedOffset>,
 }
 
+/// A user as a member of an organisation. The organisation, role and active
+/// flag come from the membership `m` rather than the user's home.
+const MEMBER_COLUMNS: &str = "
+    u.id, u.pid, m.organisation_pid, m.role, u.email, u.password_hash, u.first_name,
+    u.last_name, (u.is_active AND m.is_active) AS is_active, u.is_platform_operator,
+    u.password_change_required, u.reset_token, u.reset_token_sent_at, u.last_login,
+    u.last_password_change, u.created_at, u.updated_at";
+
 impl User {
-    pub async fn read_all<'e, C>(db: C, conditions: &UserQuery) -> ModelResult<Vec<Self>>
+    pub async fn read_all<'e, C>(
+        db: C,
+        org_pid: Uuid,
+        conditions: &UserQuery,
+    ) -> ModelResult<Vec<Self>>
     where
         C: Executor<'e, Database = Postgres>,
     {
-        let mut query = sqlx::query_as::<_, Self>("SELECT * FROM users");
+        sqlx::query_as::<_, Self>(&format!(
+            "
+            SELECT {MEMBER_COLUMNS}
+            FROM users u
+            JOIN memberships m ON m.user_pid = u.pid
+            WHERE m.organisation_pid = $1
+                AND ($2::TEXT IS NULL OR m.role ILIKE $2)
+                AND ($3::BOOLEAN IS NULL OR (u.is_active AND m.is_active) = $3)
+            ORDER BY u.created_at DESC
+            ",
+        ))
+        .bind(org_pid)
+        .bind(conditions.role.as_deref().map(str::trim))
+        .bind(conditions.active)
+        .fetch_all(db)
+        .await
+        .map_err(Into::into)
+    }
 
-        if let Some(role_id) = &conditions.role {
-            query = sqlx::query_as("SELECT * FROM users WHERE role LIKE $1")
-                .bind(format!("%{role_id}%",));
-        }
+    pub async fn read_by_pid<'e, C>(db: C, org_pid: Uuid, pid: Uuid) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        Self::find_member(db, pid, Some(org_pid))
+            .await?
+            .ok_or_else(|| ModelError::EntityNotFound)
+    }
 
-        if let Some(is_active) = conditions.active {
-            query = sqlx::query_as("SELECT * FROM users WHERE is_active LIKE $1")
-                .bind(format!("%{is_active}%",));
-        }
+    /// Fetches the user as a member of `org_pid`, or of their home
+    /// organisation when it is not given.
+    pub async fn find_member<'e, C>(
+        db: C,
+        pid: Uuid,
+        org_pid: Option<Uuid>,
+    ) -> ModelResult<Option<Self>>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_as::<_, Self>(&format!(
+            "
+            SELECT {MEMBER_COLUMNS}
+            FROM users u
+            JOIN memberships m ON m.user_pid = u.pid
+            WHERE u.pid = $1 AND m.organisation_pid = COALESCE($2, u.organisation_pid)
+            ",
+        ))
+        .bind(pid)
+        .bind(org_pid)
+        .fetch_optional(db)
+        .await
+        .map_err(Into::into)
+    }
+
+    /// Fetches the user as a member of the organisation the token was issued
+    /// for. Tokens issued before organisations could be switched fall back to
+    /// the user's home.
+    pub async fn find_by_claims<'e, C>(db: C, claims: &TokenClaims) -> ModelResult<Option<Self>>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        let pid = Uuid::parse_str(&claims.sub)?;
+        let org_pid = claims
+            .organisation
+            .as_deref()
+            .map(Uuid::parse_str)
+            .transpose()?;
+
+        Self::find_member(db, pid, org_pid).await
+    }
 
-        query.fetch_all(db).await.map_err(Into::into)
+    /// Fetches the user logging in with `email` as a member of their home
+    /// organisation, or of another one if they are no longer active at home.
+    pub async fn find_for_login<'e, C>(db: C, email: &str) -> ModelResult<Option<Self>>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_as::<_, Self>(&format!(
+            "
+            SELECT {MEMBER_COLUMNS}
+            FROM users u
+            JOIN memberships m ON m.user_pid = u.pid
+            WHERE u.email = $1
+            ORDER BY
+                (m.organisation_pid = u.organisation_pid AND m.is_active) DESC,
+                m.is_active DESC,
+                m.created_at,
+                m.id
+            LIMIT 1
+            ",
+        ))
+        .bind(email.trim())
+        .fetch_optional(db)
+        .await
+        .map_err(Into::into)
     }
 
     pub async fn read_by_id<'e, C>(db: C, id: i32) -> ModelResult<Self>
@@ -134,7 +233,7 @@ impl User {
             .map_err(Into::into)
     }
 
-    fn hash_password(plain_password: &str) -> ModelResult<String> {
+    pub(crate) fn hash_password(plain_password: &str) -> ModelResult<String> {
         let salt: SaltString = SaltString::generate(&mut OsRng);
         let password_hash: PasswordHash<'_> =
             Argon2::default().hash_password(plain_password.trim().as_bytes(), &salt)?;
@@ -200,42 +299,94 @@ impl User {
         Ok(query)
     }
 
-    pub async fn admin_create_user<'e, C>(
+    /// Adds someone who accepted an invitation to the organisation. People
+    /// without an account get one with the password they chose, while existing
+    /// users confirm it is them with their current password.
+    pub(crate) async fn create_invited(
+        db: &mut PgConnection,
+        invitation: &Invitation,
+        password: &str,
+    ) -> ModelResult<Self> {
+        if let Some(user) = Self::find_by_email(&mut *db, &invitation.email).await? {
+            if !user.validate_password(password.trim())? {
+                return Err(ModelError::Validation(
+                    "Password does not match your existing account".into(),
+                ));
+            }
+
+            Membership::create(
+                &mut *db,
+                user.pid,
+                invitation.organisation_pid,
+                &invitation.role,
+            )
+            .await
+            .map_err(|error| match error {
+                ModelError::Sqlx(error) => Self::unknown_role(error),
+                error => error,
+            })?;
+
+            return Self::find_member(&mut *db, user.pid, Some(invitation.organisation_pid))
+                .await?
+                .ok_or_else(|| ModelError::EntityNotFound);
+        }
+
+        let password = Self::hash_password(password)?;
+
+        sqlx::query_as::<_, Self>(
+            "
+            INSERT INTO users
+            (organisation_pid, email, first_name, last_name, role, password_hash, password_change_required)
+            VALUES ($1, $2, $3, $4, $5, $6, FALSE) RETURNING * ",
+        )
+        .bind(invitation.organisation_pid)
+        .bind(&invitation.email)
+        .bind(&invitation.first_name)
+        .bind(&invitation.last_name)
+        .bind(&invitation.role)
+        .bind(password)
+        .fetch_one(&mut *db)
+        .await
+        .map_err(Self::unknown_role)
+    }
+
+    /// Creates the account of someone logging in through single sign-on for
+    /// the first time. They have no password of their own, so it is set to
+    /// one nobody knows.
+    pub(crate) async fn create_provisioned<'e, C>(
         db: C,
         org_pid: Uuid,
-        dto: &CreateNewUser<'_>,
+        role: &str,
+        email: &str,
+        first_name: &str,
+        last_name: &str,
     ) -> ModelResult<Self>
     where
         C: Executor<'e, Database = Postgres>,
     {
-        let validator = Validator::new(dto);
-        let dto = validator.validate()?;
-
-        let temp_password: String = rand::rng()
+        let secret: String = rand::rng()
             .sample_iter(&Alphanumeric)
-            .take(12)
+            .take(40)
             .map(char::from)
             .collect();
+        let password = Self::hash_password(&secret)?;
+        let name = |name: &str| name.trim().chars().take(50).collect::<String>();
 
-        let password = Self::hash_password(&temp_password)?;
-
-        let user = sqlx::query_as::<_, Self>(
+        sqlx::query_as::<_, Self>(
             "
-            INSERT INTO users 
-            (organisation_pid, email, first_name, last_name, role, password_hash)
-            VALUES ($1, $2, $3, $4, $5, $6) RETURNING * ",
+            INSERT INTO users
+            (organisation_pid, email, first_name, last_name, role, password_hash, password_change_required)
+            VALUES ($1, $2, $3, $4, $5, $6, FALSE) RETURNING * ",
         )
         .bind(org_pid)
-        .bind(dto.email.trim())
-        .bind(dto.first_name.trim())
-        .bind(dto.last_name.trim())
-        .bind(dto.role.as_ref().trim())
-        // User must change his/her password upon first login.
+        .bind(email)
+        .bind(name(first_name))
+        .bind(name(last_name))
+        .bind(role)
         .bind(password)
         .fetch_one(db)
-        .await?;
-
-        Ok(user)
+        .await
+        .map_err(Self::unknown_role)
     }
 
     pub async fn update_password<'e, C>(&self, db: C, dto: &UpdatePassword<'_>) -> ModelResult<Self>
@@ -261,6 +412,215 @@ impl User {
         Ok(query)
     }
 
+    pub async fn update_role<'e, C>(&self, db: C, dto: &UpdateUserRole<'_>) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        let validator = Validator::new(dto);
+        let dto = validator.validate()?;
+
+        // The role on `users` follows the home membership.
+        sqlx::query_as::<_, Self>(&format!(
+            "
+            WITH m AS (
+                UPDATE memberships SET role = $3
+                WHERE user_pid = $1 AND organisation_pid = $2
+                RETURNING *
+            ), home AS (
+                UPDATE users SET role = $3
+                WHERE pid = $1 AND organisation_pid = $2
+            )
+            SELECT {MEMBER_COLUMNS} FROM users u JOIN m ON m.user_pid = u.pid
+            ",
+        ))
+        .bind(self.pid)
+        .bind(self.organisation_pid)
+        .bind(dto.role.trim().to_lowercase())
+        .fetch_one(db)
+        .await
+        .map_err(Self::unknown_role)
+    }
+
+    // Roles are checked by a trigger since they may be custom roles of the
+    // user's organisation.
+    fn unknown_role(error: sqlx::Error) -> ModelError {
+        match error {
+            sqlx::Error::Database(dberr) if dberr.is_foreign_key_violation() => {
+                ModelError::Validation("Role does not exist in this organisation".into())
+            }
+            error => ModelError::Sqlx(error),
+        }
+    }
+
+    /// Activates or deactivates the user's membership of their current
+    /// organisation. Deactivated members are rejected at login and by the auth
+    /// middlewares, even with an unexpired token.
+    pub async fn set_active<'e, C>(&self, db: C, active: bool) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_as::<_, Self>(&format!(
+            "
+            WITH m AS (
+                UPDATE memberships SET is_active = $3
+                WHERE user_pid = $1 AND organisation_pid = $2
+                RETURNING *
+            )
+            SELECT {MEMBER_COLUMNS} FROM users u JOIN m ON m.user_pid = u.pid
+            ",
+        ))
+        .bind(self.pid)
+        .bind(self.organisation_pid)
+        .bind(active)
+        .fetch_one(db)
+        .await
+        .map_err(Into::into)
+    }
+
+    /// Grants or revokes access to the platform console, which acts across
+    /// every organisation.
+    pub async fn set_platform_operator<'e, C>(&self, db: C, operator: bool) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_as::<_, Self>(
+            "UPDATE users SET is_platform_operator = $2 WHERE pid = $1 RETURNING *",
+        )
+        .bind(self.pid)
+        .bind(operator)
+        .fetch_one(db)
+        .await
+        .map_err(Into::into)
+    }
+
+    /// The organisation's longest-standing active admin.
+    pub async fn find_organisation_admin<'e, C>(db: C, org_pid: Uuid) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_as::<_, Self>(&format!(
+            "
+            SELECT {MEMBER_COLUMNS}
+            FROM users u
+            JOIN memberships m ON m.user_pid = u.pid
+            WHERE m.organisation_pid = $1 AND m.role = 'admin' AND u.is_active AND m.is_active
+            ORDER BY m.created_at, m.id
+            LIMIT 1
+            ",
+        ))
+        .bind(org_pid)
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    /// Flags the account so the user has to set a new password before they
+    /// can log in again.
+    pub async fn require_password_reset<'e, C>(&self, db: C) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_as::<_, Self>(&format!(
+            "
+            WITH u AS (
+                UPDATE users
+                SET password_change_required = TRUE
+                WHERE pid = $1 RETURNING *
+            )
+            SELECT {MEMBER_COLUMNS}
+            FROM u JOIN memberships m ON m.user_pid = u.pid AND m.organisation_pid = $2
+            ",
+        ))
+        .bind(self.pid)
+        .bind(self.organisation_pid)
+        .fetch_one(db)
+        .await
+        .map_err(Into::into)
+    }
+
+    pub async fn delete_by_pid<'e, C>(db: C, org_pid: Uuid, pid: Uuid) -> ModelResult<PgQueryResult>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        // Removes the membership. The account goes with it unless the user
+        // belongs to other organisations, in which case their home moves to
+        // one of those.
+        let query = sqlx::query(
+            "
+            WITH account AS (
+                DELETE FROM users
+                WHERE pid = $1 AND NOT EXISTS (
+                    SELECT 1 FROM memberships WHERE user_pid = $1 AND organisation_pid <> $2
+                )
+                AND EXISTS (
+                    SELECT 1 FROM memberships WHERE user_pid = $1 AND organisation_pid = $2
+                )
+            ), home AS (
+                UPDATE users u
+                SET organisation_pid = other.organisation_pid, role = other.role
+                FROM (
+                    SELECT organisation_pid, role FROM memberships
+                    WHERE user_pid = $1 AND organisation_pid <> $2
+                    ORDER BY created_at, id
+                    LIMIT 1
+                ) other
+                WHERE u.pid = $1 AND u.organisation_pid = $2
+            )
+            DELETE FROM memberships WHERE user_pid = $1 AND organisation_pid = $2
+            ",
+        )
+        .bind(pid)
+        .bind(org_pid)
+        .execute(db)
+        .await;
+
+        match query {
+            Ok(result) if result.rows_affected() == 0 => Err(ModelError::EntityNotFound),
+            Ok(result) => Ok(result),
+            Err(sqlx::Error::Database(dberr)) if dberr.is_foreign_key_violation() => {
+                Err(ModelError::Conflict(
+                    "User has recorded data and cannot be deleted, deactivate them instead".into(),
+                ))
+            }
+            Err(error) => Err(ModelError::Sqlx(error)),
+        }
+    }
+
+    #[must_use]
+    pub fn pid(&self) -> Uuid {
+        self.pid
+    }
+
+    #[must_use]
+    pub fn organisation_pid(&self) -> Uuid {
+        self.organisation_pid
+    }
+
+    #[must_use]
+    pub fn email(&self) -> &str {
+        &self.email
+    }
+
+    #[must_use]
+    pub fn role(&self) -> &str {
+        &self.role
+    }
+
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        self.is_active
+    }
+
+    #[must_use]
+    pub fn is_platform_operator(&self) -> bool {
+        self.is_platform_operator
+    }
+
+    #[must_use]
+    pub fn password_change_required(&self) -> bool {
+        self.password_change_required
+    }
+
     pub async fn seed(db: &PgPool, path: &str) -> ModelResult<()> {
         let users = Self::load_file(path).await?;
 
@@ -312,11 +672,16 @@ where
         parts: &mut axum::http::request::Parts,
         state: &S,
     ) -> Result<Self, Self::Rejection> {
+        // Already loaded by the `AuthLayer` on protected routes.
+        if let Some(user) = parts.extensions.get::<Self>() {
+            return Ok(user.clone());
+        }
+
         let context = AppContext::from_ref(state);
 
         let token_claims = TokenClaims::from_request_parts(parts, state).await?;
 
-        let user = Self::find_by_claims_key(&context.db, &token_claims.sub)
+        let user = Self::find_by_claims(&context.db, &token_claims)
             .await?
             .ok_or_else(|| ModelError::EntityNotFound)?;
 
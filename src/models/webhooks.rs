@@ -0,0 +1,352 @@
+#![allow(clippy::missing_errors_doc)]
+
+use chrono::{DateTime, FixedOffset};
+use rand::{Rng, distr::Alphanumeric};
+use serde::{Deserialize, Serialize};
+use sqlx::{Executor, PgConnection, PgPool, Postgres, prelude::FromRow, types::Json};
+use uuid::Uuid;
+
+use super::{
+    ModelError, ModelResult,
+    dto::{CreateWebhook, UpdateWebhook, Validator, event_types},
+    users::User,
+};
+
+/// Marks a webhook's signing secret.
+pub const WEBHOOK_SECRET_PREFIX: &str = "whsec_";
+
+#[derive(Debug, Deserialize, Serialize, FromRow, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Webhook {
+    pub id: i32,
+    pub pid: Uuid,
+    pub organisation_pid: Uuid,
+    pub url: String,
+    #[serde(skip_serializing)]
+    pub(crate) secret: String,
+    pub event_types: Vec<String>,
+    pub is_active: bool,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<FixedOffset>,
+    pub updated_at: DateTime<FixedOffset>,
+}
+
+/// A delivery as shown in a webhook's delivery log.
+#[derive(Debug, Deserialize, Serialize, FromRow, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookDelivery {
+    pub id: i32,
+    pub pid: Uuid,
+    pub webhook_pid: Uuid,
+    pub event_pid: Uuid,
+    pub event_type: String,
+    pub status: String,
+    pub attempts: i32,
+    pub next_attempt_at: DateTime<FixedOffset>,
+    pub last_attempt_at: Option<DateTime<FixedOffset>>,
+    pub response_status: Option<i32>,
+    pub last_error: Option<String>,
+    pub delivered_at: Option<DateTime<FixedOffset>>,
+    pub created_at: DateTime<FixedOffset>,
+}
+
+/// A delivery that is due, with what is needed to send it.
+#[derive(Debug, FromRow, Clone)]
+pub struct DueDelivery {
+    pub pid: Uuid,
+    pub attempts: i32,
+    pub url: String,
+    pub secret: String,
+    pub event_pid: Uuid,
+    pub event_type: String,
+    pub organisation_pid: Uuid,
+    pub payload: Json<serde_json::Value>,
+    pub occurred_at: DateTime<FixedOffset>,
+}
+
+const DELIVERY_COLUMNS: &str = "
+    d.id, d.pid, d.webhook_pid, d.event_pid, e.event_type, d.status, d.attempts,
+    d.next_attempt_at, d.last_attempt_at, d.response_status, d.last_error, d.delivered_at,
+    d.created_at";
+
+impl Webhook {
+    fn issue_secret() -> String {
+        let secret: String = rand::rng()
+            .sample_iter(&Alphanumeric)
+            .take(32)
+            .map(char::from)
+            .collect();
+
+        format!("{WEBHOOK_SECRET_PREFIX}{secret}")
+    }
+
+    /// The secret payloads are signed with. It is only shown when the webhook
+    /// is created.
+    #[must_use]
+    pub fn secret(&self) -> &str {
+        &self.secret
+    }
+
+    pub async fn create<'e, C>(db: C, creator: &User, dto: &CreateWebhook<'_>) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        let validator = Validator::new(dto);
+        let dto = validator.validate()?;
+
+        sqlx::query_as::<_, Self>(
+            "
+            INSERT INTO webhooks (organisation_pid, url, secret, event_types, created_by)
+            VALUES ($1, $2, $3, $4, $5) RETURNING *",
+        )
+        .bind(creator.organisation_pid)
+        .bind(dto.url.trim())
+        .bind(Self::issue_secret())
+        .bind(event_types(&dto.event_types)?)
+        .bind(creator.pid)
+        .fetch_one(db)
+        .await
+        .map_err(Into::into)
+    }
+
+    pub async fn find_all<'e, C>(db: C, org_pid: Uuid) -> ModelResult<Vec<Self>>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_as::<_, Self>(
+            "SELECT * FROM webhooks WHERE organisation_pid = $1 ORDER BY created_at DESC, id DESC",
+        )
+        .bind(org_pid)
+        .fetch_all(db)
+        .await
+        .map_err(Into::into)
+    }
+
+    pub async fn find_by_pid<'e, C>(db: C, org_pid: Uuid, pid: Uuid) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_as::<_, Self>("SELECT * FROM webhooks WHERE pid = $1 AND organisation_pid = $2")
+            .bind(pid)
+            .bind(org_pid)
+            .fetch_optional(db)
+            .await?
+            .ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    /// Changes where the webhook points, what it is subscribed to, or pauses
+    /// it. Deliveries for a paused webhook wait until it is resumed.
+    pub async fn update<'e, C>(
+        db: C,
+        org_pid: Uuid,
+        pid: Uuid,
+        dto: &UpdateWebhook<'_>,
+    ) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        let validator = Validator::new(dto);
+        let dto = validator.validate()?;
+
+        let event_types = dto.event_types.as_deref().map(event_types).transpose()?;
+
+        sqlx::query_as::<_, Self>(
+            "
+            UPDATE webhooks SET
+                url = COALESCE($3, url),
+                event_types = COALESCE($4, event_types),
+                is_active = COALESCE($5, is_active)
+            WHERE pid = $1 AND organisation_pid = $2 RETURNING *",
+        )
+        .bind(pid)
+        .bind(org_pid)
+        .bind(dto.url.as_deref().map(str::trim))
+        .bind(event_types)
+        .bind(dto.is_active)
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    /// Deletes the webhook along with its delivery log.
+    pub async fn delete_by_pid<'e, C>(db: C, org_pid: Uuid, pid: Uuid) -> ModelResult<()>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        let result = sqlx::query("DELETE FROM webhooks WHERE pid = $1 AND organisation_pid = $2")
+            .bind(pid)
+            .bind(org_pid)
+            .execute(db)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(ModelError::EntityNotFound);
+        }
+
+        Ok(())
+    }
+}
+
+impl WebhookDelivery {
+    /// The webhook's most recent deliveries, newest first.
+    pub async fn find_by_webhook(
+        db: &mut PgConnection,
+        org_pid: Uuid,
+        webhook_pid: Uuid,
+    ) -> ModelResult<Vec<Self>> {
+        let webhook = Webhook::find_by_pid(&mut *db, org_pid, webhook_pid).await?;
+
+        sqlx::query_as::<_, Self>(&format!(
+            "
+            SELECT {DELIVERY_COLUMNS}
+            FROM webhook_deliveries d
+            JOIN domain_events e ON d.event_pid = e.pid
+            WHERE d.webhook_pid = $1
+            ORDER BY d.created_at DESC, d.id DESC
+            LIMIT 100
+            "
+        ))
+        .bind(webhook.pid)
+        .fetch_all(&mut *db)
+        .await
+        .map_err(Into::into)
+    }
+
+    pub async fn find_by_pid<'e, C>(
+        db: C,
+        org_pid: Uuid,
+        webhook_pid: Uuid,
+        pid: Uuid,
+    ) -> ModelResult<Self>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_as::<_, Self>(&format!(
+            "
+            SELECT {DELIVERY_COLUMNS}
+            FROM webhook_deliveries d
+            JOIN domain_events e ON d.event_pid = e.pid
+            WHERE d.pid = $1 AND d.webhook_pid = $2 AND d.organisation_pid = $3
+            "
+        ))
+        .bind(pid)
+        .bind(webhook_pid)
+        .bind(org_pid)
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    /// Queues the delivery to be sent again straight away, with a fresh set
+    /// of attempts. Works for delivered and failed deliveries alike.
+    pub async fn redeliver(
+        db: &mut PgConnection,
+        org_pid: Uuid,
+        webhook_pid: Uuid,
+        pid: Uuid,
+    ) -> ModelResult<Self> {
+        let delivery = Self::find_by_pid(&mut *db, org_pid, webhook_pid, pid).await?;
+
+        sqlx::query(
+            "
+            UPDATE webhook_deliveries
+            SET status = 'pending', attempts = 0, next_attempt_at = NOW()
+            WHERE pid = $1",
+        )
+        .bind(delivery.pid)
+        .execute(&mut *db)
+        .await?;
+
+        Self::find_by_pid(&mut *db, org_pid, webhook_pid, pid).await
+    }
+
+    /// Takes up to `limit` due deliveries of active webhooks. They are leased
+    /// for `lease` seconds so that other dispatchers leave them alone while
+    /// they are being sent.
+    pub async fn claim_due(db: &PgPool, limit: i64, lease: i32) -> ModelResult<Vec<DueDelivery>> {
+        sqlx::query_as::<_, DueDelivery>(
+            "
+            WITH due AS (
+                SELECT d.id
+                FROM webhook_deliveries d
+                JOIN webhooks w ON d.webhook_pid = w.pid
+                WHERE d.status = 'pending' AND d.next_attempt_at <= NOW() AND w.is_active
+                ORDER BY d.next_attempt_at
+                LIMIT $1
+                FOR UPDATE OF d SKIP LOCKED
+            )
+            UPDATE webhook_deliveries d
+            SET next_attempt_at = NOW() + make_interval(secs => $2)
+            FROM due, webhooks w, domain_events e
+            WHERE d.id = due.id AND d.webhook_pid = w.pid AND d.event_pid = e.pid
+            RETURNING
+                d.pid, d.attempts, w.url, w.secret, e.pid AS event_pid, e.event_type,
+                e.organisation_pid, e.payload, e.created_at AS occurred_at",
+        )
+        .bind(limit)
+        .bind(f64::from(lease))
+        .fetch_all(db)
+        .await
+        .map_err(Into::into)
+    }
+
+    pub async fn record_delivered<'e, C>(db: C, pid: Uuid, response_status: i32) -> ModelResult<()>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query(
+            "
+            UPDATE webhook_deliveries
+            SET
+                status = 'delivered',
+                attempts = attempts + 1,
+                last_attempt_at = NOW(),
+                delivered_at = NOW(),
+                response_status = $2,
+                last_error = NULL
+            WHERE pid = $1",
+        )
+        .bind(pid)
+        .bind(response_status)
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Records a failed attempt. The delivery is retried after `retry_delay`
+    /// seconds, doubling with each attempt, until `max_attempts` is reached.
+    pub async fn record_failure<'e, C>(
+        db: C,
+        pid: Uuid,
+        response_status: Option<i32>,
+        error: &str,
+        max_attempts: i32,
+        retry_delay: i32,
+    ) -> ModelResult<()>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query(
+            "
+            UPDATE webhook_deliveries
+            SET
+                status = CASE WHEN attempts + 1 >= $4 THEN 'failed' ELSE 'pending' END,
+                attempts = attempts + 1,
+                last_attempt_at = NOW(),
+                next_attempt_at = NOW() + make_interval(secs => $5 * POWER(2, LEAST(attempts, 16))),
+                response_status = $2,
+                last_error = $3
+            WHERE pid = $1",
+        )
+        .bind(pid)
+        .bind(response_status)
+        .bind(error)
+        .bind(max_attempts)
+        .bind(f64::from(retry_delay))
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+}
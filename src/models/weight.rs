@@ -2,7 +2,10 @@
 use chrono::{DateTime, FixedOffset, NaiveDate};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use sqlx::{Encode, Executor, FromRow, Postgres, postgres::PgQueryResult};
+use sqlx::{
+    Encode, Executor, FromRow, Postgres,
+    postgres::{PgConnection, PgQueryResult},
+};
 use uuid::Uuid;
 
 use crate::seed::Seedable;
@@ -10,6 +13,8 @@ use crate::seed::Seedable;
 use super::{
     ModelError, ModelResult,
     dto::records::{NewWeightRecord, UpdateWeightRecord},
+    settings::OrganisationSettings,
+    trash::ensure_animal_restored,
 };
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -85,6 +90,8 @@ const FETCH_ALL: &str = "
         users u ON a.created_by = u.pid
     WHERE
         a.organisation_pid = $1
+        AND a.deleted_at IS NULL
+        AND w.deleted_at IS NULL
 ";
 
 fn fetch_query(conditions: &str) -> String {
@@ -93,12 +100,12 @@ fn fetch_query(conditions: &str) -> String {
 
 impl WeightRecord {
     pub async fn find_all<'e, C>(
-        db: &C,
+        db: C,
         org_pid: Uuid,
         conditions: &WeightQuery,
     ) -> ModelResult<Vec<WeightResponse>>
     where
-        for<'a> &'a C: Executor<'e, Database = Postgres>,
+        C: Executor<'e, Database = Postgres>,
     {
         let mut query = sqlx::query_as::<_, WeightResponse>(FETCH_ALL).bind(org_pid);
 
@@ -121,9 +128,9 @@ impl WeightRecord {
         query.fetch_all(db).await.map_err(Into::into)
     }
 
-    pub async fn find_by_id<'e, C>(db: &C, org_pid: Uuid, id: i32) -> ModelResult<WeightResponse>
+    pub async fn find_by_id<'e, C>(db: C, org_pid: Uuid, id: i32) -> ModelResult<WeightResponse>
     where
-        for<'a> &'a C: Executor<'e, Database = Postgres>,
+        C: Executor<'e, Database = Postgres>,
     {
         let model = sqlx::query_as::<_, WeightResponse>(Box::leak(
             fetch_query("AND a.id = $2").into_boxed_str(),
@@ -136,28 +143,81 @@ impl WeightRecord {
         model.ok_or_else(|| ModelError::EntityNotFound)
     }
 
-    pub async fn delete_by_id<'e, C>(db: &C, org_pid: Uuid, id: i32) -> ModelResult<PgQueryResult>
+    pub async fn delete_by_id<'e, C>(
+        db: C,
+        org_pid: Uuid,
+        id: i32,
+        user_pid: Uuid,
+    ) -> ModelResult<PgQueryResult>
     where
-        for<'a> &'a C: Executor<'e, Database = Postgres>,
+        C: Executor<'e, Database = Postgres>,
     {
-        let query =
-            sqlx::query("DELETE FROM weight_records WHERE id = $1 AND organisation_pid = $2")
-                .bind(id)
-                .bind(org_pid)
-                .execute(db)
-                .await?;
+        let query = sqlx::query(
+            "UPDATE weight_records SET deleted_at = NOW(), deleted_by = $3
+            WHERE id = $1 AND organisation_pid = $2 AND deleted_at IS NULL",
+        )
+        .bind(id)
+        .bind(org_pid)
+        .bind(user_pid)
+        .execute(db)
+        .await?;
+
+        Ok(query)
+    }
+
+    pub async fn restore_by_id(db: &mut PgConnection, org_pid: Uuid, id: i32) -> ModelResult<Self> {
+        ensure_animal_restored(&mut *db, "weight_records", org_pid, id).await?;
+
+        let query = sqlx::query_as::<_, Self>(
+            "UPDATE weight_records SET deleted_at = NULL, deleted_by = NULL
+            WHERE id = $1 AND organisation_pid = $2
+            RETURNING *",
+        )
+        .bind(id)
+        .bind(org_pid)
+        .fetch_one(&mut *db)
+        .await?;
 
         Ok(query)
     }
 
+    /// The mass of the animal's most recent weighing on or before `date`.
+    pub async fn previous_mass<'e, C>(
+        db: C,
+        org_pid: Uuid,
+        tag_id: &str,
+        date: NaiveDate,
+    ) -> ModelResult<Option<Decimal>>
+    where
+        C: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query_scalar::<_, Decimal>(
+            "
+            SELECT w.mass
+            FROM weight_records w
+            JOIN animals a ON w.animal_pid = a.pid
+            WHERE a.tag_id = $1 AND w.organisation_pid = $2
+            AND w.record_date <= $3 AND w.deleted_at IS NULL
+            ORDER BY w.record_date DESC, w.id DESC
+            LIMIT 1
+            ",
+        )
+        .bind(tag_id)
+        .bind(org_pid)
+        .bind(date)
+        .fetch_optional(db)
+        .await
+        .map_err(Into::into)
+    }
+
     pub async fn create<'e, C>(
-        db: &C,
+        db: C,
         params: &NewWeightRecord<'_>,
         org_pid: Uuid,
         user_pid: Uuid,
     ) -> ModelResult<Self>
     where
-        for<'a> &'a C: Executor<'e, Database = Postgres>,
+        C: Executor<'e, Database = Postgres>,
     {
         let query = sqlx::query_as::<_, Self>(
             "
@@ -174,7 +234,7 @@ impl WeightRecord {
                 )
                 VALUES
                 (
-                    (SELECT pid FROM animals WHERE tag_id = $1 AND organisation_pid = $2),
+                    (SELECT pid FROM animals WHERE tag_id = $1 AND organisation_pid = $2 AND deleted_at IS NULL),
                     $2,
                     $3,
                     $4,
@@ -189,8 +249,8 @@ impl WeightRecord {
         .bind(params.tag_id.as_ref())
         .bind(org_pid)
         .bind(Decimal::new(params.mass, 2))
-        .bind(params.unit.as_ref())
-        .bind(params.status.as_ref().to_lowercase())
+        .bind(params.unit.as_deref().unwrap_or("kg"))
+        .bind(params.status.as_deref().unwrap_or("normal").to_lowercase())
         .bind(params.record_date)
         .bind(params.notes.as_ref())
         .bind(user_pid)
@@ -200,16 +260,38 @@ impl WeightRecord {
         Ok(query)
     }
 
-    pub async fn update_by_id<'e, C>(
-        db: &C,
+    /// Creates the record, in the organisation's weight unit and classified
+    /// against the animal's previous weighing unless they are given.
+    pub async fn weigh(
+        db: &mut PgConnection,
+        mut params: NewWeightRecord<'_>,
+        org_pid: Uuid,
+        user_pid: Uuid,
+    ) -> ModelResult<Self> {
+        let settings = OrganisationSettings::find(&mut *db, org_pid).await?;
+
+        if params.unit.is_none() {
+            params.unit = Some(settings.weight_unit.as_str().into());
+        }
+
+        if params.status.is_none() {
+            let previous_mass =
+                Self::previous_mass(&mut *db, org_pid, &params.tag_id, params.record_date).await?;
+            let mass = Decimal::new(params.mass, 2);
+
+            params.status = Some(settings.weight_status.classify(previous_mass, mass).into());
+        }
+
+        Self::create(&mut *db, &params, org_pid, user_pid).await
+    }
+
+    pub async fn update_by_id(
+        db: &mut PgConnection,
         id: i32,
         org_pid: Uuid,
         params: &UpdateWeightRecord<'_>,
-    ) -> ModelResult<Self>
-    where
-        for<'a> &'a C: Executor<'e, Database = Postgres>,
-    {
-        let model = Self::find_by_id(db, org_pid, id).await?;
+    ) -> ModelResult<Self> {
+        let model = Self::find_by_id(&mut *db, org_pid, id).await?;
 
         let mass = params.mass.map_or(model.mass, |mass| Decimal::new(mass, 2));
         let previous_mass = params
@@ -237,7 +319,7 @@ impl WeightRecord {
                     status = $7,
                     notes = $8
                 WHERE
-                    id = $1 AND organisation_pid = $2
+                    id = $1 AND organisation_pid = $2 AND deleted_at IS NULL
                 RETURNING *
             ",
         )
@@ -249,7 +331,7 @@ impl WeightRecord {
         .bind(record_date)
         .bind(status)
         .bind(notes)
-        .fetch_one(db)
+        .fetch_one(&mut *db)
         .await?;
 
         Ok(updated)
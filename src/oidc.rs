@@ -0,0 +1,182 @@
+#![allow(clippy::missing_errors_doc)]
+
+//! Polaris as an OIDC relying party: provider discovery, the
+//! authorization code flow with PKCE and ID token validation.
+
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, jwk::JwkSet};
+use reqwest::{Client, StatusCode, Url};
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Result, config::OidcConfig, models::identities::OidcLogin};
+
+/// The parts of a provider's discovery document Polaris uses.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProviderMetadata {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub jwks_uri: String,
+}
+
+/// The claims of an ID token Polaris reads.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct IdTokenClaims {
+    pub iss: String,
+    pub sub: String,
+    pub email: Option<String>,
+    pub email_verified: Option<bool>,
+    pub given_name: Option<String>,
+    pub family_name: Option<String>,
+    pub name: Option<String>,
+    pub nonce: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Provider {
+    config: OidcConfig,
+    metadata: ProviderMetadata,
+    client: Client,
+}
+
+impl Provider {
+    /// Fetches the provider's discovery document. The issuer it names must be
+    /// the one configured, otherwise tokens could not be told apart.
+    pub async fn discover(config: &OidcConfig) -> Result<Self> {
+        let client = Client::new();
+        let issuer = config.issuer.trim_end_matches('/');
+
+        let metadata = client
+            .get(format!("{issuer}/.well-known/openid-configuration"))
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<ProviderMetadata>()
+            .await?;
+
+        if metadata.issuer.trim_end_matches('/') != issuer {
+            return Err(Error::Other(format!(
+                "Provider at {issuer} reports issuer {}",
+                metadata.issuer
+            ))
+            .into());
+        }
+
+        Ok(Self {
+            config: config.clone(),
+            metadata,
+            client,
+        })
+    }
+
+    #[must_use]
+    pub fn issuer(&self) -> &str {
+        &self.metadata.issuer
+    }
+
+    /// Where to send the person to log in at the provider.
+    pub fn authorization_url(&self, login: &OidcLogin) -> Result<Url> {
+        let mut url = Url::parse(&self.metadata.authorization_endpoint)
+            .map_err(|error| Error::Other(error.to_string()))?;
+
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &self.config.client_id)
+            .append_pair("redirect_uri", &self.config.redirect_uri)
+            .append_pair("scope", &self.config.scopes.join(" "))
+            .append_pair("state", &login.state)
+            .append_pair("nonce", &login.nonce)
+            .append_pair("code_challenge", &login.code_challenge())
+            .append_pair("code_challenge_method", "S256");
+
+        Ok(url)
+    }
+
+    /// Trades the code the provider sent back for an ID token and validates
+    /// it. A code the provider refuses is an invalid token.
+    pub async fn exchange(&self, code: &str, login: &OidcLogin) -> Result<IdTokenClaims> {
+        let response = self
+            .client
+            .post(&self.metadata.token_endpoint)
+            .form(&[
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("redirect_uri", &self.config.redirect_uri),
+                ("client_id", &self.config.client_id),
+                ("client_secret", &self.config.client_secret),
+                ("code_verifier", &login.code_verifier),
+            ])
+            .send()
+            .await?;
+
+        if matches!(
+            response.status(),
+            StatusCode::BAD_REQUEST | StatusCode::UNAUTHORIZED
+        ) {
+            tracing::warn!(
+                "Token endpoint refused the code: {}",
+                response.text().await?
+            );
+            return Err(Error::InvalidToken.into());
+        }
+
+        let tokens = response.error_for_status()?.json::<TokenResponse>().await?;
+
+        self.validate(&tokens.id_token, &login.nonce).await
+    }
+
+    /// Checks the ID token's signature against the provider's keys, that it
+    /// was issued by the provider to Polaris and not yet expired, and that it
+    /// answers this login.
+    async fn validate(&self, id_token: &str, nonce: &str) -> Result<IdTokenClaims> {
+        let header = jsonwebtoken::decode_header(id_token).map_err(|_| Error::InvalidToken)?;
+
+        // Shared-secret algorithms would let anyone holding the client secret
+        // sign tokens, so only the provider's public keys are accepted.
+        if matches!(
+            header.alg,
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+        ) {
+            return Err(Error::InvalidToken.into());
+        }
+
+        let jwks = self
+            .client
+            .get(&self.metadata.jwks_uri)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<JwkSet>()
+            .await?;
+
+        let jwk = match &header.kid {
+            Some(kid) => jwks.find(kid),
+            None => jwks.keys.first(),
+        }
+        .ok_or_else(|| Error::InvalidToken)?;
+
+        let key = DecodingKey::from_jwk(jwk).map_err(|_| Error::InvalidToken)?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&self.metadata.issuer]);
+        validation.set_audience(&[&self.config.client_id]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+
+        let claims = jsonwebtoken::decode::<IdTokenClaims>(id_token, &key, &validation)
+            .map_err(|error| {
+                tracing::warn!("Rejected ID token: {error}");
+                Error::InvalidToken
+            })?
+            .claims;
+
+        if claims.nonce.as_deref() != Some(nonce) {
+            return Err(Error::InvalidToken.into());
+        }
+
+        Ok(claims)
+    }
+}
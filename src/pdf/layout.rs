@@ -0,0 +1,362 @@
+//! Draws the layouts the report templates render onto A4 pages. A layout has
+//! one instruction per line, `kind: content`, with table cells and field
+//! parts separated by `|`:
+//!
+//! - `title:` and `heading:` start the report and its sections
+//! - `text:` and `muted:` are paragraphs, wrapped to the page
+//! - `field: Label | Value` is a labelled value
+//! - `columns: 20 | 30 | 50` sets the width of the next table's columns in
+//!   percent, `header:` and `row:` are its rows
+//! - `rule:`, `space:` and `break:` draw a line, leave a gap or start a page
+//!
+//! Blank lines and unknown instructions are skipped, so templates can be
+//! laid out freely.
+
+use printpdf::{
+    BuiltinFont, Color, IndirectFontRef, Line, Mm, PdfDocument, PdfDocumentReference,
+    PdfLayerReference, Point, Rect, Rgb, path::PaintMode,
+};
+
+use crate::models::settings::Branding;
+
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN: f32 = 16.0;
+const CONTENT_WIDTH: f32 = PAGE_WIDTH - 2.0 * MARGIN;
+/// Where the branded band at the top of each page ends.
+const HEADER_BOTTOM: f32 = PAGE_HEIGHT - 26.0;
+/// Content stops above the footer.
+const CONTENT_BOTTOM: f32 = 20.0;
+const ROW_HEIGHT: f32 = 6.0;
+/// The average width of a Helvetica character, relative to its size.
+const CHAR_WIDTH: f32 = 0.5;
+const PT_IN_MM: f32 = 0.3528;
+
+const BLACK: (f32, f32, f32) = (0.13, 0.13, 0.13);
+const GREY: (f32, f32, f32) = (0.45, 0.45, 0.45);
+const SHADE: (f32, f32, f32) = (0.93, 0.93, 0.93);
+const WHITE: (f32, f32, f32) = (1.0, 1.0, 1.0);
+
+/// Who a report is for, printed at the top of every page.
+pub struct Letterhead<'a> {
+    pub name: &'a str,
+    pub contact: String,
+    pub branding: &'a Branding,
+}
+
+struct Page<'a> {
+    doc: PdfDocumentReference,
+    layer: PdfLayerReference,
+    regular: IndirectFontRef,
+    bold: IndirectFontRef,
+    letterhead: &'a Letterhead<'a>,
+    accent: (f32, f32, f32),
+    number: usize,
+    y: f32,
+    columns: Vec<f32>,
+    /// The header of the table being drawn, repeated when it runs onto
+    /// another page.
+    header: Option<Vec<String>>,
+}
+
+/// Draws `layout` as a PDF titled `title` and returns its bytes.
+pub fn render(
+    title: &str,
+    letterhead: &Letterhead<'_>,
+    layout: &str,
+) -> Result<Vec<u8>, printpdf::Error> {
+    let (doc, page, layer) = PdfDocument::new(title, Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Page 1");
+    let regular = doc.add_builtin_font(BuiltinFont::Helvetica)?;
+    let bold = doc.add_builtin_font(BuiltinFont::HelveticaBold)?;
+    let layer = doc.get_page(page).get_layer(layer);
+
+    let mut page = Page {
+        doc,
+        layer,
+        regular,
+        bold,
+        letterhead,
+        accent: letterhead.branding.rgb().unwrap_or((0.18, 0.49, 0.2)),
+        number: 1,
+        y: HEADER_BOTTOM,
+        columns: Vec::new(),
+        header: None,
+    };
+    page.decorate();
+
+    for line in layout.lines() {
+        let Some((kind, content)) = line.trim().split_once(':') else {
+            continue;
+        };
+        let content = content.trim();
+
+        match kind.trim() {
+            "title" => page.title(content),
+            "heading" => page.heading(content),
+            "text" => page.paragraph(content, 10.0, BLACK),
+            "muted" => page.paragraph(content, 8.0, GREY),
+            "field" => page.field(content),
+            "columns" => {
+                page.columns = cells(content)
+                    .iter()
+                    .filter_map(|width| width.parse::<f32>().ok())
+                    .collect();
+            }
+            "header" => page.header(cells(content)),
+            "row" => page.row(&cells(content)),
+            "rule" => page.rule(),
+            "space" => page.y -= 4.0,
+            "break" => page.next(),
+            _ => {}
+        }
+    }
+
+    page.doc.save_to_bytes()
+}
+
+fn cells(content: &str) -> Vec<String> {
+    content
+        .split('|')
+        .map(|cell| cell.trim().to_string())
+        .collect()
+}
+
+fn colour((r, g, b): (f32, f32, f32)) -> Color {
+    Color::Rgb(Rgb::new(r, g, b, None))
+}
+
+/// How many characters of text `size` points high fit in `width` mm.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn fitting(width: f32, size: f32) -> usize {
+    (width / (size * CHAR_WIDTH * PT_IN_MM)).max(1.0) as usize
+}
+
+/// Shortens `text` to `fit` characters, marking what was cut.
+fn truncate(text: &str, fit: usize) -> String {
+    if text.chars().count() <= fit {
+        return text.to_string();
+    }
+
+    let mut short = text.chars().take(fit.saturating_sub(3)).collect::<String>();
+    short.push_str("...");
+    short
+}
+
+/// Breaks `text` into lines of at most `fit` characters, between words
+/// where it can.
+fn wrap(text: &str, fit: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+
+    for word in text.split_whitespace() {
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > fit {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+
+        while line.chars().count() > fit {
+            let rest = line.chars().skip(fit).collect::<String>();
+            lines.push(line.chars().take(fit).collect());
+            line = rest;
+        }
+    }
+
+    if !line.is_empty() {
+        lines.push(line);
+    }
+
+    lines
+}
+
+impl Page<'_> {
+    fn text(&self, text: &str, size: f32, x: f32, y: f32, bold: bool, fill: (f32, f32, f32)) {
+        self.layer.set_fill_color(colour(fill));
+        let font = if bold { &self.bold } else { &self.regular };
+        self.layer.use_text(text, size, Mm(x), Mm(y), font);
+    }
+
+    fn fill(&self, (x, y): (f32, f32), (width, height): (f32, f32), fill: (f32, f32, f32)) {
+        self.layer.set_fill_color(colour(fill));
+        self.layer.add_rect(
+            Rect::new(Mm(x), Mm(y), Mm(x + width), Mm(y + height)).with_mode(PaintMode::Fill),
+        );
+    }
+
+    fn line(&self, y: f32, stroke: (f32, f32, f32), thickness: f32) {
+        self.layer.set_outline_color(colour(stroke));
+        self.layer.set_outline_thickness(thickness);
+        self.layer.add_line(Line {
+            points: vec![
+                (Point::new(Mm(MARGIN), Mm(y)), false),
+                (Point::new(Mm(PAGE_WIDTH - MARGIN), Mm(y)), false),
+            ],
+            is_closed: false,
+        });
+    }
+
+    /// The branded band, contact details and footer of the current page.
+    fn decorate(&self) {
+        let letterhead = self.letterhead;
+
+        self.fill((0.0, PAGE_HEIGHT - 16.0), (PAGE_WIDTH, 16.0), self.accent);
+        self.text(
+            letterhead.name,
+            15.0,
+            MARGIN,
+            PAGE_HEIGHT - 10.5,
+            true,
+            WHITE,
+        );
+        self.text(
+            &truncate(&letterhead.contact, fitting(CONTENT_WIDTH, 8.0)),
+            8.0,
+            MARGIN,
+            PAGE_HEIGHT - 21.0,
+            false,
+            GREY,
+        );
+
+        self.line(14.0, SHADE, 0.5);
+        if let Some(footer) = &letterhead.branding.footer {
+            self.text(
+                &truncate(footer, fitting(CONTENT_WIDTH - 25.0, 8.0)),
+                8.0,
+                MARGIN,
+                9.0,
+                false,
+                GREY,
+            );
+        }
+        self.text(
+            &format!("Page {}", self.number),
+            8.0,
+            PAGE_WIDTH - MARGIN - 12.0,
+            9.0,
+            false,
+            GREY,
+        );
+    }
+
+    fn next(&mut self) {
+        self.number += 1;
+        let (page, layer) = self.doc.add_page(
+            Mm(PAGE_WIDTH),
+            Mm(PAGE_HEIGHT),
+            format!("Page {}", self.number),
+        );
+        self.layer = self.doc.get_page(page).get_layer(layer);
+        self.y = HEADER_BOTTOM;
+        self.decorate();
+    }
+
+    /// Starts a page unless `height` mm still fit on this one.
+    fn room(&mut self, height: f32) -> bool {
+        if self.y - height < CONTENT_BOTTOM {
+            self.next();
+            return false;
+        }
+        true
+    }
+
+    fn title(&mut self, title: &str) {
+        self.header = None;
+        self.room(12.0);
+        self.y -= 8.0;
+        self.text(title, 18.0, MARGIN, self.y, true, self.accent);
+        self.y -= 6.0;
+    }
+
+    fn heading(&mut self, heading: &str) {
+        self.header = None;
+        self.columns.clear();
+        self.room(14.0);
+        self.y -= 7.0;
+        self.text(heading, 12.0, MARGIN, self.y, true, self.accent);
+        self.y -= 2.0;
+        self.line(self.y, self.accent, 0.75);
+        self.y -= 3.0;
+    }
+
+    fn paragraph(&mut self, text: &str, size: f32, fill: (f32, f32, f32)) {
+        let height = size * PT_IN_MM * 1.4;
+
+        for line in wrap(text, fitting(CONTENT_WIDTH, size)) {
+            self.room(height);
+            self.y -= height;
+            self.text(&line, size, MARGIN, self.y, false, fill);
+        }
+        self.y -= 1.0;
+    }
+
+    fn field(&mut self, content: &str) {
+        let (label, value) = content.split_once('|').unwrap_or((content, ""));
+        let value_width = CONTENT_WIDTH - 50.0;
+
+        let lines = wrap(value.trim(), fitting(value_width, 10.0));
+        self.room(5.0);
+        self.y -= 5.0;
+        self.text(label.trim(), 10.0, MARGIN, self.y, true, BLACK);
+
+        for (i, line) in lines.iter().enumerate() {
+            if i > 0 {
+                self.room(5.0);
+                self.y -= 5.0;
+            }
+            self.text(line, 10.0, MARGIN + 50.0, self.y, false, BLACK);
+        }
+    }
+
+    /// The width of each of `count` columns in mm.
+    #[allow(clippy::cast_precision_loss)]
+    fn widths(&self, count: usize) -> Vec<f32> {
+        if self.columns.len() == count {
+            let total = self.columns.iter().sum::<f32>().max(1.0);
+            return self
+                .columns
+                .iter()
+                .map(|width| CONTENT_WIDTH * width / total)
+                .collect();
+        }
+
+        vec![CONTENT_WIDTH / count.max(1) as f32; count]
+    }
+
+    fn cells(&self, cells: &[String], bold: bool) {
+        let mut x = MARGIN;
+        for (cell, width) in cells.iter().zip(self.widths(cells.len())) {
+            let text = truncate(cell, fitting(width - 2.0, 9.0));
+            self.text(&text, 9.0, x + 1.0, self.y + 1.8, bold, BLACK);
+            x += width;
+        }
+    }
+
+    fn header(&mut self, cells: Vec<String>) {
+        self.room(ROW_HEIGHT * 2.0);
+        self.y -= ROW_HEIGHT;
+        self.fill((MARGIN, self.y), (CONTENT_WIDTH, ROW_HEIGHT), SHADE);
+        self.cells(&cells, true);
+        self.header = Some(cells);
+    }
+
+    fn row(&mut self, cells: &[String]) {
+        if !self.room(ROW_HEIGHT)
+            && let Some(header) = self.header.clone()
+        {
+            self.header(header);
+        }
+
+        self.y -= ROW_HEIGHT;
+        self.cells(cells, false);
+        self.line(self.y, SHADE, 0.3);
+    }
+
+    fn rule(&mut self) {
+        self.room(4.0);
+        self.y -= 2.0;
+        self.line(self.y, SHADE, 0.5);
+        self.y -= 2.0;
+    }
+}
@@ -0,0 +1,387 @@
+#![allow(clippy::missing_errors_doc)]
+
+//! Printable reports. Each report is a `tera` template in `templates/` that
+//! renders a layout, which [`layout`] draws onto PDF pages with the
+//! organisation's letterhead and branding.
+
+pub mod layout;
+
+use std::{collections::HashMap, sync::LazyLock};
+
+use axum::{
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use sqlx::{Executor, PgPool, Postgres};
+use tera::{Context, Tera};
+use uuid::Uuid;
+
+use self::layout::Letterhead;
+use crate::{
+    Result,
+    models::{
+        BreedSummary, BreedSummaryQuery, LiveSummary, ModelError, SpecieSummary,
+        animals::{Animal, AnimalQuery},
+        groups::AnimalGroup,
+        health::HealthRecord,
+        livestock::LivestockSummary,
+        orgs::Organisation,
+        production::ProductionRecord,
+        settings::OrganisationSettings,
+    },
+};
+
+static TEMPLATES: LazyLock<Tera> = LazyLock::new(|| {
+    let mut tera = Tera::default();
+    tera.add_raw_templates([
+        ("herd", include_str!("templates/herd.tera")),
+        ("group", include_str!("templates/group.tera")),
+        ("species", include_str!("templates/species.tera")),
+        ("breeds", include_str!("templates/breeds.tera")),
+        ("health", include_str!("templates/health.tera")),
+        ("production", include_str!("templates/production.tera")),
+    ])
+    .expect("The report templates are valid");
+    tera.register_filter("cell", cell);
+    tera.register_filter("day", day);
+    tera
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportTemplate {
+    Herd,
+    Group,
+    Species,
+    Breeds,
+    Health,
+    Production,
+}
+
+impl ReportTemplate {
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Herd => "herd",
+            Self::Group => "group",
+            Self::Species => "species",
+            Self::Breeds => "breeds",
+            Self::Health => "health",
+            Self::Production => "production",
+        }
+    }
+
+    #[must_use]
+    pub const fn title(self) -> &'static str {
+        match self {
+            Self::Herd => "Herd inventory",
+            Self::Group => "Group inventory",
+            Self::Species => "Species summary",
+            Self::Breeds => "Breed summary",
+            Self::Health => "Health history",
+            Self::Production => "Production statement",
+        }
+    }
+}
+
+/// A report being put together for an organisation.
+pub struct PdfReport {
+    template: ReportTemplate,
+    organisation: Organisation,
+    settings: OrganisationSettings,
+    context: Context,
+    /// What the report is about, for its filename.
+    subject: String,
+}
+
+impl PdfReport {
+    /// Starts a report with what every template can use: the `organisation`,
+    /// its `currency`, `weight_unit` and `date_format`, and `today`.
+    pub async fn new<'e, C>(db: C, org_pid: Uuid, template: ReportTemplate) -> Result<Self>
+    where
+        C: Executor<'e, Database = Postgres> + Copy,
+    {
+        let organisation = Organisation::find_by_pid(db, org_pid).await?;
+        let settings = OrganisationSettings::find(db, org_pid).await?;
+
+        let mut context = Context::new();
+        context.insert(
+            "organisation",
+            &json!({
+                "name": organisation.name,
+                "address": organisation.address,
+                "phone": organisation.phone,
+                "email": organisation.email,
+            }),
+        );
+        context.insert("currency", &settings.currency);
+        context.insert("weight_unit", settings.weight_unit.as_str());
+        context.insert("date_format", &settings.date_format);
+        context.insert("today", &settings.today());
+
+        Ok(Self {
+            template,
+            subject: settings.today().to_string(),
+            organisation,
+            settings,
+            context,
+        })
+    }
+
+    /// Adds `value` to what the template can use as `key`.
+    #[must_use]
+    pub fn with<T: Serialize + ?Sized>(mut self, key: &str, value: &T) -> Self {
+        self.context.insert(key, value);
+        self
+    }
+
+    /// The layout the template renders, before it is drawn.
+    pub fn layout(&self) -> Result<String> {
+        TEMPLATES
+            .render(self.template.name(), &self.context)
+            .map_err(Into::into)
+    }
+
+    /// Names the report after `subject` rather than the day it is printed.
+    #[must_use]
+    pub fn about(mut self, subject: &str) -> Self {
+        self.subject = subject.to_string();
+        self
+    }
+
+    /// Draws the report, named `<title>-<subject>.pdf`.
+    pub fn render(&self) -> Result<Pdf> {
+        let layout = self.layout()?;
+
+        let contact = [
+            self.organisation.address.as_deref(),
+            self.organisation.phone.as_deref(),
+            self.organisation.email.as_deref(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join("  -  ");
+
+        let letterhead = Letterhead {
+            name: &self.organisation.name,
+            contact,
+            branding: &self.settings.branding,
+        };
+
+        let title = self.template.title();
+        let bytes = layout::render(title, &letterhead, &layout)?;
+
+        let filename = format!("{title}-{}", self.subject)
+            .to_lowercase()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect::<String>();
+
+        Ok(Pdf {
+            filename: format!("{filename}.pdf"),
+            bytes,
+        })
+    }
+}
+
+/// The period a production statement covers, both days included.
+#[derive(Debug, Deserialize, Clone, Copy)]
+pub struct PeriodQuery {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+/// How much of a product was recorded in a production statement's period.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ProductionTotal {
+    product_type: String,
+    unit: String,
+    quantity: Decimal,
+    records: usize,
+}
+
+impl PdfReport {
+    /// The latest livestock summary and every animal, by tag.
+    pub async fn herd(db: &PgPool, org_pid: Uuid) -> Result<Self> {
+        let summary = LivestockSummary::find_all(db, org_pid)
+            .await?
+            .into_iter()
+            .next();
+        let mut animals = Animal::find_all(
+            db,
+            org_pid,
+            &AnimalQuery {
+                specie: None,
+                breed: None,
+                purchase_date: None,
+                female_parent: None,
+                male_parent: None,
+                location: None,
+            },
+        )
+        .await?;
+        animals.sort_by(|a, b| a.tag_id.cmp(&b.tag_id));
+
+        Ok(Self::new(db, org_pid, ReportTemplate::Herd)
+            .await?
+            .with("summary", &summary)
+            .with("animals", &animals))
+    }
+
+    /// A group's members, by tag, and a summary of them.
+    pub async fn group(db: &PgPool, org_pid: Uuid, group_pid: Uuid) -> Result<Self> {
+        let group = AnimalGroup::find_by_pid(db, org_pid, group_pid).await?;
+        let pids = group.animal_pids(db).await?;
+        let summary = LiveSummary::find_animals(db, org_pid, &pids).await?;
+        let animals = Animal::find_by_pids(db, org_pid, &pids).await?;
+
+        Ok(Self::new(db, org_pid, ReportTemplate::Group)
+            .await?
+            .about(&group.name)
+            .with("group", &group)
+            .with("summary", &summary)
+            .with("animals", &animals))
+    }
+
+    /// The latest summary of each specie, or only of `specie`.
+    pub async fn species(db: &PgPool, org_pid: Uuid, specie: Option<&str>) -> Result<Self> {
+        let summaries = SpecieSummary::find_latest(db, org_pid, specie).await?;
+
+        Ok(Self::new(db, org_pid, ReportTemplate::Species)
+            .await?
+            .with("summaries", &summaries))
+    }
+
+    /// The latest summary of each breed matching `params`.
+    pub async fn breeds(db: &PgPool, org_pid: Uuid, params: &BreedSummaryQuery) -> Result<Self> {
+        let summaries = BreedSummary::find_latest(db, org_pid, params).await?;
+
+        Ok(Self::new(db, org_pid, ReportTemplate::Breeds)
+            .await?
+            .with("summaries", &summaries))
+    }
+
+    /// Every health record of an animal and what its care cost.
+    pub async fn health(db: &PgPool, org_pid: Uuid, animal_pid: Uuid) -> Result<Self> {
+        let animal = Animal::find_by_id(db, org_pid, animal_pid).await?;
+        let mut records = HealthRecord::find_by_animal(db, animal_pid, org_pid).await?;
+        records.sort_by_key(|record| (record.record_date, record.id));
+
+        let total_cost = records
+            .iter()
+            .filter_map(|record| record.cost)
+            .sum::<Decimal>();
+
+        Ok(Self::new(db, org_pid, ReportTemplate::Health)
+            .await?
+            .about(&animal.tag_id)
+            .with("animal", &animal)
+            .with("records", &records)
+            .with("total_cost", &total_cost))
+    }
+
+    /// What was produced in a period, in total per product and unit.
+    pub async fn production(db: &PgPool, org_pid: Uuid, period: PeriodQuery) -> Result<Self> {
+        if period.from > period.to {
+            return Err(ModelError::Validation(
+                "The period must not end before it starts".to_string(),
+            )
+            .into());
+        }
+
+        let records = ProductionRecord::find_by_period(db, org_pid, period.from, period.to).await?;
+
+        let mut totals: Vec<ProductionTotal> = Vec::new();
+        for record in &records {
+            if let Some(total) = totals.iter_mut().find(|total| {
+                total.product_type == record.product_type && total.unit == record.unit
+            }) {
+                total.quantity += record.quantity;
+                total.records += 1;
+            } else {
+                totals.push(ProductionTotal {
+                    product_type: record.product_type.clone(),
+                    unit: record.unit.clone(),
+                    quantity: record.quantity,
+                    records: 1,
+                });
+            }
+        }
+        totals.sort_by(|a, b| (&a.product_type, &a.unit).cmp(&(&b.product_type, &b.unit)));
+
+        Ok(Self::new(db, org_pid, ReportTemplate::Production)
+            .await?
+            .about(&format!("{}-{}", period.from, period.to))
+            .with("from", &period.from)
+            .with("to", &period.to)
+            .with("totals", &totals)
+            .with("records", &records))
+    }
+}
+
+/// A rendered report, downloaded as a file.
+pub struct Pdf {
+    pub filename: String,
+    pub bytes: Vec<u8>,
+}
+
+impl IntoResponse for Pdf {
+    fn into_response(self) -> Response {
+        (
+            StatusCode::OK,
+            [
+                (header::CONTENT_TYPE, "application/pdf".to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"{}\"", self.filename),
+                ),
+            ],
+            self.bytes,
+        )
+            .into_response()
+    }
+}
+
+/// Makes a value safe to put in a layout line: nothing becomes `-`, and line
+/// breaks and `|` would start a new line or cell.
+#[allow(clippy::unnecessary_wraps)]
+fn cell(value: &Value, _: &HashMap<String, Value>) -> tera::Result<Value> {
+    let text = match value {
+        Value::Null => return Ok(Value::String("-".into())),
+        Value::String(text) if text.trim().is_empty() => return Ok(Value::String("-".into())),
+        Value::String(text) => text.clone(),
+        value => value.to_string(),
+    };
+
+    Ok(Value::String(
+        text.split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .replace('|', "/"),
+    ))
+}
+
+/// Formats a date, or the date of a timestamp, with the `format` given,
+/// usually the organisation's `date_format`.
+fn day(value: &Value, args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let Value::String(text) = value else {
+        return Ok(Value::String("-".into()));
+    };
+
+    let format = args
+        .get("format")
+        .and_then(Value::as_str)
+        .unwrap_or("%Y-%m-%d");
+
+    let date = text
+        .get(..10)
+        .and_then(|date| date.parse::<NaiveDate>().ok())
+        .ok_or_else(|| tera::Error::msg(format!("{text} is not a date")))?;
+
+    Ok(Value::String(date.format(format).to_string()))
+}
@@ -6,7 +6,8 @@ use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header};
 use sqlx::PgPool;
 
 use crate::{
-    AppConfig, Result, config::auth::RsaJwtConfig, middlewares::TokenClaims, models::users::User,
+    AppConfig, Result, config::auth::RsaJwtConfig, live::LiveEvents, middlewares::TokenClaims,
+    models::users::User,
 };
 
 #[derive(Clone)]
@@ -14,6 +15,7 @@ pub struct AppContext {
     pub config: AppConfig,
     pub db: PgPool,
     pub auth: AuthContext,
+    pub live: LiveEvents,
 }
 
 impl AppContext {
@@ -27,6 +29,7 @@ impl AppContext {
             config: config.clone(),
             db: pool,
             auth: AuthContext::new(access, refresh),
+            live: LiveEvents::new(config.database().url()),
         })
     }
 
@@ -80,6 +83,16 @@ impl RsaJwtContext {
     }
 
     pub fn jwt(&self, user: &User) -> Result<String> {
+        self.encode(user, None)
+    }
+
+    /// A token letting a platform operator act as `user`. The operator is
+    /// kept in the claims so the session can be told apart from the user's.
+    pub fn impersonation_jwt(&self, user: &User, operator: &User) -> Result<String> {
+        self.encode(user, Some(operator.pid.to_string()))
+    }
+
+    fn encode(&self, user: &User, impersonator: Option<String>) -> Result<String> {
         let header = Header::new(Algorithm::RS256);
 
         let now = Utc::now();
@@ -89,6 +102,8 @@ impl RsaJwtContext {
             role: user.role.to_string(),
             exp: (now + Duration::seconds(self.exp)).timestamp(),
             iat: now.timestamp(),
+            impersonator,
+            organisation: Some(user.organisation_pid.to_string()),
         };
 
         jsonwebtoken::encode(&header, &claims, &self.encoding).map_err(Into::into)
@@ -0,0 +1,79 @@
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::models::alerts::{Alert, AlertRule};
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AlertRuleResponse {
+    pub pid: Uuid,
+    pub name: String,
+    pub kind: String,
+    pub threshold: Option<f64>,
+    pub window_days: Option<i32>,
+    pub severity: String,
+    pub channels: Vec<String>,
+    pub is_active: bool,
+    pub last_evaluated_at: Option<String>,
+    pub created_at: String,
+}
+
+impl AlertRuleResponse {
+    #[must_use]
+    pub fn new(rule: &AlertRule) -> Self {
+        Self {
+            pid: rule.pid,
+            name: rule.name.clone(),
+            kind: rule.kind.clone(),
+            threshold: rule.threshold,
+            window_days: rule.window_days,
+            severity: rule.severity.clone(),
+            channels: rule.channels.clone(),
+            is_active: rule.is_active,
+            last_evaluated_at: rule
+                .last_evaluated_at
+                .map(|date| date.format("%d-%m-%Y %H:%M").to_string()),
+            created_at: rule.created_at.format("%d-%m-%Y %H:%M").to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AlertResponse {
+    pub pid: Uuid,
+    pub rule_pid: Uuid,
+    pub animal_pid: Uuid,
+    pub severity: String,
+    pub message: String,
+    pub details: serde_json::Value,
+    pub status: String,
+    pub acknowledged_by: Option<Uuid>,
+    pub acknowledged_at: Option<String>,
+    pub resolved_by: Option<Uuid>,
+    pub resolved_at: Option<String>,
+    pub created_at: String,
+}
+
+impl AlertResponse {
+    #[must_use]
+    pub fn new(alert: &Alert) -> Self {
+        let format =
+            |date: chrono::DateTime<chrono::FixedOffset>| date.format("%d-%m-%Y %H:%M").to_string();
+
+        Self {
+            pid: alert.pid,
+            rule_pid: alert.rule_pid,
+            animal_pid: alert.animal_pid,
+            severity: alert.severity.clone(),
+            message: alert.message.clone(),
+            details: alert.details.0.clone(),
+            status: alert.status.clone(),
+            acknowledged_by: alert.acknowledged_by,
+            acknowledged_at: alert.acknowledged_at.map(format),
+            resolved_by: alert.resolved_by,
+            resolved_at: alert.resolved_at.map(format),
+            created_at: format(alert.created_at),
+        }
+    }
+}
@@ -61,6 +61,8 @@ pub struct AnimalResponse {
     pub(crate) weight_at_birth: Option<Decimal>,
     pub(crate) current_weight: Option<Decimal>,
     pub(crate) notes: Option<String>,
+    pub(crate) location_pid: Option<Uuid>,
+    pub(crate) location_name: Option<String>,
     pub(crate) created_by_name: String,
     pub(crate) created_at: DateTime<FixedOffset>,
     pub(crate) updated_at: DateTime<FixedOffset>,
@@ -0,0 +1,61 @@
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::models::api_keys::{ApiKey, IssuedApiKey};
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiKeyResponse {
+    pub pid: Uuid,
+    pub name: String,
+    pub scopes: Vec<String>,
+    pub created_by: Uuid,
+    pub is_usable: bool,
+    pub expires_at: Option<String>,
+    pub last_used_at: Option<String>,
+    pub revoked_at: Option<String>,
+    pub created_at: String,
+}
+
+impl ApiKeyResponse {
+    #[must_use]
+    pub fn new(api_key: &ApiKey) -> Self {
+        Self {
+            pid: api_key.pid,
+            name: api_key.name.clone(),
+            scopes: api_key.scopes.clone(),
+            created_by: api_key.created_by,
+            is_usable: api_key.is_usable(),
+            expires_at: api_key
+                .expires_at
+                .map(|date| date.format("%d-%m-%Y %H:%M").to_string()),
+            last_used_at: api_key
+                .last_used_at
+                .map(|date| date.format("%d-%m-%Y %H:%M").to_string()),
+            revoked_at: api_key
+                .revoked_at
+                .map(|date| date.format("%d-%m-%Y %H:%M").to_string()),
+            created_at: api_key.created_at.format("%d-%m-%Y %H:%M").to_string(),
+        }
+    }
+}
+
+/// A freshly created key. The token is what the integration authenticates
+/// with and is not shown again.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct IssuedApiKeyResponse {
+    #[serde(flatten)]
+    pub api_key: ApiKeyResponse,
+    pub token: String,
+}
+
+impl IssuedApiKeyResponse {
+    #[must_use]
+    pub fn new(issued: &IssuedApiKey) -> Self {
+        Self {
+            api_key: ApiKeyResponse::new(&issued.api_key),
+            token: issued.token.clone(),
+        }
+    }
+}
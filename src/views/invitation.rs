@@ -0,0 +1,59 @@
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::models::invitations::{Invitation, IssuedInvitation};
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct InvitationResponse {
+    pub pid: Uuid,
+    pub email: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub role: String,
+    pub status: String,
+    pub invited_by: Option<Uuid>,
+    pub expires_at: String,
+    pub accepted_at: Option<String>,
+    pub created_at: String,
+}
+
+impl InvitationResponse {
+    #[must_use]
+    pub fn new(invitation: &Invitation) -> Self {
+        Self {
+            pid: invitation.pid,
+            email: invitation.email.clone(),
+            first_name: invitation.first_name.clone(),
+            last_name: invitation.last_name.clone(),
+            role: invitation.role.clone(),
+            status: invitation.status().as_str().to_string(),
+            invited_by: invitation.invited_by,
+            expires_at: invitation.expires_at.format("%d-%m-%Y %H:%M").to_string(),
+            accepted_at: invitation
+                .accepted_at
+                .map(|date| date.format("%d-%m-%Y %H:%M").to_string()),
+            created_at: invitation.created_at.format("%d-%m-%Y %H:%M").to_string(),
+        }
+    }
+}
+
+/// A freshly issued invitation. The token is what the invitee accepts the
+/// invitation with and is not shown again.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct IssuedInvitationResponse {
+    #[serde(flatten)]
+    pub invitation: InvitationResponse,
+    pub token: String,
+}
+
+impl IssuedInvitationResponse {
+    #[must_use]
+    pub fn new(issued: &IssuedInvitation) -> Self {
+        Self {
+            invitation: InvitationResponse::new(&issued.invitation),
+            token: issued.token.clone(),
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::models::jobs::{Job, JobSchedule};
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct JobScheduleResponse {
+    pub pid: Uuid,
+    pub kind: String,
+    pub cron: String,
+    pub is_active: bool,
+    pub next_run_at: String,
+    pub last_run_at: Option<String>,
+    pub created_at: String,
+}
+
+impl JobScheduleResponse {
+    #[must_use]
+    pub fn new(schedule: &JobSchedule) -> Self {
+        Self {
+            pid: schedule.pid,
+            kind: schedule.kind.clone(),
+            cron: schedule.cron.clone(),
+            is_active: schedule.is_active,
+            next_run_at: schedule.next_run_at.format("%d-%m-%Y %H:%M").to_string(),
+            last_run_at: schedule
+                .last_run_at
+                .map(|date| date.format("%d-%m-%Y %H:%M").to_string()),
+            created_at: schedule.created_at.format("%d-%m-%Y %H:%M").to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct JobResponse {
+    pub pid: Uuid,
+    pub schedule_pid: Option<Uuid>,
+    pub kind: String,
+    pub status: String,
+    pub attempts: i32,
+    pub max_attempts: i32,
+    pub run_at: String,
+    pub result: Option<serde_json::Value>,
+    pub last_error: Option<String>,
+    pub finished_at: Option<String>,
+    pub created_at: String,
+}
+
+impl JobResponse {
+    #[must_use]
+    pub fn new(job: &Job) -> Self {
+        let format =
+            |date: chrono::DateTime<chrono::FixedOffset>| date.format("%d-%m-%Y %H:%M").to_string();
+
+        Self {
+            pid: job.pid,
+            schedule_pid: job.schedule_pid,
+            kind: job.kind.clone(),
+            status: job.status.clone(),
+            attempts: job.attempts,
+            max_attempts: job.max_attempts,
+            run_at: format(job.run_at),
+            result: job.result.as_ref().map(|result| result.0.clone()),
+            last_error: job.last_error.clone(),
+            finished_at: job.finished_at.map(format),
+            created_at: format(job.created_at),
+        }
+    }
+}
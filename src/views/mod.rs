@@ -1,2 +1,12 @@
+pub mod alert;
 pub mod animals;
+pub mod api_key;
+pub mod invitation;
+pub mod job;
+pub mod organisation;
+pub mod platform;
+pub mod reports;
+pub mod roles;
+pub mod subscription;
 pub mod user;
+pub mod webhook;
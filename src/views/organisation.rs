@@ -0,0 +1,37 @@
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::models::{orgs::Organisation, settings::OrganisationSettings};
+
+/// The organisation's profile and the settings other modules default from.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OrganisationProfile {
+    pub pid: Uuid,
+    pub name: String,
+    pub address: Option<String>,
+    pub phone: Option<String>,
+    pub email: Option<String>,
+    pub subscription: String,
+    pub settings: OrganisationSettings,
+    pub created_at: String,
+}
+
+impl OrganisationProfile {
+    #[must_use]
+    pub fn new(organisation: &Organisation, settings: OrganisationSettings) -> Self {
+        Self {
+            pid: organisation.pid,
+            name: organisation.name.clone(),
+            address: organisation.address.clone(),
+            phone: organisation.phone.clone(),
+            email: organisation.email.clone(),
+            subscription: organisation.subscription().to_string(),
+            created_at: organisation
+                .created_at
+                .format(&settings.date_format)
+                .to_string(),
+            settings,
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::models::{entitlements::Usage, orgs::Organisation, users::User};
+
+use super::{subscription::SubscriptionResponse, user::UserResponse};
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OrganisationResponse {
+    pub pid: Uuid,
+    pub name: String,
+    pub address: Option<String>,
+    pub phone: Option<String>,
+    pub email: Option<String>,
+    pub subscription: String,
+    pub suspended: bool,
+    pub suspended_at: Option<String>,
+    pub suspension_reason: Option<String>,
+    pub created_at: String,
+}
+
+impl OrganisationResponse {
+    #[must_use]
+    pub fn new(organisation: &Organisation) -> Self {
+        Self {
+            pid: organisation.pid,
+            name: organisation.name.clone(),
+            address: organisation.address.clone(),
+            phone: organisation.phone.clone(),
+            email: organisation.email.clone(),
+            subscription: organisation.subscription().to_string(),
+            suspended: organisation.is_suspended(),
+            suspended_at: organisation
+                .suspended_at
+                .map(|date| date.format("%d-%m-%Y %H:%M").to_string()),
+            suspension_reason: organisation.suspension_reason.clone(),
+            created_at: organisation.created_at.format("%d-%m-%Y %H:%M").to_string(),
+        }
+    }
+}
+
+/// An organisation along with its plan and how much of it is in use.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OrganisationDetails {
+    pub organisation: OrganisationResponse,
+    pub subscription: SubscriptionResponse,
+}
+
+impl OrganisationDetails {
+    #[must_use]
+    pub fn new(organisation: &Organisation, usage: Usage) -> Self {
+        Self {
+            organisation: OrganisationResponse::new(organisation),
+            subscription: SubscriptionResponse::new(organisation, usage),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ImpersonationResponse {
+    pub access_token: String,
+    pub expires_in: i64,
+    pub user: UserResponse,
+}
+
+impl ImpersonationResponse {
+    #[must_use]
+    pub fn new(access_token: String, expires_in: i64, user: &User) -> Self {
+        Self {
+            access_token,
+            expires_in,
+            user: UserResponse::new(user),
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use serde::Serialize;
+
+use crate::models::{
+    finance::ProfitAndLoss, livestock::LivestockSummary, settings::OrganisationSettings,
+};
+
+/// A livestock summary with the currency its values are in.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LivestockReport {
+    #[serde(flatten)]
+    pub summary: LivestockSummary,
+    pub currency: String,
+}
+
+impl LivestockReport {
+    #[must_use]
+    pub fn new(summary: LivestockSummary, settings: &OrganisationSettings) -> Self {
+        Self {
+            summary,
+            currency: settings.currency.clone(),
+        }
+    }
+}
+
+/// A profit and loss with the currency its amounts are in.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfitReport {
+    #[serde(flatten)]
+    pub profit: ProfitAndLoss,
+    pub currency: String,
+}
+
+impl ProfitReport {
+    #[must_use]
+    pub fn new(profit: ProfitAndLoss, settings: &OrganisationSettings) -> Self {
+        Self {
+            profit,
+            currency: settings.currency.clone(),
+        }
+    }
+}
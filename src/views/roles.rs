@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::roles::{Resource, Role, Scope};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RoleResponse {
+    pub id: i32,
+    pub name: String,
+    pub description: Option<String>,
+    pub system_defined: bool,
+    pub permissions: Vec<String>,
+    pub created_at: String,
+}
+
+impl RoleResponse {
+    /// Lists the permissions of the role as `resource:action` strings, with the
+    /// role-wide flags of system roles expanded over every resource.
+    #[must_use]
+    pub fn new(role: &Role) -> Self {
+        let permissions = Scope::catalogue()
+            .into_iter()
+            .filter(|scope| role.allows(scope.resource, scope.action))
+            .map(|scope| scope.to_string())
+            .collect();
+
+        Self {
+            id: role.id,
+            name: role.name.clone(),
+            description: role.description.clone(),
+            system_defined: role.is_system_defined(),
+            permissions,
+            created_at: role.created_at.format("%d-%m-%Y %H:%M").to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionCatalogue {
+    pub resource: Resource,
+    pub permissions: Vec<String>,
+}
+
+impl PermissionCatalogue {
+    #[must_use]
+    pub fn all() -> Vec<Self> {
+        Resource::ALL
+            .iter()
+            .map(|resource| Self {
+                resource: *resource,
+                permissions: Scope::catalogue()
+                    .into_iter()
+                    .filter(|scope| scope.resource == *resource)
+                    .map(|scope| scope.to_string())
+                    .collect(),
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,27 @@
+use serde::Serialize;
+
+use crate::models::{
+    entitlements::{Limits, Usage},
+    orgs::Organisation,
+};
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionResponse {
+    pub plan: String,
+    pub limits: Limits,
+    pub usage: Usage,
+}
+
+impl SubscriptionResponse {
+    #[must_use]
+    pub fn new(organisation: &Organisation, usage: Usage) -> Self {
+        let subscription = organisation.subscription();
+
+        Self {
+            plan: subscription.to_string(),
+            limits: subscription.limits(),
+            usage,
+        }
+    }
+}
@@ -57,3 +57,36 @@ impl LoginResponse {
         }
     }
 }
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UserResponse {
+    pub pid: Uuid,
+    pub email: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub role: String,
+    pub is_active: bool,
+    pub password_change_required: bool,
+    pub last_login: Option<String>,
+    pub created_at: String,
+}
+
+impl UserResponse {
+    #[must_use]
+    pub fn new(user: &User) -> Self {
+        Self {
+            pid: user.pid,
+            email: user.email.clone(),
+            first_name: user.first_name.clone(),
+            last_name: user.last_name.clone(),
+            role: user.role.clone(),
+            is_active: user.is_active,
+            password_change_required: user.password_change_required,
+            last_login: user
+                .last_login
+                .map(|date: DateTime<FixedOffset>| date.format("%d-%m-%Y %H:%M:%S").to_string()),
+            created_at: user.created_at.format("%d-%m-%Y %H:%M").to_string(),
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::models::webhooks::{Webhook, WebhookDelivery};
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookResponse {
+    pub pid: Uuid,
+    pub url: String,
+    pub event_types: Vec<String>,
+    pub is_active: bool,
+    pub created_by: Option<Uuid>,
+    pub created_at: String,
+}
+
+impl WebhookResponse {
+    #[must_use]
+    pub fn new(webhook: &Webhook) -> Self {
+        Self {
+            pid: webhook.pid,
+            url: webhook.url.clone(),
+            event_types: webhook.event_types.clone(),
+            is_active: webhook.is_active,
+            created_by: webhook.created_by,
+            created_at: webhook.created_at.format("%d-%m-%Y %H:%M").to_string(),
+        }
+    }
+}
+
+/// A freshly created webhook. The secret is what the partner checks
+/// signatures with and is not shown again.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatedWebhookResponse {
+    #[serde(flatten)]
+    pub webhook: WebhookResponse,
+    pub secret: String,
+}
+
+impl CreatedWebhookResponse {
+    #[must_use]
+    pub fn new(webhook: &Webhook) -> Self {
+        Self {
+            webhook: WebhookResponse::new(webhook),
+            secret: webhook.secret().to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookDeliveryResponse {
+    pub pid: Uuid,
+    pub event_pid: Uuid,
+    pub event_type: String,
+    pub status: String,
+    pub attempts: i32,
+    pub next_attempt_at: Option<String>,
+    pub last_attempt_at: Option<String>,
+    pub response_status: Option<i32>,
+    pub last_error: Option<String>,
+    pub delivered_at: Option<String>,
+    pub created_at: String,
+}
+
+impl WebhookDeliveryResponse {
+    #[must_use]
+    pub fn new(delivery: &WebhookDelivery) -> Self {
+        Self {
+            pid: delivery.pid,
+            event_pid: delivery.event_pid,
+            event_type: delivery.event_type.clone(),
+            status: delivery.status.clone(),
+            attempts: delivery.attempts,
+            next_attempt_at: (delivery.status == "pending").then(|| {
+                delivery
+                    .next_attempt_at
+                    .format("%d-%m-%Y %H:%M")
+                    .to_string()
+            }),
+            last_attempt_at: delivery
+                .last_attempt_at
+                .map(|date| date.format("%d-%m-%Y %H:%M").to_string()),
+            response_status: delivery.response_status,
+            last_error: delivery.last_error.clone(),
+            delivered_at: delivery
+                .delivered_at
+                .map(|date| date.format("%d-%m-%Y %H:%M").to_string()),
+            created_at: delivery.created_at.format("%d-%m-%Y %H:%M").to_string(),
+        }
+    }
+}
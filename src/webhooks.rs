@@ -0,0 +1,144 @@
+#![allow(clippy::missing_errors_doc)]
+
+//! Sends queued webhook deliveries to partners, signing each payload with the
+//! webhook's secret and retrying failures with exponential backoff.
+
+use std::time::Duration;
+
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use reqwest::{Client, header::CONTENT_TYPE};
+use serde_json::json;
+use sha2::Sha256;
+use sqlx::PgPool;
+
+use crate::{
+    AppContext, Result,
+    config::WebhooksConfig,
+    models::webhooks::{DueDelivery, WebhookDelivery},
+};
+
+/// Deliveries sent per look.
+const BATCH_SIZE: i64 = 50;
+
+/// Signs `body` sent at `timestamp` with a webhook's secret. Partners compute
+/// the same over the `X-Polaris-Timestamp` header and the raw body, and
+/// compare it with the `X-Polaris-Signature` header.
+///
+/// # Panics
+/// Never: HMAC takes keys of any length.
+#[must_use]
+pub fn sign(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes keys of any length");
+    mac.update(format!("{timestamp}.{body}").as_bytes());
+
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+#[derive(Debug, Clone)]
+pub struct Dispatcher {
+    db: PgPool,
+    client: Client,
+    config: WebhooksConfig,
+}
+
+impl Dispatcher {
+    pub fn new(ctx: &AppContext) -> Result<Self> {
+        let config = ctx.config.webhooks().clone();
+        let client = Client::builder()
+            .timeout(Duration::from_secs(config.timeout()))
+            .build()?;
+
+        Ok(Self {
+            db: ctx.db.clone(),
+            client,
+            config,
+        })
+    }
+
+    /// Sends the deliveries that are due and returns how many went through.
+    pub async fn dispatch_due(&self) -> Result<usize> {
+        // Long enough for every delivery in the batch to time out in turn.
+        let lease =
+            i32::try_from(self.config.timeout() * BATCH_SIZE.unsigned_abs()).unwrap_or(i32::MAX);
+
+        let due = WebhookDelivery::claim_due(&self.db, BATCH_SIZE, lease).await?;
+
+        let mut delivered = 0;
+
+        for delivery in &due {
+            if self.deliver(delivery).await? {
+                delivered += 1;
+            }
+        }
+
+        Ok(delivered)
+    }
+
+    /// Posts the event to the webhook and records the outcome. Any 2xx answer
+    /// counts as delivered.
+    async fn deliver(&self, delivery: &DueDelivery) -> Result<bool> {
+        let body = json!({
+            "id": delivery.event_pid,
+            "type": delivery.event_type,
+            "organisation": delivery.organisation_pid,
+            "occurredAt": delivery.occurred_at,
+            "data": delivery.payload.0,
+        })
+        .to_string();
+        let timestamp = Utc::now().timestamp();
+
+        let response = self
+            .client
+            .post(&delivery.url)
+            .header(CONTENT_TYPE, "application/json")
+            .header("X-Polaris-Event", &delivery.event_type)
+            .header("X-Polaris-Delivery", delivery.pid.to_string())
+            .header("X-Polaris-Timestamp", timestamp.to_string())
+            .header(
+                "X-Polaris-Signature",
+                sign(&delivery.secret, timestamp, &body),
+            )
+            .body(body)
+            .send()
+            .await;
+
+        let (status, error) = match response {
+            Ok(response) if response.status().is_success() => {
+                WebhookDelivery::record_delivered(
+                    &self.db,
+                    delivery.pid,
+                    i32::from(response.status().as_u16()),
+                )
+                .await?;
+
+                return Ok(true);
+            }
+            Ok(response) => (
+                Some(i32::from(response.status().as_u16())),
+                format!("Partner answered {}", response.status()),
+            ),
+            Err(error) => (None, error.to_string()),
+        };
+
+        tracing::warn!(
+            "Webhook delivery {} to {} failed on attempt {}: {error}",
+            delivery.pid,
+            delivery.url,
+            delivery.attempts + 1
+        );
+
+        WebhookDelivery::record_failure(
+            &self.db,
+            delivery.pid,
+            status,
+            &error,
+            self.config.max_attempts(),
+            self.config.retry_delay(),
+        )
+        .await?;
+
+        Ok(false)
+    }
+}
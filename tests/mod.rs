@@ -2,10 +2,19 @@ mod models;
 mod requests;
 
 use axum_test::{TestServer, TestServerConfig};
-use polaris::{App, AppConfig, AppContext, Environment, Result, controllers};
+use polaris::{
+    App, AppConfig, AppContext, Environment, Result, controllers,
+    models::{
+        dto::{AcceptInvitation, InviteUser},
+        invitations::Invitation,
+        users::User,
+    },
+};
+use serde::Serialize;
 use sqlx::PgPool;
+use uuid::Uuid;
 
-use std::{future::Future, sync::OnceLock};
+use std::{borrow::Cow, future::Future, sync::OnceLock};
 
 pub async fn boot_test() -> Result<AppContext> {
     let config = AppConfig::deserialise_yaml(&Environment::Testing)?;
@@ -40,10 +49,56 @@ where
     f(server, context).await
 }
 
+/// The seeded organisation and its admin.
+pub const ACME: &str = "9d5b0c1e-6a48-4bce-b818-dc8c015fd8a0";
+pub const JOHN_DOE: &str = "bd6f7c26-d2c9-487e-b837-8f77be468033";
+
 pub async fn seed_data(db: &PgPool) -> Result<()> {
     App::seed_data(db).await
 }
 
+/// The pid a record or response body is serialised with.
+pub fn pid(record: &impl Serialize) -> Uuid {
+    serde_json::to_value(record).unwrap()["pid"]
+        .as_str()
+        .and_then(|pid| pid.parse().ok())
+        .expect("record has no pid")
+}
+
+/// Invites `email` to the organisation as `role` and accepts the invitation
+/// with the password `Password`.
+pub async fn invite_and_accept(db: &PgPool, org_pid: Uuid, email: &str, role: &str) -> User {
+    let mut conn = db.acquire().await.unwrap();
+    let admin = User::find_organisation_admin(&mut *conn, org_pid)
+        .await
+        .unwrap();
+
+    let issued = Invitation::create(
+        &mut conn,
+        org_pid,
+        admin.pid(),
+        &InviteUser {
+            email: Cow::Owned(email.to_string()),
+            first_name: Cow::Borrowed("Invited"),
+            last_name: Cow::Borrowed("Member"),
+            role: Cow::Owned(role.to_string()),
+        },
+    )
+    .await
+    .unwrap();
+
+    Invitation::accept(
+        &mut conn,
+        &AcceptInvitation {
+            token: Cow::Owned(issued.token),
+            password: Cow::Borrowed("Password"),
+            confirm_password: Cow::Borrowed("Password"),
+        },
+    )
+    .await
+    .unwrap()
+}
+
 static CLEANUP_UUID: OnceLock<Vec<(&'static str, &'static str)>> = OnceLock::new();
 static CLEANUP_DATE: OnceLock<Vec<(&'static str, &'static str)>> = OnceLock::new();
 static CLEANUP_INT: OnceLock<Vec<(&'static str, &'static str)>> = OnceLock::new();
@@ -0,0 +1,205 @@
+use std::borrow::Cow;
+
+use insta::{Settings, assert_debug_snapshot};
+use polaris::models::{
+    alerts::{Alert, AlertRule},
+    dto::{AlertQuery, CreateAlertRule},
+    users::User,
+};
+use serial_test::serial;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{boot_test, seed_data};
+
+macro_rules! configure_insta {
+    ($(expr:expr),*) => {
+        let mut settings = Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_suffix("alerts");
+        settings.set_snapshot_path("snapshots/alerts");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+const ACME: &str = "9d5b0c1e-6a48-4bce-b818-dc8c015fd8a0";
+
+fn rule<'a>(
+    kind: &'a str,
+    threshold: Option<f64>,
+    window_days: Option<i32>,
+) -> CreateAlertRule<'a> {
+    CreateAlertRule {
+        name: Cow::Borrowed("Herd watch"),
+        kind: Cow::Borrowed(kind),
+        threshold,
+        window_days,
+        severity: Some(Cow::Borrowed("high")),
+        channels: None,
+    }
+}
+
+async fn admin(db: &PgPool) -> User {
+    User::find_organisation_admin(db, Uuid::parse_str(ACME).unwrap())
+        .await
+        .unwrap()
+}
+
+/// Records dated relative to today, so the rules see them as recent.
+async fn record_recent_history(db: &PgPool, admin: &User) {
+    sqlx::query(
+        "
+        INSERT INTO weight_records (animal_pid, organisation_pid, created_by, mass, unit, status, record_date)
+        SELECT a.pid, a.organisation_pid, $2, w.mass, 'kg', 'normal', CURRENT_DATE - w.days_ago
+        FROM animals a
+        JOIN (VALUES ('AC001', 500.0, 10), ('AC001', 400.0, 1), ('AC002', 300.0, 10), ('AC002', 290.0, 1))
+            AS w (tag_id, mass, days_ago) ON w.tag_id = a.tag_id
+        WHERE a.organisation_pid = $1
+        ORDER BY w.days_ago DESC",
+    )
+    .bind(admin.organisation_pid())
+    .bind(admin.pid())
+    .execute(db)
+    .await
+    .unwrap();
+
+    sqlx::query(
+        "
+        INSERT INTO health_records (
+            animal_pid, organisation_pid, created_by, condition, severity, status, record_date,
+            description
+        )
+        SELECT a.pid, a.organisation_pid, $2, 'infection', 'high', 'active', CURRENT_DATE - h.days_ago,
+            'Ferdinand is unwell'
+        FROM animals a
+        JOIN (VALUES (3), (12)) AS h (days_ago) ON TRUE
+        WHERE a.organisation_pid = $1 AND a.tag_id = 'AC003'",
+    )
+    .bind(admin.organisation_pid())
+    .bind(admin.pid())
+    .execute(db)
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+#[serial]
+async fn rules_need_the_settings_of_their_kind() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    let admin = admin(&ctx.db).await;
+
+    let mut results = Vec::new();
+    for dto in [
+        rule("weight_drop", None, None),
+        rule("weight_drop", Some(150.0), None),
+        rule("weighing_overdue", Some(10.0), None),
+        rule("body_condition", Some(10.0), None),
+    ] {
+        results.push(
+            AlertRule::create(&ctx.db, &admin, &dto)
+                .await
+                .map(|_| ())
+                .unwrap_err()
+                .to_string(),
+        );
+    }
+
+    let dropped = AlertRule::create(&ctx.db, &admin, &rule("weight_drop", Some(10.0), Some(7)))
+        .await
+        .unwrap();
+    let repeated = AlertRule::create(&ctx.db, &admin, &rule("repeated_high_severity", None, None))
+        .await
+        .unwrap();
+
+    assert_debug_snapshot!((
+        results,
+        (dropped.threshold, dropped.window_days, dropped.channels),
+        (repeated.threshold, repeated.window_days),
+    ));
+}
+
+#[tokio::test]
+#[serial]
+async fn rules_raise_each_alert_once() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    let admin = admin(&ctx.db).await;
+    record_recent_history(&ctx.db, &admin).await;
+
+    let rules = [
+        rule("weight_drop", Some(10.0), None),
+        rule("repeated_high_severity", None, None),
+        rule("weighing_overdue", None, Some(30)),
+    ];
+
+    let mut raised = Vec::new();
+    let mut repeated = Vec::new();
+    for dto in &rules {
+        let rule = AlertRule::create(&ctx.db, &admin, dto).await.unwrap();
+        let mut conn = ctx.db.acquire().await.unwrap();
+
+        let mut messages = rule
+            .evaluate(&mut conn)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|alert| alert.message)
+            .collect::<Vec<_>>();
+        messages.sort();
+        raised.push((dto.kind.to_string(), messages));
+
+        repeated.push(rule.evaluate(&mut conn).await.unwrap().len());
+    }
+
+    let alerts = Alert::find_all(&ctx.db, admin.organisation_pid(), &AlertQuery::default())
+        .await
+        .unwrap();
+
+    assert_debug_snapshot!((raised, repeated, alerts.len()));
+}
+
+#[tokio::test]
+#[serial]
+async fn alerts_are_acknowledged_then_resolved() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    let admin = admin(&ctx.db).await;
+    record_recent_history(&ctx.db, &admin).await;
+
+    let rule = AlertRule::create(&ctx.db, &admin, &rule("weight_drop", Some(10.0), None))
+        .await
+        .unwrap();
+    let mut conn = ctx.db.acquire().await.unwrap();
+    let alert = rule.evaluate(&mut conn).await.unwrap().remove(0);
+
+    let acknowledged = Alert::acknowledge(&mut conn, &admin, alert.pid)
+        .await
+        .unwrap();
+    let acknowledged_again = Alert::acknowledge(&mut conn, &admin, alert.pid).await;
+    let resolved = Alert::resolve(&mut conn, &admin, alert.pid).await.unwrap();
+    let resolved_again = Alert::resolve(&mut conn, &admin, alert.pid).await;
+
+    // The evidence was already alerted on, so resolving does not bring it back.
+    let reraised = rule.evaluate(&mut conn).await.unwrap();
+
+    assert_debug_snapshot!((
+        (
+            acknowledged.status,
+            acknowledged.acknowledged_by == Some(admin.pid())
+        ),
+        acknowledged_again.map(|_| ()).unwrap_err().to_string(),
+        (resolved.status, resolved.resolved_by == Some(admin.pid())),
+        resolved_again.map(|_| ()).unwrap_err().to_string(),
+        reraised.len(),
+    ));
+}
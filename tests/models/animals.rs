@@ -33,6 +33,7 @@ macro_rules! configure_insta {
         purchase_date: None,
         female_parent: None,
         male_parent: None,
+        location: None,
     }
 )]
 #[case(
@@ -44,6 +45,7 @@ macro_rules! configure_insta {
         purchase_date: None,
         female_parent: None,
         male_parent: None,
+        location: None,
     }
 )]
 #[case(
@@ -55,6 +57,7 @@ macro_rules! configure_insta {
         purchase_date: None,
         female_parent: None,
         male_parent: None,
+        location: None,
     }
 )]
 #[case(
@@ -66,6 +69,7 @@ macro_rules! configure_insta {
         purchase_date: None,
         female_parent: Some(Uuid::parse_str("487a7b25-3ea9-4a40-ae8c-43cdf51138be").unwrap()),
         male_parent: None,
+        location: None,
     }
 )]
 #[case(
@@ -76,6 +80,7 @@ macro_rules! configure_insta {
         breed: None,
         purchase_date: None,
         male_parent: Some(Uuid::parse_str("d909e761-36da-4062-ae78-abba4f7c1103").unwrap()),
+        location: None,
         female_parent: None,
     }
 )]
@@ -144,7 +149,13 @@ async fn can_create_one() {
         notes: None,
     };
 
-    let result = Animal::register(&ctx.db, org_pid, user_pid, &params).await;
+    let result = Animal::register(
+        &mut ctx.db.acquire().await.unwrap(),
+        org_pid,
+        user_pid,
+        &params,
+    )
+    .await;
 
     with_settings!({
         filters => {
@@ -186,7 +197,13 @@ async fn can_create_one_with_parents() {
         notes: None,
     };
 
-    let result = Animal::register(&ctx.db, org_pid, user_pid, &params).await;
+    let result = Animal::register(
+        &mut ctx.db.acquire().await.unwrap(),
+        org_pid,
+        user_pid,
+        &params,
+    )
+    .await;
 
     with_settings!({
         filters => {
@@ -227,9 +244,12 @@ async fn can_update_one() {
         weight_at_birth: None,
         current_weight: Some(61000),
         notes: None,
+        sale_price: None,
+        sale_date: None,
     };
 
-    let result = Animal::update_by_id(&ctx.db, &params, org_pid, id).await;
+    let result =
+        Animal::update_by_id(&mut ctx.db.acquire().await.unwrap(), &params, org_pid, id).await;
 
     with_settings!({
         filters => {
@@ -285,7 +305,8 @@ async fn can_link_offspring(#[case] test_name: &str, #[case] params: LinkOffspri
 
     let org_pid = Uuid::parse_str("9d5b0c1e-6a48-4bce-b818-dc8c015fd8a0").unwrap();
 
-    let result = Animal::link_offspring(&ctx.db, org_pid, &params).await;
+    let result =
+        Animal::link_offspring(&mut ctx.db.acquire().await.unwrap(), org_pid, &params).await;
 
     with_settings!({
         filters => {
@@ -0,0 +1,110 @@
+use std::borrow::Cow;
+
+use insta::{Settings, assert_debug_snapshot};
+use polaris::models::{
+    api_keys::ApiKey,
+    dto::{CreateApiKey, CreateRole},
+    roles::{Action, Resource, Role},
+};
+use rstest::rstest;
+use serial_test::serial;
+use uuid::Uuid;
+
+use crate::{boot_test, invite_and_accept, seed_data};
+
+macro_rules! configure_insta {
+    ($(expr:expr),*) => {
+        let mut settings = Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_suffix("api_keys");
+        settings.set_snapshot_path("snapshots/api_keys");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+const ACME: &str = "9d5b0c1e-6a48-4bce-b818-dc8c015fd8a0";
+
+fn new_key(scopes: &[&str]) -> CreateApiKey<'static> {
+    CreateApiKey {
+        name: Cow::Borrowed("Weighing scale"),
+        scopes: scopes.iter().map(ToString::to_string).collect(),
+        expires_at: None,
+    }
+}
+
+#[rstest]
+#[case("staff_can_grant_what_they_may_do", &["animals:read", "weight_records:write"])]
+#[case("staff_cannot_grant_more_than_they_may_do", &["users:manage"])]
+#[case("cannot_create_key_without_scopes", &[])]
+#[case("cannot_create_key_with_unknown_scope", &["cows:milk"])]
+#[tokio::test]
+#[serial]
+async fn can_create(#[case] test_name: &str, #[case] scopes: &[&str]) {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    let staff = invite_and_accept(
+        &ctx.db,
+        Uuid::parse_str(ACME).unwrap(),
+        "staff@acme.com",
+        "staff",
+    )
+    .await;
+
+    let mut conn = ctx.db.acquire().await.unwrap();
+    let result = ApiKey::create(&mut conn, &staff, &new_key(scopes))
+        .await
+        .map(|issued| (issued.api_key.name, issued.api_key.scopes));
+
+    assert_debug_snapshot!(test_name, result);
+}
+
+#[tokio::test]
+#[serial]
+async fn key_is_limited_by_its_creators_role() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    let org_pid = Uuid::parse_str(ACME).unwrap();
+    let staff = invite_and_accept(&ctx.db, org_pid, "staff@acme.com", "staff").await;
+
+    let viewer_role = Role::create(
+        &ctx.db,
+        org_pid,
+        &CreateRole {
+            name: Cow::Borrowed("viewer"),
+            description: None,
+            permissions: vec!["animals:read".into()],
+        },
+    )
+    .await
+    .unwrap();
+
+    let mut conn = ctx.db.acquire().await.unwrap();
+    let issued = ApiKey::create(
+        &mut conn,
+        &staff,
+        &new_key(&["animals:read", "weight_records:write"]),
+    )
+    .await
+    .unwrap();
+
+    let staff_role = Role::find_for_organisation(&mut *conn, org_pid, "staff")
+        .await
+        .unwrap();
+
+    let as_staff = issued.api_key.restrict(&staff_role);
+    let as_viewer = issued.api_key.restrict(&viewer_role);
+
+    assert_debug_snapshot!((
+        as_staff.allows(Resource::Animals, Action::Read),
+        as_staff.allows(Resource::WeightRecords, Action::Write),
+        as_staff.allows(Resource::Animals, Action::Write),
+        as_viewer.allows(Resource::Animals, Action::Read),
+        as_viewer.allows(Resource::WeightRecords, Action::Write),
+    ));
+}
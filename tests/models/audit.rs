@@ -0,0 +1,159 @@
+use insta::{Settings, assert_debug_snapshot};
+use polaris::models::{
+    animals::Animal,
+    audit::{AuditLog, AuditQuery},
+    dto::UpdateAnimal,
+    tenant::TenantTransaction,
+    weight::WeightRecord,
+};
+use serial_test::serial;
+use uuid::Uuid;
+
+macro_rules! configure_insta {
+    ($(expr:expr),*) => {
+        let mut settings = Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_path("snapshots/audit");
+        settings.set_snapshot_suffix("audit");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+const ACME: &str = "9d5b0c1e-6a48-4bce-b818-dc8c015fd8a0";
+const JOHN_DOE: &str = "bd6f7c26-d2c9-487e-b837-8f77be468033";
+const ROSE: &str = "f6417c11-d817-4626-9e8d-c68a44002d4b";
+
+/// Renames Rose and trashes her latest weight record as John Doe.
+async fn change_rose(db: &sqlx::PgPool) {
+    let org_pid = Uuid::parse_str(ACME).unwrap();
+    let mut txn = TenantTransaction::begin(db, org_pid, Uuid::parse_str(JOHN_DOE).unwrap())
+        .await
+        .unwrap();
+
+    let params = UpdateAnimal {
+        tag_id: None,
+        name: Some("Rosie".into()),
+        gender: None,
+        status: None,
+        specie: None,
+        breed: None,
+        date_of_birth: None,
+        female_parent_id: None,
+        male_parent_id: None,
+        purchase_date: None,
+        purchase_price: None,
+        weight_at_birth: None,
+        current_weight: None,
+        notes: None,
+        sale_price: None,
+        sale_date: None,
+    };
+    Animal::update_by_id(&mut txn, &params, org_pid, Uuid::parse_str(ROSE).unwrap())
+        .await
+        .unwrap();
+    WeightRecord::delete_by_id(&mut *txn, org_pid, 115, Uuid::parse_str(JOHN_DOE).unwrap())
+        .await
+        .unwrap();
+
+    txn.commit().await.unwrap();
+}
+
+fn summarise(logs: &[AuditLog]) -> Vec<(&str, &str, Option<String>, Option<&str>)> {
+    logs.iter()
+        .map(|log| {
+            (
+                log.table_name.as_str(),
+                log.action.as_str(),
+                log.changed_by.map(|pid| pid.to_string()),
+                log.changed_by_name.as_deref(),
+            )
+        })
+        .collect()
+}
+
+#[tokio::test]
+#[serial]
+async fn records_updates_and_deletes_with_user() {
+    configure_insta!();
+
+    let ctx = crate::boot_test().await.unwrap();
+    crate::seed_data(&ctx.db).await.unwrap();
+
+    change_rose(&ctx.db).await;
+
+    let org_pid = Uuid::parse_str(ACME).unwrap();
+    let animals = AuditLog::find_all(
+        &ctx.db,
+        org_pid,
+        &AuditQuery {
+            table: Some("animals".into()),
+            record: Some(ROSE.into()),
+            user: Some(Uuid::parse_str(JOHN_DOE).unwrap()),
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    let weights = AuditLog::find_all(
+        &ctx.db,
+        org_pid,
+        &AuditQuery {
+            table: Some("weight_records".into()),
+            record: Some("115".into()),
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+
+    let renamed = animals.first().map(|log| {
+        (
+            log.old_data.as_ref().map(|data| data["name"].clone()),
+            log.new_data.as_ref().map(|data| data["name"].clone()),
+        )
+    });
+    // Deleting a record moves it to the trash.
+    let deleted = weights
+        .first()
+        .map(|log| log.new_data.as_ref().map(|data| data["deleted_by"].clone()));
+
+    assert_debug_snapshot!((summarise(&animals), renamed, summarise(&weights), deleted));
+}
+
+#[tokio::test]
+#[serial]
+async fn can_find_animal_history() {
+    configure_insta!();
+
+    let ctx = crate::boot_test().await.unwrap();
+    crate::seed_data(&ctx.db).await.unwrap();
+
+    change_rose(&ctx.db).await;
+
+    let logs = AuditLog::find_by_animal(
+        &ctx.db,
+        Uuid::parse_str(ACME).unwrap(),
+        Uuid::parse_str(ROSE).unwrap(),
+    )
+    .await
+    .unwrap();
+
+    // Seeding inserts Rose and her fifteen weight records without a user.
+    assert_debug_snapshot!((logs.len(), summarise(&logs[logs.len() - 2..])));
+}
+
+#[tokio::test]
+#[serial]
+async fn cannot_read_other_tenant_history() {
+    configure_insta!();
+
+    let ctx = crate::boot_test().await.unwrap();
+    crate::seed_data(&ctx.db).await.unwrap();
+
+    let globex = Uuid::parse_str("4a93f0a8-4a91-482d-92d8-f0b3b084c2e4").unwrap();
+    let logs = AuditLog::find_by_animal(&ctx.db, globex, Uuid::parse_str(ROSE).unwrap())
+        .await
+        .unwrap();
+
+    assert_debug_snapshot!(logs.len());
+}
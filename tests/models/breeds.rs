@@ -161,7 +161,8 @@ async fn can_update_by_id() {
     params = params.male_weight_range("550-700 kg");
     params = params.female_weight_range("400-450 kg");
 
-    let result = Breed::update_by_id(&ctx.db, org_pid, id, &params).await;
+    let result =
+        Breed::update_by_id(&mut ctx.db.acquire().await.unwrap(), org_pid, id, &params).await;
 
     with_settings!({
         filters => {
@@ -0,0 +1,383 @@
+use insta::{Settings, assert_debug_snapshot};
+use polaris::models::{
+    dto::{
+        ConversionQuery, FeedQuery, FeedRation, LedgerQuery, NewFeedPurchase, NewFeeding,
+        NewFeedstuff, NewGroup, NewRation, RationItem, UpdateRation,
+    },
+    feed::{ConversionReport, FeedPurchase, Feeding, Feedstuff, Ration},
+    finance::LedgerEntry,
+    groups::AnimalGroup,
+};
+use serial_test::serial;
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::{boot_test, seed_data};
+
+macro_rules! configure_insta {
+    ($(expr:expr),*) => {
+        let mut settings = Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_path("snapshots/feed");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+const ACME: &str = "9d5b0c1e-6a48-4bce-b818-dc8c015fd8a0";
+const JOHN_DOE: &str = "bd6f7c26-d2c9-487e-b837-8f77be468033";
+
+fn feedstuff(name: &str, dry_matter: i64, crude_protein: i64, energy: i64) -> NewFeedstuff<'_> {
+    NewFeedstuff {
+        name: name.into(),
+        dry_matter: Some(dry_matter),
+        crude_protein: Some(crude_protein),
+        crude_fibre: None,
+        energy: Some(energy),
+        reorder_level: Some(20_000),
+        notes: None,
+    }
+}
+
+/// Hay and meal, with 1000 of hay and 200 of meal bought in June 2024.
+async fn stock_up(conn: &mut PgConnection) -> (Feedstuff, Feedstuff) {
+    let org_pid = Uuid::parse_str(ACME).unwrap();
+    let user_pid = Uuid::parse_str(JOHN_DOE).unwrap();
+
+    let hay = Feedstuff::create(conn, org_pid, user_pid, &feedstuff("Hay", 8500, 900, 850))
+        .await
+        .unwrap();
+    let meal = Feedstuff::create(
+        conn,
+        org_pid,
+        user_pid,
+        &feedstuff("Dairy meal", 9000, 3600, 1200),
+    )
+    .await
+    .unwrap();
+
+    for (feedstuff, quantity, cost) in [(&hay, 100_000, 450_000), (&meal, 20_000, 160_000)] {
+        FeedPurchase::create(
+            conn,
+            org_pid,
+            user_pid,
+            &NewFeedPurchase {
+                feedstuff: feedstuff.pid,
+                quantity,
+                cost: Some(cost),
+                supplier: Some("Farmers' Co-op".into()),
+                purchase_date: "2024-06-01".parse().ok(),
+            },
+        )
+        .await
+        .unwrap();
+    }
+
+    (hay, meal)
+}
+
+#[tokio::test]
+#[serial]
+async fn feeding_takes_from_stock() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    let org_pid = Uuid::parse_str(ACME).unwrap();
+    let user_pid = Uuid::parse_str(JOHN_DOE).unwrap();
+
+    let mut conn = ctx.db.acquire().await.unwrap();
+    let (hay, meal) = stock_up(&mut conn).await;
+
+    let ration = Ration::create(
+        &mut conn,
+        org_pid,
+        user_pid,
+        &NewRation {
+            name: "Dairy cows".into(),
+            specie: Some("cattle".into()),
+            breed: None,
+            group: None,
+            items: vec![
+                RationItem {
+                    feedstuff: hay.pid,
+                    quantity: 800,
+                },
+                RationItem {
+                    feedstuff: meal.pid,
+                    quantity: 200,
+                },
+            ],
+            notes: None,
+        },
+    )
+    .await
+    .unwrap();
+
+    let to_two = Ration::feed(
+        &mut conn,
+        org_pid,
+        user_pid,
+        ration.pid,
+        &FeedRation {
+            feeding_date: "2024-06-10".parse().ok(),
+            tag_ids: Some(vec!["ac001".into(), "AC007".into()]),
+            notes: None,
+        },
+    )
+    .await
+    .unwrap();
+
+    let to_the_herd = Ration::feed(
+        &mut conn,
+        org_pid,
+        user_pid,
+        ration.pid,
+        &FeedRation {
+            feeding_date: "2024-06-11".parse().ok(),
+            ..FeedRation::default()
+        },
+    )
+    .await
+    .unwrap();
+
+    let too_much = Feeding::create(
+        &mut conn,
+        org_pid,
+        user_pid,
+        &NewFeeding {
+            feedstuff: meal.pid,
+            quantity: 100_000,
+            feeding_date: None,
+            tag_ids: None,
+            notes: None,
+        },
+    )
+    .await;
+
+    let meal_purchase = FeedPurchase::find_all(
+        &mut *conn,
+        org_pid,
+        &FeedQuery {
+            feedstuff: Some(meal.pid),
+            ..FeedQuery::default()
+        },
+    )
+    .await
+    .unwrap()
+    .remove(0);
+    let fed_purchase = FeedPurchase::delete_by_pid(&mut conn, org_pid, meal_purchase.pid).await;
+    let fed_feedstuff = Feedstuff::delete_by_pid(&mut conn, org_pid, hay.pid).await;
+
+    Feeding::delete_by_pid(&mut conn, org_pid, to_the_herd[0].pid)
+        .await
+        .unwrap();
+    drop(conn);
+
+    let stock = Feedstuff::find_all(&ctx.db, org_pid)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|feedstuff| (feedstuff.name, feedstuff.stock))
+        .collect::<Vec<_>>();
+    let ledger = LedgerEntry::find_all(
+        &ctx.db,
+        org_pid,
+        &LedgerQuery {
+            category: Some("feed".into()),
+            ..LedgerQuery::default()
+        },
+    )
+    .await
+    .unwrap()
+    .into_iter()
+    .map(|entry| (entry.amount, entry.description, entry.source))
+    .collect::<Vec<_>>();
+
+    assert_debug_snapshot!((
+        (ration.specie_name, ration.nutrients),
+        to_two
+            .iter()
+            .map(|feeding| (&feeding.feedstuff_name, feeding.quantity, &feeding.tag_ids))
+            .collect::<Vec<_>>(),
+        to_the_herd
+            .iter()
+            .map(|feeding| (
+                &feeding.feedstuff_name,
+                feeding.quantity,
+                feeding.tag_ids.len()
+            ))
+            .collect::<Vec<_>>(),
+        too_much.map_err(|error| error.to_string()).map(|_| ()),
+        fed_purchase.map_err(|error| error.to_string()),
+        fed_feedstuff.map_err(|error| error.to_string()),
+        stock,
+        ledger,
+    ));
+}
+
+#[tokio::test]
+#[serial]
+async fn can_work_out_feed_conversion() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    let org_pid = Uuid::parse_str(ACME).unwrap();
+    let user_pid = Uuid::parse_str(JOHN_DOE).unwrap();
+
+    let mut conn = ctx.db.acquire().await.unwrap();
+    let (hay, _) = stock_up(&mut conn).await;
+
+    for (quantity, date, tag_ids) in [
+        (7200, "2024-06-20", vec!["AC007"]),
+        (4000, "2024-06-21", vec!["AC001", "AC007"]),
+        (5000, "2024-06-22", vec![]),
+        (9900, "2024-08-01", vec!["AC007"]),
+    ] {
+        Feeding::create(
+            &mut conn,
+            org_pid,
+            user_pid,
+            &NewFeeding {
+                feedstuff: hay.pid,
+                quantity,
+                feeding_date: date.parse().ok(),
+                tag_ids: Some(tag_ids.into_iter().map(Into::into).collect()),
+                notes: None,
+            },
+        )
+        .await
+        .unwrap();
+    }
+    drop(conn);
+
+    let june_and_july = ConversionReport::find(
+        &ctx.db,
+        org_pid,
+        ConversionQuery {
+            from: "2024-06-01".parse().ok(),
+            to: "2024-07-31".parse().ok(),
+        },
+    )
+    .await
+    .unwrap();
+
+    let all_time = ConversionReport::find(&ctx.db, org_pid, ConversionQuery::default())
+        .await
+        .unwrap();
+
+    assert_debug_snapshot!((
+        june_and_july,
+        (
+            all_time.herd.feed,
+            all_time.herd.weight_gain,
+            all_time.herd.conversion_ratio
+        ),
+        all_time
+            .animals
+            .iter()
+            .map(|animal| (animal.tag_id.clone(), animal.feed, animal.conversion_ratio))
+            .collect::<Vec<_>>(),
+    ));
+}
+
+#[tokio::test]
+#[serial]
+async fn rations_can_feed_a_group() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    let org_pid = Uuid::parse_str(ACME).unwrap();
+    let user_pid = Uuid::parse_str(JOHN_DOE).unwrap();
+
+    let mut conn = ctx.db.acquire().await.unwrap();
+    let (hay, _) = stock_up(&mut conn).await;
+    let calves = AnimalGroup::create(
+        &mut conn,
+        org_pid,
+        user_pid,
+        &NewGroup {
+            name: "Calves".into(),
+            kind: "static".into(),
+            filter: None,
+            tag_ids: Some(vec!["AC001".into(), "AC002".into()]),
+            notes: None,
+        },
+    )
+    .await
+    .unwrap();
+
+    let ration = |specie: Option<&'static str>, group| NewRation {
+        name: "Calf starter".into(),
+        specie: specie.map(Into::into),
+        breed: None,
+        group,
+        items: vec![RationItem {
+            feedstuff: hay.pid,
+            quantity: 300,
+        }],
+        notes: None,
+    };
+
+    let mut invalid = vec![];
+    for params in [
+        ration(Some("cattle"), Some(calves.pid)),
+        ration(None, Some(Uuid::nil())),
+    ] {
+        let created = Ration::create(&mut conn, org_pid, user_pid, &params).await;
+        invalid.push(created.map(|ration| ration.name));
+    }
+
+    let starter = Ration::create(
+        &mut conn,
+        org_pid,
+        user_pid,
+        &ration(None, Some(calves.pid)),
+    )
+    .await
+    .unwrap();
+
+    let fed = Ration::feed(
+        &mut conn,
+        org_pid,
+        user_pid,
+        starter.pid,
+        &FeedRation {
+            feeding_date: "2024-06-10".parse().ok(),
+            ..FeedRation::default()
+        },
+    )
+    .await
+    .unwrap();
+
+    let for_cattle = Ration::update(
+        &mut conn,
+        org_pid,
+        starter.pid,
+        &UpdateRation {
+            name: None,
+            specie: Some("cattle".into()),
+            breed: None,
+            group: None,
+            items: None,
+            notes: None,
+        },
+    )
+    .await
+    .unwrap();
+    drop(conn);
+
+    assert_eq!(starter.group_pid, Some(calves.pid));
+    assert_debug_snapshot!((
+        invalid,
+        starter.group_name,
+        fed.into_iter()
+            .map(|feeding| (feeding.quantity, feeding.tag_ids))
+            .collect::<Vec<_>>(),
+        (for_cattle.specie_name, for_cattle.group_pid),
+    ));
+}
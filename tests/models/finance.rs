@@ -0,0 +1,326 @@
+use insta::{Settings, assert_debug_snapshot};
+use polaris::models::{
+    animals::Animal,
+    dto::{LedgerQuery, NewGroup, NewLedgerEntry, ProfitQuery, UpdateAnimal},
+    finance::{AnimalProfit, LedgerEntry, ProfitAndLoss},
+    groups::AnimalGroup,
+    trash::Trash,
+};
+use rust_decimal::Decimal;
+use serial_test::serial;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{boot_test, seed_data};
+
+macro_rules! configure_insta {
+    ($(expr:expr),*) => {
+        let mut settings = Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_path("snapshots/finance");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+const ACME: &str = "9d5b0c1e-6a48-4bce-b818-dc8c015fd8a0";
+const DAISY: &str = "b2bd6270-8bec-42ce-99ff-d0eb1a076221";
+const JOHN_DOE: &str = "bd6f7c26-d2c9-487e-b837-8f77be468033";
+
+/// Daisy's entries as `(category, amount, source)`.
+async fn daisy(db: &PgPool) -> Vec<(String, Decimal, Option<String>)> {
+    LedgerEntry::find_all(
+        db,
+        Uuid::parse_str(ACME).unwrap(),
+        &LedgerQuery {
+            animal: Some(Uuid::parse_str(DAISY).unwrap()),
+            ..LedgerQuery::default()
+        },
+    )
+    .await
+    .unwrap()
+    .into_iter()
+    .map(|entry| (entry.category, entry.amount, entry.source))
+    .collect()
+}
+
+fn update(status: &str, sale_price: Option<i64>) -> UpdateAnimal<'_> {
+    UpdateAnimal {
+        status: Some(status.into()),
+        sale_price,
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn records_post_to_the_ledger() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    let org_pid = Uuid::parse_str(ACME).unwrap();
+    let daisy_pid = Uuid::parse_str(DAISY).unwrap();
+    let mut steps = vec![("seeding", daisy(&ctx.db).await)];
+
+    for (step, sql) in [
+        (
+            "repricing her treatment",
+            "UPDATE health_records SET cost = 1800 WHERE id = 101",
+        ),
+        (
+            "trashing her treatment",
+            "UPDATE health_records SET deleted_at = NOW() WHERE id = 101",
+        ),
+        (
+            "restoring her treatment",
+            "UPDATE health_records SET deleted_at = NULL WHERE id = 101",
+        ),
+        (
+            "forgetting her price",
+            "UPDATE animals SET purchase_price = NULL WHERE pid = 'b2bd6270-8bec-42ce-99ff-d0eb1a076221'",
+        ),
+        (
+            "removing her treatment",
+            "DELETE FROM health_records WHERE id = 101",
+        ),
+    ] {
+        sqlx::query(sql).execute(&ctx.db).await.unwrap();
+        steps.push((step, daisy(&ctx.db).await));
+    }
+
+    for (step, params) in [
+        ("selling her", update("sold", Some(6_250_000))),
+        ("agreeing a better price", update("sold", Some(6_400_000))),
+        ("taking her back", update("active", None)),
+    ] {
+        let mut conn = ctx.db.acquire().await.unwrap();
+        Animal::update_by_id(&mut conn, &params, org_pid, daisy_pid)
+            .await
+            .unwrap();
+        drop(conn);
+        steps.push((step, daisy(&ctx.db).await));
+    }
+
+    assert_debug_snapshot!(steps);
+}
+
+#[tokio::test]
+#[serial]
+async fn purged_animals_keep_their_ledger_entries() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    let org_pid = Uuid::parse_str(ACME).unwrap();
+    let daisy_pid = Uuid::parse_str(DAISY).unwrap();
+
+    let mut conn = ctx.db.acquire().await.unwrap();
+    Animal::update_by_id(
+        &mut conn,
+        &update("sold", Some(6_250_000)),
+        org_pid,
+        daisy_pid,
+    )
+    .await
+    .unwrap();
+    sqlx::query("UPDATE animals SET deleted_at = NOW() - INTERVAL '31 days' WHERE pid = $1")
+        .bind(daisy_pid)
+        .execute(&mut *conn)
+        .await
+        .unwrap();
+    let purged = Trash::purge(&mut conn, 30).await.unwrap();
+    drop(conn);
+
+    let sales = LedgerEntry::find_all(&ctx.db, org_pid, &LedgerQuery::default())
+        .await
+        .unwrap()
+        .into_iter()
+        .filter(|entry| entry.source_id.as_deref() == Some(DAISY))
+        .map(|entry| {
+            (
+                entry.category,
+                entry.amount,
+                entry.animal_pid,
+                entry.description,
+                entry.source,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(1, purged.animals);
+    assert_debug_snapshot!(sales);
+}
+
+#[tokio::test]
+#[serial]
+async fn can_total_profit_and_loss() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    let org_pid = Uuid::parse_str(ACME).unwrap();
+    let user_pid = Uuid::parse_str(JOHN_DOE).unwrap();
+    let daisy_pid = Uuid::parse_str(DAISY).unwrap();
+
+    let mut conn = ctx.db.acquire().await.unwrap();
+    for (category, amount, tag_id) in [
+        ("feed", 1_200_000, None),
+        ("labour", 800_000, None),
+        ("feed", 90_000, Some("AC001")),
+        ("milk_sales", 150_000, Some("AC001")),
+    ] {
+        LedgerEntry::create(
+            &mut conn,
+            org_pid,
+            user_pid,
+            &NewLedgerEntry {
+                category: category.into(),
+                amount,
+                entry_date: "2024-06-21".parse().ok(),
+                description: None,
+                tag_id: tag_id.map(Into::into),
+                group: None,
+            },
+        )
+        .await
+        .unwrap();
+    }
+    Animal::update_by_id(
+        &mut conn,
+        &update("sold", Some(6_250_000)),
+        org_pid,
+        daisy_pid,
+    )
+    .await
+    .unwrap();
+    drop(conn);
+
+    let june = ProfitQuery {
+        from: "2024-06-01".parse().ok(),
+        to: "2024-06-30".parse().ok(),
+    };
+
+    let herd = ProfitAndLoss::for_herd(&ctx.db, org_pid, ProfitQuery::default())
+        .await
+        .unwrap();
+    let herd_in_june = ProfitAndLoss::for_herd(&ctx.db, org_pid, june)
+        .await
+        .unwrap();
+    let daisy = ProfitAndLoss::for_animal(&ctx.db, org_pid, daisy_pid, ProfitQuery::default())
+        .await
+        .unwrap();
+    let animals = AnimalProfit::find_all(&ctx.db, org_pid, ProfitQuery::default())
+        .await
+        .unwrap();
+    let unknown = ProfitAndLoss::for_animal(&ctx.db, org_pid, Uuid::nil(), june).await;
+
+    assert_debug_snapshot!((
+        herd,
+        herd_in_june,
+        daisy,
+        animals
+            .iter()
+            .take(3)
+            .map(|animal| (
+                animal.tag_id.clone(),
+                animal.profit,
+                animal.return_on_purchase
+            ))
+            .collect::<Vec<_>>(),
+        unknown.map_err(|error| error.to_string()).map(|_| ()),
+    ));
+}
+
+#[tokio::test]
+#[serial]
+async fn can_total_a_groups_profit_and_loss() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    let org_pid = Uuid::parse_str(ACME).unwrap();
+    let user_pid = Uuid::parse_str(JOHN_DOE).unwrap();
+
+    let mut conn = ctx.db.acquire().await.unwrap();
+    let lot = AnimalGroup::create(
+        &mut conn,
+        org_pid,
+        user_pid,
+        &NewGroup {
+            name: "Sale lot 12".into(),
+            kind: "static".into(),
+            filter: None,
+            tag_ids: Some(vec!["AC001".into(), "AC002".into()]),
+            notes: None,
+        },
+    )
+    .await
+    .unwrap();
+
+    let entry =
+        |category: &'static str, amount, tag_id: Option<&'static str>, group| NewLedgerEntry {
+            category: category.into(),
+            amount,
+            entry_date: "2024-06-21".parse().ok(),
+            description: None,
+            tag_id: tag_id.map(Into::into),
+            group,
+        };
+
+    for params in [
+        entry("feed", 300_000, None, Some(lot.pid)),
+        entry("vet", 45_000, Some("AC001"), None),
+        entry("labour", 800_000, None, None),
+        entry("feed", 60_000, Some("AC003"), None),
+    ] {
+        LedgerEntry::create(&mut conn, org_pid, user_pid, &params)
+            .await
+            .unwrap();
+    }
+
+    let mut invalid = vec![];
+    for params in [
+        entry("feed", 1_000, Some("AC001"), Some(lot.pid)),
+        entry("feed", 1_000, None, Some(Uuid::nil())),
+    ] {
+        let created = LedgerEntry::create(&mut conn, org_pid, user_pid, &params).await;
+        invalid.push(created.map(|entry| entry.amount));
+    }
+    drop(conn);
+
+    let entries = LedgerEntry::find_all(
+        &ctx.db,
+        org_pid,
+        &LedgerQuery {
+            group: Some(lot.pid),
+            ..LedgerQuery::default()
+        },
+    )
+    .await
+    .unwrap()
+    .into_iter()
+    .map(|entry| (entry.category, entry.amount, entry.group_name))
+    .collect::<Vec<_>>();
+
+    let june = ProfitQuery {
+        from: "2024-06-01".parse().ok(),
+        to: "2024-06-30".parse().ok(),
+    };
+    let profit = ProfitAndLoss::for_group(&ctx.db, org_pid, lot.pid, june)
+        .await
+        .unwrap();
+    let unknown = ProfitAndLoss::for_group(&ctx.db, org_pid, Uuid::nil(), june).await;
+
+    assert_eq!(profit.group_pid, Some(lot.pid));
+    assert_debug_snapshot!((
+        entries,
+        invalid,
+        profit.expenses,
+        profit.categories,
+        unknown.map_err(|error| error.to_string()).map(|_| ()),
+    ));
+}
@@ -0,0 +1,333 @@
+use insta::{Settings, assert_debug_snapshot};
+use polaris::models::{
+    LiveSummary,
+    animals::AnimalQuery,
+    dto::{
+        GroupMembers, GroupMove, GroupWeighing, GroupWeight, MoveAnimals, NewGroup, NewLocation,
+    },
+    groups::AnimalGroup,
+    locations::Location,
+};
+use serial_test::serial;
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::{ACME, JOHN_DOE, boot_test, seed_data};
+
+macro_rules! configure_insta {
+    ($(expr:expr),*) => {
+        let mut settings = Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_path("snapshots/groups");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+fn group<'a>(
+    name: &'a str,
+    kind: &'a str,
+    filter: Option<AnimalQuery>,
+    tag_ids: Option<&[&'a str]>,
+) -> NewGroup<'a> {
+    NewGroup {
+        name: name.into(),
+        kind: kind.into(),
+        filter,
+        tag_ids: tag_ids.map(|tag_ids| tag_ids.iter().map(|tag_id| (*tag_id).into()).collect()),
+        notes: None,
+    }
+}
+
+fn members(tag_ids: &[&'static str]) -> GroupMembers<'static> {
+    GroupMembers {
+        tag_ids: tag_ids.iter().map(|tag_id| (*tag_id).into()).collect(),
+    }
+}
+
+async fn tags(conn: &mut PgConnection, pid: Uuid) -> Vec<String> {
+    AnimalGroup::find_members(conn, Uuid::parse_str(ACME).unwrap(), pid)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|member| member.tag_id)
+        .collect()
+}
+
+#[tokio::test]
+#[serial]
+async fn static_groups_keep_their_history() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    let org_pid = Uuid::parse_str(ACME).unwrap();
+    let user_pid = Uuid::parse_str(JOHN_DOE).unwrap();
+
+    let mut conn = ctx.db.acquire().await.unwrap();
+    let lot = AnimalGroup::create(
+        &mut conn,
+        org_pid,
+        user_pid,
+        &group("Sale lot 12", "static", None, Some(&["AC001", "ac002"])),
+    )
+    .await
+    .unwrap();
+
+    let mut invalid = vec![];
+    for params in [
+        group("Sale lot 12", "static", None, None),
+        group("Filtered", "static", Some(AnimalQuery::default()), None),
+        group("Unfiltered", "dynamic", None, None),
+        group("Everything", "dynamic", Some(AnimalQuery::default()), None),
+        group("Unknown", "static", None, Some(&["XX999"])),
+        group("Mixed", "mob", None, None),
+    ] {
+        let created = AnimalGroup::create(&mut conn, org_pid, user_pid, &params).await;
+        invalid.push(created.map(|group| group.name));
+    }
+
+    let added = AnimalGroup::add_members(
+        &mut conn,
+        org_pid,
+        user_pid,
+        lot.pid,
+        &members(&["AC001", "AC003"]),
+    )
+    .await
+    .unwrap()
+    .into_iter()
+    .map(|member| member.tag_id)
+    .collect::<Vec<_>>();
+    let removed =
+        AnimalGroup::remove_members(&mut conn, org_pid, user_pid, lot.pid, &members(&["AC002"]))
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|member| member.tag_id)
+            .collect::<Vec<_>>();
+    let rejoined =
+        AnimalGroup::add_members(&mut conn, org_pid, user_pid, lot.pid, &members(&["AC002"]))
+            .await
+            .unwrap()
+            .len();
+    drop(conn);
+
+    let mut history = AnimalGroup::find_history(&ctx.db, org_pid, lot.pid)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|stay| {
+            (
+                stay.tag_id,
+                stay.left_at.is_some(),
+                stay.added_by_name,
+                stay.removed_by_name,
+            )
+        })
+        .collect::<Vec<_>>();
+    history.sort();
+
+    assert_debug_snapshot!((invalid, added, removed, rejoined, history));
+}
+
+#[tokio::test]
+#[serial]
+async fn dynamic_groups_follow_their_filter() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    let org_pid = Uuid::parse_str(ACME).unwrap();
+    let user_pid = Uuid::parse_str(JOHN_DOE).unwrap();
+
+    let mut conn = ctx.db.acquire().await.unwrap();
+    let pen = Location::create(
+        &mut conn,
+        org_pid,
+        user_pid,
+        &NewLocation {
+            name: "Calf shed".into(),
+            kind: "site".into(),
+            parent: None,
+            capacity: None,
+            notes: None,
+        },
+    )
+    .await
+    .unwrap();
+    Location::move_animals(
+        &mut conn,
+        org_pid,
+        user_pid,
+        &MoveAnimals {
+            tag_ids: vec!["AC004".into(), "AC007".into(), "AC012".into()],
+            to: Some(pen.pid),
+            moved_on: "2024-06-01".parse().ok(),
+            reason: None,
+        },
+    )
+    .await
+    .unwrap();
+
+    let calves = AnimalGroup::create(
+        &mut conn,
+        org_pid,
+        user_pid,
+        &group(
+            "Jersey calves",
+            "dynamic",
+            Some(AnimalQuery {
+                breed: Some("jersey".into()),
+                location: Some(pen.pid),
+                ..AnimalQuery::default()
+            }),
+            None,
+        ),
+    )
+    .await
+    .unwrap();
+    let at_first = tags(&mut conn, calves.pid).await;
+
+    let by_hand = AnimalGroup::add_members(
+        &mut conn,
+        org_pid,
+        user_pid,
+        calves.pid,
+        &members(&["AC001"]),
+    )
+    .await;
+
+    let outsider = AnimalGroup::weigh(
+        &mut conn,
+        org_pid,
+        user_pid,
+        calves.pid,
+        &GroupWeighing {
+            record_date: "2024-06-15".parse().unwrap(),
+            weights: vec![GroupWeight {
+                tag_id: "AC012".into(),
+                mass: 30_000,
+            }],
+            unit: None,
+            notes: None,
+        },
+    )
+    .await;
+    let weighed = AnimalGroup::weigh(
+        &mut conn,
+        org_pid,
+        user_pid,
+        calves.pid,
+        &GroupWeighing {
+            record_date: "2024-06-15".parse().unwrap(),
+            weights: vec![
+                GroupWeight {
+                    tag_id: "ac004".into(),
+                    mass: 25_000,
+                },
+                GroupWeight {
+                    tag_id: "AC007".into(),
+                    mass: 12_000,
+                },
+            ],
+            unit: None,
+            notes: Some("Monthly weighing".into()),
+        },
+    )
+    .await
+    .unwrap()
+    .len();
+
+    Location::move_animals(
+        &mut conn,
+        org_pid,
+        user_pid,
+        &MoveAnimals {
+            tag_ids: vec!["AC007".into()],
+            to: None,
+            moved_on: "2024-06-20".parse().ok(),
+            reason: Some("Weaned".into()),
+        },
+    )
+    .await
+    .unwrap();
+    let after_weaning = tags(&mut conn, calves.pid).await;
+
+    let moved = AnimalGroup::move_to(
+        &mut conn,
+        org_pid,
+        user_pid,
+        calves.pid,
+        &GroupMove {
+            to: None,
+            moved_on: "2024-07-01".parse().ok(),
+            reason: Some("Turned out".into()),
+        },
+    )
+    .await
+    .unwrap()
+    .into_iter()
+    .map(|movement| (movement.tag_id, movement.from_name))
+    .collect::<Vec<_>>();
+    let emptied = AnimalGroup::move_to(
+        &mut conn,
+        org_pid,
+        user_pid,
+        calves.pid,
+        &GroupMove {
+            to: Some(pen.pid),
+            moved_on: None,
+            reason: None,
+        },
+    )
+    .await;
+    drop(conn);
+
+    let jerseys = AnimalGroup::create(
+        &mut ctx.db.acquire().await.unwrap(),
+        org_pid,
+        user_pid,
+        &group(
+            "Jerseys",
+            "dynamic",
+            Some(AnimalQuery {
+                breed: Some("Jersey".into()),
+                ..AnimalQuery::default()
+            }),
+            None,
+        ),
+    )
+    .await
+    .unwrap();
+    let pids = jerseys.animal_pids(&ctx.db).await.unwrap();
+    let summary = LiveSummary::find_animals(&ctx.db, org_pid, &pids)
+        .await
+        .unwrap();
+
+    let history = AnimalGroup::find_history(&ctx.db, org_pid, calves.pid)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|stay| (stay.tag_id, stay.left_at.is_some(), stay.added_by_name))
+        .collect::<Vec<_>>();
+
+    assert_debug_snapshot!((
+        at_first,
+        by_hand,
+        outsider,
+        weighed,
+        after_weaning,
+        moved,
+        emptied,
+        (
+            summary.total,
+            summary.males,
+            summary.females,
+            summary.species,
+            summary.breeds
+        ),
+        history,
+    ));
+}
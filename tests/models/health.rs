@@ -171,7 +171,13 @@ async fn can_create_one() {
         severity: Cow::Borrowed("low"),
     };
 
-    let result = HealthRecord::create(&ctx.db, &params, org_pid, user_pid).await;
+    let result = HealthRecord::create(
+        &mut ctx.db.acquire().await.unwrap(),
+        &params,
+        org_pid,
+        user_pid,
+    )
+    .await;
 
     with_settings!({
         filters => {
@@ -214,7 +220,9 @@ async fn can_update_by_id() {
         severity: None,
     };
 
-    let result = HealthRecord::update_by_id(&ctx.db, 105, org_pid, &params).await;
+    let result =
+        HealthRecord::update_by_id(&mut ctx.db.acquire().await.unwrap(), 105, org_pid, &params)
+            .await;
 
     with_settings!({ filters => {
         crate::cleanup_date().to_vec()
@@ -233,8 +241,9 @@ async fn can_delete_by_id() {
     crate::seed_data(&ctx.db).await.unwrap();
 
     let org_pid = Uuid::parse_str("4a93f0a8-4a91-482d-92d8-f0b3b084c2e4").unwrap();
+    let user_pid = Uuid::parse_str("e761d8e3-fc3e-4a2e-a6c9-7c7a4f2130e8").unwrap();
 
-    let result = HealthRecord::delete_by_id(&ctx.db, org_pid, 104).await;
+    let result = HealthRecord::delete_by_id(&ctx.db, org_pid, 104, user_pid).await;
 
     assert_debug_snapshot!(result);
 }
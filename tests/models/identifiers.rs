@@ -0,0 +1,304 @@
+use insta::{Settings, assert_debug_snapshot};
+use polaris::models::{
+    ModelResult,
+    animals::Animal,
+    dto::{NewIdentifier, RemoveIdentifier, UpdateAnimal},
+    identifiers::{AnimalIdentifier, normalise, read_rfid},
+};
+use serial_test::serial;
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::{ACME, JOHN_DOE, boot_test, pid, seed_data};
+
+macro_rules! configure_insta {
+    ($(expr:expr),*) => {
+        let mut settings = Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_path("snapshots/identifiers");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+async fn find_animal(db: &mut PgConnection, tag_id: &str) -> Uuid {
+    pid(
+        &Animal::find_by_tag_id(&mut *db, Uuid::parse_str(ACME).unwrap(), tag_id)
+            .await
+            .unwrap(),
+    )
+}
+
+async fn attach(
+    db: &mut PgConnection,
+    animal: Uuid,
+    kind: &str,
+    value: &str,
+    reason: Option<&str>,
+) -> ModelResult<AnimalIdentifier> {
+    AnimalIdentifier::attach(
+        db,
+        Uuid::parse_str(ACME).unwrap(),
+        Uuid::parse_str(JOHN_DOE).unwrap(),
+        animal,
+        &NewIdentifier {
+            kind: kind.into(),
+            value: value.into(),
+            reason: reason.map(Into::into),
+        },
+    )
+    .await
+}
+
+async fn remove(db: &mut PgConnection, animal: Uuid, pid: Uuid) -> ModelResult<AnimalIdentifier> {
+    AnimalIdentifier::remove(
+        db,
+        Uuid::parse_str(ACME).unwrap(),
+        Uuid::parse_str(JOHN_DOE).unwrap(),
+        animal,
+        pid,
+        &RemoveIdentifier::default(),
+    )
+    .await
+}
+
+async fn history(
+    db: &mut PgConnection,
+    animal: Uuid,
+) -> Vec<(String, String, bool, Option<String>)> {
+    AnimalIdentifier::find_by_animal(db, Uuid::parse_str(ACME).unwrap(), animal)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|identifier| {
+            (
+                identifier.kind,
+                identifier.value,
+                identifier.removed_at.is_some(),
+                identifier.removal_reason,
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn identifiers_are_checked_by_kind() {
+    configure_insta!();
+
+    let read = [
+        "982000123456789",
+        "982 000123456789",
+        "FDX-B: 982-000123456789",
+        "3D6.00075BCD15",
+        "15CD5B0780F500803AE2",
+        "15CD5B0780F500803AE3",
+        "000000123456789",
+        "982999999999999",
+        "AC001",
+    ]
+    .map(|raw| (raw, read_rfid(raw)));
+
+    let normalised = [
+        ("rfid", "98200012345"),
+        ("brand", " lazy-s "),
+        ("brand", "✓"),
+        ("tattoo", "ab12c"),
+        ("tattoo", "AB 12"),
+        ("visual", " AC001 "),
+    ]
+    .map(|(kind, value)| (kind, value, normalise(kind, value)));
+
+    assert_debug_snapshot!((read, normalised));
+}
+
+#[tokio::test]
+#[serial]
+async fn retagged_animals_resolve_by_their_old_tag() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    let org_pid = Uuid::parse_str(ACME).unwrap();
+    let mut conn = ctx.db.acquire().await.unwrap();
+    let animal = find_animal(&mut conn, "AC001").await;
+
+    Animal::update_by_id(
+        &mut conn,
+        &UpdateAnimal {
+            tag_id: Some("AC101".into()),
+            ..Default::default()
+        },
+        org_pid,
+        animal,
+    )
+    .await
+    .unwrap();
+    let by_old_tag = Animal::find_by_tag_id(&mut *conn, org_pid, "AC001")
+        .await
+        .unwrap();
+    let by_new_tag = Animal::find_by_tag_id(&mut *conn, org_pid, "AC101")
+        .await
+        .unwrap();
+    let history = history(&mut conn, animal).await;
+    drop(conn);
+
+    assert_eq!(pid(&by_old_tag), animal);
+    assert_eq!(pid(&by_new_tag), animal);
+    assert_debug_snapshot!(history);
+}
+
+#[tokio::test]
+#[serial]
+async fn attaching_an_identifier_replaces_one_of_its_kind() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    let mut conn = ctx.db.acquire().await.unwrap();
+    let animal = find_animal(&mut conn, "AC001").await;
+
+    let first = attach(&mut conn, animal, "rfid", "982 000123456789", None)
+        .await
+        .unwrap();
+    attach(&mut conn, animal, "rfid", "982000123456790", Some("lost"))
+        .await
+        .unwrap();
+    attach(&mut conn, animal, "visual", "AC201", Some("damaged"))
+        .await
+        .unwrap();
+
+    let tag_id = serde_json::to_value(
+        Animal::find_by_id(&mut *conn, Uuid::parse_str(ACME).unwrap(), animal)
+            .await
+            .unwrap(),
+    )
+    .unwrap()["tagId"]
+        .clone();
+    let history = history(&mut conn, animal).await;
+    drop(conn);
+
+    assert_eq!(first.value, "982000123456789");
+    assert_debug_snapshot!((tag_id, history));
+}
+
+#[tokio::test]
+#[serial]
+async fn identifiers_are_unique_and_well_formed() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    let mut conn = ctx.db.acquire().await.unwrap();
+    let animal = find_animal(&mut conn, "AC001").await;
+    let other = find_animal(&mut conn, "AC002").await;
+
+    attach(&mut conn, animal, "rfid", "982000123456790", None)
+        .await
+        .unwrap();
+    attach(&mut conn, animal, "visual", "AC201", None)
+        .await
+        .unwrap();
+
+    let mut invalid = vec![];
+    for (animal_pid, kind, value) in [
+        (other, "rfid", "982000123456790"),
+        (other, "visual", "AC201"),
+        (other, "ear", "AC201"),
+        (animal, "rfid", "982000"),
+    ] {
+        let attached = attach(&mut conn, animal_pid, kind, value, None).await;
+        invalid.push(attached.map(|identifier| identifier.value));
+    }
+    drop(conn);
+
+    assert_debug_snapshot!(invalid);
+}
+
+#[tokio::test]
+#[serial]
+async fn identifiers_can_be_removed_once() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    let mut conn = ctx.db.acquire().await.unwrap();
+    let animal = find_animal(&mut conn, "AC001").await;
+
+    let tattoo = attach(&mut conn, animal, "tattoo", "ab12c", None)
+        .await
+        .unwrap();
+    let visual =
+        AnimalIdentifier::find_by_animal(&mut *conn, Uuid::parse_str(ACME).unwrap(), animal)
+            .await
+            .unwrap()
+            .into_iter()
+            .find(|identifier| identifier.kind == "visual")
+            .unwrap();
+
+    let mut removed = vec![];
+    for pid in [visual.pid, tattoo.pid, tattoo.pid] {
+        let identifier = remove(&mut conn, animal, pid).await;
+        removed.push(identifier.map(|identifier| (identifier.value, identifier.removal_reason)));
+    }
+    drop(conn);
+
+    assert_debug_snapshot!(removed);
+}
+
+#[tokio::test]
+#[serial]
+async fn scans_resolve_current_and_former_identifiers() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    let org_pid = Uuid::parse_str(ACME).unwrap();
+    let mut conn = ctx.db.acquire().await.unwrap();
+    let animal = find_animal(&mut conn, "AC001").await;
+
+    attach(&mut conn, animal, "rfid", "982000123456789", None)
+        .await
+        .unwrap();
+    attach(&mut conn, animal, "rfid", "982000123456790", Some("lost"))
+        .await
+        .unwrap();
+    attach(&mut conn, animal, "visual", "AC201", Some("damaged"))
+        .await
+        .unwrap();
+    let tattoo = attach(&mut conn, animal, "tattoo", "ab12c", None)
+        .await
+        .unwrap();
+    remove(&mut conn, animal, tattoo.pid).await.unwrap();
+
+    let mut scans = vec![];
+    for raw in [
+        "AC001",
+        "AC201",
+        "ab12c",
+        "982 000123456789",
+        "3D6.00075BCD16",
+        "15CD5B0780F500803AE2",
+        "15CD5B0780F500803AE3",
+        "AC999",
+    ] {
+        let found = AnimalIdentifier::scan(&mut conn, org_pid, raw).await;
+        scans.push((
+            raw,
+            found.map(|found| {
+                (
+                    pid(&found.animal) == animal,
+                    found.identifier.kind,
+                    found.identifier.value,
+                    found.identifier.removed_at.is_some(),
+                )
+            }),
+        ));
+    }
+    drop(conn);
+
+    assert_debug_snapshot!(scans);
+}
@@ -0,0 +1,93 @@
+use insta::{Settings, assert_debug_snapshot};
+use polaris::{
+    models::{
+        identities::{OidcLogin, UserIdentity},
+        users::User,
+    },
+    oidc::IdTokenClaims,
+    views::user::UserResponse,
+};
+use serial_test::serial;
+
+use crate::{boot_test, seed_data};
+
+macro_rules! configure_insta {
+    ($(expr:expr),*) => {
+        let mut settings = Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_suffix("identities");
+        settings.set_snapshot_path("snapshots/identities");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+fn claims(subject: &str, email: &str) -> IdTokenClaims {
+    IdTokenClaims {
+        iss: "http://127.0.0.1:5181".into(),
+        sub: subject.into(),
+        email: Some(email.into()),
+        email_verified: Some(true),
+        given_name: None,
+        family_name: None,
+        name: Some("Mary Ann Jones".into()),
+        nonce: None,
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn known_account_keeps_its_user() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    let config = ctx.config.auth().oidc().unwrap();
+    let mut conn = ctx.db.acquire().await.unwrap();
+
+    let first = UserIdentity::sign_in(&mut conn, config, &claims("mary", "mary@acme.com"))
+        .await
+        .unwrap();
+
+    // The provider's email changed, the subject did not.
+    let again = UserIdentity::sign_in(&mut conn, config, &claims("mary", "mary.jones@acme.com"))
+        .await
+        .unwrap();
+
+    let identity = UserIdentity::find(&mut *conn, "http://127.0.0.1:5181", "mary")
+        .await
+        .unwrap()
+        .unwrap();
+    let renamed = User::find_by_email(&mut *conn, "mary.jones@acme.com")
+        .await
+        .unwrap();
+
+    let profile = serde_json::to_value(UserResponse::new(&first)).unwrap();
+
+    assert_debug_snapshot!((
+        first.pid() == again.pid(),
+        (first.email(), first.role()),
+        (profile["firstName"].clone(), profile["lastName"].clone()),
+        identity.email,
+        renamed.is_some(),
+    ));
+}
+
+#[tokio::test]
+#[serial]
+async fn login_state_can_only_be_used_once() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+
+    let login = OidcLogin::create(&ctx.db).await.unwrap();
+
+    let taken = OidcLogin::take(&ctx.db, &login.state).await.unwrap();
+    let replayed = OidcLogin::take(&ctx.db, &login.state).await.unwrap();
+
+    assert_debug_snapshot!((
+        taken.map(|taken| taken.nonce == login.nonce),
+        replayed.is_some(),
+        login.code_challenge().len(),
+    ));
+}
@@ -0,0 +1,212 @@
+use std::borrow::Cow;
+
+use insta::{Settings, assert_debug_snapshot, with_settings};
+use polaris::models::{
+    dto::{AcceptInvitation, InviteUser},
+    invitations::{Invitation, InvitationQuery, InvitationStatus},
+};
+use rstest::rstest;
+use serial_test::serial;
+use uuid::Uuid;
+
+use crate::{boot_test, cleanup_date, cleanup_int, cleanup_uuid, seed_data};
+
+macro_rules! configure_insta {
+    ($(expr:expr),*) => {
+        let mut settings = Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_suffix("invitations");
+        settings.set_snapshot_path("snapshots/invitations");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+const ACME: &str = "9d5b0c1e-6a48-4bce-b818-dc8c015fd8a0";
+const JOHN_DOE: &str = "bd6f7c26-d2c9-487e-b837-8f77be468033";
+
+fn invite(email: &'static str, role: &'static str) -> InviteUser<'static> {
+    InviteUser {
+        email: Cow::Borrowed(email),
+        first_name: Cow::Borrowed("Staff"),
+        last_name: Cow::Borrowed("Employee"),
+        role: Cow::Borrowed(role),
+    }
+}
+
+fn accept(token: String) -> AcceptInvitation<'static> {
+    AcceptInvitation {
+        token: Cow::Owned(token),
+        password: Cow::Borrowed("Password"),
+        confirm_password: Cow::Borrowed("Password"),
+    }
+}
+
+#[rstest]
+#[case("can_invite_staff", "staff")]
+#[case("can_invite_manager", "manager")]
+#[case("can_handle_invalid_role", "wizard")]
+#[tokio::test]
+#[serial]
+async fn can_invite(#[case] test_name: &str, #[case] role: &'static str) {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    let mut conn = ctx.db.acquire().await.unwrap();
+    let org_pid = Uuid::parse_str(ACME).unwrap();
+    let admin_pid = Uuid::parse_str(JOHN_DOE).unwrap();
+
+    let result = Invitation::create(
+        &mut conn,
+        org_pid,
+        admin_pid,
+        &invite("employee@mail.org", role),
+    )
+    .await
+    .map(|issued| issued.invitation);
+
+    with_settings!({
+        filters => {
+            let mut filters = cleanup_date().to_vec();
+            filters.extend(cleanup_uuid().to_vec());
+            filters.extend(cleanup_int().to_vec());
+            filters.push((r#"token_hash: "[^"]+""#, r#"token_hash: "HASH""#));
+            filters
+        }
+    }, {
+        assert_debug_snapshot!(test_name, result);
+    });
+}
+
+#[tokio::test]
+#[serial]
+async fn cannot_invite_twice_or_invite_members() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    let mut conn = ctx.db.acquire().await.unwrap();
+    let org_pid = Uuid::parse_str(ACME).unwrap();
+    let admin_pid = Uuid::parse_str(JOHN_DOE).unwrap();
+
+    Invitation::create(
+        &mut conn,
+        org_pid,
+        admin_pid,
+        &invite("employee@mail.org", "staff"),
+    )
+    .await
+    .unwrap();
+
+    let twice = Invitation::create(
+        &mut conn,
+        org_pid,
+        admin_pid,
+        &invite("employee@mail.org", "staff"),
+    )
+    .await
+    .map(|_| ());
+    let member = Invitation::create(
+        &mut conn,
+        org_pid,
+        admin_pid,
+        &invite("john.doe@acme.com", "staff"),
+    )
+    .await
+    .map(|_| ());
+
+    assert_debug_snapshot!((twice, member));
+}
+
+#[tokio::test]
+#[serial]
+async fn can_accept_once() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    let mut conn = ctx.db.acquire().await.unwrap();
+    let org_pid = Uuid::parse_str(ACME).unwrap();
+    let admin_pid = Uuid::parse_str(JOHN_DOE).unwrap();
+
+    let issued = Invitation::create(
+        &mut conn,
+        org_pid,
+        admin_pid,
+        &invite("employee@mail.org", "staff"),
+    )
+    .await
+    .unwrap();
+
+    let user = Invitation::accept(&mut conn, &accept(issued.token.clone()))
+        .await
+        .unwrap();
+    let again = Invitation::accept(&mut conn, &accept(issued.token))
+        .await
+        .map(|_| ());
+    let accepted = Invitation::find_all(
+        &mut *conn,
+        org_pid,
+        &InvitationQuery {
+            status: Some(InvitationStatus::Accepted),
+        },
+    )
+    .await
+    .unwrap();
+
+    assert_debug_snapshot!((
+        user.email(),
+        user.role(),
+        user.password_change_required(),
+        user.validate_password("Password").unwrap(),
+        again,
+        accepted.len(),
+    ));
+}
+
+#[tokio::test]
+#[serial]
+async fn resend_and_revoke_invalidate_tokens() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    let mut conn = ctx.db.acquire().await.unwrap();
+    let org_pid = Uuid::parse_str(ACME).unwrap();
+    let admin_pid = Uuid::parse_str(JOHN_DOE).unwrap();
+
+    let issued = Invitation::create(
+        &mut conn,
+        org_pid,
+        admin_pid,
+        &invite("employee@mail.org", "staff"),
+    )
+    .await
+    .unwrap();
+    let resent = Invitation::resend(&mut conn, org_pid, issued.invitation.pid)
+        .await
+        .unwrap();
+
+    let old_token = Invitation::find_by_token(&mut *conn, &issued.token)
+        .await
+        .map(|_| ());
+    let new_token = Invitation::find_by_token(&mut *conn, &resent.token)
+        .await
+        .map(|invitation| invitation.status());
+
+    let revoked = Invitation::revoke(&mut conn, org_pid, issued.invitation.pid)
+        .await
+        .map(|invitation| invitation.status());
+    let after_revoke = Invitation::find_by_token(&mut *conn, &resent.token)
+        .await
+        .map(|_| ());
+    let revoke_again = Invitation::revoke(&mut conn, org_pid, issued.invitation.pid)
+        .await
+        .map(|_| ());
+
+    assert_debug_snapshot!((old_token, new_token, revoked, after_revoke, revoke_again));
+}
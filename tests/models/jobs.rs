@@ -0,0 +1,212 @@
+use std::borrow::Cow;
+
+use insta::{Settings, assert_debug_snapshot};
+use polaris::{
+    jobs::Scheduler,
+    models::{
+        dto::CreateJobSchedule,
+        jobs::{Job, JobKind, JobSchedule},
+        users::User,
+    },
+};
+use serial_test::serial;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{boot_test, seed_data};
+
+macro_rules! configure_insta {
+    ($(expr:expr),*) => {
+        let mut settings = Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_suffix("jobs");
+        settings.set_snapshot_path("snapshots/jobs");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+const ACME: &str = "9d5b0c1e-6a48-4bce-b818-dc8c015fd8a0";
+const CONTINENTAL: &str = "4a0f3af9-e56e-4e21-8f3a-f9e56efe215b";
+
+/// The queued jobs as `(kind, organisation, status)`, in the order they were
+/// queued.
+async fn queued(db: &PgPool) -> Vec<(String, Option<Uuid>, String)> {
+    sqlx::query_as(
+        "SELECT kind, organisation_pid, status FROM jobs ORDER BY kind, organisation_pid",
+    )
+    .fetch_all(db)
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+#[serial]
+async fn platform_schedules_queue_their_jobs_once() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    // Alerts and deliveries are due straight after migrating, the rest later.
+    let scheduler = Scheduler::new(&ctx);
+    let first = scheduler.queue_due().await.unwrap();
+    let after_first = queued(&ctx.db).await;
+
+    // Not due again yet.
+    let second = scheduler.queue_due().await.unwrap();
+
+    // Due again, but the last jobs have not run.
+    sqlx::query("UPDATE job_schedules SET next_run_at = NOW()")
+        .execute(&ctx.db)
+        .await
+        .unwrap();
+    let third = scheduler.queue_due().await.unwrap();
+    let after_third = queued(&ctx.db).await;
+
+    let schedules = sqlx::query_as::<_, (String, bool)>(
+        "SELECT kind, next_run_at > NOW() FROM job_schedules ORDER BY id",
+    )
+    .fetch_all(&ctx.db)
+    .await
+    .unwrap();
+
+    assert_debug_snapshot!((
+        (first, after_first),
+        second,
+        (third, after_third),
+        schedules,
+    ));
+}
+
+#[tokio::test]
+#[serial]
+async fn failed_jobs_are_retried_until_given_up() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    let job = Job::enqueue(&ctx.db, None, JobKind::Purge, 2)
+        .await
+        .unwrap();
+
+    let mut steps = Vec::new();
+
+    let claimed = Job::claim(&ctx.db, 60).await.unwrap().unwrap();
+    steps.push((claimed.status, claimed.attempts));
+
+    // Nobody else may take it while it runs.
+    let taken = Job::claim(&ctx.db, 60).await.unwrap();
+    steps.push(("claimed again".into(), i32::from(taken.is_some())));
+
+    let retried = Job::fail(&ctx.db, job.pid, "Disk full", 30).await.unwrap();
+    steps.push((retried.status, retried.attempts));
+
+    // The retry waits for its delay.
+    let early = Job::claim(&ctx.db, 60).await.unwrap();
+    steps.push(("claimed early".into(), i32::from(early.is_some())));
+
+    sqlx::query("UPDATE jobs SET run_at = NOW() WHERE pid = $1")
+        .bind(job.pid)
+        .execute(&ctx.db)
+        .await
+        .unwrap();
+    let claimed = Job::claim(&ctx.db, 60).await.unwrap().unwrap();
+    steps.push((claimed.status, claimed.attempts));
+
+    let given_up = Job::fail(&ctx.db, job.pid, "Disk still full", 30)
+        .await
+        .unwrap();
+    steps.push((given_up.status, given_up.attempts));
+
+    assert_debug_snapshot!((steps, given_up.last_error, given_up.finished_at.is_some(),));
+}
+
+#[tokio::test]
+#[serial]
+async fn workers_run_the_jobs_for_each_plan() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    for org in [ACME, CONTINENTAL] {
+        Job::enqueue(
+            &ctx.db,
+            Some(Uuid::parse_str(org).unwrap()),
+            JobKind::Summaries,
+            3,
+        )
+        .await
+        .unwrap();
+    }
+    Job::enqueue(&ctx.db, None, JobKind::Summaries, 1)
+        .await
+        .unwrap();
+    Job::enqueue(&ctx.db, None, JobKind::Purge, 3)
+        .await
+        .unwrap();
+
+    let scheduler = Scheduler::new(&ctx);
+    let mut ran = Vec::new();
+    while let Some(job) = scheduler.run_next().await.unwrap() {
+        ran.push((
+            job.kind,
+            job.status,
+            job.result.map(|result| result.0),
+            job.last_error,
+        ));
+    }
+
+    assert_debug_snapshot!(ran);
+}
+
+#[tokio::test]
+#[serial]
+async fn organisation_schedules_are_checked() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    let admin = User::find_organisation_admin(&ctx.db, Uuid::parse_str(ACME).unwrap())
+        .await
+        .unwrap();
+    let mut conn = ctx.db.acquire().await.unwrap();
+
+    let schedule = |kind: &'static str, cron: &'static str| CreateJobSchedule {
+        kind: Cow::Borrowed(kind),
+        cron: Cow::Borrowed(cron),
+        is_active: None,
+    };
+
+    let mut errors = Vec::new();
+    for dto in [
+        schedule("purge", "0 3 * * *"),
+        schedule("summaries", "0 3 * *"),
+        schedule("summaries", "0 25 * * *"),
+        schedule("backups", "0 3 * * *"),
+    ] {
+        errors.push(
+            JobSchedule::create(&mut conn, &admin, &dto)
+                .await
+                .map(|_| ())
+                .unwrap_err()
+                .to_string(),
+        );
+    }
+
+    let nightly = JobSchedule::create(&mut conn, &admin, &schedule("summaries", "30 1 * * *"))
+        .await
+        .unwrap();
+
+    assert_debug_snapshot!((
+        errors,
+        (
+            nightly.kind,
+            nightly.cron,
+            nightly.is_active,
+            nightly.next_run_at.format("%H:%M").to_string(),
+        ),
+    ));
+}
@@ -0,0 +1,259 @@
+use insta::{Settings, assert_debug_snapshot};
+use polaris::models::{
+    animals::{Animal, AnimalQuery},
+    dto::{MoveAnimals, MovementQuery, NewLocation},
+    locations::{Location, Movement, Occupancy},
+};
+use serial_test::serial;
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::{ACME, JOHN_DOE, boot_test, seed_data};
+
+macro_rules! configure_insta {
+    ($(expr:expr),*) => {
+        let mut settings = Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_path("snapshots/locations");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+async fn location(
+    conn: &mut PgConnection,
+    kind: &str,
+    name: &str,
+    parent: Option<Uuid>,
+    capacity: Option<i32>,
+) -> Location {
+    Location::create(
+        conn,
+        Uuid::parse_str(ACME).unwrap(),
+        Uuid::parse_str(JOHN_DOE).unwrap(),
+        &NewLocation {
+            name: name.into(),
+            kind: kind.into(),
+            parent,
+            capacity,
+            notes: None,
+        },
+    )
+    .await
+    .unwrap()
+}
+
+fn move_to(to: Option<Uuid>, tag_ids: &[&'static str], moved_on: &str) -> MoveAnimals<'static> {
+    MoveAnimals {
+        tag_ids: tag_ids.iter().map(|tag_id| (*tag_id).into()).collect(),
+        to,
+        moved_on: moved_on.parse().ok(),
+        reason: None,
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn locations_form_a_hierarchy() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    let org_pid = Uuid::parse_str(ACME).unwrap();
+    let user_pid = Uuid::parse_str(JOHN_DOE).unwrap();
+
+    let mut conn = ctx.db.acquire().await.unwrap();
+    let farm = location(&mut conn, "site", "Home farm", None, None).await;
+    let paddock = location(&mut conn, "paddock", "North paddock", Some(farm.pid), None).await;
+    location(&mut conn, "pen", "Calf pen", Some(paddock.pid), Some(4)).await;
+
+    let mut invalid = vec![];
+    for (kind, name, parent) in [
+        ("paddock", "Loose paddock", None),
+        ("paddock", "Inner paddock", Some(paddock.pid)),
+        ("site", "Inner site", Some(farm.pid)),
+        ("pen", "North paddock", Some(Uuid::nil())),
+        ("paddock", "north paddock", Some(farm.pid)),
+    ] {
+        let created = Location::create(
+            &mut conn,
+            org_pid,
+            user_pid,
+            &NewLocation {
+                name: name.into(),
+                kind: kind.into(),
+                parent,
+                capacity: None,
+                notes: None,
+            },
+        )
+        .await;
+        invalid.push(created.map(|location| location.path));
+    }
+
+    let with_paddocks = Location::delete_by_pid(&mut conn, org_pid, farm.pid).await;
+    drop(conn);
+
+    let locations = Location::find_all(&ctx.db, org_pid)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|location| (location.kind, location.path, location.capacity))
+        .collect::<Vec<_>>();
+
+    assert_debug_snapshot!((invalid, with_paddocks, locations));
+}
+
+#[tokio::test]
+#[serial]
+async fn moving_animals_respects_capacity() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    let org_pid = Uuid::parse_str(ACME).unwrap();
+    let user_pid = Uuid::parse_str(JOHN_DOE).unwrap();
+
+    let mut conn = ctx.db.acquire().await.unwrap();
+    let farm = location(&mut conn, "site", "Home farm", None, Some(5)).await;
+    let paddock = location(&mut conn, "paddock", "North paddock", Some(farm.pid), None).await;
+    let pen = location(&mut conn, "pen", "Calf pen", Some(paddock.pid), Some(2)).await;
+
+    let into_paddock = Location::move_animals(
+        &mut conn,
+        org_pid,
+        user_pid,
+        &move_to(
+            Some(paddock.pid),
+            &["AC001", "AC002", "AC003"],
+            "2024-06-01",
+        ),
+    )
+    .await
+    .unwrap();
+
+    let overfull_pen = Location::move_animals(
+        &mut conn,
+        org_pid,
+        user_pid,
+        &move_to(Some(pen.pid), &["AC001", "AC002", "AC003"], "2024-06-02"),
+    )
+    .await;
+    let overfull_farm = Location::move_animals(
+        &mut conn,
+        org_pid,
+        user_pid,
+        &move_to(Some(farm.pid), &["AC004", "AC005", "AC006"], "2024-06-02"),
+    )
+    .await;
+
+    let into_pen = Location::move_animals(
+        &mut conn,
+        org_pid,
+        user_pid,
+        &move_to(Some(pen.pid), &["ac001", "AC002"], "2024-06-03"),
+    )
+    .await
+    .unwrap();
+
+    let already_there = Location::move_animals(
+        &mut conn,
+        org_pid,
+        user_pid,
+        &move_to(Some(pen.pid), &["AC001"], "2024-06-04"),
+    )
+    .await;
+    let back_in_time = Location::move_animals(
+        &mut conn,
+        org_pid,
+        user_pid,
+        &move_to(None, &["AC001"], "2024-05-01"),
+    )
+    .await;
+    let unknown = Location::move_animals(
+        &mut conn,
+        org_pid,
+        user_pid,
+        &move_to(None, &["AC001", "XX999"], "2024-06-04"),
+    )
+    .await;
+    let occupied = Location::delete_by_pid(&mut conn, org_pid, pen.pid).await;
+    drop(conn);
+
+    let occupancy = Occupancy::find_all(&ctx.db, org_pid)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|entry| {
+            (
+                entry.path,
+                entry.animals,
+                entry.total,
+                entry.free,
+                entry.over_capacity,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let on_the_farm = Animal::find_all(
+        &ctx.db,
+        org_pid,
+        &AnimalQuery {
+            specie: None,
+            breed: None,
+            purchase_date: None,
+            female_parent: None,
+            male_parent: None,
+            location: Some(farm.pid),
+        },
+    )
+    .await
+    .unwrap()
+    .into_iter()
+    .map(|animal| {
+        let animal = serde_json::to_value(animal).unwrap();
+        (animal["tagId"].clone(), animal["locationName"].clone())
+    })
+    .collect::<Vec<_>>();
+
+    let movements = Movement::find_all(
+        &ctx.db,
+        org_pid,
+        &MovementQuery {
+            animal: Some(into_pen[0].animal_pid),
+            ..MovementQuery::default()
+        },
+    )
+    .await
+    .unwrap()
+    .into_iter()
+    .map(|movement| {
+        (
+            movement.moved_on,
+            movement.from_name,
+            movement.to_name,
+            movement.moved_by_name,
+        )
+    })
+    .collect::<Vec<_>>();
+
+    assert_debug_snapshot!((
+        into_paddock
+            .iter()
+            .map(|movement| (&movement.tag_id, &movement.to_name))
+            .collect::<Vec<_>>(),
+        into_paddock
+            .iter()
+            .all(|movement| movement.batch_pid == into_paddock[0].batch_pid),
+        overfull_pen,
+        overfull_farm,
+        already_there,
+        back_in_time,
+        unknown,
+        occupied,
+        occupancy,
+        on_the_farm,
+        movements,
+    ));
+}
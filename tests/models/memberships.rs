@@ -0,0 +1,147 @@
+use std::borrow::Cow;
+
+use insta::{Settings, assert_debug_snapshot, with_settings};
+use polaris::models::{
+    dto::{AcceptInvitation, InviteUser},
+    invitations::Invitation,
+    memberships::Membership,
+    users::{User, UserQuery},
+};
+use serial_test::serial;
+use uuid::Uuid;
+
+use crate::{boot_test, cleanup_date, cleanup_uuid, invite_and_accept, seed_data};
+
+macro_rules! configure_insta {
+    ($(expr:expr),*) => {
+        let mut settings = Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_suffix("memberships");
+        settings.set_snapshot_path("snapshots/memberships");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+const ACME: &str = "9d5b0c1e-6a48-4bce-b818-dc8c015fd8a0";
+const CONTINENTAL: &str = "4a0f3af9-e56e-4e21-8f3a-f9e56efe215b";
+const JOHN_DOE: &str = "bd6f7c26-d2c9-487e-b837-8f77be468033";
+
+#[tokio::test]
+#[serial]
+async fn existing_user_can_join_another_organisation() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    let continental = Uuid::parse_str(CONTINENTAL).unwrap();
+    let john = Uuid::parse_str(JOHN_DOE).unwrap();
+
+    let member = invite_and_accept(&ctx.db, continental, "john.doe@acme.com", "staff").await;
+
+    let memberships = Membership::find_by_user(&ctx.db, john).await.unwrap();
+    let home = User::find_member(&ctx.db, john, None)
+        .await
+        .unwrap()
+        .unwrap();
+    let continental_users = User::read_all(&ctx.db, continental, &UserQuery::default())
+        .await
+        .unwrap()
+        .iter()
+        .map(|user| (user.email().to_string(), user.role().to_string()))
+        .collect::<Vec<_>>();
+
+    with_settings!({
+        filters => {
+            let mut filters = cleanup_date().to_vec();
+            filters.extend(cleanup_uuid().to_vec());
+            filters
+        }
+    }, {
+        assert_debug_snapshot!((
+            member.pid() == john,
+            (member.organisation_pid(), member.role().to_string()),
+            memberships,
+            (home.organisation_pid(), home.role().to_string()),
+            continental_users,
+        ));
+    });
+}
+
+#[tokio::test]
+#[serial]
+async fn existing_user_must_confirm_password() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    let continental = Uuid::parse_str(CONTINENTAL).unwrap();
+    let mut conn = ctx.db.acquire().await.unwrap();
+    let admin = User::find_organisation_admin(&mut *conn, continental)
+        .await
+        .unwrap();
+
+    let issued = Invitation::create(
+        &mut conn,
+        continental,
+        admin.pid(),
+        &InviteUser {
+            email: Cow::Borrowed("john.doe@acme.com"),
+            first_name: Cow::Borrowed("John"),
+            last_name: Cow::Borrowed("Doe"),
+            role: Cow::Borrowed("staff"),
+        },
+    )
+    .await
+    .unwrap();
+
+    let result = Invitation::accept(
+        &mut conn,
+        &AcceptInvitation {
+            token: Cow::Owned(issued.token),
+            password: Cow::Borrowed("NotMyPassword"),
+            confirm_password: Cow::Borrowed("NotMyPassword"),
+        },
+    )
+    .await
+    .map(|user| user.pid());
+
+    drop(conn);
+
+    let memberships = Membership::find_by_user(&ctx.db, Uuid::parse_str(JOHN_DOE).unwrap())
+        .await
+        .unwrap()
+        .len();
+
+    assert_debug_snapshot!((result, memberships));
+}
+
+#[tokio::test]
+#[serial]
+async fn removing_member_keeps_their_account() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    let acme = Uuid::parse_str(ACME).unwrap();
+    let continental = Uuid::parse_str(CONTINENTAL).unwrap();
+    let john = Uuid::parse_str(JOHN_DOE).unwrap();
+
+    invite_and_accept(&ctx.db, continental, "john.doe@acme.com", "staff").await;
+
+    let removed = User::delete_by_pid(&ctx.db, continental, john)
+        .await
+        .map(|result| result.rows_affected());
+    let in_continental = User::find_member(&ctx.db, john, Some(continental))
+        .await
+        .unwrap()
+        .is_some();
+    let in_acme = User::find_member(&ctx.db, john, Some(acme))
+        .await
+        .unwrap()
+        .is_some();
+
+    assert_debug_snapshot!((removed, in_continental, in_acme));
+}
@@ -1,11 +1,28 @@
+mod alerts;
 mod animals;
+mod api_keys;
+mod audit;
 mod breeds;
+mod feed;
+mod finance;
+mod groups;
 mod health;
+mod identifiers;
+mod identities;
+mod invitations;
+mod jobs;
 mod livestock;
+mod locations;
+mod memberships;
 mod orgs;
+mod pdf;
 mod production;
 mod roles;
 mod seed;
 mod summaries;
+mod tasks;
+mod tenant;
+mod trash;
 mod users;
+mod webhooks;
 mod weight;
@@ -0,0 +1,184 @@
+use insta::{Settings, assert_debug_snapshot, assert_snapshot, with_settings};
+use polaris::{
+    models::{BreedSummary, BreedSummaryQuery, SpecieSummary, dto::NewGroup, groups::AnimalGroup},
+    pdf::{PdfReport, PeriodQuery},
+};
+use serial_test::serial;
+use uuid::Uuid;
+
+use crate::{boot_test, seed_data};
+
+macro_rules! configure_insta {
+    ($(expr:expr),*) => {
+        let mut settings = Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_path("snapshots/pdf");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+const ACME: &str = "9d5b0c1e-6a48-4bce-b818-dc8c015fd8a0";
+const JOHN_DOE: &str = "bd6f7c26-d2c9-487e-b837-8f77be468033";
+
+/// Drops the day a report is printed on.
+fn printed() -> Vec<(&'static str, &'static str)> {
+    vec![(r"muted: Printed .* for", "muted: Printed [TODAY] for")]
+}
+
+#[tokio::test]
+#[serial]
+async fn can_lay_out_the_herd() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    let org_pid = Uuid::parse_str(ACME).unwrap();
+    let layout = PdfReport::herd(&ctx.db, org_pid)
+        .await
+        .unwrap()
+        .layout()
+        .unwrap();
+
+    with_settings!({ filters => printed() }, {
+        assert_snapshot!(layout);
+    });
+}
+
+#[tokio::test]
+#[serial]
+async fn can_lay_out_a_group() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    let org_pid = Uuid::parse_str(ACME).unwrap();
+    let group = AnimalGroup::create(
+        &mut ctx.db.acquire().await.unwrap(),
+        org_pid,
+        Uuid::parse_str(JOHN_DOE).unwrap(),
+        &NewGroup {
+            name: "Sale lot 12".into(),
+            kind: "static".into(),
+            filter: None,
+            tag_ids: Some(vec!["AC003".into(), "AC006".into()]),
+            notes: None,
+        },
+    )
+    .await
+    .unwrap();
+
+    let layout = PdfReport::group(&ctx.db, org_pid, group.pid)
+        .await
+        .unwrap()
+        .layout()
+        .unwrap();
+
+    with_settings!({ filters => {
+        let mut filters = printed();
+        filters.push((r"Average age \(months\) \| [\d.]+", "Average age (months) | [AGE]"));
+        filters
+    }}, {
+        assert_snapshot!(layout);
+    });
+}
+
+#[tokio::test]
+#[serial]
+async fn can_lay_out_the_latest_summaries() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    let org_pid = Uuid::parse_str(ACME).unwrap();
+    SpecieSummary::generate(&ctx.db, org_pid, "cattle")
+        .await
+        .unwrap();
+    BreedSummary::generate(&ctx.db, org_pid, "jersey", "cattle")
+        .await
+        .unwrap();
+    let species = PdfReport::species(&ctx.db, org_pid, None)
+        .await
+        .unwrap()
+        .layout()
+        .unwrap();
+    let breeds = PdfReport::breeds(
+        &ctx.db,
+        org_pid,
+        &BreedSummaryQuery {
+            specie: Some("cattle".to_string()),
+            breed: None,
+        },
+    )
+    .await
+    .unwrap()
+    .layout()
+    .unwrap();
+
+    let mut filters = printed();
+    filters.push((r"As summarised on .*", "As summarised on [DATE]"));
+    filters.push((r"Average age \| [\d.]+", "Average age | [AGE]"));
+    filters.push((r"\| [\d.]+ \| ([\d.]+ USD)", "| [AGE] | $1"));
+
+    with_settings!({ filters => filters }, {
+        assert_snapshot!(format!("{species}\n---\n{breeds}"));
+    });
+}
+
+#[tokio::test]
+#[serial]
+async fn can_lay_out_records() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    let org_pid = Uuid::parse_str(ACME).unwrap();
+    let animal_pid = Uuid::parse_str("b2bd6270-8bec-42ce-99ff-d0eb1a076221").unwrap();
+
+    let health = PdfReport::health(&ctx.db, org_pid, animal_pid)
+        .await
+        .unwrap()
+        .layout()
+        .unwrap();
+    let production = PdfReport::production(
+        &ctx.db,
+        org_pid,
+        PeriodQuery {
+            from: "2024-06-01".parse().unwrap(),
+            to: "2024-06-30".parse().unwrap(),
+        },
+    )
+    .await
+    .unwrap()
+    .layout()
+    .unwrap();
+
+    with_settings!({ filters => printed() }, {
+        assert_snapshot!(format!("{health}\n---\n{production}"));
+    });
+}
+
+#[tokio::test]
+#[serial]
+async fn cannot_lay_out_a_backwards_period() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+
+    let result = PdfReport::production(
+        &ctx.db,
+        Uuid::parse_str(ACME).unwrap(),
+        PeriodQuery {
+            from: "2024-06-30".parse().unwrap(),
+            to: "2024-06-01".parse().unwrap(),
+        },
+    )
+    .await
+    .map(|report| report.layout().is_ok())
+    .map_err(|error| error.to_string());
+
+    assert_debug_snapshot!(result);
+}
@@ -134,8 +134,9 @@ async fn can_delete_by_id() {
     seed_data(&ctx.db).await.unwrap();
 
     let org_pid = Uuid::parse_str("9d5b0c1e-6a48-4bce-b818-dc8c015fd8a0").unwrap();
+    let user_pid = Uuid::parse_str("bd6f7c26-d2c9-487e-b837-8f77be468033").unwrap();
 
-    let result = ProductionRecord::delete_by_id(&ctx.db, 101, org_pid).await;
+    let result = ProductionRecord::delete_by_id(&ctx.db, 101, org_pid, user_pid).await;
 
     assert_debug_snapshot!(result);
 }
@@ -176,7 +177,9 @@ async fn can_update_by_id() {
         unit: Some(Cow::Borrowed("kg")),
         record_date: None,
     };
-    let result = ProductionRecord::update_by_id(&ctx.db, 101, org_pid, &params).await;
+    let result =
+        ProductionRecord::update_by_id(&mut ctx.db.acquire().await.unwrap(), 101, org_pid, &params)
+            .await;
 
     with_settings!({ filters => {
         crate::cleanup_date().to_vec()
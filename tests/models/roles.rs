@@ -1,7 +1,28 @@
+use std::borrow::Cow;
+
 use insta::{Settings, assert_debug_snapshot, with_settings};
-use polaris::models::roles::Role;
+use polaris::models::{
+    dto::{CreateRole, UpdateRole},
+    roles::{Action, Resource, Role},
+};
 use rstest::rstest;
 use serial_test::serial;
+use uuid::Uuid;
+
+const ACME: &str = "9d5b0c1e-6a48-4bce-b818-dc8c015fd8a0";
+const GLOBEX: &str = "4a93f0a8-4a91-482d-92d8-f0b3b084c2e4";
+
+fn vet_role() -> CreateRole<'static> {
+    CreateRole {
+        name: Cow::Borrowed("vet"),
+        description: Some(Cow::Borrowed("Can only edit health records")),
+        permissions: vec![
+            "animals:read".to_string(),
+            "health_records:read".to_string(),
+            "health_records:write".to_string(),
+        ],
+    }
+}
 
 macro_rules! configure_insta {
     ($(expr:expr),*) => {
@@ -76,3 +97,155 @@ async fn can_check_permissions(#[case] name: &str, #[case] id: i32) {
 
     assert_debug_snapshot!(name, (can_read, can_write, can_delete, can_manage_users));
 }
+
+#[rstest]
+#[case("scopes_animals_read", Resource::Animals, Action::Read)]
+#[case("scopes_animals_write", Resource::Animals, Action::Write)]
+#[case("scopes_health_records_write", Resource::HealthRecords, Action::Write)]
+#[case(
+    "scopes_health_records_delete",
+    Resource::HealthRecords,
+    Action::Delete
+)]
+#[case("scopes_users_manage", Resource::Users, Action::Manage)]
+#[tokio::test]
+#[serial]
+async fn can_check_scoped_permissions(
+    #[case] name: &str,
+    #[case] resource: Resource,
+    #[case] action: Action,
+) {
+    configure_insta!();
+
+    let ctx = crate::boot_test().await.unwrap();
+    crate::seed_data(&ctx.db).await.unwrap();
+
+    let org_pid = Uuid::parse_str(ACME).unwrap();
+    let vet = Role::create(&ctx.db, org_pid, &vet_role()).await.unwrap();
+    let staff = Role::find_by_name(&ctx.db, "staff").await.unwrap();
+
+    assert_debug_snapshot!(
+        name,
+        (vet.allows(resource, action), staff.allows(resource, action))
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn can_create_custom_role() {
+    configure_insta!();
+
+    let ctx = crate::boot_test().await.unwrap();
+    crate::seed_data(&ctx.db).await.unwrap();
+
+    let org_pid = Uuid::parse_str(ACME).unwrap();
+    let result = Role::create(&ctx.db, org_pid, &vet_role()).await;
+
+    with_settings!({
+        filters => {
+             let mut combined_filters = crate::cleanup_date().to_vec();
+             combined_filters.extend(crate::cleanup_int().iter().copied());
+             combined_filters
+        }
+    }, {
+        assert_debug_snapshot!(result);
+    })
+}
+
+#[rstest]
+#[case("cannot_create_role_system_name", "manager", vec!["animals:read"])]
+#[case("cannot_create_role_invalid_scope", "vet", vec!["animals:fly"])]
+#[case("cannot_create_role_invalid_name", "v e t", vec!["animals:read"])]
+#[tokio::test]
+#[serial]
+async fn cannot_create_invalid_role(
+    #[case] name: &str,
+    #[case] role: &str,
+    #[case] permissions: Vec<&str>,
+) {
+    configure_insta!();
+
+    let ctx = crate::boot_test().await.unwrap();
+    crate::seed_data(&ctx.db).await.unwrap();
+
+    let params = CreateRole {
+        name: Cow::Owned(role.to_string()),
+        description: None,
+        permissions: permissions.into_iter().map(ToString::to_string).collect(),
+    };
+
+    let org_pid = Uuid::parse_str(ACME).unwrap();
+    let result = Role::create(&ctx.db, org_pid, &params).await;
+
+    assert_debug_snapshot!(name, result);
+}
+
+#[tokio::test]
+#[serial]
+async fn can_find_roles_by_organisation() {
+    configure_insta!();
+
+    let ctx = crate::boot_test().await.unwrap();
+    crate::seed_data(&ctx.db).await.unwrap();
+
+    let acme = Uuid::parse_str(ACME).unwrap();
+    let globex = Uuid::parse_str(GLOBEX).unwrap();
+    Role::create(&ctx.db, acme, &vet_role()).await.unwrap();
+
+    let acme_roles = Role::find_by_organisation(&ctx.db, acme).await.unwrap();
+    let globex_roles = Role::find_by_organisation(&ctx.db, globex).await.unwrap();
+
+    assert_debug_snapshot!((
+        acme_roles.iter().map(Role::name).collect::<Vec<_>>(),
+        globex_roles.iter().map(Role::name).collect::<Vec<_>>(),
+        Role::find_for_organisation(&ctx.db, globex, "vet")
+            .await
+            .is_err(),
+    ));
+}
+
+#[tokio::test]
+#[serial]
+async fn can_update_custom_role() {
+    configure_insta!();
+
+    let ctx = crate::boot_test().await.unwrap();
+    crate::seed_data(&ctx.db).await.unwrap();
+
+    let org_pid = Uuid::parse_str(ACME).unwrap();
+    let vet = Role::create(&ctx.db, org_pid, &vet_role()).await.unwrap();
+
+    let params = UpdateRole {
+        description: None,
+        permissions: Some(vec!["health_records:manage".to_string()]),
+    };
+    let vet = vet.update(&ctx.db, &params).await.unwrap();
+
+    assert_debug_snapshot!((
+        vet.description(),
+        vet.allows(Resource::HealthRecords, Action::Delete),
+        vet.allows(Resource::Animals, Action::Read),
+    ));
+}
+
+#[tokio::test]
+#[serial]
+async fn cannot_delete_assigned_role() {
+    configure_insta!();
+
+    let ctx = crate::boot_test().await.unwrap();
+    crate::seed_data(&ctx.db).await.unwrap();
+
+    let org_pid = Uuid::parse_str(ACME).unwrap();
+    let vet = Role::create(&ctx.db, org_pid, &vet_role()).await.unwrap();
+
+    sqlx::query("UPDATE users SET role = 'vet' WHERE email = 'john.doe@acme.com'")
+        .execute(&ctx.db)
+        .await
+        .unwrap();
+
+    let assigned = Role::delete_by_id(&ctx.db, org_pid, vet.id()).await;
+    let system = Role::delete_by_id(&ctx.db, org_pid, 1).await;
+
+    assert_debug_snapshot!((assigned, system));
+}
@@ -0,0 +1,194 @@
+use insta::{Settings, assert_debug_snapshot};
+use polaris::models::{LiveSummary, SpecieSummaryExtract, livestock::SummaryData};
+use serial_test::serial;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{boot_test, seed_data};
+
+macro_rules! configure_insta {
+    ($(expr:expr),*) => {
+        let mut settings = Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_suffix("live");
+        settings.set_snapshot_path("snapshots/summaries");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+const ACME: &str = "9d5b0c1e-6a48-4bce-b818-dc8c015fd8a0";
+
+/// Checks the live summaries against summaries worked out from the animals
+/// afresh, and returns the livestock's totals.
+async fn check(db: &PgPool, org_pid: Uuid, step: &str) -> (String, i64, i64, i64, i64, i64) {
+    let live = LiveSummary::find_livestock(db, org_pid).await.unwrap();
+    let fresh = SummaryData::find_by_organisation(db, org_pid)
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(
+        (
+            live.total,
+            live.males,
+            live.females,
+            live.unknown_gender,
+            live.active,
+            live.transferred,
+            live.sold,
+            live.deceased,
+            live.species,
+            live.breeds,
+            live.total_purchase_value,
+        ),
+        (
+            fresh.total,
+            fresh.males,
+            fresh.females,
+            fresh.unkown_gender,
+            fresh.active,
+            fresh.transferred,
+            fresh.sold,
+            fresh.deceased,
+            Some(fresh.species),
+            Some(fresh.breeds),
+            fresh.total_purchased_value,
+        ),
+        "livestock after {step}"
+    );
+
+    let cattle = LiveSummary::find_species(db, org_pid, Some("cattle"))
+        .await
+        .unwrap();
+    let fresh = SpecieSummaryExtract::get_from_animals(db, org_pid, "cattle")
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(
+        (
+            cattle[0].total,
+            cattle[0].unknown_gender,
+            cattle[0].average_weight_male,
+            cattle[0].average_weight_female,
+        ),
+        (
+            fresh.total,
+            fresh.unkown_gender,
+            fresh.average_weight_male.map(|weight| weight.round_dp(2)),
+            fresh.average_weight_female.map(|weight| weight.round_dp(2)),
+        ),
+        "cattle after {step}"
+    );
+
+    (
+        step.to_string(),
+        live.total,
+        live.active,
+        live.sold,
+        live.unknown_gender,
+        live.species.unwrap_or_default(),
+    )
+}
+
+#[tokio::test]
+#[serial]
+async fn live_summaries_follow_every_change() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    let org_pid = Uuid::parse_str(ACME).unwrap();
+    let mut steps = vec![check(&ctx.db, org_pid, "seeding").await];
+
+    let changes = [
+        (
+            "adding a bull",
+            "INSERT INTO animals (
+                organisation_pid, tag_id, specie_id, breed_id, gender, status,
+                purchase_price, current_weight, weight_at_birth, date_of_birth
+            )
+            SELECT organisation_pid, 'LIVE-1', specie_id, breed_id, 'male', 'active',
+                1250.50, 610, 38, DATE '2022-03-01'
+            FROM animals WHERE organisation_pid = $1 LIMIT 1",
+        ),
+        (
+            "selling it",
+            "UPDATE animals SET status = 'sold' WHERE tag_id = 'LIVE-1' AND organisation_pid = $1",
+        ),
+        (
+            "forgetting its gender and weight",
+            "UPDATE animals SET gender = 'unknown', current_weight = NULL
+            WHERE tag_id = 'LIVE-1' AND organisation_pid = $1",
+        ),
+        (
+            "noting something",
+            "UPDATE animals SET notes = 'Sold at market' WHERE tag_id = 'LIVE-1' AND organisation_pid = $1",
+        ),
+        (
+            "recording it as a goat",
+            "UPDATE animals SET specie_id = 3, breed_id = (SELECT id FROM breeds WHERE specie_id = 3 LIMIT 1)
+            WHERE tag_id = 'LIVE-1' AND organisation_pid = $1",
+        ),
+        (
+            "trashing it",
+            "UPDATE animals SET deleted_at = NOW() WHERE tag_id = 'LIVE-1' AND organisation_pid = $1",
+        ),
+        (
+            "restoring it",
+            "UPDATE animals SET deleted_at = NULL WHERE tag_id = 'LIVE-1' AND organisation_pid = $1",
+        ),
+        (
+            "deleting it",
+            "DELETE FROM animals WHERE tag_id = 'LIVE-1' AND organisation_pid = $1",
+        ),
+    ];
+
+    for (step, sql) in changes {
+        sqlx::query(sql)
+            .bind(org_pid)
+            .execute(&ctx.db)
+            .await
+            .unwrap();
+        steps.push(check(&ctx.db, org_pid, step).await);
+    }
+
+    assert_debug_snapshot!(steps);
+}
+
+#[tokio::test]
+#[serial]
+async fn live_summaries_are_grouped_by_specie_and_breed() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    let org_pid = Uuid::parse_str("4a93f0a8-4a91-482d-92d8-f0b3b084c2e4").unwrap();
+
+    let species = LiveSummary::find_species(&ctx.db, org_pid, None)
+        .await
+        .unwrap();
+    let breeds = LiveSummary::find_breeds(
+        &ctx.db,
+        org_pid,
+        &polaris::models::BreedSummaryQuery {
+            specie: Some("goats".to_string()),
+            breed: None,
+        },
+    )
+    .await
+    .unwrap();
+
+    assert_debug_snapshot!((
+        species
+            .iter()
+            .map(|summary| (summary.specie_name.clone(), summary.total, summary.breeds))
+            .collect::<Vec<_>>(),
+        breeds
+            .iter()
+            .map(|summary| (summary.breed_name.clone(), summary.total, summary.active))
+            .collect::<Vec<_>>(),
+    ));
+}
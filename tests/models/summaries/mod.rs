@@ -1,2 +1,3 @@
 mod breeds;
+mod live;
 mod species;
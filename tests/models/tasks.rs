@@ -0,0 +1,556 @@
+use std::borrow::Cow;
+
+use chrono::{NaiveDate, TimeDelta};
+use insta::{Settings, assert_debug_snapshot};
+use polaris::models::{
+    alerts::{Alert, AlertRule},
+    dto::{
+        AlertQuery, CompleteTask, CreateAlertRule, GroupTreatment, GroupWeighing, GroupWeight,
+        NewGroup, NewRecurringTask, NewTask, NewVaccinationSchedule, ProductionReading,
+        TaskProduction, TaskQuery, UpdateTask,
+    },
+    groups::AnimalGroup,
+    health::HealthRecord,
+    settings::OrganisationSettings,
+    tasks::{OverdueTasks, RecurringTask, Task, VaccinationSchedule},
+    users::User,
+};
+use serial_test::serial;
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::{ACME, JOHN_DOE, boot_test, seed_data};
+
+macro_rules! configure_insta {
+    ($(expr:expr),*) => {
+        let mut settings = Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_path("snapshots/tasks");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+fn task<'a>(title: &'a str, due_on: NaiveDate, tag_ids: &[&'a str]) -> NewTask<'a> {
+    NewTask {
+        title: title.into(),
+        description: None,
+        due_on,
+        assignee: None,
+        priority: None,
+        tag_ids: Some(tag_ids.iter().map(|tag_id| (*tag_id).into()).collect()),
+        group: None,
+        record_kind: None,
+    }
+}
+
+fn treatment(record_date: NaiveDate) -> GroupTreatment<'static> {
+    GroupTreatment {
+        record_date: record_date.to_string().into(),
+        condition: "vaccination".into(),
+        description: "Annual booster".into(),
+        treatment: "Vaccine".into(),
+        severity: "low".into(),
+        status: "recovered".into(),
+        medicine: Some("Brucella".into()),
+        dosage: None,
+        cost: None,
+        performed_by: None,
+        prognosis: None,
+        notes: None,
+    }
+}
+
+fn completion() -> CompleteTask<'static> {
+    CompleteTask {
+        notes: None,
+        treatment: None,
+        weighing: None,
+        production: None,
+    }
+}
+
+async fn today(conn: &mut PgConnection) -> NaiveDate {
+    OrganisationSettings::find(conn, Uuid::parse_str(ACME).unwrap())
+        .await
+        .unwrap()
+        .today()
+}
+
+#[tokio::test]
+#[serial]
+async fn recurring_and_vaccination_tasks_come_due() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    let org_pid = Uuid::parse_str(ACME).unwrap();
+    let user_pid = Uuid::parse_str(JOHN_DOE).unwrap();
+
+    let mut conn = ctx.db.acquire().await.unwrap();
+    let today = today(&mut conn).await;
+    let days = |date: NaiveDate| (date - today).num_days();
+
+    // Daily since ten days ago: only today's occurrence is raised.
+    let milking = RecurringTask::create(
+        &mut conn,
+        org_pid,
+        user_pid,
+        &NewRecurringTask {
+            title: "Milk recording".into(),
+            description: None,
+            cron: "0 6 * * *".into(),
+            starts_on: Some(today - TimeDelta::days(10)),
+            assignee: Some(user_pid),
+            priority: None,
+            group: None,
+            record_kind: Some("production".into()),
+        },
+    )
+    .await
+    .unwrap();
+    let never = RecurringTask::create(
+        &mut conn,
+        org_pid,
+        user_pid,
+        &NewRecurringTask {
+            title: "Never".into(),
+            description: None,
+            cron: "0 6 31 2 *".into(),
+            starts_on: None,
+            assignee: None,
+            priority: None,
+            group: None,
+            record_kind: None,
+        },
+    )
+    .await
+    .map(|recurring| recurring.title);
+
+    let herd = AnimalGroup::create(
+        &mut conn,
+        org_pid,
+        user_pid,
+        &NewGroup {
+            name: "Milking herd".into(),
+            kind: "static".into(),
+            filter: None,
+            tag_ids: Some(vec!["AC001".into(), "AC002".into(), "AC004".into()]),
+            notes: None,
+        },
+    )
+    .await
+    .unwrap();
+
+    // AC001 was vaccinated almost a year ago, AC004 recently and AC002 never.
+    for (tag_id, days_ago) in [("AC001", 360), ("AC004", 10)] {
+        HealthRecord::create(
+            &mut conn,
+            &treatment(today - TimeDelta::days(days_ago)).for_animal(tag_id),
+            org_pid,
+            user_pid,
+        )
+        .await
+        .unwrap();
+    }
+
+    let schedule = VaccinationSchedule::create(
+        &mut conn,
+        org_pid,
+        user_pid,
+        &NewVaccinationSchedule {
+            vaccine: "brucella".into(),
+            group: herd.pid,
+            interval_days: 365,
+            lead_days: None,
+            assignee: Some(user_pid),
+            priority: Some("high".into()),
+        },
+    )
+    .await
+    .unwrap();
+    let duplicate = VaccinationSchedule::create(
+        &mut conn,
+        org_pid,
+        user_pid,
+        &NewVaccinationSchedule {
+            vaccine: "Brucella".into(),
+            group: herd.pid,
+            interval_days: 180,
+            lead_days: None,
+            assignee: None,
+            priority: None,
+        },
+    )
+    .await
+    .map(|schedule| schedule.vaccine);
+
+    let first = Task::generate(&mut conn, org_pid).await.unwrap();
+    let again = Task::generate(&mut conn, org_pid).await.unwrap();
+
+    let milking = RecurringTask::find_by_pid(&mut *conn, org_pid, milking.pid)
+        .await
+        .unwrap();
+
+    let vaccinations = Task::find_all(
+        &mut *conn,
+        org_pid,
+        &TaskQuery {
+            source: Some("vaccination".into()),
+            ..TaskQuery::default()
+        },
+    )
+    .await
+    .unwrap();
+    let raised = vaccinations
+        .iter()
+        .map(|task| {
+            (
+                task.title.clone(),
+                days(task.due_on),
+                task.tag_ids.clone(),
+                task.priority.clone(),
+                task.source_pid == Some(schedule.pid),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    // Vaccinating AC002 settles it until next year; cancelling AC001's task
+    // skips its turn.
+    let vaccinated = Task::complete(
+        &mut conn,
+        org_pid,
+        user_pid,
+        vaccinations[0].pid,
+        &CompleteTask {
+            treatment: Some(treatment(today)),
+            ..completion()
+        },
+    )
+    .await
+    .unwrap();
+    Task::update(
+        &mut conn,
+        org_pid,
+        vaccinations[1].pid,
+        &UpdateTask {
+            title: None,
+            description: None,
+            due_on: None,
+            assignee: None,
+            priority: None,
+            status: Some("cancelled".into()),
+            notes: Some("Sold before it was due".into()),
+        },
+    )
+    .await
+    .unwrap();
+    let settled = Task::generate(&mut conn, org_pid).await.unwrap();
+    drop(conn);
+
+    assert_debug_snapshot!((
+        never,
+        duplicate,
+        (first, again, settled),
+        (milking.is_active, days(milking.next_due_on)),
+        raised,
+        (
+            vaccinated.task.status,
+            vaccinated
+                .health_records
+                .iter()
+                .map(|record| serde_json::to_value(record).unwrap()["medicine"].clone())
+                .collect::<Vec<_>>(),
+        ),
+    ));
+}
+
+#[tokio::test]
+#[serial]
+async fn completing_tasks_records_the_work() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    let org_pid = Uuid::parse_str(ACME).unwrap();
+    let user_pid = Uuid::parse_str(JOHN_DOE).unwrap();
+
+    let mut conn = ctx.db.acquire().await.unwrap();
+    let today = today(&mut conn).await;
+
+    let mut invalid = vec![];
+    for params in [
+        NewTask {
+            assignee: Some(Uuid::nil()),
+            ..task("Stranger", today, &[])
+        },
+        task("Unknown", today, &["XX999"]),
+        NewTask {
+            priority: Some("urgent".into()),
+            ..task("Urgent", today, &[])
+        },
+    ] {
+        let created = Task::create(&mut conn, org_pid, user_pid, &params).await;
+        invalid.push(created.map(|task| task.title));
+    }
+
+    let weighing = Task::create(
+        &mut conn,
+        org_pid,
+        user_pid,
+        &NewTask {
+            record_kind: Some("weight".into()),
+            ..task("Weigh the calves", today, &["ac004", "AC007"])
+        },
+    )
+    .await
+    .unwrap();
+
+    let weights = |tag_ids: &[&'static str]| GroupWeighing {
+        record_date: today,
+        weights: tag_ids
+            .iter()
+            .map(|tag_id| GroupWeight {
+                tag_id: (*tag_id).into(),
+                mass: 20_000,
+            })
+            .collect(),
+        unit: None,
+        notes: None,
+    };
+
+    let mut refused = vec![];
+    for params in [
+        CompleteTask {
+            treatment: Some(treatment(today)),
+            ..completion()
+        },
+        CompleteTask {
+            treatment: Some(treatment(today)),
+            weighing: Some(weights(&["AC004"])),
+            ..completion()
+        },
+        CompleteTask {
+            weighing: Some(weights(&["AC004", "AC012"])),
+            ..completion()
+        },
+    ] {
+        let completed = Task::complete(&mut conn, org_pid, user_pid, weighing.pid, &params).await;
+        refused.push(completed.map(|completion| completion.task.status));
+    }
+
+    let weighed = Task::complete(
+        &mut conn,
+        org_pid,
+        user_pid,
+        weighing.pid,
+        &CompleteTask {
+            notes: Some(Cow::Borrowed("AC007 was out")),
+            weighing: Some(weights(&["AC004"])),
+            ..completion()
+        },
+    )
+    .await
+    .unwrap();
+    let twice = Task::complete(&mut conn, org_pid, user_pid, weighing.pid, &completion())
+        .await
+        .map(|completion| completion.task.status);
+
+    // A group task covers the group's members.
+    let heifers = AnimalGroup::create(
+        &mut conn,
+        org_pid,
+        user_pid,
+        &NewGroup {
+            name: "Heifers".into(),
+            kind: "static".into(),
+            filter: None,
+            tag_ids: Some(vec!["AC012".into(), "AC013".into()]),
+            notes: None,
+        },
+    )
+    .await
+    .unwrap();
+    let milking = Task::create(
+        &mut conn,
+        org_pid,
+        user_pid,
+        &NewTask {
+            group: Some(heifers.pid),
+            ..task("Milk recording", today, &["AC001"])
+        },
+    )
+    .await
+    .unwrap();
+    let milked = Task::complete(
+        &mut conn,
+        org_pid,
+        user_pid,
+        milking.pid,
+        &CompleteTask {
+            production: Some(TaskProduction {
+                production_type: "milk".into(),
+                unit: "litres".into(),
+                quality: None,
+                record_date: None,
+                readings: vec![
+                    ProductionReading {
+                        tag_id: "ac001".into(),
+                        quantity: 1_250,
+                    },
+                    ProductionReading {
+                        tag_id: "AC013".into(),
+                        quantity: 980,
+                    },
+                ],
+                notes: None,
+            }),
+            ..completion()
+        },
+    )
+    .await
+    .unwrap();
+
+    // An alert raises a task, and completing it resolves the alert.
+    let admin = User::find_organisation_admin(&mut *conn, org_pid)
+        .await
+        .unwrap();
+    drop(conn);
+    let rule = AlertRule::create(
+        &ctx.db,
+        &admin,
+        &CreateAlertRule {
+            name: Cow::Borrowed("Vaccinations"),
+            kind: Cow::Borrowed("vaccination_overdue"),
+            threshold: None,
+            window_days: Some(30),
+            severity: Some(Cow::Borrowed("high")),
+            channels: None,
+        },
+    )
+    .await
+    .unwrap();
+    let mut conn = ctx.db.acquire().await.unwrap();
+    let alerts = rule.evaluate(&mut conn).await.unwrap();
+    let raised = Task::find_all(
+        &mut *conn,
+        org_pid,
+        &TaskQuery {
+            source: Some("alert".into()),
+            animal: Some(alerts[0].animal_pid),
+            ..TaskQuery::default()
+        },
+    )
+    .await
+    .unwrap()
+    .remove(0);
+    let looked_into = Task::complete(&mut conn, org_pid, admin.pid(), raised.pid, &completion())
+        .await
+        .unwrap();
+    drop(conn);
+    let alert = Alert::find_all(
+        &ctx.db,
+        org_pid,
+        &AlertQuery {
+            animal: Some(alerts[0].animal_pid),
+            ..AlertQuery::default()
+        },
+    )
+    .await
+    .unwrap()
+    .remove(0);
+
+    assert_debug_snapshot!((
+        invalid,
+        refused,
+        (
+            weighed.task.status,
+            weighed.task.notes,
+            weighed.task.completed_by == Some(user_pid),
+            weighed.weight_records.len(),
+        ),
+        twice,
+        (
+            milking.tag_ids,
+            milking.group_name,
+            milked
+                .production_records
+                .iter()
+                .map(|record| serde_json::to_value(record).unwrap()["quantity"].clone())
+                .collect::<Vec<_>>(),
+        ),
+        (
+            raised.priority,
+            raised.tag_ids.len(),
+            raised.title == alerts[0].message,
+            looked_into.task.status,
+            alert.status,
+        ),
+    ));
+}
+
+#[tokio::test]
+#[serial]
+async fn staff_see_their_day_and_managers_what_is_overdue() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    let org_pid = Uuid::parse_str(ACME).unwrap();
+    let user_pid = Uuid::parse_str(JOHN_DOE).unwrap();
+
+    let mut conn = ctx.db.acquire().await.unwrap();
+    let today = today(&mut conn).await;
+
+    for (title, days, assignee, priority) in [
+        ("Fix the fence", -3, Some(user_pid), "low"),
+        ("Deworm AC001", -1, Some(user_pid), "high"),
+        ("Clean the troughs", 0, Some(user_pid), "medium"),
+        ("Order feed", 0, Some(user_pid), "high"),
+        ("Shear the flock", 2, Some(user_pid), "medium"),
+        ("Check the borehole", -2, None, "medium"),
+    ] {
+        Task::create(
+            &mut conn,
+            org_pid,
+            user_pid,
+            &NewTask {
+                assignee,
+                priority: Some(priority.into()),
+                ..task(title, today + TimeDelta::days(days), &[])
+            },
+        )
+        .await
+        .unwrap();
+    }
+    drop(conn);
+
+    let day = Task::find_today(&ctx.db, org_pid, user_pid, today)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|task| task.title)
+        .collect::<Vec<_>>();
+
+    let overdue = OverdueTasks::find_all(&ctx.db, org_pid, today)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|assignee| {
+            (
+                assignee.assignee_name,
+                assignee.overdue,
+                (today - assignee.oldest_due_on).num_days(),
+                assignee
+                    .tasks
+                    .into_iter()
+                    .map(|task| task.title)
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_debug_snapshot!((day, overdue));
+}
@@ -0,0 +1,141 @@
+use insta::{Settings, assert_debug_snapshot};
+use polaris::models::{
+    animals::{Animal, AnimalQuery},
+    orgs::{Organisation, OrganisationQuery},
+    tenant::TenantTransaction,
+    users::{User, UserQuery},
+};
+use serial_test::serial;
+use uuid::Uuid;
+
+macro_rules! configure_insta {
+    ($(expr:expr),*) => {
+        let mut settings = Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_path("snapshots/tenant");
+        settings.set_snapshot_suffix("tenant");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+const ACME: &str = "9d5b0c1e-6a48-4bce-b818-dc8c015fd8a0";
+const GLOBEX: &str = "4a93f0a8-4a91-482d-92d8-f0b3b084c2e4";
+const JOHN_DOE: &str = "bd6f7c26-d2c9-487e-b837-8f77be468033";
+const GLOBEX_ANIMAL: &str = "60b6f9a8-f238-4f91-91c7-1040ed638286";
+
+async fn acme_transaction(db: &sqlx::PgPool) -> TenantTransaction {
+    TenantTransaction::begin(
+        db,
+        Uuid::parse_str(ACME).unwrap(),
+        Uuid::parse_str(JOHN_DOE).unwrap(),
+    )
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+#[serial]
+async fn cannot_read_other_tenant_animals() {
+    configure_insta!();
+
+    let ctx = crate::boot_test().await.unwrap();
+    crate::seed_data(&ctx.db).await.unwrap();
+
+    let globex = Uuid::parse_str(GLOBEX).unwrap();
+    let mut txn = acme_transaction(&ctx.db).await;
+
+    // Queries scoped to the wrong organisation, as a buggy handler would.
+    let by_filter = Animal::find_all(
+        &mut *txn,
+        globex,
+        &AnimalQuery {
+            specie: None,
+            breed: None,
+            purchase_date: None,
+            female_parent: None,
+            male_parent: None,
+            location: None,
+        },
+    )
+    .await;
+    let by_id =
+        Animal::find_by_id(&mut *txn, globex, Uuid::parse_str(GLOBEX_ANIMAL).unwrap()).await;
+
+    // A query that forgets the organisation filter altogether.
+    let (unfiltered,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM animals")
+        .fetch_one(&mut *txn)
+        .await
+        .unwrap();
+
+    assert_debug_snapshot!((by_filter.map(|animals| animals.len()), by_id, unfiltered));
+}
+
+#[tokio::test]
+#[serial]
+async fn cannot_read_other_tenant_users_and_organisations() {
+    configure_insta!();
+
+    let ctx = crate::boot_test().await.unwrap();
+    crate::seed_data(&ctx.db).await.unwrap();
+
+    let globex = Uuid::parse_str(GLOBEX).unwrap();
+    let mut txn = acme_transaction(&ctx.db).await;
+
+    let users = User::read_all(&mut *txn, globex, &UserQuery::default()).await;
+    let organisations = Organisation::read_all(&mut *txn, &OrganisationQuery::default())
+        .await
+        .map(|orgs| orgs.len());
+
+    assert_debug_snapshot!((users.map(|users| users.len()), organisations));
+}
+
+#[tokio::test]
+#[serial]
+async fn cannot_write_other_tenant_rows() {
+    configure_insta!();
+
+    let ctx = crate::boot_test().await.unwrap();
+    crate::seed_data(&ctx.db).await.unwrap();
+
+    let mut txn = acme_transaction(&ctx.db).await;
+
+    let updated = sqlx::query("UPDATE animals SET name = 'Stolen' WHERE pid = $1")
+        .bind(Uuid::parse_str(GLOBEX_ANIMAL).unwrap())
+        .execute(&mut *txn)
+        .await
+        .map(|result| result.rows_affected());
+
+    let moved = sqlx::query("UPDATE animals SET organisation_pid = $1")
+        .bind(Uuid::parse_str(GLOBEX).unwrap())
+        .execute(&mut *txn)
+        .await
+        .map_err(|e| e.to_string());
+
+    assert_debug_snapshot!((updated.unwrap(), moved));
+}
+
+#[tokio::test]
+#[serial]
+async fn settings_do_not_outlive_transaction() {
+    configure_insta!();
+
+    let ctx = crate::boot_test().await.unwrap();
+    crate::seed_data(&ctx.db).await.unwrap();
+
+    let mut txn = acme_transaction(&ctx.db).await;
+    let (inside,): (Option<Uuid>,) = sqlx::query_as("SELECT current_org_pid()")
+        .fetch_one(&mut *txn)
+        .await
+        .unwrap();
+    txn.commit().await.unwrap();
+
+    // Single connection so the reused session is the one the transaction ran on.
+    let mut conn = ctx.db.acquire().await.unwrap();
+    let (outside, role): (Option<Uuid>, String) =
+        sqlx::query_as("SELECT current_org_pid(), current_user::TEXT")
+            .fetch_one(&mut *conn)
+            .await
+            .unwrap();
+
+    assert_debug_snapshot!((inside.is_some(), outside, role == "polaris_tenant"));
+}
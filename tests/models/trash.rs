@@ -0,0 +1,131 @@
+use insta::{Settings, assert_debug_snapshot, with_settings};
+use polaris::models::{
+    animals::Animal,
+    health::HealthRecord,
+    trash::{TRASHABLE, Trash},
+    weight::{WeightQuery, WeightRecord},
+};
+use serial_test::serial;
+use uuid::Uuid;
+
+macro_rules! configure_insta {
+    ($(expr:expr),*) => {
+        let mut settings = Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_path("snapshots/trash");
+        settings.set_snapshot_suffix("trash");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+const ACME: &str = "9d5b0c1e-6a48-4bce-b818-dc8c015fd8a0";
+const JOHN_DOE: &str = "bd6f7c26-d2c9-487e-b837-8f77be468033";
+const ROSE: &str = "f6417c11-d817-4626-9e8d-c68a44002d4b";
+
+#[tokio::test]
+#[serial]
+async fn trashed_animal_is_hidden_with_its_records() {
+    configure_insta!();
+
+    let ctx = crate::boot_test().await.unwrap();
+    crate::seed_data(&ctx.db).await.unwrap();
+
+    let org_pid = Uuid::parse_str(ACME).unwrap();
+    let rose = Uuid::parse_str(ROSE).unwrap();
+
+    Animal::delete_by_id(&ctx.db, org_pid, rose, Uuid::parse_str(JOHN_DOE).unwrap())
+        .await
+        .unwrap();
+
+    let animal = Animal::find_by_id(&ctx.db, org_pid, rose).await;
+    let weights = WeightRecord::find_all(
+        &ctx.db,
+        org_pid,
+        &WeightQuery {
+            animal: Some(rose),
+            mass: None,
+        },
+    )
+    .await
+    .map(|records| records.len());
+    let trash = Trash::find_all(&ctx.db, org_pid, &TRASHABLE, 30).await;
+
+    with_settings!({
+        filters => crate::cleanup_date().to_vec()
+    }, {
+        assert_debug_snapshot!((animal, weights, trash));
+    });
+}
+
+#[tokio::test]
+#[serial]
+async fn can_restore_animal_and_records() {
+    configure_insta!();
+
+    let ctx = crate::boot_test().await.unwrap();
+    crate::seed_data(&ctx.db).await.unwrap();
+
+    let org_pid = Uuid::parse_str(ACME).unwrap();
+    let user_pid = Uuid::parse_str(JOHN_DOE).unwrap();
+    let rose = Uuid::parse_str(ROSE).unwrap();
+
+    WeightRecord::delete_by_id(&ctx.db, org_pid, 115, user_pid)
+        .await
+        .unwrap();
+    Animal::delete_by_id(&ctx.db, org_pid, rose, user_pid)
+        .await
+        .unwrap();
+
+    let mut conn = ctx.db.acquire().await.unwrap();
+    let before_animal = WeightRecord::restore_by_id(&mut conn, org_pid, 115)
+        .await
+        .map(|_| ());
+    let animal = Animal::restore_by_id(&mut *conn, org_pid, rose)
+        .await
+        .map(|_| ());
+    let record = WeightRecord::restore_by_id(&mut conn, org_pid, 115)
+        .await
+        .map(|_| ());
+    let again = HealthRecord::restore_by_id(&mut conn, org_pid, 101)
+        .await
+        .map(|_| ());
+
+    assert_debug_snapshot!((before_animal, animal, record, again));
+}
+
+#[tokio::test]
+#[serial]
+async fn purge_removes_expired_rows() {
+    configure_insta!();
+
+    let ctx = crate::boot_test().await.unwrap();
+    crate::seed_data(&ctx.db).await.unwrap();
+
+    let org_pid = Uuid::parse_str(ACME).unwrap();
+    let user_pid = Uuid::parse_str(JOHN_DOE).unwrap();
+
+    Animal::delete_by_id(&ctx.db, org_pid, Uuid::parse_str(ROSE).unwrap(), user_pid)
+        .await
+        .unwrap();
+    WeightRecord::delete_by_id(&ctx.db, org_pid, 101, user_pid)
+        .await
+        .unwrap();
+    sqlx::query("UPDATE animals SET deleted_at = NOW() - INTERVAL '31 days' WHERE pid = $1")
+        .bind(Uuid::parse_str(ROSE).unwrap())
+        .execute(&ctx.db)
+        .await
+        .unwrap();
+
+    let mut conn = ctx.db.acquire().await.unwrap();
+    let purged = Trash::purge(&mut conn, 30).await.unwrap();
+
+    // The weight record went with its animal.
+    let (remaining,): (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM weight_records WHERE animal_pid = $1")
+            .bind(Uuid::parse_str(ROSE).unwrap())
+            .fetch_one(&mut *conn)
+            .await
+            .unwrap();
+
+    assert_debug_snapshot!((purged, remaining));
+}
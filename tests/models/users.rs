@@ -4,7 +4,7 @@ use std::borrow::Cow;
 
 use insta::{Settings, assert_debug_snapshot, with_settings};
 use polaris::models::{
-    dto::{CreateNewUser, RegisterAdmin},
+    dto::{RegisterAdmin, UpdateUserRole},
     users::{User, UserQuery},
 };
 use rstest::rstest;
@@ -103,76 +103,159 @@ async fn can_find_by_email() {
 }
 
 #[rstest]
-#[case("can_find_all_no_query", UserQuery::new(None, None))]
+#[case(
+    "can_find_all_no_query",
+    "9d5b0c1e-6a48-4bce-b818-dc8c015fd8a0",
+    UserQuery::new(None, None)
+)]
 #[case(
     "can_find_all_role_query",
+    "9d5b0c1e-6a48-4bce-b818-dc8c015fd8a0",
     UserQuery::new(Some("admin".to_string()), None)
 )]
-#[case("can_find_all_active_query", UserQuery::new(None, Some(true)))]
+#[case(
+    "can_find_all_active_query",
+    "9d5b0c1e-6a48-4bce-b818-dc8c015fd8a0",
+    UserQuery::new(None, Some(true))
+)]
+#[case(
+    "can_find_all_inactive_query",
+    "4a93f0a8-4a91-482d-92d8-f0b3b084c2e4",
+    UserQuery::new(None, Some(false))
+)]
+#[case(
+    "can_find_all_active_other_org_query",
+    "4a93f0a8-4a91-482d-92d8-f0b3b084c2e4",
+    UserQuery::new(None, Some(true))
+)]
 #[tokio::test]
 #[serial]
-async fn can_find_all(#[case] test_name: &str, #[case] query: UserQuery) {
+async fn can_find_all(#[case] test_name: &str, #[case] org_pid: &str, #[case] query: UserQuery) {
     configure_insta!();
 
     let ctx = boot_test().await.unwrap();
     seed_data(&ctx.db).await.unwrap();
 
-    let result = User::read_all(&ctx.db, &query).await;
+    let org_pid = Uuid::parse_str(org_pid).unwrap();
+
+    let result = User::read_all(&ctx.db, org_pid, &query).await;
 
     assert_debug_snapshot!(test_name, result);
 }
 
+#[tokio::test]
+#[serial]
+async fn can_find_by_pid_in_organisation() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    // John Doe belongs to Acme Corp, not Globex Corporation.
+    let pid = Uuid::parse_str("bd6f7c26-d2c9-487e-b837-8f77be468033").unwrap();
+    let other_org = Uuid::parse_str("4a93f0a8-4a91-482d-92d8-f0b3b084c2e4").unwrap();
+
+    let result = User::read_by_pid(&ctx.db, other_org, pid).await;
+
+    assert_debug_snapshot!(result);
+}
+
+#[rstest]
+#[case("can_update_role_manager", "manager")]
+#[case("can_update_role_invalid", "wizard")]
+#[tokio::test]
+#[serial]
+async fn can_update_role(#[case] test_name: &str, #[case] role: &str) {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    let pid = Uuid::parse_str("bd6f7c26-d2c9-487e-b837-8f77be468033").unwrap();
+    let user = User::find_by_pid(&ctx.db, pid).await.unwrap().unwrap();
+
+    let result = user
+        .update_role(
+            &ctx.db,
+            &UpdateUserRole {
+                role: Cow::Borrowed(role),
+            },
+        )
+        .await
+        .map(|user| (user.pid(), user.role().to_string()));
+
+    assert_debug_snapshot!(test_name, result);
+}
+
+#[tokio::test]
+#[serial]
+async fn can_deactivate_and_reactivate() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    let pid = Uuid::parse_str("bd6f7c26-d2c9-487e-b837-8f77be468033").unwrap();
+    let user = User::find_by_pid(&ctx.db, pid).await.unwrap().unwrap();
+
+    let deactivated = user.set_active(&ctx.db, false).await.unwrap();
+    let reactivated = deactivated.set_active(&ctx.db, true).await.unwrap();
+
+    assert_debug_snapshot!((deactivated.is_active(), reactivated.is_active()));
+}
+
+#[tokio::test]
+#[serial]
+async fn can_require_password_reset() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    let pid = Uuid::parse_str("bd6f7c26-d2c9-487e-b837-8f77be468033").unwrap();
+    let user = User::find_by_pid(&ctx.db, pid).await.unwrap().unwrap();
+
+    let user = user.require_password_reset(&ctx.db).await.unwrap();
+
+    assert_debug_snapshot!(user.password_change_required());
+}
+
 #[rstest]
 #[case(
-    "can_create_staff_user",
-    CreateNewUser { email: Cow::Borrowed("staffemployee@mail.org"), first_name: Cow::Borrowed("Staff") , last_name: Cow::Borrowed("Employee") , role:  Cow::Borrowed("staff")  }
-)]
-#[case(
-    "can_create_manager_user",
-    CreateNewUser { email: Cow::Borrowed("manageremployee@mail.org"), first_name: Cow::Borrowed("Manager") , last_name: Cow::Borrowed("Employee") , role:  Cow::Borrowed("manager")  }
+    "can_delete_by_pid_with_records",
+    "bd6f7c26-d2c9-487e-b837-8f77be468033"
 )]
+#[case("can_delete_by_pid_not_found", "e761d8e3-fc3e-4a2e-a6c9-7c7a4f2130e8")]
 #[tokio::test]
 #[serial]
-async fn can_manage_users(#[case] test_name: &str, #[case] params: CreateNewUser<'static>) {
+async fn can_delete_by_pid(#[case] test_name: &str, #[case] pid: &str) {
     configure_insta!();
 
     let ctx = boot_test().await.unwrap();
     seed_data(&ctx.db).await.unwrap();
 
     let org_pid = Uuid::parse_str("9d5b0c1e-6a48-4bce-b818-dc8c015fd8a0").unwrap();
+    let pid = Uuid::parse_str(pid).unwrap();
 
-    let result = User::admin_create_user(&ctx.db, org_pid, &params).await;
+    let result = User::delete_by_pid(&ctx.db, org_pid, pid).await;
 
-    with_settings!({
-        filters => {
-            let mut filters = cleanup_date().to_vec();
-            filters.extend(cleanup_password().to_vec());
-            filters.extend(cleanup_uuid().to_vec());
-            filters.extend(cleanup_int().to_vec());
-            filters
-        }
-    }, {
-            assert_debug_snapshot!(test_name, result);
-        })
+    assert_debug_snapshot!(test_name, result);
 }
 
 #[tokio::test]
 #[serial]
-async fn can_handle_invalid_role() {
+async fn can_delete_new_user() {
     configure_insta!();
 
     let ctx = boot_test().await.unwrap();
     seed_data(&ctx.db).await.unwrap();
 
     let org_pid = Uuid::parse_str("9d5b0c1e-6a48-4bce-b818-dc8c015fd8a0").unwrap();
-    let params = CreateNewUser {
-        email: Cow::Borrowed("staffemployee@mail.org"),
-        first_name: Cow::Borrowed("Staff"),
-        last_name: Cow::Borrowed("Employee"),
-        role: Cow::Borrowed("wizard"),
-    };
+    let user = crate::invite_and_accept(&ctx.db, org_pid, "staffemployee@mail.org", "staff").await;
 
-    let result = User::admin_create_user(&ctx.db, org_pid, &params).await;
+    let result = User::delete_by_pid(&ctx.db, org_pid, user.pid())
+        .await
+        .map(|result| result.rows_affected());
 
     assert_debug_snapshot!(result);
 }
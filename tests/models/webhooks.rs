@@ -0,0 +1,175 @@
+use std::borrow::Cow;
+
+use insta::{Settings, assert_debug_snapshot};
+use polaris::models::{
+    dto::{CreateWebhook, UpdateWebhook},
+    events::{DomainEvent, EventType},
+    users::User,
+    webhooks::{Webhook, WebhookDelivery},
+};
+use serde_json::json;
+use serial_test::serial;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{boot_test, seed_data};
+
+macro_rules! configure_insta {
+    ($(expr:expr),*) => {
+        let mut settings = Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_suffix("webhooks");
+        settings.set_snapshot_path("snapshots/webhooks");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+const ACME: &str = "9d5b0c1e-6a48-4bce-b818-dc8c015fd8a0";
+const CONTINENTAL: &str = "4a0f3af9-e56e-4e21-8f3a-f9e56efe215b";
+
+async fn subscribe(db: &PgPool, org: &str, event_types: &[&str]) -> Webhook {
+    let mut conn = db.acquire().await.unwrap();
+    let admin = User::find_organisation_admin(&mut *conn, Uuid::parse_str(org).unwrap())
+        .await
+        .unwrap();
+
+    Webhook::create(
+        &mut *conn,
+        &admin,
+        &CreateWebhook {
+            url: Cow::Borrowed("https://partner.example.com/hooks"),
+            event_types: event_types.iter().map(ToString::to_string).collect(),
+        },
+    )
+    .await
+    .unwrap()
+}
+
+async fn deliveries(db: &PgPool, webhook: &Webhook) -> Vec<WebhookDelivery> {
+    let mut conn = db.acquire().await.unwrap();
+
+    WebhookDelivery::find_by_webhook(&mut conn, webhook.organisation_pid, webhook.pid)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+#[serial]
+async fn events_are_queued_for_subscribed_webhooks_only() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    let sales = subscribe(&ctx.db, ACME, &["animal.sold", "animal.sold"]).await;
+    let births = subscribe(&ctx.db, ACME, &["animal.registered"]).await;
+    let paused = subscribe(&ctx.db, ACME, &["animal.sold"]).await;
+    let elsewhere = subscribe(&ctx.db, CONTINENTAL, &["animal.sold"]).await;
+
+    Webhook::update(
+        &ctx.db,
+        paused.organisation_pid,
+        paused.pid,
+        &UpdateWebhook {
+            url: None,
+            event_types: None,
+            is_active: Some(false),
+        },
+    )
+    .await
+    .unwrap();
+
+    let event = DomainEvent::emit(
+        &mut ctx.db.acquire().await.unwrap(),
+        Uuid::parse_str(ACME).unwrap(),
+        EventType::AnimalSold,
+        &json!({ "tagId": "AC001" }),
+    )
+    .await
+    .unwrap();
+
+    let queued = deliveries(&ctx.db, &sales).await;
+
+    assert_debug_snapshot!((
+        sales.event_types.clone(),
+        (event.event_type.clone(), event.payload.0.clone()),
+        queued
+            .iter()
+            .map(|delivery| (
+                delivery.event_pid == event.pid,
+                delivery.event_type.clone(),
+                delivery.status.clone(),
+                delivery.attempts,
+            ))
+            .collect::<Vec<_>>(),
+        deliveries(&ctx.db, &births).await.len(),
+        deliveries(&ctx.db, &paused).await.len(),
+        deliveries(&ctx.db, &elsewhere).await.len(),
+    ));
+}
+
+#[tokio::test]
+#[serial]
+async fn failed_deliveries_back_off_until_given_up() {
+    configure_insta!();
+
+    let ctx = boot_test().await.unwrap();
+    seed_data(&ctx.db).await.unwrap();
+
+    let webhook = subscribe(&ctx.db, ACME, &["animal.deceased"]).await;
+
+    DomainEvent::emit(
+        &mut ctx.db.acquire().await.unwrap(),
+        webhook.organisation_pid,
+        EventType::AnimalDeceased,
+        &json!({ "tagId": "AC002" }),
+    )
+    .await
+    .unwrap();
+
+    let claimed = WebhookDelivery::claim_due(&ctx.db, 10, 60).await.unwrap();
+    let leased = WebhookDelivery::claim_due(&ctx.db, 10, 60).await.unwrap();
+
+    WebhookDelivery::record_failure(
+        &ctx.db,
+        claimed[0].pid,
+        Some(500),
+        "Partner answered 500",
+        2,
+        30,
+    )
+    .await
+    .unwrap();
+    let retrying = deliveries(&ctx.db, &webhook).await.remove(0);
+
+    WebhookDelivery::record_failure(&ctx.db, claimed[0].pid, None, "connection refused", 2, 30)
+        .await
+        .unwrap();
+    let failed = deliveries(&ctx.db, &webhook).await.remove(0);
+
+    let redelivered = WebhookDelivery::redeliver(
+        &mut ctx.db.acquire().await.unwrap(),
+        webhook.organisation_pid,
+        webhook.pid,
+        failed.pid,
+    )
+    .await
+    .unwrap();
+    let reclaimed = WebhookDelivery::claim_due(&ctx.db, 10, 60).await.unwrap();
+
+    let backoff = (retrying.next_attempt_at - retrying.last_attempt_at.unwrap()).num_seconds();
+
+    assert_debug_snapshot!((
+        (claimed.len(), claimed[0].secret == webhook.secret()),
+        leased.len(),
+        (
+            retrying.status,
+            retrying.attempts,
+            retrying.response_status,
+            backoff
+        ),
+        (failed.status, failed.attempts, failed.last_error),
+        (redelivered.status, redelivered.attempts),
+        reclaimed.len(),
+    ));
+}
@@ -87,8 +87,9 @@ async fn can_delete_one() {
     crate::seed_data(&ctx.db).await.unwrap();
 
     let org_pid = Uuid::parse_str("9d5b0c1e-6a48-4bce-b818-dc8c015fd8a0").unwrap();
+    let user_pid = Uuid::parse_str("bd6f7c26-d2c9-487e-b837-8f77be468033").unwrap();
 
-    let results = WeightRecord::delete_by_id(&ctx.db, org_pid, 115).await;
+    let results = WeightRecord::delete_by_id(&ctx.db, org_pid, 115, user_pid).await;
 
     assert_debug_snapshot!(results);
 }
@@ -109,8 +110,8 @@ async fn can_create_one() {
         record_date: NaiveDate::parse_from_str("2025-04-22", "%Y-%m-%d").unwrap(),
         mass: 55000,
         notes: None,
-        status: Cow::Borrowed("normal"),
-        unit: Cow::Borrowed("kg"),
+        status: Some(Cow::Borrowed("normal")),
+        unit: Some(Cow::Borrowed("kg")),
     };
 
     let results = WeightRecord::create(&ctx.db, &params, org_pid, user_pid).await;
@@ -147,7 +148,9 @@ async fn can_update_by_id() {
         status: None,
     };
 
-    let results = WeightRecord::update_by_id(&ctx.db, 115, org_pid, &params).await;
+    let results =
+        WeightRecord::update_by_id(&mut ctx.db.acquire().await.unwrap(), 115, org_pid, &params)
+            .await;
 
     with_settings!({
         filters => {
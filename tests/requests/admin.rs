@@ -1,6 +1,8 @@
-use insta::{Settings, assert_debug_snapshot};
+use insta::{Settings, assert_debug_snapshot, with_settings};
+use polaris::models::users::User;
 use rstest::rstest;
 use serial_test::serial;
+use uuid::Uuid;
 
 macro_rules! configure_insta {
     ($(expr:expr),*) => {
@@ -13,11 +15,11 @@ macro_rules! configure_insta {
 }
 
 #[rstest]
-#[case("can_create_user_with_role_manager", "manager")]
-#[case("can_create_user_with_role_staff", "staff")]
+#[case("can_invite_user_with_role_manager", "manager")]
+#[case("can_invite_user_with_role_staff", "staff")]
 #[tokio::test]
 #[serial]
-async fn can_create_user_with_role(#[case] name: &str, #[case] role: &str) {
+async fn can_invite_user_with_role(#[case] name: &str, #[case] role: &str) {
     crate::request(|server, context| async move {
         configure_insta!();
 
@@ -32,12 +34,21 @@ async fn can_create_user_with_role(#[case] name: &str, #[case] role: &str) {
         });
 
         let response = server
-            .post("/admin/add-user")
+            .post("/admin/invitations")
             .add_header(auth_header, auth_value)
             .json(&new_user_params)
             .await;
 
-        assert_debug_snapshot!(name, (response.status_code(), response.text()));
+        with_settings!({
+            filters => {
+                let mut filters = crate::cleanup_date().to_vec();
+                filters.extend(crate::cleanup_uuid().to_vec());
+                filters.push((r"PID\.[A-Za-z0-9]{32}", "TOKEN"));
+                filters
+            }
+        }, {
+            assert_debug_snapshot!(name, (response.status_code(), response.text()));
+        });
     })
     .await;
 }
@@ -59,7 +70,7 @@ async fn can_handle_invalid_roles() {
         });
 
         let response = server
-            .post("/admin/add-user")
+            .post("/admin/invitations")
             .add_header(auth_header, auth_value)
             .json(&new_user_params)
             .await;
@@ -68,3 +79,177 @@ async fn can_handle_invalid_roles() {
     })
     .await;
 }
+
+#[tokio::test]
+#[serial]
+async fn can_list_users() {
+    crate::request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let admin_login = super::prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = super::prepare_auth::auth_header(admin_login.access_token);
+
+        let response = server
+            .get("/admin/users")
+            .add_query_param("active", true)
+            .add_header(auth_header, auth_value)
+            .await;
+
+        with_settings!({
+            filters => {
+                let mut filters = crate::cleanup_date().to_vec();
+                filters.extend(crate::cleanup_uuid().to_vec());
+                filters
+            }
+        }, {
+            assert_debug_snapshot!((response.status_code(), response.text()));
+        });
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn cannot_deactivate_self() {
+    crate::request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let admin_login = super::prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = super::prepare_auth::auth_header(admin_login.access_token);
+
+        let response = server
+            .patch("/admin/users/bd6f7c26-d2c9-487e-b837-8f77be468033/deactivate")
+            .add_header(auth_header, auth_value)
+            .await;
+
+        assert_debug_snapshot!((response.status_code(), response.text()));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_deactivate_user() {
+    crate::request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let admin_login = super::prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = super::prepare_auth::auth_header(admin_login.access_token);
+
+        let org_pid = Uuid::parse_str("9d5b0c1e-6a48-4bce-b818-dc8c015fd8a0").unwrap();
+        let staff = crate::invite_and_accept(&context.db, org_pid, "staff@acme.com", "staff").await;
+
+        let response = server
+            .patch(&format!("/admin/users/{}/deactivate", staff.pid()))
+            .add_header(auth_header, auth_value)
+            .await;
+
+        with_settings!({
+            filters => {
+                let mut filters = crate::cleanup_date().to_vec();
+                filters.extend(crate::cleanup_uuid().to_vec());
+                filters
+            }
+        }, {
+            assert_debug_snapshot!((response.status_code(), response.text()));
+        });
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn rejects_deactivated_user_token() {
+    crate::request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let login = super::prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = super::prepare_auth::auth_header(login.access_token);
+
+        login.user.set_active(&context.db, false).await.unwrap();
+
+        let response = server
+            .get("/animals")
+            .add_header(auth_header, auth_value)
+            .await;
+
+        assert_debug_snapshot!((response.status_code(), response.text()));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn rejects_deactivated_user_login() {
+    crate::request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let pid = Uuid::parse_str("bd6f7c26-d2c9-487e-b837-8f77be468033").unwrap();
+        let user = User::find_by_pid(&context.db, pid).await.unwrap().unwrap();
+        user.set_active(&context.db, false).await.unwrap();
+
+        let response = server
+            .post("/auth/login")
+            .json(&serde_json::json!({
+                "email": "john.doe@acme.com",
+                "password": "Password"
+            }))
+            .await;
+
+        assert_debug_snapshot!((response.status_code(), response.text()));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn rejects_deactivated_user_with_a_password_change_pending() {
+    crate::request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let pid = Uuid::parse_str("bd6f7c26-d2c9-487e-b837-8f77be468033").unwrap();
+        let user = User::find_by_pid(&context.db, pid).await.unwrap().unwrap();
+        user.set_active(&context.db, false).await.unwrap();
+        sqlx::query("UPDATE users SET password_change_required = TRUE WHERE pid = $1")
+            .bind(pid)
+            .execute(&context.db)
+            .await
+            .unwrap();
+
+        let login = server
+            .post("/auth/login")
+            .json(&serde_json::json!({
+                "email": "john.doe@acme.com",
+                "password": "Password"
+            }))
+            .await;
+
+        let updated = server
+            .post("/auth/update-password")
+            .json(&serde_json::json!({
+                "email": "john.doe@acme.com",
+                "current_password": "Password",
+                "password": "NewPassword",
+                "confirm_password": "NewPassword"
+            }))
+            .await;
+
+        assert_debug_snapshot!((
+            (login.status_code(), login.text()),
+            (updated.status_code(), updated.text()),
+        ));
+    })
+    .await;
+}
@@ -0,0 +1,195 @@
+use chrono::{Duration, Local};
+use insta::{Settings, assert_debug_snapshot, with_settings};
+use polaris::alerts::Evaluator;
+use serde_json::{Value, json};
+use serial_test::serial;
+
+use crate::{request, requests::prepare_auth};
+
+macro_rules! configure_insta {
+    ($(expr:expr),*) => {
+        let mut settings = Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_path("snapshots/alerts");
+        settings.set_snapshot_suffix("alerts");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+#[tokio::test]
+#[serial]
+async fn can_manage_alert_rules() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        let invalid = server
+            .post("/alerts/rules")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({ "name": "Milk", "kind": "milk_yield_drop", "channels": ["pager"] }))
+            .await;
+
+        let missing_threshold = server
+            .post("/alerts/rules")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({ "name": "Milk", "kind": "milk_yield_drop" }))
+            .await;
+
+        let created = server
+            .post("/alerts/rules")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({
+                "name": "Vaccinations",
+                "kind": "vaccination_overdue",
+                "windowDays": 365,
+                "severity": "low",
+                "channels": ["webhook", "webhook"]
+            }))
+            .await;
+        let pid = created.json::<Value>()["pid"].as_str().unwrap().to_string();
+
+        let paused = server
+            .patch(&format!("/alerts/rules/{pid}"))
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({ "isActive": false, "windowDays": 180 }))
+            .await;
+
+        let listed = server
+            .get("/alerts/rules")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+
+        let removed = server
+            .delete(&format!("/alerts/rules/{pid}"))
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+
+        let removed_again = server
+            .delete(&format!("/alerts/rules/{pid}"))
+            .add_header(auth_header, auth_value)
+            .await;
+
+        crate::invite_and_accept(
+            &context.db,
+            user.user.organisation_pid(),
+            "staff@acme.com",
+            "staff",
+        )
+        .await;
+        let staff_login = server
+            .post("/auth/login")
+            .json(&json!({ "email": "staff@acme.com", "password": "Password" }))
+            .await;
+        let forbidden = server
+            .post("/alerts/rules")
+            .add_header("authorization", staff_login.header("authorization"))
+            .json(&json!({ "name": "Weights", "kind": "weight_drop", "threshold": 5 }))
+            .await;
+
+        with_settings!({
+            filters => {
+                let mut filters = crate::cleanup_date().to_vec();
+                filters.extend(crate::cleanup_uuid().to_vec());
+                filters
+            }
+        }, {
+            assert_debug_snapshot!((
+                (invalid.status_code(), invalid.json::<Value>()),
+                (missing_threshold.status_code(), missing_threshold.json::<Value>()),
+                (created.status_code(), created.json::<Value>()),
+                (paused.status_code(), paused.json::<Value>()),
+                listed.json::<Value>(),
+                removed.status_code(),
+                removed_again.status_code(),
+                forbidden.status_code(),
+            ));
+        })
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn raised_alerts_are_acknowledged_and_resolved() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        server
+            .post("/alerts/rules")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({ "name": "Weight loss", "kind": "weight_drop", "threshold": 15 }))
+            .await;
+
+        let today = Local::now().date_naive();
+        for (days_ago, mass) in [(14, 52000), (2, 43000)] {
+            server
+                .post("/weight-records")
+                .add_header(auth_header.clone(), auth_value.clone())
+                .json(&json!({
+                    "tagId": "AC002",
+                    "recordDate": (today - Duration::days(days_ago)).to_string(),
+                    "mass": mass,
+                    "unit": "kg",
+                    "status": "normal"
+                }))
+                .await;
+        }
+
+        let evaluator = Evaluator::new(&context);
+        let raised = evaluator.evaluate_all().await.unwrap();
+        let raised_again = evaluator.evaluate_all().await.unwrap();
+
+        let open = server
+            .get("/alerts")
+            .add_query_param("status", "open")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+        let pid = open.json::<Value>()[0]["pid"].as_str().unwrap().to_string();
+
+        let acknowledged = server
+            .post(&format!("/alerts/{pid}/acknowledge"))
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+        let acknowledged_again = server
+            .post(&format!("/alerts/{pid}/acknowledge"))
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+        let resolved = server
+            .post(&format!("/alerts/{pid}/resolve"))
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+
+        let still_open = server
+            .get("/alerts")
+            .add_query_param("status", "open")
+            .add_header(auth_header, auth_value)
+            .await;
+
+        with_settings!({
+            filters => {
+                let mut filters = crate::cleanup_date().to_vec();
+                filters.extend(crate::cleanup_uuid().to_vec());
+                filters
+            }
+        }, {
+            assert_debug_snapshot!((
+                (raised, raised_again),
+                open.json::<Value>(),
+                (acknowledged.status_code(), acknowledged.json::<Value>()["status"].clone()),
+                (acknowledged_again.status_code(), acknowledged_again.json::<Value>()),
+                (resolved.status_code(), resolved.json::<Value>()),
+                still_open.json::<Value>(),
+            ));
+        })
+    })
+    .await;
+}
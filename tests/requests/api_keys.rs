@@ -0,0 +1,287 @@
+use axum::http::{HeaderName, HeaderValue};
+use insta::{Settings, assert_debug_snapshot, with_settings};
+use polaris::{
+    AppContext,
+    models::{
+        audit::{AuditLog, AuditQuery},
+        enums::Subscription,
+        orgs::Organisation,
+    },
+};
+use serde_json::{Value, json};
+use serial_test::serial;
+use uuid::Uuid;
+
+use crate::{
+    request,
+    requests::prepare_auth::{self, LoggedInUser},
+};
+
+macro_rules! configure_insta {
+    ($(expr:expr),*) => {
+        let mut settings = Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_path("snapshots/api_keys");
+        settings.set_snapshot_suffix("api_keys");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+fn api_key_header(token: &str) -> (HeaderName, HeaderValue) {
+    (
+        HeaderName::from_static("x-api-key"),
+        HeaderValue::from_str(token).unwrap(),
+    )
+}
+
+fn bearer_header(token: &str) -> (HeaderName, HeaderValue) {
+    (
+        HeaderName::from_static("authorization"),
+        HeaderValue::from_str(&format!("Bearer {token}")).unwrap(),
+    )
+}
+
+/// Moves the user's organisation onto `subscription`.
+async fn subscribe(context: &AppContext, user: &LoggedInUser, subscription: &Subscription) {
+    Organisation::change_subscription(&context.db, user.user.organisation_pid(), subscription)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+#[serial]
+async fn api_key_is_limited_to_its_scopes() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        subscribe(&context, &user, &Subscription::Enterprise).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        let created = server
+            .post("/admin/api-keys")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({
+                "name": "Weighing scale",
+                "scopes": ["weight_records:write", "animals:read"]
+            }))
+            .await;
+        let created_json = created.json::<Value>();
+        let token = created_json["token"].as_str().unwrap().to_string();
+
+        let (key_header, key_value) = api_key_header(&token);
+        let animals = server
+            .get("/animals")
+            .add_header(key_header.clone(), key_value.clone())
+            .await;
+        let users = server
+            .get("/admin/users")
+            .add_header(key_header.clone(), key_value.clone())
+            .await;
+        let minted = server
+            .post("/admin/api-keys")
+            .add_header(key_header, key_value)
+            .json(&json!({ "name": "Another", "scopes": ["animals:read"] }))
+            .await;
+
+        let (bearer_name, bearer_value) = bearer_header(&token);
+        let weighed = server
+            .post("/weight-records")
+            .add_header(bearer_name, bearer_value)
+            .json(&json!({
+                "tagId": "AC001",
+                "recordDate": "2024-11-05",
+                "mass": 48500,
+                "notes": "Weighed by the scale"
+            }))
+            .await;
+        let (invalid_name, invalid_value) = bearer_header(&format!("{token}x"));
+        let invalid = server
+            .get("/animals")
+            .add_header(invalid_name, invalid_value)
+            .await;
+
+        let listed = server
+            .get("/admin/api-keys")
+            .add_header(auth_header, auth_value)
+            .await;
+
+        with_settings!({
+            filters => {
+                let mut filters = crate::cleanup_date().to_vec();
+                filters.extend(crate::cleanup_uuid().to_vec());
+                filters.push((r"plk_PID\.[A-Za-z0-9]{40}", "TOKEN"));
+                filters
+            }
+        }, {
+            assert_debug_snapshot!((
+                created.status_code(),
+                created_json,
+                animals.status_code(),
+                users.status_code(),
+                minted.status_code(),
+                weighed.status_code(),
+                invalid.status_code(),
+                listed.json::<Value>(),
+            ));
+        });
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn revoked_api_key_is_rejected() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        subscribe(&context, &user, &Subscription::Enterprise).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        let created = server
+            .post("/admin/api-keys")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({ "name": "Milking parlour", "scopes": ["animals:read"] }))
+            .await
+            .json::<Value>();
+        let token = created["token"].as_str().unwrap().to_string();
+        let pid = created["pid"].as_str().unwrap().to_string();
+
+        let revoked = server
+            .delete(&format!("/admin/api-keys/{pid}"))
+            .add_header(auth_header, auth_value)
+            .await;
+
+        let (key_header, key_value) = api_key_header(&token);
+        let animals = server
+            .get("/animals")
+            .add_header(key_header, key_value)
+            .await;
+
+        assert_debug_snapshot!((
+            revoked.status_code(),
+            revoked.json::<Value>()["isUsable"].clone(),
+            animals.status_code(),
+        ));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn changes_made_with_api_key_are_audited_as_the_key() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        subscribe(&context, &user, &Subscription::Enterprise).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        let created = server
+            .post("/admin/api-keys")
+            .add_header(auth_header, auth_value)
+            .json(&json!({ "name": "Weighing scale", "scopes": ["weight_records:write"] }))
+            .await
+            .json::<Value>();
+        let token = created["token"].as_str().unwrap().to_string();
+        let api_key = Uuid::parse_str(created["pid"].as_str().unwrap()).unwrap();
+
+        let (key_header, key_value) = api_key_header(&token);
+        let weighed = server
+            .post("/weight-records")
+            .add_header(key_header, key_value)
+            .json(&json!({
+                "tagId": "AC001",
+                "recordDate": "2024-11-05",
+                "mass": 48500,
+                "notes": "Weighed by the scale"
+            }))
+            .await;
+
+        let logs = AuditLog::find_all(
+            &context.db,
+            user.user.organisation_pid(),
+            &AuditQuery {
+                api_key: Some(api_key),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap()
+        .iter()
+        .map(|log| {
+            (
+                log.table_name.clone(),
+                log.action.clone(),
+                log.changed_by == Some(user.user.pid()),
+                log.api_key_name.clone(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+        assert_debug_snapshot!((weighed.status_code(), logs));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn api_keys_need_api_access() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token.clone());
+        let body = json!({ "name": "Weighing scale", "scopes": ["animals:read"] });
+
+        let refused = server
+            .post("/admin/api-keys")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&body)
+            .await;
+
+        subscribe(&context, &user, &Subscription::Enterprise).await;
+        let created = server
+            .post("/admin/api-keys")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&body)
+            .await;
+        let token = created.json::<Value>()["token"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        let (key_header, key_value) = api_key_header(&token);
+        let allowed = server
+            .get("/animals")
+            .add_header(key_header.clone(), key_value.clone())
+            .await;
+
+        subscribe(&context, &user, &Subscription::Business).await;
+        let downgraded = server
+            .get("/animals")
+            .add_header(key_header, key_value)
+            .await;
+        let listed = server
+            .get("/admin/api-keys")
+            .add_header(auth_header, auth_value)
+            .await;
+
+        assert_debug_snapshot!((
+            (refused.status_code(), refused.text()),
+            created.status_code(),
+            allowed.status_code(),
+            (downgraded.status_code(), downgraded.text()),
+            listed.status_code(),
+        ));
+    })
+    .await;
+}
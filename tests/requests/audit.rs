@@ -0,0 +1,190 @@
+use std::borrow::Cow;
+
+use crate::{request, requests::prepare_auth};
+
+use insta::{Settings, assert_debug_snapshot, with_settings};
+use polaris::models::dto::UpdateUserRole;
+use serde_json::{Value, json};
+use serial_test::serial;
+
+macro_rules! configure_insta {
+    ($(expr:expr),*) => {
+        let mut settings = Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_path("snapshots/audit");
+        settings.set_snapshot_suffix("audit");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+const ROSE: &str = "f6417c11-d817-4626-9e8d-c68a44002d4b";
+
+#[tokio::test]
+#[serial]
+async fn can_filter_audit_logs() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        server
+            .patch(&format!("/animals/{ROSE}"))
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({ "name": "Rosie" }))
+            .await;
+
+        let response = server
+            .get("/audit")
+            .add_query_params(json!({
+                "table": "animals",
+                "record": ROSE,
+                "user": user.user.pid(),
+            }))
+            .add_header(auth_header, auth_value)
+            .await;
+
+        with_settings!({
+            filters => {
+                let mut filters = crate::cleanup_date().to_vec();
+                filters.extend(crate::cleanup_int().iter().copied());
+                filters
+            }
+        }, {
+            let logs = response.json::<Vec<Value>>();
+            assert_debug_snapshot!((
+                response.status_code(),
+                logs.iter()
+                    .map(|log| (log["action"].clone(), log["changedByName"].clone()))
+                    .collect::<Vec<_>>()
+            ));
+        });
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_get_animal_history() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        server
+            .delete("/weight-records/115")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+
+        let response = server
+            .get(&format!("/animals/{ROSE}/history"))
+            .add_header(auth_header, auth_value)
+            .await;
+
+        let logs = response.json::<Vec<Value>>();
+        let last = logs.last().map(|log| {
+            (
+                log["tableName"].clone(),
+                log["recordId"].clone(),
+                log["action"].clone(),
+                log["changedByName"].clone(),
+            )
+        });
+
+        assert_debug_snapshot!((response.status_code(), logs.len(), last));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn cannot_read_audit_logs_as_staff() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let login = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(login.access_token);
+
+        login
+            .user
+            .update_role(
+                &context.db,
+                &UpdateUserRole {
+                    role: Cow::Borrowed("staff"),
+                },
+            )
+            .await
+            .unwrap();
+
+        let response = server
+            .get("/audit")
+            .add_header(auth_header, auth_value)
+            .await;
+
+        assert_debug_snapshot!((response.status_code(), response.text()));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn audit_logs_leave_out_credentials() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        server
+            .post("/auth/update-password")
+            .json(&json!({
+                "email": "john.doe@acme.com",
+                "current_password": "Password",
+                "password": "NewPassword",
+                "confirm_password": "NewPassword"
+            }))
+            .await;
+
+        let response = server
+            .get("/audit")
+            .add_query_params(json!({ "table": "users", "record": user.user.pid() }))
+            .add_header(auth_header, auth_value)
+            .await;
+
+        let logs = response.json::<Vec<Value>>();
+        let leaked = logs
+            .iter()
+            .flat_map(|log| [&log["oldData"], &log["newData"]])
+            .filter_map(Value::as_object)
+            .flat_map(|data| data.keys())
+            .filter(|key| {
+                [
+                    "password_hash",
+                    "reset_token",
+                    "token_hash",
+                    "secret_hash",
+                    "secret",
+                ]
+                .contains(&key.as_str())
+            })
+            .collect::<Vec<_>>();
+
+        assert_debug_snapshot!((
+            response.status_code(),
+            logs.iter()
+                .map(|log| log["action"].clone())
+                .collect::<Vec<_>>(),
+            leaked,
+        ));
+    })
+    .await;
+}
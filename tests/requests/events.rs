@@ -0,0 +1,323 @@
+use std::time::Duration;
+
+use insta::{Settings, assert_debug_snapshot, with_settings};
+use serde_json::{Value, json};
+use serial_test::serial;
+use tokio::net::TcpListener;
+use uuid::Uuid;
+
+use polaris::controllers;
+
+use crate::{request, requests::prepare_auth};
+
+macro_rules! configure_insta {
+    ($(expr:expr),*) => {
+        let mut settings = Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_path("snapshots/events");
+        settings.set_snapshot_suffix("events");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+const CONTINENTAL: &str = "4a0f3af9-e56e-4e21-8f3a-f9e56efe215b";
+
+/// Reads Server-Sent Events until `count` have arrived, skipping keep-alive
+/// comments.
+async fn read_events(response: &mut reqwest::Response, count: usize) -> Vec<(String, Value)> {
+    let mut buffer = String::new();
+    let mut events = Vec::new();
+
+    while events.len() < count {
+        let chunk = tokio::time::timeout(Duration::from_secs(10), response.chunk())
+            .await
+            .expect("Timed out waiting for events")
+            .unwrap()
+            .expect("The stream ended");
+        buffer.push_str(std::str::from_utf8(&chunk).unwrap());
+
+        while let Some(end) = buffer.find("\n\n") {
+            let block = buffer[..end].to_string();
+            buffer.drain(..end + 2);
+
+            let field = |name: &str| {
+                block
+                    .lines()
+                    .find_map(|line| line.strip_prefix(name))
+                    .map(str::to_string)
+            };
+
+            if let (Some(event), Some(data)) = (field("event: "), field("data: ")) {
+                events.push((event, serde_json::from_str(&data).unwrap()));
+            }
+        }
+    }
+
+    events
+}
+
+/// Whether the stream ends within `seconds`, skipping keep-alive comments.
+async fn ends_within(response: &mut reqwest::Response, seconds: u64) -> bool {
+    tokio::time::timeout(Duration::from_secs(seconds), async {
+        while response.chunk().await.unwrap().is_some() {}
+    })
+    .await
+    .is_ok()
+}
+
+#[tokio::test]
+#[serial]
+async fn streams_the_organisations_events() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        // Streaming needs a real connection rather than the mock transport.
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let app = controllers::router(context.clone());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let client = reqwest::Client::new();
+        let unauthenticated = client
+            .get(format!("http://{address}/events/stream"))
+            .send()
+            .await
+            .unwrap();
+        let mut stream = client
+            .get(format!("http://{address}/events/stream"))
+            .header(auth_header.as_str(), auth_value.to_str().unwrap())
+            .send()
+            .await
+            .unwrap();
+
+        // Another organisation's change is not sent.
+        sqlx::query(
+            "
+            UPDATE animals SET status = 'sold'
+            WHERE id = (SELECT MIN(id) FROM animals WHERE organisation_pid = $1)",
+        )
+        .bind(Uuid::parse_str(CONTINENTAL).unwrap())
+        .execute(&context.db)
+        .await
+        .unwrap();
+
+        server
+            .post("/health-records")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({
+                "tagId": "AC002",
+                "recordDate": "2025-05-12",
+                "condition": "fever",
+                "description": "Buttercup is running a temperature.",
+                "treatment": "rest",
+                "dosage": null,
+                "severity": "medium",
+                "status": "active",
+                "medicine": null,
+                "cost": null,
+                "prognosis": null,
+                "performedBy": null,
+                "notes": null,
+            }))
+            .await;
+
+        server
+            .post("/weight-records")
+            .add_header(auth_header, auth_value)
+            .json(&json!({
+                "tagId": "AC001",
+                "recordDate": "2024-11-05",
+                "mass": 48500,
+                "unit": "kg",
+                "status": "normal"
+            }))
+            .await;
+
+        sqlx::query(
+            "UPDATE animals SET status = 'deceased' WHERE tag_id = 'AC003' AND organisation_pid = $1",
+        )
+        .bind(user.user.organisation_pid())
+        .execute(&context.db)
+        .await
+        .unwrap();
+
+        let content_type = stream.headers()["content-type"].to_str().unwrap().to_string();
+        let events = read_events(&mut stream, 3).await;
+
+        with_settings!({
+            filters => {
+                let mut filters = crate::cleanup_date().to_vec();
+                filters.extend(crate::cleanup_uuid().to_vec());
+                filters.extend(crate::cleanup_int().to_vec());
+                filters
+            }
+        }, {
+            assert_debug_snapshot!((
+                unauthenticated.status().as_u16(),
+                (stream.status().as_u16(), content_type),
+                events,
+            ));
+        })
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn leaves_out_records_the_role_cannot_read() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        sqlx::query(
+            "INSERT INTO roles (organisation_pid, name, permissions) VALUES ($1, 'analyst',
+            '{\"scopes\": [\"reports:read\", \"weight_records:read\", \"health_records:write\",
+            \"weight_records:write\"]}'::jsonb)",
+        )
+        .bind(user.user.organisation_pid())
+        .execute(&context.db)
+        .await
+        .unwrap();
+        sqlx::query("UPDATE users SET role = 'analyst' WHERE pid = $1")
+            .bind(user.user.pid())
+            .execute(&context.db)
+            .await
+            .unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let app = controllers::router(context.clone());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let mut stream = reqwest::Client::new()
+            .get(format!("http://{address}/events/stream"))
+            .header(auth_header.as_str(), auth_value.to_str().unwrap())
+            .send()
+            .await
+            .unwrap();
+
+        let treated = server
+            .post("/health-records")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({
+                "tagId": "AC002",
+                "recordDate": "2025-05-12",
+                "condition": "fever",
+                "description": "Buttercup is running a temperature.",
+                "treatment": "rest",
+                "severity": "medium",
+                "status": "active"
+            }))
+            .await;
+
+        sqlx::query(
+            "UPDATE animals SET status = 'deceased' WHERE tag_id = 'AC003' AND organisation_pid = $1",
+        )
+        .bind(user.user.organisation_pid())
+        .execute(&context.db)
+        .await
+        .unwrap();
+
+        let weighed = server
+            .post("/weight-records")
+            .add_header(auth_header, auth_value)
+            .json(&json!({
+                "tagId": "AC001",
+                "recordDate": "2024-11-05",
+                "mass": 48500,
+                "unit": "kg",
+                "status": "normal"
+            }))
+            .await;
+
+        // The health record and the animal's death come first, but only the
+        // weighing is sent.
+        let events = read_events(&mut stream, 1)
+            .await
+            .into_iter()
+            .map(|(event, _)| event)
+            .collect::<Vec<_>>();
+
+        assert_debug_snapshot!((
+            treated.status_code(),
+            weighed.status_code(),
+            events
+        ));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn ends_the_stream_when_the_token_expires() {
+    request(|server, context| async move {
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let mut access = context.auth.access.clone();
+        access.exp = 2;
+        let token = access.jwt(&user.user).unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let app = controllers::router(context.clone());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let mut stream = reqwest::Client::new()
+            .get(format!("http://{address}/events/stream"))
+            .bearer_auth(token)
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(200, stream.status().as_u16());
+        assert!(ends_within(&mut stream, 10).await);
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn ends_the_stream_when_the_user_is_deactivated() {
+    request(|server, context| async move {
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let app = controllers::router(context.clone());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let mut stream = reqwest::Client::new()
+            .get(format!("http://{address}/events/stream"))
+            .header(auth_header.as_str(), auth_value.to_str().unwrap())
+            .send()
+            .await
+            .unwrap();
+
+        // The stream stays open while the user may read it.
+        let open = !ends_within(&mut stream, 3).await;
+
+        sqlx::query("UPDATE memberships SET is_active = FALSE WHERE user_pid = $1")
+            .bind(user.user.pid())
+            .execute(&context.db)
+            .await
+            .unwrap();
+
+        assert_eq!(200, stream.status().as_u16());
+        assert!(open);
+        assert!(ends_within(&mut stream, 10).await);
+    })
+    .await;
+}
@@ -0,0 +1,206 @@
+use insta::{Settings, assert_debug_snapshot, with_settings};
+use serde_json::{Value, json};
+use serial_test::serial;
+
+use crate::{request, requests::prepare_auth};
+
+macro_rules! configure_insta {
+    ($(expr:expr),*) => {
+        let mut settings = Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_path("snapshots/feed");
+        settings.set_snapshot_suffix("feed");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+#[tokio::test]
+#[serial]
+async fn can_manage_feed() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        let invalid = server
+            .post("/feed/feedstuffs")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({ "name": "", "dryMatter": 12000 }))
+            .await;
+
+        let created = server
+            .post("/feed/feedstuffs")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({
+                "name": "Maize silage",
+                "dryMatter": 3200,
+                "crudeProtein": 250,
+                "energy": 1100,
+                "reorderLevel": 50000
+            }))
+            .await;
+        let silage = created.json::<Value>()["pid"].as_str().unwrap().to_string();
+
+        let duplicate = server
+            .post("/feed/feedstuffs")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({ "name": "Maize silage" }))
+            .await;
+
+        let bought = server
+            .post("/feed/purchases")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({
+                "feedstuff": silage,
+                "quantity": 200_000,
+                "cost": 300_000,
+                "purchaseDate": "2024-06-01"
+            }))
+            .await;
+
+        let unknown_breed = server
+            .post("/feed/rations")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({
+                "name": "Finishers",
+                "specie": "cattle",
+                "breed": "Merino",
+                "items": [{ "feedstuff": silage, "quantity": 2500 }]
+            }))
+            .await;
+
+        let ration = server
+            .post("/feed/rations")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({
+                "name": "Finishers",
+                "specie": "cattle",
+                "items": [{ "feedstuff": silage, "quantity": 2500 }]
+            }))
+            .await;
+        let ration_pid = ration.json::<Value>()["pid"].as_str().unwrap().to_string();
+
+        let fed = server
+            .post(&format!("/feed/rations/{ration_pid}/feed"))
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({ "tagIds": ["AC001", "AC002", "AC003", "AC004"] }))
+            .await;
+
+        let unknown_tag = server
+            .post("/feed/feedings")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({ "feedstuff": silage, "quantity": 1000, "tagIds": ["XX999"] }))
+            .await;
+
+        let stock = server
+            .get("/feed/stock?days=10&within=180")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+
+        let feedings = server
+            .get("/feed/feedings?animal=b2bd6270-8bec-42ce-99ff-d0eb1a076221")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+        let feeding = feedings.json::<Vec<Value>>()[0]["pid"]
+            .as_str()
+            .unwrap()
+            .to_string();
+
+        let removed = server
+            .delete(&format!("/feed/feedings/{feeding}"))
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+
+        let restocked = server
+            .get(&format!("/feed/feedstuffs/{silage}"))
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+
+        let spent = server
+            .get("/finance/entries?category=feed")
+            .add_header(auth_header, auth_value)
+            .await;
+
+        with_settings!({ filters => {
+            let mut filters = crate::cleanup_uuid().to_vec();
+            filters.extend(crate::cleanup_date().to_vec());
+            filters.push((r#""id": Number\(\d+\)"#, r#""id": ID"#));
+            filters.push((r#""runsOutOn": String\("[^"]+"\)"#, r#""runsOutOn": DATE"#));
+            filters
+        }}, {
+            assert_debug_snapshot!((
+                (invalid.status_code(), invalid.text()),
+                (created.status_code(), created.json::<Value>()),
+                (duplicate.status_code(), duplicate.text()),
+                bought.status_code(),
+                (unknown_breed.status_code(), unknown_breed.text()),
+                (ration.status_code(), ration.json::<Value>()),
+                (fed.status_code(), fed.json::<Value>()),
+                (unknown_tag.status_code(), unknown_tag.text()),
+                (stock.status_code(), stock.json::<Value>()),
+                removed.status_code(),
+                restocked.json::<Value>()["stock"].clone(),
+                spent.json::<Vec<Value>>()[0]["amount"].clone(),
+            ));
+        });
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_report_feed_conversion() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        let hay = server
+            .post("/feed/feedstuffs")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({ "name": "Hay", "dryMatter": 8500 }))
+            .await;
+        let hay = hay.json::<Value>()["pid"].as_str().unwrap().to_string();
+
+        let out_of_stock = server
+            .post("/feed/feedings")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({ "feedstuff": hay, "quantity": 5400, "tagIds": ["AC007"] }))
+            .await;
+
+        server
+            .post("/feed/purchases")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({ "feedstuff": hay, "quantity": 10000 }))
+            .await;
+
+        let fed = server
+            .post("/feed/feedings")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({
+                "feedstuff": hay,
+                "quantity": 5400,
+                "feedingDate": "2024-06-15",
+                "tagIds": ["AC007"]
+            }))
+            .await;
+
+        let conversion = server
+            .get("/feed/conversion?from=2024-06-01&to=2024-07-31")
+            .add_header(auth_header, auth_value)
+            .await;
+
+        assert_debug_snapshot!((
+            (out_of_stock.status_code(), out_of_stock.text()),
+            fed.status_code(),
+            (conversion.status_code(), conversion.json::<Value>()),
+        ));
+    })
+    .await;
+}
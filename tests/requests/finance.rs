@@ -0,0 +1,238 @@
+use insta::{Settings, assert_debug_snapshot, with_settings};
+use serde_json::{Value, json};
+use serial_test::serial;
+
+use crate::{request, requests::prepare_auth};
+
+macro_rules! configure_insta {
+    ($(expr:expr),*) => {
+        let mut settings = Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_path("snapshots/finance");
+        settings.set_snapshot_suffix("finance");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+#[tokio::test]
+#[serial]
+async fn can_keep_the_ledger() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        let invalid = server
+            .post("/finance/entries")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({ "category": "lottery", "amount": 0 }))
+            .await;
+
+        let unknown_animal = server
+            .post("/finance/entries")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({ "category": "feed", "amount": 25000, "tagId": "XX999" }))
+            .await;
+
+        let created = server
+            .post("/finance/entries")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({
+                "category": "feed",
+                "amount": 25000,
+                "entryDate": "2024-06-03",
+                "description": "Hay bales",
+                "tagId": "ac002"
+            }))
+            .await;
+        let pid = created.json::<Value>()["pid"].as_str().unwrap().to_string();
+
+        let updated = server
+            .patch(&format!("/finance/entries/{pid}"))
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({ "amount": 27500, "description": "Hay bales, delivered" }))
+            .await;
+
+        let posted = server
+            .get("/finance/entries?category=vet")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+        let posted_pid = posted.json::<Vec<Value>>()[0]["pid"]
+            .as_str()
+            .unwrap()
+            .to_string();
+
+        let change_posted = server
+            .patch(&format!("/finance/entries/{posted_pid}"))
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({ "amount": 100 }))
+            .await;
+
+        let remove_posted = server
+            .delete(&format!("/finance/entries/{posted_pid}"))
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+
+        let june = server
+            .get("/finance/entries?from=2024-06-01&to=2024-06-30")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+
+        let removed = server
+            .delete(&format!("/finance/entries/{pid}"))
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+
+        let gone = server
+            .get(&format!("/finance/entries/{pid}"))
+            .add_header(auth_header, auth_value)
+            .await;
+
+        with_settings!({ filters => {
+            let mut filters = crate::cleanup_uuid().to_vec();
+            filters.extend(crate::cleanup_date().to_vec());
+            filters.push((r#""id": Number\(\d+\)"#, r#""id": ID"#));
+            filters
+        }}, {
+            assert_debug_snapshot!((
+                (invalid.status_code(), invalid.text()),
+                (unknown_animal.status_code(), unknown_animal.text()),
+                (created.status_code(), created.json::<Value>()),
+                (updated.status_code(), updated.json::<Value>()),
+                (change_posted.status_code(), change_posted.text()),
+                (remove_posted.status_code(), remove_posted.text()),
+                (june.status_code(), june.json::<Value>()),
+                removed.status_code(),
+                gone.status_code(),
+            ));
+        });
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_report_profit_and_loss() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        let sold = server
+            .patch("/animals/b2bd6270-8bec-42ce-99ff-d0eb1a076221")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({ "status": "sold", "salePrice": 6250000, "saleDate": "2024-06-28" }))
+            .await;
+
+        let herd = server
+            .get("/finance/herd?from=2024-06-01&to=2024-06-30")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+
+        let animal = server
+            .get("/finance/animals/b2bd6270-8bec-42ce-99ff-d0eb1a076221")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+
+        let animals = server
+            .get("/finance/animals?from=2024-06-01&to=2024-06-30")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+
+        let unknown = server
+            .get("/finance/animals/00000000-0000-0000-0000-000000000000")
+            .add_header(auth_header, auth_value)
+            .await;
+
+        assert_debug_snapshot!((
+            sold.status_code(),
+            (herd.status_code(), herd.json::<Value>()),
+            (animal.status_code(), animal.json::<Value>()),
+            (
+                animals.status_code(),
+                animals.json::<Vec<Value>>()[0].clone()
+            ),
+            unknown.status_code(),
+        ));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_report_a_groups_profit_and_loss() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        let lot = server
+            .post("/groups")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({ "name": "Sale lot 12", "kind": "static", "tagIds": ["AC001", "AC002"] }))
+            .await
+            .json::<Value>()["pid"]
+            .as_str()
+            .unwrap()
+            .to_string();
+
+        let created = server
+            .post("/finance/entries")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({
+                "category": "feed",
+                "amount": 300000,
+                "entryDate": "2024-06-21",
+                "group": lot
+            }))
+            .await;
+
+        let both = server
+            .post("/finance/entries")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({ "category": "feed", "amount": 1000, "tagId": "AC001", "group": lot }))
+            .await;
+
+        let entries = server
+            .get(&format!("/finance/entries?group={lot}"))
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+
+        let report = server
+            .get(&format!(
+                "/finance/groups/{lot}?from=2024-06-01&to=2024-06-30"
+            ))
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+
+        let unknown = server
+            .get("/finance/groups/00000000-0000-0000-0000-000000000000")
+            .add_header(auth_header, auth_value)
+            .await;
+
+        with_settings!({ filters => {
+            let mut filters = crate::cleanup_uuid().to_vec();
+            filters.extend(crate::cleanup_date().to_vec());
+            filters.push((r#""id": Number\(\d+\)"#, r#""id": ID"#));
+            filters
+        }}, {
+            assert_debug_snapshot!((
+                created.status_code(),
+                (both.status_code(), both.text()),
+                (entries.status_code(), entries.json::<Value>()),
+                (report.status_code(), report.json::<Value>()),
+                unknown.status_code(),
+            ));
+        });
+    })
+    .await;
+}
@@ -0,0 +1,680 @@
+use axum::http::{HeaderName, HeaderValue};
+use axum_test::TestServer;
+use insta::{Settings, assert_debug_snapshot, with_settings};
+use serde_json::{Value, json};
+use serial_test::serial;
+use uuid::Uuid;
+
+use crate::{pid, request, requests::prepare_auth};
+
+macro_rules! configure_insta {
+    ($(expr:expr),*) => {
+        let mut settings = Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_path("snapshots/groups");
+        settings.set_snapshot_suffix("groups");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+/// Creates a static group of `tag_ids`, returning its pid.
+async fn group(
+    server: &TestServer,
+    (auth_header, auth_value): &(HeaderName, HeaderValue),
+    name: &str,
+    tag_ids: &[&str],
+) -> Uuid {
+    let created = server
+        .post("/groups")
+        .add_header(auth_header.clone(), auth_value.clone())
+        .json(&json!({ "name": name, "kind": "static", "tagIds": tag_ids }))
+        .await;
+
+    pid(&created.json::<Value>())
+}
+
+fn weighing() -> Value {
+    json!({
+        "recordDate": "2024-06-15",
+        "weights": [
+            { "tagId": "AC012", "mass": 32_000 },
+            { "tagId": "AC013", "mass": 31_050 }
+        ]
+    })
+}
+
+fn treatment() -> Value {
+    json!({
+        "recordDate": "2024-06-16",
+        "condition": "vaccination",
+        "description": "Clostridial booster",
+        "treatment": "Vaccine",
+        "severity": "low",
+        "status": "recovered",
+        "cost": 850
+    })
+}
+
+#[tokio::test]
+#[serial]
+async fn can_create_groups() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        let created = server
+            .post("/groups")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({
+                "name": "Heifers 2025",
+                "kind": "static",
+                "tagIds": ["AC012", "AC013", "AC014"]
+            }))
+            .await;
+
+        let dynamic = server
+            .post("/groups")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({ "name": "Jerseys", "kind": "dynamic", "filter": { "breed": "Jersey" } }))
+            .await;
+
+        let taken = server
+            .post("/groups")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({ "name": "heifers 2025", "kind": "static" }))
+            .await;
+
+        let unfiltered = server
+            .post("/groups")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({ "name": "Everything", "kind": "dynamic", "filter": {} }))
+            .await;
+
+        let unknown_tag = server
+            .post("/groups")
+            .add_header(auth_header, auth_value)
+            .json(&json!({ "name": "Sale lot 12", "kind": "static", "tagIds": ["XX999"] }))
+            .await;
+
+        with_settings!({ filters => {
+            let mut filters = crate::cleanup_uuid().to_vec();
+            filters.extend(crate::cleanup_date().to_vec());
+            filters.push((r#""id": Number\(\d+\)"#, r#""id": ID"#));
+            filters
+        }}, {
+            assert_debug_snapshot!((
+                (created.status_code(), created.json::<Value>()),
+                (dynamic.status_code(), dynamic.json::<Value>()["filter"].clone()),
+                (taken.status_code(), taken.text()),
+                (unfiltered.status_code(), unfiltered.text()),
+                (unknown_tag.status_code(), unknown_tag.text()),
+            ));
+        });
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_list_groups() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let auth = prepare_auth::auth_header(user.access_token);
+        group(&server, &auth, "Sale lot 12", &["AC001"]).await;
+        group(&server, &auth, "Heifers 2025", &["AC012"]).await;
+
+        let groups = server.get("/groups").add_header(auth.0, auth.1).await;
+
+        assert_debug_snapshot!((
+            groups.status_code(),
+            groups
+                .json::<Vec<Value>>()
+                .iter()
+                .map(|group| group["name"].clone())
+                .collect::<Vec<_>>(),
+        ));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_get_a_group() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let auth = prepare_auth::auth_header(user.access_token);
+        let heifers = group(&server, &auth, "Heifers 2025", &["AC012"]).await;
+
+        let found = server
+            .get(&format!("/groups/{heifers}"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .await;
+
+        let unknown = server
+            .get(&format!("/groups/{}", Uuid::nil()))
+            .add_header(auth.0, auth.1)
+            .await;
+
+        assert_debug_snapshot!((
+            found.status_code(),
+            found.json::<Value>()["name"].clone(),
+            unknown.status_code(),
+        ));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_update_a_group() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let auth = prepare_auth::auth_header(user.access_token);
+        let heifers = group(&server, &auth, "Heifers 2025", &["AC012"]).await;
+
+        let renamed = server
+            .patch(&format!("/groups/{heifers}"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&json!({ "name": "Heifers 2026", "notes": "Bred in spring" }))
+            .await;
+
+        let filtered = server
+            .patch(&format!("/groups/{heifers}"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&json!({ "filter": { "breed": "Jersey" } }))
+            .await;
+
+        let unknown = server
+            .patch(&format!("/groups/{}", Uuid::nil()))
+            .add_header(auth.0, auth.1)
+            .json(&json!({ "name": "Nobody" }))
+            .await;
+
+        assert_debug_snapshot!((
+            renamed.status_code(),
+            (
+                renamed.json::<Value>()["name"].clone(),
+                renamed.json::<Value>()["notes"].clone()
+            ),
+            (filtered.status_code(), filtered.text()),
+            unknown.status_code(),
+        ));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_delete_a_group() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let auth = prepare_auth::auth_header(user.access_token);
+        let heifers = group(&server, &auth, "Heifers 2025", &["AC012"]).await;
+
+        let deleted = server
+            .delete(&format!("/groups/{heifers}"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .await;
+
+        let gone = server
+            .get(&format!("/groups/{heifers}"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .await;
+
+        let again = server
+            .delete(&format!("/groups/{heifers}"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .await;
+
+        let animal = server
+            .get("/animals?tag_id=AC012")
+            .add_header(auth.0, auth.1)
+            .await;
+
+        assert_debug_snapshot!((
+            deleted.status_code(),
+            gone.status_code(),
+            again.status_code(),
+            animal.status_code(),
+        ));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_manage_members() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let auth = prepare_auth::auth_header(user.access_token);
+        let heifers = group(&server, &auth, "Heifers 2025", &["AC012", "AC013"]).await;
+
+        let added = server
+            .post(&format!("/groups/{heifers}/members"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&json!({ "tagIds": ["ac014", "AC012"] }))
+            .await;
+
+        let removed = server
+            .delete(&format!("/groups/{heifers}/members"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&json!({ "tagIds": ["AC014"] }))
+            .await;
+
+        let none = server
+            .post(&format!("/groups/{heifers}/members"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&json!({ "tagIds": [] }))
+            .await;
+
+        let unknown_tag = server
+            .post(&format!("/groups/{heifers}/members"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&json!({ "tagIds": ["XX999"] }))
+            .await;
+
+        let members = server
+            .get(&format!("/groups/{heifers}/members"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .await;
+
+        let history = server
+            .get(&format!("/groups/{heifers}/history"))
+            .add_header(auth.0, auth.1)
+            .await;
+
+        let tags = |response: &axum_test::TestResponse| {
+            response
+                .json::<Vec<Value>>()
+                .iter()
+                .map(|member| member["tagId"].clone())
+                .collect::<Vec<_>>()
+        };
+
+        assert_debug_snapshot!((
+            (added.status_code(), tags(&added)),
+            (removed.status_code(), tags(&removed)),
+            (none.status_code(), none.text()),
+            (unknown_tag.status_code(), unknown_tag.text()),
+            (members.status_code(), tags(&members)),
+            history
+                .json::<Vec<Value>>()
+                .iter()
+                .map(|stay| (
+                    stay["tagId"].clone(),
+                    stay["addedByName"].clone(),
+                    stay["removedByName"].clone()
+                ))
+                .collect::<Vec<_>>(),
+        ));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn dynamic_groups_follow_their_filter() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        let jerseys = server
+            .post("/groups")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({ "name": "Jerseys", "kind": "dynamic", "filter": { "breed": "Jersey" } }))
+            .await;
+        let jerseys = pid(&jerseys.json::<Value>());
+
+        let members = server
+            .get(&format!("/groups/{jerseys}/members"))
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+
+        let added = server
+            .post(&format!("/groups/{jerseys}/members"))
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({ "tagIds": ["AC001"] }))
+            .await;
+
+        let refiltered = server
+            .patch(&format!("/groups/{jerseys}"))
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({ "filter": { "specie": "Goat" } }))
+            .await;
+
+        let history = server
+            .get(&format!("/groups/{jerseys}/history"))
+            .add_header(auth_header, auth_value)
+            .await;
+
+        assert_debug_snapshot!((
+            members.status_code(),
+            members
+                .json::<Vec<Value>>()
+                .iter()
+                .map(|member| member["tagId"].clone())
+                .collect::<Vec<_>>(),
+            (added.status_code(), added.text()),
+            refiltered.status_code(),
+            history
+                .json::<Vec<Value>>()
+                .iter()
+                .map(|stay| (stay["tagId"].clone(), stay["leftAt"].is_string()))
+                .collect::<Vec<_>>(),
+        ));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_weigh_a_group() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let auth = prepare_auth::auth_header(user.access_token);
+        let heifers = group(&server, &auth, "Heifers 2025", &["AC012", "AC013"]).await;
+
+        let weighed = server
+            .post(&format!("/groups/{heifers}/weighings"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&weighing())
+            .await;
+
+        let outsider = server
+            .post(&format!("/groups/{heifers}/weighings"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&json!({
+                "recordDate": "2024-06-15",
+                "weights": [{ "tagId": "AC001", "mass": 45_000 }]
+            }))
+            .await;
+
+        let nothing = server
+            .post(&format!("/groups/{heifers}/weighings"))
+            .add_header(auth.0, auth.1)
+            .json(&json!({ "recordDate": "2024-06-15", "weights": [] }))
+            .await;
+
+        assert_debug_snapshot!((
+            weighed.status_code(),
+            weighed
+                .json::<Vec<Value>>()
+                .iter()
+                .map(|record| (record["mass"].clone(), record["unit"].clone()))
+                .collect::<Vec<_>>(),
+            (outsider.status_code(), outsider.text()),
+            (nothing.status_code(), nothing.text()),
+        ));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_treat_a_group() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let auth = prepare_auth::auth_header(user.access_token);
+        let heifers = group(&server, &auth, "Heifers 2025", &["AC012", "AC013"]).await;
+
+        let treated = server
+            .post(&format!("/groups/{heifers}/treatments"))
+            .add_header(auth.0, auth.1)
+            .json(&treatment())
+            .await;
+
+        assert_debug_snapshot!((
+            treated.status_code(),
+            treated
+                .json::<Vec<Value>>()
+                .iter()
+                .map(|record| (record["condition"].clone(), record["cost"].clone()))
+                .collect::<Vec<_>>(),
+        ));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_move_a_group() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let auth = prepare_auth::auth_header(user.access_token);
+        let heifers = group(&server, &auth, "Heifers 2025", &["AC012", "AC013"]).await;
+
+        let site = server
+            .post("/locations")
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&json!({ "name": "Home farm", "kind": "site" }))
+            .await;
+
+        let moved = server
+            .post(&format!("/groups/{heifers}/movements"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&json!({ "to": pid(&site.json::<Value>()), "reason": "Grazing" }))
+            .await;
+
+        let unknown_location = server
+            .post(&format!("/groups/{heifers}/movements"))
+            .add_header(auth.0, auth.1)
+            .json(&json!({ "to": Uuid::nil() }))
+            .await;
+
+        with_settings!({ filters => crate::cleanup_uuid().to_vec() }, {
+            assert_debug_snapshot!((
+                moved.status_code(),
+                moved
+                    .json::<Vec<Value>>()
+                    .iter()
+                    .map(|movement| (movement["tagId"].clone(), movement["toName"].clone()))
+                    .collect::<Vec<_>>(),
+                (unknown_location.status_code(), unknown_location.text()),
+            ));
+        });
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_summarise_a_group() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let auth = prepare_auth::auth_header(user.access_token);
+        let heifers = group(&server, &auth, "Heifers 2025", &["AC012", "AC013"]).await;
+
+        server
+            .post(&format!("/groups/{heifers}/weighings"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&weighing())
+            .await;
+
+        let summary = server
+            .get(&format!("/groups/{heifers}/summary"))
+            .add_header(auth.0, auth.1)
+            .await;
+
+        with_settings!({ filters => {
+            let mut filters = crate::cleanup_uuid().to_vec();
+            filters.extend(crate::cleanup_date().to_vec());
+            filters.push((r#""averageAgeMonths": String\("[^"]+"\)"#, r#""averageAgeMonths": AGE"#));
+            filters
+        }}, {
+            assert_debug_snapshot!((summary.status_code(), summary.json::<Value>()));
+        });
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_download_a_group_as_pdf() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let auth = prepare_auth::auth_header(user.access_token);
+        let heifers = group(&server, &auth, "Heifers 2025", &["AC012", "AC013"]).await;
+
+        let pdf = server
+            .get(&format!("/groups/{heifers}/pdf"))
+            .add_header(auth.0, auth.1)
+            .await;
+
+        assert_debug_snapshot!((
+            pdf.status_code(),
+            pdf.header("content-disposition"),
+            pdf.as_bytes().starts_with(b"%PDF"),
+        ));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn cannot_work_on_an_empty_group() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let auth = prepare_auth::auth_header(user.access_token);
+        let empty = group(&server, &auth, "Sale lot 12", &[]).await;
+
+        let weighed = server
+            .post(&format!("/groups/{empty}/weighings"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&weighing())
+            .await;
+
+        let treated = server
+            .post(&format!("/groups/{empty}/treatments"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&treatment())
+            .await;
+
+        let moved = server
+            .post(&format!("/groups/{empty}/movements"))
+            .add_header(auth.0, auth.1)
+            .json(&json!({}))
+            .await;
+
+        assert_debug_snapshot!((
+            (weighed.status_code(), weighed.text()),
+            (treated.status_code(), treated.text()),
+            (moved.status_code(), moved.text()),
+        ));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn cannot_work_on_a_deleted_group() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let auth = prepare_auth::auth_header(user.access_token);
+        let heifers = group(&server, &auth, "Heifers 2025", &["AC012", "AC013"]).await;
+
+        server
+            .delete(&format!("/groups/{heifers}"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .await;
+
+        let weighed = server
+            .post(&format!("/groups/{heifers}/weighings"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&weighing())
+            .await;
+
+        let treated = server
+            .post(&format!("/groups/{heifers}/treatments"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&treatment())
+            .await;
+
+        let moved = server
+            .post(&format!("/groups/{heifers}/movements"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&json!({}))
+            .await;
+
+        let members = server
+            .post(&format!("/groups/{heifers}/members"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&json!({ "tagIds": ["AC014"] }))
+            .await;
+
+        let summary = server
+            .get(&format!("/groups/{heifers}/summary"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .await;
+
+        let pdf = server
+            .get(&format!("/groups/{heifers}/pdf"))
+            .add_header(auth.0, auth.1)
+            .await;
+
+        assert_debug_snapshot!((
+            weighed.status_code(),
+            treated.status_code(),
+            moved.status_code(),
+            members.status_code(),
+            summary.status_code(),
+            pdf.status_code(),
+        ));
+    })
+    .await;
+}
@@ -0,0 +1,298 @@
+use axum::http::{HeaderName, HeaderValue};
+use axum_test::TestServer;
+use insta::{Settings, assert_debug_snapshot, with_settings};
+use serde_json::{Value, json};
+use serial_test::serial;
+use uuid::Uuid;
+
+use crate::{pid, request, requests::prepare_auth};
+
+macro_rules! configure_insta {
+    ($(expr:expr),*) => {
+        let mut settings = Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_path("snapshots/identifiers");
+        settings.set_snapshot_suffix("identifiers");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+/// The pid of the animal tagged `tag_id`.
+async fn find_animal(
+    server: &TestServer,
+    (auth_header, auth_value): &(HeaderName, HeaderValue),
+    tag_id: &str,
+) -> Uuid {
+    let found = server
+        .get(&format!("/animals/tag-id/{tag_id}"))
+        .add_header(auth_header.clone(), auth_value.clone())
+        .await;
+
+    pid(&found.json::<Value>())
+}
+
+/// Attaches an identifier, returning its pid.
+async fn attach(
+    server: &TestServer,
+    (auth_header, auth_value): &(HeaderName, HeaderValue),
+    animal: Uuid,
+    body: Value,
+) -> Uuid {
+    let attached = server
+        .post(&format!("/animals/{animal}/identifiers"))
+        .add_header(auth_header.clone(), auth_value.clone())
+        .json(&body)
+        .await;
+
+    pid(&attached.json::<Value>())
+}
+
+#[tokio::test]
+#[serial]
+async fn can_attach_identifiers() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let auth = prepare_auth::auth_header(user.access_token);
+        let animal = find_animal(&server, &auth, "AC003").await;
+        let other = find_animal(&server, &auth, "AC004").await;
+
+        let rfid = server
+            .post(&format!("/animals/{animal}/identifiers"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&json!({ "kind": "rfid", "value": "982 000123456789" }))
+            .await;
+
+        let retagged = server
+            .post(&format!("/animals/{animal}/identifiers"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&json!({ "kind": "visual", "value": "AC103", "reason": "lost" }))
+            .await;
+
+        let taken = server
+            .post(&format!("/animals/{other}/identifiers"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&json!({ "kind": "rfid", "value": "982000123456789" }))
+            .await;
+
+        let invalid = server
+            .post(&format!("/animals/{animal}/identifiers"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&json!({ "kind": "rfid", "value": "982000" }))
+            .await;
+
+        let unknown = server
+            .post(&format!("/animals/{}/identifiers", Uuid::nil()))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&json!({ "kind": "tattoo", "value": "AB12C" }))
+            .await;
+
+        let by_new_tag = server
+            .get("/animals/tag-id/AC103")
+            .add_header(auth.0, auth.1)
+            .await;
+
+        with_settings!({ filters => {
+            let mut filters = crate::cleanup_uuid().to_vec();
+            filters.extend(crate::cleanup_date().to_vec());
+            filters
+        }}, {
+            assert_debug_snapshot!((
+                (rfid.status_code(), rfid.json::<Value>()),
+                (retagged.status_code(), retagged.json::<Value>()["value"].clone()),
+                (taken.status_code(), taken.text()),
+                (invalid.status_code(), invalid.text()),
+                unknown.status_code(),
+                (by_new_tag.status_code(), pid(&by_new_tag.json::<Value>()) == animal),
+            ));
+        });
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_list_identifiers() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let auth = prepare_auth::auth_header(user.access_token);
+        let animal = find_animal(&server, &auth, "AC003").await;
+        attach(
+            &server,
+            &auth,
+            animal,
+            json!({ "kind": "tattoo", "value": "ab12c" }),
+        )
+        .await;
+        attach(
+            &server,
+            &auth,
+            animal,
+            json!({ "kind": "visual", "value": "AC103", "reason": "damaged" }),
+        )
+        .await;
+
+        let identifiers = server
+            .get(&format!("/animals/{animal}/identifiers"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .await;
+
+        let unknown = server
+            .get(&format!("/animals/{}/identifiers", Uuid::nil()))
+            .add_header(auth.0, auth.1)
+            .await;
+
+        assert_debug_snapshot!((
+            identifiers.status_code(),
+            identifiers
+                .json::<Vec<Value>>()
+                .iter()
+                .map(|identifier| {
+                    (
+                        identifier["kind"].clone(),
+                        identifier["value"].clone(),
+                        identifier["removalReason"].clone(),
+                    )
+                })
+                .collect::<Vec<_>>(),
+            unknown.status_code(),
+        ));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_remove_identifiers() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let auth = prepare_auth::auth_header(user.access_token);
+        let animal = find_animal(&server, &auth, "AC003").await;
+        let rfid = attach(
+            &server,
+            &auth,
+            animal,
+            json!({ "kind": "rfid", "value": "982000123456789" }),
+        )
+        .await;
+
+        let removed = server
+            .delete(&format!(
+                "/animals/{animal}/identifiers/{rfid}?reason=failed"
+            ))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .await;
+
+        let again = server
+            .delete(&format!("/animals/{animal}/identifiers/{rfid}"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .await;
+
+        let unknown = server
+            .delete(&format!("/animals/{animal}/identifiers/{}", Uuid::nil()))
+            .add_header(auth.0, auth.1)
+            .await;
+
+        assert_debug_snapshot!((
+            (
+                removed.status_code(),
+                removed.json::<Value>()["value"].clone(),
+                removed.json::<Value>()["removalReason"].clone(),
+            ),
+            (again.status_code(), again.text()),
+            unknown.status_code(),
+        ));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn cannot_remove_a_visual_tag() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let auth = prepare_auth::auth_header(user.access_token);
+        let animal = find_animal(&server, &auth, "AC003").await;
+
+        let identifiers = server
+            .get(&format!("/animals/{animal}/identifiers"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .await;
+        let visual = pid(&identifiers.json::<Vec<Value>>()[0]);
+
+        let removed = server
+            .delete(&format!("/animals/{animal}/identifiers/{visual}"))
+            .add_header(auth.0, auth.1)
+            .await;
+
+        assert_debug_snapshot!((removed.status_code(), removed.text()));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_scan_identifiers() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let auth = prepare_auth::auth_header(user.access_token);
+        let animal = find_animal(&server, &auth, "AC003").await;
+        let rfid = attach(
+            &server,
+            &auth,
+            animal,
+            json!({ "kind": "rfid", "value": "982000123456789" }),
+        )
+        .await;
+        server
+            .delete(&format!(
+                "/animals/{animal}/identifiers/{rfid}?reason=failed"
+            ))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .await;
+
+        let mut scans = vec![];
+        for raw in [
+            "AC003",
+            "FDX-B 15CD5B0780F500803AE2",
+            "15CD5B0780F500803AE3",
+            "AC999",
+        ] {
+            let scanned = server
+                .get("/animals/scan")
+                .add_query_param("raw", raw)
+                .add_header(auth.0.clone(), auth.1.clone())
+                .await;
+            let body = scanned.json::<Value>();
+            scans.push((
+                raw,
+                scanned.status_code(),
+                body["animal"]["tagId"].clone(),
+                body["identifier"]["removedAt"].is_string(),
+                body["message"].clone(),
+            ));
+        }
+
+        assert_debug_snapshot!(scans);
+    })
+    .await;
+}
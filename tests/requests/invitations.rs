@@ -0,0 +1,143 @@
+use axum::http::StatusCode;
+use insta::{Settings, assert_debug_snapshot, with_settings};
+use serde_json::{Value, json};
+use serial_test::serial;
+
+use crate::{request, requests::prepare_auth};
+
+macro_rules! configure_insta {
+    ($(expr:expr),*) => {
+        let mut settings = Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_path("snapshots/invitations");
+        settings.set_snapshot_suffix("invitations");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+#[tokio::test]
+#[serial]
+async fn invitee_can_accept_and_login() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        let invited = server
+            .post("/admin/invitations")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({
+                "email": "vet@acme.com",
+                "firstName": "Doc",
+                "lastName": "Vet",
+                "role": "staff"
+            }))
+            .await;
+        let token = invited.json::<Value>()["token"]
+            .as_str()
+            .unwrap()
+            .to_string();
+
+        let accepted = server
+            .post("/auth/accept-invitation")
+            .json(&json!({
+                "token": token,
+                "password": "Password",
+                "confirmPassword": "Password"
+            }))
+            .await;
+
+        let reused = server
+            .post("/auth/accept-invitation")
+            .json(&json!({
+                "token": token,
+                "password": "Password",
+                "confirmPassword": "Password"
+            }))
+            .await;
+
+        let login = server
+            .post("/auth/login")
+            .json(&json!({ "email": "vet@acme.com", "password": "Password" }))
+            .await;
+
+        let listed = server
+            .get("/admin/invitations")
+            .add_query_param("status", "accepted")
+            .add_header(auth_header, auth_value)
+            .await;
+
+        with_settings!({
+            filters => {
+                let mut filters = crate::cleanup_date().to_vec();
+                filters.extend(crate::cleanup_uuid().to_vec());
+                filters
+            }
+        }, {
+            assert_debug_snapshot!((
+                invited.status_code(),
+                accepted.status_code(),
+                accepted.json::<Value>(),
+                reused.json::<Value>(),
+                login.status_code(),
+                listed.json::<Value>(),
+            ));
+        });
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_resend_and_revoke() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        let invited = server
+            .post("/admin/invitations")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({
+                "email": "vet@acme.com",
+                "firstName": "Doc",
+                "lastName": "Vet",
+                "role": "staff"
+            }))
+            .await
+            .json::<Value>();
+        let pid = invited["pid"].as_str().unwrap().to_string();
+
+        let resent = server
+            .post(&format!("/admin/invitations/{pid}/resend"))
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await
+            .json::<Value>();
+
+        let revoked = server
+            .delete(&format!("/admin/invitations/{pid}"))
+            .add_header(auth_header, auth_value)
+            .await;
+
+        let accepted = server
+            .post("/auth/accept-invitation")
+            .json(&json!({
+                "token": resent["token"],
+                "password": "Password",
+                "confirmPassword": "Password"
+            }))
+            .await;
+
+        assert_ne!(invited["token"], resent["token"]);
+        assert_eq!(StatusCode::OK, revoked.status_code());
+        assert_eq!(Some("revoked"), revoked.json::<Value>()["status"].as_str());
+        assert_debug_snapshot!((accepted.status_code(), accepted.json::<Value>()));
+    })
+    .await;
+}
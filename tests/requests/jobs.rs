@@ -0,0 +1,153 @@
+use insta::{Settings, assert_debug_snapshot, with_settings};
+use polaris::jobs::Scheduler;
+use serde_json::{Value, json};
+use serial_test::serial;
+
+use crate::{request, requests::prepare_auth};
+
+macro_rules! configure_insta {
+    ($(expr:expr),*) => {
+        let mut settings = Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_path("snapshots/jobs");
+        settings.set_snapshot_suffix("jobs");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+#[tokio::test]
+#[serial]
+async fn can_manage_job_schedules() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        let invalid = server
+            .post("/jobs/schedules")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({ "kind": "deliver", "cron": "*/5 * * * *" }))
+            .await;
+
+        let created = server
+            .post("/jobs/schedules")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({ "kind": "summaries", "cron": "0 2 * * *" }))
+            .await;
+        let pid = created.json::<Value>()["pid"].as_str().unwrap().to_string();
+
+        let paused = server
+            .patch(&format!("/jobs/schedules/{pid}"))
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({ "cron": "0 4 * * MON-FRI", "isActive": false }))
+            .await;
+
+        // The platform's own schedules are not the organisation's.
+        let listed = server
+            .get("/jobs/schedules")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+
+        let removed = server
+            .delete(&format!("/jobs/schedules/{pid}"))
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+
+        let missing = server
+            .delete(&format!("/jobs/schedules/{pid}"))
+            .add_header(auth_header, auth_value)
+            .await;
+
+        crate::invite_and_accept(
+            &context.db,
+            user.user.organisation_pid(),
+            "staff@acme.com",
+            "staff",
+        )
+        .await;
+        let staff_login = server
+            .post("/auth/login")
+            .json(&json!({ "email": "staff@acme.com", "password": "Password" }))
+            .await;
+        let forbidden = server
+            .get("/jobs/schedules")
+            .add_header("authorization", staff_login.header("authorization"))
+            .await;
+
+        with_settings!({
+            filters => {
+                let mut filters = crate::cleanup_date().to_vec();
+                filters.extend(crate::cleanup_uuid().to_vec());
+                filters
+            }
+        }, {
+            assert_debug_snapshot!((
+                (invalid.status_code(), invalid.json::<Value>()),
+                (created.status_code(), created.json::<Value>()),
+                (paused.status_code(), paused.json::<Value>()),
+                listed.json::<Value>(),
+                removed.status_code(),
+                missing.status_code(),
+                forbidden.status_code(),
+            ));
+        })
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn queued_jobs_are_run_by_the_workers() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        let queued = server
+            .post("/jobs")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({ "kind": "summaries" }))
+            .await;
+
+        let platform = server
+            .post("/jobs")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({ "kind": "purge" }))
+            .await;
+
+        let ran = Scheduler::new(&context).run_next().await.unwrap().is_some();
+
+        let jobs = server
+            .get("/jobs")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+
+        let reports = server
+            .get("/reports/livestock")
+            .add_header(auth_header, auth_value)
+            .await;
+
+        with_settings!({
+            filters => {
+                let mut filters = crate::cleanup_date().to_vec();
+                filters.extend(crate::cleanup_uuid().to_vec());
+                filters
+            }
+        }, {
+            assert_debug_snapshot!((
+                (queued.status_code(), queued.json::<Value>()["status"].clone()),
+                (platform.status_code(), platform.json::<Value>()),
+                ran,
+                jobs.json::<Value>(),
+                reports.json::<Vec<Value>>().len(),
+            ));
+        })
+    })
+    .await;
+}
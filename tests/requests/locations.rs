@@ -0,0 +1,484 @@
+use axum::http::{HeaderName, HeaderValue};
+use axum_test::TestServer;
+use insta::{Settings, assert_debug_snapshot, with_settings};
+use serde_json::{Value, json};
+use serial_test::serial;
+use uuid::Uuid;
+
+use crate::{pid, request, requests::prepare_auth};
+
+macro_rules! configure_insta {
+    ($(expr:expr),*) => {
+        let mut settings = Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_path("snapshots/locations");
+        settings.set_snapshot_suffix("locations");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+/// Adds the Home farm site and a calf pen on it for `capacity` animals,
+/// returning their pids.
+async fn site_and_pen(
+    server: &TestServer,
+    (auth_header, auth_value): &(HeaderName, HeaderValue),
+    capacity: i32,
+) -> (Uuid, Uuid) {
+    let site = server
+        .post("/locations")
+        .add_header(auth_header.clone(), auth_value.clone())
+        .json(&json!({ "name": "Home farm", "kind": "site" }))
+        .await
+        .json::<Value>();
+
+    let pen = server
+        .post("/locations")
+        .add_header(auth_header.clone(), auth_value.clone())
+        .json(&json!({
+            "name": "Calf pen",
+            "kind": "pen",
+            "parent": pid(&site),
+            "capacity": capacity
+        }))
+        .await
+        .json::<Value>();
+
+    (pid(&site), pid(&pen))
+}
+
+#[tokio::test]
+#[serial]
+async fn can_add_locations() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        let invalid = server
+            .post("/locations")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({ "name": "Barn", "kind": "shed" }))
+            .await;
+
+        let site = server
+            .post("/locations")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({ "name": "Home farm", "kind": "site" }))
+            .await;
+
+        let pen = server
+            .post("/locations")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({
+                "name": "Calf pen",
+                "kind": "pen",
+                "parent": pid(&site.json::<Value>()),
+                "capacity": 1
+            }))
+            .await;
+
+        let unknown_parent = server
+            .post("/locations")
+            .add_header(auth_header, auth_value)
+            .json(&json!({ "name": "Calf pen", "kind": "pen", "parent": Uuid::nil() }))
+            .await;
+
+        with_settings!({ filters => {
+            let mut filters = crate::cleanup_uuid().to_vec();
+            filters.extend(crate::cleanup_date().to_vec());
+            filters.push((r#""id": Number\(\d+\)"#, r#""id": ID"#));
+            filters
+        }}, {
+            assert_debug_snapshot!((
+                (invalid.status_code(), invalid.text()),
+                site.status_code(),
+                (pen.status_code(), pen.json::<Value>()),
+                (unknown_parent.status_code(), unknown_parent.text()),
+            ));
+        });
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_list_locations() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let auth = prepare_auth::auth_header(user.access_token);
+        site_and_pen(&server, &auth, 1).await;
+
+        let locations = server.get("/locations").add_header(auth.0, auth.1).await;
+
+        with_settings!({ filters => {
+            let mut filters = crate::cleanup_uuid().to_vec();
+            filters.extend(crate::cleanup_date().to_vec());
+            filters.push((r#""id": Number\(\d+\)"#, r#""id": ID"#));
+            filters
+        }}, {
+            assert_debug_snapshot!((
+                locations.status_code(),
+                locations.json::<Value>()
+            ));
+        });
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_get_a_location() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let auth = prepare_auth::auth_header(user.access_token);
+        let (_, pen) = site_and_pen(&server, &auth, 1).await;
+
+        let found = server
+            .get(&format!("/locations/{pen}"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .await;
+
+        let unknown = server
+            .get(&format!("/locations/{}", Uuid::nil()))
+            .add_header(auth.0, auth.1)
+            .await;
+
+        with_settings!({ filters => {
+            let mut filters = crate::cleanup_uuid().to_vec();
+            filters.extend(crate::cleanup_date().to_vec());
+            filters.push((r#""id": Number\(\d+\)"#, r#""id": ID"#));
+            filters
+        }}, {
+            assert_debug_snapshot!((
+                (found.status_code(), found.json::<Value>()),
+                unknown.status_code(),
+            ));
+        });
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_update_a_location() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let auth = prepare_auth::auth_header(user.access_token);
+        let (_, pen) = site_and_pen(&server, &auth, 1).await;
+
+        let enlarged = server
+            .patch(&format!("/locations/{pen}"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&json!({ "name": "Weaning pen", "capacity": 2 }))
+            .await;
+
+        let unknown = server
+            .patch(&format!("/locations/{}", Uuid::nil()))
+            .add_header(auth.0, auth.1)
+            .json(&json!({ "capacity": 2 }))
+            .await;
+
+        with_settings!({ filters => {
+            let mut filters = crate::cleanup_uuid().to_vec();
+            filters.extend(crate::cleanup_date().to_vec());
+            filters.push((r#""id": Number\(\d+\)"#, r#""id": ID"#));
+            filters
+        }}, {
+            assert_debug_snapshot!((
+                (enlarged.status_code(), enlarged.json::<Value>()),
+                unknown.status_code(),
+            ));
+        });
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_delete_an_empty_location() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let auth = prepare_auth::auth_header(user.access_token);
+        let (site, pen) = site_and_pen(&server, &auth, 1).await;
+
+        server
+            .post("/locations/movements")
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&json!({ "tagIds": ["AC001"], "to": pen }))
+            .await;
+
+        let holding_locations = server
+            .delete(&format!("/locations/{site}"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .await;
+
+        let occupied = server
+            .delete(&format!("/locations/{pen}"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .await;
+
+        server
+            .post("/locations/movements")
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&json!({ "tagIds": ["AC001"] }))
+            .await;
+
+        let emptied = server
+            .delete(&format!("/locations/{pen}"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .await;
+
+        let gone = server
+            .get(&format!("/locations/{pen}"))
+            .add_header(auth.0, auth.1)
+            .await;
+
+        assert_debug_snapshot!((
+            (holding_locations.status_code(), holding_locations.text()),
+            (occupied.status_code(), occupied.text()),
+            emptied.status_code(),
+            gone.status_code(),
+        ));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_move_animals() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let auth = prepare_auth::auth_header(user.access_token);
+        let (site, pen) = site_and_pen(&server, &auth, 2).await;
+
+        let moved = server
+            .post("/locations/movements")
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&json!({
+                "tagIds": ["AC001", "AC002"],
+                "to": pen,
+                "movedOn": "2024-06-01",
+                "reason": "Weaning"
+            }))
+            .await;
+
+        let again = server
+            .post("/locations/movements")
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&json!({ "tagIds": ["AC001", "AC002"], "to": pen }))
+            .await;
+
+        let in_site = server
+            .get(&format!("/animals?location={site}"))
+            .add_header(auth.0, auth.1)
+            .await;
+
+        with_settings!({ filters => {
+            let mut filters = crate::cleanup_uuid().to_vec();
+            filters.extend(crate::cleanup_date().to_vec());
+            filters.push((r#""id": Number\(\d+\)"#, r#""id": ID"#));
+            filters
+        }}, {
+            assert_debug_snapshot!((
+                (moved.status_code(), moved.json::<Value>()),
+                (again.status_code(), again.text()),
+                in_site
+                    .json::<Vec<Value>>()
+                    .iter()
+                    .map(|animal| (animal["tagId"].clone(), animal["locationName"].clone()))
+                    .collect::<Vec<_>>(),
+            ));
+        });
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn cannot_move_animals_beyond_capacity() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let auth = prepare_auth::auth_header(user.access_token);
+        let (_, pen) = site_and_pen(&server, &auth, 1).await;
+
+        let too_many = server
+            .post("/locations/movements")
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&json!({ "tagIds": ["AC001", "AC002"], "to": pen }))
+            .await;
+
+        server
+            .post("/locations/movements")
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&json!({ "tagIds": ["AC001"], "to": pen }))
+            .await;
+
+        let full = server
+            .post("/locations/movements")
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&json!({ "tagIds": ["AC002"], "to": pen }))
+            .await;
+
+        let movements = server
+            .get(&format!("/locations/movements?location={pen}"))
+            .add_header(auth.0, auth.1)
+            .await;
+
+        assert_debug_snapshot!((
+            (too_many.status_code(), too_many.text()),
+            (full.status_code(), full.text()),
+            movements.json::<Vec<Value>>().len(),
+        ));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn cannot_move_unknown_animals() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let auth = prepare_auth::auth_header(user.access_token);
+        let (_, pen) = site_and_pen(&server, &auth, 5).await;
+
+        let unknown_animal = server
+            .post("/locations/movements")
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&json!({ "tagIds": ["AC001", "XX999"], "to": pen }))
+            .await;
+
+        let unknown_location = server
+            .post("/locations/movements")
+            .add_header(auth.0, auth.1)
+            .json(&json!({ "tagIds": ["AC001"], "to": Uuid::nil() }))
+            .await;
+
+        with_settings!({ filters => {
+            let mut filters = crate::cleanup_uuid().to_vec();
+            filters.extend(crate::cleanup_date().to_vec());
+            filters.push((r#""id": Number\(\d+\)"#, r#""id": ID"#));
+            filters
+        }}, {
+            assert_debug_snapshot!((
+                (unknown_animal.status_code(), unknown_animal.text()),
+                (unknown_location.status_code(), unknown_location.text()),
+            ));
+        });
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_list_movements() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let auth = prepare_auth::auth_header(user.access_token);
+        let (_, pen) = site_and_pen(&server, &auth, 2).await;
+
+        server
+            .post("/locations/movements")
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&json!({ "tagIds": ["AC001", "AC002"], "to": pen, "movedOn": "2024-05-01" }))
+            .await;
+
+        server
+            .post("/locations/movements")
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&json!({ "tagIds": ["AC001"], "movedOn": "2024-06-01" }))
+            .await;
+
+        let in_june = server
+            .get(&format!(
+                "/locations/movements?location={pen}&from=2024-06-01"
+            ))
+            .add_header(auth.0, auth.1)
+            .await;
+
+        with_settings!({ filters => {
+            let mut filters = crate::cleanup_uuid().to_vec();
+            filters.extend(crate::cleanup_date().to_vec());
+            filters.push((r#""id": Number\(\d+\)"#, r#""id": ID"#));
+            filters
+        }}, {
+            assert_debug_snapshot!((
+                in_june.status_code(),
+                in_june.json::<Value>()
+            ));
+        });
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_report_occupancy() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let auth = prepare_auth::auth_header(user.access_token);
+        let (_, pen) = site_and_pen(&server, &auth, 2).await;
+
+        server
+            .post("/locations/movements")
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&json!({ "tagIds": ["AC001", "AC002"], "to": pen }))
+            .await;
+
+        let occupancy = server
+            .get("/locations/occupancy")
+            .add_header(auth.0, auth.1)
+            .await;
+
+        with_settings!({ filters => {
+            let mut filters = crate::cleanup_uuid().to_vec();
+            filters.extend(crate::cleanup_date().to_vec());
+            filters.push((r#""id": Number\(\d+\)"#, r#""id": ID"#));
+            filters
+        }}, {
+            assert_debug_snapshot!((
+                occupancy.status_code(),
+                occupancy.json::<Value>()
+            ));
+        });
+    })
+    .await;
+}
@@ -0,0 +1,98 @@
+use insta::{Settings, assert_debug_snapshot, with_settings};
+use serde_json::{Value, json};
+use serial_test::serial;
+use uuid::Uuid;
+
+use crate::{request, requests::prepare_auth};
+
+macro_rules! configure_insta {
+    ($(expr:expr),*) => {
+        let mut settings = Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_path("snapshots/memberships");
+        settings.set_snapshot_suffix("memberships");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+const CONTINENTAL: &str = "4a0f3af9-e56e-4e21-8f3a-f9e56efe215b";
+const GLOBEX: &str = "4a93f0a8-4a91-482d-92d8-f0b3b084c2e4";
+
+#[tokio::test]
+#[serial]
+async fn member_can_switch_organisation() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let continental = Uuid::parse_str(CONTINENTAL).unwrap();
+        crate::invite_and_accept(&context.db, continental, "john.doe@acme.com", "staff").await;
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        let organisations = server
+            .get("/auth/organisations")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+
+        let switched = server
+            .post("/auth/switch-organisation")
+            .add_header(auth_header.clone(), auth_value)
+            .json(&json!({ "organisation": CONTINENTAL }))
+            .await;
+        let switched_value = switched.header("authorization");
+
+        let current = server
+            .get("/auth/current")
+            .add_header(auth_header.clone(), switched_value.clone())
+            .await;
+
+        // Staff cannot manage users, unlike the admin John is at home.
+        let users = server
+            .get("/admin/users")
+            .add_header(auth_header, switched_value)
+            .await;
+
+        with_settings!({
+            filters => {
+                let mut filters = crate::cleanup_date().to_vec();
+                filters.extend(crate::cleanup_uuid().to_vec());
+                filters
+            }
+        }, {
+            assert_debug_snapshot!((
+                organisations.status_code(),
+                organisations.json::<Value>(),
+                switched.status_code(),
+                current.json::<Value>()["organisationPid"].clone(),
+                current.json::<Value>()["role"].clone(),
+                users.status_code(),
+            ));
+        });
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn cannot_switch_to_foreign_organisation() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        let response = server
+            .post("/auth/switch-organisation")
+            .add_header(auth_header, auth_value)
+            .json(&json!({ "organisation": GLOBEX }))
+            .await;
+
+        assert_debug_snapshot!((response.status_code(), response.text()));
+    })
+    .await;
+}
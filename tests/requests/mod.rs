@@ -1,11 +1,31 @@
 mod admin;
+mod alerts;
 mod animals;
+mod api_keys;
+mod audit;
 mod auth;
 mod breeds;
+mod events;
+mod feed;
+mod finance;
+mod groups;
 mod health;
+mod identifiers;
+mod invitations;
+mod jobs;
+mod locations;
+mod memberships;
+mod oidc;
+mod organisation;
+mod platform;
 mod prepare_auth;
 mod production;
 mod reports;
+mod roles;
+mod subscription;
+mod tasks;
+mod trash;
+mod webhooks;
 mod weight;
 
 pub use self::prepare_auth::*;
@@ -0,0 +1,158 @@
+use axum::http::StatusCode;
+use insta::{Settings, assert_debug_snapshot, with_settings};
+use serde_json::{Value, json};
+use serial_test::serial;
+
+use crate::{request, requests::prepare_auth};
+
+macro_rules! configure_insta {
+    ($(expr:expr),*) => {
+        let mut settings = Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_path("snapshots/organisation");
+        settings.set_snapshot_suffix("organisation");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+#[tokio::test]
+#[serial]
+async fn can_get_current() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        let response = server
+            .get("/organisation")
+            .add_header(auth_header, auth_value)
+            .await;
+
+        with_settings!({
+            filters => {
+                let mut filters = crate::cleanup_date().to_vec();
+                filters.extend(crate::cleanup_uuid().to_vec());
+                filters
+            }
+        }, {
+            assert_debug_snapshot!((response.status_code(), response.json::<Value>()));
+        });
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_update_profile() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        let response = server
+            .patch("/organisation")
+            .add_header(auth_header, auth_value)
+            .json(&json!({
+                "address": "12 Ranch Road, Nakuru",
+                "phone": "+254700000000"
+            }))
+            .await;
+
+        with_settings!({
+            filters => {
+                let mut filters = crate::cleanup_date().to_vec();
+                filters.extend(crate::cleanup_uuid().to_vec());
+                filters
+            }
+        }, {
+            assert_debug_snapshot!((response.status_code(), response.json::<Value>()));
+        });
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn cannot_save_invalid_settings() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        let response = server
+            .patch("/organisation/settings")
+            .add_header(auth_header, auth_value)
+            .json(&json!({ "timezone": "Africa/Mars" }))
+            .await;
+
+        assert_debug_snapshot!((response.status_code(), response.json::<Value>()));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn settings_drive_module_defaults() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        let settings = server
+            .patch("/organisation/settings")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({
+                "currency": "KES",
+                "weightUnit": "lb",
+                "tagId": { "prefix": "AC", "uppercase": true }
+            }))
+            .await;
+
+        let animal = server
+            .post("/animals")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({
+               "tagId": "ac025",
+               "name": "Geoffrey",
+               "specie": "cattle",
+               "breed": "Friesian",
+               "gender": "male",
+               "status": "active",
+               "dateOfBirth": "2023-10-16"
+            }))
+            .await;
+
+        let weight = server
+            .post("/weight-records")
+            .add_header(auth_header, auth_value)
+            .json(&json!({
+                "tagId": "AC001",
+                "recordDate": "2024-11-05",
+                "mass": 48500
+            }))
+            .await;
+        let weight = weight.json::<Value>();
+
+        assert_eq!(StatusCode::OK, settings.status_code());
+        assert_debug_snapshot!((
+            settings.json::<Value>(),
+            animal.status_code(),
+            animal.json::<Value>(),
+            weight["unit"].clone(),
+            weight["status"].clone(),
+        ));
+    })
+    .await;
+}
@@ -0,0 +1,294 @@
+use axum::http::StatusCode;
+use insta::{Settings, assert_debug_snapshot, with_settings};
+use polaris::models::users::User;
+use serde_json::{Value, json};
+use serial_test::serial;
+
+use crate::{request, requests::prepare_auth};
+
+macro_rules! configure_insta {
+    ($(expr:expr),*) => {
+        let mut settings = Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_path("snapshots/platform");
+        settings.set_snapshot_suffix("platform");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+const CONTINENTAL: &str = "4a0f3af9-e56e-4e21-8f3a-f9e56efe215b";
+
+#[tokio::test]
+#[serial]
+async fn rejects_non_operators() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        let response = server
+            .get("/platform/organisations")
+            .add_header(auth_header, auth_value)
+            .await;
+
+        assert_debug_snapshot!((response.status_code(), response.text()));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_search_organisations() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        user.user
+            .set_platform_operator(&context.db, true)
+            .await
+            .unwrap();
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        let response = server
+            .get("/platform/organisations")
+            .add_query_param("search", "corp")
+            .add_query_param("subscription", "business")
+            .add_header(auth_header, auth_value)
+            .await;
+
+        with_settings!({
+            filters => {
+                let mut filters = crate::cleanup_date().to_vec();
+                filters.extend(crate::cleanup_uuid().to_vec());
+                filters
+            }
+        }, {
+            assert_debug_snapshot!((response.status_code(), response.json::<Value>()));
+        });
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_change_subscription_with_audit_trail() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        user.user
+            .set_platform_operator(&context.db, true)
+            .await
+            .unwrap();
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        let changed = server
+            .patch(&format!(
+                "/platform/organisations/{CONTINENTAL}/subscription"
+            ))
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&serde_json::json!({ "subscription": "business" }))
+            .await;
+
+        let audit = server
+            .get("/platform/audit")
+            .add_query_param("organisation", CONTINENTAL)
+            .add_header(auth_header, auth_value)
+            .await;
+
+        with_settings!({
+            filters => {
+                let mut filters = crate::cleanup_date().to_vec();
+                filters.extend(crate::cleanup_uuid().to_vec());
+                filters.push((r#""id": Number\(\d+\)"#, r#""id": ID"#));
+                filters
+            }
+        }, {
+            assert_debug_snapshot!((
+                changed.status_code(),
+                changed.json::<Value>(),
+                audit.json::<Value>(),
+            ));
+        });
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn suspension_blocks_organisation_users() {
+    request(|server, context| async move {
+        crate::seed_data(&context.db).await.unwrap();
+
+        let operator = prepare_auth::login_user(&server, &context).await;
+        operator
+            .user
+            .set_platform_operator(&context.db, true)
+            .await
+            .unwrap();
+        let (auth_header, auth_value) = prepare_auth::auth_header(operator.access_token);
+
+        let member = User::find_by_email(&context.db, "james.moriaty@continental.org")
+            .await
+            .unwrap()
+            .unwrap();
+        let token = context.auth.access.jwt(&member).unwrap();
+        let (member_header, member_value) =
+            prepare_auth::auth_header(format!("Bearer {token}").parse().unwrap());
+
+        let suspended = server
+            .patch(&format!("/platform/organisations/{CONTINENTAL}/suspend"))
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&serde_json::json!({ "reason": "Unpaid invoices" }))
+            .await;
+
+        let blocked = server
+            .get("/animals")
+            .add_header(member_header.clone(), member_value.clone())
+            .await;
+
+        let unsuspended = server
+            .patch(&format!("/platform/organisations/{CONTINENTAL}/unsuspend"))
+            .add_header(auth_header, auth_value)
+            .await;
+
+        let allowed = server
+            .get("/animals")
+            .add_header(member_header, member_value)
+            .await;
+
+        assert_eq!(StatusCode::OK, suspended.status_code());
+        assert_eq!(Some(true), suspended.json::<Value>()["suspended"].as_bool());
+        assert_eq!(StatusCode::FORBIDDEN, blocked.status_code());
+        assert_eq!(StatusCode::OK, unsuspended.status_code());
+        assert_eq!(StatusCode::OK, allowed.status_code());
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_impersonate_organisation_admin() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let operator = prepare_auth::login_user(&server, &context).await;
+        operator
+            .user
+            .set_platform_operator(&context.db, true)
+            .await
+            .unwrap();
+        let (auth_header, auth_value) = prepare_auth::auth_header(operator.access_token);
+
+        let response = server
+            .post(&format!(
+                "/platform/organisations/{CONTINENTAL}/impersonate"
+            ))
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+        let token = response.json::<Value>()["accessToken"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        let (_, impersonated) =
+            prepare_auth::auth_header(format!("Bearer {token}").parse().unwrap());
+
+        let current = server
+            .get("/auth/current")
+            .add_header(auth_header.clone(), impersonated.clone())
+            .await;
+
+        let console = server
+            .get("/platform/organisations")
+            .add_header(auth_header.clone(), impersonated)
+            .await;
+
+        let audit = server
+            .get("/platform/audit")
+            .add_query_param("action", "impersonate")
+            .add_header(auth_header, auth_value)
+            .await;
+        let audited = audit
+            .json::<Vec<Value>>()
+            .iter()
+            .map(|entry| (entry["action"].clone(), entry["details"].clone()))
+            .collect::<Vec<_>>();
+
+        assert_debug_snapshot!((
+            response.status_code(),
+            current.json::<Value>()["email"].clone(),
+            console.status_code(),
+            audited,
+        ));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn impersonated_writes_are_audited_as_the_operator() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let operator = prepare_auth::login_user(&server, &context).await;
+        operator
+            .user
+            .set_platform_operator(&context.db, true)
+            .await
+            .unwrap();
+        let (auth_header, auth_value) = prepare_auth::auth_header(operator.access_token);
+
+        let response = server
+            .post(&format!(
+                "/platform/organisations/{CONTINENTAL}/impersonate"
+            ))
+            .add_header(auth_header.clone(), auth_value)
+            .await;
+        let token = response.json::<Value>()["accessToken"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        let (_, impersonated) =
+            prepare_auth::auth_header(format!("Bearer {token}").parse().unwrap());
+
+        let created = server
+            .post("/breeds")
+            .json(&json!({
+                "name": "Fleckvieh",
+                "description": "A dual purpose breed of cattle",
+                "typicalGestationPeriod": "285 days",
+                "specie": "cattle",
+                "typicalMaleWeightRange": "1000-1300",
+                "typicalFemaleWeightRange": "600-800"
+            }))
+            .add_header(auth_header.clone(), impersonated.clone())
+            .await;
+
+        let audit = server
+            .get("/audit")
+            .add_query_param("table", "breeds")
+            .add_header(auth_header, impersonated)
+            .await;
+        let audited = audit.json::<Vec<Value>>();
+
+        assert_eq!(StatusCode::CREATED, created.status_code());
+        assert_eq!(1, audited.len());
+        assert_eq!(
+            operator.user.pid().to_string(),
+            audited[0]["impersonatorPid"].as_str().unwrap()
+        );
+        assert_ne!(audited[0]["changedBy"], audited[0]["impersonatorPid"]);
+    })
+    .await;
+}
@@ -1,6 +1,6 @@
 use axum::http::StatusCode;
 use insta::{Settings, assert_debug_snapshot, with_settings};
-use polaris::models::SpecieSummary;
+use polaris::models::{SpecieSummary, enums::Subscription, orgs::Organisation};
 use serial_test::serial;
 
 use crate::request;
@@ -29,6 +29,14 @@ async fn can_generate_one() {
             .unwrap();
 
         let user = prepare_auth::login_user(&server, &context).await;
+        Organisation::change_subscription(
+            &context.db,
+            user.user.organisation_pid(),
+            &Subscription::Business,
+        )
+        .await
+        .unwrap();
+
         let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
 
         let request = server
@@ -64,6 +72,14 @@ async fn can_get_all() {
             .unwrap();
 
         let user = prepare_auth::login_user(&server, &context).await;
+        Organisation::change_subscription(
+            &context.db,
+            user.user.organisation_pid(),
+            &Subscription::Business,
+        )
+        .await
+        .unwrap();
+
         let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
 
         let request = server
@@ -0,0 +1,113 @@
+use insta::{Settings, assert_debug_snapshot};
+use polaris::models::{enums::Subscription, orgs::Organisation};
+use serde_json::Value;
+use serial_test::serial;
+
+use crate::request;
+
+use crate::requests::prepare_auth;
+
+macro_rules! configure_insta {
+    ($(expr:expr),*) => {
+        let mut settings = Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_path("snapshots/reports");
+        settings.set_snapshot_suffix("live");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+/// Drops what changes from day to day: the average age and when the
+/// summary last changed.
+fn settled(mut summary: Value) -> Value {
+    if let Some(summary) = summary.as_object_mut() {
+        summary.remove("averageAgeMonths");
+        summary.remove("updatedAt");
+    }
+    summary
+}
+
+#[tokio::test]
+#[serial]
+async fn live_summaries_need_no_generating() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        let before = server
+            .get("/reports/livestock/live")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+
+        // An animal dies and nobody generates a summary.
+        sqlx::query(
+            "UPDATE animals SET status = 'deceased'
+            WHERE id = (SELECT MIN(id) FROM animals WHERE organisation_pid = $1 AND status = 'active')",
+        )
+        .bind(user.user.organisation_pid())
+        .execute(&context.db)
+        .await
+        .unwrap();
+
+        let after = server
+            .get("/reports/livestock/live")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+
+        let dashboard = server
+            .get("/dashboard")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+
+        let outside_plan = server
+            .get("/reports/categories/live")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+
+        Organisation::change_subscription(
+            &context.db,
+            user.user.organisation_pid(),
+            &Subscription::Enterprise,
+        )
+        .await
+        .unwrap();
+
+        let species = server
+            .get("/reports/categories/live?specie=cattle")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+
+        let breeds = server
+            .get("/reports/breeds/live?specie=cattle")
+            .add_header(auth_header, auth_value)
+            .await;
+
+        assert_debug_snapshot!((
+            (before.status_code(), settled(before.json::<Value>())),
+            (after.status_code(), settled(after.json::<Value>())),
+            settled(dashboard.json::<Value>()["liveSummary"].clone()),
+            outside_plan.status_code(),
+            (
+                species.status_code(),
+                species
+                    .json::<Vec<Value>>()
+                    .into_iter()
+                    .map(settled)
+                    .collect::<Vec<_>>()
+            ),
+            (
+                breeds.status_code(),
+                breeds
+                    .json::<Vec<Value>>()
+                    .into_iter()
+                    .map(settled)
+                    .collect::<Vec<_>>()
+            ),
+        ));
+    })
+    .await;
+}
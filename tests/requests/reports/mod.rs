@@ -1 +1,4 @@
 mod category;
+mod live;
+mod pdf;
+mod trends;
@@ -0,0 +1,145 @@
+use axum::http::header;
+use insta::{Settings, assert_debug_snapshot};
+use polaris::models::{enums::Subscription, orgs::Organisation};
+use serde_json::{Value, json};
+use serial_test::serial;
+
+use crate::request;
+
+use crate::requests::prepare_auth;
+
+macro_rules! configure_insta {
+    ($(expr:expr),*) => {
+        let mut settings = Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_path("snapshots/reports");
+        settings.set_snapshot_suffix("pdf");
+        settings.add_filter(r"inventory-\d{4}-\d{2}-\d{2}", "inventory-TODAY");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+#[tokio::test]
+#[serial]
+async fn reports_download_as_pdfs() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        Organisation::change_subscription(
+            &context.db,
+            user.user.organisation_pid(),
+            &Subscription::Enterprise,
+        )
+        .await
+        .unwrap();
+
+        let mut downloads = Vec::new();
+        for path in [
+            "/reports/livestock/pdf",
+            "/reports/categories/pdf?specie=cattle",
+            "/reports/breeds/pdf?specie=cattle",
+            "/reports/animals/b2bd6270-8bec-42ce-99ff-d0eb1a076221/health/pdf",
+            "/reports/production/pdf?from=2024-06-01&to=2024-06-30",
+        ] {
+            let response = server
+                .get(path)
+                .add_header(auth_header.clone(), auth_value.clone())
+                .await;
+
+            downloads.push((
+                path,
+                response.status_code(),
+                response.header(header::CONTENT_TYPE),
+                response.header(header::CONTENT_DISPOSITION),
+                response.as_bytes().starts_with(b"%PDF-"),
+            ));
+        }
+
+        assert_debug_snapshot!(downloads);
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn pdf_reports_check_what_they_are_asked_for() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        let mut responses = Vec::new();
+        for path in [
+            "/reports/categories/pdf",
+            "/reports/animals/00000000-0000-0000-0000-000000000000/health/pdf",
+            "/reports/production/pdf?from=2024-06-30&to=2024-06-01",
+            "/reports/production/pdf?from=2024-06-01",
+        ] {
+            let response = server
+                .get(path)
+                .add_header(auth_header.clone(), auth_value.clone())
+                .await;
+
+            responses.push((path, response.status_code(), response.text()));
+        }
+
+        assert_debug_snapshot!(responses);
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn pdf_reports_carry_the_organisation_branding() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        let bad_colour = server
+            .patch("/organisation/settings")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({ "branding": { "colour": "green" } }))
+            .await;
+
+        let branded = server
+            .patch("/organisation/settings")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({ "branding": { "colour": "#1565c0", "footer": "Acme Corp, est. 1999" } }))
+            .await;
+
+        let report = server
+            .get("/reports/livestock/pdf")
+            .add_header(auth_header, auth_value)
+            .await;
+        let bytes = report.as_bytes();
+        let footer = "Acme Corp, est. 1999"
+            .bytes()
+            .map(|byte| format!("{byte:02X}"))
+            .collect::<String>();
+
+        assert_debug_snapshot!((
+            (bad_colour.status_code(), bad_colour.text()),
+            (
+                branded.status_code(),
+                branded.json::<Value>()["branding"].clone()
+            ),
+            report.status_code(),
+            // Text is drawn hex encoded, and the accent in 0 to 1 parts.
+            String::from_utf8_lossy(bytes).contains(&footer),
+            String::from_utf8_lossy(bytes).contains("0.08235294 0.39607844 0.7529412 rg"),
+        ));
+    })
+    .await;
+}
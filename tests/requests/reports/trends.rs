@@ -0,0 +1,264 @@
+use insta::{Settings, assert_debug_snapshot};
+use polaris::models::{enums::Subscription, orgs::Organisation, settings::OrganisationSettings};
+use serde_json::Value;
+use serial_test::serial;
+use sqlx::PgPool;
+
+use crate::request;
+
+use crate::requests::prepare_auth;
+
+macro_rules! configure_insta {
+    ($(expr:expr),*) => {
+        let mut settings = Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_path("snapshots/reports");
+        settings.set_snapshot_suffix("trends");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+/// Moves the latest snapshot in `table` back to `date`, so each call leaves
+/// one more dated snapshot behind.
+async fn backdate(db: &PgPool, table: &str, date: &str) {
+    sqlx::query(&format!(
+        "UPDATE {table} SET created_at = $1::date + TIME '12:00'
+        WHERE id = (SELECT MAX(id) FROM {table})"
+    ))
+    .bind(date)
+    .execute(db)
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+#[serial]
+async fn livestock_trends_take_the_latest_snapshot_of_each_period() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        for date in ["2025-01-10", "2025-02-03", "2025-02-25", "2025-04-14"] {
+            server
+                .post("/reports/livestock")
+                .add_header(auth_header.clone(), auth_value.clone())
+                .await;
+            backdate(&context.db, "livestock_summary", date).await;
+        }
+
+        // The herd grew during February and shrank by April.
+        sqlx::query(
+            "UPDATE livestock_summary SET total = total + 4, active = active + 4
+            WHERE created_at::date = '2025-02-25'",
+        )
+        .execute(&context.db)
+        .await
+        .unwrap();
+        sqlx::query(
+            "UPDATE livestock_summary SET total = total - 2, deceased = deceased + 2
+            WHERE created_at::date = '2025-04-14'",
+        )
+        .execute(&context.db)
+        .await
+        .unwrap();
+
+        let monthly = server
+            .get("/reports/livestock/trends?metric=total&from=2025-01-01&to=2025-04-30")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+
+        let quarterly = server
+            .get("/reports/livestock/trends?metric=deceased&from=2025-01-01&to=2025-06-30&interval=quarter")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+
+        let mut invalid = Vec::new();
+        for query in [
+            "metric=average_weight",
+            "metric=births",
+            "metric=total&interval=year",
+            "metric=total&from=2025-03-01&to=2025-01-01",
+            "metric=total&from=2023-01-01&to=2025-01-01&interval=day",
+        ] {
+            let response = server
+                .get(&format!("/reports/livestock/trends?{query}"))
+                .add_header(auth_header.clone(), auth_value.clone())
+                .await;
+            invalid.push((response.status_code(), response.json::<Value>()));
+        }
+
+        assert_debug_snapshot!((
+            (monthly.status_code(), monthly.json::<Value>()),
+            (quarterly.status_code(), quarterly.json::<Value>()),
+            invalid,
+        ));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn category_trends_compare_species() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        // Basic plans have no category reports, nor their trends.
+        let outside_plan = server
+            .get("/reports/categories/trends?metric=total")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+
+        Organisation::change_subscription(
+            &context.db,
+            user.user.organisation_pid(),
+            &Subscription::Business,
+        )
+        .await
+        .unwrap();
+
+        for date in ["2025-01-20", "2025-02-20"] {
+            server
+                .post("/reports/categories?specie=cattle")
+                .add_header(auth_header.clone(), auth_value.clone())
+                .await;
+            backdate(&context.db, "species_summary", date).await;
+        }
+
+        // The cattle put on weight over the winter.
+        sqlx::query(
+            "UPDATE species_summary SET average_weight_male = average_weight_male + 30
+            WHERE created_at::date = '2025-02-20'",
+        )
+        .execute(&context.db)
+        .await
+        .unwrap();
+
+        // A flock of sheep kept alongside, counted once in January.
+        sqlx::query(
+            "INSERT INTO species_summary (
+                organisation_pid, specie_name, specie_id, total, males, females, active,
+                average_weight_male, average_weight_female, total_purchase_value, created_at
+            )
+            VALUES ($1, 'sheep', 2, 30, 10, 20, 30, 80, 65, 4500, '2025-01-05 08:00')",
+        )
+        .bind(user.user.organisation_pid())
+        .execute(&context.db)
+        .await
+        .unwrap();
+
+        let compared = server
+            .get("/reports/categories/trends?metric=average_weight&from=2025-01-01&to=2025-03-31&compare=sheep,Cattle")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+
+        let sheep = server
+            .get("/reports/categories/trends?metric=purchase_value&from=2025-01-01&to=2025-01-31&interval=week&compare=sheep")
+            .add_header(auth_header, auth_value)
+            .await;
+
+        assert_debug_snapshot!((
+            outside_plan.status_code(),
+            (compared.status_code(), compared.json::<Value>()),
+            (sheep.status_code(), sheep.json::<Value>()),
+        ));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn breed_trends_follow_each_breed() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        Organisation::change_subscription(
+            &context.db,
+            user.user.organisation_pid(),
+            &Subscription::Enterprise,
+        )
+        .await
+        .unwrap();
+
+        for date in ["2025-01-15", "2025-04-15", "2025-07-15"] {
+            server
+                .post("/reports/breeds?breed=Jersey&specie=cattle")
+                .add_header(auth_header.clone(), auth_value.clone())
+                .await;
+            backdate(&context.db, "breed_summary", date).await;
+        }
+
+        sqlx::query(
+            "UPDATE breed_summary SET active = active - 1 WHERE created_at::date = '2025-07-15'",
+        )
+        .execute(&context.db)
+        .await
+        .unwrap();
+
+        let quarterly = server
+            .get("/reports/breeds/trends?metric=active&specie=cattle&from=2025-01-01&to=2025-09-30&interval=quarter")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+
+        let other_specie = server
+            .get("/reports/breeds/trends?metric=active&specie=goats&from=2025-01-01&to=2025-09-30")
+            .add_header(auth_header, auth_value)
+            .await;
+
+        assert_debug_snapshot!((
+            (quarterly.status_code(), quarterly.json::<Value>()),
+            (other_specie.status_code(), other_specie.json::<Value>()),
+        ));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn trends_end_today_in_the_organisations_timezone() {
+    request(|server, context| async move {
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let org_pid = user.user.organisation_pid();
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        // A day apart, so one of them differs from the server's date.
+        for timezone in ["+14:00", "-12:00"] {
+            sqlx::query(
+                "UPDATE organisations SET settings = jsonb_set(settings, '{timezone}', to_jsonb($2::text))
+                WHERE pid = $1",
+            )
+            .bind(org_pid)
+            .bind(timezone)
+            .execute(&context.db)
+            .await
+            .unwrap();
+            let today = OrganisationSettings::find(&context.db, org_pid)
+                .await
+                .unwrap()
+                .today();
+
+            let trend = server
+                .get("/reports/livestock/trends?metric=total")
+                .add_header(auth_header.clone(), auth_value.clone())
+                .await;
+
+            assert_eq!(today.to_string(), trend.json::<Value>()["to"]);
+        }
+    })
+    .await;
+}
@@ -0,0 +1,141 @@
+use insta::{Settings, assert_debug_snapshot, with_settings};
+use rstest::rstest;
+use serial_test::serial;
+
+macro_rules! configure_insta {
+    ($(expr:expr),*) => {
+        let mut settings = Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_path("snapshots/roles");
+        settings.set_snapshot_suffix("roles");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+#[tokio::test]
+#[serial]
+async fn can_list_roles() {
+    crate::request(|server, context| async move {
+        configure_insta!();
+
+        let admin_login = super::prepare_auth::init_login(&server, &context).await;
+        let (auth_header, auth_value) = super::prepare_auth::auth_header(admin_login.access_token);
+
+        let response = server
+            .get("/roles")
+            .add_header(auth_header, auth_value)
+            .await;
+
+        with_settings!({
+            filters => {
+                let mut combined_filters = crate::cleanup_date().to_vec();
+                combined_filters.extend(crate::cleanup_int().iter().copied());
+                combined_filters
+            }
+        }, {
+            assert_debug_snapshot!((response.status_code(), response.text()));
+        });
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_create_role_and_assign_it() {
+    crate::request(|server, context| async move {
+        configure_insta!();
+
+        let admin_login = super::prepare_auth::init_login(&server, &context).await;
+        let (auth_header, auth_value) = super::prepare_auth::auth_header(admin_login.access_token);
+
+        let created = server
+            .post("/roles")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&serde_json::json!({
+                "name": "vet",
+                "description": "Can only edit health records",
+                "permissions": ["animals:read", "health_records:read", "health_records:write"]
+            }))
+            .await;
+
+        let assigned = server
+            .post("/admin/invitations")
+            .add_header(auth_header, auth_value)
+            .json(&serde_json::json!({
+               "email": "vet@mail.com",
+               "firstName": "Doc",
+               "lastName": "Vet",
+               "role": "vet"
+            }))
+            .await;
+
+        with_settings!({
+            filters => {
+                let mut combined_filters = crate::cleanup_date().to_vec();
+                combined_filters.extend(crate::cleanup_int().iter().copied());
+                combined_filters.extend(crate::cleanup_uuid().iter().copied());
+                combined_filters.push((r"PID\.[A-Za-z0-9]{32}", "TOKEN"));
+                combined_filters
+            }
+        }, {
+            assert_debug_snapshot!((
+                created.status_code(),
+                created.text(),
+                assigned.status_code(),
+                assigned.text()
+            ));
+        });
+    })
+    .await;
+}
+
+#[rstest]
+#[case("custom_role_can_read_animals", "get", "/animals")]
+#[case("custom_role_cannot_add_breed", "post", "/breeds")]
+#[case(
+    "custom_role_cannot_delete_health_record",
+    "delete",
+    "/health-records/1"
+)]
+#[case("custom_role_cannot_list_users", "get", "/admin/users")]
+#[case("custom_role_cannot_read_reports", "get", "/reports/livestock")]
+#[tokio::test]
+#[serial]
+async fn enforces_custom_role_permissions(
+    #[case] name: &str,
+    #[case] method: &str,
+    #[case] uri: &str,
+) {
+    crate::request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let login = super::prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = super::prepare_auth::auth_header(login.access_token);
+
+        sqlx::query(
+            "INSERT INTO roles (organisation_pid, name, permissions) VALUES ($1, 'vet',
+            '{\"scopes\": [\"animals:read\", \"health_records:write\"]}'::jsonb)",
+        )
+        .bind(login.user.organisation_pid())
+        .execute(&context.db)
+        .await
+        .unwrap();
+        sqlx::query("UPDATE users SET role = 'vet' WHERE pid = $1")
+            .bind(login.user.pid())
+            .execute(&context.db)
+            .await
+            .unwrap();
+
+        let request = match method {
+            "post" => server.post(uri).json(&serde_json::json!({})),
+            "delete" => server.delete(uri),
+            _ => server.get(uri),
+        };
+        let response = request.add_header(auth_header, auth_value).await;
+
+        assert_debug_snapshot!(name, response.status_code());
+    })
+    .await;
+}
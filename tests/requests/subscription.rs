@@ -0,0 +1,122 @@
+use axum::http::StatusCode;
+use insta::{Settings, assert_debug_snapshot};
+use polaris::models::{enums::Subscription, orgs::Organisation};
+use serde_json::Value;
+use serial_test::serial;
+
+use crate::{request, requests::prepare_auth};
+
+macro_rules! configure_insta {
+    ($(expr:expr),*) => {
+        let mut settings = Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_path("snapshots/subscription");
+        settings.set_snapshot_suffix("subscription");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+#[tokio::test]
+#[serial]
+async fn can_get_usage_against_limits() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        let response = server
+            .get("/subscription")
+            .add_header(auth_header, auth_value)
+            .await;
+
+        assert_debug_snapshot!((response.status_code(), response.json::<Value>()));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn cannot_use_reports_outside_plan() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        let response = server
+            .post("/reports/breeds")
+            .add_header(auth_header, auth_value)
+            .await;
+
+        assert_debug_snapshot!((response.status_code(), response.json::<Value>()));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_use_reports_after_upgrade() {
+    request(|server, context| async move {
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        Organisation::change_subscription(
+            &context.db,
+            user.user.organisation_pid(),
+            &Subscription::Business,
+        )
+        .await
+        .unwrap();
+
+        let response = server
+            .get("/reports/breeds")
+            .add_header(auth_header, auth_value)
+            .await;
+
+        assert_eq!(StatusCode::OK, response.status_code());
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn cannot_register_animals_past_limit() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        // Acme is on the basic plan and the fixtures give it 15 animals.
+        sqlx::query(
+            "
+            INSERT INTO animals (organisation_pid, tag_id, specie_id, breed_id)
+            SELECT organisation_pid, 'LIMIT-' || n, specie_id, breed_id
+            FROM (SELECT * FROM animals WHERE organisation_pid = $1 LIMIT 1) AS animal,
+                generate_series(1, 10) AS n
+            ",
+        )
+        .bind(user.user.organisation_pid())
+        .execute(&context.db)
+        .await
+        .unwrap();
+
+        let response = server
+            .post("/animals")
+            .add_header(auth_header, auth_value)
+            .json(&serde_json::json!({}))
+            .await;
+
+        assert_debug_snapshot!((response.status_code(), response.json::<Value>()));
+    })
+    .await;
+}
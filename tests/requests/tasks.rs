@@ -0,0 +1,762 @@
+use axum::http::{HeaderName, HeaderValue};
+use axum_test::TestServer;
+use chrono::{Datelike, NaiveDate, Weekday};
+use insta::{Settings, assert_debug_snapshot, with_settings};
+use polaris::models::{dto::NewTask, settings::OrganisationSettings, tasks::Task};
+use serde_json::{Value, json};
+use serial_test::serial;
+use uuid::Uuid;
+
+use crate::{JOHN_DOE, pid, request, requests::prepare_auth};
+
+macro_rules! configure_insta {
+    ($(expr:expr),*) => {
+        let mut settings = Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_path("snapshots/tasks");
+        settings.set_snapshot_suffix("tasks");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+const CONTINENTAL: &str = "4a0f3af9-e56e-4e21-8f3a-f9e56efe215b";
+const JAMES_MORIATY: &str = "3c008e68-88fa-4072-808e-6888fa60724c";
+
+/// Creates a task from `body`, returning its pid.
+async fn task(
+    server: &TestServer,
+    (auth_header, auth_value): &(HeaderName, HeaderValue),
+    body: Value,
+) -> Uuid {
+    let created = server
+        .post("/tasks")
+        .add_header(auth_header.clone(), auth_value.clone())
+        .json(&body)
+        .await;
+
+    pid(&created.json::<Value>())
+}
+
+/// Creates a static group of the calves, returning its pid.
+async fn calves(
+    server: &TestServer,
+    (auth_header, auth_value): &(HeaderName, HeaderValue),
+) -> Uuid {
+    let created = server
+        .post("/groups")
+        .add_header(auth_header.clone(), auth_value.clone())
+        .json(&json!({ "name": "Calves", "kind": "static", "tagIds": ["AC004", "AC007"] }))
+        .await;
+
+    pid(&created.json::<Value>())
+}
+
+fn titles_of(tasks: &Value) -> Vec<Value> {
+    tasks
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|task| task["title"].clone())
+        .collect()
+}
+
+fn titles(response: &axum_test::TestResponse) -> Vec<Value> {
+    titles_of(&response.json::<Value>())
+}
+
+#[tokio::test]
+#[serial]
+async fn can_add_tasks() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        let created = server
+            .post("/tasks")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({
+                "title": "Weigh the calves",
+                "dueOn": "2024-06-15",
+                "assignee": JOHN_DOE,
+                "priority": "high",
+                "tagIds": ["AC004", "AC007"],
+                "recordKind": "weight"
+            }))
+            .await;
+
+        let invalid = server
+            .post("/tasks")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({ "title": "", "dueOn": "2024-06-15", "priority": "urgent" }))
+            .await;
+
+        let outsider = server
+            .post("/tasks")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({
+                "title": "Fix the fence",
+                "dueOn": "2024-06-15",
+                "assignee": JAMES_MORIATY
+            }))
+            .await;
+
+        let unknown_group = server
+            .post("/tasks")
+            .add_header(auth_header, auth_value)
+            .json(&json!({ "title": "Drench", "dueOn": "2024-06-15", "group": Uuid::nil() }))
+            .await;
+
+        with_settings!({ filters => {
+            let mut filters = crate::cleanup_uuid().to_vec();
+            filters.extend(crate::cleanup_date().to_vec());
+            filters.push((r#""id": Number\(\d+\)"#, r#""id": ID"#));
+            filters
+        }}, {
+            assert_debug_snapshot!((
+                (created.status_code(), created.json::<Value>()),
+                (invalid.status_code(), invalid.text()),
+                (outsider.status_code(), outsider.text()),
+                (unknown_group.status_code(), unknown_group.text()),
+            ));
+        });
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_list_tasks() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let auth = prepare_auth::auth_header(user.access_token);
+        task(
+            &server,
+            &auth,
+            json!({ "title": "Weigh the calves", "dueOn": "2024-06-15", "assignee": JOHN_DOE }),
+        )
+        .await;
+        let fence = task(
+            &server,
+            &auth,
+            json!({ "title": "Fix the fence", "dueOn": "2024-06-14" }),
+        )
+        .await;
+
+        server
+            .patch(&format!("/tasks/{fence}"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&json!({ "status": "cancelled" }))
+            .await;
+
+        let all = server
+            .get("/tasks")
+            .add_header(auth.0.clone(), auth.1.clone())
+            .await;
+
+        let assigned = server
+            .get(&format!("/tasks?assignee={JOHN_DOE}"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .await;
+
+        let cancelled = server
+            .get("/tasks?status=cancelled")
+            .add_header(auth.0, auth.1)
+            .await;
+
+        assert_debug_snapshot!((
+            (all.status_code(), titles(&all)),
+            titles(&assigned),
+            titles(&cancelled),
+        ));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_list_todays_and_overdue_tasks() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let today = OrganisationSettings::find(&context.db, user.user.organisation_pid())
+            .await
+            .unwrap()
+            .today();
+        let auth = prepare_auth::auth_header(user.access_token);
+
+        task(
+            &server,
+            &auth,
+            json!({ "title": "Weigh the calves", "dueOn": today, "assignee": JOHN_DOE }),
+        )
+        .await;
+        task(
+            &server,
+            &auth,
+            json!({ "title": "Fix the fence", "dueOn": today.pred_opt(), "assignee": JOHN_DOE }),
+        )
+        .await;
+        task(
+            &server,
+            &auth,
+            json!({ "title": "Order feed", "dueOn": today.succ_opt(), "assignee": JOHN_DOE }),
+        )
+        .await;
+
+        let mine = server
+            .get("/tasks/today")
+            .add_header(auth.0.clone(), auth.1.clone())
+            .await;
+
+        let overdue = server
+            .get("/tasks/overdue")
+            .add_header(auth.0, auth.1)
+            .await;
+
+        assert_debug_snapshot!((
+            (mine.status_code(), titles(&mine)),
+            overdue.status_code(),
+            overdue
+                .json::<Vec<Value>>()
+                .iter()
+                .map(|assignee| {
+                    (
+                        assignee["assigneeName"].clone(),
+                        assignee["overdue"].clone(),
+                        titles_of(&assignee["tasks"]),
+                    )
+                })
+                .collect::<Vec<_>>(),
+        ));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_get_a_task() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let auth = prepare_auth::auth_header(user.access_token);
+        let weighing = task(
+            &server,
+            &auth,
+            json!({ "title": "Weigh the calves", "dueOn": "2024-06-15", "tagIds": ["AC004"] }),
+        )
+        .await;
+
+        let found = server
+            .get(&format!("/tasks/{weighing}"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .await;
+
+        let unknown = server
+            .get(&format!("/tasks/{}", Uuid::nil()))
+            .add_header(auth.0, auth.1)
+            .await;
+
+        assert_debug_snapshot!((
+            found.status_code(),
+            found.json::<Value>()["title"].clone(),
+            unknown.status_code(),
+        ));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_update_a_task() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let auth = prepare_auth::auth_header(user.access_token);
+        let weighing = task(
+            &server,
+            &auth,
+            json!({ "title": "Weigh the calves", "dueOn": "2024-06-15" }),
+        )
+        .await;
+
+        let updated = server
+            .patch(&format!("/tasks/{weighing}"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&json!({ "dueOn": "2024-06-20", "priority": "low", "status": "in_progress" }))
+            .await;
+
+        let done = server
+            .patch(&format!("/tasks/{weighing}"))
+            .add_header(auth.0, auth.1)
+            .json(&json!({ "status": "done" }))
+            .await;
+
+        assert_debug_snapshot!((
+            updated.status_code(),
+            (
+                updated.json::<Value>()["dueOn"].clone(),
+                updated.json::<Value>()["priority"].clone(),
+                updated.json::<Value>()["status"].clone(),
+            ),
+            (done.status_code(), done.text()),
+        ));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_delete_a_task() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let auth = prepare_auth::auth_header(user.access_token);
+        let weighing = task(
+            &server,
+            &auth,
+            json!({ "title": "Weigh the calves", "dueOn": "2024-06-15" }),
+        )
+        .await;
+
+        let deleted = server
+            .delete(&format!("/tasks/{weighing}"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .await;
+
+        let gone = server
+            .get(&format!("/tasks/{weighing}"))
+            .add_header(auth.0, auth.1)
+            .await;
+
+        assert_debug_snapshot!((deleted.status_code(), gone.status_code()));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_complete_a_task() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let auth = prepare_auth::auth_header(user.access_token);
+        let weighing = task(
+            &server,
+            &auth,
+            json!({
+                "title": "Weigh the calves",
+                "dueOn": "2024-06-15",
+                "tagIds": ["AC004", "AC007"],
+                "recordKind": "weight"
+            }),
+        )
+        .await;
+
+        let wrong_record = server
+            .post(&format!("/tasks/{weighing}/complete"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&json!({
+                "production": {
+                    "productionType": "milk",
+                    "unit": "litres",
+                    "readings": [{ "tagId": "AC004", "quantity": 1_000 }]
+                }
+            }))
+            .await;
+
+        let outsider = server
+            .post(&format!("/tasks/{weighing}/complete"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&json!({
+                "weighing": {
+                    "recordDate": "2024-06-15",
+                    "weights": [{ "tagId": "AC001", "mass": 45_000 }]
+                }
+            }))
+            .await;
+
+        let completed = server
+            .post(&format!("/tasks/{weighing}/complete"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&json!({
+                "notes": "Both gaining",
+                "weighing": {
+                    "recordDate": "2024-06-15",
+                    "weights": [
+                        { "tagId": "AC004", "mass": 25_000 },
+                        { "tagId": "AC007", "mass": 12_000 }
+                    ]
+                }
+            }))
+            .await;
+
+        let done = server
+            .get("/tasks?status=done")
+            .add_header(auth.0, auth.1)
+            .await;
+
+        assert_debug_snapshot!((
+            (wrong_record.status_code(), wrong_record.text()),
+            (outsider.status_code(), outsider.text()),
+            (
+                completed.status_code(),
+                completed.json::<Value>()["task"]["status"].clone(),
+                completed.json::<Value>()["task"]["notes"].clone(),
+                completed.json::<Value>()["weightRecords"]
+                    .as_array()
+                    .map(Vec::len),
+            ),
+            titles(&done),
+        ));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn cannot_complete_a_task_twice() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let auth = prepare_auth::auth_header(user.access_token);
+        let fence = task(
+            &server,
+            &auth,
+            json!({ "title": "Fix the fence", "dueOn": "2024-06-15" }),
+        )
+        .await;
+
+        let completed = server
+            .post(&format!("/tasks/{fence}/complete"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&json!({}))
+            .await;
+
+        let again = server
+            .post(&format!("/tasks/{fence}/complete"))
+            .add_header(auth.0, auth.1)
+            .json(&json!({ "notes": "Fixed it again" }))
+            .await;
+
+        assert_debug_snapshot!((completed.status_code(), (again.status_code(), again.text()),));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn cannot_reach_another_organisations_task() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let mut conn = context.db.acquire().await.unwrap();
+        let theirs = Task::create(
+            &mut conn,
+            Uuid::parse_str(CONTINENTAL).unwrap(),
+            Uuid::parse_str(JAMES_MORIATY).unwrap(),
+            &NewTask {
+                title: "Check the water troughs".into(),
+                description: None,
+                due_on: NaiveDate::from_ymd_opt(2024, 6, 15).unwrap(),
+                assignee: None,
+                priority: None,
+                tag_ids: None,
+                group: None,
+                record_kind: None,
+            },
+        )
+        .await
+        .unwrap()
+        .pid;
+        drop(conn);
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        let found = server
+            .get(&format!("/tasks/{theirs}"))
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+
+        let updated = server
+            .patch(&format!("/tasks/{theirs}"))
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({ "title": "Mine now" }))
+            .await;
+
+        let completed = server
+            .post(&format!("/tasks/{theirs}/complete"))
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({}))
+            .await;
+
+        let deleted = server
+            .delete(&format!("/tasks/{theirs}"))
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+
+        let listed = server
+            .get("/tasks")
+            .add_header(auth_header, auth_value)
+            .await;
+
+        assert_debug_snapshot!((
+            found.status_code(),
+            updated.status_code(),
+            completed.status_code(),
+            deleted.status_code(),
+            titles(&listed),
+        ));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_add_recurring_tasks() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        let recurring = server
+            .post("/tasks/recurring")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({
+                "title": "Milk recording",
+                "cron": "0 6 * * TUE",
+                "assignee": JOHN_DOE,
+                "recordKind": "production"
+            }))
+            .await;
+
+        let invalid_cron = server
+            .post("/tasks/recurring")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({ "title": "Milk recording", "cron": "every tuesday" }))
+            .await;
+
+        let listed = server
+            .get("/tasks/recurring")
+            .add_header(auth_header, auth_value)
+            .await;
+
+        let next_due_on = recurring.json::<Value>()["nextDueOn"]
+            .as_str()
+            .and_then(|date| date.parse::<NaiveDate>().ok());
+
+        assert_debug_snapshot!((
+            (
+                recurring.status_code(),
+                recurring.json::<Value>()["cron"].clone(),
+                next_due_on.map(|date| date.weekday() == Weekday::Tue),
+            ),
+            (invalid_cron.status_code(), invalid_cron.text()),
+            (listed.status_code(), titles(&listed)),
+        ));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_update_and_delete_recurring_tasks() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        let recurring = server
+            .post("/tasks/recurring")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({ "title": "Milk recording", "cron": "0 6 * * TUE" }))
+            .await;
+        let recurring = pid(&recurring.json::<Value>());
+
+        let updated = server
+            .patch(&format!("/tasks/recurring/{recurring}"))
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({ "cron": "0 6 1 * *", "isActive": false }))
+            .await;
+
+        let invalid_cron = server
+            .patch(&format!("/tasks/recurring/{recurring}"))
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({ "cron": "monthly" }))
+            .await;
+
+        let deleted = server
+            .delete(&format!("/tasks/recurring/{recurring}"))
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+
+        let again = server
+            .delete(&format!("/tasks/recurring/{recurring}"))
+            .add_header(auth_header, auth_value)
+            .await;
+
+        assert_debug_snapshot!((
+            (
+                updated.status_code(),
+                updated.json::<Value>()["cron"].clone(),
+                updated.json::<Value>()["isActive"].clone(),
+                updated.json::<Value>()["nextDueOn"]
+                    .as_str()
+                    .map(|date| date.ends_with("-01")),
+            ),
+            (invalid_cron.status_code(), invalid_cron.text()),
+            deleted.status_code(),
+            again.status_code(),
+        ));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_schedule_vaccinations() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let auth = prepare_auth::auth_header(user.access_token);
+        let calves = calves(&server, &auth).await;
+
+        let scheduled = server
+            .post("/tasks/vaccinations")
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&json!({ "vaccine": "Clostridial 7-in-1", "group": calves, "intervalDays": 365 }))
+            .await;
+
+        let invalid = server
+            .post("/tasks/vaccinations")
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&json!({ "vaccine": "Clostridial 7-in-1", "group": calves, "intervalDays": 0 }))
+            .await;
+
+        let unknown_group = server
+            .post("/tasks/vaccinations")
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&json!({
+                "vaccine": "Clostridial 7-in-1",
+                "group": Uuid::nil(),
+                "intervalDays": 365
+            }))
+            .await;
+
+        let listed = server
+            .get("/tasks/vaccinations")
+            .add_header(auth.0, auth.1)
+            .await;
+
+        with_settings!({ filters => {
+            let mut filters = crate::cleanup_uuid().to_vec();
+            filters.extend(crate::cleanup_date().to_vec());
+            filters.push((r#""id": Number\(\d+\)"#, r#""id": ID"#));
+            filters
+        }}, {
+            assert_debug_snapshot!((
+                (scheduled.status_code(), scheduled.json::<Value>()),
+                (invalid.status_code(), invalid.text()),
+                (unknown_group.status_code(), unknown_group.text()),
+                listed
+                    .json::<Vec<Value>>()
+                    .iter()
+                    .map(|schedule| (schedule["vaccine"].clone(), schedule["groupName"].clone()))
+                    .collect::<Vec<_>>(),
+            ));
+        });
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_update_and_delete_vaccination_schedules() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let auth = prepare_auth::auth_header(user.access_token);
+        let calves = calves(&server, &auth).await;
+
+        let scheduled = server
+            .post("/tasks/vaccinations")
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&json!({ "vaccine": "Clostridial 7-in-1", "group": calves, "intervalDays": 365 }))
+            .await;
+        let schedule = pid(&scheduled.json::<Value>());
+
+        let updated = server
+            .patch(&format!("/tasks/vaccinations/{schedule}"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&json!({ "intervalDays": 180, "leadDays": 14 }))
+            .await;
+
+        let invalid = server
+            .patch(&format!("/tasks/vaccinations/{schedule}"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&json!({ "leadDays": -1 }))
+            .await;
+
+        let deleted = server
+            .delete(&format!("/tasks/vaccinations/{schedule}"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .await;
+
+        let listed = server
+            .get("/tasks/vaccinations")
+            .add_header(auth.0, auth.1)
+            .await;
+
+        assert_debug_snapshot!((
+            (
+                updated.status_code(),
+                updated.json::<Value>()["intervalDays"].clone(),
+                updated.json::<Value>()["leadDays"].clone(),
+            ),
+            (invalid.status_code(), invalid.text()),
+            deleted.status_code(),
+            listed.json::<Vec<Value>>().len(),
+        ));
+    })
+    .await;
+}
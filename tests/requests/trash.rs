@@ -0,0 +1,107 @@
+use crate::{request, requests::prepare_auth};
+
+use insta::{Settings, assert_debug_snapshot};
+use serde_json::Value;
+use serial_test::serial;
+
+macro_rules! configure_insta {
+    ($(expr:expr),*) => {
+        let mut settings = Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_path("snapshots/trash");
+        settings.set_snapshot_suffix("trash");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+const ROSE: &str = "f6417c11-d817-4626-9e8d-c68a44002d4b";
+
+#[tokio::test]
+#[serial]
+async fn can_trash_and_restore_animal() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        let deleted = server
+            .delete(&format!("/animals/{ROSE}"))
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+
+        let hidden = server
+            .get(&format!("/animals/{ROSE}"))
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+
+        let trash = server
+            .get("/trash")
+            .add_query_param("resource", "animals")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+        let trashed = trash
+            .json::<Vec<Value>>()
+            .iter()
+            .map(|item| {
+                (
+                    item["resource"].clone(),
+                    item["animalTagId"].clone(),
+                    item["deletedByName"].clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let restored = server
+            .patch(&format!("/animals/{ROSE}/restore"))
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+
+        let found = server
+            .get(&format!("/animals/{ROSE}"))
+            .add_header(auth_header, auth_value)
+            .await;
+
+        assert_debug_snapshot!((
+            deleted.status_code(),
+            hidden.status_code(),
+            trash.status_code(),
+            trashed,
+            restored.status_code(),
+            found.status_code(),
+        ));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn cannot_restore_record_of_trashed_animal() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        server
+            .delete("/weight-records/115")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+        server
+            .delete(&format!("/animals/{ROSE}"))
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+
+        let response = server
+            .patch("/weight-records/115/restore")
+            .add_header(auth_header, auth_value)
+            .await;
+
+        assert_debug_snapshot!((response.status_code(), response.text()));
+    })
+    .await;
+}
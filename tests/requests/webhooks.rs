@@ -0,0 +1,249 @@
+use std::sync::{
+    Arc, Mutex,
+    atomic::{AtomicBool, Ordering},
+};
+
+use axum::{
+    Router,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    routing::post,
+};
+use insta::{Settings, assert_debug_snapshot, with_settings};
+use polaris::webhooks::{Dispatcher, sign};
+use serde_json::{Value, json};
+use serial_test::serial;
+use tokio::net::TcpListener;
+
+use crate::{request, requests::prepare_auth};
+
+macro_rules! configure_insta {
+    ($(expr:expr),*) => {
+        let mut settings = Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_path("snapshots/webhooks");
+        settings.set_snapshot_suffix("webhooks");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+/// A partner endpoint that records what it is sent, and fails while told to.
+#[derive(Clone, Default)]
+struct Receiver {
+    failing: Arc<AtomicBool>,
+    received: Arc<Mutex<Vec<(HeaderMap, String)>>>,
+}
+
+impl Receiver {
+    async fn start(&self) -> String {
+        async fn receive(
+            State(receiver): State<Receiver>,
+            headers: HeaderMap,
+            body: String,
+        ) -> StatusCode {
+            receiver.received.lock().unwrap().push((headers, body));
+
+            if receiver.failing.load(Ordering::SeqCst) {
+                StatusCode::INTERNAL_SERVER_ERROR
+            } else {
+                StatusCode::NO_CONTENT
+            }
+        }
+
+        let app = Router::new()
+            .route("/hooks", post(receive))
+            .with_state(self.clone());
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        format!("http://{address}/hooks")
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn can_manage_webhooks() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        let event_types = server
+            .get("/webhooks/event-types")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+
+        let invalid = server
+            .post("/webhooks")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({ "url": "not a url", "eventTypes": ["animal.hatched"] }))
+            .await;
+
+        let created = server
+            .post("/webhooks")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({
+                "url": "https://partner.example.com/hooks",
+                "eventTypes": ["animal.sold", "animal.registered"]
+            }))
+            .await;
+        let created_json = created.json::<Value>();
+        let pid = created_json["pid"].as_str().unwrap().to_string();
+        let secret = created_json["secret"].as_str().unwrap().to_string();
+
+        let paused = server
+            .patch(&format!("/webhooks/{pid}"))
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({ "isActive": false, "eventTypes": ["animal.deceased"] }))
+            .await;
+
+        let listed = server
+            .get("/webhooks")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+
+        let removed = server
+            .delete(&format!("/webhooks/{pid}"))
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+
+        let missing = server
+            .get(&format!("/webhooks/{pid}"))
+            .add_header(auth_header, auth_value)
+            .await;
+
+        crate::invite_and_accept(
+            &context.db,
+            user.user.organisation_pid(),
+            "staff@acme.com",
+            "staff",
+        )
+        .await;
+        let staff_login = server
+            .post("/auth/login")
+            .json(&json!({ "email": "staff@acme.com", "password": "Password" }))
+            .await;
+        let forbidden = server
+            .get("/webhooks")
+            .add_header("authorization", staff_login.header("authorization"))
+            .await;
+
+        with_settings!({
+            filters => {
+                let mut filters = crate::cleanup_date().to_vec();
+                filters.extend(crate::cleanup_uuid().to_vec());
+                filters
+            }
+        }, {
+            assert_debug_snapshot!((
+                event_types.json::<Value>(),
+                (invalid.status_code(), invalid.json::<Value>()),
+                (created.status_code(), created_json["eventTypes"].clone()),
+                secret.starts_with("whsec_"),
+                (paused.status_code(), paused.json::<Value>()),
+                listed.json::<Value>(),
+                removed.status_code(),
+                missing.status_code(),
+                forbidden.status_code(),
+            ));
+        })
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn events_are_delivered_signed_and_retried() {
+    request(|server, context| async move {
+        configure_insta!();
+
+        crate::seed_data(&context.db).await.unwrap();
+
+        let user = prepare_auth::login_user(&server, &context).await;
+        let (auth_header, auth_value) = prepare_auth::auth_header(user.access_token);
+
+        let receiver = Receiver::default();
+        let url = receiver.start().await;
+
+        let created = server
+            .post("/webhooks")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({ "url": url, "eventTypes": ["animal.registered"] }))
+            .await
+            .json::<Value>();
+        let pid = created["pid"].as_str().unwrap().to_string();
+        let secret = created["secret"].as_str().unwrap().to_string();
+
+        server
+            .post("/animals")
+            .add_header(auth_header.clone(), auth_value.clone())
+            .json(&json!({
+               "tagId": "AC025",
+               "name": "Geoffrey",
+               "specie": "cattle",
+               "breed": "Friesian",
+               "gender": "male",
+               "status": "active",
+               "dateOfBirth": "2023-10-16"
+            }))
+            .await;
+
+        let dispatcher = Dispatcher::new(&context).unwrap();
+
+        receiver.failing.store(true, Ordering::SeqCst);
+        let first = dispatcher.dispatch_due().await.unwrap();
+
+        let retrying = server
+            .get(&format!("/webhooks/{pid}/deliveries"))
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await
+            .json::<Value>();
+        let delivery = retrying[0]["pid"].as_str().unwrap().to_string();
+
+        // Sent again straight away rather than waiting out the backoff.
+        let redelivered = server
+            .post(&format!("/webhooks/{pid}/deliveries/{delivery}/redeliver"))
+            .add_header(auth_header.clone(), auth_value.clone())
+            .await;
+
+        receiver.failing.store(false, Ordering::SeqCst);
+        let second = dispatcher.dispatch_due().await.unwrap();
+        let nothing_due = dispatcher.dispatch_due().await.unwrap();
+
+        let delivered = server
+            .get(&format!("/webhooks/{pid}/deliveries"))
+            .add_header(auth_header, auth_value)
+            .await
+            .json::<Value>();
+
+        let received = receiver.received.lock().unwrap().clone();
+        let (headers, body) = received.last().unwrap();
+        let header = |name: &str| headers[name].to_str().unwrap().to_string();
+        let timestamp = header("x-polaris-timestamp").parse::<i64>().unwrap();
+        let payload = serde_json::from_str::<Value>(body).unwrap();
+
+        with_settings!({
+            filters => {
+                let mut filters = crate::cleanup_date().to_vec();
+                filters.extend(crate::cleanup_uuid().to_vec());
+                filters
+            }
+        }, {
+            assert_debug_snapshot!((
+                (first, second, nothing_due, received.len()),
+                retrying,
+                (redelivered.status_code(), redelivered.json::<Value>()["status"].clone()),
+                delivered,
+                (header("x-polaris-event"), header("x-polaris-delivery") == delivery),
+                header("x-polaris-signature") == sign(&secret, timestamp, body),
+                (payload["type"].clone(), payload["data"]["tag_id"].clone()),
+            ));
+        })
+    })
+    .await;
+}